@@ -6,6 +6,39 @@ pub enum TreeKind {
     Pragma,
     PragmaArgumentList,
     PragmaArgument,
+    WithClause,
+    UseClause,
+    PackageDeclaration,
+    TypeDeclaration,
+    SubtypeDeclaration,
+    ObjectDeclaration,
+    SubprogramDeclaration,
+    ParameterList,
+    Parameter,
+    DiscriminantPart,
+    Discriminant,
+    EnumerationType,
+    ModularType,
+    DerivedType,
+    ArrayType,
+    AccessType,
+    RecordType,
+    ComponentList,
+    ComponentDeclaration,
+    VariantPart,
+    Variant,
+    SubtypeIndication,
+    RangeConstraint,
+    IndexConstraint,
+    Choice,
+    Name,
+    ArgumentList,
+    Literal,
+    Aggregate,
+    Association,
+    UnaryExpression,
+    BinaryExpression,
+    Membership,
 }
 
 /// The output from the parser that specifies how a CST should be constructed
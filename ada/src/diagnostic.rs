@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// Error type that can be shown to a user of this compiler
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Diagnostic {
@@ -12,3 +14,9 @@ impl Diagnostic {
         Self { name: name.into() }
     }
 }
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
@@ -415,10 +415,11 @@ fn join_tokens(last: &mut Token, new: Token) -> bool {
     use TokenKind::*;
     let to_join = NfcError | UnicodeError | UnicodeNotCommentError | Error | Eof | Whitespace;
 
-    if to_join.contains(last.kind) {
-        last.end = new.end
+    if !to_join.contains(last.kind) {
+        return false;
     }
 
+    last.end = new.end;
     true
 }
 
@@ -2,6 +2,8 @@ use lexer::Lexer;
 
 use crate::parser::Parser;
 
+pub use diagnostic::Diagnostic;
+
 mod cst;
 mod diagnostic;
 mod lexer;
@@ -23,9 +25,10 @@ impl Compiler {
         Compiler
     }
 
-    pub fn add_file(&mut self, source: String) {
-        let parse = Parser::run(Lexer::run(&source));
+    /// Parse a source file, giving the errors found in it
+    pub fn add_file(&mut self, source: String) -> Result<(), Vec<Diagnostic>> {
+        Parser::run(Lexer::run(&source))?;
 
-        println!("{parse:?}");
+        Ok(())
     }
 }
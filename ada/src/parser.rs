@@ -1,3 +1,5 @@
+mod declaration;
+mod expression;
 mod file;
 
 use std::cell::Cell;
@@ -46,6 +48,13 @@ struct MarkOpened {
     index: usize,
 }
 
+/// Marker for a completed tree, which can be wrapped in a new tree, such as
+/// the left operand of a binary operator
+#[derive(Debug, Clone, Copy)]
+struct MarkClosed {
+    index: usize,
+}
+
 impl Parser {
     /// Construct a parser from a list of tokens.
     ///
@@ -95,12 +104,27 @@ impl Parser {
         mark
     }
 
+    /// start a new nested tree node containing an already completed node
+    fn open_before(&mut self, m: MarkClosed) -> MarkOpened {
+        assert_ne!(self.recursion_fuel.get(), 0);
+        self.recursion_fuel.set(self.recursion_fuel.get() - 1);
+
+        self.events.insert(
+            m.index,
+            Event::Open {
+                kind: TreeKind::ErrorTree,
+            },
+        );
+        MarkOpened { index: m.index }
+    }
+
     /// end a nested tree node
-    fn close(&mut self, m: MarkOpened, kind: TreeKind) {
+    fn close(&mut self, m: MarkOpened, kind: TreeKind) -> MarkClosed {
         self.recursion_fuel.set(self.recursion_fuel.get() + 1);
 
         self.events[m.index] = Event::Open { kind };
         self.events.push(Event::Close);
+        MarkClosed { index: m.index }
     }
 
     /// Consume the next token (non-whitespace)
@@ -122,6 +146,19 @@ impl Parser {
         }
     }
 
+    /// Consume the trivia before the first token of the file, as advancing
+    /// only consumes the trivia after each token
+    fn trivia(&mut self) {
+        while self
+            .tokens
+            .get(self.position)
+            .is_some_and(|t| !is_not_trivia(&t))
+        {
+            self.events.push(Event::Advance);
+            self.position += 1;
+        }
+    }
+
     /// is the parser at the end of the file
     fn eof(&self) -> bool {
         self.position >= self.tokens.len()
@@ -165,14 +202,17 @@ impl Parser {
             .push(Diagnostic::new(format!("Expected {kind:?}")));
     }
 
-    /// Add the next (non-whitespace) token to an error tree and consume it
-    fn advance_with_error(&mut self, message: impl Into<String>) {
+    /// Add the next (non-whitespace) token to an error tree and consume it,
+    /// unless the end of the file has been reached
+    fn advance_with_error(&mut self, message: impl Into<String>) -> MarkClosed {
         let m = self.open();
 
         self.errors.push(Diagnostic::new(message));
-        self.advance();
+        if !self.eof() {
+            self.advance();
+        }
 
-        self.close(m, TreeKind::ErrorTree);
+        self.close(m, TreeKind::ErrorTree)
     }
 }
 
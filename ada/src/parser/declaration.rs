@@ -0,0 +1,287 @@
+use crate::{cst::TreeKind, token::TokenKind};
+
+use super::{
+    expression::{choices, expression, name, range_constraint, subtype_indication},
+    file::pragma,
+    Parser,
+};
+
+/// Parse the basic declarations of a package, up to the end of the package
+/// or the start of its private part
+pub fn declarations(p: &mut Parser) {
+    while !p.eof() && !p.at(TokenKind::KwEnd) && !p.at(TokenKind::KwPrivate) {
+        match p.nth(0) {
+            TokenKind::KwPragma => pragma(p),
+            TokenKind::KwType => type_declaration(p),
+            TokenKind::KwSubtype => subtype_declaration(p),
+            TokenKind::KwProcedure | TokenKind::KwFunction => subprogram_declaration(p),
+            TokenKind::Identifier => object_declaration(p),
+            _ => {
+                p.advance_with_error("Expected a declaration");
+            }
+        }
+    }
+}
+
+/// Parse a type declaration, which is incomplete if it has no definition
+fn type_declaration(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwType));
+    let m = p.open();
+
+    p.expect(TokenKind::KwType);
+    p.expect(TokenKind::Identifier);
+    if p.at(TokenKind::LParen) {
+        parameters(p, TreeKind::DiscriminantPart, TreeKind::Discriminant);
+    }
+    if p.eat(TokenKind::KwIs) {
+        type_definition(p);
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::TypeDeclaration);
+}
+
+/// Parse the definition of a type after `is`
+fn type_definition(p: &mut Parser) {
+    match p.nth(0) {
+        TokenKind::LParen => enumeration_type(p),
+        TokenKind::KwRange => range_constraint(p),
+        TokenKind::KwMod => {
+            let m = p.open();
+            p.expect(TokenKind::KwMod);
+            expression(p);
+            p.close(m, TreeKind::ModularType);
+        }
+        TokenKind::KwNew => {
+            let m = p.open();
+            p.expect(TokenKind::KwNew);
+            subtype_indication(p);
+            p.close(m, TreeKind::DerivedType);
+        }
+        TokenKind::KwArray => array_type(p),
+        TokenKind::KwAccess => {
+            let m = p.open();
+            p.expect(TokenKind::KwAccess);
+            if !p.eat(TokenKind::KwAll) {
+                p.eat(TokenKind::KwConstant);
+            }
+            subtype_indication(p);
+            p.close(m, TreeKind::AccessType);
+        }
+        TokenKind::KwNull | TokenKind::KwRecord => record_type(p),
+        _ => {
+            p.advance_with_error("Expected a type definition");
+        }
+    }
+}
+
+/// Parse the literals of an enumeration type
+fn enumeration_type(p: &mut Parser) {
+    assert!(p.at(TokenKind::LParen));
+    let m = p.open();
+
+    p.expect(TokenKind::LParen);
+    identifiers(p);
+    p.expect(TokenKind::RParen);
+
+    p.close(m, TreeKind::EnumerationType);
+}
+
+/// Parse an array type, with the ranges or subtypes of its indices
+fn array_type(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwArray));
+    let m = p.open();
+
+    p.expect(TokenKind::KwArray);
+    p.expect(TokenKind::LParen);
+    choices(p);
+    while p.eat(TokenKind::Comma) {
+        choices(p);
+    }
+    p.expect(TokenKind::RParen);
+    p.expect(TokenKind::KwOf);
+    p.eat(TokenKind::KwAliased);
+    subtype_indication(p);
+
+    p.close(m, TreeKind::ArrayType);
+}
+
+/// Parse a record type, or a null record
+fn record_type(p: &mut Parser) {
+    let m = p.open();
+
+    if p.eat(TokenKind::KwNull) {
+        p.expect(TokenKind::KwRecord);
+    } else {
+        p.expect(TokenKind::KwRecord);
+        component_list(p);
+        p.expect(TokenKind::KwEnd);
+        p.expect(TokenKind::KwRecord);
+    }
+
+    p.close(m, TreeKind::RecordType);
+}
+
+/// Parse the components of a record or a variant, followed by an optional
+/// variant part
+fn component_list(p: &mut Parser) {
+    let m = p.open();
+
+    if p.eat(TokenKind::KwNull) {
+        p.expect(TokenKind::SemiColon);
+    } else {
+        loop {
+            match p.nth(0) {
+                TokenKind::KwPragma => pragma(p),
+                TokenKind::Identifier => component_declaration(p),
+                _ => break,
+            }
+        }
+        if p.at(TokenKind::KwCase) {
+            variant_part(p);
+        }
+    }
+
+    p.close(m, TreeKind::ComponentList);
+}
+
+/// Parse the declaration of components of a record
+fn component_declaration(p: &mut Parser) {
+    assert!(p.at(TokenKind::Identifier));
+    let m = p.open();
+
+    identifiers(p);
+    p.expect(TokenKind::Colon);
+    p.eat(TokenKind::KwAliased);
+    subtype_indication(p);
+    if p.eat(TokenKind::ColonEquals) {
+        expression(p);
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::ComponentDeclaration);
+}
+
+/// Parse the variants of a discriminated record
+fn variant_part(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwCase));
+    let m = p.open();
+
+    p.expect(TokenKind::KwCase);
+    p.expect(TokenKind::Identifier);
+    p.expect(TokenKind::KwIs);
+    while p.at(TokenKind::KwWhen) {
+        let v = p.open();
+        p.expect(TokenKind::KwWhen);
+        choices(p);
+        p.expect(TokenKind::Arrow);
+        component_list(p);
+        p.close(v, TreeKind::Variant);
+    }
+    p.expect(TokenKind::KwEnd);
+    p.expect(TokenKind::KwCase);
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::VariantPart);
+}
+
+/// Parse a subtype declaration
+fn subtype_declaration(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwSubtype));
+    let m = p.open();
+
+    p.expect(TokenKind::KwSubtype);
+    p.expect(TokenKind::Identifier);
+    p.expect(TokenKind::KwIs);
+    subtype_indication(p);
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::SubtypeDeclaration);
+}
+
+/// Parse the declaration of objects, named numbers or exceptions
+fn object_declaration(p: &mut Parser) {
+    assert!(p.at(TokenKind::Identifier));
+    let m = p.open();
+
+    identifiers(p);
+    p.expect(TokenKind::Colon);
+    if !p.eat(TokenKind::KwException) {
+        p.eat(TokenKind::KwAliased);
+        p.eat(TokenKind::KwConstant);
+
+        // named numbers have no subtype
+        if !p.at(TokenKind::ColonEquals) {
+            subtype_indication(p);
+        }
+        if p.eat(TokenKind::ColonEquals) {
+            expression(p);
+        }
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::ObjectDeclaration);
+}
+
+/// Parse the declaration of a procedure or function, which might be an
+/// expression function
+fn subprogram_declaration(p: &mut Parser) {
+    let m = p.open();
+
+    let function = p.eat(TokenKind::KwFunction);
+    if !function {
+        p.expect(TokenKind::KwProcedure);
+    }
+    p.expect(TokenKind::Identifier);
+    if p.at(TokenKind::LParen) {
+        parameters(p, TreeKind::ParameterList, TreeKind::Parameter);
+    }
+    if function {
+        p.expect(TokenKind::KwReturn);
+        subtype_indication(p);
+    }
+    if p.eat(TokenKind::KwIs) {
+        expression(p);
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::SubprogramDeclaration);
+}
+
+/// Parse the parameters of a subprogram or the discriminants of a type,
+/// separated by semicolons
+fn parameters(p: &mut Parser, list: TreeKind, item: TreeKind) {
+    assert!(p.at(TokenKind::LParen));
+    let m = p.open();
+
+    p.expect(TokenKind::LParen);
+    loop {
+        let i = p.open();
+        identifiers(p);
+        p.expect(TokenKind::Colon);
+        p.eat(TokenKind::KwAliased);
+        p.eat(TokenKind::KwIn);
+        p.eat(TokenKind::KwOut);
+        p.eat(TokenKind::KwAccess);
+        name(p, false);
+        if p.eat(TokenKind::ColonEquals) {
+            expression(p);
+        }
+        p.close(i, item);
+
+        if !p.eat(TokenKind::SemiColon) {
+            break;
+        }
+    }
+    p.expect(TokenKind::RParen);
+
+    p.close(m, list);
+}
+
+/// Parse a list of identifiers separated by commas
+fn identifiers(p: &mut Parser) {
+    p.expect(TokenKind::Identifier);
+    while p.eat(TokenKind::Comma) {
+        p.expect(TokenKind::Identifier);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{cst::TreeKind, token::TokenKind};
+
+use super::{MarkClosed, Parser};
+
+/// Parse a name, made of an identifier followed by selected components and
+/// attributes.  Calls, indexing and slices are only part of the name if
+/// `calls` is set, otherwise a parenthesised list after a name is left for
+/// the constraint of a subtype indication.
+pub fn name(p: &mut Parser, calls: bool) -> MarkClosed {
+    let m = p.open();
+
+    p.expect(TokenKind::Identifier);
+    loop {
+        if p.eat(TokenKind::Dot) {
+            if !p.eat(TokenKind::KwAll) {
+                p.expect(TokenKind::Identifier);
+            }
+        } else if p.eat(TokenKind::Apostrophe) {
+            // reserved words that are also the names of attributes
+            match p.nth(0) {
+                TokenKind::LParen => {
+                    aggregate(p);
+                }
+                TokenKind::KwAccess
+                | TokenKind::KwDelta
+                | TokenKind::KwDigits
+                | TokenKind::KwMod
+                | TokenKind::KwRange => p.advance(),
+                _ => p.expect(TokenKind::Identifier),
+            }
+        } else if calls && p.at(TokenKind::LParen) {
+            let a = p.open();
+            associations(p, TokenKind::LParen, TokenKind::RParen);
+            p.close(a, TreeKind::ArgumentList);
+        } else {
+            break;
+        }
+    }
+
+    p.close(m, TreeKind::Name)
+}
+
+/// Parse a subtype mark, optionally followed by a range or index
+/// constraint
+pub fn subtype_indication(p: &mut Parser) {
+    let m = p.open();
+
+    if p.eat(TokenKind::KwNot) {
+        p.expect(TokenKind::KwNull);
+    }
+    name(p, false);
+    if p.at(TokenKind::KwRange) {
+        range_constraint(p);
+    } else if p.at(TokenKind::LParen) {
+        let c = p.open();
+        p.expect(TokenKind::LParen);
+        choice(p);
+        while p.eat(TokenKind::Comma) {
+            choice(p);
+        }
+        p.expect(TokenKind::RParen);
+        p.close(c, TreeKind::IndexConstraint);
+    }
+
+    p.close(m, TreeKind::SubtypeIndication);
+}
+
+/// Parse a range constraint, or the box of an unconstrained index
+pub fn range_constraint(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwRange));
+    let m = p.open();
+
+    p.expect(TokenKind::KwRange);
+    if !p.eat(TokenKind::Box) {
+        simple_expression(p);
+        p.expect(TokenKind::DoubleDot);
+        simple_expression(p);
+    }
+
+    p.close(m, TreeKind::RangeConstraint);
+}
+
+/// Parse the choices of a variant or an array aggregate, separated by
+/// vertical bars
+pub fn choices(p: &mut Parser) {
+    choice(p);
+    while p.eat(TokenKind::VerticalBar) {
+        choice(p);
+    }
+}
+
+/// Parse a single choice, which is `others`, a value, a range of values or
+/// a constrained subtype
+fn choice(p: &mut Parser) {
+    let m = p.open();
+
+    if !p.eat(TokenKind::KwOthers) {
+        expression(p);
+        range_rest(p);
+    }
+
+    p.close(m, TreeKind::Choice);
+}
+
+/// Parse the upper bound of a range, or the range constraint of a subtype,
+/// after the first part of a choice
+fn range_rest(p: &mut Parser) {
+    if p.eat(TokenKind::DoubleDot) {
+        simple_expression(p);
+    } else if p.at(TokenKind::KwRange) {
+        range_constraint(p);
+    }
+}
+
+/// Parse an expression, made of relations joined by logical operators
+pub fn expression(p: &mut Parser) -> MarkClosed {
+    let mut left = relation(p);
+    while matches!(
+        p.nth(0),
+        TokenKind::KwAnd | TokenKind::KwOr | TokenKind::KwXor
+    ) {
+        let m = p.open_before(left);
+        let operator = p.nth(0);
+        p.advance();
+
+        // short circuit forms
+        match operator {
+            TokenKind::KwAnd => p.eat(TokenKind::KwThen),
+            TokenKind::KwOr => p.eat(TokenKind::KwElse),
+            _ => false,
+        };
+        relation(p);
+        left = p.close(m, TreeKind::BinaryExpression);
+    }
+    left
+}
+
+/// Parse a comparison or membership test, or a simple expression
+fn relation(p: &mut Parser) -> MarkClosed {
+    let left = simple_expression(p);
+    match p.nth(0) {
+        TokenKind::Equals
+        | TokenKind::SlashEquals
+        | TokenKind::LessThan
+        | TokenKind::LessEquals
+        | TokenKind::GreaterThan
+        | TokenKind::GreaterEquals => {
+            let m = p.open_before(left);
+            p.advance();
+            simple_expression(p);
+            p.close(m, TreeKind::BinaryExpression)
+        }
+        TokenKind::KwNot | TokenKind::KwIn => {
+            let m = p.open_before(left);
+            p.eat(TokenKind::KwNot);
+            p.expect(TokenKind::KwIn);
+            membership_choice(p);
+            while p.eat(TokenKind::VerticalBar) {
+                membership_choice(p);
+            }
+            p.close(m, TreeKind::Membership)
+        }
+        _ => left,
+    }
+}
+
+/// Parse a choice of a membership test, which does not contain logical
+/// operators, so that they apply to the whole test
+fn membership_choice(p: &mut Parser) {
+    let m = p.open();
+
+    simple_expression(p);
+    range_rest(p);
+
+    p.close(m, TreeKind::Choice);
+}
+
+/// Parse terms joined by adding operators, with an optional sign
+fn simple_expression(p: &mut Parser) -> MarkClosed {
+    let mut left = if matches!(p.nth(0), TokenKind::Plus | TokenKind::Hyphen) {
+        let m = p.open();
+        p.advance();
+        term(p);
+        p.close(m, TreeKind::UnaryExpression)
+    } else {
+        term(p)
+    };
+
+    while matches!(
+        p.nth(0),
+        TokenKind::Plus | TokenKind::Hyphen | TokenKind::Ampersand
+    ) {
+        let m = p.open_before(left);
+        p.advance();
+        term(p);
+        left = p.close(m, TreeKind::BinaryExpression);
+    }
+    left
+}
+
+/// Parse factors joined by multiplying operators
+fn term(p: &mut Parser) -> MarkClosed {
+    let mut left = factor(p);
+    while matches!(
+        p.nth(0),
+        TokenKind::Star | TokenKind::Slash | TokenKind::KwMod | TokenKind::KwRem
+    ) {
+        let m = p.open_before(left);
+        p.advance();
+        factor(p);
+        left = p.close(m, TreeKind::BinaryExpression);
+    }
+    left
+}
+
+/// Parse an exponentiation, or a primary with a highest precedence
+/// operator
+fn factor(p: &mut Parser) -> MarkClosed {
+    if matches!(p.nth(0), TokenKind::KwAbs | TokenKind::KwNot) {
+        let m = p.open();
+        p.advance();
+        primary(p);
+        return p.close(m, TreeKind::UnaryExpression);
+    }
+
+    let left = primary(p);
+    if p.at(TokenKind::DoubleStar) {
+        let m = p.open_before(left);
+        p.advance();
+        primary(p);
+        return p.close(m, TreeKind::BinaryExpression);
+    }
+    left
+}
+
+/// Parse a literal, name or aggregate
+fn primary(p: &mut Parser) -> MarkClosed {
+    match p.nth(0) {
+        TokenKind::Number | TokenKind::String | TokenKind::KwNull => {
+            let m = p.open();
+            p.advance();
+            p.close(m, TreeKind::Literal)
+        }
+        TokenKind::Identifier => name(p, true),
+        TokenKind::LParen | TokenKind::LSquare => aggregate(p),
+        _ => p.advance_with_error("Expected an expression"),
+    }
+}
+
+/// Parse an aggregate, which cannot be told apart from a parenthesised
+/// expression without knowing the types of the expressions
+fn aggregate(p: &mut Parser) -> MarkClosed {
+    let m = p.open();
+
+    if p.at(TokenKind::LSquare) {
+        associations(p, TokenKind::LSquare, TokenKind::RSquare);
+    } else {
+        associations(p, TokenKind::LParen, TokenKind::RParen);
+    }
+
+    p.close(m, TreeKind::Aggregate)
+}
+
+/// Parse a bracketed list of the associations of an aggregate or call,
+/// which are values optionally preceded by the choices they are for
+fn associations(p: &mut Parser, open: TokenKind, close: TokenKind) {
+    p.expect(open);
+    // empty container aggregates
+    if !p.at(close) {
+        loop {
+            let a = p.open();
+            choices(p);
+            if p.eat(TokenKind::Arrow) && !p.eat(TokenKind::Box) {
+                expression(p);
+            }
+            p.close(a, TreeKind::Association);
+
+            if !p.eat(TokenKind::Comma) {
+                break;
+            }
+        }
+    }
+    p.expect(close);
+}
@@ -1,24 +1,82 @@
 use crate::{cst::TreeKind, token::TokenKind};
 
-use super::Parser;
+use super::{declaration::declarations, expression::name, Parser};
 
 /// Parse a whole source file
 pub fn file(p: &mut Parser) {
     let m = p.open();
+    p.trivia();
 
     while !p.eof() {
-        if p.at(TokenKind::KwPragma) {
-            pragma(p);
-        } else {
-            p.advance_with_error("Expected a pragma :3");
+        match p.nth(0) {
+            TokenKind::KwPragma => pragma(p),
+            TokenKind::KwWith => with_clause(p),
+            TokenKind::KwUse => use_clause(p),
+            TokenKind::KwPackage => package(p),
+            _ => {
+                p.advance_with_error("Expected a pragma, context clause or package");
+            }
         }
     }
 
     p.close(m, TreeKind::File);
 }
 
+/// Parse a with clause, naming the library units used by a file
+fn with_clause(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwWith));
+    let m = p.open();
+
+    p.expect(TokenKind::KwWith);
+    name(p, false);
+    while p.eat(TokenKind::Comma) {
+        name(p, false);
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::WithClause);
+}
+
+/// Parse a use clause, making the declarations of packages or the operators
+/// of types visible
+fn use_clause(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwUse));
+    let m = p.open();
+
+    p.expect(TokenKind::KwUse);
+    p.eat(TokenKind::KwType);
+    name(p, false);
+    while p.eat(TokenKind::Comma) {
+        name(p, false);
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::UseClause);
+}
+
+/// Parse the declaration of a package, with its visible and private parts
+fn package(p: &mut Parser) {
+    assert!(p.at(TokenKind::KwPackage));
+    let m = p.open();
+
+    p.expect(TokenKind::KwPackage);
+    name(p, false);
+    p.expect(TokenKind::KwIs);
+    declarations(p);
+    if p.eat(TokenKind::KwPrivate) {
+        declarations(p);
+    }
+    p.expect(TokenKind::KwEnd);
+    if p.at(TokenKind::Identifier) {
+        name(p, false);
+    }
+    p.expect(TokenKind::SemiColon);
+
+    p.close(m, TreeKind::PackageDeclaration);
+}
+
 /// Parse a pragma
-fn pragma(p: &mut Parser) {
+pub fn pragma(p: &mut Parser) {
     assert!(p.at(TokenKind::KwPragma));
     let m = p.open();
 
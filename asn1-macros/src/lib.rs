@@ -0,0 +1,286 @@
+//! Macros compiling ASN.1 modules into rust source where they are used.
+//! Errors found in the modules are reported as compile errors, located at
+//! the ASN.1 source written within [`asn1!`], or at the file name given to
+//! [`include_asn1!`].
+
+use std::{ops::Range, path::PathBuf};
+
+use asn1::{AsnCompiler, CodegenOptions, CodegenTarget, Diagnostic};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Compile ASN.1 modules written within the macro.
+///
+/// ```ignore
+/// asn1_macros::asn1! {
+///     Messages DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+///         Message ::= SEQUENCE { id INTEGER, name UTF8String }
+///     END
+/// }
+/// ```
+///
+/// The ASN.1 source must be made of valid rust tokens, so comments are
+/// written as rust comments, and bit and hexadecimal strings are not
+/// supported.  Modules using them can be compiled with [`include_asn1!`].
+#[proc_macro]
+pub fn asn1(input: TokenStream) -> TokenStream {
+    let mut source = Source::default();
+    source.tokens(input);
+
+    let mut compiler = AsnCompiler::new();
+    if let Err(e) = compiler.add_file("asn1!".into(), source.text.clone()) {
+        return source.errors(&e);
+    }
+
+    let diagnostics = compiler.analysis().diagnostics;
+    if !diagnostics.is_empty() {
+        return diagnostics.iter().flat_map(|d| source.errors(d)).collect();
+    }
+
+    generate(&mut compiler, Span::call_site())
+}
+
+/// Compile ASN.1 modules from files, given as string literals of paths
+/// relative to the directory of the crate's manifest.
+///
+/// ```ignore
+/// asn1_macros::include_asn1!("asn1/messages.asn1", "asn1/common.asn1");
+/// ```
+#[proc_macro]
+pub fn include_asn1(input: TokenStream) -> TokenStream {
+    let mut paths = vec![];
+    for token in input {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => (),
+            TokenTree::Literal(literal) => match string_value(&literal) {
+                Some(path) => paths.push((path, literal.span())),
+                None => return compile_error("expected a string literal", literal.span()),
+            },
+            token => return compile_error("expected a string literal", token.span()),
+        }
+    }
+    if paths.is_empty() {
+        return compile_error(
+            "expected the paths of ASN.1 source files",
+            Span::call_site(),
+        );
+    }
+
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").map_or_else(PathBuf::new, PathBuf::from);
+    let mut compiler = AsnCompiler::new();
+    let mut files = vec![];
+    let mut errors = TokenStream::new();
+    let mut tracked = TokenStream::new();
+    for (path, span) in paths {
+        let path = root.join(path);
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                let message = format!("unable to read `{}`: {e}", path.display());
+                errors.extend(compile_error(&message, span));
+                continue;
+            }
+        };
+
+        // including the file makes cargo compile the crate again when the
+        // file changes
+        let path = path.to_string_lossy().to_string();
+        tracked
+            .extend(format!("const _: &[u8] = include_bytes!({path:?});").parse::<TokenStream>());
+
+        match compiler.add_file(path, source) {
+            Ok(file) => files.push((file, span)),
+            Err(e) => errors.extend(compile_error(&compiler.render_diagnostic(&e), span)),
+        }
+    }
+    if !errors.is_empty() {
+        return errors;
+    }
+
+    let diagnostics = compiler.analysis().diagnostics;
+    if !diagnostics.is_empty() {
+        return diagnostics
+            .iter()
+            .flat_map(|diagnostic| {
+                let source = diagnostic.labels.iter().find_map(|l| l.source);
+                let span = files
+                    .iter()
+                    .find(|(file, _)| Some(*file) == source)
+                    .map_or_else(Span::call_site, |(_, span)| *span);
+                compile_error(&compiler.render_diagnostic(diagnostic), span)
+            })
+            .collect();
+    }
+
+    let mut output = generate(&mut compiler, Span::call_site());
+    output.extend(tracked);
+    output
+}
+
+/// Generate the rust source of the analysed modules
+fn generate(compiler: &mut AsnCompiler, span: Span) -> TokenStream {
+    let files = match compiler
+        .analysis()
+        .codegen(CodegenTarget::Rust, &CodegenOptions::default())
+    {
+        Ok(files) => files,
+        Err(e) => return compile_error(&format!("unable to generate code: {e:?}"), span),
+    };
+
+    match files[0].content.parse() {
+        Ok(tokens) => tokens,
+        Err(e) => compile_error(&format!("generated code is not valid rust: {e}"), span),
+    }
+}
+
+/// ASN.1 source text rebuilt from the tokens written within a macro, keeping
+/// the lines and columns of the tokens so that comments are ended as they
+/// are in the macro
+#[derive(Default)]
+struct Source {
+    text: String,
+
+    /// The range of each token within the text, with its span
+    spans: Vec<(Range<usize>, Span)>,
+
+    /// Line and column of the end of the text, counted from one
+    line: usize,
+    column: usize,
+}
+
+impl Source {
+    fn tokens(&mut self, tokens: TokenStream) {
+        for token in tokens {
+            match token {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    self.push(open, group.span_open());
+                    self.tokens(group.stream());
+                    self.push(close, group.span_close());
+                }
+                token => {
+                    let span = token.span();
+                    let text = span.source_text().unwrap_or_else(|| token.to_string());
+                    self.push(&text, span);
+                }
+            }
+        }
+    }
+
+    /// Add a token to the text at its line and column, or separated by a
+    /// space if it would overlap the previous token
+    fn push(&mut self, token: &str, span: Span) {
+        if token.is_empty() {
+            return;
+        }
+
+        let (line, column) = (span.line(), span.column());
+        if self.text.is_empty() {
+            self.line = line;
+            self.column = 1;
+        }
+        if line > self.line {
+            self.text.push_str(&"\n".repeat(line - self.line));
+            self.line = line;
+            self.column = 1;
+        }
+        if column > self.column {
+            self.text.push_str(&" ".repeat(column - self.column));
+        } else if column < self.column && !self.text.ends_with(char::is_whitespace) {
+            self.text.push(' ');
+        }
+
+        let start = self.text.len();
+        self.text.push_str(token);
+        self.spans.push((start..self.text.len(), span));
+
+        match token.rfind('\n') {
+            Some(last) => {
+                self.line += token.matches('\n').count();
+                self.column = token[last + 1..].chars().count() + 1;
+            }
+            None => self.column = column.max(self.column) + token.chars().count(),
+        }
+    }
+
+    /// Get the span of the token at a byte offset within the text, or the
+    /// token following it
+    fn span(&self, offset: usize) -> Span {
+        self.spans
+            .iter()
+            .find(|(range, _)| range.end > offset)
+            .or(self.spans.last())
+            .map_or_else(Span::call_site, |(_, span)| *span)
+    }
+
+    /// Report a diagnostic as compile errors at the tokens of its labels
+    fn errors(&self, diagnostic: &Diagnostic) -> TokenStream {
+        let mut errors = TokenStream::new();
+        for label in &diagnostic.labels {
+            let span = match &label.location {
+                Some(location) => self.span(location.start),
+                None => Span::call_site(),
+            };
+            let message = match label.message.as_str() {
+                "" => diagnostic.name.clone(),
+                message => format!("{}: {message}", diagnostic.name),
+            };
+            errors.extend(compile_error(&message, span));
+        }
+        if errors.is_empty() {
+            errors = compile_error(&diagnostic.name, Span::call_site());
+        }
+        errors
+    }
+}
+
+/// Get the value of a string literal, either a raw string or a string with
+/// escapes
+fn string_value(literal: &Literal) -> Option<String> {
+    let text = literal.to_string();
+    if let Some(raw) = text.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let inner = raw.get(hashes + 1..raw.len() - hashes - 1)?;
+        return Some(inner.to_string());
+    }
+
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        value.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            c => c,
+        });
+    }
+    Some(value)
+}
+
+/// Create a `compile_error!` invocation located at a span
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut literal = Literal::string(message);
+    literal.set_span(span);
+    let mut group = Group::new(Delimiter::Brace, TokenTree::Literal(literal).into());
+    group.set_span(span);
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+
+    [
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        TokenTree::Punct(bang),
+        TokenTree::Group(group),
+    ]
+    .into_iter()
+    .collect()
+}
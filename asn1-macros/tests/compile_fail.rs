@@ -0,0 +1,6 @@
+/// Errors in ASN.1 modules are compile errors located at their source
+#[test]
+fn errors_are_located() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,25 @@
+asn1_macros::asn1! {
+    Messages DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+        Colour ::= ENUMERATED { red, green }
+        Message ::= SEQUENCE { id INTEGER, colour Colour OPTIONAL }
+    END
+}
+
+asn1_macros::include_asn1!("test/shapes.asn1");
+
+#[test]
+fn modules_are_generated() {
+    let message = messages::Message {
+        id: 5,
+        colour: Some(messages::Colour::Green),
+    };
+    assert_eq!(message.id, 5);
+    assert_eq!(message.colour, Some(messages::Colour::Green));
+}
+
+#[test]
+fn included_modules_are_generated() {
+    let centre = shapes::Point { x: 1, y: 2 };
+    let shape = shapes::Shape::Circle(shapes::ShapeCircle { centre, radius: 3 });
+    assert!(matches!(shape, shapes::Shape::Circle(circle) if circle.centre.y == 2));
+}
@@ -0,0 +1,3 @@
+asn1_macros::include_asn1!("test/missing.asn1");
+
+fn main() {}
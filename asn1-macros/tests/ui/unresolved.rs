@@ -0,0 +1,7 @@
+asn1_macros::asn1! {
+    Messages DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+        Message ::= SEQUENCE { id Identifier }
+    END
+}
+
+fn main() {}
@@ -1,13 +1,19 @@
 //! Type checking and name resolution for ASN.1.
 //! The following analysis passes are defined:
 //! - Local: analyse each module in isolation to get its assignments,
-//!   imports and exports.
-//! - Global: resolve dependencies between modules (imports and exports).
+//!   imports and exports, and the comments documenting its assignments.
+//! - Global: resolve dependencies between modules (imports and exports) and
+//!   link references to the assignments they refer to, then expand
+//!   `COMPONENTS OF`, selection types and information object fields.
 //! - Type: resolve types across all modules.
 //! - Value: parse and analyse values now that the type of the value is known.
 //! Note that modules can depend upon each other and must be checked at the
 //! same time so circular and recursive dependency resolution can take place.
 //!
+//! The results of analysis are cached within the compiler.  When a source file
+//! is changed, local analysis is re-run for that file, and global analysis is
+//! re-run for its modules and any modules that (indirectly) import them.
+//!
 //! The analysis passes are all based on the fact that the provided CST from the
 //! parser is valid as far as the parser can tell.  Any parse errors that put
 //! tokens in weird locations within the cst due to error recovery, etc (not
@@ -17,12 +23,31 @@
 //! however it could not be detected in parsing, then it likely will be thrown
 //! as a type error for the user to fix and analysis to continue.
 
+mod cache;
+mod comments;
+mod constraint;
 mod context;
+mod duplicates;
 mod environment;
+mod expand;
 mod global;
 mod local;
+mod number;
 mod object_tree;
+mod oid;
+mod resolve;
+mod search;
+mod strings;
+mod time;
 
+pub(crate) use cache::AnalysisCache;
+pub(crate) use constraint::{Bounds, Discrete, EffectiveConstraints};
 pub use context::AnalysisContext;
+pub(crate) use duplicates::enum_numbers;
 pub use environment::Environment;
-// pub use object_tree::{Iri, IriParseError, Oid};
+pub use number::{Real, RealBase};
+pub use object_tree::{Iri, IriComponent, IriParseError, Oid, OidEntry, OidRegistry, RelativeOid};
+pub use resolve::Definition;
+pub(crate) use search::SearchPath;
+pub(crate) use strings::restricted_string;
+pub(crate) use time::parse_time;
@@ -0,0 +1,172 @@
+//! Storage of analysis results between runs of the analyser, so that editors
+//! and watch mode tools only need to re-analyse the modules affected by an edit.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{compiler::SourceId, cst::AsnNodeId, Diagnostic};
+
+use super::environment::Environment;
+
+/// Analysis results kept by the compiler after an analysis has finished.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AnalysisCache {
+    /// All modules from all source files
+    pub(crate) modules: HashMap<AsnNodeId, Environment>,
+
+    /// The error from the last parse of each source file that could not be
+    /// parsed.  It is reported by every analysis until the file is parsed
+    /// again or removed.
+    pub(crate) parse_diagnostics: HashMap<SourceId, Diagnostic>,
+
+    /// Diagnostics reported by local analysis of each source file
+    pub(super) local_diagnostics: HashMap<SourceId, Vec<Diagnostic>>,
+
+    /// Diagnostics reported by global analysis of each module
+    pub(super) global_diagnostics: HashMap<AsnNodeId, Vec<Diagnostic>>,
+
+    /// Source files that have been added, modified or removed since the last
+    /// time analysis was run.
+    changed: HashSet<SourceId>,
+
+    /// The number of global analyses run, so that tests can check which
+    /// modules are re-analysed
+    #[cfg(test)]
+    pub(super) global_runs: usize,
+}
+
+impl AnalysisCache {
+    /// Mark a source file as requiring re-analysis
+    pub(crate) fn changed(&mut self, file: SourceId) {
+        self.changed.insert(file);
+    }
+
+    /// Get all changed source files, resetting the list of changed files.
+    pub(super) fn take_changed(&mut self) -> Vec<SourceId> {
+        let mut files: Vec<_> = self.changed.drain().collect();
+        files.sort();
+        files
+    }
+
+    /// Remove all modules and local diagnostics originating from the given
+    /// source file.  Returns the removed modules.
+    pub(super) fn remove_source(&mut self, file: SourceId) -> Vec<Environment> {
+        self.local_diagnostics.remove(&file);
+
+        let ids: Vec<_> = self
+            .modules
+            .keys()
+            .filter(|id| id.source() == file)
+            .copied()
+            .collect();
+
+        ids.into_iter()
+            .filter_map(|id| {
+                self.global_diagnostics.remove(&id);
+                self.modules.remove(&id)
+            })
+            .collect()
+    }
+
+    /// Find all modules that directly or indirectly import any of the named
+    /// modules, including the named modules themselves.
+    pub(super) fn dependents(&self, names: &HashSet<String>) -> Vec<AsnNodeId> {
+        let mut names = names.clone();
+        let mut result = HashSet::new();
+
+        loop {
+            let found: Vec<_> = self
+                .modules
+                .values()
+                .filter(|module| !result.contains(&module.node))
+                .filter(|module| {
+                    names.contains(&module.name)
+                        || module
+                            .imports
+                            .iter()
+                            .any(|import| names.contains(&import.module.value))
+                })
+                .map(|module| (module.node, module.name.clone()))
+                .collect();
+
+            if found.is_empty() {
+                break;
+            }
+
+            for (id, name) in found {
+                result.insert(id);
+                names.insert(name);
+            }
+        }
+
+        let mut result: Vec<_> = result.into_iter().collect();
+        result.sort();
+        result
+    }
+
+    /// Get all cached diagnostics in a stable order.
+    pub(super) fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        let mut parse: Vec<_> = self.parse_diagnostics.iter().collect();
+        parse.sort_by_key(|(id, _)| **id);
+
+        let mut local: Vec<_> = self.local_diagnostics.iter().collect();
+        local.sort_by_key(|(id, _)| **id);
+
+        let mut global: Vec<_> = self.global_diagnostics.iter().collect();
+        global.sort_by_key(|(id, _)| **id);
+
+        parse
+            .into_iter()
+            .map(|(_, d)| d)
+            .chain(local.into_iter().flat_map(|(_, d)| d))
+            .chain(global.into_iter().flat_map(|(_, d)| d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AsnCompiler;
+
+    const BASE: &str = "Base DEFINITIONS ::= BEGIN
+    base INTEGER ::= 5
+END";
+
+    const USER: &str = "User DEFINITIONS ::= BEGIN
+    IMPORTS base FROM Base;
+    user INTEGER ::= base
+END";
+
+    const OTHER: &str = "Other DEFINITIONS ::= BEGIN
+    Other ::= Missing
+END";
+
+    fn codes(compiler: &mut AsnCompiler) -> Vec<String> {
+        compiler
+            .analysis()
+            .diagnostics
+            .into_iter()
+            .map(|d| d.error_code)
+            .collect()
+    }
+
+    #[test]
+    fn unrelated_modules_are_not_reanalysed() {
+        let mut compiler = AsnCompiler::new();
+        let base = compiler.add_file("base.asn1".into(), BASE.into()).unwrap();
+        compiler.add_file("user.asn1".into(), USER.into()).unwrap();
+        compiler
+            .add_file("other.asn1".into(), OTHER.into())
+            .unwrap();
+        let unresolved = ["Asn1::Analysis::UnresolvedReference"];
+        assert_eq!(codes(&mut compiler), unresolved);
+        assert_eq!(compiler.cache.global_runs, 3);
+
+        // only the edited module and its dependent are analysed again, and
+        // the cached diagnostics of the other module are still reported
+        compiler.update_file(base, BASE.replace("5", "7")).unwrap();
+        assert_eq!(codes(&mut compiler), unresolved);
+        assert_eq!(compiler.cache.global_runs, 5);
+
+        let other = compiler.cache.modules.values().find(|m| m.name == "Other");
+        assert!(other.is_some_and(|other| other.variables.contains_key("Other")));
+    }
+}
@@ -0,0 +1,130 @@
+//! Comments documenting assignments and the components, alternatives and
+//! items of their types.  The parser keeps comments wherever they were found
+//! in the tree, so they are matched to what they document by their position
+//! in the source text instead.
+
+use std::collections::HashMap;
+
+use crate::{
+    ast::{ComponentType, Type},
+    cst::AsnNodeId,
+    token::TokenKind,
+};
+
+use super::context::AnalysisContext;
+
+impl AnalysisContext<'_> {
+    /// Find the comments documenting each of the given names, which are the
+    /// names of assignments, components, alternatives or items of a single
+    /// source file.  The comments on the lines before a name, and a comment
+    /// later on the line its definition ends, document it.  Each comment is
+    /// a line of the text, with empty lines between paragraphs.
+    pub(super) fn comments(&self, names: &[AsnNodeId]) -> HashMap<AsnNodeId, String> {
+        let Some(first) = names.first() else {
+            return HashMap::new();
+        };
+        let source = self.source(first.source());
+        let tree = &source.tree;
+        let text = source.source.as_str();
+
+        let mut comments = vec![];
+        let mut nodes = vec![tree.root];
+        while let Some(node) = nodes.pop() {
+            match tree.token(node) {
+                Some(token) if tree.is_trivia(node) => comments.push((tree.span(node), token.kind)),
+                Some(_) => (),
+                None => nodes.extend(tree.children(node)),
+            }
+        }
+        comments.sort_by_key(|(span, _)| span.start);
+
+        let mut result = HashMap::new();
+        for &name in names {
+            let start = tree.span(name).start;
+            let end = tree
+                .parent(name)
+                .map_or(start, |parent| tree.span(parent).end);
+
+            let mut lines = vec![];
+            let before = comments.partition_point(|(span, _)| span.end <= start);
+            let mut boundary = start;
+            let mut leading = before;
+            while leading > 0 {
+                let (span, _) = &comments[leading - 1];
+                if !text[span.end..boundary].trim().is_empty() || !own_line(text, span.start) {
+                    break;
+                }
+                boundary = span.start;
+                leading -= 1;
+            }
+            for (span, kind) in &comments[leading..before] {
+                comment_lines(&text[span.clone()], *kind, &mut lines);
+            }
+
+            let after = comments.partition_point(|(span, _)| span.start < end);
+            if let Some((span, kind)) = comments.get(after) {
+                let between = &text[end..span.start];
+                if !between.contains('\n') && between.trim_matches([' ', '\t', ',']).is_empty() {
+                    comment_lines(&text[span.clone()], *kind, &mut lines);
+                }
+            }
+
+            // leading and trailing empty lines are only separators
+            while lines.last().is_some_and(String::is_empty) {
+                lines.pop();
+            }
+            let first = lines.iter().position(|line| !line.is_empty());
+            if let Some(first) = first {
+                result.insert(name, lines[first..].join("\n"));
+            }
+        }
+        result
+    }
+}
+
+/// Add the names of the components, alternatives and items within a type to
+/// a list of names that can be documented
+pub(super) fn named_items(ty: &Type, names: &mut Vec<AsnNodeId>) {
+    match ty {
+        Type::Sequence(components) | Type::Set(components) => {
+            for component in components.items() {
+                if let ComponentType::Named(component) = component {
+                    names.push(component.name.id);
+                }
+                named_items(&component.ty().value, names);
+            }
+        }
+        Type::Choice(alternatives) => {
+            for alternative in alternatives.items() {
+                names.push(alternative.name.id);
+                named_items(&alternative.ty.value, names);
+            }
+        }
+        Type::Enumerated(items) => names.extend(items.items().map(|item| item.name.id)),
+        Type::SequenceOf(of) | Type::SetOf(of) => named_items(&of.ty.value, names),
+        Type::Tagged(tagged) => named_items(&tagged.ty.value, names),
+        _ => (),
+    }
+}
+
+/// Is there nothing but whitespace before a position on its line
+fn own_line(text: &str, position: usize) -> bool {
+    let line = text[..position].rfind('\n').map_or(0, |i| i + 1);
+    text[line..position].trim().is_empty()
+}
+
+/// Add the lines of text within a comment, without the comment markers
+fn comment_lines(comment: &str, kind: TokenKind, lines: &mut Vec<String>) {
+    if kind == TokenKind::SingleComment {
+        let text = comment.strip_prefix("--").unwrap_or(comment);
+        lines.push(text.strip_suffix("--").unwrap_or(text).trim().to_string());
+        return;
+    }
+
+    let text = comment.strip_prefix("/*").unwrap_or(comment);
+    let text = text.strip_suffix("*/").unwrap_or(text);
+    for line in text.lines() {
+        let line = line.trim();
+        lines.push(line.strip_prefix('*').unwrap_or(line).trim().to_string());
+    }
+}
@@ -0,0 +1,705 @@
+//! Interpretation of the constraints of types as the values, sizes and
+//! characters that they permit.  The constraints following a type are
+//! combined with those of the types it is defined in terms of, and each kind
+//! of restriction is kept separately, as for the PER-visible constraints of
+//! ITU-T X.691: a union permits the values of any of its sets, an
+//! intersection those of all of them, and the values removed by `EXCEPT` are
+//! ignored.  Restrictions that cannot be interpreted, such as ranges of real
+//! numbers, do not restrict the type.
+
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{
+        Constraint, ElementSet, ElementSets, Endpoint, NamedNumber, SubtypeElement, Type,
+        TypeOrValue, Value, WithId,
+    },
+    cst::AsnNodeId,
+};
+
+use super::{context::AnalysisContext, environment::Environment, oid::cstring_value};
+
+/// Contained subtypes nested deeper than this are not interpreted, so that
+/// types containing themselves end
+const MAX_DEPTH: usize = 32;
+
+/// An inclusive range of values, either end of which may be unbounded
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    pub lower: Option<T>,
+    pub upper: Option<T>,
+}
+
+/// A set of values permitted by a constraint
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bounds<T> {
+    /// The permitted values, as sorted intervals that neither overlap nor
+    /// touch each other
+    pub intervals: Vec<Interval<T>>,
+
+    /// Are other values permitted by extensions of the constraint
+    pub extensible: bool,
+}
+
+/// The values of a type permitted by its constraints
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EffectiveConstraints {
+    /// The permitted integer values
+    pub value: Option<Bounds<BigInt>>,
+
+    /// The permitted number of characters, bits, octets or elements
+    pub size: Option<Bounds<BigInt>>,
+
+    /// The characters permitted in strings, `FROM`
+    pub alphabet: Option<Bounds<char>>,
+
+    /// Regular expressions that strings must match, `PATTERN`
+    pub patterns: Vec<String>,
+
+    /// The property settings that time values must have, `SETTINGS`
+    pub settings: Vec<String>,
+}
+
+/// Values with a next and previous value, so that open endpoints and
+/// adjacent intervals can be represented
+pub trait Discrete: Ord + Clone {
+    fn next(&self) -> Option<Self>;
+    fn previous(&self) -> Option<Self>;
+}
+
+impl Discrete for BigInt {
+    fn next(&self) -> Option<Self> {
+        Some(self + 1)
+    }
+
+    fn previous(&self) -> Option<Self> {
+        Some(self - 1)
+    }
+}
+
+impl Discrete for char {
+    fn next(&self) -> Option<Self> {
+        match *self {
+            '\u{d7ff}' => Some('\u{e000}'),
+            c => char::from_u32(c as u32 + 1),
+        }
+    }
+
+    fn previous(&self) -> Option<Self> {
+        match *self {
+            '\u{e000}' => Some('\u{d7ff}'),
+            c => char::from_u32((c as u32).checked_sub(1)?),
+        }
+    }
+}
+
+impl<T: Discrete> Bounds<T> {
+    /// Create bounds permitting a single range of values
+    pub fn range(lower: Option<T>, upper: Option<T>) -> Self {
+        Bounds {
+            intervals: vec![Interval { lower, upper }],
+            extensible: false,
+        }
+        .normalize()
+    }
+
+    /// The least permitted value, if there is one
+    pub fn lower(&self) -> Option<&T> {
+        self.intervals.first()?.lower.as_ref()
+    }
+
+    /// The greatest permitted value, if there is one
+    pub fn upper(&self) -> Option<&T> {
+        self.intervals.last()?.upper.as_ref()
+    }
+
+    /// Is a value within the permitted values, ignoring extensions
+    pub fn contains(&self, value: &T) -> bool {
+        self.intervals.iter().any(|interval| {
+            interval.lower.as_ref().is_none_or(|lower| lower <= value)
+                && interval.upper.as_ref().is_none_or(|upper| value <= upper)
+        })
+    }
+
+    /// Are all of the values permitted by these bounds permitted by others,
+    /// ignoring extensions
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.intervals.iter().all(|interval| {
+            other.intervals.iter().any(|outer| {
+                below_or_equal(&outer.lower, &interval.lower, true)
+                    && below_or_equal(&interval.upper, &outer.upper, false)
+            })
+        })
+    }
+
+    /// The values permitted by either set of bounds
+    pub fn union(&self, other: &Self) -> Self {
+        Bounds {
+            intervals: self
+                .intervals
+                .iter()
+                .chain(&other.intervals)
+                .cloned()
+                .collect(),
+            extensible: self.extensible || other.extensible,
+        }
+        .normalize()
+    }
+
+    /// The values permitted by both sets of bounds
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        for a in &self.intervals {
+            for b in &other.intervals {
+                let lower = match (&a.lower, &b.lower) {
+                    (Some(a), Some(b)) => Some(a.max(b).clone()),
+                    (a, b) => a.clone().or(b.clone()),
+                };
+                let upper = match (&a.upper, &b.upper) {
+                    (Some(a), Some(b)) => Some(a.min(b).clone()),
+                    (a, b) => a.clone().or(b.clone()),
+                };
+                intervals.push(Interval { lower, upper });
+            }
+        }
+
+        Bounds {
+            intervals,
+            extensible: self.extensible && other.extensible,
+        }
+        .normalize()
+    }
+
+    /// Sort the intervals, removing empty intervals and joining those that
+    /// overlap or touch
+    fn normalize(mut self) -> Self {
+        self.intervals
+            .retain(|interval| match (&interval.lower, &interval.upper) {
+                (Some(lower), Some(upper)) => lower <= upper,
+                _ => true,
+            });
+        self.intervals.sort_by(|a, b| match (&a.lower, &b.lower) {
+            (None, None) => std::cmp::Ordering::Equal,
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b),
+        });
+
+        let mut intervals: Vec<Interval<T>> = vec![];
+        for interval in self.intervals {
+            if let Some(last) = intervals.last_mut() {
+                let touches = match (&last.upper, &interval.lower) {
+                    (None, _) | (_, None) => true,
+                    (Some(upper), Some(lower)) => {
+                        lower <= upper || upper.next().as_ref() == Some(lower)
+                    }
+                };
+                if touches {
+                    if !below_or_equal(&interval.upper, &last.upper, false) {
+                        last.upper = interval.upper;
+                    }
+                    continue;
+                }
+            }
+            intervals.push(interval);
+        }
+        self.intervals = intervals;
+
+        self
+    }
+}
+
+/// Compare two endpoints, where a missing lower endpoint is below every value
+/// and a missing upper endpoint is above every value
+fn below_or_equal<T: Ord>(a: &Option<T>, b: &Option<T>, lower: bool) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a <= b,
+        (None, None) => true,
+        (None, Some(_)) => lower,
+        (Some(_), None) => !lower,
+    }
+}
+
+impl EffectiveConstraints {
+    /// The values permitted by either set of constraints.  A restriction is
+    /// only kept if both sets have it.
+    fn union(self, other: Self) -> Self {
+        EffectiveConstraints {
+            value: both(self.value, other.value, |a, b| a.union(&b)),
+            size: both(self.size, other.size, |a, b| a.union(&b)),
+            alphabet: both(self.alphabet, other.alphabet, |a, b| a.union(&b)),
+            patterns: [self.patterns, other.patterns].concat(),
+            settings: self
+                .settings
+                .into_iter()
+                .filter(|settings| other.settings.contains(settings))
+                .collect(),
+        }
+    }
+
+    /// The values permitted by both sets of constraints
+    fn intersection(self, other: Self) -> Self {
+        EffectiveConstraints {
+            value: either(self.value, other.value, |a, b| a.intersection(&b)),
+            size: either(self.size, other.size, |a, b| a.intersection(&b)),
+            alphabet: either(self.alphabet, other.alphabet, |a, b| a.intersection(&b)),
+            patterns: [self.patterns, other.patterns].concat(),
+            settings: [self.settings, other.settings].concat(),
+        }
+    }
+
+    /// Apply a constraint to a type already restricted by these constraints.
+    /// Whether the result is extensible is decided by the later constraint.
+    fn serial(self, next: Self) -> Self {
+        fn apply<T: Discrete>(a: Bounds<T>, b: Bounds<T>) -> Bounds<T> {
+            Bounds {
+                extensible: b.extensible,
+                ..a.intersection(&b)
+            }
+        }
+
+        EffectiveConstraints {
+            value: either(self.value, next.value, apply),
+            size: either(self.size, next.size, apply),
+            alphabet: either(self.alphabet, next.alphabet, apply),
+            patterns: [self.patterns, next.patterns].concat(),
+            settings: [self.settings, next.settings].concat(),
+        }
+    }
+
+    /// Mark every restriction as permitting other values in extensions
+    fn extensible(mut self) -> Self {
+        for bounds in [&mut self.value, &mut self.size].into_iter().flatten() {
+            bounds.extensible = true;
+        }
+        if let Some(alphabet) = &mut self.alphabet {
+            alphabet.extensible = true;
+        }
+        self
+    }
+
+    /// Get the constraints that are visible to the packed encoding rules for
+    /// a built-in type: the value range of integers, the size of strings and
+    /// lists, and the alphabet of the known-multiplier character string
+    /// types, if it is not extensible.
+    pub fn per_visible(&self, ty: &Type) -> EffectiveConstraints {
+        let mut visible = EffectiveConstraints::default();
+        match ty {
+            Type::Integer(_) => visible.value = self.value.clone(),
+            Type::BitString(_) | Type::OctetString | Type::SequenceOf(_) | Type::SetOf(_) => {
+                visible.size = self.size.clone()
+            }
+            Type::IA5String
+            | Type::PrintableString
+            | Type::VisibleString
+            | Type::ISO64String
+            | Type::NumericString
+            | Type::UniversalString
+            | Type::BmpString => {
+                visible.size = self.size.clone();
+                visible.alphabet = self.alphabet.clone().filter(|a| !a.extensible);
+            }
+            _ => (),
+        }
+        visible
+    }
+}
+
+/// Combine two optional restrictions, keeping neither unless both are present
+fn both<T>(a: Option<T>, b: Option<T>, f: impl FnOnce(T, T) -> T) -> Option<T> {
+    Some(f(a?, b?))
+}
+
+/// Combine two optional restrictions, keeping whichever is present
+fn either<T>(a: Option<T>, b: Option<T>, f: impl FnOnce(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(f(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Interprets the constraints of a type and the types it refers to
+struct Evaluator<'a, 'b> {
+    /// Context to find constraints and referenced values in
+    context: &'a AnalysisContext<'b>,
+
+    /// The actual parameters of the instances of parameterized types that
+    /// have been followed, keyed by the name of their dummy reference, with
+    /// the module they are given in
+    dummies: HashMap<String, (&'a Environment, &'a WithId<TypeOrValue>)>,
+
+    /// The types being interpreted, so that types containing themselves end
+    depth: usize,
+}
+
+impl AnalysisContext<'_> {
+    /// Get the constraints following a type, with the module they are written
+    /// in, given the node ID of the type
+    pub(crate) fn constraints(&self, node: AsnNodeId) -> Option<(&Environment, &[Constraint])> {
+        self.modules().find_map(|module| {
+            module
+                .constraints
+                .get(&node)
+                .map(|constraints| (module, &constraints[..]))
+        })
+    }
+
+    /// Get the values of a type permitted by its constraints, given the node
+    /// ID of the type.  The constraints of the types it refers to are only
+    /// included if `follow` is set, those within its tags always are.
+    pub(crate) fn constraints_of(
+        &self,
+        module: &Environment,
+        ty: &Type,
+        id: AsnNodeId,
+        follow: bool,
+    ) -> EffectiveConstraints {
+        let mut evaluator = Evaluator {
+            context: self,
+            dummies: HashMap::new(),
+            depth: 0,
+        };
+        evaluator.ty(module, ty, id, follow)
+    }
+
+    /// Get the values of a type permitted by its constraints, where the type
+    /// is within the expanded type of an instance of a parameterized type.
+    /// Dummy references within the constraints are the actual parameters of
+    /// the instance.
+    pub(crate) fn constraints_within(
+        &self,
+        module: &Environment,
+        instance: &str,
+        ty: &Type,
+        id: AsnNodeId,
+        follow: bool,
+    ) -> EffectiveConstraints {
+        let mut evaluator = Evaluator {
+            context: self,
+            dummies: HashMap::new(),
+            depth: 0,
+        };
+        evaluator.instance(module, instance);
+        evaluator.ty(module, ty, id, follow)
+    }
+}
+
+impl<'a> Evaluator<'a, '_> {
+    /// Interpret the constraints of a type, starting with those of the types
+    /// it is defined in terms of
+    fn ty(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        id: AsnNodeId,
+        follow: bool,
+    ) -> EffectiveConstraints {
+        if self.depth > MAX_DEPTH {
+            return EffectiveConstraints::default();
+        }
+        self.depth += 1;
+
+        let mut types = vec![id];
+        let mut current = (module, ty);
+        let mut visited = vec![];
+        loop {
+            match current.1 {
+                Type::Tagged(tagged) => {
+                    types.push(tagged.ty.id);
+                    current.1 = &tagged.ty.value;
+                }
+                Type::Reference(reference) if follow => {
+                    let Some((module, var)) = self
+                        .context
+                        .resolve_expanded(current.0, reference)
+                        .or_else(|| self.context.resolve(current.0, reference))
+                    else {
+                        break;
+                    };
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        break;
+                    };
+                    if visited.contains(&var.id) {
+                        break;
+                    }
+                    visited.push(var.id);
+                    if reference.module.is_none() {
+                        self.instance(module, &reference.name);
+                    }
+
+                    types.push(var.value.id);
+                    current = (module, ty);
+                }
+                _ => break,
+            }
+        }
+
+        let numbers = match current.1 {
+            Type::Integer(numbers) => &numbers[..],
+            _ => &[],
+        };
+
+        let mut effective = EffectiveConstraints::default();
+        for id in types.into_iter().rev() {
+            let Some((module, constraints)) = self.context.constraints(id) else {
+                continue;
+            };
+            for constraint in constraints {
+                let next = self.constraint(module, constraint, numbers, false);
+                effective = effective.serial(next);
+            }
+        }
+
+        self.depth -= 1;
+        effective
+    }
+
+    /// Record the actual parameters of an instance of a parameterized type,
+    /// given its name within a module, so that the dummy references within
+    /// its constraints can be interpreted
+    fn instance(&mut self, module: &'a Environment, name: &str) {
+        let Some(instance) = module.instances.get(name) else {
+            return;
+        };
+        let Some(definition) = self
+            .context
+            .module_named(&instance.definition.module)
+            .and_then(|env| env.variables.get(&instance.definition.name))
+        else {
+            return;
+        };
+
+        for (dummy, actual) in definition.parameters.iter().zip(&instance.parameters) {
+            self.dummies
+                .insert(dummy.name.value.clone(), (module, actual));
+        }
+    }
+
+    /// Interpret a single constraint.  Within `FROM`, values are the
+    /// characters of strings rather than integers.
+    fn constraint(
+        &mut self,
+        module: &'a Environment,
+        constraint: &'a Constraint,
+        numbers: &'a [NamedNumber],
+        characters: bool,
+    ) -> EffectiveConstraints {
+        match constraint {
+            Constraint::Subtype(sets) => self.element_sets(module, sets, numbers, characters),
+            Constraint::Table(_) | Constraint::Contents(_) | Constraint::UserDefined => {
+                EffectiveConstraints::default()
+            }
+        }
+    }
+
+    /// Interpret the root of a subtype constraint, ignoring its extension
+    /// additions
+    fn element_sets(
+        &mut self,
+        module: &'a Environment,
+        sets: &'a ElementSets,
+        numbers: &'a [NamedNumber],
+        characters: bool,
+    ) -> EffectiveConstraints {
+        let Some(root) = &sets.root else {
+            return EffectiveConstraints::default();
+        };
+
+        let effective = self.element_set(module, root, numbers, characters);
+        match sets.extensible {
+            true => effective.extensible(),
+            false => effective,
+        }
+    }
+
+    fn element_set(
+        &mut self,
+        module: &'a Environment,
+        set: &'a ElementSet,
+        numbers: &'a [NamedNumber],
+        characters: bool,
+    ) -> EffectiveConstraints {
+        match set {
+            ElementSet::Element(element) => self.element(module, element, numbers, characters),
+            ElementSet::Union(sets) => {
+                let mut sets = sets
+                    .iter()
+                    .map(|set| self.element_set(module, set, numbers, characters))
+                    .collect::<Vec<_>>()
+                    .into_iter();
+                let first = sets.next().unwrap_or_default();
+                sets.fold(first, EffectiveConstraints::union)
+            }
+            ElementSet::Intersection(sets) => sets
+                .iter()
+                .map(|set| self.element_set(module, set, numbers, characters))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .fold(
+                    EffectiveConstraints::default(),
+                    EffectiveConstraints::intersection,
+                ),
+            ElementSet::Except(set, _) => self.element_set(module, set, numbers, characters),
+            ElementSet::AllExcept(_) => EffectiveConstraints::default(),
+        }
+    }
+
+    fn element(
+        &mut self,
+        module: &'a Environment,
+        element: &'a SubtypeElement,
+        numbers: &'a [NamedNumber],
+        characters: bool,
+    ) -> EffectiveConstraints {
+        let mut effective = EffectiveConstraints::default();
+        match element {
+            SubtypeElement::Value(value) if characters => {
+                if let Some(text) = self.string(module, &value.value) {
+                    effective.alphabet = Some(
+                        text.chars()
+                            .map(|c| Bounds::range(Some(c), Some(c)))
+                            .reduce(|a, b| a.union(&b))
+                            .unwrap_or(Bounds {
+                                intervals: vec![],
+                                extensible: false,
+                            }),
+                    );
+                }
+            }
+            SubtypeElement::Value(value) => {
+                if let Some(value) = self.integer(module, &value.value, numbers) {
+                    effective.value = Some(Bounds::range(Some(value.clone()), Some(value)));
+                }
+            }
+            SubtypeElement::Range(lower, upper) if characters => {
+                let lower = self.endpoint(lower, |value| self.character(module, value));
+                let upper = self.endpoint(upper, |value| self.character(module, value));
+                if let (Some(lower), Some(upper)) = (lower, upper) {
+                    effective.alphabet = Some(Bounds::range(
+                        open(lower, Discrete::next),
+                        open(upper, Discrete::previous),
+                    ));
+                }
+            }
+            SubtypeElement::Range(lower, upper) => {
+                let lower = self.endpoint(lower, |value| self.integer(module, value, numbers));
+                let upper = self.endpoint(upper, |value| self.integer(module, value, numbers));
+                if let (Some(lower), Some(upper)) = (lower, upper) {
+                    effective.value = Some(Bounds::range(
+                        open(lower, Discrete::next),
+                        open(upper, Discrete::previous),
+                    ));
+                }
+            }
+            SubtypeElement::Type(ty) => {
+                let contained = self.ty(module, &ty.value, ty.id, true);
+                match characters {
+                    true => effective.alphabet = contained.alphabet,
+                    false => effective = contained,
+                }
+            }
+            SubtypeElement::Size(constraint) => {
+                effective.size = self.constraint(module, constraint, &[], false).value;
+            }
+            SubtypeElement::From(constraint) => {
+                effective.alphabet = self.constraint(module, constraint, &[], true).alphabet;
+            }
+            SubtypeElement::Pattern(value) => {
+                if let Some(text) = self.string(module, &value.value) {
+                    effective.patterns.push(text);
+                }
+            }
+            SubtypeElement::Settings(settings) => {
+                effective.settings.push(cstring_value(&settings.value));
+            }
+            SubtypeElement::Component(_) | SubtypeElement::Components(_) => (),
+        }
+        effective
+    }
+
+    /// Interpret the value of an endpoint of a range.  The outer option is
+    /// missing if the value cannot be interpreted, and the inner option is
+    /// missing for `MIN` and `MAX`.  Open endpoints are returned with `true`.
+    fn endpoint<T>(
+        &self,
+        endpoint: &Endpoint,
+        value: impl Fn(&Value) -> Option<T>,
+    ) -> Option<(Option<T>, bool)> {
+        match &endpoint.value {
+            Some(v) => Some((Some(value(&v.value)?), endpoint.open)),
+            None => Some((None, false)),
+        }
+    }
+
+    /// Interpret an integer value, which may be a named number of the
+    /// constrained type, a dummy reference or a reference to another value
+    fn integer(
+        &self,
+        module: &Environment,
+        value: &Value,
+        numbers: &[NamedNumber],
+    ) -> Option<BigInt> {
+        match value {
+            Value::Number(text) => text.parse().ok(),
+            Value::Reference(reference) => {
+                if reference.module.is_none() {
+                    let name = &reference.name.value;
+                    if let Some(number) = numbers.iter().find(|n| &n.name.value == name) {
+                        return self.integer(module, &number.value.value, &[]);
+                    }
+                    if let Some((module, actual)) = self.dummies.get(name) {
+                        let TypeOrValue::Value(value) = &***actual else {
+                            return None;
+                        };
+                        return self.integer(module, value, &[]);
+                    }
+                }
+
+                let (module, _) = self.context.resolve(module, reference)?;
+                module.integers.get(&reference.name.value).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    /// Interpret a character string value, which may be a dummy reference or
+    /// a reference to another value
+    fn string(&self, module: &Environment, value: &Value) -> Option<String> {
+        match value {
+            Value::String(text) => Some(cstring_value(text)),
+            Value::Reference(reference) => {
+                if reference.module.is_none() {
+                    if let Some((module, actual)) = self.dummies.get(&reference.name.value) {
+                        let TypeOrValue::Value(value) = &***actual else {
+                            return None;
+                        };
+                        return self.string(module, value);
+                    }
+                }
+
+                let (module, _) = self.context.resolve(module, reference)?;
+                module.strings.get(&reference.name.value).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    /// Interpret a string value of a single character, the endpoint of a
+    /// range within `FROM`
+    fn character(&self, module: &Environment, value: &Value) -> Option<char> {
+        let text = self.string(module, value)?;
+        let mut chars = text.chars();
+        let c = chars.next()?;
+        chars.next().is_none().then_some(c)
+    }
+}
+
+/// Move an open endpoint to the nearest value within the range
+fn open<T: Discrete>((value, open): (Option<T>, bool), step: fn(&T) -> Option<T>) -> Option<T> {
+    match open {
+        true => value.as_ref().and_then(step),
+        false => value,
+    }
+}
@@ -1,11 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::HashSet,
     ops::{Deref, DerefMut},
 };
 
 use crate::{compiler::SourceId, cst::AsnNodeId, diagnostic::Label, AsnCompiler, Diagnostic};
 
-use super::environment::Environment;
+use super::environment::{Environment, Variable};
 
 /// Data used and produced by static analysis of source files
 #[derive(Debug)]
@@ -15,45 +15,157 @@ pub struct AnalysisContext<'a> {
 
     /// All diagnostics that occurred while running the analysis.
     pub diagnostics: Vec<Diagnostic>,
-
-    /// List of all modules from all source files
-    pub(crate) modules: HashMap<AsnNodeId, Environment>,
 }
 
 impl<'a> AnalysisContext<'a> {
-    /// Create a new, blank, analysis context
+    /// Create a new analysis context, re-analysing anything that changed since
+    /// the last analysis of the compiler's source files.
     pub(crate) fn new(compiler: &'a mut AsnCompiler) -> Self {
-        let errors = std::mem::take(&mut compiler.diagnostics);
-
         let mut this = Self {
             compiler,
-            diagnostics: errors,
-            modules: HashMap::new(),
+            diagnostics: vec![],
         };
 
-        let sources: Vec<_> = this.compiler.all_sources().collect();
+        this.run();
+
+        this.diagnostics = this.cache.diagnostics().cloned().collect();
+
+        this
+    }
 
-        for file in sources {
-            this.add_source(file);
+    /// Run all analysis passes on the modules that require it
+    fn run(&mut self) {
+        let mut changed = HashSet::new();
+        let mut stale = HashSet::new();
+        let mut oids = HashSet::new();
+
+        // files loaded from the include directories are marked as changed, so
+        // keep going until no more files are loaded.
+        loop {
+            let files = self.cache.take_changed();
+            if files.is_empty() {
+                break;
+            }
+
+            for file in files {
+                let old = self.cache.remove_source(file);
+                oids.extend(old.iter().filter_map(|m| m.oid.clone()));
+                let old: HashSet<_> = old.into_iter().map(|m| (m.name, m.source_hash)).collect();
+
+                let new = if self.has_source(file) {
+                    self.add_source(file)
+                } else {
+                    vec![]
+                };
+                stale.extend(new.iter().copied());
+                oids.extend(
+                    new.iter()
+                        .filter_map(|id| self.cache.modules[id].oid.clone()),
+                );
+
+                // modules that have been removed or whose text has changed require
+                // re-analysis of their dependents, modules that are textually
+                // identical only require their own global analysis to be re-run.
+                let new: HashSet<_> = new
+                    .iter()
+                    .map(|id| {
+                        let module = &self.cache.modules[id];
+                        (module.name.clone(), module.source_hash)
+                    })
+                    .collect();
+                changed.extend(old.symmetric_difference(&new).map(|(name, _)| name.clone()));
+            }
+
+            self.discover_imports();
         }
 
-        this.run();
+        stale.extend(self.cache.dependents(&changed));
 
-        this
+        // a module sharing an object identifier with a changed module may have
+        // gained or lost a conflict with it
+        stale.extend(
+            self.modules()
+                .filter(|m| m.oid.as_ref().is_some_and(|oid| oids.contains(oid)))
+                .map(|m| m.node),
+        );
+
+        let mut stale: Vec<_> = stale.into_iter().collect();
+        stale.sort();
+        for module in stale {
+            self.global(module);
+        }
     }
 
-    /// Add a new source file to the context and run local analysis on it.
-    fn add_source(&mut self, file: SourceId) {
-        if let Err(e) = self.local(file) {
+    /// Run local analysis on a source file, adding its modules to the cache.
+    /// Returns the IDs of all modules found in the file.
+    fn add_source(&mut self, file: SourceId) -> Vec<AsnNodeId> {
+        let modules = match self.local(file) {
+            Ok(modules) => modules,
+            Err(e) => {
+                self.diagnostics.push(e);
+                vec![]
+            }
+        };
+
+        let diagnostics = std::mem::take(&mut self.diagnostics);
+        self.cache.local_diagnostics.insert(file, diagnostics);
+
+        let ids = modules.iter().map(|m| m.node).collect();
+        self.cache
+            .modules
+            .extend(modules.into_iter().map(|m| (m.node, m)));
+
+        ids
+    }
+
+    /// Run global analysis of a single module
+    fn global(&mut self, module: AsnNodeId) {
+        #[cfg(test)]
+        {
+            self.cache.global_runs += 1;
+        }
+        if let Err(e) = self.global_module(module) {
             self.diagnostics.push(e);
         }
+
+        let diagnostics = std::mem::take(&mut self.diagnostics);
+        self.cache.global_diagnostics.insert(module, diagnostics);
     }
 
-    /// Run all analysis passes
-    fn run(&mut self) {
-        if let Err(e) = self.global() {
-            self.diagnostics.push(e);
+    /// Get all modules that have been analysed
+    pub(crate) fn modules(&self) -> impl Iterator<Item = &Environment> {
+        self.cache.modules.values()
+    }
+
+    /// Find an analysed module by its name
+    pub(crate) fn module_named(&self, name: &str) -> Option<&Environment> {
+        self.modules().find(|m| m.name == name)
+    }
+
+    /// Find the variable a name refers to from within a module, either
+    /// defined in the module or imported from another module.  Returns the
+    /// variable and the module that defines it.
+    pub(crate) fn lookup<'s>(
+        &'s self,
+        module: &'s Environment,
+        name: &str,
+    ) -> Option<(&'s Environment, &'s Variable)> {
+        // a symbol can be imported from a module that itself imports it, so
+        // follow the imports until the module defining it is found.
+        let mut module = module;
+        for _ in 0..=self.cache.modules.len() {
+            if let Some(var) = module.variables.get(name) {
+                return Some((module, var));
+            }
+
+            let import = module
+                .imports
+                .iter()
+                .find(|import| import.symbols.iter().any(|s| s.value == name))?;
+            module = self.module_named(&import.module)?;
         }
+
+        None
     }
 
     /// Construct a diagnostic label that references a given tree node
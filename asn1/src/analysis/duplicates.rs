@@ -0,0 +1,271 @@
+//! Detection of assignments, identifiers, numbers and modules that are defined
+//! more than once.
+
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{
+        ComponentType, EnumItem, Extensible, ExtensionAddition, NamedNumber, Parameter, Type,
+        Value, WithId,
+    },
+    cst::AsnNodeId,
+    Diagnostic,
+};
+
+use super::{context::AnalysisContext, environment::Environment};
+
+impl AnalysisContext<'_> {
+    /// Report assignments within a module that use the same name as an earlier
+    /// assignment.  The names must be in the order they appear in the source.
+    pub(super) fn duplicate_assignments(&mut self, module: &str, names: &[WithId<String>]) {
+        let mut seen: HashMap<&str, AsnNodeId> = HashMap::new();
+
+        for name in names {
+            let Some(&first) = seen.get(name.as_str()) else {
+                seen.insert(name, name.id);
+                continue;
+            };
+
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::DuplicateAssignment")
+                    .name(format!(
+                        "`{}` is defined more than once in module `{module}`",
+                        **name
+                    ))
+                    .label(self.label(first).message("First defined here"))
+                    .label(self.label(name.id).message("Defined again here")),
+            );
+        }
+    }
+
+    /// Report identifiers and numbers that are used more than once within a
+    /// type, including any types nested within it.
+    pub(super) fn duplicate_identifiers(&mut self, ty: &Type) {
+        match ty {
+            Type::Integer(numbers) => self.duplicate_named_numbers("named number", numbers),
+            Type::BitString(numbers) => self.duplicate_named_numbers("named bit", numbers),
+            Type::Enumerated(items) => {
+                self.unique_names("enumeration item", items.items().map(|item| &item.name));
+                self.unique_numbers("enumeration item", enum_numbers(items));
+            }
+            Type::Sequence(components) | Type::Set(components) => {
+                let names = components.items().filter_map(|component| match component {
+                    ComponentType::Named(component) => Some(&component.name),
+                    ComponentType::ComponentsOf(_) => None,
+                });
+                self.unique_names("component", names);
+
+                for component in components.items() {
+                    self.duplicate_identifiers(component.ty());
+                }
+            }
+            Type::Choice(alternatives) => {
+                self.unique_names("alternative", alternatives.items().map(|a| &a.name));
+
+                for alternative in alternatives.items() {
+                    self.duplicate_identifiers(&alternative.ty);
+                }
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => self.duplicate_identifiers(&of.ty),
+            Type::Tagged(tagged) => self.duplicate_identifiers(&tagged.ty),
+            Type::Selection(_, ty) => self.duplicate_identifiers(ty),
+            Type::Class(class) => {
+                self.unique_names("field", class.fields.iter().map(|field| &field.name));
+            }
+            _ => (),
+        }
+    }
+
+    /// Report dummy references of a parameterized assignment that share a
+    /// name
+    pub(super) fn duplicate_parameters(&mut self, parameters: &[Parameter]) {
+        self.unique_names("parameter", parameters.iter().map(|p| &p.name));
+    }
+
+    /// Report modules that share a name or object identifier with another
+    /// module.  The conflict is reported by the later of the two modules, so
+    /// that it is only reported once.
+    pub(super) fn check_module_conflicts(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let order = |m: &Environment| (m.node.source(), m.node);
+        let mut earlier: Vec<_> = self.modules().filter(|m| order(m) < order(env)).collect();
+        earlier.sort_by_key(|m| order(m));
+
+        // modules with the same name can be told apart by their object
+        // identifiers, if both have one.
+        let same_name = earlier.iter().find(|m| {
+            m.name == env.name && (m.oid.is_none() || env.oid.is_none() || m.oid == env.oid)
+        });
+        let same_oid = earlier
+            .iter()
+            .find(|m| m.name != env.name && env.oid.is_some() && m.oid == env.oid);
+
+        let mut diagnostics = vec![];
+        if let Some(first) = same_name {
+            diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::DuplicateModule")
+                    .name(format!("Module `{}` is defined more than once", env.name))
+                    .label(self.label(first.name_id).message("First defined here"))
+                    .label(self.label(env.name_id).message("Defined again here"))
+                    .label("Modules with the same name must have different object identifiers"),
+            );
+        }
+        if let (Some(first), Some(oid)) = (same_oid, &env.oid) {
+            diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::DuplicateModuleOid")
+                    .name(format!(
+                        "Modules `{}` and `{}` have the same object identifier {oid}",
+                        first.name, env.name
+                    ))
+                    .label(
+                        self.label(first.name_id)
+                            .message(format!("Identified by {oid}")),
+                    )
+                    .label(
+                        self.label(env.name_id)
+                            .message(format!("Also identified by {oid}")),
+                    ),
+            );
+        }
+
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Report named numbers or named bits with the same identifier or number
+    fn duplicate_named_numbers(&mut self, what: &str, numbers: &[NamedNumber]) {
+        self.unique_names(what, numbers.iter().map(|number| &number.name));
+
+        let values = numbers.iter().filter_map(|number| {
+            Some((&number.name, number_value(&number.value)?, number.value.id))
+        });
+        self.unique_numbers(what, values);
+    }
+
+    /// Report identifiers that are the same as an earlier identifier
+    fn unique_names<'n>(
+        &mut self,
+        what: &str,
+        names: impl IntoIterator<Item = &'n WithId<String>>,
+    ) {
+        let mut seen: HashMap<&str, AsnNodeId> = HashMap::new();
+
+        for name in names {
+            let Some(&first) = seen.get(name.as_str()) else {
+                seen.insert(name, name.id);
+                continue;
+            };
+
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::DuplicateIdentifier")
+                    .name(format!("Duplicate {what} identifier `{}`", **name))
+                    .label(self.label(first).message("First used here"))
+                    .label(self.label(name.id).message("Used again here")),
+            );
+        }
+    }
+
+    /// Report identifiers with the same number as an earlier identifier.  Each
+    /// number is given with the node to label it with.
+    fn unique_numbers<'n>(
+        &mut self,
+        what: &str,
+        numbers: impl IntoIterator<Item = (&'n WithId<String>, BigInt, AsnNodeId)>,
+    ) {
+        let mut seen: HashMap<BigInt, (&str, AsnNodeId)> = HashMap::new();
+
+        for (name, number, id) in numbers {
+            let Some(&(first, first_id)) = seen.get(&number) else {
+                seen.insert(number.clone(), (name, id));
+                continue;
+            };
+
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::DuplicateValue")
+                    .name(format!("Duplicate {what} value {number}"))
+                    .label(
+                        self.label(first_id)
+                            .message(format!("`{first}` is {number}")),
+                    )
+                    .label(
+                        self.label(id)
+                            .message(format!("`{}` is also {number}", **name)),
+                    ),
+            );
+        }
+    }
+}
+
+/// Get the number of each enumeration item.  Root items without a number are
+/// given the smallest number not used by another root item, and additions
+/// without a number are one more than the largest number before them.  If any
+/// number is a reference the numbers given to items cannot be known yet, so
+/// only the items with numbers are returned.
+pub(crate) fn enum_numbers(
+    items: &Extensible<EnumItem>,
+) -> Vec<(&WithId<String>, BigInt, AsnNodeId)> {
+    let root: Vec<_> = items.root_items().collect();
+    let additions: Vec<_> = items
+        .extension
+        .iter()
+        .flat_map(|ext| ext.additions.iter().flat_map(ExtensionAddition::items))
+        .collect();
+
+    let explicit =
+        |item: &EnumItem| Some((number_value(item.value.as_ref()?)?, item.value.as_ref()?.id));
+    let complete = root.iter().chain(&additions).all(|item| {
+        item.value
+            .as_ref()
+            .is_none_or(|v| number_value(v).is_some())
+    });
+    if !complete {
+        return root
+            .iter()
+            .chain(&additions)
+            .filter_map(|item| {
+                let (number, id) = explicit(item)?;
+                Some((&item.name, number, id))
+            })
+            .collect();
+    }
+
+    let used: Vec<_> = root
+        .iter()
+        .filter_map(|item| explicit(item))
+        .map(|(n, _)| n)
+        .collect();
+    let mut numbers = vec![];
+    let mut next = BigInt::default();
+    for item in root {
+        let (number, id) = explicit(item).unwrap_or_else(|| {
+            while used.contains(&next) {
+                next += 1;
+            }
+            next += 1;
+            (&next - 1, item.name.id)
+        });
+        numbers.push((&item.name, number, id));
+    }
+
+    for item in additions {
+        let (number, id) = explicit(item).unwrap_or_else(|| {
+            let largest = numbers.iter().map(|(_, n, _)| n).max();
+            (
+                largest.map_or_else(BigInt::default, |n| n + 1),
+                item.name.id,
+            )
+        });
+        numbers.push((&item.name, number, id));
+    }
+
+    numbers
+}
+
+/// Get the number of a value if it is an integer literal
+fn number_value(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Number(text) => text.parse().ok(),
+        _ => None,
+    }
+}
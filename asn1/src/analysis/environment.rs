@@ -1,14 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigInt;
 
 use crate::{
-    ast::{Type, WithId},
+    ast::{
+        Constraint, Extensible, Import, Object, Parameter, TagDefault, Type, TypeOrValue, WithId,
+    },
     cst::AsnNodeId,
 };
 
-// use super::{Iri, Oid};
+use super::{Definition, Iri, Oid, Real, RelativeOid};
 
 /// Local variable resolution environment
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Environment {
     /// ID of the tree node for the whole module
     pub node: AsnNodeId,
@@ -16,13 +20,85 @@ pub struct Environment {
     /// The initially defined module name (hopefully but not necessarily unique)
     pub name: String,
 
-    // /// The object identifier for the module (if present)
-    // pub oid: Option<Oid>,
+    /// Node ID of the module's name in its identifier
+    pub name_id: AsnNodeId,
+
+    /// The object identifier for the module (if present)
+    pub oid: Option<Oid>,
+
+    /// The iri of the module (if present)
+    pub iri: Option<Iri>,
+
+    /// Hash of the source text of the module, used to detect changes to the
+    /// module when its source file is edited.
+    pub source_hash: u64,
+
+    /// How tags are applied to types within the module, explicit if the
+    /// module does not say
+    pub tag_default: TagDefault,
+
+    /// Do the types of the module have an implied extension marker,
+    /// `EXTENSIBILITY IMPLIED`
+    pub extensibility_implied: bool,
+
+    /// Symbols imported from other modules
+    pub imports: Vec<Import>,
 
-    // /// The iri of the module (if present)
-    // pub iri: Option<Iri>,
     /// All variables defined within the module
     pub variables: HashMap<String, Variable>,
+
+    /// The text of the comments documenting assignments and the components,
+    /// alternatives and items of their types, keyed by the node ID of the
+    /// documented name.  Paragraphs are separated by empty lines.
+    pub comments: HashMap<AsnNodeId, String>,
+
+    /// The constraints following each type within the module, keyed by the
+    /// node ID of the constrained type.
+    pub constraints: HashMap<AsnNodeId, Vec<Constraint>>,
+
+    /// Names of assignments that could not be interpreted.  References to
+    /// them are not reported as unresolved.
+    pub invalid: HashSet<String>,
+
+    /// Object identifier values assigned within the module, including OID-IRI
+    /// values that identify a known node.  Set by global analysis once any
+    /// references to other values have been resolved.
+    pub oids: HashMap<String, Oid>,
+
+    /// Relative object identifier values assigned within the module, set by
+    /// global analysis.
+    pub relative_oids: HashMap<String, RelativeOid>,
+
+    /// INTEGER values assigned within the module, set by global analysis.
+    pub integers: HashMap<String, BigInt>,
+
+    /// REAL values assigned within the module, set by global analysis.
+    pub reals: HashMap<String, Real>,
+
+    /// Values of the restricted character string types assigned within the
+    /// module, set by global analysis.
+    pub strings: HashMap<String, String>,
+
+    /// The assignment each reference within the module resolves to, keyed by
+    /// the node ID of the referenced name.  Set by global analysis.
+    pub references: HashMap<AsnNodeId, Definition>,
+
+    /// Each assignment with its `COMPONENTS OF`, selection types and
+    /// information object field references replaced by what they refer to.
+    /// Assignments that could not be expanded are left out.  Set by global
+    /// analysis.
+    pub expanded: HashMap<String, Variable>,
+
+    /// The information objects defined within the module, set by global
+    /// analysis.
+    pub objects: HashMap<String, Object>,
+
+    /// The instances of parameterized types used within the module, keyed by
+    /// the ASN.1 notation of the instance, `Name { Type, value }`.  The
+    /// instantiated type of each is added to the expanded assignments under
+    /// the same name, and expanded references to the instance refer to it by
+    /// that name.  Set by global analysis.
+    pub instances: HashMap<String, Instance>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -31,10 +107,25 @@ pub struct Variable {
     pub id: AsnNodeId,
 
     /// The value assigned to the variable
-    pub value: WithId<Type>,
+    pub value: WithId<TypeOrValue>,
 
     /// The Type associated with the variable, if specified
-    pub ty: Option<()>,
+    pub ty: Option<WithId<Type>>,
+
+    /// The dummy references of a parameterized assignment, empty if the
+    /// assignment is not parameterized.
+    pub parameters: Vec<Parameter>,
+}
+
+/// An instance of a parameterized assignment, created for the actual
+/// parameters used by a reference to it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Instance {
+    /// The parameterized assignment
+    pub definition: Definition,
+
+    /// The actual parameters, with any types expanded
+    pub parameters: Vec<WithId<TypeOrValue>>,
 }
 
 impl Environment {
@@ -43,9 +134,40 @@ impl Environment {
         Environment {
             node,
             name: String::new(),
-            // oid: None,
-            // iri: None,
+            name_id: node,
+            oid: None,
+            iri: None,
+            source_hash: 0,
+            tag_default: TagDefault::Explicit,
+            extensibility_implied: false,
+            imports: vec![],
             variables: HashMap::new(),
+            comments: HashMap::new(),
+            constraints: HashMap::new(),
+            invalid: HashSet::new(),
+            oids: HashMap::new(),
+            relative_oids: HashMap::new(),
+            integers: HashMap::new(),
+            reals: HashMap::new(),
+            strings: HashMap::new(),
+            references: HashMap::new(),
+            expanded: HashMap::new(),
+            objects: HashMap::new(),
+            instances: HashMap::new(),
         }
     }
+
+    /// Are the components or alternatives of a type within the module tagged
+    /// automatically, which is when the module uses automatic tagging and no
+    /// item in the extension root has a tag
+    pub(crate) fn automatic_tags<T>(
+        &self,
+        items: &Extensible<T>,
+        ty: impl Fn(&T) -> &Type,
+    ) -> bool {
+        self.tag_default == TagDefault::Automatic
+            && !items
+                .root_items()
+                .any(|item| matches!(ty(item), Type::Tagged(_)))
+    }
 }
@@ -0,0 +1,851 @@
+//! Expansion of types and values that are defined in terms of parts of other
+//! types: `COMPONENTS OF`, selection types and references to the fields of
+//! information object classes and information objects, and instantiation of
+//! parameterized types.
+
+mod instantiate;
+
+use std::collections::HashMap;
+
+use crate::{
+    ast::{
+        ComponentType, Extensible, Extension, ExtensionAddition, ExtensionGroup, FieldKind,
+        FieldReference, FieldSource, FieldSpec, ObjIdComponent, Object, ObjectClass, Reference,
+        Setting, Type, TypeOrValue, Value, WithId,
+    },
+    cst::AsnNodeId,
+    util::closest,
+    Diagnostic,
+};
+
+use super::{
+    context::AnalysisContext,
+    environment::{Environment, Instance, Variable},
+};
+
+/// Expands the assignments of a single module
+struct Expander<'a, 'b> {
+    /// Context to find referenced modules and assignments in
+    context: &'a AnalysisContext<'b>,
+
+    /// The module being expanded, which instances of parameterized types are
+    /// created in
+    module: &'a Environment,
+
+    /// Type assignments currently being expanded, starting with the
+    /// assignment of the module being expanded.  Used to find types that
+    /// include themselves.
+    stack: Vec<AsnNodeId>,
+
+    /// Errors found while expanding the module
+    diagnostics: Vec<Diagnostic>,
+
+    /// Instances of parameterized types created for the module, keyed by
+    /// name, with the instantiated type once it has been expanded.
+    instances: HashMap<String, (Instance, Option<Variable>)>,
+
+    /// The parameterized assignments and names of the instances currently
+    /// being created, innermost last.
+    instantiating: Vec<(AsnNodeId, String)>,
+}
+
+impl AnalysisContext<'_> {
+    /// Expand the `COMPONENTS OF`, selection types and information object
+    /// field references of every assignment in a module, and interpret the
+    /// information objects it defines.
+    pub(super) fn expand_assignments(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let mut expander = Expander {
+            context: self,
+            module: env,
+            stack: vec![],
+            diagnostics: vec![],
+            instances: HashMap::new(),
+            instantiating: vec![],
+        };
+        let mut expanded = HashMap::new();
+        let mut objects = HashMap::new();
+
+        let mut names: Vec<_> = env.variables.keys().collect();
+        names.sort();
+        for name in names {
+            let var = &env.variables[name];
+            expander.stack = vec![var.id];
+
+            // parameterized assignments are only expanded once instantiated
+            if !var.parameters.is_empty() {
+                continue;
+            }
+
+            let object = matches!(&*var.value, TypeOrValue::Value(_))
+                && var
+                    .ty
+                    .as_ref()
+                    .is_some_and(|ty| expander.class(env, ty).is_some());
+            if object {
+                if let Some((_, object, _)) = expander.object(env, var, true) {
+                    expander.object_references(env, &object);
+                    objects.insert(name.clone(), object);
+                }
+                continue;
+            }
+
+            let value = match &*var.value {
+                TypeOrValue::Type(ty) => expander.ty(env, ty).map(TypeOrValue::Type),
+                TypeOrValue::Value(value) => expander.value(env, value).map(TypeOrValue::Value),
+            };
+            let ty = match &var.ty {
+                Some(ty) => expander.with_id(env, ty).map(Some),
+                None => Some(None),
+            };
+
+            if let (Some(value), Some(ty)) = (value, ty) {
+                let value = WithId {
+                    value,
+                    id: var.value.id,
+                };
+                expanded.insert(
+                    name.clone(),
+                    Variable {
+                        id: var.id,
+                        value,
+                        ty,
+                        parameters: vec![],
+                    },
+                );
+            }
+        }
+
+        let Expander {
+            diagnostics,
+            instances: created,
+            ..
+        } = expander;
+
+        let mut instances = HashMap::new();
+        for (name, (instance, var)) in created {
+            if let Some(var) = var {
+                expanded.insert(name.clone(), var);
+            }
+            instances.insert(name, instance);
+        }
+
+        self.diagnostics.extend(diagnostics);
+        if let Some(env) = self.cache.modules.get_mut(&module) {
+            env.expanded = expanded;
+            env.objects = objects;
+            env.instances = instances;
+        }
+    }
+
+    /// Find the information object class a type refers to, if any, with the
+    /// module the class is defined in.
+    pub(super) fn class<'s>(
+        &'s self,
+        module: &'s Environment,
+        ty: &Type,
+    ) -> Option<(&'s Environment, ObjectClass)> {
+        let mut module = module;
+        let mut ty = ty.clone();
+        let mut visited = vec![];
+
+        loop {
+            match ty {
+                Type::Class(class) => return Some((module, class)),
+                Type::Reference(reference) => {
+                    let (target_module, var) = self.resolve(module, &reference)?;
+                    let TypeOrValue::Type(target) = &*var.value else {
+                        return None;
+                    };
+                    if visited.contains(&var.id) {
+                        return None;
+                    }
+                    visited.push(var.id);
+
+                    module = target_module;
+                    ty = target.clone();
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+impl<'a> Expander<'a, '_> {
+    /// Expand a type and keep the ID of the original
+    fn with_id(&mut self, module: &'a Environment, ty: &WithId<Type>) -> Option<WithId<Type>> {
+        Some(WithId {
+            value: self.ty(module, ty)?,
+            id: ty.id,
+        })
+    }
+
+    /// Expand a type and any types nested within it.  Returns `None` if the
+    /// type cannot be expanded, either due to an error that has been reported
+    /// or an unresolved reference that is reported separately.
+    fn ty(&mut self, module: &'a Environment, ty: &Type) -> Option<Type> {
+        Some(match ty {
+            Type::Sequence(components) => {
+                Type::Sequence(self.components(module, components, false)?)
+            }
+            Type::Set(components) => Type::Set(self.components(module, components, true)?),
+            Type::Choice(alternatives) => {
+                let mut alternatives = alternatives.clone();
+                for alternative in alternatives.items_mut() {
+                    alternative.ty = self.with_id(module, &alternative.ty)?;
+                }
+                Type::Choice(alternatives)
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                let mut of = of.clone();
+                of.ty = self.with_id(module, &of.ty)?;
+                match ty {
+                    Type::SequenceOf(_) => Type::SequenceOf(of),
+                    _ => Type::SetOf(of),
+                }
+            }
+            Type::Tagged(tagged) => {
+                let mut tagged = tagged.clone();
+                tagged.ty = self.with_id(module, &tagged.ty)?;
+                Type::Tagged(tagged)
+            }
+            Type::Selection(name, ty) => self.selection(module, name, ty)?,
+            Type::Field(field) => self.field_type(module, field)?,
+            Type::Parameterized(parameterized) => self.parameterized(module, parameterized)?,
+            ty => ty.clone(),
+        })
+    }
+
+    /// Expand a value, replacing a reference to a field of an object with the
+    /// setting of the field.
+    fn value(&mut self, module: &'a Environment, value: &Value) -> Option<Value> {
+        let Value::Field(field) = value else {
+            return Some(value.clone());
+        };
+        let FieldSource::Object(reference) = &field.source else {
+            return Some(value.clone());
+        };
+
+        let (object_module, object, _) = self.field_object(module, reference, &field.field)?;
+        match object.settings.get(&field.field.value) {
+            Some(Setting::Value(setting)) => {
+                Some(self.qualify_value(object_module, module, setting.value.clone()))
+            }
+            Some(_) => {
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::FieldKind")
+                        .name(format!(
+                            "Field `{}` of object `{}` is not a value",
+                            *field.field, *reference.name
+                        ))
+                        .label(
+                            self.context
+                                .label(field.field.id)
+                                .message("Used as a value here"),
+                        ),
+                );
+                None
+            }
+            None => {
+                self.missing_setting(reference, &field.field);
+                None
+            }
+        }
+    }
+
+    /// Expand the components of a sequence or set type, including the
+    /// components of any `COMPONENTS OF` types.
+    fn components(
+        &mut self,
+        module: &'a Environment,
+        components: &Extensible<ComponentType>,
+        set: bool,
+    ) -> Option<Extensible<ComponentType>> {
+        let mut included = HashMap::new();
+
+        let root = self.component_list(module, &components.root, set, &mut included)?;
+        let extension = match &components.extension {
+            Some(extension) => {
+                let mut additions = vec![];
+                for addition in &extension.additions {
+                    match addition {
+                        ExtensionAddition::Single(item) => {
+                            let items = std::slice::from_ref(item);
+                            let items = self.component_list(module, items, set, &mut included)?;
+                            additions.extend(items.into_iter().map(ExtensionAddition::Single));
+                        }
+                        ExtensionAddition::Group(group) => {
+                            let items =
+                                self.component_list(module, &group.items, set, &mut included)?;
+                            additions.push(ExtensionAddition::Group(ExtensionGroup {
+                                version: group.version.clone(),
+                                items,
+                            }));
+                        }
+                    }
+                }
+
+                Some(Extension {
+                    id: extension.id,
+                    additions,
+                    root: self.component_list(module, &extension.root, set, &mut included)?,
+                })
+            }
+            None => None,
+        };
+        let components = Extensible { root, extension };
+
+        // identifiers of the original components are checked by local
+        // analysis, so only conflicts with included components are reported.
+        let mut seen: HashMap<&str, AsnNodeId> = HashMap::new();
+        for component in components.items() {
+            let ComponentType::Named(component) = component else {
+                continue;
+            };
+            let name = &component.name;
+            let Some(&first) = seen.get(name.as_str()) else {
+                seen.insert(name, name.id);
+                continue;
+            };
+            if !included.contains_key(&first) && !included.contains_key(&name.id) {
+                continue;
+            }
+
+            let mut diagnostic = Diagnostic::error("Asn1::Analysis::DuplicateIdentifier")
+                .name(format!("Duplicate component identifier `{}`", **name))
+                .label(self.context.label(first).message("First used here"))
+                .label(self.context.label(name.id).message("Used again here"));
+            for id in [first, name.id] {
+                if let Some(&components_of) = included.get(&id) {
+                    diagnostic = diagnostic.label(
+                        self.context
+                            .label(components_of)
+                            .message("Included by `COMPONENTS OF` here"),
+                    );
+                }
+            }
+            self.diagnostics.push(diagnostic);
+        }
+
+        Some(components)
+    }
+
+    /// Expand a list of components.  The identifiers of components included
+    /// by `COMPONENTS OF` are recorded with the node that included them.
+    fn component_list(
+        &mut self,
+        module: &'a Environment,
+        items: &[ComponentType],
+        set: bool,
+        included: &mut HashMap<AsnNodeId, AsnNodeId>,
+    ) -> Option<Vec<ComponentType>> {
+        let mut components = vec![];
+
+        for item in items {
+            match item {
+                ComponentType::Named(component) => {
+                    let mut component = component.clone();
+                    component.ty = self.with_id(module, &component.ty)?;
+                    components.push(ComponentType::Named(component));
+                }
+                ComponentType::ComponentsOf(ty) => {
+                    for component in self.components_of(module, ty, set)? {
+                        if let ComponentType::Named(named) = &component {
+                            included.insert(named.name.id, ty.id);
+                        }
+                        components.push(component);
+                    }
+                }
+            }
+        }
+
+        Some(components)
+    }
+
+    /// Get the components included by `COMPONENTS OF Type`, which are the
+    /// components in the extension root of the type.
+    fn components_of(
+        &mut self,
+        module: &'a Environment,
+        ty: &WithId<Type>,
+        set: bool,
+    ) -> Option<Vec<ComponentType>> {
+        let (target_module, target) = self.target(module, ty)?;
+
+        let components = match (&target, set) {
+            (Type::Sequence(components), false) | (Type::Set(components), true) => components,
+            _ => {
+                let kind = if set { "SET" } else { "SEQUENCE" };
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::ComponentsOf")
+                        .name(format!(
+                            "`COMPONENTS OF` within a {kind} type requires a {kind} type"
+                        ))
+                        .label(
+                            self.context
+                                .label(ty.id)
+                                .message(format!("This is not a {kind} type")),
+                        ),
+                );
+                return None;
+            }
+        };
+
+        Some(
+            components
+                .root_items()
+                .map(|component| {
+                    let mut component = component.clone();
+                    if let ComponentType::Named(named) = &mut component {
+                        let ty = std::mem::replace(&mut named.ty.value, Type::Null);
+                        named.ty.value = self.qualify(target_module, module, ty);
+                        if let Some(default) = &mut named.default {
+                            let value = std::mem::replace(&mut default.value, Value::Unparsed);
+                            default.value = self.qualify_value(target_module, module, value);
+                        }
+                    }
+                    component
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the type of the alternative chosen by a selection type
+    fn selection(
+        &mut self,
+        module: &'a Environment,
+        name: &WithId<String>,
+        ty: &WithId<Type>,
+    ) -> Option<Type> {
+        let (target_module, target) = self.target(module, ty)?;
+
+        let Type::Choice(alternatives) = &target else {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::Selection")
+                    .name("Selection types require a CHOICE type")
+                    .label(
+                        self.context
+                            .label(ty.id)
+                            .message("This is not a CHOICE type"),
+                    ),
+            );
+            return None;
+        };
+
+        let Some(alternative) = alternatives.items().find(|a| a.name.value == name.value) else {
+            let mut diagnostic = Diagnostic::error("Asn1::Analysis::Selection")
+                .name(format!("The CHOICE type has no alternative `{}`", **name))
+                .label(self.context.label(name.id).message("Selected here"))
+                .label(self.context.label(ty.id).message("CHOICE type used here"));
+            let candidates = alternatives.items().map(|a| a.name.as_str());
+            if let Some(suggestion) = closest(name, candidates) {
+                diagnostic = diagnostic.label(format!("Did you mean `{suggestion}`?"));
+            }
+            self.diagnostics.push(diagnostic);
+            return None;
+        };
+
+        Some(self.qualify(target_module, module, alternative.ty.value.clone()))
+    }
+
+    /// Get the type of a field of a class or object.  Type fields of a class
+    /// are open types and are kept as field references.
+    fn field_type(&mut self, module: &'a Environment, field: &FieldReference) -> Option<Type> {
+        match &field.source {
+            FieldSource::Class(class) => {
+                let Some((class_module, class_def)) = self.class(module, class) else {
+                    let resolved = match &class.value {
+                        Type::Reference(reference) => {
+                            self.context.resolve(module, reference).is_some()
+                        }
+                        _ => true,
+                    };
+                    if resolved {
+                        self.diagnostics.push(
+                            Diagnostic::error("Asn1::Analysis::FieldKind")
+                                .name("Only information object classes and objects have fields")
+                                .label(
+                                    self.context
+                                        .label(class.id)
+                                        .message("Not an information object class"),
+                                ),
+                        );
+                    }
+                    return None;
+                };
+
+                let spec = self.field_spec(&class_def, &field.field)?;
+                match &spec.kind {
+                    FieldKind::Value { ty, .. } | FieldKind::ValueSet(ty) => {
+                        Some(self.qualify(class_module, module, ty.value.clone()))
+                    }
+                    _ => Some(Type::Field(Box::new(field.clone()))),
+                }
+            }
+            FieldSource::Object(reference) => {
+                let (object_module, object, _) =
+                    self.field_object(module, reference, &field.field)?;
+                match object.settings.get(&field.field.value) {
+                    Some(Setting::Type(ty)) => {
+                        Some(self.qualify(object_module, module, ty.value.clone()))
+                    }
+                    Some(_) => {
+                        self.diagnostics.push(
+                            Diagnostic::error("Asn1::Analysis::FieldKind")
+                                .name(format!(
+                                    "Field `{}` of object `{}` is not a type",
+                                    *field.field, *reference.name
+                                ))
+                                .label(
+                                    self.context
+                                        .label(field.field.id)
+                                        .message("Used as a type here"),
+                                ),
+                        );
+                        None
+                    }
+                    None => {
+                        self.missing_setting(reference, &field.field);
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find the object a field reference refers to, and check that its class
+    /// has the field.
+    fn field_object(
+        &mut self,
+        module: &'a Environment,
+        reference: &Reference,
+        field: &WithId<String>,
+    ) -> Option<(&'a Environment, Object, ObjectClass)> {
+        let (object_module, var) = self.context.resolve(module, reference)?;
+        let Some((object_module, object, class)) = self.object(object_module, var, false) else {
+            if matches!(&*var.value, TypeOrValue::Value(_))
+                && var
+                    .ty
+                    .as_ref()
+                    .is_some_and(|ty| self.class(object_module, ty).is_none())
+            {
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::FieldKind")
+                        .name(format!(
+                            "`{}` is not an information object",
+                            *reference.name
+                        ))
+                        .label(
+                            self.context
+                                .label(reference.name.id)
+                                .message("Only information objects have fields"),
+                        ),
+                );
+            }
+            return None;
+        };
+
+        self.field_spec(&class, field)?;
+        Some((object_module, object, class))
+    }
+
+    /// Find the definition of a field of a class, reporting it if the class
+    /// does not have the field.
+    fn field_spec(&mut self, class: &ObjectClass, field: &WithId<String>) -> Option<FieldSpec> {
+        if let Some(spec) = class
+            .fields
+            .iter()
+            .find(|spec| spec.name.value == field.value)
+        {
+            return Some(spec.clone());
+        }
+
+        let mut diagnostic = Diagnostic::error("Asn1::Analysis::UnknownField")
+            .name(format!("The class has no field `{}`", **field))
+            .label(self.context.label(field.id).message("Referenced here"));
+        let candidates = class.fields.iter().map(|spec| spec.name.as_str());
+        if let Some(suggestion) = closest(field, candidates) {
+            diagnostic = diagnostic.label(format!("Did you mean `{suggestion}`?"));
+        }
+        self.diagnostics.push(diagnostic);
+        None
+    }
+
+    /// Report a reference to a field that an object does not define
+    fn missing_setting(&mut self, object: &Reference, field: &WithId<String>) {
+        self.diagnostics.push(
+            Diagnostic::error("Asn1::Analysis::FieldKind")
+                .name(format!(
+                    "Object `{}` does not define field `{}`",
+                    *object.name, **field
+                ))
+                .label(self.context.label(field.id).message("Referenced here"))
+                .label("The field is OPTIONAL in the class of the object"),
+        );
+    }
+
+    /// Find the information object class a type refers to, if any
+    fn class(&self, module: &'a Environment, ty: &Type) -> Option<(&'a Environment, ObjectClass)> {
+        self.context.class(module, ty)
+    }
+
+    /// Interpret a value assignment as an information object, if its type is
+    /// an information object class.  References to other objects are
+    /// followed.  Errors in the definition of the object are only reported if
+    /// requested, so that they are reported once by the module defining it.
+    fn object(
+        &mut self,
+        module: &'a Environment,
+        var: &'a Variable,
+        report: bool,
+    ) -> Option<(&'a Environment, Object, ObjectClass)> {
+        let mut current = (module, var);
+        let mut visited = vec![];
+
+        loop {
+            let (module, var) = current;
+            let (_, class) = self.class(module, var.ty.as_ref()?)?;
+            match &*var.value {
+                TypeOrValue::Value(Value::Reference(reference)) => {
+                    if visited.contains(&var.id) {
+                        return None;
+                    }
+                    visited.push(var.id);
+                    current = self.context.resolve(module, reference)?;
+                }
+                TypeOrValue::Value(_) => {
+                    return match self.context.object(&class, var.value.id) {
+                        Ok(object) => Some((module, object, class)),
+                        Err(e) => {
+                            if report {
+                                self.diagnostics.push(e);
+                            }
+                            None
+                        }
+                    };
+                }
+                TypeOrValue::Type(_) => return None,
+            }
+        }
+    }
+
+    /// Report type references in the settings of an object that cannot be
+    /// resolved
+    fn object_references(&mut self, module: &'a Environment, object: &Object) {
+        let mut settings: Vec<_> = object.settings.values().collect();
+        settings.sort_by_key(|setting| match setting {
+            Setting::Type(ty) => ty.id,
+            Setting::Value(value) => value.id,
+            Setting::Unparsed(id) => *id,
+        });
+
+        for setting in settings {
+            let Setting::Type(ty) = setting else {
+                continue;
+            };
+            let Type::Reference(reference) = &ty.value else {
+                continue;
+            };
+            if self.context.resolve(module, reference).is_some()
+                || module.invalid.contains(&reference.name.value)
+            {
+                continue;
+            }
+
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::UnresolvedReference")
+                    .name(format!("Unable to find type `{}`", *reference.name))
+                    .label(
+                        self.context
+                            .label(reference.name.id)
+                            .message("Referenced here"),
+                    ),
+            );
+        }
+    }
+
+    /// Find the type a type refers to, following references and tags, with
+    /// the module the type is defined in.
+    fn target(
+        &mut self,
+        module: &'a Environment,
+        ty: &WithId<Type>,
+    ) -> Option<(&'a Environment, Type)> {
+        let mut module = module;
+        let mut ty = self.ty(module, ty)?;
+        let mut visited = vec![];
+
+        loop {
+            ty = match ty {
+                Type::Tagged(tagged) => tagged.ty.value,
+                Type::Reference(reference) if self.is_instance(&reference) => {
+                    module = self.module;
+                    self.instance_type(&reference)?
+                }
+                Type::Reference(reference) => {
+                    let (target_module, var) = self.context.resolve(module, &reference)?;
+                    let TypeOrValue::Type(target) = &*var.value else {
+                        return None;
+                    };
+
+                    if self.stack.contains(&var.id) {
+                        // recursion through other assignments is reported by
+                        // those assignments
+                        if self.stack[0] == var.id {
+                            self.recursive(&reference, var);
+                        }
+                        return None;
+                    }
+                    // reference cycles are reported as types with no values
+                    if visited.contains(&var.id) {
+                        return None;
+                    }
+                    visited.push(var.id);
+
+                    self.stack.push(var.id);
+                    let expanded = self.ty(target_module, target);
+                    self.stack.pop();
+
+                    module = target_module;
+                    expanded?
+                }
+                ty => return Some((module, ty)),
+            };
+        }
+    }
+
+    /// Report a type that includes itself through `COMPONENTS OF`, a
+    /// selection type or a field reference
+    fn recursive(&mut self, reference: &Reference, var: &Variable) {
+        self.diagnostics.push(
+            Diagnostic::error("Asn1::Analysis::RecursiveType")
+                .name(format!(
+                    "Type `{}` is expanded in terms of itself",
+                    *reference.name
+                ))
+                .label(self.context.label(var.id).message("Type defined here"))
+                .label(
+                    self.context
+                        .label(reference.name.id)
+                        .message("Expanded again here"),
+                )
+                .label("`COMPONENTS OF` and selection types cannot refer back to the type containing them"),
+        );
+    }
+
+    /// Make the references within a type from one module refer to the same
+    /// assignments when the type is used in another module.
+    fn qualify(&self, from: &Environment, to: &Environment, mut ty: Type) -> Type {
+        if from.node != to.node {
+            self.qualify_type(from, &mut ty);
+        }
+        ty
+    }
+
+    /// Make the references within a value from one module refer to the same
+    /// assignments when the value is used in another module.
+    fn qualify_value(&self, from: &Environment, to: &Environment, mut value: Value) -> Value {
+        if from.node != to.node {
+            self.qualify_value_in(from, &mut value);
+        }
+        value
+    }
+
+    /// Add the defining module to every reference within a type
+    fn qualify_type(&self, from: &Environment, ty: &mut Type) {
+        match ty {
+            Type::Integer(numbers) | Type::BitString(numbers) => {
+                for number in numbers {
+                    self.qualify_value_in(from, &mut number.value.value);
+                }
+            }
+            Type::Enumerated(items) => {
+                for item in items.items_mut() {
+                    if let Some(value) = &mut item.value {
+                        self.qualify_value_in(from, &mut value.value);
+                    }
+                }
+            }
+            Type::Sequence(components) | Type::Set(components) => {
+                for component in components.items_mut() {
+                    match component {
+                        ComponentType::Named(component) => {
+                            self.qualify_type(from, &mut component.ty.value);
+                            if let Some(default) = &mut component.default {
+                                self.qualify_value_in(from, &mut default.value);
+                            }
+                        }
+                        ComponentType::ComponentsOf(ty) => self.qualify_type(from, &mut ty.value),
+                    }
+                }
+            }
+            Type::Choice(alternatives) => {
+                for alternative in alternatives.items_mut() {
+                    self.qualify_type(from, &mut alternative.ty.value);
+                }
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => self.qualify_type(from, &mut of.ty.value),
+            Type::Tagged(tagged) => {
+                self.qualify_value_in(from, &mut tagged.number.value);
+                self.qualify_type(from, &mut tagged.ty.value);
+            }
+            Type::Selection(_, ty) => self.qualify_type(from, &mut ty.value),
+            Type::Reference(reference) => self.qualify_reference(from, reference),
+            Type::Field(field) => match &mut field.source {
+                FieldSource::Class(class) => self.qualify_type(from, &mut class.value),
+                FieldSource::Object(reference) => self.qualify_reference(from, reference),
+            },
+            Type::Parameterized(parameterized) => {
+                self.qualify_reference(from, &mut parameterized.reference);
+                for parameter in &mut parameterized.parameters {
+                    match &mut parameter.value {
+                        TypeOrValue::Type(ty) => self.qualify_type(from, ty),
+                        TypeOrValue::Value(value) => self.qualify_value_in(from, value),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Add the defining module to every reference within a value
+    fn qualify_value_in(&self, from: &Environment, value: &mut Value) {
+        match value {
+            Value::Reference(reference) => self.qualify_reference(from, reference),
+            Value::Field(field) => {
+                if let FieldSource::Object(reference) = &mut field.source {
+                    self.qualify_reference(from, reference);
+                }
+            }
+            Value::ObjectIdentifier(components) => {
+                for component in components {
+                    let ObjIdComponent::Name(name) = component else {
+                        continue;
+                    };
+                    let mut reference = Reference {
+                        module: None,
+                        name: name.clone(),
+                    };
+                    self.qualify_reference(from, &mut reference);
+                    if let Some(module) = reference.module {
+                        *component = ObjIdComponent::External(module, reference.name);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Add the defining module to a reference that does not name a module
+    fn qualify_reference(&self, from: &Environment, reference: &mut Reference) {
+        if reference.module.is_some() {
+            return;
+        }
+        if let Some((module, _)) = self.context.resolve(from, reference) {
+            reference.module = Some(WithId {
+                value: module.name.clone(),
+                id: reference.name.id,
+            });
+        }
+    }
+}
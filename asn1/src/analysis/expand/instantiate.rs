@@ -0,0 +1,371 @@
+//! Instantiation of parameterized types with the actual parameters of each
+//! reference to them.
+
+use std::collections::HashMap;
+
+use crate::{
+    analysis::{environment::Environment, Definition},
+    ast::{
+        ComponentType, FieldReference, FieldSource, ParameterizedReference, Reference, Type,
+        TypeOrValue, Value, WithId,
+    },
+    Diagnostic,
+};
+
+use super::{Expander, Instance, Variable};
+
+/// The number of nested instances of the same parameterized assignment that
+/// are created before the instantiation is taken to never end
+const MAX_DEPTH: usize = 16;
+
+/// The actual parameters of an instance, keyed by the names of the dummy
+/// references they replace
+type Dummies<'d> = HashMap<&'d str, &'d WithId<TypeOrValue>>;
+
+impl<'a> Expander<'a, '_> {
+    /// Instantiate a parameterized type with the actual parameters of a
+    /// reference to it.  Each distinct instance is created once per module,
+    /// and the expanded type refers to the instance by its name.
+    pub(super) fn parameterized(
+        &mut self,
+        module: &'a Environment,
+        parameterized: &ParameterizedReference,
+    ) -> Option<Type> {
+        // instances are created in the module being expanded, which may not be
+        // the module the reference was written in.
+        let mut parameterized = parameterized.clone();
+        if module.node != self.module.node {
+            let mut ty = Type::Parameterized(Box::new(parameterized));
+            self.qualify_type(module, &mut ty);
+            let Type::Parameterized(qualified) = ty else {
+                return None;
+            };
+            parameterized = *qualified;
+        }
+        let module = self.module;
+
+        let reference = &parameterized.reference;
+        let (definition_module, var) = self.context.resolve(module, reference)?;
+        let TypeOrValue::Type(body) = &*var.value else {
+            return None;
+        };
+        // the kinds and number of parameters are checked when resolving
+        if var.parameters.is_empty() || var.parameters.len() != parameterized.parameters.len() {
+            return None;
+        }
+
+        let mut parameters = vec![];
+        for parameter in &parameterized.parameters {
+            let value = match &parameter.value {
+                TypeOrValue::Type(ty) => TypeOrValue::Type(self.ty(module, ty)?),
+                TypeOrValue::Value(value) => TypeOrValue::Value(self.value(module, value)?),
+            };
+            parameters.push(WithId {
+                value,
+                id: parameter.id,
+            });
+        }
+
+        // the name is the same however the assignment is referenced
+        let qualified = Reference {
+            module: (definition_module.node != module.node).then(|| WithId {
+                value: definition_module.name.clone(),
+                id: reference.name.id,
+            }),
+            name: reference.name.clone(),
+        };
+        let name = self.instance_name(&qualified, &parameters);
+        let instance = Type::Reference(Reference {
+            module: None,
+            name: WithId {
+                value: name.clone(),
+                id: reference.name.id,
+            },
+        });
+        if let Some((_, expanded)) = self.instances.get(&name) {
+            // instances that failed to expand have been reported already
+            if expanded.is_some() || self.is_instantiating(&name) {
+                return Some(instance);
+            }
+            return None;
+        }
+
+        let depth = self
+            .instantiating
+            .iter()
+            .filter(|(id, _)| *id == var.id)
+            .count();
+        if depth >= MAX_DEPTH {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::RecursiveInstantiation")
+                    .name(format!("Instantiating `{}` never ends", *reference.name))
+                    .label(
+                        self.context
+                            .label(var.id)
+                            .message("Parameterized type defined here"),
+                    )
+                    .label(
+                        self.context
+                            .label(reference.name.id)
+                            .message("Instantiated again with different parameters here"),
+                    )
+                    .label("Each instance requires another instance with different parameters"),
+            );
+            return None;
+        }
+
+        let dummies: Dummies = var
+            .parameters
+            .iter()
+            .map(|dummy| dummy.name.as_str())
+            .zip(&parameters)
+            .collect();
+        let mut ty = WithId {
+            value: body.clone(),
+            id: var.value.id,
+        };
+        self.substitute_type(definition_module, &dummies, &mut ty);
+
+        let definition = Definition {
+            module: definition_module.name.clone(),
+            name: reference.name.value.clone(),
+            node: var.id,
+        };
+        self.instances.insert(
+            name.clone(),
+            (
+                Instance {
+                    definition,
+                    parameters,
+                },
+                None,
+            ),
+        );
+
+        self.instantiating.push((var.id, name.clone()));
+        let expanded = self.with_id(module, &ty);
+        self.instantiating.pop();
+
+        let expanded = expanded?;
+        if let Some((_, var)) = self.instances.get_mut(&name) {
+            *var = Some(Variable {
+                id: reference.name.id,
+                value: WithId {
+                    value: TypeOrValue::Type(expanded.value),
+                    id: expanded.id,
+                },
+                ty: None,
+                parameters: vec![],
+            });
+        }
+
+        Some(instance)
+    }
+
+    /// Is a reference within an expanded type a reference to an instance of a
+    /// parameterized type
+    pub(super) fn is_instance(&self, reference: &Reference) -> bool {
+        reference.module.is_none() && self.instances.contains_key(&reference.name.value)
+    }
+
+    /// Get the expanded type of an instance of a parameterized type.  An
+    /// instance that is still being created includes itself, such as by
+    /// `COMPONENTS OF`, which is reported.
+    pub(super) fn instance_type(&mut self, reference: &Reference) -> Option<Type> {
+        let (_, var) = self.instances.get(&reference.name.value)?;
+        if let Some(var) = var {
+            let TypeOrValue::Type(ty) = &var.value.value else {
+                return None;
+            };
+            return Some(ty.clone());
+        }
+
+        if self.is_instantiating(&reference.name) {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::RecursiveType")
+                    .name(format!(
+                        "Type `{}` is expanded in terms of itself",
+                        *reference.name
+                    ))
+                    .label(
+                        self.context
+                            .label(reference.name.id)
+                            .message("Expanded again here"),
+                    )
+                    .label("`COMPONENTS OF` and selection types cannot refer back to the type containing them"),
+            );
+        }
+        None
+    }
+
+    /// Is an instance currently being created
+    fn is_instantiating(&self, name: &str) -> bool {
+        self.instantiating.iter().any(|(_, n)| n == name)
+    }
+
+    /// Get the name of an instance, the ASN.1 notation of the reference with
+    /// its actual parameters.  Values that are not interpreted are named by
+    /// their source text.
+    fn instance_name(&self, reference: &Reference, parameters: &[WithId<TypeOrValue>]) -> String {
+        let parameters: Vec<_> = parameters
+            .iter()
+            .map(|parameter| match &parameter.value {
+                TypeOrValue::Value(Value::Unparsed) => {
+                    let location = self.context.label(parameter.id).location;
+                    let text = match location {
+                        Some(location) => {
+                            &self.context.source(parameter.id.source()).source[location]
+                        }
+                        None => "{ ... }",
+                    };
+                    text.split_whitespace().collect::<Vec<_>>().join(" ")
+                }
+                value => value.to_string(),
+            })
+            .collect();
+
+        format!("{reference} {{ {} }}", parameters.join(", "))
+    }
+
+    /// Replace the dummy references within a type from a parameterized
+    /// assignment with the actual parameters.  The actual parameters are
+    /// already in the module being expanded, other references are qualified
+    /// if the assignment is defined in another module.
+    fn substitute_type(&self, from: &Environment, dummies: &Dummies, ty: &mut WithId<Type>) {
+        match &mut ty.value {
+            Type::Reference(reference) => {
+                if let Some(actual) = dummy(reference, dummies) {
+                    if let TypeOrValue::Type(actual_ty) = &actual.value {
+                        *ty = WithId {
+                            value: actual_ty.clone(),
+                            id: actual.id,
+                        };
+                    }
+                    return;
+                }
+                self.qualify_from(from, reference);
+            }
+            Type::Integer(numbers) | Type::BitString(numbers) => {
+                for number in numbers {
+                    self.substitute_value(from, dummies, &mut number.value);
+                }
+            }
+            Type::Enumerated(items) => {
+                for item in items.items_mut() {
+                    if let Some(value) = &mut item.value {
+                        self.substitute_value(from, dummies, value);
+                    }
+                }
+            }
+            Type::Sequence(components) | Type::Set(components) => {
+                for component in components.items_mut() {
+                    match component {
+                        ComponentType::Named(component) => {
+                            self.substitute_type(from, dummies, &mut component.ty);
+                            if let Some(default) = &mut component.default {
+                                self.substitute_value(from, dummies, default);
+                            }
+                        }
+                        ComponentType::ComponentsOf(ty) => self.substitute_type(from, dummies, ty),
+                    }
+                }
+            }
+            Type::Choice(alternatives) => {
+                for alternative in alternatives.items_mut() {
+                    self.substitute_type(from, dummies, &mut alternative.ty);
+                }
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                self.substitute_type(from, dummies, &mut of.ty)
+            }
+            Type::Tagged(tagged) => {
+                self.substitute_value(from, dummies, &mut tagged.number);
+                self.substitute_type(from, dummies, &mut tagged.ty);
+            }
+            Type::Selection(_, ty) => self.substitute_type(from, dummies, ty),
+            Type::Field(field) => self.substitute_field(from, dummies, field),
+            Type::Parameterized(parameterized) => {
+                self.qualify_from(from, &mut parameterized.reference);
+                for parameter in &mut parameterized.parameters {
+                    match &mut parameter.value {
+                        TypeOrValue::Type(ty) => {
+                            let mut inner = WithId {
+                                value: std::mem::replace(ty, Type::Null),
+                                id: parameter.id,
+                            };
+                            self.substitute_type(from, dummies, &mut inner);
+                            *ty = inner.value;
+                            parameter.id = inner.id;
+                        }
+                        TypeOrValue::Value(value) => {
+                            let mut inner = WithId {
+                                value: std::mem::replace(value, Value::Unparsed),
+                                id: parameter.id,
+                            };
+                            self.substitute_value(from, dummies, &mut inner);
+                            *value = inner.value;
+                            parameter.id = inner.id;
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Replace the dummy references within a value from a parameterized
+    /// assignment with the actual parameters
+    fn substitute_value(&self, from: &Environment, dummies: &Dummies, value: &mut WithId<Value>) {
+        match &mut value.value {
+            Value::Reference(reference) => {
+                if let Some(actual) = dummy(reference, dummies) {
+                    if let TypeOrValue::Value(actual_value) = &actual.value {
+                        *value = WithId {
+                            value: actual_value.clone(),
+                            id: actual.id,
+                        };
+                    }
+                    return;
+                }
+                self.qualify_from(from, reference);
+            }
+            Value::Field(field) => self.substitute_field(from, dummies, field),
+            other => {
+                if from.node != self.module.node {
+                    self.qualify_value_in(from, other);
+                }
+            }
+        }
+    }
+
+    /// Replace a dummy reference to the class or object of a field reference
+    fn substitute_field(&self, from: &Environment, dummies: &Dummies, field: &mut FieldReference) {
+        match &mut field.source {
+            FieldSource::Class(class) => self.substitute_type(from, dummies, class),
+            FieldSource::Object(reference) => match dummy(reference, dummies) {
+                Some(actual) => {
+                    if let TypeOrValue::Value(Value::Reference(object)) = &actual.value {
+                        *reference = object.clone();
+                    }
+                }
+                None => self.qualify_from(from, reference),
+            },
+        }
+    }
+
+    /// Qualify a reference from a parameterized assignment that is defined in
+    /// another module
+    fn qualify_from(&self, from: &Environment, reference: &mut Reference) {
+        if from.node != self.module.node {
+            self.qualify_reference(from, reference);
+        }
+    }
+}
+
+/// Find the actual parameter a reference is a dummy reference for
+fn dummy<'d>(reference: &Reference, dummies: &Dummies<'d>) -> Option<&'d WithId<TypeOrValue>> {
+    match &reference.module {
+        Some(_) => None,
+        None => dummies.get(reference.name.as_str()).copied(),
+    }
+}
@@ -1,10 +1,39 @@
-use crate::diagnostic::Result;
+use crate::{cst::AsnNodeId, diagnostic::Result, Diagnostic};
 
 use super::context::AnalysisContext;
 
 impl AnalysisContext<'_> {
-    /// Run global analysis to resolve module names/imports/exports
-    pub(super) fn global(&mut self) -> Result {
+    /// Run global analysis of a module to resolve module names/imports/exports.
+    /// Will be re-run whenever a module that this module imports changes.
+    pub(super) fn global_module(&mut self, module: AsnNodeId) -> Result {
+        self.check_module_conflicts(module);
+        self.check_imported_modules(module);
+        self.resolve_references(module);
+        self.expand_assignments(module);
+        self.resolve_oids(module);
+        self.resolve_numbers(module);
+        self.check_times(module);
+        self.resolve_strings(module);
+
         Ok(())
     }
+
+    /// Report imports from modules that have not been loaded
+    fn check_imported_modules(&mut self, module: AsnNodeId) {
+        let mut missing = vec![];
+        for import in &self.cache.modules[&module].imports {
+            if !self.modules().any(|m| m.name == import.module.value) {
+                missing.push(import.module.clone());
+            }
+        }
+
+        for import in missing {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::UnknownModule")
+                    .name(format!("Unable to find module `{}`", import.value))
+                    .label(self.label(import.id).message("Module imported here"))
+                    .label("Add the file defining the module or a directory containing it to the include path"),
+            );
+        }
+    }
 }
@@ -1,11 +1,14 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
 use crate::{
+    ast::{TagDefault, TypeOrValue},
     compiler::SourceId,
     cst::{Asn1Tag, AsnNodeId},
     diagnostic::Result,
-    Diagnostic,
 };
 
 use super::{
+    comments::named_items,
     context::AnalysisContext,
     environment::{Environment, Variable},
 };
@@ -13,23 +16,17 @@ use super::{
 impl AnalysisContext<'_> {
     /// Run module-local analysis to gather imports / exports and other requirements
     /// that do not need full name and type resolution
-    pub(super) fn local(&mut self, file: SourceId) -> Result {
+    pub(super) fn local(&mut self, file: SourceId) -> Result<Vec<Environment>> {
         let cst = self.source(file);
-        let mut root = self.tree(cst.tree.root, &[Asn1Tag::Root])?;
-
-        let mut ids = vec![];
-        while let Some(module) = root.next() {
-            ids.push(module);
-        }
+        let root = self.tree(cst.tree.root, &[Asn1Tag::Root])?;
+        let ids: Vec<_> = root.collect();
 
         let mut modules = Vec::with_capacity(ids.len());
         for module in ids {
-            modules.push((module, self.local_module(module)?));
+            modules.push(self.local_module(module)?);
         }
 
-        self.modules.extend(modules);
-
-        Ok(())
+        Ok(modules)
     }
 
     fn local_module(&mut self, module: AsnNodeId) -> Result<Environment> {
@@ -38,36 +35,66 @@ impl AnalysisContext<'_> {
         let identifier = ast.identifier;
         let module_id = identifier.name.value.to_string();
 
-        if let Some(iri) = identifier.iri {
-            let id = iri.id;
+        let (oid, iri) = self.definitive_identifier(&identifier);
 
-            self.diagnostics.push(
-                Diagnostic::error("Asn1::Analysis::Iri")
-                    .name("Module IRI Descriptors are not supported")
-                    .label(self.label(id).message("IRI included here")),
-            );
+        let mut hasher = DefaultHasher::new();
+        if let Some(loc) = self.label(module).location {
+            self.source(module.source()).source[loc].hash(&mut hasher);
         }
 
-        if let Some(oid) = identifier.oid {
-            let id = oid.id;
+        let mut module = Environment::new(module);
+        module.name = module_id;
+        module.name_id = identifier.name.id;
+        module.oid = oid;
+        module.iri = iri;
+        module.source_hash = hasher.finish();
+        module.tag_default = ast.tag_default.map_or(TagDefault::Explicit, |t| t.value);
+        module.extensibility_implied = ast.extensibility;
+        module.imports = ast.imports;
+
+        let mut names: Vec<_> = ast.assignments.iter().map(|a| a.name.clone()).collect();
+        names.extend(ast.invalid.iter().map(|a| a.name.clone()));
+        names.sort_by_key(|name| name.id);
+        self.duplicate_assignments(&module.name, &names);
 
-            self.diagnostics.push(
-                Diagnostic::error("Asn1::Analysis::OID")
-                    .name("Module OID Descriptors are not supported")
-                    .label(self.label(id).message("OID included here")),
-            );
+        for assign in ast.invalid {
+            module.invalid.insert(assign.name.value);
+            self.diagnostics.push(assign.error);
         }
 
-        let mut module = Environment::new(module);
-        module.name = module_id;
+        let mut documented = vec![];
+        for assign in &ast.assignments {
+            documented.push(assign.name.id);
+            if let TypeOrValue::Type(ty) = &*assign.value {
+                named_items(ty, &mut documented);
+            }
+        }
+        module.comments = self.comments(&documented);
+        match self.type_constraints(module.node) {
+            Ok(constraints) => module.constraints = constraints,
+            Err(e) => self.diagnostics.push(e),
+        }
 
         for assign in ast.assignments {
+            if let TypeOrValue::Type(ty) = &*assign.value {
+                self.duplicate_identifiers(ty);
+            }
+            if let Some(ty) = &assign.ty {
+                self.duplicate_identifiers(ty);
+            }
+            self.duplicate_parameters(&assign.parameters);
+
+            // only the first of any duplicate assignments is kept
+            if module.variables.contains_key(&assign.name.value) {
+                continue;
+            }
             module.variables.insert(
                 assign.name.value,
                 Variable {
                     id: assign.name.id,
                     value: assign.value,
-                    ty: None,
+                    ty: assign.ty,
+                    parameters: assign.parameters,
                 },
             );
         }
@@ -0,0 +1,497 @@
+//! Interpretation of INTEGER and REAL values, following references to other
+//! values across modules.  Integers are unbounded, and reals are exact, kept
+//! as a mantissa, base and exponent rather than the nearest double.
+
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+
+use crate::{
+    ast::{NamedNumber, Reference, Type, TypeOrValue, Value, WithId},
+    cst::AsnNodeId,
+    token::TokenKind,
+    Diagnostic,
+};
+
+use super::{
+    context::AnalysisContext,
+    environment::{Environment, Variable},
+};
+
+/// A REAL value
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Real {
+    /// `mantissa × base ^ exponent`.  The mantissa is not a multiple of the
+    /// base, so that each value has a single form in each base, and zero has
+    /// an exponent of zero.
+    Finite {
+        mantissa: BigInt,
+        base: RealBase,
+        exponent: BigInt,
+    },
+
+    /// `-0`, which is distinct from zero
+    MinusZero,
+
+    /// `PLUS-INFINITY`
+    PlusInfinity,
+
+    /// `MINUS-INFINITY`
+    MinusInfinity,
+
+    /// `NOT-A-NUMBER`
+    NotANumber,
+}
+
+/// The base of the exponent of a finite REAL value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RealBase {
+    Two,
+    Ten,
+}
+
+impl RealBase {
+    fn radix(self) -> u32 {
+        match self {
+            RealBase::Two => 2,
+            RealBase::Ten => 10,
+        }
+    }
+}
+
+impl Real {
+    /// Create a finite value from its mantissa, base and exponent, removing
+    /// any factors of the base from the mantissa
+    pub fn new(mantissa: BigInt, base: RealBase, exponent: BigInt) -> Self {
+        let radix = BigInt::from(base.radix());
+        let (mut mantissa, mut exponent) = (mantissa, exponent);
+        if mantissa.sign() == Sign::NoSign {
+            exponent = BigInt::default();
+        }
+        while mantissa.sign() != Sign::NoSign && (&mantissa % &radix).sign() == Sign::NoSign {
+            mantissa /= &radix;
+            exponent += 1;
+        }
+        Real::Finite {
+            mantissa,
+            base,
+            exponent,
+        }
+    }
+
+    /// Parse a number in value notation, such as `-1.5e3`, as a base 10
+    /// value.  A negative zero, such as `-0.0`, is minus zero.
+    pub fn parse(text: &str) -> Option<Self> {
+        // exponents may be written with a non-breaking hyphen
+        let text = text.replace('\u{2011}', "-");
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text.as_str()),
+        };
+        let (digits, exponent) = match text.split_once(['e', 'E']) {
+            Some((digits, exponent)) => (digits, exponent.parse::<BigInt>().ok()?),
+            None => (text, BigInt::default()),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let all_digits = |digits: &str| digits.chars().all(|c| c.is_ascii_digit());
+        if integer.is_empty() || !all_digits(integer) || !all_digits(fraction) {
+            return None;
+        }
+
+        let mantissa: BigInt = format!("{integer}{fraction}").parse().ok()?;
+        if negative && mantissa.sign() == Sign::NoSign {
+            return Some(Real::MinusZero);
+        }
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Some(Real::new(
+            mantissa,
+            RealBase::Ten,
+            exponent - fraction.len(),
+        ))
+    }
+
+    /// Get the `f64` nearest to the value.  Values beyond the range of `f64`
+    /// are infinite or zero.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Real::Finite {
+                mantissa,
+                base: RealBase::Ten,
+                exponent,
+            } => format!("{mantissa}e{exponent}").parse().unwrap_or(f64::NAN),
+            Real::Finite {
+                mantissa,
+                base: RealBase::Two,
+                exponent,
+            } => {
+                // only the most significant bits of the mantissa are kept
+                let shift = mantissa.bits().saturating_sub(63);
+                let mut value = i64::try_from(mantissa >> shift).unwrap_or_default() as f64;
+                let exponent = (exponent + shift).clamp((-4096).into(), 4096.into());
+                let mut exponent = i32::try_from(exponent).unwrap_or_default();
+                while exponent.abs() > 1000 {
+                    let step = 1000 * exponent.signum();
+                    value *= 2f64.powi(step);
+                    exponent -= step;
+                }
+                value * 2f64.powi(exponent)
+            }
+            Real::MinusZero => -0.0,
+            Real::PlusInfinity => f64::INFINITY,
+            Real::MinusInfinity => f64::NEG_INFINITY,
+            Real::NotANumber => f64::NAN,
+        }
+    }
+}
+
+impl fmt::Display for Real {
+    /// Write the value in ASN.1 value notation, base 10 values as numbers
+    /// and base 2 values as their components
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Real::Finite {
+                mantissa,
+                base: RealBase::Ten,
+                exponent,
+            } => match exponent.sign() {
+                Sign::NoSign => write!(f, "{mantissa}"),
+                _ => write!(f, "{mantissa}e{exponent}"),
+            },
+            Real::Finite {
+                mantissa,
+                base: RealBase::Two,
+                exponent,
+            } => write!(f, "{{ mantissa {mantissa}, base 2, exponent {exponent} }}"),
+            Real::MinusZero => f.write_str("-0"),
+            Real::PlusInfinity => f.write_str("PLUS-INFINITY"),
+            Real::MinusInfinity => f.write_str("MINUS-INFINITY"),
+            Real::NotANumber => f.write_str("NOT-A-NUMBER"),
+        }
+    }
+}
+
+/// An interpreted INTEGER or REAL value
+#[derive(Debug, Clone)]
+enum Number {
+    Integer(BigInt),
+    Real(Real),
+}
+
+/// The type of a value that is interpreted as a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberKind {
+    Integer,
+    Real,
+}
+
+impl NumberKind {
+    fn name(self) -> &'static str {
+        match self {
+            NumberKind::Integer => "an INTEGER",
+            NumberKind::Real => "a REAL",
+        }
+    }
+}
+
+/// Interprets INTEGER and REAL values, following references to other values
+struct NumberResolver<'a, 'b> {
+    /// Context to find referenced values in
+    context: &'a AnalysisContext<'b>,
+
+    /// The variables currently being interpreted.  Values defined in terms of
+    /// themselves are reported by reference resolution.
+    stack: Vec<AsnNodeId>,
+
+    /// Errors found while interpreting the value.  Errors within referenced
+    /// values are not kept, as they are reported by the module defining them.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AnalysisContext<'_> {
+    /// Interpret every INTEGER and REAL value assigned within a module
+    pub(super) fn resolve_numbers(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let mut names: Vec<_> = env.variables.keys().collect();
+        names.sort();
+
+        let mut integers = std::collections::HashMap::new();
+        let mut reals = std::collections::HashMap::new();
+        let mut diagnostics = vec![];
+        for name in names {
+            let var = &env.variables[name];
+            // the value of a parameterized assignment depends on its parameters
+            if !var.parameters.is_empty() {
+                continue;
+            }
+
+            // the fields of information objects are only replaced by their
+            // values in the expanded assignment
+            let var = env.expanded.get(name).unwrap_or(var);
+
+            let mut resolver = NumberResolver {
+                context: self,
+                stack: vec![var.id],
+                diagnostics: vec![],
+            };
+            let number = resolver.variable(env, var);
+            diagnostics.extend(resolver.diagnostics);
+
+            match number {
+                Some(Number::Integer(integer)) => {
+                    integers.insert(name.clone(), integer);
+                }
+                Some(Number::Real(real)) => {
+                    reals.insert(name.clone(), real);
+                }
+                None => (),
+            }
+        }
+
+        self.diagnostics.extend(diagnostics);
+
+        if let Some(env) = self.cache.modules.get_mut(&module) {
+            env.integers = integers;
+            env.reals = reals;
+        }
+    }
+
+    /// Get the INTEGER value assigned to a name within a module
+    pub fn integer_value(&self, module: &str, name: &str) -> Option<&BigInt> {
+        self.module_named(module)?.integers.get(name)
+    }
+
+    /// Get the REAL value assigned to a name within a module
+    pub fn real_value(&self, module: &str, name: &str) -> Option<&Real> {
+        self.module_named(module)?.reals.get(name)
+    }
+}
+
+impl<'a> NumberResolver<'a, '_> {
+    /// Interpret the value of a variable of an INTEGER or REAL type
+    fn variable(&mut self, module: &'a Environment, var: &'a Variable) -> Option<Number> {
+        let (Some(ty), TypeOrValue::Value(value)) = (&var.ty, &*var.value) else {
+            return None;
+        };
+        let (kind, numbers) = self.number_type(module, ty)?;
+        let value = WithId {
+            value: value.clone(),
+            id: var.value.id,
+        };
+        self.value(module, kind, numbers, &value)
+    }
+
+    /// Find whether a type is an INTEGER or REAL type, following references
+    /// to other types.  The named numbers of an INTEGER type are returned
+    /// with it.
+    fn number_type(
+        &self,
+        module: &'a Environment,
+        ty: &'a Type,
+    ) -> Option<(NumberKind, &'a [NamedNumber])> {
+        match self.context.builtin_type(module, ty)? {
+            Type::Integer(numbers) => Some((NumberKind::Integer, numbers)),
+            Type::Real => Some((NumberKind::Real, &[])),
+            _ => None,
+        }
+    }
+
+    /// Interpret a value of an INTEGER or REAL type.  Identifiers are the
+    /// named numbers of the type, if they are one.
+    fn value(
+        &mut self,
+        module: &'a Environment,
+        kind: NumberKind,
+        numbers: &'a [NamedNumber],
+        value: &WithId<Value>,
+    ) -> Option<Number> {
+        match &value.value {
+            Value::Number(text) => self.literal(kind, text, value.id),
+            Value::Reference(reference) => {
+                let named = numbers
+                    .iter()
+                    .find(|number| number.name.value == reference.name.value);
+                if let (Some(named), None) = (named, &reference.module) {
+                    return self.value(module, kind, &[], &named.value);
+                }
+
+                // unresolved references are reported by reference resolution
+                let (module, var) = self
+                    .context
+                    .resolve_expanded(module, reference)
+                    .or_else(|| self.context.resolve(module, reference))?;
+                self.reference(kind, &reference.name, module, var)
+            }
+            Value::Unparsed if kind == NumberKind::Real => self.unparsed_real(module, value.id),
+            // the values of information object fields are not followed
+            Value::Field(_) => None,
+            _ => {
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::NumberValue")
+                        .name(format!("Expected {} value", kind.name()))
+                        .label(self.context.label(value.id).message("Value given here")),
+                );
+                None
+            }
+        }
+    }
+
+    /// Interpret a number written in a value
+    fn literal(&mut self, kind: NumberKind, text: &str, id: AsnNodeId) -> Option<Number> {
+        let number = match kind {
+            NumberKind::Integer => text.parse().ok().map(Number::Integer),
+            NumberKind::Real => Real::parse(text).map(Number::Real),
+        };
+        if number.is_none() {
+            let message = match kind {
+                NumberKind::Integer => "INTEGER values cannot have a fraction or exponent",
+                NumberKind::Real => "Not a decimal number",
+            };
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::NumberValue")
+                    .name(format!("`{text}` is not {} value", kind.name()))
+                    .label(self.context.label(id).message(message)),
+            );
+        }
+        number
+    }
+
+    /// Interpret the value of a referenced variable, which must be of the
+    /// same kind of type
+    fn reference(
+        &mut self,
+        kind: NumberKind,
+        name: &WithId<String>,
+        module: &'a Environment,
+        var: &'a Variable,
+    ) -> Option<Number> {
+        if self.stack.contains(&var.id) {
+            return None;
+        }
+
+        let referenced = var.ty.as_ref().and_then(|ty| self.number_type(module, ty));
+        if referenced.is_some_and(|(referenced, _)| referenced != kind) {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::NumberValue")
+                    .name(format!("`{}` is not {} value", **name, kind.name()))
+                    .label(self.context.label(name.id).message("Referenced here"))
+                    .label(self.context.label(var.id).message("Value defined here")),
+            );
+            return None;
+        }
+
+        let len = self.diagnostics.len();
+        self.stack.push(var.id);
+        let resolved = self.variable(module, var);
+        self.stack.pop();
+        self.diagnostics.truncate(len);
+        resolved
+    }
+
+    /// Interpret a REAL value that is not a number, which is either a special
+    /// value or the components of the value, `{ mantissa 5, base 2, exponent
+    /// -3 }`.  Values in braces are not interpreted until the type of the
+    /// value is known, so they are read from their tokens.
+    fn unparsed_real(&mut self, module: &'a Environment, id: AsnNodeId) -> Option<Number> {
+        let invalid = |context: &AnalysisContext, id| {
+            Diagnostic::error("Asn1::Analysis::RealValue")
+                .name("Invalid REAL value")
+                .label(context.label(id).message(
+                    "Expected a number, `PLUS-INFINITY`, `MINUS-INFINITY`, `NOT-A-NUMBER` or `{ mantissa m, base b, exponent e }`",
+                ))
+        };
+
+        let mut tokens = vec![];
+        for node in self.context.tree(id, &[]).ok()? {
+            match self.context.tree(node, &[]) {
+                Ok(tree) => {
+                    for node in tree {
+                        tokens.push(self.context.token(node, &[]).ok()?);
+                    }
+                }
+                Err(_) => tokens.push(self.context.token(node, &[]).ok()?),
+            }
+        }
+
+        let kinds: Vec<_> = tokens.iter().map(|tok| tok.kind).collect();
+        let special = match kinds[..] {
+            [TokenKind::KwPlusInfinity] => Some(Real::PlusInfinity),
+            [TokenKind::KwMinusInfinity] => Some(Real::MinusInfinity),
+            [TokenKind::KwNotANumber] => Some(Real::NotANumber),
+            _ => None,
+        };
+        if let Some(special) = special {
+            return Some(Number::Real(special));
+        }
+
+        // each component is named, then a number or a reference to one
+        let [first, inner @ .., last] = &tokens[..] else {
+            self.diagnostics.push(invalid(self.context, id));
+            return None;
+        };
+        if first.kind != TokenKind::LeftCurly || last.kind != TokenKind::RightCurly {
+            self.diagnostics.push(invalid(self.context, id));
+            return None;
+        }
+        let mut components = vec![];
+        for component in inner.split(|tok| tok.kind == TokenKind::Comma) {
+            let [name, value @ ..] = component else {
+                self.diagnostics.push(invalid(self.context, id));
+                return None;
+            };
+            let value_id = value.first().map_or(name.id, |tok| tok.id);
+            let text: String = value
+                .iter()
+                .map(|tok| self.context.token_value(**tok))
+                .collect();
+            let value = match value {
+                [tok] if tok.kind == TokenKind::ValueRefOrIdent => Value::Reference(Reference {
+                    module: None,
+                    name: WithId {
+                        value: self.context.ident_value(**tok),
+                        id: tok.id,
+                    },
+                }),
+                _ => Value::Number(text),
+            };
+            components.push((
+                self.context.token_value(**name),
+                WithId {
+                    value,
+                    id: value_id,
+                },
+            ));
+        }
+
+        let names: Vec<_> = components.iter().map(|(name, _)| *name).collect();
+        if names != ["mantissa", "base", "exponent"] {
+            self.diagnostics.push(invalid(self.context, id));
+            return None;
+        }
+        let mut integers = vec![];
+        for (_, value) in &components {
+            match self.value(module, NumberKind::Integer, &[], value)? {
+                Number::Integer(integer) => integers.push(integer),
+                Number::Real(_) => return None,
+            }
+        }
+
+        let [mantissa, base, exponent] = <[BigInt; 3]>::try_from(integers).ok()?;
+        let base = match u32::try_from(&base) {
+            Ok(2) => RealBase::Two,
+            Ok(10) => RealBase::Ten,
+            _ => {
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::RealValue")
+                        .name(format!("Invalid REAL base {base}"))
+                        .label(
+                            self.context
+                                .label(components[1].1.id)
+                                .message("The base must be 2 or 10"),
+                        ),
+                );
+                return None;
+            }
+        };
+        Some(Number::Real(Real::new(mantissa, base, exponent)))
+    }
+}
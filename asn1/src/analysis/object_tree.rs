@@ -1,63 +1,479 @@
-// //! Tools for dealing with Object Identifiers and Internationalized Resource
-// //! Identifiers (OIDs and IRIs)
-
-// /// An object identifier
-// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-// pub struct Oid {
-//     /// Ordered list of all components in the OID
-//     pub components: Vec<OidComponent>,
-// }
-
-// /// A single component of an object identifier
-// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-// pub struct OidComponent {
-//     /// the non-integer label for the component
-//     pub label: Option<String>,
-
-//     /// the integer label for the component.  note that this is not a number as
-//     /// math should not be done to it, it is an identifier.
-//     pub number: Option<String>,
-// }
-
-// /// An internationalized resource identifier
-// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-// pub struct Iri {
-//     /// The list of labels within the iri
-//     pub components: Vec<IriComponent>,
-// }
-
-// /// A single component of an iri.
-// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-// pub struct IriComponent {
-//     /// the unicode or integer label
-//     pub label: String,
-// }
-
-// /// An error encountered while parsing an iri string
-// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-// pub enum IriParseError {
-//     /// A
-//     MissingPrefix,
-//     MissingSuffix,
-// }
-
-// impl Iri {
-//     pub fn from_str(source: &str) -> Result<Self, IriParseError> {
-//         let mut iri = Iri { components: vec![] };
-
-//         let source = source
-//             .strip_prefix("\"/")
-//             .ok_or(IriParseError::MissingPrefix)?;
-//         let source = source
-//             .strip_suffix('\"')
-//             .ok_or(IriParseError::MissingSuffix)?;
-
-//         let components = source.split('/');
-//         for comp in components {
-//             let comp = comp.trim();
-//             iri.components.push(IriComponent { label: comp.to_string() });
-//         }
-
-//         Ok(iri)
-//     }
-// }
+//! Tools for dealing with Object Identifiers and Internationalized Resource
+//! Identifiers (OIDs and IRIs)
+
+use std::{collections::BTreeMap, fmt::Display, num::ParseIntError, str::FromStr};
+
+use crate::cst::AsnNodeId;
+
+/// An object identifier, the path from the root of the object identifier tree
+/// to one of its nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Oid {
+    /// The number of every arc along the path, starting at the root.  Note
+    /// that these are identifiers, so math should not be done to them.
+    pub arcs: Vec<u128>,
+}
+
+/// A relative object identifier, the path from some node of the object
+/// identifier tree to one of its descendants.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RelativeOid {
+    /// The number of every arc along the path
+    pub arcs: Vec<u128>,
+}
+
+/// An internationalized resource identifier, the path from the root of the
+/// object identifier tree given as unicode labels, such as
+/// `/ISO/Registration_Authority`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Iri {
+    /// Is this a relative IRI, that does not start at the root of the tree
+    pub relative: bool,
+
+    /// The list of labels within the iri
+    pub components: Vec<IriComponent>,
+}
+
+/// A single component of an iri.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IriComponent {
+    /// the unicode or integer label
+    pub label: String,
+}
+
+/// An error encountered while parsing an iri string
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IriParseError {
+    /// An absolute IRI did not start with a `/`
+    MissingPrefix,
+
+    /// A relative IRI started with a `/`
+    UnexpectedPrefix,
+
+    /// Two slashes were next to each other, or the IRI ended with a slash
+    EmptyLabel,
+
+    /// A character not permitted within an IRI label was found
+    InvalidCharacter(char),
+
+    /// An integer label had a leading zero
+    LeadingZero(String),
+
+    /// A non-integer label started or ended with a hyphen, or had hyphens as
+    /// its third and fourth characters.
+    InvalidHyphen(String),
+}
+
+/// A well known arc of the object identifier tree, from ITU-T X.660 Annex A.
+#[derive(Debug, Clone, Copy)]
+struct NamedArc {
+    /// The arcs leading to this arc
+    parent: &'static [u128],
+
+    /// The number of the arc
+    number: u128,
+
+    /// The identifiers that can be used for the arc in ASN.1 values, including
+    /// as a NameForm that does not specify the number.
+    names: &'static [&'static str],
+
+    /// The non-integer unicode labels of the arc, used within IRIs
+    labels: &'static [&'static str],
+}
+
+/// All arcs with names that may be used in ASN.1 values
+const NAMED_ARCS: &[NamedArc] = &[
+    NamedArc::new(&[], 0, &["itu-t", "ccitt", "itu-r"], &["ITU-T"]),
+    NamedArc::new(&[], 1, &["iso"], &["ISO"]),
+    NamedArc::new(
+        &[],
+        2,
+        &["joint-iso-itu-t", "joint-iso-ccitt"],
+        &["Joint-ISO-ITU-T"],
+    ),
+    NamedArc::new(&[0], 0, &["recommendation"], &["Recommendation"]),
+    NamedArc::new(&[0], 1, &["question"], &["Question"]),
+    NamedArc::new(&[0], 2, &["administration"], &["Administration"]),
+    NamedArc::new(&[0], 3, &["network-operator"], &["Network-Operator"]),
+    NamedArc::new(
+        &[0],
+        4,
+        &["identified-organization"],
+        &["Identified-Organization"],
+    ),
+    NamedArc::new(&[0], 5, &["r-recommendation"], &["R-Recommendation"]),
+    NamedArc::new(&[0], 9, &["data"], &["Data"]),
+    NamedArc::new(&[1], 0, &["standard"], &["Standard"]),
+    NamedArc::new(
+        &[1],
+        1,
+        &["registration-authority"],
+        &["Registration_Authority"],
+    ),
+    NamedArc::new(&[1], 2, &["member-body"], &["Member-Body"]),
+    NamedArc::new(
+        &[1],
+        3,
+        &["identified-organization"],
+        &["Identified-Organization"],
+    ),
+    NamedArc::new(&[2], 25, &[], &["UUID"]),
+    NamedArc::new(&[2], 999, &[], &["Example"]),
+];
+
+/// Long arcs, labels of the root arc that identify a node further down the
+/// tree, allowing them to be used as the first label of an IRI.
+const LONG_ARCS: &[(&str, &[u128])] = &[("UUID", &[2, 25]), ("Example", &[2, 999])];
+
+impl NamedArc {
+    const fn new(
+        parent: &'static [u128],
+        number: u128,
+        names: &'static [&'static str],
+        labels: &'static [&'static str],
+    ) -> Self {
+        NamedArc {
+            parent,
+            number,
+            names,
+            labels,
+        }
+    }
+}
+
+/// Get the number of a well known arc from its name, given the arcs leading
+/// to it.  The arcs below `{itu-t recommendation}` are named `a` to `z`.
+pub(crate) fn named_arc(parent: &[u128], name: &str) -> Option<u128> {
+    if let ([0, 0], [letter @ b'a'..=b'z']) = (parent, name.as_bytes()) {
+        return Some((letter - b'a' + 1).into());
+    }
+
+    NAMED_ARCS
+        .iter()
+        .find(|arc| arc.parent == parent && arc.names.contains(&name))
+        .map(|arc| arc.number)
+}
+
+/// Get all names of a well known arc, given the arcs leading to it.
+pub(crate) fn arc_names(parent: &[u128], number: u128) -> &'static [&'static str] {
+    NAMED_ARCS
+        .iter()
+        .find(|arc| arc.parent == parent && arc.number == number)
+        .map_or(&[], |arc| arc.names)
+}
+
+impl Oid {
+    /// Create an object identifier from its arcs
+    pub fn new(arcs: impl Into<Vec<u128>>) -> Self {
+        Oid { arcs: arcs.into() }
+    }
+
+    /// Get the identifier of the parent node, unless this is the root
+    pub fn parent(&self) -> Option<Oid> {
+        let (_, parent) = self.arcs.split_last()?;
+        Some(Oid::new(parent))
+    }
+
+    /// Is this identifier the same as or an ancestor of the other identifier
+    pub fn is_prefix_of(&self, other: &Oid) -> bool {
+        other.arcs.starts_with(&self.arcs)
+    }
+
+    /// Get the identifier of a node relative to this one
+    pub fn join(&self, relative: &RelativeOid) -> Oid {
+        Oid::new([&self.arcs[..], &relative.arcs[..]].concat())
+    }
+
+    /// Write the identifier in ASN.1 value notation, including the names of
+    /// any well known arcs, such as `{ iso(1) member-body(2) 840 }`.
+    pub fn to_asn1(&self) -> String {
+        let mut res = String::from("{");
+        for (i, arc) in self.arcs.iter().enumerate() {
+            match arc_names(&self.arcs[..i], *arc).first() {
+                Some(name) => res.push_str(&format!(" {name}({arc})")),
+                None => res.push_str(&format!(" {arc}")),
+            }
+        }
+        res.push_str(" }");
+        res
+    }
+}
+
+impl RelativeOid {
+    /// Create a relative object identifier from its arcs
+    pub fn new(arcs: impl Into<Vec<u128>>) -> Self {
+        RelativeOid { arcs: arcs.into() }
+    }
+}
+
+impl Iri {
+    /// Parse the text of an IRI value, not including the quotes.  Absolute
+    /// IRIs start with a `/`, relative IRIs do not.
+    pub fn parse(source: &str, relative: bool) -> Result<Self, IriParseError> {
+        let source = match (source.strip_prefix('/'), relative) {
+            (Some(rest), false) => rest,
+            (None, true) => source,
+            (Some(_), true) => return Err(IriParseError::UnexpectedPrefix),
+            (None, false) => return Err(IriParseError::MissingPrefix),
+        };
+
+        let mut iri = Iri {
+            relative,
+            components: vec![],
+        };
+
+        for label in source.split('/') {
+            iri.components.push(IriComponent::parse(label)?);
+        }
+
+        Ok(iri)
+    }
+
+    /// Get the object identifier of an absolute IRI, if every label is either
+    /// an integer label or a well known label from ITU-T X.660.
+    pub fn to_oid(&self) -> Option<Oid> {
+        if self.relative {
+            return None;
+        }
+
+        let (first, rest) = self.components.split_first()?;
+        let mut arcs = match LONG_ARCS.iter().find(|(label, _)| *label == first.label) {
+            Some((_, arcs)) => arcs.to_vec(),
+            None => vec![first.arc(&[])?],
+        };
+
+        for component in rest {
+            arcs.push(component.arc(&arcs)?);
+        }
+
+        Some(Oid::new(arcs))
+    }
+}
+
+impl IriComponent {
+    /// Parse and validate a single label of an IRI, following the rules for
+    /// integer and non-integer unicode labels in ITU-T X.660.
+    fn parse(label: &str) -> Result<Self, IriParseError> {
+        if label.is_empty() {
+            return Err(IriParseError::EmptyLabel);
+        }
+
+        if let Some(ch) = label.chars().find(|ch| !is_iunreserved(*ch)) {
+            return Err(IriParseError::InvalidCharacter(ch));
+        }
+
+        if label.bytes().all(|b| b.is_ascii_digit()) {
+            if label.len() > 1 && label.starts_with('0') {
+                return Err(IriParseError::LeadingZero(label.to_string()));
+            }
+        } else if label.starts_with('-') || label.ends_with('-') || label.get(2..4) == Some("--") {
+            return Err(IriParseError::InvalidHyphen(label.to_string()));
+        }
+
+        Ok(IriComponent {
+            label: label.to_string(),
+        })
+    }
+
+    /// Get the number of an integer label
+    pub fn integer(&self) -> Option<u128> {
+        if self.label.bytes().all(|b| b.is_ascii_digit()) {
+            self.label.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Get the number of the arc identified by this label, given the arcs
+    /// leading to it.
+    fn arc(&self, parent: &[u128]) -> Option<u128> {
+        self.integer().or_else(|| {
+            NAMED_ARCS
+                .iter()
+                .find(|arc| arc.parent == parent && arc.labels.contains(&self.label.as_str()))
+                .map(|arc| arc.number)
+        })
+    }
+}
+
+/// Is the character in the `iunreserved` set from RFC 3987
+fn is_iunreserved(ch: char) -> bool {
+    if ch.is_ascii() {
+        return ch.is_ascii_alphanumeric() || matches!(ch, '-' | '.' | '_' | '~');
+    }
+
+    // ucschar, every plane excludes its last two code points
+    let ch = ch as u32;
+    match ch {
+        0xA0..=0xD7FF | 0xF900..=0xFDCF | 0xFDF0..=0xFFEF => true,
+        0x10000..=0xEFFFF => ch & 0xFFFF < 0xFFFE,
+        _ => false,
+    }
+}
+
+impl Display for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_arcs(f, &self.arcs)
+    }
+}
+
+impl Display for RelativeOid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_arcs(f, &self.arcs)
+    }
+}
+
+/// Write arcs in dot notation, `1.2.3`
+fn write_arcs(f: &mut std::fmt::Formatter<'_>, arcs: &[u128]) -> std::fmt::Result {
+    for (i, arc) in arcs.iter().enumerate() {
+        if i != 0 {
+            write!(f, ".")?;
+        }
+        write!(f, "{arc}")?;
+    }
+
+    Ok(())
+}
+
+impl FromStr for Oid {
+    type Err = ParseIntError;
+
+    /// Parse an object identifier in dot notation, `1.2.3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let arcs = s.split('.').map(str::parse).collect::<Result<_, _>>()?;
+        Ok(Oid { arcs })
+    }
+}
+
+impl Display for Iri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            if i != 0 || !self.relative {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", component.label)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Iri {
+    type Err = IriParseError;
+
+    /// Parse an IRI, which is relative if it does not start with a `/`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Iri::parse(s, !s.starts_with('/'))
+    }
+}
+
+impl Display for IriParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IriParseError::MissingPrefix => write!(f, "IRI values must start with `/`"),
+            IriParseError::UnexpectedPrefix => {
+                write!(f, "relative IRI values must not start with `/`")
+            }
+            IriParseError::EmptyLabel => write!(f, "IRI labels must not be empty"),
+            IriParseError::InvalidCharacter(ch) => {
+                write!(f, "the character {ch:?} is not permitted in IRI labels")
+            }
+            IriParseError::LeadingZero(label) => {
+                write!(f, "integer label `{label}` must not have leading zeros")
+            }
+            IriParseError::InvalidHyphen(label) => write!(
+                f,
+                "label `{label}` must not start or end with a hyphen, or have hyphens as its third and fourth characters"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IriParseError {}
+
+/// All object identifiers defined across the loaded modules, including the
+/// identifiers of the modules themselves.
+#[derive(Debug, Clone, Default)]
+pub struct OidRegistry {
+    /// Every defined identifier and the definitions of it
+    entries: BTreeMap<Oid, Vec<OidEntry>>,
+}
+
+/// A single definition of an object identifier
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OidEntry {
+    /// Name of the module containing the definition
+    pub module: String,
+
+    /// Name of the value assigned the identifier, or none if it is the
+    /// identifier of the module.
+    pub name: Option<String>,
+
+    /// Tree node of the definition
+    pub node: AsnNodeId,
+}
+
+impl OidRegistry {
+    /// Add a definition of an object identifier
+    pub(crate) fn insert(&mut self, oid: Oid, entry: OidEntry) {
+        let entries = self.entries.entry(oid).or_default();
+        entries.push(entry);
+        entries.sort();
+    }
+
+    /// Get all definitions of an object identifier
+    pub fn get(&self, oid: &Oid) -> &[OidEntry] {
+        self.entries.get(oid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Find the object identifier assigned to a value within a module
+    pub fn find(&self, module: &str, name: &str) -> Option<&Oid> {
+        self.find_entry(module, Some(name))
+    }
+
+    /// Find the object identifier of a module
+    pub fn module(&self, module: &str) -> Option<&Oid> {
+        self.find_entry(module, None)
+    }
+
+    fn find_entry(&self, module: &str, name: Option<&str>) -> Option<&Oid> {
+        self.entries
+            .iter()
+            .find(|(_, entries)| {
+                entries
+                    .iter()
+                    .any(|e| e.module == module && e.name.as_deref() == name)
+            })
+            .map(|(oid, _)| oid)
+    }
+
+    /// Get all defined identifiers that are below the given identifier in the
+    /// tree, in order.
+    pub fn descendants<'a>(
+        &'a self,
+        oid: &'a Oid,
+    ) -> impl Iterator<Item = (&'a Oid, &'a [OidEntry])> + 'a {
+        self.entries
+            .range(oid..)
+            .skip_while(move |(other, _)| *other == oid)
+            .take_while(move |(other, _)| oid.is_prefix_of(other))
+            .map(|(oid, entries)| (oid, entries.as_slice()))
+    }
+
+    /// Get all defined identifiers, in order
+    pub fn iter(&self) -> impl Iterator<Item = (&Oid, &[OidEntry])> {
+        self.entries
+            .iter()
+            .map(|(oid, entries)| (oid, entries.as_slice()))
+    }
+
+    /// Get the number of distinct defined identifiers
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Are there no defined identifiers
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
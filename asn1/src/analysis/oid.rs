@@ -0,0 +1,438 @@
+//! Resolution of object identifier and IRI values, following references to
+//! other values across modules.
+
+use std::collections::HashMap;
+
+use crate::{
+    ast::{ModuleIdentifier, ObjIdComponent, Type, TypeOrValue, Value, WithId},
+    cst::AsnNodeId,
+    Diagnostic,
+};
+
+use super::{
+    context::AnalysisContext,
+    environment::{Environment, Variable},
+    object_tree::{arc_names, named_arc},
+    Iri, IriComponent, Oid, OidEntry, OidRegistry, RelativeOid,
+};
+
+/// The arcs of a resolved value
+#[derive(Debug, Clone)]
+enum Arcs {
+    /// An OBJECT IDENTIFIER or OID-IRI value
+    Absolute(Vec<u128>),
+
+    /// A RELATIVE-OID or RELATIVE-OID-IRI value
+    Relative(Vec<u128>),
+}
+
+/// Resolves object identifier values, following references to other values.
+struct OidResolver<'a, 'b> {
+    /// Context to find referenced values in
+    context: &'a AnalysisContext<'b>,
+
+    /// The variables currently being resolved, to detect recursive definitions
+    stack: Vec<AsnNodeId>,
+
+    /// Was a reference back to the first variable being resolved found
+    recursive: bool,
+
+    /// Errors found while resolving the value.  Errors within referenced
+    /// values are not kept, as they are reported by the module defining them.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AnalysisContext<'_> {
+    /// Resolve the object identifier and IRI of a module.  If only an IRI is
+    /// present and it identifies a known node, that is used as the module's
+    /// object identifier.
+    pub(super) fn definitive_identifier(
+        &mut self,
+        identifier: &ModuleIdentifier,
+    ) -> (Option<Oid>, Option<Iri>) {
+        let mut resolver = OidResolver::new(self);
+
+        let oid = identifier.oid.as_ref().and_then(|oid| {
+            let components: Vec<_> = oid
+                .components
+                .iter()
+                .filter_map(|comp| match (&comp.label, &comp.number) {
+                    (Some(label), Some(number)) => {
+                        Some(ObjIdComponent::NameAndNumber(label.clone(), number.clone()))
+                    }
+                    (Some(label), None) => Some(ObjIdComponent::Name(label.clone())),
+                    (None, Some(number)) => Some(ObjIdComponent::Number(number.clone())),
+                    (None, None) => None,
+                })
+                .collect();
+
+            resolver.components(None, &components, false).map(Oid::new)
+        });
+
+        let iri = identifier
+            .iri
+            .as_ref()
+            .and_then(|iri| Some((iri.id, resolver.iri(iri, false)?)));
+
+        let mut diagnostics = resolver.diagnostics;
+
+        if let (Some(oid), Some(oid_node), Some((iri_node, iri_oid))) = (
+            &oid,
+            &identifier.oid,
+            iri.as_ref().and_then(|(id, iri)| Some((id, iri.to_oid()?))),
+        ) {
+            if *oid != iri_oid {
+                diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::IriMismatch")
+                        .name("Module IRI and object identifier identify different nodes")
+                        .label(self.label(oid_node.id).message(format!("Identifies {oid}")))
+                        .label(
+                            self.label(*iri_node)
+                                .message(format!("Identifies {iri_oid}")),
+                        ),
+                );
+            }
+        }
+
+        self.diagnostics.extend(diagnostics);
+
+        let iri = iri.map(|(_, iri)| iri);
+        let oid = match identifier.oid {
+            Some(_) => oid,
+            None => iri.as_ref().and_then(Iri::to_oid),
+        };
+        (oid, iri)
+    }
+
+    /// Resolve every object identifier and relative object identifier value
+    /// assigned within a module.
+    pub(super) fn resolve_oids(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let mut names: Vec<_> = env.variables.keys().collect();
+        names.sort();
+
+        let mut oids = HashMap::new();
+        let mut relative_oids = HashMap::new();
+        let mut diagnostics = vec![];
+        for name in names {
+            let var = &env.variables[name];
+            // the value of a parameterized assignment depends on its parameters
+            if oid_kind(var).is_none() || !var.parameters.is_empty() {
+                continue;
+            }
+
+            let mut resolver = OidResolver::new(self);
+            resolver.stack.push(var.id);
+            let arcs = resolver.variable(env, var);
+
+            if resolver.recursive {
+                resolver.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::OidRecursive")
+                        .name(format!(
+                            "Object identifier `{name}` is defined in terms of itself"
+                        ))
+                        .label(self.label(var.id).message("Value defined here")),
+                );
+            }
+            diagnostics.extend(resolver.diagnostics);
+
+            match arcs {
+                Some(Arcs::Absolute(arcs)) => {
+                    oids.insert(name.clone(), Oid::new(arcs));
+                }
+                Some(Arcs::Relative(arcs)) => {
+                    relative_oids.insert(name.clone(), RelativeOid::new(arcs));
+                }
+                None => (),
+            }
+        }
+
+        self.diagnostics.extend(diagnostics);
+
+        if let Some(env) = self.cache.modules.get_mut(&module) {
+            env.oids = oids;
+            env.relative_oids = relative_oids;
+        }
+    }
+
+    /// Get a registry of all object identifiers defined across the analysed
+    /// modules, including the identifiers of the modules.
+    pub fn oid_registry(&self) -> OidRegistry {
+        let mut registry = OidRegistry::default();
+
+        for module in self.modules() {
+            if let Some(oid) = &module.oid {
+                registry.insert(
+                    oid.clone(),
+                    OidEntry {
+                        module: module.name.clone(),
+                        name: None,
+                        node: module.node,
+                    },
+                );
+            }
+
+            for (name, oid) in &module.oids {
+                registry.insert(
+                    oid.clone(),
+                    OidEntry {
+                        module: module.name.clone(),
+                        name: Some(name.clone()),
+                        node: module.variables[name].id,
+                    },
+                );
+            }
+        }
+
+        registry
+    }
+}
+
+impl<'a, 'b> OidResolver<'a, 'b> {
+    fn new(context: &'a AnalysisContext<'b>) -> Self {
+        OidResolver {
+            context,
+            stack: vec![],
+            recursive: false,
+            diagnostics: vec![],
+        }
+    }
+
+    /// Resolve the value of a variable of one of the object identifier types
+    fn variable(&mut self, module: &'a Environment, var: &'a Variable) -> Option<Arcs> {
+        let (Some(ty), TypeOrValue::Value(value)) = (&var.ty, &*var.value) else {
+            return None;
+        };
+
+        match (&**ty, value) {
+            (Type::ObjectIdentifier, Value::ObjectIdentifier(components)) => self
+                .components(Some(module), components, false)
+                .map(Arcs::Absolute),
+            (Type::RelativeOid, Value::ObjectIdentifier(components)) => self
+                .components(Some(module), components, true)
+                .map(Arcs::Relative),
+            (Type::OidIri, Value::Iri(text)) => {
+                let oid = self.iri(text, false)?.to_oid()?;
+                Some(Arcs::Absolute(oid.arcs))
+            }
+            (Type::RelativeOidIri, Value::Iri(text)) => {
+                let iri = self.iri(text, true)?;
+                let arcs: Option<_> = iri.components.iter().map(IriComponent::integer).collect();
+                arcs.map(Arcs::Relative)
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolve the components of an object identifier value.  The module is
+    /// used to find referenced values, if not present then references are not
+    /// permitted.
+    fn components(
+        &mut self,
+        module: Option<&'a Environment>,
+        components: &[ObjIdComponent],
+        relative: bool,
+    ) -> Option<Vec<u128>> {
+        let mut arcs = vec![];
+
+        for component in components {
+            match component {
+                ObjIdComponent::Name(name) => {
+                    let target = module.and_then(|m| self.context.lookup(m, name));
+                    if let Some(target) = target {
+                        self.reference(&mut arcs, relative, name, target)?;
+                        continue;
+                    }
+
+                    let Some(arc) = named_arc(&arcs, name).filter(|_| !relative) else {
+                        let message = if module.is_some() {
+                            "Not the name of a well known arc or a value"
+                        } else {
+                            "Not the name of a well known arc"
+                        };
+                        self.diagnostics.push(
+                            Diagnostic::error("Asn1::Analysis::OidComponent")
+                                .name(format!("Unknown object identifier component `{}`", **name))
+                                .label(self.context.label(name.id).message(message))
+                                .label("Only the arcs named in ITU-T X.660, such as `iso` or `member-body`, may be written without their number"),
+                        );
+                        return None;
+                    };
+                    arcs.push(arc);
+                }
+                ObjIdComponent::Number(number) => {
+                    let arc = self.number(number)?;
+                    if !relative {
+                        self.check_arc(&arcs, arc, number.id)?;
+                    }
+                    arcs.push(arc);
+                }
+                ObjIdComponent::NameAndNumber(name, number) => {
+                    let arc = self.number(number)?;
+                    if !relative {
+                        self.check_arc(&arcs, arc, number.id)?;
+                        self.check_name(&arcs, name, arc)?;
+                    }
+                    arcs.push(arc);
+                }
+                ObjIdComponent::External(module_name, name) => {
+                    let target = self
+                        .context
+                        .module_named(module_name)
+                        .and_then(|m| Some((m, m.variables.get(&name.value)?)));
+
+                    let Some(target) = target else {
+                        self.diagnostics.push(
+                            Diagnostic::error("Asn1::Analysis::OidReference")
+                                .name(format!(
+                                    "Unable to find value `{}.{}`",
+                                    **module_name, **name
+                                ))
+                                .label(self.context.label(name.id).message("Referenced here")),
+                        );
+                        return None;
+                    };
+                    self.reference(&mut arcs, relative, name, target)?;
+                }
+            }
+        }
+
+        Some(arcs)
+    }
+
+    /// Add the arcs of a referenced value.  The first component of an object
+    /// identifier must reference an object identifier, any other components
+    /// must reference relative object identifiers.
+    fn reference(
+        &mut self,
+        arcs: &mut Vec<u128>,
+        relative: bool,
+        name: &WithId<String>,
+        (module, var): (&'a Environment, &'a Variable),
+    ) -> Option<()> {
+        // values whose type is a reference are not interpreted until types
+        // are resolved, so cannot be followed yet
+        if matches!(var.ty.as_deref(), Some(Type::Reference(_))) {
+            return None;
+        }
+
+        let absolute = arcs.is_empty() && !relative;
+        if oid_kind(var) != Some(!absolute) {
+            let expected = if absolute {
+                "an OBJECT IDENTIFIER"
+            } else {
+                "a RELATIVE-OID"
+            };
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::OidReference")
+                    .name(format!("`{}` is not {expected} value", **name))
+                    .label(self.context.label(name.id).message("Referenced here"))
+                    .label(self.context.label(var.id).message("Value defined here")),
+            );
+            return None;
+        }
+
+        if self.stack.contains(&var.id) {
+            self.recursive |= self.stack[0] == var.id;
+            return None;
+        }
+
+        let len = self.diagnostics.len();
+        self.stack.push(var.id);
+        let resolved = self.variable(module, var);
+        self.stack.pop();
+        self.diagnostics.truncate(len);
+
+        match resolved? {
+            Arcs::Absolute(resolved) | Arcs::Relative(resolved) => arcs.extend(resolved),
+        }
+
+        Some(())
+    }
+
+    /// Parse the number of an arc
+    fn number(&mut self, number: &WithId<String>) -> Option<u128> {
+        let Ok(arc) = number.parse() else {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::OidArc")
+                    .name(format!("Object identifier arc `{}` is too large", **number))
+                    .label(self.context.label(number.id).message("Arc used here")),
+            );
+            return None;
+        };
+
+        Some(arc)
+    }
+
+    /// Check that an arc can be used below the given arcs.  There are only
+    /// three arcs at the root, and at most 40 arcs below `itu-t` and `iso`.
+    fn check_arc(&mut self, arcs: &[u128], arc: u128, id: AsnNodeId) -> Option<()> {
+        let message = match arcs {
+            [] if arc > 2 => "The first arc must be 0, 1 or 2",
+            [0 | 1] if arc > 39 => "Arcs below `itu-t` and `iso` must be between 0 and 39",
+            _ => return Some(()),
+        };
+
+        self.diagnostics.push(
+            Diagnostic::error("Asn1::Analysis::OidArc")
+                .name(format!("Invalid object identifier arc `{arc}`"))
+                .label(self.context.label(id).message(message)),
+        );
+        None
+    }
+
+    /// Check that the name given to an arc is consistent with its number.  An
+    /// arc's name may be anything, unless the name is of a well known arc, or
+    /// it is at the root of the tree where only the well known names are
+    /// permitted.
+    fn check_name(&mut self, arcs: &[u128], name: &WithId<String>, arc: u128) -> Option<()> {
+        let names = arc_names(arcs, arc);
+
+        let message = match named_arc(arcs, name) {
+            Some(expected) if expected != arc => format!("`{}` is arc {expected}", **name),
+            None if arcs.is_empty() => format!("Arc {arc} is named `{}`", names.join("` or `")),
+            _ => return Some(()),
+        };
+
+        self.diagnostics.push(
+            Diagnostic::error("Asn1::Analysis::OidMismatch")
+                .name(format!(
+                    "Object identifier component `{}({arc})` has inconsistent name and number forms",
+                    **name
+                ))
+                .label(self.context.label(name.id).message(message)),
+        );
+        None
+    }
+
+    /// Parse the text of an IRI value
+    fn iri(&mut self, text: &WithId<String>, relative: bool) -> Option<Iri> {
+        match Iri::parse(&cstring_value(text), relative) {
+            Ok(iri) => Some(iri),
+            Err(e) => {
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::Iri")
+                        .name("Invalid IRI value")
+                        .label(self.context.label(text.id).message(e.to_string())),
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Is the variable an object identifier value, and is it relative
+fn oid_kind(var: &Variable) -> Option<bool> {
+    match **var.ty.as_ref()? {
+        Type::ObjectIdentifier | Type::OidIri => Some(false),
+        Type::RelativeOid | Type::RelativeOidIri => Some(true),
+        _ => None,
+    }
+}
+
+/// Get the contents of a quoted string, replacing escaped quotes
+pub(super) fn cstring_value(text: &str) -> String {
+    let text = text.strip_prefix('"').unwrap_or(text);
+    let text = text.strip_suffix('"').unwrap_or(text);
+    text.replace("\"\"", "\"")
+}
@@ -0,0 +1,988 @@
+//! Resolution of references to types and values, linking every reference to
+//! the assignment it refers to and detecting illegal recursive definitions.
+
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{
+        ComponentType, Constraint, ElementSet, FieldKind, FieldReference, FieldSource,
+        ObjIdComponent, Optionality, Parameter, ParameterizedReference, Reference, SubtypeElement,
+        Type, TypeOrValue, Value, WithId,
+    },
+    cst::AsnNodeId,
+    util::closest,
+    Diagnostic,
+};
+
+use super::{
+    context::AnalysisContext,
+    environment::{Environment, Variable},
+};
+
+/// The assignment that a reference resolves to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Definition {
+    /// Name of the module containing the assignment
+    pub module: String,
+
+    /// Name of the assignment
+    pub name: String,
+
+    /// Node ID of the name in the assignment
+    pub node: AsnNodeId,
+}
+
+/// Is a reference to a type or a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Type,
+    Value,
+}
+
+/// What a dummy reference of a parameterized assignment stands for, given by
+/// its governor and the case of its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParameterKind {
+    Type,
+    Value,
+    ValueSet,
+    Object,
+    ObjectSet,
+}
+
+/// Links the references within a single module to their definitions
+struct Resolver<'a, 'b> {
+    /// Context to find referenced modules and assignments in
+    context: &'a AnalysisContext<'b>,
+
+    /// The module containing the references
+    module: &'a Environment,
+
+    /// The assignment being resolved, which is not suggested in place of
+    /// references within it
+    assignment: Option<AsnNodeId>,
+
+    /// Names of the dummy references of the parameterized assignment being
+    /// resolved, which are not references to other assignments.
+    dummies: Vec<String>,
+
+    /// Is a constraint on the components of a value being resolved, where
+    /// unresolved value references are taken to be identifiers defined by
+    /// the type of a component
+    components: bool,
+
+    /// The definitions found for each reference
+    references: HashMap<AsnNodeId, Definition>,
+
+    /// Errors found while resolving references
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AnalysisContext<'_> {
+    /// Resolve every reference to a type or value within a module, and check
+    /// for types and values that are defined in terms of themselves.
+    pub(super) fn resolve_references(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let mut resolver = Resolver {
+            context: self,
+            module: env,
+            assignment: None,
+            dummies: vec![],
+            components: false,
+            references: HashMap::new(),
+            diagnostics: vec![],
+        };
+
+        resolver.imports();
+
+        let mut names: Vec<_> = env.variables.keys().collect();
+        names.sort();
+        for name in &names {
+            let var = &env.variables[*name];
+            resolver.assignment = Some(var.id);
+            resolver.dummies = var
+                .parameters
+                .iter()
+                .map(|p| p.name.value.clone())
+                .collect();
+            for parameter in &var.parameters {
+                if let Some(governor) = &parameter.governor {
+                    resolver.ty(governor, governor.id);
+                }
+            }
+
+            match &*var.value {
+                TypeOrValue::Type(ty) => resolver.ty(ty, var.value.id),
+                TypeOrValue::Value(value) => {
+                    if let Some(ty) = &var.ty {
+                        resolver.ty(ty, ty.id);
+                    }
+                    resolver.value(value, var.ty.as_ref().map(|ty| &ty.value));
+                }
+            }
+        }
+
+        let Resolver {
+            references,
+            mut diagnostics,
+            ..
+        } = resolver;
+
+        let finite = self.finite_types();
+        for name in &names {
+            let var = &env.variables[*name];
+            match &*var.value {
+                TypeOrValue::Type(ty) if !finite.contains(&var.id) => {
+                    diagnostics.push(self.infinite_type(env, name, var, ty, &finite));
+                }
+                TypeOrValue::Value(_) => diagnostics.extend(self.recursive_value(env, name, var)),
+                _ => (),
+            }
+        }
+
+        self.diagnostics.extend(diagnostics);
+        if let Some(env) = self.cache.modules.get_mut(&module) {
+            env.references = references;
+        }
+    }
+
+    /// Get the assignment a reference resolves to, given the node ID of the
+    /// referenced name.
+    pub fn definition(&self, node: AsnNodeId) -> Option<&Definition> {
+        self.modules()
+            .find_map(|module| module.references.get(&node))
+    }
+
+    /// Find the variable a possibly external reference refers to
+    pub(crate) fn resolve<'s>(
+        &'s self,
+        module: &'s Environment,
+        reference: &Reference,
+    ) -> Option<(&'s Environment, &'s Variable)> {
+        match &reference.module {
+            Some(name) => {
+                let module = self.module_named(name)?;
+                Some((module, module.variables.get(&reference.name.value)?))
+            }
+            None => self.lookup(module, &reference.name),
+        }
+    }
+
+    /// Find the type a type is defined as, following references to other
+    /// types and removing tags.  References to parameterized types and types
+    /// defined in terms of themselves have no such type.
+    pub(crate) fn builtin_type<'s>(
+        &'s self,
+        module: &'s Environment,
+        ty: &'s Type,
+    ) -> Option<&'s Type> {
+        let mut current = (module, ty);
+        let mut visited = vec![];
+        loop {
+            match current.1 {
+                Type::Tagged(tagged) => current.1 = &tagged.ty,
+                Type::Reference(reference) => {
+                    let (module, var) = self.resolve(current.0, reference)?;
+                    if visited.contains(&var.id) || !var.parameters.is_empty() {
+                        return None;
+                    }
+                    visited.push(var.id);
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        return None;
+                    };
+                    current = (module, ty);
+                }
+                ty => return Some(ty),
+            }
+        }
+    }
+
+    /// Find the type an expanded type is defined as, following references to
+    /// other expanded types and instances of parameterized types, and
+    /// removing tags
+    pub(crate) fn expanded_builtin_type<'s>(
+        &'s self,
+        module: &'s Environment,
+        ty: &'s Type,
+    ) -> Option<&'s Type> {
+        let mut current = (module, ty);
+        let mut visited = vec![];
+        loop {
+            match current.1 {
+                Type::Tagged(tagged) => current.1 = &tagged.ty,
+                Type::Reference(reference) => {
+                    let (module, var) = self.resolve_expanded(current.0, reference)?;
+                    if visited.contains(&var.id) {
+                        return None;
+                    }
+                    visited.push(var.id);
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        return None;
+                    };
+                    current = (module, ty);
+                }
+                ty => return Some(ty),
+            }
+        }
+    }
+
+    /// Find the expanded assignment a reference within an expanded type
+    /// refers to, which may be an instance of a parameterized type
+    pub(crate) fn resolve_expanded<'s>(
+        &'s self,
+        module: &'s Environment,
+        reference: &Reference,
+    ) -> Option<(&'s Environment, &'s Variable)> {
+        if reference.module.is_none() {
+            if let Some(var) = module.expanded.get(&reference.name.value) {
+                return Some((module, var));
+            }
+        }
+
+        let (module, _) = self.resolve(module, reference)?;
+        Some((module, module.expanded.get(&reference.name.value)?))
+    }
+
+    /// Find every type assignment, across all modules, that has at least one
+    /// value of finite size.  A type is finite unless all of its values must
+    /// contain a value of the type itself, such as a sequence with a required
+    /// component of its own type.  Types are assumed to be infinite until
+    /// shown to be finite, so that recursion is only accepted where it can
+    /// end.
+    fn finite_types(&self) -> HashSet<AsnNodeId> {
+        let mut finite = HashSet::new();
+
+        loop {
+            let mut changed = false;
+            for module in self.modules() {
+                for var in module.variables.values() {
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        continue;
+                    };
+                    if !finite.contains(&var.id)
+                        && self.is_finite(module, ty, var.value.id, &finite)
+                    {
+                        finite.insert(var.id);
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                return finite;
+            }
+        }
+    }
+
+    /// Can a type have a finite value, given the type assignments currently
+    /// known to be finite and the node ID of the type.  Unresolved references
+    /// are treated as finite, as they are reported separately.
+    fn is_finite(
+        &self,
+        module: &Environment,
+        ty: &Type,
+        id: AsnNodeId,
+        finite: &HashSet<AsnNodeId>,
+    ) -> bool {
+        match ty {
+            Type::Reference(reference) => match self.resolve(module, reference) {
+                Some((_, var)) => {
+                    !matches!(&*var.value, TypeOrValue::Type(_)) || finite.contains(&var.id)
+                }
+                None => true,
+            },
+            Type::Sequence(components) | Type::Set(components) => {
+                components.items().all(|component| match component {
+                    ComponentType::Named(c) if c.optional || c.default.is_some() => true,
+                    component => self.is_finite(module, component.ty(), component.ty().id, finite),
+                })
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                self.may_be_empty(module, ty, id)
+                    || self.is_finite(module, &of.ty, of.ty.id, finite)
+            }
+            Type::Choice(alternatives) => alternatives.items().any(|alternative| {
+                self.is_finite(module, &alternative.ty, alternative.ty.id, finite)
+            }),
+            Type::Tagged(tagged) => self.is_finite(module, &tagged.ty, tagged.ty.id, finite),
+            Type::Selection(_, ty) => self.is_finite(module, ty, ty.id, finite),
+            _ => true,
+        }
+    }
+
+    /// Can a `SEQUENCE OF` or `SET OF` type have no elements, given the node
+    /// ID of the type.  Sizes beyond an extensible size constraint may be
+    /// empty.
+    fn may_be_empty(&self, module: &Environment, ty: &Type, id: AsnNodeId) -> bool {
+        let constraints = self.constraints_of(module, ty, id, false);
+        constraints
+            .size
+            .is_none_or(|size| size.extensible || size.contains(&BigInt::from(0)))
+    }
+
+    /// Create the error for a type with no finite values
+    fn infinite_type(
+        &self,
+        module: &Environment,
+        name: &str,
+        var: &Variable,
+        ty: &Type,
+        finite: &HashSet<AsnNodeId>,
+    ) -> Diagnostic {
+        let mut diagnostic = Diagnostic::error("Asn1::Analysis::InfiniteType")
+            .name(format!(
+                "Type `{name}` is recursive and has no finite values"
+            ))
+            .label(self.label(var.id).message("Type defined here"));
+
+        if let Some(id) = self.infinite_part(module, var.value.id, ty, finite) {
+            diagnostic = diagnostic.label(
+                self.label(id)
+                    .message("Every value must contain a value of this type"),
+            );
+        }
+
+        diagnostic.label("Make the recursive component OPTIONAL, use SEQUENCE OF or SET OF that may be empty, or add a CHOICE alternative that is not recursive")
+    }
+
+    /// Find the part of an infinite type that forces the recursion
+    fn infinite_part(
+        &self,
+        module: &Environment,
+        id: AsnNodeId,
+        ty: &Type,
+        finite: &HashSet<AsnNodeId>,
+    ) -> Option<AsnNodeId> {
+        match ty {
+            Type::Reference(_) => Some(id),
+            Type::Sequence(components) | Type::Set(components) => {
+                components.items().find_map(|component| {
+                    if let ComponentType::Named(c) = component {
+                        if c.optional || c.default.is_some() {
+                            return None;
+                        }
+                    }
+
+                    let ty = component.ty();
+                    if self.is_finite(module, ty, ty.id, finite) {
+                        return None;
+                    }
+                    self.infinite_part(module, ty.id, ty, finite)
+                })
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) if !self.may_be_empty(module, ty, id) => {
+                self.infinite_part(module, of.ty.id, &of.ty, finite)
+            }
+            Type::Choice(alternatives) => alternatives.items().find_map(|alternative| {
+                self.infinite_part(module, alternative.ty.id, &alternative.ty, finite)
+            }),
+            Type::Tagged(tagged) => self.infinite_part(module, tagged.ty.id, &tagged.ty, finite),
+            Type::Selection(_, ty) => self.infinite_part(module, ty.id, ty, finite),
+            _ => None,
+        }
+    }
+
+    /// Check whether a value assignment is defined by a chain of references
+    /// that leads back to itself.
+    fn recursive_value(
+        &self,
+        module: &Environment,
+        name: &str,
+        var: &Variable,
+    ) -> Option<Diagnostic> {
+        let mut visited = vec![var.id];
+        let mut current = (module, var);
+
+        loop {
+            let (module, var) = current;
+            let TypeOrValue::Value(Value::Reference(reference)) = &*var.value else {
+                return None;
+            };
+            if let (None, Some(ty)) = (&reference.module, &var.ty) {
+                if self.is_identifier(module, ty, &reference.name) {
+                    return None;
+                }
+            }
+
+            current = self.resolve(module, reference)?;
+            if current.1.id == visited[0] {
+                return Some(
+                    Diagnostic::error("Asn1::Analysis::RecursiveValue")
+                        .name(format!("Value `{name}` is defined in terms of itself"))
+                        .label(self.label(visited[0]).message("Value defined here"))
+                        .label(
+                            self.label(reference.name.id)
+                                .message(format!("Refers back to `{name}` here")),
+                        ),
+                );
+            }
+
+            // cycles that do not include this value are reported by the
+            // values within them
+            if visited.contains(&current.1.id) {
+                return None;
+            }
+            visited.push(current.1.id);
+        }
+    }
+
+    /// Is a name one of the identifiers defined by a type, such as a named
+    /// number or an enumeration item, rather than a reference to a value.
+    fn is_identifier(&self, module: &Environment, ty: &Type, name: &str) -> bool {
+        let mut current = (module, ty);
+
+        // the limit ensures recursive type references terminate
+        for _ in 0..=self.cache.modules.len() + module.variables.len() {
+            let (module, ty) = current;
+            match ty {
+                Type::Integer(numbers) | Type::BitString(numbers) => {
+                    return numbers.iter().any(|number| *number.name == name)
+                }
+                Type::Enumerated(items) => return items.items().any(|item| *item.name == name),
+                Type::Tagged(tagged) => current = (module, &tagged.ty),
+                Type::Reference(reference) => {
+                    let Some((module, var)) = self.resolve(module, reference) else {
+                        return false;
+                    };
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        return false;
+                    };
+                    current = (module, ty);
+                }
+                _ => return false,
+            }
+        }
+
+        false
+    }
+}
+
+impl<'a> Resolver<'a, '_> {
+    /// Check that every imported symbol is defined by the module it is
+    /// imported from.  Imports from unknown modules are reported separately.
+    fn imports(&mut self) {
+        for import in &self.module.imports {
+            let Some(source) = self.context.module_named(&import.module) else {
+                continue;
+            };
+
+            for symbol in &import.symbols {
+                if self.context.lookup(source, symbol).is_some()
+                    || source.invalid.contains(&symbol.value)
+                {
+                    continue;
+                }
+
+                let mut diagnostic = Diagnostic::error("Asn1::Analysis::ImportedSymbol")
+                    .name(format!(
+                        "Module `{}` does not define `{}`",
+                        *import.module, **symbol
+                    ))
+                    .label(self.context.label(symbol.id).message("Imported here"));
+
+                let candidates = source.variables.keys().map(String::as_str);
+                if let Some(suggestion) = closest(symbol, candidates) {
+                    diagnostic = diagnostic.label(format!("Did you mean `{suggestion}`?"));
+                }
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    /// Resolve the references within a type and the constraints following
+    /// it, given the node ID of the type
+    fn ty(&mut self, ty: &Type, id: AsnNodeId) {
+        if let Some(constraints) = self.module.constraints.get(&id) {
+            for constraint in constraints {
+                self.constraint(constraint, Some(ty));
+            }
+        }
+
+        match ty {
+            Type::Integer(numbers) | Type::BitString(numbers) => {
+                for number in numbers {
+                    self.value(&number.value, None);
+                }
+            }
+            Type::Enumerated(items) => {
+                for item in items.items() {
+                    if let Some(value) = &item.value {
+                        self.value(value, None);
+                    }
+                }
+            }
+            Type::Sequence(components) | Type::Set(components) => {
+                for component in components.items() {
+                    let ty = component.ty();
+                    self.ty(ty, ty.id);
+                    if let ComponentType::Named(component) = component {
+                        if let Some(default) = &component.default {
+                            self.value(default, Some(&component.ty.value));
+                        }
+                    }
+                }
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => self.ty(&of.ty, of.ty.id),
+            Type::Choice(alternatives) => {
+                for alternative in alternatives.items() {
+                    self.ty(&alternative.ty, alternative.ty.id);
+                }
+            }
+            Type::Tagged(tagged) => {
+                self.value(&tagged.number, None);
+                self.ty(&tagged.ty, tagged.ty.id);
+            }
+            Type::Selection(_, ty) => self.ty(ty, ty.id),
+            Type::Reference(reference) => self.reference(reference, Kind::Type),
+            Type::Class(class) => {
+                for field in &class.fields {
+                    let ty = match &field.kind {
+                        FieldKind::Value { ty, .. } | FieldKind::ValueSet(ty) => {
+                            self.ty(ty, ty.id);
+                            Some(&ty.value)
+                        }
+                        _ => None,
+                    };
+                    if let Optionality::Default(default) = &field.optionality {
+                        match &default.value {
+                            TypeOrValue::Type(ty) => self.ty(ty, default.id),
+                            TypeOrValue::Value(default) => self.value(default, ty),
+                        }
+                    }
+                }
+            }
+            Type::Field(field) => self.field(field),
+            Type::Parameterized(parameterized) => self.parameterized(parameterized),
+            _ => (),
+        }
+    }
+
+    /// Resolve the references within a constraint.  The constrained type is
+    /// used to find identifiers that name numbers or enumeration items, and
+    /// is missing within constraints on the size, characters or components
+    /// of a value.
+    fn constraint(&mut self, constraint: &Constraint, ty: Option<&Type>) {
+        match constraint {
+            Constraint::Subtype(sets) | Constraint::Table(sets) => {
+                for set in sets.root.iter().chain(&sets.additions) {
+                    self.element_set(set, ty);
+                }
+            }
+            Constraint::Contents(contents) => {
+                if let Some(containing) = &contents.containing {
+                    self.ty(containing, containing.id);
+                }
+                if let Some(encoded_by) = &contents.encoded_by {
+                    self.value(encoded_by, None);
+                }
+            }
+            Constraint::UserDefined => (),
+        }
+    }
+
+    /// Resolve the references within the elements of a constraint
+    fn element_set(&mut self, set: &ElementSet, ty: Option<&Type>) {
+        match set {
+            ElementSet::Element(element) => match &**element {
+                SubtypeElement::Value(value) => self.value(value, ty),
+                SubtypeElement::Range(lower, upper) => {
+                    for endpoint in [lower, upper] {
+                        if let Some(value) = &endpoint.value {
+                            self.value(value, ty);
+                        }
+                    }
+                }
+                SubtypeElement::Type(ty) => self.ty(ty, ty.id),
+                SubtypeElement::Size(constraint) | SubtypeElement::From(constraint) => {
+                    self.constraint(constraint, None);
+                }
+                SubtypeElement::Component(constraint) => self.components(constraint),
+                SubtypeElement::Components(components) => {
+                    for component in &components.components {
+                        if let Some(constraint) = &component.constraint {
+                            self.components(constraint);
+                        }
+                    }
+                }
+                SubtypeElement::Pattern(value) => self.value(value, None),
+                SubtypeElement::Settings(_) => (),
+            },
+            ElementSet::Union(sets) | ElementSet::Intersection(sets) => {
+                for set in sets {
+                    self.element_set(set, ty);
+                }
+            }
+            ElementSet::Except(included, excluded) => {
+                self.element_set(included, ty);
+                self.element_set(excluded, ty);
+            }
+            ElementSet::AllExcept(excluded) => self.element_set(excluded, ty),
+        }
+    }
+
+    /// Resolve the references within the constraint on the components of a
+    /// value, where values may be identifiers defined by the types of the
+    /// components.
+    fn components(&mut self, constraint: &Constraint) {
+        let within = std::mem::replace(&mut self.components, true);
+        self.constraint(constraint, None);
+        self.components = within;
+    }
+
+    /// Resolve a reference to a parameterized assignment and the references
+    /// within its actual parameters, and check the actual parameters are of
+    /// the kinds the assignment expects.
+    fn parameterized(&mut self, parameterized: &ParameterizedReference) {
+        for parameter in &parameterized.parameters {
+            match &parameter.value {
+                TypeOrValue::Type(ty) => self.ty(ty, parameter.id),
+                TypeOrValue::Value(value) => self.value(value, None),
+            }
+        }
+
+        let reference = &parameterized.reference;
+        let Some((module, var)) = self.lookup(reference, Kind::Type) else {
+            return;
+        };
+
+        if var.parameters.is_empty() {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::Parameter")
+                    .name(format!("`{}` is not parameterized", *reference.name))
+                    .label(
+                        self.context
+                            .label(reference.name.id)
+                            .message("Parameters given here"),
+                    )
+                    .label(self.context.label(var.id).message("Defined here")),
+            );
+            return;
+        }
+
+        let expected = var.parameters.len();
+        let given = parameterized.parameters.len();
+        if expected != given {
+            let plural = if expected == 1 { "" } else { "s" };
+            let were = if given == 1 { "was" } else { "were" };
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::Parameter")
+                    .name(format!(
+                        "`{}` expects {expected} parameter{plural}, but {given} {were} given",
+                        *reference.name
+                    ))
+                    .label(
+                        self.context
+                            .label(reference.name.id)
+                            .message("Referenced here"),
+                    )
+                    .label(self.context.label(var.id).message("Defined here")),
+            );
+            return;
+        }
+
+        for (dummy, actual) in var.parameters.iter().zip(&parameterized.parameters) {
+            let kind = self.parameter_kind(module, dummy);
+            let accepted = match (kind, &actual.value) {
+                (ParameterKind::Type, TypeOrValue::Type(_)) => true,
+                (
+                    ParameterKind::ValueSet | ParameterKind::ObjectSet,
+                    TypeOrValue::Type(Type::Reference(_)),
+                ) => true,
+                (ParameterKind::Object, TypeOrValue::Value(Value::Unparsed)) => {
+                    self.diagnostics.push(
+                        Diagnostic::error("Asn1::Analysis::Unsupported")
+                            .name("Information objects defined in actual parameters are not supported")
+                            .label(self.context.label(actual.id).message("Used here"))
+                            .label("Assign the object to a name and use the name instead"),
+                    );
+                    continue;
+                }
+                (ParameterKind::Value | ParameterKind::Object, TypeOrValue::Value(_)) => true,
+                (
+                    ParameterKind::ValueSet | ParameterKind::ObjectSet,
+                    TypeOrValue::Value(Value::Unparsed),
+                ) => true,
+                _ => false,
+            };
+            if accepted {
+                continue;
+            }
+
+            let given = match &actual.value {
+                TypeOrValue::Type(_) => "type",
+                TypeOrValue::Value(_) => "value",
+            };
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::ParameterKind")
+                    .name(format!(
+                        "Parameter `{}` of `{}` is a {}, but a {given} was given",
+                        *dummy.name,
+                        *reference.name,
+                        kind.describe()
+                    ))
+                    .label(
+                        self.context
+                            .label(actual.id)
+                            .message("Actual parameter given here"),
+                    )
+                    .label(
+                        self.context
+                            .label(dummy.name.id)
+                            .message("Parameter defined here"),
+                    ),
+            );
+        }
+    }
+
+    /// Find what a dummy reference stands for.  Dummy references without a
+    /// governor are types, others are values or objects, or sets of them if
+    /// the name starts with an upper case letter.
+    fn parameter_kind(&self, module: &Environment, parameter: &Parameter) -> ParameterKind {
+        let Some(governor) = &parameter.governor else {
+            return ParameterKind::Type;
+        };
+
+        let set = parameter.name.starts_with(char::is_uppercase);
+        match (self.context.class(module, governor).is_some(), set) {
+            (true, true) => ParameterKind::ObjectSet,
+            (true, false) => ParameterKind::Object,
+            (false, true) => ParameterKind::ValueSet,
+            (false, false) => ParameterKind::Value,
+        }
+    }
+
+    /// Resolve the class or object of a field reference
+    fn field(&mut self, field: &FieldReference) {
+        match &field.source {
+            FieldSource::Class(class) => self.ty(class, class.id),
+            FieldSource::Object(object) => self.reference(object, Kind::Value),
+        }
+    }
+
+    /// Resolve the references within a value.  The type of the value is used
+    /// to find identifiers that name numbers or enumeration items.
+    fn value(&mut self, value: &Value, ty: Option<&Type>) {
+        match value {
+            Value::Reference(reference) => {
+                let identifier = reference.module.is_none()
+                    && !self.dummies.contains(&reference.name.value)
+                    && (ty.is_some_and(|ty| {
+                        self.context.is_identifier(self.module, ty, &reference.name)
+                    }) || self.components
+                        && self.context.resolve(self.module, reference).is_none());
+                if !identifier {
+                    self.reference(reference, Kind::Value);
+                }
+            }
+            // object identifier components are checked when the value is
+            // resolved, only the links to referenced values are kept here.
+            Value::ObjectIdentifier(components) => {
+                for component in components {
+                    let (module, name) = match component {
+                        ObjIdComponent::Name(name) => (None, name),
+                        ObjIdComponent::External(module, name) => (Some(module.clone()), name),
+                        _ => continue,
+                    };
+                    let reference = Reference {
+                        module,
+                        name: name.clone(),
+                    };
+                    if let Some((module, var)) = self.context.resolve(self.module, &reference) {
+                        self.link(name, module, var);
+                    }
+                }
+            }
+            Value::Field(field) => self.field(field),
+            _ => (),
+        }
+    }
+
+    /// Resolve a single reference, reporting it if it cannot be found or is
+    /// a parameterized assignment used without parameters.  Dummy references
+    /// are left unresolved.
+    fn reference(&mut self, reference: &Reference, kind: Kind) {
+        if reference.module.is_none() && self.dummies.contains(&reference.name.value) {
+            return;
+        }
+        let Some((_, var)) = self.lookup(reference, kind) else {
+            return;
+        };
+
+        if !var.parameters.is_empty() {
+            let names: Vec<_> = var.parameters.iter().map(|p| p.name.as_str()).collect();
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::Parameter")
+                    .name(format!(
+                        "`{}` is parameterized and must be given parameters",
+                        *reference.name
+                    ))
+                    .label(
+                        self.context
+                            .label(reference.name.id)
+                            .message("Referenced here"),
+                    )
+                    .label(
+                        self.context
+                            .label(var.id)
+                            .message(format!("Expects {{ {} }}", names.join(", "))),
+                    ),
+            );
+        }
+    }
+
+    /// Find the variable a reference refers to and link the reference to it,
+    /// reporting it if it cannot be found.
+    fn lookup(
+        &mut self,
+        reference: &Reference,
+        kind: Kind,
+    ) -> Option<(&'a Environment, &'a Variable)> {
+        if let Some((module, var)) = self.context.resolve(self.module, reference) {
+            self.link(&reference.name, module, var);
+            return Some((module, var));
+        }
+
+        let name = &reference.name;
+        let what = match kind {
+            Kind::Type => "type",
+            Kind::Value => "value",
+        };
+
+        let diagnostic = match &reference.module {
+            Some(module_name) => {
+                let Some(module) = self.context.module_named(module_name) else {
+                    // imports from unknown modules are reported already
+                    if self
+                        .module
+                        .imports
+                        .iter()
+                        .any(|i| i.module.value == module_name.value)
+                    {
+                        return None;
+                    }
+
+                    let mut diagnostic = Diagnostic::error("Asn1::Analysis::UnresolvedReference")
+                        .name(format!("Unable to find module `{}`", **module_name))
+                        .label(
+                            self.context
+                                .label(module_name.id)
+                                .message("Referenced here"),
+                        );
+                    let candidates = self.context.modules().map(|m| m.name.as_str());
+                    if let Some(suggestion) = closest(module_name, candidates) {
+                        diagnostic = diagnostic.label(format!("Did you mean `{suggestion}`?"));
+                    }
+                    self.diagnostics.push(diagnostic);
+                    return None;
+                };
+                if module.invalid.contains(&name.value) {
+                    return None;
+                }
+
+                let diagnostic = Diagnostic::error("Asn1::Analysis::UnresolvedReference")
+                    .name(format!(
+                        "Unable to find {what} `{}` in module `{}`",
+                        **name, **module_name
+                    ))
+                    .label(self.context.label(name.id).message("Referenced here"));
+                let candidates = module
+                    .variables
+                    .iter()
+                    .filter(|(_, var)| var_kind(var) == kind && Some(var.id) != self.assignment)
+                    .map(|(name, _)| name.as_str());
+                match closest(name, candidates) {
+                    Some(suggestion) => {
+                        diagnostic.label(format!("Did you mean `{}.{suggestion}`?", **module_name))
+                    }
+                    None => diagnostic,
+                }
+            }
+            None => {
+                // imported symbols that cannot be found are reported by the
+                // import, and invalid assignments are reported already.
+                let imported = self
+                    .module
+                    .imports
+                    .iter()
+                    .any(|import| import.symbols.iter().any(|s| s.value == name.value));
+                if imported || self.module.invalid.contains(&name.value) {
+                    return None;
+                }
+
+                let mut diagnostic = Diagnostic::error("Asn1::Analysis::UnresolvedReference")
+                    .name(format!("Unable to find {what} `{}`", **name))
+                    .label(self.context.label(name.id).message("Referenced here"));
+
+                let local = self
+                    .module
+                    .variables
+                    .iter()
+                    .filter(|(_, var)| var_kind(var) == kind && Some(var.id) != self.assignment)
+                    .map(|(name, _)| name.as_str());
+                let imported = self
+                    .module
+                    .imports
+                    .iter()
+                    .flat_map(|import| &import.symbols)
+                    .map(|symbol| symbol.value.as_str());
+                if let Some(suggestion) = closest(name, local.chain(imported)) {
+                    diagnostic = diagnostic.label(format!("Did you mean `{suggestion}`?"));
+                }
+
+                let mut defining: Vec<_> = self
+                    .context
+                    .modules()
+                    .filter(|module| module.variables.contains_key(&name.value))
+                    .map(|module| module.name.as_str())
+                    .collect();
+                defining.sort();
+                if let Some(module) = defining.first() {
+                    diagnostic = diagnostic.label(format!(
+                        "`{}` is defined in module `{module}`, which is not imported",
+                        **name
+                    ));
+                }
+
+                diagnostic
+            }
+        };
+
+        self.diagnostics.push(diagnostic);
+        None
+    }
+
+    /// Record the definition a reference resolves to
+    fn link(&mut self, name: &WithId<String>, module: &Environment, var: &Variable) {
+        self.references.insert(
+            name.id,
+            Definition {
+                module: module.name.clone(),
+                name: name.value.clone(),
+                node: var.id,
+            },
+        );
+    }
+}
+
+impl ParameterKind {
+    /// Describe the kind for use in an error message
+    fn describe(self) -> &'static str {
+        match self {
+            ParameterKind::Type => "type",
+            ParameterKind::Value => "value",
+            ParameterKind::ValueSet => "value set",
+            ParameterKind::Object => "information object",
+            ParameterKind::ObjectSet => "information object set",
+        }
+    }
+}
+
+/// Is a variable a type or a value
+fn var_kind(var: &Variable) -> Kind {
+    match &*var.value {
+        TypeOrValue::Type(_) => Kind::Type,
+        TypeOrValue::Value(_) => Kind::Value,
+    }
+}
@@ -0,0 +1,261 @@
+//! Discovery of modules that are imported but have not been added to the
+//! compiler, by searching the include directories for files that define them.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{
+    cst::{Asn1, AsnNodeId},
+    token::TokenKind,
+    AsnCompiler,
+};
+
+use super::{context::AnalysisContext, object_tree::named_arc};
+
+/// File extensions of source files found in include directories
+const EXTENSIONS: [&str; 2] = ["asn", "asn1"];
+
+/// Directories that are searched for imported modules
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SearchPath {
+    /// The include directories, in search order
+    dirs: Vec<PathBuf>,
+
+    /// All modules defined in files within the include directories, created
+    /// the first time a module is searched for.
+    index: Option<Vec<IndexedModule>>,
+
+    /// Files from the include directories that have already been loaded
+    loaded: HashSet<PathBuf>,
+}
+
+/// A module found in an include directory
+#[derive(Debug, Clone)]
+struct IndexedModule {
+    /// The file that defines the module
+    path: PathBuf,
+
+    /// Name of the module
+    name: String,
+
+    /// The arcs of the module's object identifier, if present and it does not
+    /// reference any values.
+    oid: Option<Vec<u128>>,
+}
+
+impl SearchPath {
+    /// Add a directory to the end of the search path
+    pub(crate) fn add_dir(&mut self, dir: PathBuf) {
+        self.dirs.push(dir);
+        self.invalidate();
+    }
+
+    /// Discard the modules found in the include directories, so that they
+    /// are searched again the next time a module is missing.  Called whenever
+    /// a source file is updated or removed, as the files in the directories
+    /// may have been edited as well.
+    pub(crate) fn invalidate(&mut self) {
+        self.index = None;
+    }
+
+    /// Forget that a file from the include directories has been loaded, once
+    /// it is removed from the compiler, so that it can be loaded again if its
+    /// modules are still imported
+    pub(crate) fn remove_file(&mut self, file_name: &str) {
+        self.loaded.remove(Path::new(file_name));
+        self.invalidate();
+    }
+
+    /// Find the file defining a module, preferring a match of the object
+    /// identifier over the name, if an object identifier is provided.
+    fn find(&self, name: &str, oid: Option<&[u128]>) -> Option<&Path> {
+        let index = self.index.as_ref()?;
+
+        oid.and_then(|oid| index.iter().find(|m| m.oid.as_deref() == Some(oid)))
+            .or_else(|| index.iter().find(|m| m.name == name))
+            .map(|m| m.path.as_path())
+    }
+}
+
+impl AsnCompiler {
+    /// Add a directory that will be searched for modules that are imported by
+    /// the source files, but have not been added to the compiler.  Files with
+    /// the extension `.asn` or `.asn1` directly within the directory will be
+    /// loaded during analysis if they define a module that is required.
+    /// Directories are searched in the order they are added.
+    pub fn add_include_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.search_path.add_dir(dir.into());
+    }
+
+    /// Parse every file within the include directories to find the modules
+    /// that they define.  Files that cannot be read or parsed are ignored.
+    fn index_search_path(&mut self) {
+        if self.search_path.index.is_some() {
+            return;
+        }
+
+        let mut index = vec![];
+        for dir in &self.search_path.dirs {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+
+            let mut paths: Vec<_> = entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| {
+                    path.extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                })
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                let Ok(source) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+
+                let mut compiler = AsnCompiler::new();
+                compiler.features = self.features;
+                let Ok(file) = compiler.add_file(path.to_string_lossy().to_string(), source) else {
+                    continue;
+                };
+
+                let source = compiler.source(file);
+                let (tree, text) = (&source.tree, source.source.as_str());
+                for module in tree.modules() {
+                    let Some(name) = module.name() else {
+                        continue;
+                    };
+                    let oid = module
+                        .identifier()
+                        .and_then(|ident| ident.definitive_oid())
+                        .and_then(|oid| oid_arcs(&tokens(tree, text, oid)));
+
+                    index.push(IndexedModule {
+                        path: path.clone(),
+                        name: normalise(&text[name.span()]),
+                        oid,
+                    });
+                }
+            }
+        }
+
+        self.search_path.index = Some(index);
+    }
+}
+
+impl AnalysisContext<'_> {
+    /// Load the files from the include directories that define any modules
+    /// that are imported but not yet loaded.  The loaded files are marked as
+    /// changed so they will be analysed.  Errors from parsing the loaded files
+    /// are kept by the analysis cache.
+    pub(super) fn discover_imports(&mut self) {
+        if self.search_path.dirs.is_empty() {
+            return;
+        }
+
+        let names: HashSet<_> = self.modules().map(|m| m.name.clone()).collect();
+        let mut missing = vec![];
+        for module in self.modules() {
+            for import in &module.imports {
+                if names.contains(&import.module.value) {
+                    continue;
+                }
+
+                let identifier: Vec<_> = import
+                    .identifier
+                    .iter()
+                    .map(|tok| (tok.kind, self.token_value(**tok)))
+                    .collect();
+                missing.push((import.module.value.clone(), oid_arcs(&identifier)));
+            }
+        }
+        missing.sort();
+        missing.dedup();
+
+        if missing.is_empty() {
+            return;
+        }
+
+        self.index_search_path();
+
+        let mut paths = vec![];
+        for (name, oid) in &missing {
+            let Some(path) = self.search_path.find(name, oid.as_deref()) else {
+                continue;
+            };
+            if !paths.contains(&path) && !self.search_path.loaded.contains(path) {
+                paths.push(path);
+            }
+        }
+        let paths: Vec<_> = paths.into_iter().map(Path::to_path_buf).collect();
+
+        for path in paths {
+            self.search_path.loaded.insert(path.clone());
+
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+
+            let file = self.push_source(path.to_string_lossy().to_string(), source);
+            // parse errors are reported by the analysis cache
+            let _ = self.parse(file);
+        }
+    }
+}
+
+/// Get the text of every token within a node, not including comments
+fn tokens<'a>(tree: &Asn1, text: &'a str, node: AsnNodeId) -> Vec<(TokenKind, &'a str)> {
+    if let Some(tok) = tree.token(node) {
+        return vec![(tok.kind, &text[tok.span()])];
+    }
+
+    tree.children(node)
+        .filter(|&child| !tree.is_trivia(child))
+        .flat_map(|child| tokens(tree, text, child))
+        .collect()
+}
+
+/// Get the arcs of an object identifier written as `{ iso a(1) 2 ... }`.
+/// Returns `None` if it is not in that form or any component without a number
+/// is not a well known arc, as those require value resolution.
+fn oid_arcs(tokens: &[(TokenKind, &str)]) -> Option<Vec<u128>> {
+    let (first, rest) = tokens.split_first()?;
+    let (last, mut rest) = rest.split_last()?;
+    if first.0 != TokenKind::LeftCurly || last.0 != TokenKind::RightCurly {
+        return None;
+    }
+
+    let mut arcs = vec![];
+    while let Some((tok, tail)) = rest.split_first() {
+        rest = tail;
+        let number = match (tok.0, rest) {
+            (TokenKind::Number, _) => tok.1,
+            (
+                TokenKind::ValueRefOrIdent,
+                [(TokenKind::LeftParen, _), (TokenKind::Number, number), (TokenKind::RightParen, _), tail @ ..],
+            ) => {
+                rest = tail;
+                number
+            }
+            (TokenKind::ValueRefOrIdent, _) => {
+                arcs.push(named_arc(&arcs, &normalise(tok.1))?);
+                continue;
+            }
+            _ => return None,
+        };
+        arcs.push(number.parse().ok()?);
+    }
+
+    Some(arcs)
+}
+
+/// Normalise an identifier in the same way as identifiers during analysis
+fn normalise(name: &str) -> String {
+    name.replace('\u{2011}', "-").nfc().to_string()
+}
@@ -0,0 +1,315 @@
+//! Interpretation of the values of the restricted character string types,
+//! which are strings, the positions of single characters, or lists of both,
+//! checked against the character set of their type.
+
+use crate::{
+    ast::{Reference, Type, TypeOrValue, Value, WithId},
+    cst::AsnNodeId,
+    strings::RestrictedString,
+    token::{Token, TokenKind},
+    Diagnostic,
+};
+
+use super::{
+    context::AnalysisContext,
+    environment::{Environment, Variable},
+    oid::cstring_value,
+};
+
+/// Interprets character string values, following references to other values
+struct StringResolver<'a, 'b> {
+    /// Context to find referenced values in
+    context: &'a AnalysisContext<'b>,
+
+    /// The variables currently being interpreted.  Values defined in terms of
+    /// themselves are reported by reference resolution.
+    stack: Vec<AsnNodeId>,
+
+    /// Errors found while interpreting the value.  Errors within referenced
+    /// values are not kept, as they are reported by the module defining them.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AnalysisContext<'_> {
+    /// Interpret every value of a restricted character string type assigned
+    /// within a module, and report characters that are not in the character
+    /// set of the type.
+    pub(super) fn resolve_strings(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let mut names: Vec<_> = env.variables.keys().collect();
+        names.sort();
+
+        let mut strings = std::collections::HashMap::new();
+        let mut diagnostics = vec![];
+        for name in names {
+            let var = &env.variables[name];
+            if !var.parameters.is_empty() {
+                continue;
+            }
+
+            let mut resolver = StringResolver {
+                context: self,
+                stack: vec![var.id],
+                diagnostics: vec![],
+            };
+            let string = resolver.variable(env, var);
+            diagnostics.extend(resolver.diagnostics);
+
+            if let Some((_, string)) = string {
+                strings.insert(name.clone(), string);
+            }
+        }
+
+        self.diagnostics.extend(diagnostics);
+
+        if let Some(env) = self.cache.modules.get_mut(&module) {
+            env.strings = strings;
+        }
+    }
+
+    /// Get the value of a restricted character string type assigned to a
+    /// name within a module
+    pub fn string_value(&self, module: &str, name: &str) -> Option<&str> {
+        Some(self.module_named(module)?.strings.get(name)?)
+    }
+}
+
+/// The restricted character string type a type is, if any
+pub(crate) fn restricted_string(ty: &Type) -> Option<RestrictedString> {
+    Some(match ty {
+        Type::NumericString => RestrictedString::Numeric,
+        Type::PrintableString => RestrictedString::Printable,
+        Type::VisibleString | Type::ISO64String => RestrictedString::Visible,
+        Type::IA5String => RestrictedString::IA5,
+        Type::TeletexString | Type::T61String => RestrictedString::Teletex,
+        Type::VideotexString => RestrictedString::Videotex,
+        Type::GraphicString | Type::ObjectDescriptor => RestrictedString::Graphic,
+        Type::GeneralString => RestrictedString::General,
+        Type::BmpString => RestrictedString::Bmp,
+        Type::UniversalString => RestrictedString::Universal,
+        Type::UTF8String => RestrictedString::Utf8,
+        _ => return None,
+    })
+}
+
+impl<'a> StringResolver<'a, '_> {
+    /// Interpret the value of a variable of a restricted character string
+    /// type, checking its characters
+    fn variable(
+        &mut self,
+        module: &'a Environment,
+        var: &'a Variable,
+    ) -> Option<(RestrictedString, String)> {
+        let (Some(ty), TypeOrValue::Value(value)) = (&var.ty, &*var.value) else {
+            return None;
+        };
+        let ty = restricted_string(self.context.builtin_type(module, ty)?)?;
+        let string = self.value(module, value, var.value.id)?;
+
+        if let Err(e) = ty.check(&string) {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::StringValue")
+                    .name(format!("Invalid {ty} value"))
+                    .label(self.context.label(var.value.id).message(e.to_string())),
+            );
+            return None;
+        }
+        Some((ty, string))
+    }
+
+    /// Interpret a character string value
+    fn value(&mut self, module: &'a Environment, value: &Value, id: AsnNodeId) -> Option<String> {
+        match value {
+            Value::String(text) => Some(cstring_value(text)),
+            Value::Reference(reference) => {
+                // unresolved references are reported by reference resolution
+                let (module, var) = self.context.resolve(module, reference)?;
+                self.reference(&reference.name, module, var)
+            }
+            Value::Unparsed => self.unparsed(module, id),
+            // the values of information object fields are not followed
+            Value::Field(_) => None,
+            _ => {
+                self.diagnostics.push(
+                    Diagnostic::error("Asn1::Analysis::StringValue")
+                        .name("Expected a character string value")
+                        .label(self.context.label(id).message("Value given here")),
+                );
+                None
+            }
+        }
+    }
+
+    /// Interpret the value of a referenced variable, which must be of a
+    /// restricted character string type
+    fn reference(
+        &mut self,
+        name: &WithId<String>,
+        module: &'a Environment,
+        var: &'a Variable,
+    ) -> Option<String> {
+        if self.stack.contains(&var.id) {
+            return None;
+        }
+
+        let len = self.diagnostics.len();
+        self.stack.push(var.id);
+        let resolved = self.variable(module, var);
+        self.stack.pop();
+        self.diagnostics.truncate(len);
+
+        let is_string = var
+            .ty
+            .as_ref()
+            .and_then(|ty| self.context.builtin_type(module, ty))
+            .is_some_and(|ty| restricted_string(ty).is_some());
+        if !is_string {
+            self.diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::StringValue")
+                    .name(format!("`{}` is not a character string value", **name))
+                    .label(self.context.label(name.id).message("Referenced here"))
+                    .label(self.context.label(var.id).message("Value defined here")),
+            );
+        }
+        resolved.map(|(_, string)| string)
+    }
+
+    /// Interpret a value in braces, which is either the position of a single
+    /// character or a list of strings, positions and references.  Values in
+    /// braces are not interpreted until the type of the value is known, so
+    /// they are read from their tokens.
+    fn unparsed(&mut self, module: &'a Environment, id: AsnNodeId) -> Option<String> {
+        let mut tokens = vec![];
+        self.tokens(id, &mut tokens)?;
+
+        if let Some(c) = self.character(&tokens, id) {
+            return c.map(String::from);
+        }
+
+        let invalid = |context: &AnalysisContext, id| {
+            Diagnostic::error("Asn1::Analysis::StringValue")
+                .name("Invalid character string value")
+                .label(context.label(id).message(
+                    "Expected a string, `{ group, plane, row, cell }`, `{ column, row }` or a list of them",
+                ))
+        };
+
+        let [first, inner @ .., last] = &tokens[..] else {
+            self.diagnostics.push(invalid(self.context, id));
+            return None;
+        };
+        if first.kind != TokenKind::LeftCurly || last.kind != TokenKind::RightCurly {
+            self.diagnostics.push(invalid(self.context, id));
+            return None;
+        }
+
+        // split the list at the commas that are not within a position
+        let mut items = vec![];
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, tok) in inner.iter().enumerate() {
+            match tok.kind {
+                TokenKind::LeftCurly => depth += 1,
+                TokenKind::RightCurly => depth -= 1,
+                TokenKind::Comma if depth == 0 => {
+                    items.push(&inner[start..i]);
+                    start = i + 1;
+                }
+                _ => (),
+            }
+        }
+        items.push(&inner[start..]);
+
+        let mut string = String::new();
+        for item in items {
+            match item {
+                [tok] if tok.kind == TokenKind::CString => {
+                    string.push_str(&cstring_value(self.context.token_value(**tok)));
+                }
+                [tok] if tok.kind == TokenKind::ValueRefOrIdent => {
+                    let reference = Reference {
+                        module: None,
+                        name: WithId {
+                            value: self.context.ident_value(**tok),
+                            id: tok.id,
+                        },
+                    };
+                    let (module, var) = self.context.resolve(module, &reference)?;
+                    string.push_str(&self.reference(&reference.name, module, var)?);
+                }
+                [tok, ..] => match self.character(item, tok.id) {
+                    Some(c) => string.push(c?),
+                    None => {
+                        self.diagnostics.push(invalid(self.context, tok.id));
+                        return None;
+                    }
+                },
+                [] => {
+                    self.diagnostics.push(invalid(self.context, id));
+                    return None;
+                }
+            }
+        }
+        Some(string)
+    }
+
+    /// Interpret the position of a character, `{ group, plane, row, cell }`
+    /// in ISO 10646 or `{ column, row }` in ISO 646.  Returns `None` if the
+    /// tokens are not a position, and `Some(None)` if the position is not of
+    /// a character.
+    fn character(&mut self, tokens: &[WithId<Token>], id: AsnNodeId) -> Option<Option<char>> {
+        let [first, inner @ .., last] = tokens else {
+            return None;
+        };
+        if first.kind != TokenKind::LeftCurly || last.kind != TokenKind::RightCurly {
+            return None;
+        }
+
+        let mut numbers = vec![];
+        for (i, tok) in inner.iter().enumerate() {
+            match tok.kind {
+                TokenKind::Number if i % 2 == 0 => {
+                    numbers.push(self.context.token_value(**tok).parse::<u32>().ok());
+                }
+                TokenKind::Comma if i % 2 == 1 => (),
+                _ => return None,
+            }
+        }
+
+        let (code, message) = match numbers[..] {
+            [Some(group @ 0..=127), Some(plane @ 0..=255), Some(row @ 0..=255), Some(cell @ 0..=255)] => {
+                (group << 24 | plane << 16 | row << 8 | cell, None)
+            }
+            [_, _, _, _] => (
+                0,
+                Some("Groups are 0 to 127, and planes, rows and cells 0 to 255"),
+            ),
+            [Some(column @ 0..=7), Some(row @ 0..=15)] => (column << 4 | row, None),
+            [_, _] => (0, Some("Columns are 0 to 7, and rows 0 to 15")),
+            _ => return None,
+        };
+
+        let message = match (message, char::from_u32(code)) {
+            (None, Some(c)) => return Some(Some(c)),
+            (Some(message), _) => message.to_string(),
+            (None, None) => format!("U+{code:04X} is not a character"),
+        };
+        self.diagnostics.push(
+            Diagnostic::error("Asn1::Analysis::StringValue")
+                .name("Invalid character position")
+                .label(self.context.label(id).message(message)),
+        );
+        Some(None)
+    }
+
+    /// Collect the tokens of a node and the nodes within it
+    fn tokens(&self, id: AsnNodeId, tokens: &mut Vec<WithId<Token>>) -> Option<()> {
+        for node in self.context.tree(id, &[]).ok()? {
+            match self.context.tree(node, &[]) {
+                Ok(_) => self.tokens(node, tokens)?,
+                Err(_) => tokens.push(self.context.token(node, &[]).ok()?),
+            }
+        }
+        Some(())
+    }
+}
@@ -0,0 +1,159 @@
+//! Validation of the values of the time types, which are strings in the
+//! forms of ISO 8601 given by ITU-T X.680.
+
+use crate::{
+    ast::{Constraint, ElementSet, SubtypeElement, Type, TypeOrValue, Value, WithId},
+    cst::AsnNodeId,
+    time::{
+        Date, DateTime, Duration, GeneralizedTime, PropertySettings, Time, TimeError, TimeOfDay,
+        UtcTime,
+    },
+    Diagnostic,
+};
+
+use super::{context::AnalysisContext, environment::Environment, oid::cstring_value};
+
+impl AnalysisContext<'_> {
+    /// Report values of the time types assigned within a module that are not
+    /// in the form of their type, or do not have the property settings their
+    /// type is constrained to, and settings constraints that are invalid.
+    /// Values that are references are checked where the referenced value is
+    /// assigned.
+    pub(super) fn check_times(&mut self, module: AsnNodeId) {
+        let env = &self.cache.modules[&module];
+        let mut diagnostics = vec![];
+
+        let mut ids: Vec<_> = env.constraints.keys().collect();
+        ids.sort();
+        for id in ids {
+            let mut found = vec![];
+            for constraint in &env.constraints[id] {
+                settings_within(constraint, &mut found);
+            }
+            for settings in found {
+                if let Err(e) = cstring_value(settings).parse::<PropertySettings>() {
+                    diagnostics.push(
+                        Diagnostic::error("Asn1::Analysis::TimeSettings")
+                            .name("Invalid property settings")
+                            .label(self.label(settings.id).message(e.to_string())),
+                    );
+                }
+            }
+        }
+
+        let mut names: Vec<_> = env.variables.keys().collect();
+        names.sort();
+        for name in names {
+            let var = &env.variables[name];
+            let (Some(ty), TypeOrValue::Value(value)) = (&var.ty, &*var.value) else {
+                continue;
+            };
+            if !var.parameters.is_empty() {
+                continue;
+            }
+            let Some(builtin) = self.builtin_type(env, ty).filter(|ty| is_time(ty)) else {
+                continue;
+            };
+
+            let message = match value {
+                Value::String(text) => {
+                    let text = cstring_value(text);
+                    match parse_time(builtin, &text) {
+                        Ok(()) => match self.unpermitted_settings(env, ty, &text) {
+                            Some(settings) => {
+                                format!("The type is constrained to the settings `{settings}`")
+                            }
+                            None => continue,
+                        },
+                        Err(e) => e.to_string(),
+                    }
+                }
+                Value::Reference(_) | Value::Field(_) | Value::Unparsed => continue,
+                _ => format!("{builtin} values are strings"),
+            };
+            diagnostics.push(
+                Diagnostic::error("Asn1::Analysis::TimeValue")
+                    .name(format!("Invalid {builtin} value"))
+                    .label(self.label(var.value.id).message(message)),
+            );
+        }
+
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Find the property settings a type is constrained to that a value of
+    /// it does not have.  Settings that are invalid are reported where they
+    /// are written.
+    fn unpermitted_settings(
+        &self,
+        module: &Environment,
+        ty: &WithId<Type>,
+        text: &str,
+    ) -> Option<PropertySettings> {
+        let constraints = self.constraints_of(module, ty, ty.id, true);
+        if constraints.settings.is_empty() {
+            return None;
+        }
+        let time = text.parse::<Time>().ok()?;
+        constraints
+            .settings
+            .iter()
+            .filter_map(|settings| settings.parse::<PropertySettings>().ok())
+            .find(|settings| !settings.permits(time.settings()))
+    }
+}
+
+/// Find the property settings within a constraint, `SETTINGS "Basic=Date"`
+fn settings_within<'c>(constraint: &'c Constraint, found: &mut Vec<&'c WithId<String>>) {
+    let (Constraint::Subtype(sets) | Constraint::Table(sets)) = constraint else {
+        return;
+    };
+    let mut stack: Vec<&ElementSet> = sets.root.iter().chain(&sets.additions).collect();
+    while let Some(set) = stack.pop() {
+        match set {
+            ElementSet::Element(element) => match &**element {
+                SubtypeElement::Settings(settings) => found.push(settings),
+                SubtypeElement::Size(constraint)
+                | SubtypeElement::From(constraint)
+                | SubtypeElement::Component(constraint) => settings_within(constraint, found),
+                SubtypeElement::Components(components) => {
+                    for component in &components.components {
+                        if let Some(constraint) = &component.constraint {
+                            settings_within(constraint, found);
+                        }
+                    }
+                }
+                _ => (),
+            },
+            ElementSet::Union(sets) | ElementSet::Intersection(sets) => stack.extend(sets),
+            ElementSet::Except(set, except) => stack.extend([&**set, &**except]),
+            ElementSet::AllExcept(set) => stack.push(set),
+        }
+    }
+}
+
+fn is_time(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::UTCTime
+            | Type::GeneralizedTime
+            | Type::Time
+            | Type::Date
+            | Type::TimeOfDay
+            | Type::DateTime
+            | Type::Duration
+    )
+}
+
+/// Parse a value of one of the time types
+pub(crate) fn parse_time(ty: &Type, text: &str) -> Result<(), TimeError> {
+    match ty {
+        Type::UTCTime => text.parse::<UtcTime>().map(drop),
+        Type::GeneralizedTime => text.parse::<GeneralizedTime>().map(drop),
+        Type::Date => text.parse::<Date>().map(drop),
+        Type::TimeOfDay => text.parse::<TimeOfDay>().map(drop),
+        Type::DateTime => text.parse::<DateTime>().map(drop),
+        Type::Duration => text.parse::<Duration>().map(drop),
+        _ => text.parse::<Time>().map(drop),
+    }
+}
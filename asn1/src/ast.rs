@@ -14,7 +14,14 @@ use crate::{
     Diagnostic,
 };
 
-pub use ty_or_value::Type;
+pub use module::{Import, ModuleIdentifier, Parameter, TagDefault};
+pub use ty_or_value::{
+    ComponentType, Constraint, ElementSet, ElementSets, Endpoint, EnumItem, Extensible, Extension,
+    ExtensionAddition, ExtensionGroup, FieldKind, FieldReference, FieldSource, FieldSpec,
+    NamedNumber, NamedType, ObjIdComponent, Object, ObjectClass, Optionality,
+    ParameterizedReference, Presence, Reference, Setting, SubtypeElement, SyntaxItem, TagClass,
+    TagKind, Type, TypeOrValue, Value,
+};
 
 /// A piece of data with an associated id.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -2,10 +2,15 @@ use crate::{
     analysis::AnalysisContext,
     cst::{Asn1Tag, AsnNodeId, CstIter},
     diagnostic::Result,
+    parser::symbols_from_modules,
     token::{Token, TokenKind},
+    Diagnostic,
 };
 
-use super::{ty_or_value::Type, WithId};
+use super::{
+    ty_or_value::{Type, TypeOrValue},
+    WithId,
+};
 
 /// A group of ASN.1 assignments and settings.
 #[derive(Debug, Clone)]
@@ -22,8 +27,14 @@ pub struct ModuleDefinition {
     /// Is extensibility implied in this module
     pub extensibility: bool,
 
+    /// Symbols imported from other modules
+    pub imports: Vec<Import>,
+
     /// assigned values within the module
     pub assignments: Vec<Assignment>,
+
+    /// Assignments that could not be interpreted
+    pub invalid: Vec<InvalidAssignment>,
 }
 
 #[derive(Debug, Clone)]
@@ -59,8 +70,23 @@ pub struct ModuleOidComponent {
     pub number: Option<WithId<String>>,
 }
 
+/// A list of symbols imported from a single module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// Name of the module the symbols are imported from
+    pub module: WithId<String>,
+
+    /// The tokens of the module's assigned identifier, if present.  This is
+    /// either an object identifier value including its braces, or a defined
+    /// value.
+    pub identifier: Vec<WithId<Token>>,
+
+    /// All imported symbol names
+    pub symbols: Vec<WithId<String>>,
+}
+
 /// How tags are applied to a module
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TagDefault {
     /// All tagging is automatically calculated
     Automatic,
@@ -79,19 +105,45 @@ pub struct Assignment {
     pub name: WithId<String>,
 
     /// The value or type assigned to the name.
-    pub value: WithId<Type>,
+    pub value: WithId<TypeOrValue>,
 
-    /// A specified type within the assignment
-    pub ty: Option<WithId<()>>,
+    /// The type of the value in a value assignment
+    pub ty: Option<WithId<Type>>,
 
     /// The sort of assignment, how to interpret the values
     pub kind: AssignmentKind,
+
+    /// The dummy references of a parameterized assignment, empty if the
+    /// assignment is not parameterized.
+    pub parameters: Vec<Parameter>,
+}
+
+/// A dummy reference of a parameterized assignment, `Governor : name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    /// The type or class governing a value, value set, object or object set
+    /// parameter.  Type and class parameters have no governor.
+    pub governor: Option<WithId<Type>>,
+
+    /// The name of the dummy reference
+    pub name: WithId<String>,
+}
+
+/// An assignment that could not be interpreted
+#[derive(Debug, Clone)]
+pub struct InvalidAssignment {
+    /// The name being assigned to
+    pub name: WithId<String>,
+
+    /// The reason the assignment could not be interpreted
+    pub error: Diagnostic,
 }
 
 /// What sort of assignment is this
 #[derive(Debug, Clone)]
 pub enum AssignmentKind {
     TypeAssignment,
+    ValueAssignment,
 }
 
 impl AnalysisContext<'_> {
@@ -112,7 +164,16 @@ impl AnalysisContext<'_> {
         self.token(iter.next(), TokenKind::Assignment)?;
         self.token(iter.next(), TokenKind::KwBegin)?;
 
-        let assignments = self.assignments(&mut iter)?;
+        if self.tree(iter.peek(), Asn1Tag::Exports).is_ok() {
+            iter.next();
+        }
+        let imports = self.imports(&mut iter)?;
+
+        let (assignments, invalid) = self.assignments(&mut iter)?;
+
+        if self.tree(iter.peek(), Asn1Tag::EncodingControl).is_ok() {
+            iter.next();
+        }
         self.token(iter.next(), TokenKind::KwEnd)?;
         iter.assert_empty()?;
 
@@ -121,7 +182,9 @@ impl AnalysisContext<'_> {
             encoding_reference,
             tag_default,
             extensibility,
+            imports,
             assignments,
+            invalid,
         })
     }
 
@@ -267,9 +330,66 @@ impl AnalysisContext<'_> {
         Ok(true)
     }
 
+    /// Interpret the imports section of a module, if present.  The parser
+    /// provides the imports as a flat list of tokens, so this splits them
+    /// into the symbols imported from each module.
+    fn imports(&self, iter: &mut CstIter) -> Result<Vec<Import>> {
+        let Ok(mut imports) = self.tree(iter.peek(), Asn1Tag::Imports) else {
+            return Ok(vec![]);
+        };
+        iter.next();
+
+        self.token(imports.next(), TokenKind::KwImports)?;
+        let list = self.tree(imports.next(), Asn1Tag::SymbolsFromModuleList)?;
+        self.token(imports.next(), TokenKind::SemiColon)?;
+        imports.assert_empty()?;
+
+        let list_id = list.node;
+        let mut tokens = vec![];
+        for tok in list {
+            tokens.push(self.token(tok, &[])?);
+        }
+
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        let groups = symbols_from_modules(&kinds)
+            .ok_or_else(|| Diagnostic::error(format!("invalid imports {list_id:?}")))?;
+
+        let mut res = vec![];
+        for group in groups {
+            let mut symbols = vec![];
+            let mut depth = 0;
+            for tok in &tokens[group.symbols] {
+                match tok.kind {
+                    TokenKind::LeftCurly => depth += 1,
+                    TokenKind::RightCurly => depth -= 1,
+                    TokenKind::TypeOrModuleRef | TokenKind::ValueRefOrIdent if depth == 0 => {
+                        symbols.push(WithId {
+                            value: self.ident_value(**tok),
+                            id: tok.id,
+                        })
+                    }
+                    _ => (),
+                }
+            }
+
+            let module = tokens[group.module];
+            res.push(Import {
+                module: WithId {
+                    value: self.ident_value(*module),
+                    id: module.id,
+                },
+                identifier: tokens[group.identifier].to_vec(),
+                symbols,
+            });
+        }
+
+        Ok(res)
+    }
+
     /// Gather the list of assignments
-    fn assignments(&self, iter: &mut CstIter) -> Result<Vec<Assignment>> {
+    fn assignments(&self, iter: &mut CstIter) -> Result<(Vec<Assignment>, Vec<InvalidAssignment>)> {
         let mut res = vec![];
+        let mut invalid = vec![];
 
         while let Some(node) = iter.peek() {
             let Ok(mut assign) = self.tree(node, Asn1Tag::Assignment) else {
@@ -277,10 +397,19 @@ impl AnalysisContext<'_> {
             };
             iter.next();
 
-            res.push(self.assignment(&mut assign)?);
+            // an assignment that cannot be interpreted does not prevent the
+            // rest of the module from being analysed
+            let name = self.tree(node, Asn1Tag::Assignment)?.next();
+            match self.assignment(&mut assign) {
+                Ok(assignment) => res.push(assignment),
+                Err(error) => invalid.push(InvalidAssignment {
+                    name: self.identifier(name)?,
+                    error,
+                }),
+            }
         }
 
-        Ok(res)
+        Ok((res, invalid))
     }
 
     /// Parse a single assignment statement
@@ -290,20 +419,83 @@ impl AnalysisContext<'_> {
             &[TokenKind::TypeOrModuleRef, TokenKind::ValueRefOrIdent],
         )?;
 
-        let (value, kind) = if name.kind == TokenKind::TypeOrModuleRef {
-            self.type_assignment(iter)?
+        let mut parameters = vec![];
+        if let Ok(mut list) = self.tree(iter.peek(), Asn1Tag::ParameterList) {
+            iter.next();
+            parameters = self.parameters(&mut list)?;
+        }
+
+        let (value, ty, kind) = if name.kind == TokenKind::TypeOrModuleRef {
+            let (value, kind) = self.type_assignment(iter)?;
+            (value, None, kind)
         } else {
-            todo!()
+            let (value, ty, kind) = self.value_assignment(iter)?;
+            (value, Some(ty), kind)
         };
 
         Ok(Assignment {
             name: WithId {
-                value: self.token_value(*name).to_string(),
+                value: self.ident_value(*name),
                 id: name.id,
             },
             value,
-            ty: None,
+            ty,
             kind,
+            parameters,
         })
     }
+
+    /// Parse the dummy references of a parameterized assignment
+    fn parameters(&self, iter: &mut CstIter) -> Result<Vec<Parameter>> {
+        let mut parameters = vec![];
+
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+        loop {
+            let mut parameter = self.tree(iter.next(), Asn1Tag::Parameter)?;
+            let mut first = self.tree(parameter.next(), Asn1Tag::TypeOrValue)?;
+
+            let parameter = if self.token(parameter.peek(), TokenKind::Colon).is_ok() {
+                parameter.next();
+                let governor = self.ty(&mut first)?;
+                let name = self.identifier(parameter.next())?;
+                parameter.assert_empty()?;
+
+                Parameter {
+                    governor: Some(governor),
+                    name,
+                }
+            } else {
+                parameter.assert_empty()?;
+                let mut defined = self.tree(first.next(), Asn1Tag::Defined)?;
+                first.assert_empty()?;
+                let name = self.identifier(defined.next())?;
+                defined.assert_empty()?;
+
+                // only types and classes can be passed without a governor
+                if name.starts_with(char::is_lowercase) {
+                    return Err(Diagnostic::error("Asn1::Analysis::Parameter")
+                        .name(format!("Parameter `{}` has no governor", *name))
+                        .label(self.label(name.id).message("Parameter defined here"))
+                        .label(format!(
+                            "Value and object parameters name their type or class, `Type : {}`",
+                            *name
+                        )));
+                }
+
+                Parameter {
+                    governor: None,
+                    name,
+                }
+            };
+            parameters.push(parameter);
+
+            let tok = self.token(iter.next(), &[TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::RightCurly {
+                break;
+            }
+        }
+        iter.assert_empty()?;
+
+        Ok(parameters)
+    }
 }
@@ -1,16 +1,38 @@
+mod class;
+mod composite_ty;
+mod constraint;
+mod display;
+mod value;
+
 use crate::{
     analysis::AnalysisContext,
-    cst::{Asn1Tag, CstIter},
+    cst::{Asn1Tag, AsnNodeId, CstIter},
     diagnostic::Result,
-    token::TokenKind,
+    token::{Token, TokenKind},
+    Diagnostic,
 };
 
 use super::{module::AssignmentKind, WithId};
 
+pub use class::{
+    FieldKind, FieldReference, FieldSource, FieldSpec, Object, ObjectClass, Optionality, Setting,
+    SyntaxItem,
+};
+pub use constraint::{Constraint, ElementSet, ElementSets, Endpoint, Presence, SubtypeElement};
+pub use value::{ObjIdComponent, Value};
+
+/// The right hand side of an assignment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeOrValue {
+    Type(Type),
+    Value(Value),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     Boolean,
     Null,
+    ObjectIdentifier,
     OidIri,
     GeneralizedTime,
     UTCTime,
@@ -37,6 +59,233 @@ pub enum Type {
     UTF8String,
     VideotexString,
     VisibleString,
+    OctetString,
+    CharacterString,
+    EmbeddedPdv,
+
+    /// `INTEGER`, with its named numbers
+    Integer(Vec<NamedNumber>),
+
+    /// `BIT STRING`, with its named bits
+    BitString(Vec<NamedNumber>),
+
+    /// `ENUMERATED { ... }`
+    Enumerated(Extensible<EnumItem>),
+
+    /// `SEQUENCE { ... }`
+    Sequence(Extensible<ComponentType>),
+
+    /// `SET { ... }`
+    Set(Extensible<ComponentType>),
+
+    /// `SEQUENCE OF Type`
+    SequenceOf(Box<OfType>),
+
+    /// `SET OF Type`
+    SetOf(Box<OfType>),
+
+    /// `CHOICE { ... }`
+    Choice(Extensible<NamedType>),
+
+    /// A type with a tag prefix, `[APPLICATION 1] IMPLICIT Type`
+    Tagged(Box<TaggedType>),
+
+    /// A selection type, `alternative < Type`
+    Selection(WithId<String>, Box<WithId<Type>>),
+
+    /// A reference to a type defined by a type assignment
+    Reference(Reference),
+
+    /// An information object class, `CLASS { ... }`
+    Class(ObjectClass),
+
+    /// The type of a field of an information object class or of an
+    /// information object, `CLASS.&id` or `object.&Type`
+    Field(Box<FieldReference>),
+
+    /// An instance of a parameterized type, `Name { Type, value }`
+    Parameterized(Box<ParameterizedReference>),
+}
+
+/// A reference to an assignment, either in the same module, imported, or an
+/// external reference `Module.name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// Name of the module in an external reference
+    pub module: Option<WithId<String>>,
+
+    /// The referenced name
+    pub name: WithId<String>,
+}
+
+/// A reference to a parameterized assignment with its actual parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterizedReference {
+    /// The referenced parameterized assignment
+    pub reference: Reference,
+
+    /// The actual parameters, in the order of the dummy references
+    pub parameters: Vec<WithId<TypeOrValue>>,
+}
+
+/// The parts of a reference parsed from a defined node
+struct DefinedReference {
+    /// The referenced name
+    reference: Reference,
+
+    /// The actual parameters of a parameterized reference
+    parameters: Option<Vec<WithId<TypeOrValue>>>,
+
+    /// The field name, if the reference is to a field of a class or object
+    field: Option<WithId<String>>,
+}
+
+/// A list of items that can have an extension marker and extension additions,
+/// used by sequence, set, choice and enumerated types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extensible<T> {
+    /// Items in the extension root before any extension marker
+    pub root: Vec<T>,
+
+    /// The extension marker and additions, if present
+    pub extension: Option<Extension<T>>,
+}
+
+/// Extension additions to a type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extension<T> {
+    /// Node ID of the extension marker
+    pub id: AsnNodeId,
+
+    /// Extension additions, in the order they were added
+    pub additions: Vec<ExtensionAddition<T>>,
+
+    /// Items in the extension root after the closing extension marker
+    pub root: Vec<T>,
+}
+
+/// A single extension addition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionAddition<T> {
+    /// An addition of a single item
+    Single(T),
+
+    /// A group of items added together, `[[ 2: a INTEGER, b BOOLEAN ]]`
+    Group(ExtensionGroup<T>),
+}
+
+/// A group of items within an extension addition group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionGroup<T> {
+    /// The version number of the group, if specified
+    pub version: Option<WithId<String>>,
+
+    /// The items within the group
+    pub items: Vec<T>,
+}
+
+/// A component of a sequence or set type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentType {
+    /// A named component
+    Named(Component),
+
+    /// `COMPONENTS OF Type`, including all root components of another type
+    ComponentsOf(WithId<Type>),
+}
+
+/// A named component of a sequence or set type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    /// The component's identifier
+    pub name: WithId<String>,
+
+    /// The type of the component
+    pub ty: WithId<Type>,
+
+    /// Is the component marked `OPTIONAL`
+    pub optional: bool,
+
+    /// The `DEFAULT` value of the component
+    pub default: Option<WithId<Value>>,
+}
+
+/// A type with an identifier, used for choice alternatives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedType {
+    /// The identifier of the type
+    pub name: WithId<String>,
+
+    /// The type
+    pub ty: WithId<Type>,
+}
+
+/// The element type of a `SEQUENCE OF` or `SET OF` type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfType {
+    /// Optional identifier of the elements
+    pub name: Option<WithId<String>>,
+
+    /// The type of the elements
+    pub ty: WithId<Type>,
+}
+
+/// A named number of an integer type, or named bit of a bit string type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedNumber {
+    /// The identifier
+    pub name: WithId<String>,
+
+    /// The number, either a number or a reference to an integer value
+    pub value: WithId<Value>,
+}
+
+/// An item of an enumerated type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumItem {
+    /// The identifier
+    pub name: WithId<String>,
+
+    /// The number of the item, if specified
+    pub value: Option<WithId<Value>>,
+}
+
+/// A tagged type, `[APPLICATION 1] IMPLICIT Type`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedType {
+    /// The class of the tag
+    pub class: TagClass,
+
+    /// The tag number, either a number or a reference to an integer value
+    pub number: WithId<Value>,
+
+    /// How the tag is applied to the type
+    pub kind: TagKind,
+
+    /// The type being tagged
+    pub ty: WithId<Type>,
+}
+
+/// The class of a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagClass {
+    Universal,
+    Application,
+    Private,
+    ContextSpecific,
+}
+
+/// How a tag is applied to a type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagKind {
+    /// Neither `IMPLICIT` nor `EXPLICIT`, uses the module's tag default
+    Default,
+
+    /// `IMPLICIT`
+    Implicit,
+
+    /// `EXPLICIT`
+    Explicit,
 }
 
 impl AnalysisContext<'_> {
@@ -44,58 +293,529 @@ impl AnalysisContext<'_> {
     pub(super) fn type_assignment(
         &self,
         iter: &mut CstIter,
-    ) -> Result<(WithId<Type>, AssignmentKind)> {
+    ) -> Result<(WithId<TypeOrValue>, AssignmentKind)> {
         let mut inner = self.tree(iter.next(), Asn1Tag::TypeAssignment)?;
         iter.assert_empty()?;
 
+        if self.token(inner.peek(), TokenKind::Assignment).is_err() {
+            return Err(self.unsupported(inner.node, "Value set assignments"));
+        }
+
         self.token(inner.next(), TokenKind::Assignment)?;
         let mut ty = self.tree(inner.next(), Asn1Tag::TypeOrValue)?;
         inner.assert_empty()?;
 
-        let ret = self.type_or_value(&mut ty)?;
-
-        Ok((ret, AssignmentKind::TypeAssignment))
-    }
-
-    /// Parse either a type or value
-    fn type_or_value(&self, iter: &mut CstIter) -> Result<WithId<Type>> {
-        let first = self.token(iter.next(), &[])?;
-
-        let result = match first.kind {
-            TokenKind::KwBoolean => Type::Boolean,
-            TokenKind::KwNull => Type::Null,
-            TokenKind::KwOidIri => Type::OidIri,
-            TokenKind::KwGeneralizedTime => Type::GeneralizedTime,
-            TokenKind::KwUTCTime => Type::UTCTime,
-            TokenKind::KwObjectDescriptor => Type::ObjectDescriptor,
-            TokenKind::KwReal => Type::Real,
-            TokenKind::KwRelativeOid => Type::RelativeOid,
-            TokenKind::KwRelativeOidIri => Type::RelativeOidIri,
-            TokenKind::KwExternal => Type::External,
-            TokenKind::KwTime => Type::Time,
-            TokenKind::KwDate => Type::Date,
-            TokenKind::KwTimeOfDay => Type::TimeOfDay,
-            TokenKind::KwDateTime => Type::DateTime,
-            TokenKind::KwDuration => Type::Duration,
-            TokenKind::KwBmpString => Type::BmpString,
-            TokenKind::KwGeneralString => Type::GeneralString,
-            TokenKind::KwGraphicString => Type::GraphicString,
-            TokenKind::KwIA5String => Type::IA5String,
-            TokenKind::KwISO64String => Type::ISO64String,
-            TokenKind::KwNumericString => Type::NumericString,
-            TokenKind::KwPrintableString => Type::PrintableString,
-            TokenKind::KwTeletexString => Type::TeletexString,
-            TokenKind::KwT61String => Type::T61String,
-            TokenKind::KwUniversalString => Type::UniversalString,
-            TokenKind::KwUTF8String => Type::UTF8String,
-            TokenKind::KwVideotexString => Type::VideotexString,
-            TokenKind::KwVisibleString => Type::VisibleString,
-            _ => todo!(),
+        let ret = self.ty(&mut ty)?;
+
+        Ok((
+            WithId {
+                value: TypeOrValue::Type(ret.value),
+                id: ret.id,
+            },
+            AssignmentKind::TypeAssignment,
+        ))
+    }
+
+    /// Parse a value assignment, returning the value and its type
+    pub(super) fn value_assignment(
+        &self,
+        iter: &mut CstIter,
+    ) -> Result<(WithId<TypeOrValue>, WithId<Type>, AssignmentKind)> {
+        let mut inner = self.tree(iter.next(), Asn1Tag::ValueAssignment)?;
+        iter.assert_empty()?;
+
+        if self.token(inner.peek(), TokenKind::Assignment).is_ok() {
+            return Err(self.unsupported(inner.node, "XML value assignments"));
+        }
+
+        let mut ty = self.tree(inner.next(), Asn1Tag::TypeOrValue)?;
+        let ty = self.ty(&mut ty)?;
+        self.token(inner.next(), TokenKind::Assignment)?;
+        if self.tree(inner.peek(), Asn1Tag::XMLValue).is_ok() {
+            return Err(self.unsupported(inner.node, "XML values"));
+        }
+        let mut value = self.tree(inner.next(), Asn1Tag::TypeOrValue)?;
+        inner.assert_empty()?;
+
+        let value = self.value(&mut value, Some(&ty))?;
+
+        Ok((
+            WithId {
+                value: TypeOrValue::Value(value.value),
+                id: value.id,
+            },
+            ty,
+            AssignmentKind::ValueAssignment,
+        ))
+    }
+
+    /// Parse a type from a type or value node
+    pub(super) fn ty(&self, iter: &mut CstIter) -> Result<WithId<Type>> {
+        let id = iter.node;
+        let Some(first) = iter.next() else {
+            return Err(Diagnostic::error(format!("empty type {id:?}")));
+        };
+
+        let result = if let Ok(mut tree) = self.tree(first, &[]) {
+            match tree.tag {
+                Asn1Tag::ObjectIDType => Type::ObjectIdentifier,
+                Asn1Tag::IntegerType => {
+                    self.token(tree.next(), TokenKind::KwInteger)?;
+                    Type::Integer(self.named_numbers(&mut tree)?)
+                }
+                Asn1Tag::BitStringType => {
+                    self.token(tree.next(), TokenKind::KwBit)?;
+                    self.token(tree.next(), TokenKind::KwString)?;
+                    Type::BitString(self.named_numbers(&mut tree)?)
+                }
+                Asn1Tag::OctetStringType => {
+                    let tok =
+                        self.token(tree.next(), &[TokenKind::KwOctet, TokenKind::KwCharacter])?;
+                    if tok.kind == TokenKind::KwOctet {
+                        Type::OctetString
+                    } else {
+                        Type::CharacterString
+                    }
+                }
+                Asn1Tag::EmbeddedPDVType => Type::EmbeddedPdv,
+                Asn1Tag::EnumeratedType => self.enumerated_type(&mut tree)?,
+                Asn1Tag::SequenceType | Asn1Tag::SetType => self.sequence_set_type(&mut tree)?,
+                Asn1Tag::ChoiceType => self.choice_type(&mut tree)?,
+                Asn1Tag::PrefixType => self.prefix_type(&mut tree)?,
+                Asn1Tag::Defined => self.defined_type(&mut tree)?,
+                Asn1Tag::ObjectFields => self.object_fields(&mut tree)?,
+                Asn1Tag::InstanceOfType => return Err(self.unsupported(first, "INSTANCE OF types")),
+                Asn1Tag::ObjectClass => Type::Class(self.object_class(&mut tree)?),
+                _ => return Err(self.unsupported(first, "Values in place of types")),
+            }
+        } else {
+            let first = self.token(first, &[])?;
+            match keyword_type(first.kind) {
+                Some(ty) => ty,
+                None => return Err(self.unsupported(first.id, "Values in place of types")),
+            }
+        };
+
+        if self.tree(iter.peek(), Asn1Tag::OpenTypeFieldValue).is_ok() {
+            return Err(self.unsupported(id, "Open type field values"));
+        }
+        self.skip_constraints(iter);
+        iter.assert_empty()?;
+
+        Ok(WithId { value: result, id })
+    }
+
+    /// Skip the constraints following a type, which are interpreted for the
+    /// whole module by `type_constraints`
+    pub(super) fn skip_constraints(&self, iter: &mut CstIter) {
+        while self.tree(iter.peek(), Asn1Tag::Constraint).is_ok() {
+            iter.next();
+        }
+    }
+
+    /// Parse the optional list of named numbers of an integer or bit string
+    /// type, `{ one(1), two(2) }`
+    fn named_numbers(&self, iter: &mut CstIter) -> Result<Vec<NamedNumber>> {
+        let mut numbers = vec![];
+        if iter.peek().is_none() {
+            return Ok(numbers);
+        }
+
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+        loop {
+            let mut number = self.tree(iter.next(), Asn1Tag::NamedNumber)?;
+            let name = self.identifier(number.next())?;
+            self.token(number.next(), TokenKind::LeftParen)?;
+            let value = self.value(&mut self.tree(number.next(), Asn1Tag::TypeOrValue)?, None)?;
+            self.token(number.next(), TokenKind::RightParen)?;
+            number.assert_empty()?;
+
+            numbers.push(NamedNumber { name, value });
+
+            let tok = self.token(iter.next(), &[TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::RightCurly {
+                break;
+            }
+        }
+        iter.assert_empty()?;
+
+        Ok(numbers)
+    }
+
+    /// Parse a prefixed type, which must be a tagged type as encoding
+    /// instructions are not supported.
+    /// `"[" ( UNIVERSAL | APPLICATION | PRIVATE )? number "]" ( IMPLICIT | EXPLICIT )? Type`
+    fn prefix_type(&self, iter: &mut CstIter) -> Result<Type> {
+        let prefix = iter.node;
+        self.token(iter.next(), TokenKind::LeftSquare)?;
+
+        let mut class = TagClass::ContextSpecific;
+        let tok = self.token(iter.peek(), &[])?;
+        match tok.kind {
+            TokenKind::KwUniversal => class = TagClass::Universal,
+            TokenKind::KwApplication => class = TagClass::Application,
+            TokenKind::KwPrivate => class = TagClass::Private,
+            _ => (),
+        }
+        if class != TagClass::ContextSpecific {
+            iter.next();
+        }
+
+        let tok = self.token(iter.next(), &[])?;
+        let number = match tok.kind {
+            TokenKind::Number => Value::Number(self.token_value(*tok).to_string()),
+            TokenKind::ValueRefOrIdent => Value::Reference(Reference {
+                module: None,
+                name: self.identifier(tok.id)?,
+            }),
+            TokenKind::TypeOrModuleRef => {
+                if self.token(iter.next(), TokenKind::Dot).is_err() {
+                    return Err(self.unsupported(prefix, "Encoding prefixes"));
+                }
+                Value::Reference(Reference {
+                    module: Some(self.identifier(tok.id)?),
+                    name: self.identifier(iter.next())?,
+                })
+            }
+            _ => return Err(self.unsupported(prefix, "Encoding prefixes")),
+        };
+        let number = WithId {
+            value: number,
+            id: tok.id,
+        };
+        if self.token(iter.next(), TokenKind::RightSquare).is_err() {
+            return Err(self.unsupported(prefix, "Encoding prefixes"));
+        }
+
+        let tok = self.token(iter.peek(), &[]);
+        let kind = match tok.map(|tok| tok.kind) {
+            Ok(TokenKind::KwImplicit) => TagKind::Implicit,
+            Ok(TokenKind::KwExplicit) => TagKind::Explicit,
+            _ => TagKind::Default,
+        };
+        if kind != TagKind::Default {
+            iter.next();
+        }
+
+        let ty = self.ty(&mut self.tree(iter.next(), Asn1Tag::TypeOrValue)?)?;
+        iter.assert_empty()?;
+
+        Ok(Type::Tagged(Box::new(TaggedType {
+            class,
+            number,
+            kind,
+            ty,
+        })))
+    }
+
+    /// Parse a defined node used as a type, either a type reference, a
+    /// selection type or a reference to a field of a class or object.
+    fn defined_type(&self, iter: &mut CstIter) -> Result<Type> {
+        let defined = iter.node;
+        let tok = self.token(
+            iter.next(),
+            &[TokenKind::TypeOrModuleRef, TokenKind::ValueRefOrIdent],
+        )?;
+
+        if tok.kind == TokenKind::ValueRefOrIdent {
+            if let Ok(mut selection) = self.tree(iter.peek(), Asn1Tag::SelectionType) {
+                let name = self.identifier(tok.id)?;
+                iter.next();
+                iter.assert_empty()?;
+
+                self.token(selection.next(), TokenKind::Less)?;
+                let ty = self.ty(&mut self.tree(selection.next(), Asn1Tag::TypeOrValue)?)?;
+                selection.assert_empty()?;
+
+                return Ok(Type::Selection(name, Box::new(ty)));
+            }
+        }
+
+        let DefinedReference {
+            reference,
+            parameters,
+            field,
+        } = self.reference(tok, iter)?;
+        let Some(field) = field else {
+            if tok.kind == TokenKind::ValueRefOrIdent {
+                return Err(self.unsupported(defined, "Values in place of types"));
+            }
+            return Ok(match parameters {
+                Some(parameters) => Type::Parameterized(Box::new(ParameterizedReference {
+                    reference,
+                    parameters,
+                })),
+                None => Type::Reference(reference),
+            });
+        };
+        if parameters.is_some() {
+            return Err(self.unsupported(defined, "Fields of parameterized references"));
+        }
+
+        let source = match tok.kind {
+            TokenKind::ValueRefOrIdent => FieldSource::Object(reference),
+            _ if reference.name.starts_with(char::is_lowercase) => FieldSource::Object(reference),
+            _ => FieldSource::Class(Box::new(WithId {
+                value: Type::Reference(reference),
+                id: defined,
+            })),
         };
+        Ok(Type::Field(Box::new(FieldReference { source, field })))
+    }
+
+    /// Parse the remainder of a reference to a type or value, where the first
+    /// name has already been consumed from the defined node.
+    fn reference(&self, first: WithId<Token>, iter: &mut CstIter) -> Result<DefinedReference> {
+        let defined = iter.node;
+        let mut reference = Reference {
+            module: None,
+            name: self.identifier(first.id)?,
+        };
+        let mut field = None;
+
+        if self.token(iter.peek(), TokenKind::Dot).is_ok() {
+            iter.next();
+            let tok = self.token(
+                iter.next(),
+                &[
+                    TokenKind::ValueRefOrIdent,
+                    TokenKind::TypeOrModuleRef,
+                    TokenKind::TypeField,
+                    TokenKind::ValueField,
+                ],
+            )?;
+            if matches!(tok.kind, TokenKind::TypeField | TokenKind::ValueField) {
+                field = Some(WithId {
+                    value: self.token_value(*tok).to_string(),
+                    id: tok.id,
+                });
+            } else {
+                reference.module = Some(reference.name);
+                reference.name = self.identifier(tok.id)?;
+            }
+        }
+
+        let mut parameters = None;
+        if let Ok(mut list) = self.tree(iter.peek(), Asn1Tag::ActualParameterList) {
+            iter.next();
+            if field.is_some() {
+                return Err(self.unsupported(defined, "Fields of parameterized references"));
+            }
+            parameters = Some(self.actual_parameters(&mut list)?);
+        }
+
+        if let Ok(mut names) = self.tree(iter.peek(), Asn1Tag::FieldNames) {
+            iter.next();
+            if field.is_some() {
+                return Err(self.unsupported(defined, "Nested information object field references"));
+            }
+            field = Some(self.field_name(defined, &mut names)?);
+        }
+        iter.assert_empty()?;
 
+        Ok(DefinedReference {
+            reference,
+            parameters,
+            field,
+        })
+    }
+
+    /// Parse the actual parameters of a parameterized reference.  Each
+    /// parameter is interpreted as a type where possible, otherwise as a
+    /// value, as which is expected depends on the referenced assignment.
+    fn actual_parameters(&self, iter: &mut CstIter) -> Result<Vec<WithId<TypeOrValue>>> {
+        let mut parameters = vec![];
+
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+        loop {
+            let node = iter.next();
+            let parameter = match self.ty(&mut self.tree(node, Asn1Tag::TypeOrValue)?) {
+                Ok(ty) => WithId {
+                    value: TypeOrValue::Type(ty.value),
+                    id: ty.id,
+                },
+                Err(e) => {
+                    let Ok(value) = self.value(&mut self.tree(node, Asn1Tag::TypeOrValue)?, None)
+                    else {
+                        return Err(e);
+                    };
+                    WithId {
+                        value: TypeOrValue::Value(value.value),
+                        id: value.id,
+                    }
+                }
+            };
+            parameters.push(parameter);
+
+            let tok = self.token(iter.next(), &[TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::RightCurly {
+                break;
+            }
+        }
+        iter.assert_empty()?;
+
+        Ok(parameters)
+    }
+
+    /// Get the normalised identifier of a name or reference token
+    pub(super) fn identifier(&self, node: impl Into<Option<AsnNodeId>>) -> Result<WithId<String>> {
+        let tok = self.token(
+            node,
+            &[TokenKind::ValueRefOrIdent, TokenKind::TypeOrModuleRef],
+        )?;
         Ok(WithId {
-            value: result,
-            id: iter.node,
+            value: self.ident_value(*tok),
+            id: tok.id,
+        })
+    }
+
+    /// Create an error for a language feature that analysis does not support
+    fn unsupported(&self, node: AsnNodeId, what: &str) -> Diagnostic {
+        Diagnostic::error("Asn1::Analysis::Unsupported")
+            .name(format!("{what} are not supported"))
+            .label(self.label(node).message("Used here"))
+    }
+}
+
+/// Get the type a single keyword token represents, if any
+fn keyword_type(kind: TokenKind) -> Option<Type> {
+    Some(match kind {
+        TokenKind::KwBoolean => Type::Boolean,
+        TokenKind::KwNull => Type::Null,
+        TokenKind::KwOidIri => Type::OidIri,
+        TokenKind::KwGeneralizedTime => Type::GeneralizedTime,
+        TokenKind::KwUTCTime => Type::UTCTime,
+        TokenKind::KwObjectDescriptor => Type::ObjectDescriptor,
+        TokenKind::KwReal => Type::Real,
+        TokenKind::KwRelativeOid => Type::RelativeOid,
+        TokenKind::KwRelativeOidIri => Type::RelativeOidIri,
+        TokenKind::KwExternal => Type::External,
+        TokenKind::KwTime => Type::Time,
+        TokenKind::KwDate => Type::Date,
+        TokenKind::KwTimeOfDay => Type::TimeOfDay,
+        TokenKind::KwDateTime => Type::DateTime,
+        TokenKind::KwDuration => Type::Duration,
+        TokenKind::KwBmpString => Type::BmpString,
+        TokenKind::KwGeneralString => Type::GeneralString,
+        TokenKind::KwGraphicString => Type::GraphicString,
+        TokenKind::KwIA5String => Type::IA5String,
+        TokenKind::KwISO64String => Type::ISO64String,
+        TokenKind::KwNumericString => Type::NumericString,
+        TokenKind::KwPrintableString => Type::PrintableString,
+        TokenKind::KwTeletexString => Type::TeletexString,
+        TokenKind::KwT61String => Type::T61String,
+        TokenKind::KwUniversalString => Type::UniversalString,
+        TokenKind::KwUTF8String => Type::UTF8String,
+        TokenKind::KwVideotexString => Type::VideotexString,
+        TokenKind::KwVisibleString => Type::VisibleString,
+        _ => return None,
+    })
+}
+
+impl<T> Extensible<T> {
+    /// Get every item, whether in the root or an extension addition, in the
+    /// order they appear in the source.
+    pub fn items(&self) -> impl Iterator<Item = &T> {
+        let extension = self.extension.iter();
+        self.root
+            .iter()
+            .chain(
+                extension
+                    .clone()
+                    .flat_map(|ext| ext.additions.iter().flat_map(ExtensionAddition::items)),
+            )
+            .chain(extension.flat_map(|ext| ext.root.iter()))
+    }
+
+    /// Get mutable references to every item, in the order they appear in the
+    /// source.
+    pub fn items_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        let (additions, root) = match &mut self.extension {
+            Some(ext) => (ext.additions.as_mut_slice(), ext.root.as_mut_slice()),
+            None => (&mut [][..], &mut [][..]),
+        };
+        self.root
+            .iter_mut()
+            .chain(additions.iter_mut().flat_map(ExtensionAddition::items_mut))
+            .chain(root.iter_mut())
+    }
+
+    /// Get the items in the extension root, not including extension additions
+    pub fn root_items(&self) -> impl Iterator<Item = &T> {
+        self.root
+            .iter()
+            .chain(self.extension.iter().flat_map(|ext| ext.root.iter()))
+    }
+}
+
+impl<T> ExtensionAddition<T> {
+    /// Get the items of the addition
+    pub fn items(&self) -> &[T] {
+        match self {
+            ExtensionAddition::Single(item) => std::slice::from_ref(item),
+            ExtensionAddition::Group(group) => &group.items,
+        }
+    }
+
+    /// Get mutable references to the items of the addition
+    pub fn items_mut(&mut self) -> &mut [T] {
+        match self {
+            ExtensionAddition::Single(item) => std::slice::from_mut(item),
+            ExtensionAddition::Group(group) => &mut group.items,
+        }
+    }
+}
+
+impl ComponentType {
+    /// Get the type of the component, or the type of the `COMPONENTS OF`
+    pub fn ty(&self) -> &WithId<Type> {
+        match self {
+            ComponentType::Named(component) => &component.ty,
+            ComponentType::ComponentsOf(ty) => ty,
+        }
+    }
+}
+
+impl Type {
+    /// Get the number of the universal tag of a built-in type
+    pub(crate) fn universal_tag(&self) -> Option<u32> {
+        Some(match self {
+            Type::Boolean => 1,
+            Type::Integer(_) => 2,
+            Type::BitString(_) => 3,
+            Type::OctetString => 4,
+            Type::Null => 5,
+            Type::ObjectIdentifier => 6,
+            Type::ObjectDescriptor => 7,
+            Type::External => 8,
+            Type::Real => 9,
+            Type::Enumerated(_) => 10,
+            Type::EmbeddedPdv => 11,
+            Type::UTF8String => 12,
+            Type::RelativeOid => 13,
+            Type::Time => 14,
+            Type::Sequence(_) | Type::SequenceOf(_) => 16,
+            Type::Set(_) | Type::SetOf(_) => 17,
+            Type::NumericString => 18,
+            Type::PrintableString => 19,
+            Type::TeletexString | Type::T61String => 20,
+            Type::VideotexString => 21,
+            Type::IA5String => 22,
+            Type::UTCTime => 23,
+            Type::GeneralizedTime => 24,
+            Type::GraphicString => 25,
+            Type::VisibleString | Type::ISO64String => 26,
+            Type::GeneralString => 27,
+            Type::UniversalString => 28,
+            Type::CharacterString => 29,
+            Type::BmpString => 30,
+            Type::Date => 31,
+            Type::TimeOfDay => 32,
+            Type::DateTime => 33,
+            Type::Duration => 34,
+            Type::OidIri => 35,
+            Type::RelativeOidIri => 36,
+            _ => return None,
         })
     }
 }
@@ -0,0 +1,756 @@
+//! information object class and information object interpretation
+
+use std::collections::HashMap;
+
+use crate::{
+    analysis::AnalysisContext,
+    ast::WithId,
+    cst::{Asn1Tag, AsnNodeId, CstIter},
+    diagnostic::Result,
+    token::{Token, TokenKind},
+    Diagnostic,
+};
+
+use super::{keyword_type, NamedNumber, Reference, Type, TypeOrValue, Value};
+
+/// An information object class, `CLASS { ... } WITH SYNTAX { ... }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectClass {
+    /// The fields of the class, in the order they are defined
+    pub fields: Vec<FieldSpec>,
+
+    /// The syntax for defining objects of the class, if specified
+    pub syntax: Option<Vec<SyntaxItem>>,
+}
+
+/// A single field of an information object class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    /// The name of the field, including the leading `&`
+    pub name: WithId<String>,
+
+    /// What kind of setting the field has
+    pub kind: FieldKind,
+
+    /// Whether objects must define the field
+    pub optionality: Optionality,
+}
+
+/// The kind of an information object class field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A type field, `&Type`
+    Type,
+
+    /// A fixed type value field, `&id INTEGER UNIQUE`.  Object fields, whose
+    /// type is an information object class, are also fixed type value fields.
+    Value { ty: WithId<Type>, unique: bool },
+
+    /// A variable type value field, whose type is given by a type field,
+    /// `&value &Type`
+    VariableTypeValue(Vec<WithId<String>>),
+
+    /// A fixed type value set field, `&Values INTEGER`.  Object set fields
+    /// are also fixed type value set fields.
+    ValueSet(WithId<Type>),
+
+    /// A variable type value set field, `&Values &Type`
+    VariableTypeValueSet(Vec<WithId<String>>),
+}
+
+/// Whether objects must define a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Optionality {
+    /// The field must be defined
+    Required,
+
+    /// `OPTIONAL`
+    Optional,
+
+    /// `DEFAULT`, the setting used if the field is not defined
+    Default(WithId<TypeOrValue>),
+}
+
+/// An item of the syntax for defining objects of a class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxItem {
+    /// A word or comma that must appear in the definition
+    Literal(WithId<String>),
+
+    /// The setting of a field
+    Field(WithId<String>),
+
+    /// A group of items that can be left out, `[ ... ]`
+    Optional(Vec<SyntaxItem>),
+}
+
+/// A reference to a field of an information object class or of an
+/// information object, `CLASS.&field` or `object.&field`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldReference {
+    /// The class or object the field belongs to
+    pub source: FieldSource,
+
+    /// The name of the field, including the leading `&`
+    pub field: WithId<String>,
+}
+
+/// The class or object a field reference refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldSource {
+    /// An information object class, either a reference to a class assignment
+    /// or a built in class such as `TYPE-IDENTIFIER`.
+    Class(Box<WithId<Type>>),
+
+    /// A reference to an information object
+    Object(Reference),
+}
+
+/// An information object, with the setting of each field that it defines
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Object {
+    /// The settings, keyed by the name of the field including the leading `&`
+    pub settings: HashMap<String, Setting>,
+}
+
+/// The setting of a single field of an information object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Setting {
+    /// The setting of a type field
+    Type(WithId<Type>),
+
+    /// The setting of a value field
+    Value(WithId<Value>),
+
+    /// The setting of a value set, object or object set field, which is not
+    /// interpreted yet.
+    Unparsed(AsnNodeId),
+}
+
+impl AnalysisContext<'_> {
+    /// Interpret an information object class definition
+    pub(super) fn object_class(&self, iter: &mut CstIter) -> Result<ObjectClass> {
+        self.token(iter.next(), TokenKind::KwClass)?;
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+
+        let mut fields = vec![];
+        for node in self.tree(iter.next(), Asn1Tag::FieldSpecList)? {
+            if self.token(node, TokenKind::Comma).is_ok() {
+                continue;
+            }
+            fields.push(self.field_spec(&mut self.tree(node, Asn1Tag::FieldSpec)?)?);
+        }
+        self.token(iter.next(), TokenKind::RightCurly)?;
+
+        let mut syntax_spec = self.tree(iter.next(), Asn1Tag::SyntaxSpec)?;
+        let syntax = if syntax_spec.peek().is_some() {
+            self.token(syntax_spec.next(), TokenKind::KwWith)?;
+            self.token(syntax_spec.next(), TokenKind::KwSyntax)?;
+            self.token(syntax_spec.next(), TokenKind::LeftCurly)?;
+            let items =
+                self.syntax_items(&mut self.tree(syntax_spec.next(), Asn1Tag::SyntaxSpecList)?)?;
+            self.token(syntax_spec.next(), TokenKind::RightCurly)?;
+            syntax_spec.assert_empty()?;
+            Some(items)
+        } else {
+            None
+        };
+        iter.assert_empty()?;
+
+        Ok(ObjectClass { fields, syntax })
+    }
+
+    /// Interpret a single field of an object class
+    fn field_spec(&self, iter: &mut CstIter) -> Result<FieldSpec> {
+        let mut spec = self.tree(
+            iter.next(),
+            &[Asn1Tag::TypeFieldSpec, Asn1Tag::ValueFieldSpec],
+        )?;
+        iter.assert_empty()?;
+
+        let tok = self.token(spec.next(), &[TokenKind::TypeField, TokenKind::ValueField])?;
+        let name = WithId {
+            value: self.token_value(*tok).to_string(),
+            id: tok.id,
+        };
+
+        let kind = if let Ok(mut ty) = self.tree(spec.peek(), Asn1Tag::TypeOrValue) {
+            spec.next();
+            let ty = self.ty(&mut ty)?;
+            match tok.kind {
+                TokenKind::TypeField => FieldKind::ValueSet(ty),
+                _ => {
+                    let unique = self.token(spec.peek(), TokenKind::KwUnique).is_ok();
+                    if unique {
+                        spec.next();
+                    }
+                    FieldKind::Value { ty, unique }
+                }
+            }
+        } else if self.tree(spec.peek(), Asn1Tag::OptionalitySpec).is_err() {
+            let path = self.field_path(&mut spec)?;
+            match tok.kind {
+                TokenKind::TypeField => FieldKind::VariableTypeValueSet(path),
+                _ => FieldKind::VariableTypeValue(path),
+            }
+        } else if tok.kind == TokenKind::TypeField {
+            FieldKind::Type
+        } else {
+            return Err(Diagnostic::error(format!(
+                "value field without a type {:?}",
+                tok.id
+            )));
+        };
+
+        let mut optionality_spec = self.tree(spec.next(), Asn1Tag::OptionalitySpec)?;
+        spec.assert_empty()?;
+
+        let optionality = match self.token(optionality_spec.next(), &[]) {
+            Ok(tok) if tok.kind == TokenKind::KwOptional => Optionality::Optional,
+            Ok(tok) if tok.kind == TokenKind::KwDefault => {
+                let mut default = self.tree(optionality_spec.next(), Asn1Tag::TypeOrValue)?;
+                let default = match &kind {
+                    FieldKind::Type => {
+                        let ty = self.ty(&mut default)?;
+                        WithId {
+                            value: TypeOrValue::Type(ty.value),
+                            id: ty.id,
+                        }
+                    }
+                    FieldKind::Value { ty, .. } => {
+                        let value = self.value(&mut default, Some(ty))?;
+                        WithId {
+                            value: TypeOrValue::Value(value.value),
+                            id: value.id,
+                        }
+                    }
+                    _ => {
+                        let value = self.value(&mut default, None)?;
+                        WithId {
+                            value: TypeOrValue::Value(value.value),
+                            id: value.id,
+                        }
+                    }
+                };
+                Optionality::Default(default)
+            }
+            _ => Optionality::Required,
+        };
+        optionality_spec.assert_empty()?;
+
+        Ok(FieldSpec {
+            name,
+            kind,
+            optionality,
+        })
+    }
+
+    /// Interpret the field names giving the type of a variable type field,
+    /// `&Type.&Inner`
+    fn field_path(&self, iter: &mut CstIter) -> Result<Vec<WithId<String>>> {
+        let tok = self.token(iter.next(), &[TokenKind::TypeField, TokenKind::ValueField])?;
+        let mut path = vec![WithId {
+            value: self.token_value(*tok).to_string(),
+            id: tok.id,
+        }];
+
+        if let Ok(names) = self.tree(iter.peek(), Asn1Tag::FieldNames) {
+            iter.next();
+            for node in names {
+                let tok = self.token(
+                    node,
+                    &[TokenKind::Dot, TokenKind::TypeField, TokenKind::ValueField],
+                )?;
+                if tok.kind != TokenKind::Dot {
+                    path.push(WithId {
+                        value: self.token_value(*tok).to_string(),
+                        id: tok.id,
+                    });
+                }
+            }
+        }
+
+        Ok(path)
+    }
+
+    /// Interpret the items of a syntax specification
+    fn syntax_items(&self, iter: &mut CstIter) -> Result<Vec<SyntaxItem>> {
+        let mut items = vec![];
+
+        for node in iter {
+            if let Ok(mut optional) = self.tree(node, Asn1Tag::OptionalSyntaxSpec) {
+                self.token(optional.next(), TokenKind::LeftSquare)?;
+                let group =
+                    self.syntax_items(&mut self.tree(optional.next(), Asn1Tag::SyntaxSpecList)?)?;
+                self.token(optional.next(), TokenKind::RightSquare)?;
+                optional.assert_empty()?;
+
+                if !matches!(group.first(), Some(SyntaxItem::Literal(_))) {
+                    return Err(Diagnostic::error("Asn1::Analysis::SyntaxSpec")
+                        .name("Optional groups of a syntax specification must start with a word")
+                        .label(self.label(node).message("Optional group defined here")));
+                }
+                items.push(SyntaxItem::Optional(group));
+                continue;
+            }
+
+            let tok = self.token(node, &[])?;
+            let text = WithId {
+                value: self.token_value(*tok).to_string(),
+                id: tok.id,
+            };
+            match tok.kind {
+                TokenKind::TypeField | TokenKind::ValueField => items.push(SyntaxItem::Field(text)),
+                _ => items.push(SyntaxItem::Literal(text)),
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Interpret a reference to a field of a built in information object
+    /// class, `TYPE-IDENTIFIER.&Type`, or the built in class itself.
+    pub(super) fn object_fields(&self, iter: &mut CstIter) -> Result<Type> {
+        let id = iter.node;
+        let tok = self.token(
+            iter.next(),
+            &[TokenKind::KwTypeIdentifier, TokenKind::KwAbstractSyntax],
+        )?;
+        let class = WithId {
+            value: Type::Class(builtin_class(tok.kind, tok.id)),
+            id: tok.id,
+        };
+
+        let Ok(mut names) = self.tree(iter.next(), Asn1Tag::FieldNames) else {
+            return Ok(class.value);
+        };
+        iter.assert_empty()?;
+
+        let field = self.field_name(id, &mut names)?;
+        Ok(Type::Field(Box::new(FieldReference {
+            source: FieldSource::Class(Box::new(class)),
+            field,
+        })))
+    }
+
+    /// Interpret a field names node containing a single field, `.&field`
+    pub(super) fn field_name(
+        &self,
+        defined: AsnNodeId,
+        iter: &mut CstIter,
+    ) -> Result<WithId<String>> {
+        self.token(iter.next(), TokenKind::Dot)?;
+        let tok = self.token(iter.next(), &[TokenKind::TypeField, TokenKind::ValueField])?;
+        if iter.peek().is_some() {
+            return Err(self.unsupported(defined, "Nested information object field references"));
+        }
+
+        Ok(WithId {
+            value: self.token_value(*tok).to_string(),
+            id: tok.id,
+        })
+    }
+
+    /// Interpret the value of an information object of a class.  The parser
+    /// keeps braced values as a flat list of tokens, so the settings of each
+    /// field are found by matching the tokens against the syntax of the class.
+    pub(crate) fn object(&self, class: &ObjectClass, node: AsnNodeId) -> Result<Object> {
+        let mut value = self.tree(node, Asn1Tag::TypeOrValue)?;
+        let braced_id = value.peek();
+        let Ok(braced) = self.tree(value.next(), Asn1Tag::BracedValue) else {
+            return Err(Diagnostic::error("Asn1::Analysis::Object")
+                .name("Expected the definition of an information object")
+                .label(self.label(node).message("Expected `{ ... }` here")));
+        };
+        let braced_id = braced_id.unwrap_or(node);
+
+        let mut tokens = vec![];
+        for node in braced {
+            tokens.push(self.token(node, &[])?);
+        }
+        let inner = &tokens[1..tokens.len() - 1];
+
+        let mut matcher = ObjectMatcher {
+            context: self,
+            tokens: inner,
+            pos: 0,
+            settings: HashMap::new(),
+        };
+        match &class.syntax {
+            Some(syntax) => matcher.syntax(syntax, &[])?,
+            None => matcher.default_syntax()?,
+        }
+        if let Some(tok) = inner.get(matcher.pos) {
+            return Err(matcher.unexpected(tok));
+        }
+        let settings = matcher.settings;
+
+        let mut object = Object::default();
+        for field in &class.fields {
+            let Some(tokens) = settings.get(&field.name.value) else {
+                match &field.optionality {
+                    Optionality::Required => {
+                        return Err(Diagnostic::error("Asn1::Analysis::Object")
+                            .name(format!("Missing setting for field `{}`", *field.name))
+                            .label(self.label(braced_id).message("Object defined here"))
+                            .label(self.label(field.name.id).message("Field defined here")));
+                    }
+                    Optionality::Optional => (),
+                    Optionality::Default(default) => {
+                        let setting = match &default.value {
+                            TypeOrValue::Type(ty) => Setting::Type(WithId {
+                                value: ty.clone(),
+                                id: default.id,
+                            }),
+                            TypeOrValue::Value(value) => Setting::Value(WithId {
+                                value: value.clone(),
+                                id: default.id,
+                            }),
+                        };
+                        object.settings.insert(field.name.value.clone(), setting);
+                    }
+                }
+                continue;
+            };
+
+            let setting = match &field.kind {
+                FieldKind::Type => Setting::Type(self.setting_type(tokens)?),
+                FieldKind::Value { ty, .. } => {
+                    Setting::Value(self.setting_value(tokens, Some(ty))?)
+                }
+                FieldKind::VariableTypeValue(_) => {
+                    Setting::Value(self.setting_value(tokens, None)?)
+                }
+                FieldKind::ValueSet(_) | FieldKind::VariableTypeValueSet(_) => {
+                    Setting::Unparsed(tokens[0].id)
+                }
+            };
+            object.settings.insert(field.name.value.clone(), setting);
+        }
+
+        for (name, tokens) in &settings {
+            if !class.fields.iter().any(|field| field.name.value == *name) {
+                return Err(Diagnostic::error("Asn1::Analysis::Object")
+                    .name(format!(
+                        "The class of the object does not have a field `{name}`"
+                    ))
+                    .label(self.label(tokens[0].id).message("Setting given here")));
+            }
+        }
+
+        Ok(object)
+    }
+
+    /// Interpret the setting of a type field, which can be a built in type
+    /// without any components or constraints, or a reference to a type.
+    fn setting_type(&self, tokens: &[WithId<Token>]) -> Result<WithId<Type>> {
+        let id = tokens[0].id;
+        let kinds: Vec<_> = tokens.iter().map(|tok| tok.kind).collect();
+
+        let ty = match kinds[..] {
+            [TokenKind::KwInteger] => Type::Integer(vec![]),
+            [TokenKind::KwBit, TokenKind::KwString] => Type::BitString(vec![]),
+            [TokenKind::KwOctet, TokenKind::KwString] => Type::OctetString,
+            [TokenKind::KwCharacter, TokenKind::KwString] => Type::CharacterString,
+            [TokenKind::KwObject, TokenKind::KwIdentifier] => Type::ObjectIdentifier,
+            [TokenKind::KwEmbedded, TokenKind::KwPDV] => Type::EmbeddedPdv,
+            [TokenKind::TypeOrModuleRef] => Type::Reference(Reference {
+                module: None,
+                name: self.identifier(id)?,
+            }),
+            [TokenKind::TypeOrModuleRef, TokenKind::Dot, TokenKind::TypeOrModuleRef] => {
+                Type::Reference(Reference {
+                    module: Some(self.identifier(id)?),
+                    name: self.identifier(tokens[2].id)?,
+                })
+            }
+            [kind] => match keyword_type(kind) {
+                Some(ty) => ty,
+                None => return Err(self.unsupported(id, "Values in place of types")),
+            },
+            _ => {
+                return Err(self.unsupported(
+                    id,
+                    "Types other than keywords and references in information objects",
+                ))
+            }
+        };
+
+        Ok(WithId { value: ty, id })
+    }
+
+    /// Interpret the setting of a value field.  Values other than simple
+    /// values, references and object identifiers are not interpreted until
+    /// their type is resolved.
+    fn setting_value(&self, tokens: &[WithId<Token>], ty: Option<&Type>) -> Result<WithId<Value>> {
+        let id = tokens[0].id;
+        let kinds: Vec<_> = tokens.iter().map(|tok| tok.kind).collect();
+
+        let value = match (&kinds[..], ty) {
+            ([TokenKind::Number] | [TokenKind::Hyphen, TokenKind::Number], _) => {
+                Value::Number(tokens.iter().map(|tok| self.token_value(**tok)).collect())
+            }
+            ([TokenKind::CString], Some(Type::OidIri | Type::RelativeOidIri)) => {
+                Value::Iri(WithId {
+                    value: self.token_value(*tokens[0]).to_string(),
+                    id,
+                })
+            }
+            ([TokenKind::CString], _) => Value::String(self.token_value(*tokens[0]).to_string()),
+            ([TokenKind::KwTrue], _) => Value::Boolean(true),
+            ([TokenKind::KwFalse], _) => Value::Boolean(false),
+            ([TokenKind::KwNull], _) => Value::Null,
+            ([TokenKind::ValueRefOrIdent], _) => Value::Reference(Reference {
+                module: None,
+                name: self.identifier(id)?,
+            }),
+            ([TokenKind::TypeOrModuleRef, TokenKind::Dot, TokenKind::ValueRefOrIdent], _) => {
+                Value::Reference(Reference {
+                    module: Some(self.identifier(id)?),
+                    name: self.identifier(tokens[2].id)?,
+                })
+            }
+            ([TokenKind::LeftCurly, ..], Some(Type::ObjectIdentifier | Type::RelativeOid)) => {
+                self.object_identifier_components(id, tokens)?
+            }
+            _ => Value::Unparsed,
+        };
+
+        Ok(WithId { value, id })
+    }
+}
+
+/// Finds the tokens of each setting in the definition of an information object
+struct ObjectMatcher<'a, 'b> {
+    /// Context to get the text of tokens from
+    context: &'a AnalysisContext<'b>,
+
+    /// The tokens between the braces of the definition
+    tokens: &'a [WithId<Token>],
+
+    /// The index of the next token to match
+    pos: usize,
+
+    /// The tokens of the setting of each field
+    settings: HashMap<String, &'a [WithId<Token>]>,
+}
+
+impl<'a> ObjectMatcher<'a, '_> {
+    /// Match the default syntax, `&field setting, ...`
+    fn default_syntax(&mut self) -> Result {
+        while let Some(tok) = self.tokens.get(self.pos) {
+            if !matches!(tok.kind, TokenKind::TypeField | TokenKind::ValueField) {
+                return Err(self.unexpected(tok));
+            }
+            self.pos += 1;
+
+            let name = self.context.token_value(**tok).to_string();
+            self.setting(name, tok.id, &[","])?;
+
+            if self.pos < self.tokens.len() {
+                self.pos += 1;
+                if self.pos == self.tokens.len() {
+                    return Err(self.unexpected(&self.tokens[self.pos - 1]));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Match the items of a syntax specification.  The words that can follow
+    /// the items are used to find the end of the last setting.
+    fn syntax(&mut self, items: &[SyntaxItem], following: &[&str]) -> Result {
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                SyntaxItem::Literal(word) => match self.tokens.get(self.pos) {
+                    Some(tok) if self.context.token_value(**tok) == word.value => self.pos += 1,
+                    Some(tok) => return Err(self.expected(word, Some(tok))),
+                    None => return Err(self.expected(word, None)),
+                },
+                SyntaxItem::Field(name) => {
+                    let mut stops = vec![];
+                    let mut rest = items[i + 1..].iter();
+                    loop {
+                        match rest.next() {
+                            Some(SyntaxItem::Literal(word)) => {
+                                stops.push(word.as_str());
+                                break;
+                            }
+                            Some(SyntaxItem::Optional(group)) => {
+                                if let Some(SyntaxItem::Literal(word)) = group.first() {
+                                    stops.push(word.as_str());
+                                }
+                            }
+                            Some(SyntaxItem::Field(_)) => break,
+                            None => {
+                                stops.extend_from_slice(following);
+                                break;
+                            }
+                        }
+                    }
+
+                    let id = self.tokens.get(self.pos).map_or(name.id, |tok| tok.id);
+                    self.setting(name.value.clone(), id, &stops)?;
+                }
+                SyntaxItem::Optional(group) => {
+                    let Some(SyntaxItem::Literal(word)) = group.first() else {
+                        continue;
+                    };
+                    let present = self
+                        .tokens
+                        .get(self.pos)
+                        .is_some_and(|tok| self.context.token_value(**tok) == word.value);
+                    if present {
+                        let mut following = following.to_vec();
+                        for item in &items[i + 1..] {
+                            if let SyntaxItem::Literal(word) | SyntaxItem::Field(word) = item {
+                                following.push(word.as_str());
+                            }
+                        }
+                        self.syntax(group, &following)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record the tokens of the setting of a field, which continues until one
+    /// of the stopping words is found outside of any brackets.
+    fn setting(&mut self, name: String, id: AsnNodeId, stops: &[&str]) -> Result {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(tok) = self.tokens.get(self.pos) {
+            match tok.kind {
+                TokenKind::LeftCurly | TokenKind::LeftParen | TokenKind::LeftSquare => depth += 1,
+                TokenKind::RightCurly | TokenKind::RightParen | TokenKind::RightSquare => {
+                    depth -= 1
+                }
+                _ if depth == 0 && stops.contains(&self.context.token_value(**tok)) => break,
+                _ => (),
+            }
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Err(Diagnostic::error("Asn1::Analysis::Object")
+                .name(format!("Missing setting for field `{name}`"))
+                .label(self.context.label(id).message("Expected a setting here")));
+        }
+        if self.settings.contains_key(&name) {
+            return Err(Diagnostic::error("Asn1::Analysis::Object")
+                .name(format!("Field `{name}` is defined more than once"))
+                .label(self.context.label(id).message("Defined again here")));
+        }
+
+        self.settings.insert(name, &self.tokens[start..self.pos]);
+        Ok(())
+    }
+
+    /// Create an error for a word of the syntax that was not found
+    fn expected(&self, word: &WithId<String>, found: Option<&WithId<Token>>) -> Diagnostic {
+        let diagnostic = Diagnostic::error("Asn1::Analysis::Object").name(format!(
+            "Expected `{}` in the definition of the object",
+            **word
+        ));
+        let diagnostic = match found {
+            Some(tok) => diagnostic.label(self.context.label(tok.id).message(format!(
+                "Found `{}` instead",
+                self.context.token_value(**tok)
+            ))),
+            None => diagnostic.label(
+                self.context
+                    .label(self.tokens.last().map_or(word.id, |tok| tok.id))
+                    .message("Definition ends here"),
+            ),
+        };
+        diagnostic.label(
+            self.context
+                .label(word.id)
+                .message("Required by the syntax here"),
+        )
+    }
+
+    /// Create an error for a token that does not match the syntax
+    fn unexpected(&self, tok: &WithId<Token>) -> Diagnostic {
+        Diagnostic::error("Asn1::Analysis::Object")
+            .name(format!(
+                "Unexpected `{}` in the definition of the object",
+                self.context.token_value(**tok)
+            ))
+            .label(
+                self.context
+                    .label(tok.id)
+                    .message("Does not match the syntax of the class"),
+            )
+    }
+}
+
+/// Construct one of the built in information object classes defined by
+/// X.681 annex A, labelled with the keyword naming it.
+fn builtin_class(kind: TokenKind, id: AsnNodeId) -> ObjectClass {
+    let text = |text: &str| WithId {
+        value: text.to_string(),
+        id,
+    };
+
+    let mut fields = vec![
+        FieldSpec {
+            name: text("&id"),
+            kind: FieldKind::Value {
+                ty: WithId {
+                    value: Type::ObjectIdentifier,
+                    id,
+                },
+                unique: true,
+            },
+            optionality: Optionality::Required,
+        },
+        FieldSpec {
+            name: text("&Type"),
+            kind: FieldKind::Type,
+            optionality: Optionality::Required,
+        },
+    ];
+    let mut syntax = vec![
+        SyntaxItem::Field(text("&Type")),
+        SyntaxItem::Literal(text("IDENTIFIED")),
+        SyntaxItem::Literal(text("BY")),
+        SyntaxItem::Field(text("&id")),
+    ];
+
+    if kind == TokenKind::KwAbstractSyntax {
+        fields.push(FieldSpec {
+            name: text("&property"),
+            kind: FieldKind::Value {
+                ty: WithId {
+                    value: Type::BitString(vec![NamedNumber {
+                        name: text("handles-invalid-encodings"),
+                        value: WithId {
+                            value: Value::Number("0".to_string()),
+                            id,
+                        },
+                    }]),
+                    id,
+                },
+                unique: false,
+            },
+            optionality: Optionality::Default(WithId {
+                value: TypeOrValue::Value(Value::Unparsed),
+                id,
+            }),
+        });
+        syntax.push(SyntaxItem::Optional(vec![
+            SyntaxItem::Literal(text("HAS")),
+            SyntaxItem::Literal(text("PROPERTY")),
+            SyntaxItem::Field(text("&property")),
+        ]));
+    }
+
+    ObjectClass {
+        fields,
+        syntax: Some(syntax),
+    }
+}
@@ -0,0 +1,367 @@
+//! sequence, set, choice and enumerated type interpretation
+
+use crate::{
+    analysis::AnalysisContext,
+    ast::WithId,
+    cst::{Asn1Tag, AsnNodeId, CstIter},
+    diagnostic::Result,
+    token::TokenKind,
+    Diagnostic,
+};
+
+use super::{
+    Component, ComponentType, EnumItem, Extensible, Extension, ExtensionAddition, ExtensionGroup,
+    NamedType, OfType, Type,
+};
+
+impl AnalysisContext<'_> {
+    /// Interpret an enumerated type
+    pub(super) fn enumerated_type(&self, iter: &mut CstIter) -> Result<Type> {
+        self.token(iter.next(), TokenKind::KwEnumerated)?;
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+
+        let mut list = self.tree(iter.next(), Asn1Tag::EnumItemList)?;
+        let root = self.enum_items(&mut list)?;
+        let mut ty = Extensible {
+            root,
+            extension: None,
+        };
+
+        if let Ok(marker) = self.token(iter.peek(), TokenKind::Ellipsis) {
+            iter.next();
+            self.tree(iter.next(), Asn1Tag::ExceptionSpec)?;
+
+            let mut additions = vec![];
+            if self.token(iter.peek(), TokenKind::Comma).is_ok() {
+                iter.next();
+                let mut list = self.tree(iter.next(), Asn1Tag::EnumItemList)?;
+                additions = self.enum_items(&mut list)?;
+            }
+
+            ty.extension = Some(Extension {
+                id: marker.id,
+                additions: additions
+                    .into_iter()
+                    .map(ExtensionAddition::Single)
+                    .collect(),
+                root: vec![],
+            });
+        }
+
+        self.token(iter.next(), TokenKind::RightCurly)?;
+        iter.assert_empty()?;
+
+        Ok(Type::Enumerated(ty))
+    }
+
+    /// Interpret a comma separated list of enumeration items
+    fn enum_items(&self, iter: &mut CstIter) -> Result<Vec<EnumItem>> {
+        let mut items = vec![];
+
+        for node in iter {
+            if self.token(node, TokenKind::Comma).is_ok() {
+                continue;
+            }
+
+            let mut item = self.tree(node, Asn1Tag::EnumItem)?;
+            let name = self.identifier(item.next())?;
+            let value = if self.token(item.peek(), TokenKind::LeftParen).is_ok() {
+                item.next();
+                let value = self.value(&mut self.tree(item.next(), Asn1Tag::TypeOrValue)?, None)?;
+                self.token(item.next(), TokenKind::RightParen)?;
+                Some(value)
+            } else {
+                None
+            };
+            item.assert_empty()?;
+
+            items.push(EnumItem { name, value });
+        }
+
+        Ok(items)
+    }
+
+    /// Interpret a sequence or set type, including `SEQUENCE OF` and `SET OF`
+    pub(super) fn sequence_set_type(&self, iter: &mut CstIter) -> Result<Type> {
+        let tok = self.token(iter.next(), &[TokenKind::KwSequence, TokenKind::KwSet])?;
+        let is_sequence = tok.kind == TokenKind::KwSequence;
+
+        if self.token(iter.peek(), TokenKind::KwSize).is_ok() {
+            iter.next();
+        }
+        self.skip_constraints(iter);
+
+        if self.token(iter.peek(), TokenKind::KwOf).is_ok() {
+            iter.next();
+            let (name, ty) = self.named_type(&mut self.tree(iter.next(), Asn1Tag::TypeOrValue)?)?;
+            iter.assert_empty()?;
+
+            let of = Box::new(OfType { name, ty });
+            return Ok(if is_sequence {
+                Type::SequenceOf(of)
+            } else {
+                Type::SetOf(of)
+            });
+        }
+
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+
+        let mut ty = Extensible {
+            root: vec![],
+            extension: None,
+        };
+        loop {
+            let node = iter.next();
+            if let Ok(tok) = self.token(node, &[]) {
+                match tok.kind {
+                    TokenKind::RightCurly => break,
+                    TokenKind::Comma | TokenKind::Ellipsis => continue,
+                    _ => (),
+                }
+            }
+
+            let mut tree = self.tree(node, &[])?;
+            match (tree.tag, &mut ty.extension) {
+                (Asn1Tag::ComponentTypeList, None) => {
+                    ty.root.extend(self.component_types(&mut tree)?)
+                }
+                (Asn1Tag::ComponentTypeList, Some(ext)) => {
+                    ext.root.extend(self.component_types(&mut tree)?)
+                }
+                (Asn1Tag::ExtensionAndException, _) => {
+                    ty.extension = Some(Extension {
+                        id: tree.node,
+                        additions: vec![],
+                        root: vec![],
+                    })
+                }
+                (Asn1Tag::ExtensionAdditions, Some(ext)) => {
+                    ext.additions = self.extension_additions(&mut tree)?
+                }
+                _ => self
+                    .tree(tree.node, Asn1Tag::ComponentTypeList)
+                    .map(|_| ())?,
+            }
+        }
+        iter.assert_empty()?;
+
+        Ok(if is_sequence {
+            Type::Sequence(ty)
+        } else {
+            Type::Set(ty)
+        })
+    }
+
+    /// Interpret the extension additions of a sequence or set type
+    fn extension_additions(
+        &self,
+        iter: &mut CstIter,
+    ) -> Result<Vec<ExtensionAddition<ComponentType>>> {
+        let mut additions = vec![];
+
+        for node in iter {
+            if self.token(node, TokenKind::Comma).is_ok() {
+                continue;
+            }
+
+            let mut addition = self.tree(node, Asn1Tag::ExtensionAddition)?;
+            let inner = addition.next();
+            addition.assert_empty()?;
+
+            if let Ok(mut group) = self.tree(inner, Asn1Tag::ExtensionAdditionGroup) {
+                self.token(group.next(), TokenKind::VersionOpen)?;
+                let version = self.version_number(&mut group)?;
+                let items = self
+                    .component_types(&mut self.tree(group.next(), Asn1Tag::ComponentTypeList)?)?;
+                self.token(group.next(), TokenKind::VersionClose)?;
+                group.assert_empty()?;
+
+                additions.push(ExtensionAddition::Group(ExtensionGroup { version, items }));
+            } else {
+                let mut component = self.tree(inner, Asn1Tag::ComponentType)?;
+                additions.push(ExtensionAddition::Single(
+                    self.component_type(&mut component)?,
+                ));
+            }
+        }
+
+        Ok(additions)
+    }
+
+    /// Interpret the optional version number of an extension addition group
+    fn version_number(&self, iter: &mut CstIter) -> Result<Option<WithId<String>>> {
+        let Ok(mut version) = self.tree(iter.peek(), Asn1Tag::VersionNumber) else {
+            return Ok(None);
+        };
+        iter.next();
+
+        let number = self.token(version.next(), TokenKind::Number)?;
+        self.token(version.next(), TokenKind::Colon)?;
+        version.assert_empty()?;
+
+        Ok(Some(WithId {
+            value: self.token_value(*number).to_string(),
+            id: number.id,
+        }))
+    }
+
+    /// Interpret a comma separated list of component types
+    fn component_types(&self, iter: &mut CstIter) -> Result<Vec<ComponentType>> {
+        let mut components = vec![];
+
+        for node in iter {
+            if self.token(node, TokenKind::Comma).is_ok() {
+                continue;
+            }
+
+            let mut component = self.tree(node, Asn1Tag::ComponentType)?;
+            components.push(self.component_type(&mut component)?);
+        }
+
+        Ok(components)
+    }
+
+    /// Interpret a single component of a sequence or set type
+    fn component_type(&self, iter: &mut CstIter) -> Result<ComponentType> {
+        if self.token(iter.peek(), TokenKind::KwComponents).is_ok() {
+            iter.next();
+            self.token(iter.next(), TokenKind::KwOf)?;
+            let ty = self.ty(&mut self.tree(iter.next(), Asn1Tag::TypeOrValue)?)?;
+            iter.assert_empty()?;
+
+            return Ok(ComponentType::ComponentsOf(ty));
+        }
+
+        let name = self.identifier(iter.next())?;
+        let ty = self.ty(&mut self.tree(iter.next(), Asn1Tag::TypeOrValue)?)?;
+
+        let mut component = Component {
+            name,
+            ty,
+            optional: false,
+            default: None,
+        };
+
+        if let Ok(tok) = self.token(iter.peek(), &[TokenKind::KwOptional, TokenKind::KwDefault]) {
+            iter.next();
+            if tok.kind == TokenKind::KwOptional {
+                component.optional = true;
+            } else {
+                let mut value = self.tree(iter.next(), Asn1Tag::TypeOrValue)?;
+                component.default = Some(self.value(&mut value, Some(&component.ty))?);
+            }
+        }
+        iter.assert_empty()?;
+
+        Ok(ComponentType::Named(component))
+    }
+
+    /// Interpret a choice type
+    pub(super) fn choice_type(&self, iter: &mut CstIter) -> Result<Type> {
+        self.token(iter.next(), TokenKind::KwChoice)?;
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+
+        let root = self.type_list(&mut self.tree(iter.next(), Asn1Tag::TypeList)?)?;
+        let mut ty = Extensible {
+            root,
+            extension: None,
+        };
+
+        if let Ok(mut extension) = self.tree(iter.peek(), Asn1Tag::ChoiceExtension) {
+            iter.next();
+
+            let marker = self.tree(extension.next(), Asn1Tag::ExtensionAndException)?;
+            let mut additions = vec![];
+            for node in extension {
+                let Ok(mut list) = self.tree(node, Asn1Tag::ChoiceExtensionList) else {
+                    self.token(node, &[TokenKind::Comma, TokenKind::Ellipsis])?;
+                    continue;
+                };
+
+                for item in &mut list {
+                    if self.token(item, TokenKind::Comma).is_ok() {
+                        continue;
+                    }
+                    additions.push(self.choice_extension_item(
+                        &mut self.tree(item, Asn1Tag::ChoiceExtensionItem)?,
+                    )?);
+                }
+            }
+
+            ty.extension = Some(Extension {
+                id: marker.node,
+                additions,
+                root: vec![],
+            });
+        }
+
+        self.token(iter.next(), TokenKind::RightCurly)?;
+        iter.assert_empty()?;
+
+        Ok(Type::Choice(ty))
+    }
+
+    /// Interpret a single extension addition of a choice type
+    fn choice_extension_item(&self, iter: &mut CstIter) -> Result<ExtensionAddition<NamedType>> {
+        if self.token(iter.peek(), TokenKind::VersionOpen).is_err() {
+            let alternative = self.alternative(iter.next())?;
+            iter.assert_empty()?;
+            return Ok(ExtensionAddition::Single(alternative));
+        }
+
+        iter.next();
+        let version = self.version_number(iter)?;
+        let items = self.type_list(&mut self.tree(iter.next(), Asn1Tag::TypeList)?)?;
+        self.token(iter.next(), TokenKind::VersionClose)?;
+        iter.assert_empty()?;
+
+        Ok(ExtensionAddition::Group(ExtensionGroup { version, items }))
+    }
+
+    /// Interpret a comma separated list of choice alternatives
+    fn type_list(&self, iter: &mut CstIter) -> Result<Vec<NamedType>> {
+        let mut alternatives = vec![];
+
+        for node in iter {
+            if self.token(node, TokenKind::Comma).is_ok() {
+                continue;
+            }
+
+            alternatives.push(self.alternative(Some(node))?);
+        }
+
+        Ok(alternatives)
+    }
+
+    /// Interpret a single choice alternative, which must be named
+    fn alternative(&self, node: Option<AsnNodeId>) -> Result<NamedType> {
+        let mut tree = self.tree(node, Asn1Tag::TypeOrValue)?;
+        let (name, ty) = self.named_type(&mut tree)?;
+
+        let Some(name) = name else {
+            return Err(Diagnostic::error("Asn1::Analysis::UnnamedAlternative")
+                .name("Choice alternatives must have an identifier")
+                .label(self.label(ty.id).message("Alternative defined here")));
+        };
+
+        Ok(NamedType { name, ty })
+    }
+
+    /// Interpret a type that may be preceded by an identifier
+    fn named_type(&self, iter: &mut CstIter) -> Result<(Option<WithId<String>>, WithId<Type>)> {
+        if let Ok(mut defined) = self.tree(iter.peek(), Asn1Tag::Defined) {
+            let name = self.token(defined.next(), TokenKind::ValueRefOrIdent);
+            let ty = self.tree(defined.next(), Asn1Tag::TypeOrValue);
+
+            if let (Ok(name), Ok(mut ty)) = (name, ty) {
+                defined.assert_empty()?;
+                iter.next();
+                iter.assert_empty()?;
+
+                return Ok((Some(self.identifier(name.id)?), self.ty(&mut ty)?));
+            }
+        }
+
+        Ok((None, self.ty(iter)?))
+    }
+}
@@ -0,0 +1,505 @@
+//! constraint interpretation
+
+use std::collections::HashMap;
+
+use crate::{
+    analysis::AnalysisContext,
+    ast::WithId,
+    cst::{Asn1Tag, AsnNodeId, CstIter},
+    diagnostic::Result,
+    token::TokenKind,
+};
+
+use super::{Type, Value};
+
+/// A constraint following a type, `(0..255, ...)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// A subtype constraint, restricting the type to a set of its values
+    Subtype(ElementSets),
+
+    /// A table constraint, restricting the type to the values of a field of
+    /// a set of information objects.  The component relations following the
+    /// object set, `({Objects}{@id})`, are not kept.
+    Table(ElementSets),
+
+    /// `CONTAINING Type ENCODED BY value`
+    Contents(ContentsConstraint),
+
+    /// `CONSTRAINED BY { ... }`, which is not checked
+    UserDefined,
+}
+
+/// The root elements and extension additions of a subtype constraint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementSets {
+    /// The elements of the extension root, missing if the constraint is only
+    /// an extension marker, `(...)`
+    pub root: Option<ElementSet>,
+
+    /// Does the constraint have an extension marker
+    pub extensible: bool,
+
+    /// The elements added after the extension marker
+    pub additions: Option<ElementSet>,
+}
+
+/// A set of values, as elements combined with set operators
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementSet {
+    /// A single element
+    Element(Box<SubtypeElement>),
+
+    /// The values in any of the sets, `A | B`
+    Union(Vec<ElementSet>),
+
+    /// The values in every one of the sets, `A ^ B`
+    Intersection(Vec<ElementSet>),
+
+    /// The values of the first set that are not in the second, `A EXCEPT B`
+    Except(Box<ElementSet>, Box<ElementSet>),
+
+    /// Every value not in the set, `ALL EXCEPT A`
+    AllExcept(Box<ElementSet>),
+}
+
+/// A single element of an element set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubtypeElement {
+    /// A single value, `5`
+    Value(WithId<Value>),
+
+    /// The values between two endpoints, `0..<10`
+    Range(Endpoint, Endpoint),
+
+    /// The values of another type, `Byte` or `INCLUDES Byte`
+    Type(WithId<Type>),
+
+    /// The values with a number of items permitted by the constraint,
+    /// `SIZE (1..4)`
+    Size(Constraint),
+
+    /// The strings containing only the characters permitted by the
+    /// constraint, `FROM ("a".."z")`
+    From(Constraint),
+
+    /// The values whose every element is permitted by the constraint,
+    /// `WITH COMPONENT (0..7)`
+    Component(Constraint),
+
+    /// The values whose components are permitted by the constraints,
+    /// `WITH COMPONENTS { ..., a PRESENT }`
+    Components(MultipleTypeConstraints),
+
+    /// The strings matching a regular expression, `PATTERN "[a-z]+"`
+    Pattern(WithId<Value>),
+
+    /// The time values with the property settings, `SETTINGS "Basic=Date"`.
+    /// The settings are the source text of the string, including the quotes.
+    Settings(WithId<String>),
+}
+
+/// One end of a range of values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// The value at the end of the range, or `None` for `MIN` and `MAX`
+    pub value: Option<WithId<Value>>,
+
+    /// Is the value itself left out of the range, `<`
+    pub open: bool,
+}
+
+/// The constraints on the components of a sequence, set or choice type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipleTypeConstraints {
+    /// Can components that are not named be present, `{ ..., a PRESENT }`
+    pub partial: bool,
+
+    /// The constraints on each named component
+    pub components: Vec<NamedConstraint>,
+}
+
+/// The constraint on a single component, `a (0..7) PRESENT`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedConstraint {
+    /// The identifier of the component
+    pub name: WithId<String>,
+
+    /// The constraint on the value of the component
+    pub constraint: Option<Constraint>,
+
+    /// Whether the component must be present, if specified
+    pub presence: Option<Presence>,
+}
+
+/// Whether a component is present in the values permitted by a constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Present,
+    Absent,
+    Optional,
+}
+
+/// The contents of a bit string or octet string, `CONTAINING Type ENCODED BY
+/// value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentsConstraint {
+    /// The type of the encoded value
+    pub containing: Option<WithId<Type>>,
+
+    /// The object identifier of the encoding rules
+    pub encoded_by: Option<WithId<Value>>,
+}
+
+impl AnalysisContext<'_> {
+    /// Interpret the constraints following each type within a tree node,
+    /// keyed by the node ID of the constrained type.  The size constraint
+    /// before `OF`, `SEQUENCE SIZE (1..4) OF Type`, is a constraint of the
+    /// whole type.
+    pub(crate) fn type_constraints(
+        &self,
+        node: AsnNodeId,
+    ) -> Result<HashMap<AsnNodeId, Vec<Constraint>>> {
+        let mut constraints: HashMap<AsnNodeId, Vec<Constraint>> = HashMap::new();
+        let mut nodes = vec![node];
+        while let Some(node) = nodes.pop() {
+            let tree = &self.source(node.source()).tree;
+            let Some(tag) = tree.tree_tag(node) else {
+                continue;
+            };
+            let children: Vec<_> = tree.children(node).collect();
+            nodes.extend(children.iter().rev());
+
+            let mut found = vec![];
+            match tag {
+                Asn1Tag::TypeOrValue => {
+                    for &child in &children {
+                        if tree.tree_tag(child) == Some(Asn1Tag::Constraint) {
+                            found.push(self.constraint(&mut self.tree(child, &[])?)?);
+                        }
+                    }
+                }
+                Asn1Tag::SequenceType | Asn1Tag::SetType => {
+                    let mut size = false;
+                    for &child in &children {
+                        if self.token(child, TokenKind::KwSize).is_ok() {
+                            size = true;
+                        } else if let Ok(mut tree) = self.tree(child, Asn1Tag::Constraint) {
+                            let constraint = self.constraint(&mut tree)?;
+                            found.push(match size {
+                                true => Constraint::Subtype(ElementSets {
+                                    root: Some(ElementSet::Element(Box::new(
+                                        SubtypeElement::Size(constraint),
+                                    ))),
+                                    extensible: false,
+                                    additions: None,
+                                }),
+                                false => constraint,
+                            });
+                        }
+                    }
+                }
+                _ => (),
+            }
+
+            if found.is_empty() {
+                continue;
+            }
+            // the constraints of a sequence of type belong to the type or value
+            // node containing it, before any constraints following the type
+            match tag {
+                Asn1Tag::TypeOrValue => constraints.entry(node).or_default().extend(found),
+                _ => {
+                    let ty = tree.parent(node).unwrap_or(node);
+                    let list: &mut Vec<_> = constraints.entry(ty).or_default();
+                    list.splice(0..0, found);
+                }
+            }
+        }
+
+        Ok(constraints)
+    }
+
+    /// Interpret a constraint, including its parentheses
+    fn constraint(&self, iter: &mut CstIter) -> Result<Constraint> {
+        self.token(iter.next(), TokenKind::LeftParen)?;
+
+        let inner = iter.next();
+        let constraint = if let Ok(mut contents) = self.tree(inner, Asn1Tag::ContentsConstraint) {
+            Constraint::Contents(self.contents_constraint(&mut contents)?)
+        } else if self.tree(inner, Asn1Tag::UserDefinedConstraint).is_ok() {
+            Constraint::UserDefined
+        } else {
+            let sets = self.element_set_specs(&mut self.tree(inner, Asn1Tag::ElementSetSpecs)?)?;
+            if self.tree(iter.peek(), Asn1Tag::BracedValue).is_ok() {
+                iter.next();
+                Constraint::Table(sets)
+            } else {
+                Constraint::Subtype(sets)
+            }
+        };
+
+        self.tree(iter.next(), Asn1Tag::ExceptionSpec)?;
+        self.token(iter.next(), TokenKind::RightParen)?;
+        iter.assert_empty()?;
+
+        Ok(constraint)
+    }
+
+    /// Interpret the root elements and extension additions of a subtype
+    /// constraint
+    fn element_set_specs(&self, iter: &mut CstIter) -> Result<ElementSets> {
+        let mut sets = ElementSets {
+            root: None,
+            extensible: false,
+            additions: None,
+        };
+
+        for node in iter {
+            if let Ok(tok) = self.token(node, &[TokenKind::Comma, TokenKind::Ellipsis]) {
+                sets.extensible |= tok.kind == TokenKind::Ellipsis;
+                continue;
+            }
+
+            let set = self.element_set(&mut self.tree(node, Asn1Tag::ElementSetSpec)?)?;
+            match sets.extensible {
+                false => sets.root = Some(set),
+                true => sets.additions = Some(set),
+            }
+        }
+
+        Ok(sets)
+    }
+
+    /// Interpret a set of elements combined by set operators.  `EXCEPT` binds
+    /// most tightly, followed by intersections and then unions.
+    fn element_set(&self, iter: &mut CstIter) -> Result<ElementSet> {
+        let mut all = false;
+        if self.token(iter.peek(), TokenKind::KwAll).is_ok() {
+            iter.next();
+            self.token(iter.next(), TokenKind::KwExcept)?;
+            all = true;
+        }
+
+        let mut unions = vec![];
+        let mut intersections = vec![];
+        let mut current = None;
+        let mut except = false;
+        for node in iter {
+            let Ok(tok) = self.token(node, &[]) else {
+                let element = self.element(&mut self.tree(node, Asn1Tag::SubtypeElement)?)?;
+                current = Some(match (current, except) {
+                    (Some(set), true) => ElementSet::Except(Box::new(set), Box::new(element)),
+                    _ => element,
+                });
+                continue;
+            };
+
+            except = tok.kind == TokenKind::KwExcept;
+            if except {
+                continue;
+            }
+            intersections.extend(current.take());
+            if matches!(tok.kind, TokenKind::Pipe | TokenKind::KwUnion) {
+                unions.push(combine(
+                    std::mem::take(&mut intersections),
+                    ElementSet::Intersection,
+                ));
+            }
+        }
+        intersections.extend(current);
+        unions.push(combine(intersections, ElementSet::Intersection));
+
+        let set = combine(unions, ElementSet::Union);
+        Ok(match all {
+            true => ElementSet::AllExcept(Box::new(set)),
+            false => set,
+        })
+    }
+
+    /// Interpret a single element of an element set, which may be an element
+    /// set within parentheses
+    fn element(&self, iter: &mut CstIter) -> Result<ElementSet> {
+        let first = iter.next();
+        let element = match self.token(first, &[]).map(|tok| tok.kind) {
+            Ok(TokenKind::LeftParen) => {
+                let set =
+                    self.element_set(&mut self.tree(iter.next(), Asn1Tag::ElementSetSpec)?)?;
+                self.token(iter.next(), TokenKind::RightParen)?;
+                iter.assert_empty()?;
+                return Ok(set);
+            }
+            Ok(TokenKind::KwSize) => SubtypeElement::Size(
+                self.constraint(&mut self.tree(iter.next(), Asn1Tag::Constraint)?)?,
+            ),
+            Ok(TokenKind::KwFrom) => SubtypeElement::From(
+                self.constraint(&mut self.tree(iter.next(), Asn1Tag::Constraint)?)?,
+            ),
+            Ok(TokenKind::KwWith) => {
+                let tok = self.token(
+                    iter.next(),
+                    &[TokenKind::KwComponent, TokenKind::KwComponents],
+                )?;
+                if tok.kind == TokenKind::KwComponent {
+                    let constraint =
+                        self.constraint(&mut self.tree(iter.next(), Asn1Tag::Constraint)?)?;
+                    SubtypeElement::Component(constraint)
+                } else {
+                    let mut constraints =
+                        self.tree(iter.next(), Asn1Tag::MultipleTypeConstraints)?;
+                    SubtypeElement::Components(self.multiple_type_constraints(&mut constraints)?)
+                }
+            }
+            Ok(TokenKind::KwPattern) => {
+                let mut value = self.tree(iter.next(), Asn1Tag::TypeOrValue)?;
+                SubtypeElement::Pattern(self.value(&mut value, None)?)
+            }
+            Ok(TokenKind::KwIncludes) => {
+                SubtypeElement::Type(self.ty(&mut self.tree(iter.next(), Asn1Tag::TypeOrValue)?)?)
+            }
+            Ok(TokenKind::KwSettings) => {
+                let tok = self.token(iter.next(), TokenKind::CString)?;
+                SubtypeElement::Settings(WithId {
+                    value: self.token_value(*tok).to_string(),
+                    id: tok.id,
+                })
+            }
+            Ok(TokenKind::KwMin) => {
+                let lower = Endpoint {
+                    value: None,
+                    open: false,
+                };
+                self.value_range(lower, iter)?
+            }
+            _ => {
+                let node = self.tree(first, Asn1Tag::TypeOrValue)?.node;
+                if iter.peek().is_some() {
+                    let lower = Endpoint {
+                        value: Some(self.value(&mut self.tree(node, &[])?, None)?),
+                        open: false,
+                    };
+                    self.value_range(lower, iter)?
+                } else {
+                    self.single_value(node)?
+                }
+            }
+        };
+        iter.assert_empty()?;
+
+        Ok(ElementSet::Element(Box::new(element)))
+    }
+
+    /// Interpret an element that is either a single value or the values of a
+    /// type.  A value that cannot be interpreted without its type, such as a
+    /// braced value, is a type only if it can be interpreted as one.
+    fn single_value(&self, node: AsnNodeId) -> Result<SubtypeElement> {
+        let value = self.value(&mut self.tree(node, &[])?, None)?;
+        if value.value != Value::Unparsed {
+            return Ok(SubtypeElement::Value(value));
+        }
+
+        Ok(match self.ty(&mut self.tree(node, &[])?) {
+            Ok(ty) => SubtypeElement::Type(ty),
+            Err(_) => SubtypeElement::Value(value),
+        })
+    }
+
+    /// Interpret the rest of a value range after its lower endpoint value
+    fn value_range(&self, mut lower: Endpoint, iter: &mut CstIter) -> Result<SubtypeElement> {
+        if self.token(iter.peek(), TokenKind::Less).is_ok() {
+            iter.next();
+            lower.open = true;
+        }
+        self.token(iter.next(), TokenKind::RangeSeparator)?;
+
+        let mut upper = Endpoint {
+            value: None,
+            open: false,
+        };
+        if self.token(iter.peek(), TokenKind::Less).is_ok() {
+            iter.next();
+            upper.open = true;
+        }
+        if self.token(iter.peek(), TokenKind::KwMax).is_ok() {
+            iter.next();
+        } else {
+            let mut value = self.tree(iter.next(), Asn1Tag::TypeOrValue)?;
+            upper.value = Some(self.value(&mut value, None)?);
+        }
+
+        Ok(SubtypeElement::Range(lower, upper))
+    }
+
+    /// Interpret the constraints on the components of a type
+    fn multiple_type_constraints(&self, iter: &mut CstIter) -> Result<MultipleTypeConstraints> {
+        self.token(iter.next(), TokenKind::LeftCurly)?;
+
+        let mut constraints = MultipleTypeConstraints {
+            partial: false,
+            components: vec![],
+        };
+        for node in iter {
+            if let Ok(tok) = self.token(node, &[]) {
+                constraints.partial |= tok.kind == TokenKind::Ellipsis;
+                continue;
+            }
+
+            let mut named = self.tree(node, Asn1Tag::NamedConstraint)?;
+            let name = self.identifier(named.next())?;
+            let mut constraint = None;
+            if let Ok(mut tree) = self.tree(named.peek(), Asn1Tag::Constraint) {
+                named.next();
+                constraint = Some(self.constraint(&mut tree)?);
+            }
+            let mut presence = None;
+            if let Ok(tok) = self.token(named.next(), &[]) {
+                presence = Some(match tok.kind {
+                    TokenKind::KwPresent => Presence::Present,
+                    TokenKind::KwAbsent => Presence::Absent,
+                    _ => Presence::Optional,
+                });
+            }
+            named.assert_empty()?;
+
+            constraints.components.push(NamedConstraint {
+                name,
+                constraint,
+                presence,
+            });
+        }
+
+        Ok(constraints)
+    }
+
+    /// Interpret a contents constraint
+    fn contents_constraint(&self, iter: &mut CstIter) -> Result<ContentsConstraint> {
+        let mut contents = ContentsConstraint {
+            containing: None,
+            encoded_by: None,
+        };
+
+        if self.token(iter.peek(), TokenKind::KwContaining).is_ok() {
+            iter.next();
+            let ty = self.ty(&mut self.tree(iter.next(), Asn1Tag::TypeOrValue)?)?;
+            contents.containing = Some(ty);
+        }
+        if self.token(iter.peek(), TokenKind::KwEncoded).is_ok() {
+            iter.next();
+            self.token(iter.next(), TokenKind::KwBy)?;
+            let mut value = self.tree(iter.next(), Asn1Tag::TypeOrValue)?;
+            contents.encoded_by = Some(self.value(&mut value, Some(&Type::ObjectIdentifier))?);
+        }
+        iter.assert_empty()?;
+
+        Ok(contents)
+    }
+}
+
+/// Combine a list of element sets with a set operator, unless there is only
+/// one of them
+fn combine(mut sets: Vec<ElementSet>, operator: fn(Vec<ElementSet>) -> ElementSet) -> ElementSet {
+    match sets.len() {
+        1 => sets.remove(0),
+        _ => operator(sets),
+    }
+}
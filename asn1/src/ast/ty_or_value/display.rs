@@ -0,0 +1,299 @@
+//! ASN.1 notation for interpreted types and values
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{
+    ComponentType, Extensible, ExtensionAddition, FieldKind, FieldReference, FieldSource,
+    FieldSpec, NamedNumber, ObjIdComponent, ObjectClass, Optionality, ParameterizedReference,
+    Reference, TagClass, TagKind, Type, TypeOrValue, Value,
+};
+
+impl Display for TypeOrValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeOrValue::Type(ty) => ty.fmt(f),
+            TypeOrValue::Value(value) => value.fmt(f),
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let keyword = match self {
+            Type::Boolean => "BOOLEAN",
+            Type::Null => "NULL",
+            Type::ObjectIdentifier => "OBJECT IDENTIFIER",
+            Type::OidIri => "OID-IRI",
+            Type::GeneralizedTime => "GeneralizedTime",
+            Type::UTCTime => "UTCTime",
+            Type::ObjectDescriptor => "ObjectDescriptor",
+            Type::Real => "REAL",
+            Type::RelativeOid => "RELATIVE-OID",
+            Type::RelativeOidIri => "RELATIVE-OID-IRI",
+            Type::External => "EXTERNAL",
+            Type::Time => "TIME",
+            Type::Date => "DATE",
+            Type::TimeOfDay => "TIME-OF-DAY",
+            Type::DateTime => "DATE-TIME",
+            Type::Duration => "DURATION",
+            Type::BmpString => "BMPString",
+            Type::GeneralString => "GeneralString",
+            Type::GraphicString => "GraphicString",
+            Type::IA5String => "IA5String",
+            Type::ISO64String => "ISO646String",
+            Type::NumericString => "NumericString",
+            Type::PrintableString => "PrintableString",
+            Type::TeletexString => "TeletexString",
+            Type::T61String => "T61String",
+            Type::UniversalString => "UniversalString",
+            Type::UTF8String => "UTF8String",
+            Type::VideotexString => "VideotexString",
+            Type::VisibleString => "VisibleString",
+            Type::OctetString => "OCTET STRING",
+            Type::CharacterString => "CHARACTER STRING",
+            Type::EmbeddedPdv => "EMBEDDED PDV",
+            Type::Integer(numbers) => return named_numbers(f, "INTEGER", numbers),
+            Type::BitString(numbers) => return named_numbers(f, "BIT STRING", numbers),
+            Type::Enumerated(items) => {
+                return braced(f, "ENUMERATED", items, |f, item| {
+                    write!(f, "{}", *item.name)?;
+                    match &item.value {
+                        Some(value) => write!(f, "({})", **value),
+                        None => Ok(()),
+                    }
+                })
+            }
+            Type::Sequence(components) => return braced(f, "SEQUENCE", components, component),
+            Type::Set(components) => return braced(f, "SET", components, component),
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                match self {
+                    Type::SequenceOf(_) => f.write_str("SEQUENCE OF ")?,
+                    _ => f.write_str("SET OF ")?,
+                }
+                if let Some(name) = &of.name {
+                    write!(f, "{} ", **name)?;
+                }
+                return of.ty.fmt(f);
+            }
+            Type::Choice(alternatives) => {
+                return braced(f, "CHOICE", alternatives, |f, alternative| {
+                    write!(f, "{} {}", *alternative.name, *alternative.ty)
+                })
+            }
+            Type::Tagged(tagged) => {
+                f.write_char('[')?;
+                match tagged.class {
+                    TagClass::Universal => f.write_str("UNIVERSAL ")?,
+                    TagClass::Application => f.write_str("APPLICATION ")?,
+                    TagClass::Private => f.write_str("PRIVATE ")?,
+                    TagClass::ContextSpecific => (),
+                }
+                write!(f, "{}] ", *tagged.number)?;
+                match tagged.kind {
+                    TagKind::Implicit => f.write_str("IMPLICIT ")?,
+                    TagKind::Explicit => f.write_str("EXPLICIT ")?,
+                    TagKind::Default => (),
+                }
+                return tagged.ty.fmt(f);
+            }
+            Type::Selection(name, ty) => return write!(f, "{} < {}", **name, ***ty),
+            Type::Reference(reference) => return reference.fmt(f),
+            Type::Class(class) => return class.fmt(f),
+            Type::Field(field) => return field.fmt(f),
+            Type::Parameterized(parameterized) => return parameterized.fmt(f),
+        };
+
+        f.write_str(keyword)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::ObjectIdentifier(components) => {
+                f.write_char('{')?;
+                for component in components {
+                    match component {
+                        ObjIdComponent::Name(name) | ObjIdComponent::Number(name) => {
+                            write!(f, " {}", **name)?
+                        }
+                        ObjIdComponent::NameAndNumber(name, number) => {
+                            write!(f, " {}({})", **name, **number)?
+                        }
+                        ObjIdComponent::External(module, name) => {
+                            write!(f, " {}.{}", **module, **name)?
+                        }
+                    }
+                }
+                f.write_str(" }")
+            }
+            Value::Iri(iri) => f.write_str(iri),
+            Value::Number(number) => f.write_str(number),
+            Value::Boolean(true) => f.write_str("TRUE"),
+            Value::Boolean(false) => f.write_str("FALSE"),
+            Value::Null => f.write_str("NULL"),
+            Value::String(string) => f.write_str(string),
+            Value::Reference(reference) => reference.fmt(f),
+            Value::Field(field) => field.fmt(f),
+            Value::Unparsed => f.write_str("{ ... }"),
+        }
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(module) = &self.module {
+            write!(f, "{}.", **module)?;
+        }
+        f.write_str(&self.name)
+    }
+}
+
+impl Display for ParameterizedReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.reference)?;
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, " {}", **parameter)?;
+        }
+        f.write_str(" }")
+    }
+}
+
+impl Display for FieldReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            FieldSource::Class(class) => write!(f, "{}.{}", ***class, *self.field),
+            FieldSource::Object(object) => write!(f, "{object}.{}", *self.field),
+        }
+    }
+}
+
+impl Display for ObjectClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("CLASS {")?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, " {field}")?;
+        }
+        f.write_str(" }")
+    }
+}
+
+impl Display for FieldSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        match &self.kind {
+            FieldKind::Type => (),
+            FieldKind::Value { ty, unique } => {
+                write!(f, " {}", **ty)?;
+                if *unique {
+                    f.write_str(" UNIQUE")?;
+                }
+            }
+            FieldKind::ValueSet(ty) => write!(f, " {}", **ty)?,
+            FieldKind::VariableTypeValue(path) | FieldKind::VariableTypeValueSet(path) => {
+                for (i, name) in path.iter().enumerate() {
+                    f.write_str(if i == 0 { " " } else { "." })?;
+                    f.write_str(name)?;
+                }
+            }
+        }
+        match &self.optionality {
+            Optionality::Required => Ok(()),
+            Optionality::Optional => f.write_str(" OPTIONAL"),
+            Optionality::Default(default) => write!(f, " DEFAULT {}", **default),
+        }
+    }
+}
+
+/// Write a sequence or set component
+fn component(f: &mut Formatter<'_>, component: &ComponentType) -> fmt::Result {
+    match component {
+        ComponentType::Named(component) => {
+            write!(f, "{} {}", *component.name, *component.ty)?;
+            if component.optional {
+                f.write_str(" OPTIONAL")?;
+            }
+            match &component.default {
+                Some(default) => write!(f, " DEFAULT {}", **default),
+                None => Ok(()),
+            }
+        }
+        ComponentType::ComponentsOf(ty) => write!(f, "COMPONENTS OF {}", **ty),
+    }
+}
+
+/// Write a keyword followed by its optional list of named numbers
+fn named_numbers(f: &mut Formatter<'_>, keyword: &str, numbers: &[NamedNumber]) -> fmt::Result {
+    f.write_str(keyword)?;
+    if numbers.is_empty() {
+        return Ok(());
+    }
+
+    f.write_str(" {")?;
+    for (i, number) in numbers.iter().enumerate() {
+        if i > 0 {
+            f.write_char(',')?;
+        }
+        write!(f, " {}({})", *number.name, *number.value)?;
+    }
+    f.write_str(" }")
+}
+
+/// Write a keyword followed by its braced list of items and any extensions
+fn braced<T>(
+    f: &mut Formatter<'_>,
+    keyword: &str,
+    items: &Extensible<T>,
+    item: impl Fn(&mut Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    write!(f, "{keyword} {{")?;
+
+    let mut first = true;
+    let mut separator = |f: &mut Formatter<'_>| {
+        let sep = if first { " " } else { ", " };
+        first = false;
+        f.write_str(sep)
+    };
+
+    for value in &items.root {
+        separator(f)?;
+        item(f, value)?;
+    }
+    if let Some(extension) = &items.extension {
+        separator(f)?;
+        f.write_str("...")?;
+        for addition in &extension.additions {
+            separator(f)?;
+            match addition {
+                ExtensionAddition::Single(value) => item(f, value)?,
+                ExtensionAddition::Group(group) => {
+                    f.write_str("[[")?;
+                    if let Some(version) = &group.version {
+                        write!(f, " {}:", **version)?;
+                    }
+                    for (i, value) in group.items.iter().enumerate() {
+                        f.write_str(if i == 0 { " " } else { ", " })?;
+                        item(f, value)?;
+                    }
+                    f.write_str(" ]]")?;
+                }
+            }
+        }
+        if !extension.root.is_empty() {
+            separator(f)?;
+            f.write_str("...")?;
+            for value in &extension.root {
+                separator(f)?;
+                item(f, value)?;
+            }
+        }
+    }
+
+    f.write_str(" }")
+}
@@ -0,0 +1,223 @@
+//! value interpretation
+
+use crate::{
+    analysis::AnalysisContext,
+    ast::WithId,
+    cst::{Asn1Tag, AsnNodeId, CstIter},
+    diagnostic::Result,
+    token::{Token, TokenKind},
+    Diagnostic,
+};
+
+use super::{DefinedReference, FieldReference, FieldSource, Reference, Type};
+
+/// A value, as far as it can be interpreted without knowing its resolved type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// An OBJECT IDENTIFIER or RELATIVE-OID value, `{ iso standard 8571 }`
+    ObjectIdentifier(Vec<ObjIdComponent>),
+
+    /// An OID-IRI or RELATIVE-OID-IRI value, the source text of the string
+    /// including the quotes.
+    Iri(WithId<String>),
+
+    /// An integer or real number, including any leading minus sign
+    Number(String),
+
+    /// `TRUE` or `FALSE`
+    Boolean(bool),
+
+    /// `NULL`
+    Null,
+
+    /// A character string, the source text including the quotes
+    String(String),
+
+    /// A reference to a value assignment, or an identifier from the type of the
+    /// value such as a named number or enumeration item.
+    Reference(Reference),
+
+    /// The value of a field of an information object, `object.&id`
+    Field(Box<FieldReference>),
+
+    /// A value that is not interpreted until the type of the value has been
+    /// fully resolved.
+    Unparsed,
+}
+
+/// A single component of an object identifier value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjIdComponent {
+    /// Only an identifier, which is either the name of a well known arc or a
+    /// reference to another value.
+    Name(WithId<String>),
+
+    /// Only the number of the arc
+    Number(WithId<String>),
+
+    /// An identifier with the number of the arc, `iso(1)`
+    NameAndNumber(WithId<String>, WithId<String>),
+
+    /// A reference to a value in another module, `Module.value`
+    External(WithId<String>, WithId<String>),
+}
+
+impl AnalysisContext<'_> {
+    /// Interpret a value from a type or value node.  The type of the value is
+    /// used to interpret braced values and strings, if it is known.
+    pub(super) fn value(&self, iter: &mut CstIter, ty: Option<&Type>) -> Result<WithId<Value>> {
+        let id = iter.node;
+        let Some(first) = iter.next() else {
+            return Err(Diagnostic::error(format!("empty value {id:?}")));
+        };
+
+        let value = if let Ok(mut tree) = self.tree(first, &[]) {
+            match tree.tag {
+                Asn1Tag::NumberValue => {
+                    let mut text = String::new();
+                    for tok in tree {
+                        text.push_str(self.token_value(*self.token(tok, &[])?));
+                    }
+                    Value::Number(text)
+                }
+                Asn1Tag::BracedValue => match ty {
+                    Some(Type::ObjectIdentifier | Type::RelativeOid) => {
+                        self.object_identifier_value(&mut tree)?
+                    }
+                    _ => Value::Unparsed,
+                },
+                Asn1Tag::Defined => self.defined_value(&mut tree)?,
+                _ => Value::Unparsed,
+            }
+        } else {
+            let tok = self.token(first, &[])?;
+            match (tok.kind, ty) {
+                (TokenKind::CString, Some(Type::OidIri | Type::RelativeOidIri)) => {
+                    Value::Iri(WithId {
+                        value: self.token_value(*tok).to_string(),
+                        id: tok.id,
+                    })
+                }
+                (TokenKind::CString, _) => Value::String(self.token_value(*tok).to_string()),
+                (TokenKind::KwTrue, _) => Value::Boolean(true),
+                (TokenKind::KwFalse, _) => Value::Boolean(false),
+                (TokenKind::KwNull, _) => Value::Null,
+                _ => Value::Unparsed,
+            }
+        };
+
+        if iter.peek().is_some() {
+            return Ok(WithId {
+                value: Value::Unparsed,
+                id,
+            });
+        }
+
+        Ok(WithId { value, id })
+    }
+
+    /// Interpret a defined node used as a value, which is a reference if it
+    /// only contains the name of a value, or a field reference if the name is
+    /// followed by the name of a field.
+    fn defined_value(&self, iter: &mut CstIter) -> Result<Value> {
+        let Ok(first) = self.token(iter.next(), &[]) else {
+            return Ok(Value::Unparsed);
+        };
+        if !matches!(
+            first.kind,
+            TokenKind::ValueRefOrIdent | TokenKind::TypeOrModuleRef
+        ) {
+            return Ok(Value::Unparsed);
+        }
+
+        let defined = iter.node;
+        let Ok(DefinedReference {
+            reference,
+            parameters,
+            field,
+        }) = self.reference(first, iter)
+        else {
+            return Ok(Value::Unparsed);
+        };
+        if !reference.name.starts_with(char::is_lowercase) {
+            return Ok(Value::Unparsed);
+        }
+        if parameters.is_some() {
+            return Err(self.unsupported(defined, "Parameterized values"));
+        }
+
+        Ok(match field {
+            Some(field) => Value::Field(Box::new(FieldReference {
+                source: FieldSource::Object(reference),
+                field,
+            })),
+            None => Value::Reference(reference),
+        })
+    }
+
+    /// Parse the components of an object identifier value.  The parser keeps
+    /// braced values as a flat list of tokens, so the components are found
+    /// from the sequence of tokens.
+    fn object_identifier_value(&self, braced: &mut CstIter) -> Result<Value> {
+        let braced_id = braced.node;
+        let mut tokens = vec![];
+        for node in braced {
+            tokens.push(self.token(node, &[])?);
+        }
+
+        self.object_identifier_components(braced_id, &tokens)
+    }
+
+    /// Parse the components of an object identifier value from its tokens,
+    /// including the braces.  The node is used to label any error.
+    pub(super) fn object_identifier_components(
+        &self,
+        braced_id: AsnNodeId,
+        tokens: &[WithId<Token>],
+    ) -> Result<Value> {
+        let invalid = || {
+            Diagnostic::error("Asn1::Analysis::OidValue")
+                .name("Invalid object identifier value")
+                .label(self.label(braced_id).message(
+                    "Expected components of the form `name`, `number`, `name(number)` or `Module.name`",
+                ))
+        };
+
+        let [first, inner @ .., last] = tokens else {
+            return Err(invalid());
+        };
+        if first.kind != TokenKind::LeftCurly || last.kind != TokenKind::RightCurly {
+            return Err(invalid());
+        }
+
+        let with_value = |tok: &WithId<_>| WithId {
+            value: self.ident_value(**tok),
+            id: tok.id,
+        };
+
+        let mut components = vec![];
+        let mut rest = inner;
+        while !rest.is_empty() {
+            let kinds: Vec<_> = rest.iter().take(4).map(|tok| tok.kind).collect();
+            let (component, len) = match kinds[..] {
+                [TokenKind::ValueRefOrIdent, TokenKind::LeftParen, TokenKind::Number, TokenKind::RightParen] => {
+                    (
+                        ObjIdComponent::NameAndNumber(with_value(&rest[0]), with_value(&rest[2])),
+                        4,
+                    )
+                }
+                [TokenKind::TypeOrModuleRef, TokenKind::Dot, TokenKind::ValueRefOrIdent, ..] => (
+                    ObjIdComponent::External(with_value(&rest[0]), with_value(&rest[2])),
+                    3,
+                ),
+                [TokenKind::ValueRefOrIdent, ..] => (ObjIdComponent::Name(with_value(&rest[0])), 1),
+                [TokenKind::Number, ..] => (ObjIdComponent::Number(with_value(&rest[0])), 1),
+                _ => return Err(invalid()),
+            };
+            components.push(component);
+            rest = &rest[len..];
+        }
+
+        Ok(Value::ObjectIdentifier(components))
+    }
+}
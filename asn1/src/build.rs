@@ -0,0 +1,210 @@
+//! Compile ASN.1 modules from a cargo build script, writing the generated
+//! rust source to the build's output directory.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     asn1::Build::new()
+//!         .file("asn1/messages.asn1")
+//!         .include("asn1/common")
+//!         .compile();
+//! }
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/asn1.rs"));
+//! ```
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{AsnCompiler, CodegenError, CodegenOptions, CodegenTarget};
+
+/// Configuration of the ASN.1 modules compiled by a build script
+#[derive(Debug, Clone)]
+pub struct Build {
+    /// The source files, in the order they are added
+    files: Vec<PathBuf>,
+
+    /// Directories to search for imported modules that are not in the files
+    include: Vec<PathBuf>,
+
+    /// The directory the generated file is written to, `OUT_DIR` if not set
+    out_dir: Option<PathBuf>,
+
+    /// Name of the generated file within the output directory
+    output: String,
+
+    /// Options controlling the generated rust source
+    options: CodegenOptions,
+}
+
+/// An error compiling the ASN.1 modules of a build script
+#[derive(Debug)]
+pub enum BuildError {
+    /// No output directory was set, and `OUT_DIR` is not set as the build is
+    /// not run by cargo
+    NoOutDir,
+
+    /// A source file could not be read
+    Read(PathBuf, io::Error),
+
+    /// The generated file could not be written
+    Write(PathBuf, io::Error),
+
+    /// The modules could not be parsed or analysed.  Each diagnostic is
+    /// written with the location of its labels.
+    Diagnostics(Vec<String>),
+
+    /// The modules could not be generated
+    Codegen(CodegenError),
+}
+
+impl Build {
+    /// Create a build without any source files, generating `asn1.rs`
+    pub fn new() -> Self {
+        Self {
+            files: vec![],
+            include: vec![],
+            out_dir: None,
+            output: "asn1.rs".into(),
+            options: CodegenOptions::default(),
+        }
+    }
+
+    /// Add a source file
+    pub fn file(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add a directory to search for imported modules that are not defined
+    /// by the source files
+    pub fn include(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.include.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the directory the generated file is written to, instead of the
+    /// `OUT_DIR` of the build script
+    pub fn out_dir(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.out_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the name of the generated file within the output directory.  If
+    /// modules are written to a file each, this is the name of the file
+    /// declaring them, and the files of the modules are written beside it.
+    pub fn output(&mut self, name: impl Into<String>) -> &mut Self {
+        self.output = name.into();
+        self
+    }
+
+    /// Set the options controlling the generated rust source
+    pub fn options(&mut self, options: CodegenOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Compile the source files, panicking with the errors found if they
+    /// cannot be compiled.  Returns the path of the generated file.
+    pub fn compile(&self) -> PathBuf {
+        match self.try_compile() {
+            Ok(path) => path,
+            Err(e) => panic!("\n{e}\n"),
+        }
+    }
+
+    /// Compile the source files and write the generated rust source to the
+    /// output directory, returning its path.  Cargo is told to run the build
+    /// script again when any loaded source file or include directory
+    /// changes.
+    pub fn try_compile(&self) -> Result<PathBuf, BuildError> {
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(BuildError::NoOutDir)?,
+        };
+
+        for dir in &self.include {
+            println!("cargo:rerun-if-changed={}", dir.display());
+        }
+        for path in &self.files {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+
+        let mut compiler = AsnCompiler::new();
+        for dir in &self.include {
+            compiler.add_include_dir(dir);
+        }
+
+        let mut errors = vec![];
+        for path in &self.files {
+            let source =
+                std::fs::read_to_string(path).map_err(|e| BuildError::Read(path.clone(), e))?;
+            if let Err(e) = compiler.add_file(path.to_string_lossy().to_string(), source) {
+                errors.push(e);
+            }
+        }
+        if errors.is_empty() {
+            errors = compiler.analysis().diagnostics;
+        }
+
+        // files found in the include directories are only loaded by analysis
+        for file in compiler.sources() {
+            let Some(path) = compiler.source_name(file).map(Path::new) else {
+                continue;
+            };
+            if !self.files.iter().any(|added| added == path) {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+
+        if !errors.is_empty() {
+            let errors = errors
+                .iter()
+                .map(|e| compiler.render_diagnostic(e))
+                .collect();
+            return Err(BuildError::Diagnostics(errors));
+        }
+
+        let files = compiler
+            .analysis()
+            .codegen(CodegenTarget::Rust, &self.options)
+            .map_err(BuildError::Codegen)?;
+
+        // the first file declares the modules
+        let root = out_dir.join(&self.output);
+        for (i, file) in files.iter().enumerate() {
+            let path = match i {
+                0 => root.clone(),
+                _ => out_dir.join(&file.file),
+            };
+            std::fs::write(&path, &file.content).map_err(|e| BuildError::Write(path, e))?;
+        }
+        Ok(root)
+    }
+}
+
+impl Default for Build {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::NoOutDir => f.write_str("no output directory set, and OUT_DIR is not set"),
+            BuildError::Read(path, e) => write!(f, "unable to read `{}`: {e}", path.display()),
+            BuildError::Write(path, e) => write!(f, "unable to write `{}`: {e}", path.display()),
+            BuildError::Diagnostics(errors) => f.write_str(&errors.join("\n\n")),
+            BuildError::Codegen(e) => write!(f, "unable to generate code: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
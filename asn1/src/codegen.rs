@@ -1,66 +1,238 @@
-use std::fmt::Write;
+mod ada;
+mod c;
+mod model;
+mod rust;
 
-use convert_case::{Case, Casing};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
 
-use crate::analysis::{AnalysisContext, Environment};
+use crate::analysis::AnalysisContext;
 
-/// Generate rust source code from a successful analysis context
-#[derive(Debug, Clone)]
-struct RustCodegen<'a> {
-    analysis: &'a AnalysisContext<'a>,
-    result: String,
+/// How instances of parameterized types are generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ParameterizedTypes {
+    /// Generate a generic type for each parameterized type, used with the
+    /// actual parameters as type arguments.  Parameterized types with value
+    /// or object parameters cannot be generic, so their instances are
+    /// generated as separate types.
+    Generic,
+
+    /// Generate a separate type for each instance of a parameterized type
+    #[default]
+    Monomorphised,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum CodegenError {
-    /// Cannot run codegen if any errors are present in the analysis context
-    AnalysisErrors,
+/// Options controlling the code generated by the rust target
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CodegenOptions {
+    /// How instances of parameterized types are generated
+    pub parameterized: ParameterizedTypes,
 
-    /// Error during write to string? should never occur
-    FmtError(std::fmt::Error),
+    /// Traits derived by every generated type in addition to `Debug`, `Clone`
+    /// and `PartialEq`, as paths such as `Hash` or `schemars::JsonSchema`.
+    /// `Eq`, `Hash` and `Ord` are not derived by types containing a `REAL`.
+    pub derives: Vec<String>,
+
+    /// Derive `serde::Serialize` and `serde::Deserialize` for every generated
+    /// type, with fields and variants named by their ASN.1 identifiers
+    pub serde: bool,
+
+    /// The type of `INTEGER` values
+    pub integers: IntegerType,
+
+    /// Represent values of the time types by the types of a `time_values`
+    /// module written with the modules, which parse and write the value
+    /// notation of each type, rather than by `String`.  The types implement
+    /// `Debug`, `Clone`, `PartialEq`, `Eq` and `Hash`.
+    pub time_types: bool,
+
+    /// Visibility of the generated modules, types and fields
+    pub visibility: Visibility,
+
+    /// Whether modules are written to a single file or a file each
+    pub layout: ModuleLayout,
+
+    /// Cases names are converted to
+    pub naming: Naming,
+
+    /// Names used instead of converted names, keyed by the ASN.1 names of
+    /// the module, assignment and members separated by dots: `Module`,
+    /// `Module.Type` or `Module.Type.component`.  Components of nested types
+    /// are keyed by the components they are nested within,
+    /// `Module.Type.component.nested`.
+    pub renames: BTreeMap<String, String>,
 }
 
-type Result<T = (), E = CodegenError> = std::result::Result<T, E>;
+/// The rust type of `INTEGER` values whose constraints do not limit them to
+/// the values of `i64` or `u64`, which are given the first of those types
+/// holding every permitted value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum IntegerType {
+    /// `i64`, which cannot hold every value
+    #[default]
+    I64,
 
-impl AnalysisContext<'_> {
-    /// Run the code generator to produce a rust source code listing to represent the input files.
-    pub fn rust_codegen(&self) -> Result<String> {
-        if !self.diagnostics.is_empty() {
-            return Err(CodegenError::AnalysisErrors);
-        }
+    /// `num_bigint::BigInt`, which requires the generated code to depend on
+    /// the `num-bigint` crate
+    BigInt,
+}
+
+/// Visibility of generated items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Visibility {
+    /// `pub`
+    #[default]
+    Public,
+
+    /// `pub(crate)`
+    Crate,
 
-        RustCodegen {
-            analysis: self,
-            result: String::new(),
+    /// `pub(super)`, visible to the module the generated modules are in
+    Super,
+}
+
+impl Visibility {
+    fn keyword(self) -> &'static str {
+        match self {
+            Visibility::Public => "pub",
+            Visibility::Crate => "pub(crate)",
+            Visibility::Super => "pub(super)",
         }
-        .run()
     }
 }
 
-impl RustCodegen<'_> {
-    fn run(mut self) -> Result<String> {
-        for module in self.analysis.modules.values() {
-            self.module(module)?;
-        }
+/// Layout of the generated rust modules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ModuleLayout {
+    /// A single file, `asn1.rs`, containing an inline module for each ASN.1
+    /// module
+    #[default]
+    SingleFile,
 
-        Ok(self.result)
-    }
+    /// A file for each module, declared by a `mod.rs` file
+    FilePerModule,
+}
+
+/// The cases the names of each kind of item are converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Naming {
+    /// Modules generated for ASN.1 modules
+    pub modules: NameCase,
+
+    /// Types and the parameters of generic types
+    pub types: NameCase,
+
+    /// Fields of structs generated for `SEQUENCE` and `SET` types
+    pub fields: NameCase,
 
-    fn module(&mut self, module: &Environment) -> Result {
-        writeln!(self.result, "mod {} {{", module.name.to_case(Case::Snake))?;
+    /// Variants of enums generated for `CHOICE` and `ENUMERATED` types
+    pub variants: NameCase,
 
-        for (name, var) in &module.variables {
-            writeln!(
-                self.result,
-                "\ttype {} = {:?};",
-                name.to_case(Case::ScreamingSnake),
-                *var.value,
-            )?;
+    /// Constants generated for `INTEGER` and `REAL` value assignments
+    pub constants: NameCase,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self {
+            modules: NameCase::Snake,
+            types: NameCase::Pascal,
+            fields: NameCase::Snake,
+            variants: NameCase::Pascal,
+            constants: NameCase::ScreamingSnake,
         }
+    }
+}
+
+/// A case names are converted to, with words separated at hyphens and
+/// changes of case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NameCase {
+    /// `snake_case`
+    Snake,
+
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+
+    /// `PascalCase`
+    Pascal,
+
+    /// `camelCase`
+    Camel,
+}
+
+/// The language code is generated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum CodegenTarget {
+    /// Rust source containing a module for each ASN.1 module
+    #[default]
+    Rust,
+
+    /// A C header and source file for each ASN.1 module, with BER and UPER
+    /// encoders and decoders, and the runtime they are built with
+    C,
 
-        writeln!(self.result, "}}")?;
+    /// An Ada package spec and body for each ASN.1 module, with BER encoders
+    /// and decoders, and the runtime package they are built with
+    Ada,
+}
+
+/// A file produced by the code generator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    /// Name of the file, relative to the output directory
+    pub file: String,
+
+    /// Content of the file
+    pub content: String,
+}
 
-        Ok(())
+#[derive(Debug, Clone, Copy)]
+pub enum CodegenError {
+    /// Cannot run codegen if any errors are present in the analysis context
+    AnalysisErrors,
+
+    /// Error during write to string? should never occur
+    FmtError(std::fmt::Error),
+
+    /// A type cannot be represented in the target language, such as a tag
+    /// number that is not an integer
+    Unsupported(&'static str),
+}
+
+type Result<T = (), E = CodegenError> = std::result::Result<T, E>;
+
+impl AnalysisContext<'_> {
+    /// Run the code generator for a target language, producing the files
+    /// representing the input files.  The options are only used by the rust
+    /// target, and the root file of the rust modules is the first file.
+    pub fn codegen(
+        &self,
+        target: CodegenTarget,
+        options: &CodegenOptions,
+    ) -> Result<Vec<GeneratedFile>> {
+        match target {
+            CodegenTarget::Rust => {
+                if !self.diagnostics.is_empty() {
+                    return Err(CodegenError::AnalysisErrors);
+                }
+                rust::generate(self, options)
+            }
+            CodegenTarget::C => {
+                if !self.diagnostics.is_empty() {
+                    return Err(CodegenError::AnalysisErrors);
+                }
+                c::generate(self)
+            }
+            CodegenTarget::Ada => {
+                if !self.diagnostics.is_empty() {
+                    return Err(CodegenError::AnalysisErrors);
+                }
+                ada::generate(self)
+            }
+        }
     }
 }
 
@@ -69,3 +241,17 @@ impl From<std::fmt::Error> for CodegenError {
         Self::FmtError(value)
     }
 }
+
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::AnalysisErrors => f.write_str("the modules have analysis errors"),
+            CodegenError::FmtError(e) => write!(f, "unable to write generated code: {e}"),
+            CodegenError::Unsupported(what) => {
+                write!(f, "{what} are not supported by the target language")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
@@ -0,0 +1,585 @@
+//! Ada 2022 code generated from analysed modules, with a package spec and
+//! body for each module, and the runtime package they are built with.
+//!
+//! Each type assignment is an Ada type named after it in the package of its
+//! module, with subprograms to encode and decode its values in BER into a
+//! buffer provided by the caller:
+//! - `SEQUENCE` and `SET` types are records with a component for each
+//!   component.  `OPTIONAL` and `DEFAULT` components and extension additions
+//!   have a flag in the `Present` record component.
+//! - `CHOICE` types are discriminated records, with the `Kind` of the
+//!   alternative chosen as their discriminant.
+//! - `ENUMERATED` types are enumerations, and `SEQUENCE OF` and `SET OF`
+//!   types are records with a `Count` of `Items`, up to `Max_Items`.
+//! - `INTEGER` is `Integer_64`, `REAL` is `Long_Float`, and strings, bit
+//!   strings and object identifiers are the fixed capacity types of the
+//!   runtime.  Open types, `EXTERNAL`, `EMBEDDED PDV` and `CHARACTER STRING`
+//!   hold encodings of their values.
+//! - Types nested within another type are named after the component or
+//!   alternative using them, or `item` for the elements of a `SEQUENCE OF`.
+//!
+//! Types that contain themselves are stored through access types, and are
+//! allocated when decoded.  Integers with a value range that is not
+//! extensible are given a constrained subtype, and decoding a value outside
+//! of it is an error.  Other constraints are not checked, and `DEFAULT`
+//! values are always encoded when present.  Packages cannot depend on each
+//! other, so modules whose types refer to each other cannot be generated,
+//! and integers with values beyond `Integer_64` cannot be either.  Names
+//! are not case sensitive, so names that only differ in case from an
+//! earlier name are given numbered suffixes.
+
+mod ber;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::analysis::AnalysisContext;
+
+use super::{
+    model::{
+        self, identifier, Choice, Code, Definition, Field, Kind, Model, Primitive, Sequence, Slot,
+        SlotType, Tag,
+    },
+    CodegenError, GeneratedFile, Result,
+};
+
+/// Name of the runtime package used by every generated package
+const RUNTIME: &str = "Asn1_Runtime";
+
+/// Identifiers that cannot name a package, type, component or enumeration
+/// literal, compared without case.  These are the reserved words, the names
+/// of the packages and types used by generated code, and the components of
+/// generated records.
+const RESERVED: &[&str] = &[
+    "abort",
+    "abs",
+    "abstract",
+    "accept",
+    "access",
+    "ada",
+    "aliased",
+    "all",
+    "and",
+    "array",
+    "asn1_runtime",
+    "at",
+    "begin",
+    "body",
+    "boolean",
+    "case",
+    "constant",
+    "declare",
+    "delay",
+    "delta",
+    "digits",
+    "do",
+    "else",
+    "elsif",
+    "end",
+    "entry",
+    "exception",
+    "exit",
+    "for",
+    "function",
+    "generic",
+    "goto",
+    "if",
+    "in",
+    "interface",
+    "interfaces",
+    "is",
+    "kind",
+    "limited",
+    "long_float",
+    "loop",
+    "mod",
+    "natural",
+    "new",
+    "not",
+    "null",
+    "of",
+    "or",
+    "others",
+    "out",
+    "overriding",
+    "package",
+    "parallel",
+    "pragma",
+    "present",
+    "private",
+    "procedure",
+    "protected",
+    "raise",
+    "range",
+    "record",
+    "rem",
+    "renames",
+    "requeue",
+    "return",
+    "reverse",
+    "select",
+    "separate",
+    "some",
+    "standard",
+    "subtype",
+    "synchronized",
+    "system",
+    "tagged",
+    "task",
+    "terminate",
+    "then",
+    "type",
+    "until",
+    "use",
+    "when",
+    "while",
+    "with",
+    "xor",
+];
+
+/// Generate the packages of every analysed module, followed by the runtime
+pub(super) fn generate(analysis: &AnalysisContext) -> Result<Vec<GeneratedFile>> {
+    let mut definitions = model::definitions(analysis, member_name)?;
+    deduplicate(&mut definitions);
+    let generator = Generator {
+        model: Model::new(&definitions),
+    };
+    let mut modules: Vec<_> = analysis.modules().map(|module| &module.name).collect();
+    modules.sort();
+
+    if generator.circular() {
+        return Err(CodegenError::Unsupported(
+            "modules whose types refer to each other",
+        ));
+    }
+    let unsigned = |slot: &Slot| matches!(slot.ty, SlotType::Primitive(Primitive::Unsigned));
+    if definitions.iter().flat_map(|def| def.slots()).any(unsigned) {
+        return Err(CodegenError::Unsupported(
+            "integer constraints beyond signed 64 bit integers",
+        ));
+    }
+
+    let mut files = vec![];
+    for module in modules {
+        files.extend(generator.package(module));
+    }
+    files.push(GeneratedFile {
+        file: "asn1_runtime.ads".into(),
+        content: include_str!("ada/asn1_runtime.ads").into(),
+    });
+    files.push(GeneratedFile {
+        file: "asn1_runtime.adb".into(),
+        content: include_str!("ada/asn1_runtime.adb").into(),
+    });
+    Ok(files)
+}
+
+/// Add a suffix to an identifier that cannot be used
+fn escape(name: &str) -> String {
+    if RESERVED.contains(&name.to_lowercase().as_str()) {
+        format!("{name}_Id")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Names declared within the same declarative region, which are the same
+/// if they only differ in case
+#[derive(Default)]
+struct Scope(HashSet<String>);
+
+impl Scope {
+    fn claim(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 1;
+        while !self.0.insert(candidate.to_lowercase()) {
+            n += 1;
+            candidate = format!("{name}_{n}");
+        }
+        candidate
+    }
+}
+
+/// Number the names of types, components and enumeration items that are the
+/// same as an earlier name of the same region when their case is ignored,
+/// and the references to renamed types
+fn deduplicate(definitions: &mut [Definition]) {
+    let mut types = Scope::default();
+    let mut renamed = HashMap::new();
+    for def in definitions.iter_mut() {
+        let name = types.claim(&def.name);
+        if name != def.name {
+            renamed.insert(std::mem::replace(&mut def.name, name.clone()), name);
+        }
+
+        let mut scope = Scope::default();
+        match &mut def.kind {
+            Kind::Sequence(Sequence { fields, .. })
+            | Kind::Choice(Choice {
+                alternatives: fields,
+                ..
+            }) => {
+                for field in fields {
+                    field.name = scope.claim(&field.name);
+                }
+            }
+            Kind::Enumerated(enumerated) => {
+                for (constant, _, _) in &mut enumerated.items {
+                    *constant = scope.claim(constant);
+                }
+            }
+            Kind::List(_) | Kind::Alias(_) => {}
+        }
+    }
+
+    for def in definitions {
+        let slots = match &mut def.kind {
+            Kind::Sequence(Sequence { fields, .. })
+            | Kind::Choice(Choice {
+                alternatives: fields,
+                ..
+            }) => fields.iter_mut().map(|f| &mut f.slot).collect(),
+            Kind::Enumerated(_) => vec![],
+            Kind::List(slot) | Kind::Alias(slot) => vec![slot],
+        };
+        for slot in slots.into_iter().chain(&mut def.assignment) {
+            if let SlotType::Named { name, .. } = &mut slot.ty {
+                if let Some(new) = renamed.get(name) {
+                    name.clone_from(new);
+                }
+            }
+        }
+    }
+}
+
+/// Get the name of a component or enumeration item
+fn member_name(name: &str) -> String {
+    escape(&identifier(name))
+}
+
+/// Get the name of the package generated for a module
+fn package_name(module: &str) -> String {
+    escape(&identifier(module))
+}
+
+/// Get the name of a type or enumeration literal within the package of its
+/// module, which must not be the name of the package
+fn local_name(module: &str, name: &str) -> String {
+    let prefix = format!("{}_", identifier(module));
+    let name = escape(name.strip_prefix(&prefix).unwrap_or(name));
+    if name.eq_ignore_ascii_case(&package_name(module)) {
+        format!("{name}_Id")
+    } else {
+        name
+    }
+}
+
+/// Get the name of the files generated for a module, without an extension
+fn file_name(module: &str) -> String {
+    package_name(module).to_lowercase()
+}
+
+/// Write a comment, if there is one
+fn comment(code: &mut Code, comment: Option<&str>) {
+    for line in comment.iter().flat_map(|comment| comment.lines()) {
+        code.line(format!("--  {line}").trim_end());
+    }
+}
+
+/// Write an enumeration type, with a literal on each line
+fn enumeration(code: &mut Code, name: &str, literals: &[(String, Option<&str>)]) {
+    code.line(&format!("type {name} is"));
+    for (i, (literal, doc)) in literals.iter().enumerate() {
+        for line in doc.iter().flat_map(|doc| doc.lines()) {
+            code.line(format!("   --  {line}").trim_end());
+        }
+        let start = if i == 0 { "  (" } else { "   " };
+        let end = if i + 1 == literals.len() { ");" } else { "," };
+        code.line(&format!("{start}{literal}{end}"));
+    }
+}
+
+/// Get the range of the values of an integer slot, if they are constrained
+/// by a value range that is not extensible
+fn value_range(slot: &Slot) -> Option<String> {
+    let range = slot.value.filter(|range| !range.extensible)?;
+    if !matches!(slot.ty, SlotType::Primitive(Primitive::Integer))
+        || (range.lower.is_none() && range.upper.is_none())
+    {
+        return None;
+    }
+
+    let bound = |bound: Option<i128>, attribute: &str| match bound {
+        Some(bound) => bound.to_string(),
+        None => format!("{RUNTIME}.Integer_Value'{attribute}"),
+    };
+    Some(format!(
+        "{} .. {}",
+        bound(range.lower, "First"),
+        bound(range.upper, "Last")
+    ))
+}
+
+/// Write the start of a subprogram, with a parameter on each line, which is
+/// either a declaration or followed by its body
+fn signature(
+    code: &mut Code,
+    head: &str,
+    parameters: &[(&str, String)],
+    result: Option<&str>,
+    body: bool,
+) {
+    code.line(head);
+    for (i, (name, ty)) in parameters.iter().enumerate() {
+        let start = if i == 0 { "  (" } else { "   " };
+        let end = match (i + 1 == parameters.len(), result, body) {
+            (false, _, _) => ";",
+            (true, Some(_), _) | (true, None, true) => ")",
+            (true, None, false) => ");",
+        };
+        code.line(&format!("{start}{name:<6} : {ty}{end}"));
+    }
+    if let Some(result) = result {
+        let end = if body { "" } else { ";" };
+        code.line(&format!("   return {result}{end}"));
+    }
+    if body {
+        code.line("is");
+    }
+}
+
+/// Writes the packages of each module from the definitions of every module
+struct Generator<'a> {
+    model: Model<'a>,
+}
+
+impl<'a> Generator<'a> {
+    /// Write the spec and body of the package of a module
+    fn package(&self, module: &str) -> Vec<GeneratedFile> {
+        let definitions = self.model.ordered(module);
+        let package = package_name(module);
+        let file = file_name(module);
+
+        let mut spec = Code::new(3);
+        spec.line(&format!("--  Generated from the ASN.1 module `{module}`"));
+        spec.line("");
+        spec.line(&format!("with {RUNTIME};"));
+        let withs: BTreeSet<_> = definitions
+            .iter()
+            .flat_map(|def| self.model.dependencies(def, true))
+            .map(|name| self.model.definition(name).module)
+            .filter(|&other| other != module)
+            .map(package_name)
+            .collect();
+        for with in withs {
+            spec.line(&format!("with {with};"));
+        }
+        spec.line("");
+        spec.open(&format!("package {package} is"));
+
+        // types stored through access types are declared before their
+        // completion
+        let mut pointers = BTreeSet::new();
+        for def in &definitions {
+            for slot in def.slots() {
+                if let SlotType::Named {
+                    name,
+                    pointer: true,
+                } = &slot.ty
+                {
+                    pointers.insert(local_name(module, name));
+                }
+            }
+        }
+        for name in pointers {
+            spec.line("");
+            spec.line(&format!("type {name};"));
+            spec.line(&format!("type {name}_Access is access {name};"));
+        }
+
+        for def in &definitions {
+            spec.line("");
+            self.declaration(&mut spec, def);
+        }
+        spec.line("");
+        spec.close(&format!("end {package};"));
+
+        let mut files = vec![GeneratedFile {
+            file: format!("{file}.ads"),
+            content: spec.text,
+        }];
+
+        // a package body is only allowed if the spec has subprograms
+        if !definitions.is_empty() {
+            let mut body = Code::new(3);
+            body.line(&format!("--  Generated from the ASN.1 module `{module}`"));
+            body.line("");
+            body.open(&format!("package body {package} is"));
+            for def in &definitions {
+                self.ber_bodies(&mut body, def);
+            }
+            body.line("");
+            body.close(&format!("end {package};"));
+            files.push(GeneratedFile {
+                file: format!("{file}.adb"),
+                content: body.text,
+            });
+        }
+        files
+    }
+
+    /// Do the types of packages refer to each other, so the packages would
+    /// depend on each other
+    fn circular(&self) -> bool {
+        let mut imports: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for def in self.model.definitions {
+            for name in self.model.dependencies(def, true) {
+                let other = self.model.definition(name).module;
+                if other != def.module {
+                    imports.entry(def.module).or_default().insert(other);
+                }
+            }
+        }
+
+        // remove modules that do not import a remaining module until none
+        // are left, unless there is a cycle
+        while !imports.is_empty() {
+            let remaining: BTreeSet<_> = imports.keys().copied().collect();
+            let before = imports.len();
+            imports.retain(|_, imported| imported.iter().any(|m| remaining.contains(m)));
+            if imports.len() == before {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Get the name of a generated type, qualified by its package
+    fn qualified(&self, name: &str) -> String {
+        let def = self.model.definition(name);
+        format!(
+            "{}.{}",
+            package_name(def.module),
+            local_name(def.module, &def.name)
+        )
+    }
+
+    /// Write the Ada type and subprogram declarations of a definition
+    fn declaration(&self, code: &mut Code, def: &Definition) {
+        let package = package_name(def.module);
+        let name = local_name(def.module, &def.name);
+        match &def.kind {
+            Kind::Sequence(sequence) => {
+                if sequence.presence > 0 {
+                    code.open(&format!("type {name}_Presence is record"));
+                    for field in &sequence.fields {
+                        if field.presence.is_some() {
+                            code.line(&format!("{} : Boolean := False;", field.name));
+                        }
+                    }
+                    code.close("end record;");
+                    code.line("");
+                }
+
+                comment(code, def.doc);
+                if sequence.presence == 0 && sequence.fields.is_empty() {
+                    code.line(&format!("type {name} is null record;"));
+                } else {
+                    code.open(&format!("type {name} is record"));
+                    if sequence.presence > 0 {
+                        code.line(&format!("Present : {package}.{name}_Presence;"));
+                    }
+                    for field in &sequence.fields {
+                        comment(code, field.doc);
+                        code.line(&format!("{} : {};", field.name, self.member(&field.slot)));
+                    }
+                    code.close("end record;");
+                }
+            }
+            Kind::Choice(choice) => {
+                let mut literals = vec![(format!("{name}_Nothing"), None)];
+                for alternative in &choice.alternatives {
+                    literals.push((format!("{name}_{}_Chosen", alternative.name), None));
+                }
+                enumeration(code, &format!("{name}_Kind"), &literals);
+                code.line("");
+
+                comment(code, def.doc);
+                code.open(&format!(
+                    "type {name} (Kind : {package}.{name}_Kind := {package}.{name}_Nothing) is record"
+                ));
+                code.open("case Kind is");
+                code.open(&format!("when {package}.{name}_Nothing =>"));
+                code.line("null;");
+                code.dedent();
+                for alternative in &choice.alternatives {
+                    code.open(&format!(
+                        "when {package}.{name}_{}_Chosen =>",
+                        alternative.name
+                    ));
+                    comment(code, alternative.doc);
+                    code.line(&format!(
+                        "{} : {};",
+                        alternative.name,
+                        self.member(&alternative.slot)
+                    ));
+                    code.dedent();
+                }
+                code.close("end case;");
+                code.close("end record;");
+            }
+            Kind::Enumerated(enumerated) => {
+                comment(code, def.doc);
+                let literals: Vec<_> = enumerated
+                    .items
+                    .iter()
+                    .map(|(constant, _, doc)| (local_name(def.module, constant), *doc))
+                    .collect();
+                enumeration(code, &name, &literals);
+            }
+            Kind::List(slot) => {
+                code.line(&format!(
+                    "type {name}_Items is array (1 .. {RUNTIME}.Max_Items) of {};",
+                    self.member(slot)
+                ));
+                code.line("");
+                comment(code, def.doc);
+                code.open(&format!("type {name} is record"));
+                code.line(&format!(
+                    "Count : Natural range 0 .. {RUNTIME}.Max_Items := 0;"
+                ));
+                code.line(&format!("Items : {package}.{name}_Items;"));
+                code.close("end record;");
+            }
+            Kind::Alias(slot) => {
+                comment(code, def.doc);
+                code.line(&format!("subtype {name} is {};", self.member(slot)));
+            }
+        }
+        self.ber_declarations(code, def);
+    }
+
+    /// Get the type of a component storing a slot
+    fn member(&self, slot: &Slot) -> String {
+        match &slot.ty {
+            SlotType::Named {
+                name,
+                pointer: true,
+            } => format!("{}_Access", self.qualified(name)),
+            SlotType::Named { name, .. } => self.qualified(name),
+            SlotType::Primitive(primitive) => match primitive {
+                Primitive::Boolean => "Boolean".into(),
+                Primitive::Integer => match value_range(slot) {
+                    Some(range) => format!("{RUNTIME}.Integer_Value range {range}"),
+                    None => format!("{RUNTIME}.Integer_Value"),
+                },
+                Primitive::Unsigned => unreachable!("unsigned integers are not supported"),
+                Primitive::Real => "Long_Float".into(),
+                Primitive::Null => format!("{RUNTIME}.Null_Value"),
+                Primitive::BitString => format!("{RUNTIME}.Bits"),
+                Primitive::Octets | Primitive::Chars { .. } => format!("{RUNTIME}.Octets"),
+                Primitive::Oid { .. } => format!("{RUNTIME}.Oid"),
+                Primitive::Open => format!("{RUNTIME}.Open"),
+            },
+        }
+    }
+}
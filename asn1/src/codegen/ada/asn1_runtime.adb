@@ -0,0 +1,583 @@
+package body Asn1_Runtime is
+
+   use type Interfaces.Unsigned_8;
+   use type Interfaces.Unsigned_32;
+   use type Interfaces.Unsigned_64;
+   use type Interfaces.Integer_64;
+
+   subtype Unsigned_64 is Interfaces.Unsigned_64;
+
+   --  Convert the two's complement bits of an integer to the integer
+   function To_Integer (Value : Unsigned_64) return Integer_Value is
+     (if Value >= 2**63 then -Integer_Value (not Value) - 1
+      else Integer_Value (Value));
+
+   procedure Prepend
+     (Buffer : in out Octet_Array; Length : in out Natural;
+      Data   :        Octet_Array)
+   is
+   begin
+      if Data'Length > Buffer'Length - Length then
+         raise Buffer_Error;
+      end if;
+      Buffer
+        (Buffer'Last - Length - Data'Length + 1 .. Buffer'Last - Length) :=
+        Data;
+      Length := Length + Data'Length;
+   end Prepend;
+
+   procedure Prepend_Octet
+     (Buffer : in out Octet_Array; Length : in out Natural; Value : Octet)
+   is
+   begin
+      Prepend (Buffer, Length, [1 => Value]);
+   end Prepend_Octet;
+
+   procedure Write_Length
+     (Buffer : in out Octet_Array; Length : in out Natural; Value : Natural)
+   is
+      Rest  : Natural := Value;
+      Count : Octet   := 0;
+   begin
+      if Value < 128 then
+         Prepend_Octet (Buffer, Length, Octet (Value));
+         return;
+      end if;
+      while Rest > 0 loop
+         Prepend_Octet (Buffer, Length, Octet (Rest mod 256));
+         Rest  := Rest / 256;
+         Count := Count + 1;
+      end loop;
+      Prepend_Octet (Buffer, Length, 16#80# or Count);
+   end Write_Length;
+
+   procedure Write_Header
+     (Buffer      : in out Octet_Array; Length : in out Natural;
+      Class       :        Tag_Class; Number : Tag_Number;
+      Constructed :        Boolean; Contents : Natural)
+   is
+      First : Octet      := Octet (Tag_Class'Pos (Class)) * 64;
+      Rest  : Tag_Number := Interfaces.Shift_Right (Number, 7);
+   begin
+      Write_Length (Buffer, Length, Contents);
+      if Constructed then
+         First := First or 16#20#;
+      end if;
+      if Number < 31 then
+         Prepend_Octet (Buffer, Length, First or Octet (Number));
+         return;
+      end if;
+
+      Prepend_Octet (Buffer, Length, Octet (Number and 16#7F#));
+      while Rest > 0 loop
+         Prepend_Octet (Buffer, Length, 16#80# or Octet (Rest and 16#7F#));
+         Rest := Interfaces.Shift_Right (Rest, 7);
+      end loop;
+      Prepend_Octet (Buffer, Length, First or 31);
+   end Write_Header;
+
+   procedure Write_Boolean
+     (Buffer : in out Octet_Array; Length : in out Natural;
+      Value  :        Boolean)
+   is
+   begin
+      Prepend_Octet (Buffer, Length, (if Value then 16#FF# else 0));
+   end Write_Boolean;
+
+   procedure Write_Integer
+     (Buffer : in out Octet_Array; Length : in out Natural;
+      Value  :        Integer_Value)
+   is
+      Rest : Unsigned_64 := Unsigned_64'Mod (Value);
+   begin
+      --  write the fewest octets that keep the sign of the value
+      loop
+         Prepend_Octet (Buffer, Length, Octet (Rest and 16#FF#));
+         exit when
+           (if Value < 0 then Rest >= 16#FFFF_FFFF_FFFF_FF80#
+            else Rest < 16#80#);
+         Rest := Interfaces.Shift_Right_Arithmetic (Rest, 8);
+      end loop;
+   end Write_Integer;
+
+   procedure Write_Real
+     (Buffer : in out Octet_Array; Length : in out Natural;
+      Value  :        Long_Float)
+   is
+      Magnitude : constant Long_Float := abs Value;
+      Mantissa  : Unsigned_64;
+      Exponent  : Integer;
+      First     : Octet := 16#80#;
+   begin
+      if Value /= Value then
+         Prepend_Octet (Buffer, Length, 16#42#);
+         return;
+      elsif Value = 0.0 then
+         if Long_Float'Copy_Sign (1.0, Value) < 0.0 then
+            Prepend_Octet (Buffer, Length, 16#43#);
+         end if;
+         return;
+      elsif Magnitude > Long_Float'Last then
+         Prepend_Octet
+           (Buffer, Length, (if Value > 0.0 then 16#40# else 16#41#));
+         return;
+      end if;
+
+      --  base 2, with an odd mantissa
+      Exponent :=
+        Long_Float'Exponent (Magnitude) - Long_Float'Machine_Mantissa;
+      Mantissa :=
+        Unsigned_64
+          (Long_Float'Scaling
+             (Long_Float'Fraction (Magnitude), Long_Float'Machine_Mantissa));
+      while Mantissa mod 2 = 0 loop
+         Mantissa := Mantissa / 2;
+         Exponent := Exponent + 1;
+      end loop;
+
+      loop
+         Prepend_Octet (Buffer, Length, Octet (Mantissa and 16#FF#));
+         Mantissa := Interfaces.Shift_Right (Mantissa, 8);
+         exit when Mantissa = 0;
+      end loop;
+      Prepend_Octet
+        (Buffer, Length, Octet (Unsigned_64'Mod (Exponent) and 16#FF#));
+      if Exponent not in -128 .. 127 then
+         Prepend_Octet
+           (Buffer, Length,
+            Octet
+              (Interfaces.Shift_Right (Unsigned_64'Mod (Exponent), 8)
+               and 16#FF#));
+         First := First or 1;
+      end if;
+      if Value < 0.0 then
+         First := First or 16#40#;
+      end if;
+      Prepend_Octet (Buffer, Length, First);
+   end Write_Real;
+
+   procedure Write_Bits
+     (Buffer : in out Octet_Array; Length : in out Natural; Value : Bits)
+   is
+      Count  : constant Natural := (Value.Count + 7) / 8;
+      Unused : constant Natural := Count * 8 - Value.Count;
+   begin
+      if Count > 0 then
+         Prepend_Octet
+           (Buffer, Length,
+            Value.Data (Count)
+            and Interfaces.Shift_Left (Octet'(16#FF#), Unused));
+         Prepend (Buffer, Length, Value.Data (1 .. Count - 1));
+      end if;
+      Prepend_Octet (Buffer, Length, Octet (Unused));
+   end Write_Bits;
+
+   procedure Write_Octets
+     (Buffer : in out Octet_Array; Length : in out Natural; Value : Octets)
+   is
+   begin
+      Prepend (Buffer, Length, Value.Data (1 .. Value.Length));
+   end Write_Octets;
+
+   procedure Write_Arc
+     (Buffer : in out Octet_Array; Length : in out Natural; Arc : Unsigned_64)
+   is
+      Rest : Unsigned_64 := Interfaces.Shift_Right (Arc, 7);
+   begin
+      Prepend_Octet (Buffer, Length, Octet (Arc and 16#7F#));
+      while Rest > 0 loop
+         Prepend_Octet (Buffer, Length, 16#80# or Octet (Rest and 16#7F#));
+         Rest := Interfaces.Shift_Right (Rest, 7);
+      end loop;
+   end Write_Arc;
+
+   procedure Write_Oid
+     (Buffer   : in out Octet_Array; Length : in out Natural; Value : Oid;
+      Relative :        Boolean)
+   is
+      First : Positive := 1;
+   begin
+      --  the first two arcs of an object identifier are a single arc
+      if not Relative then
+         if Value.Count < 2 or else Value.Arcs (1) > 2
+           or else (Value.Arcs (1) < 2 and then Value.Arcs (2) >= 40)
+         then
+            raise Value_Error;
+         end if;
+         First := 3;
+      end if;
+
+      for I in reverse First .. Value.Count loop
+         Write_Arc (Buffer, Length, Unsigned_64 (Value.Arcs (I)));
+      end loop;
+      if not Relative then
+         Write_Arc
+           (Buffer, Length,
+            Unsigned_64 (Value.Arcs (1)) * 40 + Unsigned_64 (Value.Arcs (2)));
+      end if;
+   end Write_Oid;
+
+   procedure Write_Open
+     (Buffer : in out Octet_Array; Length : in out Natural; Value : Open)
+   is
+   begin
+      Prepend (Buffer, Length, Value.Data (1 .. Value.Length));
+   end Write_Open;
+
+   procedure Finish (Buffer : in out Octet_Array; Length : Natural) is
+   begin
+      Buffer (Buffer'First .. Buffer'First + Length - 1) :=
+        Buffer (Buffer'Last - Length + 1 .. Buffer'Last);
+   end Finish;
+
+   procedure Read_Octet
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Octet)
+   is
+   begin
+      if At_End (R) then
+         raise Buffer_Error;
+      end if;
+      Value      := Buffer (R.Position);
+      R.Position := R.Position + 1;
+   end Read_Octet;
+
+   procedure Read_Tag
+     (Buffer : Octet_Array; R : in out Ber_Reader; Class : out Tag_Class;
+      Number : out Tag_Number)
+   is
+      First : Octet;
+      Next  : Octet;
+      Rest  : Unsigned_64 := 0;
+   begin
+      Read_Octet (Buffer, R, First);
+      Class := Tag_Class'Val (First / 64);
+      if (First and 31) /= 31 then
+         Number := Tag_Number (First and 31);
+         return;
+      end if;
+
+      loop
+         Read_Octet (Buffer, R, Next);
+         if Rest = 0 and then Next = 16#80# then
+            raise Decode_Error;
+         end if;
+         Rest :=
+           Interfaces.Shift_Left (Rest, 7) or Unsigned_64 (Next and 16#7F#);
+         if Rest > Unsigned_64 (Tag_Number'Last) then
+            raise Limit_Error;
+         end if;
+         exit when (Next and 16#80#) = 0;
+      end loop;
+      Number := Tag_Number (Rest);
+   end Read_Tag;
+
+   procedure Read_Length
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Natural)
+   is
+      First : Octet;
+      Next  : Octet;
+      Rest  : Unsigned_64 := 0;
+   begin
+      Read_Octet (Buffer, R, First);
+      if First < 16#80# then
+         Rest := Unsigned_64 (First);
+      elsif First = 16#80# then
+         --  the indefinite length form is not supported
+         raise Decode_Error;
+      else
+         for I in 1 .. (First and 16#7F#) loop
+            Read_Octet (Buffer, R, Next);
+            Rest := Interfaces.Shift_Left (Rest, 8) or Unsigned_64 (Next);
+            if Rest > Unsigned_64 (Natural'Last) then
+               raise Buffer_Error;
+            end if;
+         end loop;
+      end if;
+
+      if Rest > Unsigned_64 (R.Last - R.Position + 1) then
+         raise Buffer_Error;
+      end if;
+      Value := Natural (Rest);
+   end Read_Length;
+
+   function Next_Tag_Is
+     (Buffer : Octet_Array; R : Ber_Reader; Class : Tag_Class;
+      Number : Tag_Number) return Boolean
+   is
+      Copy       : Ber_Reader := R;
+      Next_Class : Tag_Class;
+      Next       : Tag_Number;
+   begin
+      if At_End (R) then
+         return False;
+      end if;
+      Read_Tag (Buffer, Copy, Next_Class, Next);
+      return Next_Class = Class and then Next = Number;
+   exception
+      when Buffer_Error | Decode_Error | Limit_Error =>
+         return False;
+   end Next_Tag_Is;
+
+   procedure Read_Header
+     (Buffer   :     Octet_Array; R : in out Ber_Reader; Class : Tag_Class;
+      Number   :     Tag_Number;
+      Contents : out Ber_Reader)
+   is
+      Next_Class : Tag_Class;
+      Next       : Tag_Number;
+      Length     : Natural;
+   begin
+      Read_Tag (Buffer, R, Next_Class, Next);
+      if Next_Class /= Class or else Next /= Number then
+         raise Decode_Error;
+      end if;
+      Read_Length (Buffer, R, Length);
+      Contents   := (Position => R.Position, Last => R.Position + Length - 1);
+      R.Position := R.Position + Length;
+   end Read_Header;
+
+   procedure Skip (Buffer : Octet_Array; R : in out Ber_Reader) is
+      Class  : Tag_Class;
+      Number : Tag_Number;
+      Length : Natural;
+   begin
+      Read_Tag (Buffer, R, Class, Number);
+      Read_Length (Buffer, R, Length);
+      R.Position := R.Position + Length;
+   end Skip;
+
+   procedure Read_End (Contents : Ber_Reader) is
+   begin
+      if not At_End (Contents) then
+         raise Decode_Error;
+      end if;
+   end Read_End;
+
+   procedure Read_Boolean
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Boolean)
+   is
+      Next : Octet;
+   begin
+      if R.Last /= R.Position then
+         raise Decode_Error;
+      end if;
+      Read_Octet (Buffer, R, Next);
+      Value := Next /= 0;
+   end Read_Boolean;
+
+   procedure Read_Integer
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Integer_Value)
+   is
+      Rest : Unsigned_64;
+      Next : Octet;
+   begin
+      if At_End (R) then
+         raise Decode_Error;
+      elsif R.Last - R.Position >= 8 then
+         raise Limit_Error;
+      end if;
+
+      Rest := (if Buffer (R.Position) >= 16#80# then Unsigned_64'Last else 0);
+      while not At_End (R) loop
+         Read_Octet (Buffer, R, Next);
+         Rest := Interfaces.Shift_Left (Rest, 8) or Unsigned_64 (Next);
+      end loop;
+      Value := To_Integer (Rest);
+   end Read_Integer;
+
+   --  Read a real in the decimal form of ISO 6093
+   procedure Read_Decimal
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Long_Float)
+   is
+      Text : String (1 .. R.Last - R.Position + 1);
+   begin
+      for I in Text'Range loop
+         Text (I) := Character'Val (Buffer (R.Position + I - 1));
+         if Text (I) = ',' then
+            Text (I) := '.';
+         end if;
+      end loop;
+      R.Position := R.Last + 1;
+      Value      := Long_Float'Value (Text);
+   exception
+      when Constraint_Error =>
+         raise Decode_Error;
+   end Read_Decimal;
+
+   procedure Read_Real
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Long_Float)
+   is
+      First    : Octet;
+      Next     : Octet;
+      Count    : Natural;
+      Rest     : Unsigned_64;
+      Exponent : Integer_Value;
+      Mantissa : Unsigned_64 := 0;
+      Base     : Natural;
+   begin
+      if At_End (R) then
+         Value := 0.0;
+         return;
+      end if;
+
+      Read_Octet (Buffer, R, First);
+      if (First and 16#C0#) = 16#40# then
+         if First = 16#43# and then At_End (R) then
+            Value := Long_Float'Copy_Sign (0.0, -1.0);
+            return;
+         end if;
+         --  infinities and NaN cannot be represented portably
+         raise Limit_Error;
+      elsif (First and 16#80#) = 0 then
+         Read_Decimal (Buffer, R, Value);
+         return;
+      end if;
+
+      Base :=
+        (case Interfaces.Shift_Right (First, 4) and 3 is
+           when 0 => 1,
+           when 1 => 3,
+           when 2 => 4,
+           when others => raise Decode_Error);
+      if (First and 3) = 3 then
+         Read_Octet (Buffer, R, Next);
+         Count := Natural (Next);
+      else
+         Count := Natural (First and 3) + 1;
+      end if;
+      if Count = 0 then
+         raise Decode_Error;
+      elsif Count > 8 then
+         raise Limit_Error;
+      end if;
+
+      for I in 1 .. Count loop
+         Read_Octet (Buffer, R, Next);
+         if I = 1 then
+            Rest := (if Next >= 16#80# then Unsigned_64'Last else 0);
+         end if;
+         Rest := Interfaces.Shift_Left (Rest, 8) or Unsigned_64 (Next);
+      end loop;
+      Exponent := To_Integer (Rest);
+
+      if R.Last - R.Position >= 8 then
+         raise Limit_Error;
+      end if;
+      while not At_End (R) loop
+         Read_Octet (Buffer, R, Next);
+         Mantissa := Interfaces.Shift_Left (Mantissa, 8) or Unsigned_64 (Next);
+      end loop;
+
+      if abs Exponent > 4096 then
+         raise Limit_Error;
+      end if;
+      Value :=
+        Long_Float'Scaling
+          (Long_Float (Mantissa),
+           Integer (Exponent) * Base
+           + Integer (Interfaces.Shift_Right (First, 2) and 3));
+      if Value > Long_Float'Last then
+         raise Limit_Error;
+      elsif (First and 16#40#) /= 0 then
+         Value := -Value;
+      end if;
+   end Read_Real;
+
+   procedure Read_Bits
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Bits)
+   is
+      Unused : Octet;
+      Count  : Natural;
+   begin
+      Read_Octet (Buffer, R, Unused);
+      Count := R.Last - R.Position + 1;
+      if Unused > 7 or else (Count = 0 and then Unused /= 0) then
+         raise Decode_Error;
+      elsif Count > Max_Octets then
+         raise Limit_Error;
+      end if;
+      Value.Data (1 .. Count) := Buffer (R.Position .. R.Last);
+      Value.Count             := Count * 8 - Natural (Unused);
+      R.Position              := R.Last + 1;
+   end Read_Bits;
+
+   procedure Read_Octets
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Octets)
+   is
+      Count : constant Natural := R.Last - R.Position + 1;
+   begin
+      if Count > Max_Octets then
+         raise Limit_Error;
+      end if;
+      Value.Data (1 .. Count) := Buffer (R.Position .. R.Last);
+      Value.Length            := Count;
+      R.Position              := R.Last + 1;
+   end Read_Octets;
+
+   procedure Add_Arc (Value : in out Oid; Arc : Unsigned_64) is
+   begin
+      if Value.Count = Max_Arcs
+        or else Arc > Unsigned_64 (Interfaces.Unsigned_32'Last)
+      then
+         raise Limit_Error;
+      end if;
+      Value.Count              := Value.Count + 1;
+      Value.Arcs (Value.Count) := Interfaces.Unsigned_32 (Arc);
+   end Add_Arc;
+
+   procedure Read_Oid
+     (Buffer   :     Octet_Array; R : in out Ber_Reader; Value : out Oid;
+      Relative :     Boolean)
+   is
+      Arc   : Unsigned_64 := 0;
+      Next  : Octet;
+      Start : Boolean     := True;
+   begin
+      Value.Count := 0;
+      if At_End (R) then
+         raise Decode_Error;
+      end if;
+
+      while not At_End (R) loop
+         Read_Octet (Buffer, R, Next);
+         if Start and then Next = 16#80# then
+            raise Decode_Error;
+         end if;
+         Arc :=
+           Interfaces.Shift_Left (Arc, 7) or Unsigned_64 (Next and 16#7F#);
+         if Arc > 2**40 then
+            raise Limit_Error;
+         end if;
+
+         Start := (Next and 16#80#) = 0;
+         if Start then
+            --  the first two arcs of an object identifier are a single arc
+            if Value.Count = 0 and then not Relative then
+               Add_Arc (Value, (if Arc < 80 then Arc / 40 else 2));
+               Add_Arc (Value, (if Arc < 80 then Arc mod 40 else Arc - 80));
+            else
+               Add_Arc (Value, Arc);
+            end if;
+            Arc := 0;
+         end if;
+      end loop;
+
+      if not Start then
+         raise Decode_Error;
+      end if;
+   end Read_Oid;
+
+   procedure Read_Open
+     (Buffer : Octet_Array; R : in out Ber_Reader; Value : out Open)
+   is
+      Start : constant Positive := R.Position;
+   begin
+      Skip (Buffer, R);
+      if R.Position - Start > Max_Octets then
+         raise Limit_Error;
+      end if;
+      Value.Length                   := R.Position - Start;
+      Value.Data (1 .. Value.Length) := Buffer (Start .. R.Position - 1);
+   end Read_Open;
+
+end Asn1_Runtime;
@@ -0,0 +1,581 @@
+//! Subprograms encoding and decoding generated types in BER.  Values are
+//! written backwards, so the contents of each value are written before its
+//! tags, and read with a reader of the contents of each tag.
+
+use super::{
+    local_name, package_name, signature, value_range, Code, Definition, Field, Generator, Kind,
+    Primitive, Slot, SlotType, Tag, RUNTIME,
+};
+
+/// Tag classes as named by the runtime, in the canonical order of tags
+const CLASSES: [&str; 4] = [
+    "Universal_Class",
+    "Application_Class",
+    "Context_Class",
+    "Private_Class",
+];
+
+/// The subprograms generated for a definition
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Subprogram {
+    Write,
+    Read,
+
+    /// Is the next value read one of the alternatives of an untagged
+    /// choice
+    Starts,
+    Encode,
+    Decode,
+}
+
+impl Generator<'_> {
+    /// Get the subprograms of a definition
+    fn ber_subprograms(&self, def: &Definition) -> Vec<Subprogram> {
+        let mut subprograms = vec![];
+        if !matches!(def.kind, Kind::Alias(_)) {
+            subprograms.extend([Subprogram::Write, Subprogram::Read]);
+        }
+        if matches!(def.kind, Kind::Choice(_)) {
+            subprograms.push(Subprogram::Starts);
+        }
+        if def.assignment.is_some() {
+            subprograms.extend([Subprogram::Encode, Subprogram::Decode]);
+        }
+        subprograms
+    }
+
+    /// Write the start of a subprogram of a definition, returning its name
+    fn ber_signature(
+        &self,
+        code: &mut Code,
+        def: &Definition,
+        subprogram: Subprogram,
+        body: bool,
+    ) -> String {
+        let name = local_name(def.module, &def.name);
+        let ty = format!("{}.{name}", package_name(def.module));
+        let buffer = format!("{RUNTIME}.Octet_Array");
+        let reader = format!("{RUNTIME}.Ber_Reader");
+        let (suffix, parameters, result) = match subprogram {
+            Subprogram::Write => (
+                "Ber_Write",
+                vec![
+                    ("Buffer", format!("in out {buffer}")),
+                    ("Length", "in out Natural".into()),
+                    ("Value", ty),
+                ],
+                None,
+            ),
+            Subprogram::Read => (
+                "Ber_Read",
+                vec![
+                    ("Buffer", buffer),
+                    ("R", format!("in out {reader}")),
+                    ("Value", format!("out {ty}")),
+                ],
+                None,
+            ),
+            Subprogram::Starts => (
+                "Ber_Starts",
+                vec![("Buffer", buffer), ("R", reader)],
+                Some("Boolean"),
+            ),
+            Subprogram::Encode => (
+                "Encode_Ber",
+                vec![
+                    ("Value", ty),
+                    ("Buffer", format!("out {buffer}")),
+                    ("Length", "out Natural".into()),
+                ],
+                None,
+            ),
+            Subprogram::Decode => (
+                "Decode_Ber",
+                vec![
+                    ("Value", format!("out {ty}")),
+                    ("Buffer", buffer),
+                    ("Length", "out Natural".into()),
+                ],
+                None,
+            ),
+        };
+
+        let kind = if result.is_some() {
+            "function"
+        } else {
+            "procedure"
+        };
+        let subprogram = format!("{name}_{suffix}");
+        signature(
+            code,
+            &format!("{kind} {subprogram}"),
+            &parameters,
+            result,
+            body,
+        );
+        subprogram
+    }
+
+    /// Declare the BER subprograms of a definition
+    pub(super) fn ber_declarations(&self, code: &mut Code, def: &Definition) {
+        for subprogram in self.ber_subprograms(def) {
+            code.line("");
+            self.ber_signature(code, def, subprogram, false);
+        }
+    }
+
+    /// Write the bodies of the BER subprograms of a definition
+    pub(super) fn ber_bodies(&self, code: &mut Code, def: &Definition) {
+        for subprogram in self.ber_subprograms(def) {
+            code.line("");
+            let name = self.ber_signature(code, def, subprogram, true);
+            if subprogram == Subprogram::Decode {
+                code.line(&format!(
+                    "   R : {RUNTIME}.Ber_Reader := {RUNTIME}.Reader (Buffer);"
+                ));
+            }
+            code.open("begin");
+            match subprogram {
+                Subprogram::Write => self.ber_write_contents(code, def),
+                Subprogram::Read => self.ber_read_contents(code, def),
+                Subprogram::Starts => self.ber_starts(code, def),
+                Subprogram::Encode => {
+                    code.line("Length := 0;");
+                    if let Some(slot) = &def.assignment {
+                        self.ber_write(code, slot, "Value");
+                    }
+                    code.line(&format!("{RUNTIME}.Finish (Buffer, Length);"));
+                }
+                Subprogram::Decode => {
+                    if let Some(slot) = &def.assignment {
+                        self.ber_read(code, slot, "Value");
+                    }
+                    code.line("Length := R.Position - Buffer'First;");
+                }
+            }
+            code.close(&format!("end {name};"));
+        }
+    }
+
+    /// Write the statements encoding the contents of a value
+    fn ber_write_contents(&self, code: &mut Code, def: &Definition) {
+        let package = package_name(def.module);
+        let name = local_name(def.module, &def.name);
+        match &def.kind {
+            Kind::Sequence(sequence) => {
+                for field in sequence.fields.iter().rev() {
+                    self.optional(code, field, |code| {
+                        self.ber_write(code, &field.slot, &format!("Value.{}", field.name));
+                    });
+                }
+                if sequence.fields.is_empty() {
+                    code.line("null;");
+                }
+            }
+
+            Kind::Choice(choice) => {
+                code.open("case Value.Kind is");
+                code.open(&format!("when {package}.{name}_Nothing =>"));
+                code.line(&format!("raise {RUNTIME}.Value_Error;"));
+                code.dedent();
+                for alternative in &choice.alternatives {
+                    code.open(&format!(
+                        "when {package}.{name}_{}_Chosen =>",
+                        alternative.name
+                    ));
+                    let place = format!("Value.{}", alternative.name);
+                    self.ber_write(code, &alternative.slot, &place);
+                    code.dedent();
+                }
+                code.close("end case;");
+            }
+
+            Kind::Enumerated(enumerated) => {
+                code.open("case Value is");
+                for (constant, number, _) in &enumerated.items {
+                    let literal = local_name(def.module, constant);
+                    code.open(&format!("when {package}.{literal} =>"));
+                    code.line(&format!(
+                        "{RUNTIME}.Write_Integer (Buffer, Length, {number});"
+                    ));
+                    code.dedent();
+                }
+                code.close("end case;");
+            }
+
+            Kind::List(slot) => {
+                code.open("for I in reverse 1 .. Value.Count loop");
+                self.ber_write(code, slot, "Value.Items (I)");
+                code.close("end loop;");
+            }
+
+            Kind::Alias(_) => unreachable!("aliases use the subprograms of their type"),
+        }
+    }
+
+    /// Write the statements decoding the contents of a value
+    fn ber_read_contents(&self, code: &mut Code, def: &Definition) {
+        let package = package_name(def.module);
+        let name = local_name(def.module, &def.name);
+        match &def.kind {
+            Kind::Sequence(sequence) => {
+                if sequence.presence > 0 {
+                    code.line("Value.Present := (others => False);");
+                }
+                if sequence.set {
+                    self.ber_read_set(code, &sequence.fields, sequence.extensible);
+                } else {
+                    self.ber_read_sequence(code, &sequence.fields, sequence.extensible);
+                }
+            }
+
+            Kind::Choice(choice) => {
+                for (i, alternative) in choice.alternatives.iter().enumerate() {
+                    let condition = format!("if {} then", self.ber_matches(&alternative.slot));
+                    if i == 0 {
+                        code.open(&condition);
+                    } else {
+                        code.next(&format!("els{condition}"));
+                    }
+                    code.open("declare");
+                    code.line(&format!("Item : {};", self.member(&alternative.slot)));
+                    code.next("begin");
+                    self.ber_read(code, &alternative.slot, "Item");
+                    code.line(&format!(
+                        "Value := (Kind => {package}.{name}_{0}_Chosen, {0} => Item);",
+                        alternative.name
+                    ));
+                    code.close("end;");
+                }
+                code.next("else");
+                code.line(&format!("raise {RUNTIME}.Decode_Error;"));
+                code.close("end if;");
+            }
+
+            Kind::Enumerated(enumerated) => {
+                code.open("declare");
+                code.line(&format!("Number : {RUNTIME}.Integer_Value;"));
+                code.next("begin");
+                code.line(&format!("{RUNTIME}.Read_Integer (Buffer, R, Number);"));
+                code.open("case Number is");
+                for (constant, number, _) in &enumerated.items {
+                    code.open(&format!("when {number} =>"));
+                    let literal = local_name(def.module, constant);
+                    code.line(&format!("Value := {package}.{literal};"));
+                    code.dedent();
+                }
+                code.open("when others =>");
+                code.line(&format!("raise {RUNTIME}.Decode_Error;"));
+                code.dedent();
+                code.close("end case;");
+                code.close("end;");
+            }
+
+            Kind::List(slot) => {
+                code.line("Value.Count := 0;");
+                code.open(&format!("while not {RUNTIME}.At_End (R) loop"));
+                code.open(&format!("if Value.Count = {RUNTIME}.Max_Items then"));
+                code.line(&format!("raise {RUNTIME}.Limit_Error;"));
+                code.close("end if;");
+                code.line("Value.Count := Value.Count + 1;");
+                self.ber_read(code, slot, "Value.Items (Value.Count)");
+                code.close("end loop;");
+            }
+
+            Kind::Alias(_) => unreachable!("aliases use the subprograms of their type"),
+        }
+    }
+
+    /// Write the expression of the next value being an alternative of a
+    /// choice
+    fn ber_starts(&self, code: &mut Code, def: &Definition) {
+        let Kind::Choice(choice) = &def.kind else {
+            unreachable!("only choices are untagged");
+        };
+        code.line("return");
+        for (i, alternative) in choice.alternatives.iter().enumerate() {
+            let start = if i == 0 { "  " } else { "  or else " };
+            let end = if i + 1 == choice.alternatives.len() {
+                ";"
+            } else {
+                ""
+            };
+            code.line(&format!(
+                "{start}{}{end}",
+                self.ber_matches(&alternative.slot)
+            ));
+        }
+        if choice.alternatives.is_empty() {
+            code.line("  False;");
+        }
+    }
+
+    /// Read the components of a sequence in order, skipping optional
+    /// components with other tags
+    fn ber_read_sequence(&self, code: &mut Code, fields: &[Field], extensible: bool) {
+        for field in fields {
+            let place = format!("Value.{}", field.name);
+            if field.presence.is_none() {
+                self.ber_read(code, &field.slot, &place);
+                continue;
+            }
+
+            code.open(&format!("if {} then", self.ber_matches(&field.slot)));
+            code.line(&format!("Value.Present.{} := True;", field.name));
+            self.ber_read(code, &field.slot, &place);
+            code.close("end if;");
+        }
+        if extensible {
+            code.open(&format!("while not {RUNTIME}.At_End (R) loop"));
+            code.line(&format!("{RUNTIME}.Skip (Buffer, R);"));
+            code.close("end loop;");
+        } else if fields.is_empty() {
+            code.line("null;");
+        }
+    }
+
+    /// Read the components of a set in any order
+    fn ber_read_set(&self, code: &mut Code, fields: &[Field], extensible: bool) {
+        let other = if extensible {
+            format!("{RUNTIME}.Skip (Buffer, R);")
+        } else {
+            format!("raise {RUNTIME}.Decode_Error;")
+        };
+        if fields.is_empty() {
+            code.open(&format!("while not {RUNTIME}.At_End (R) loop"));
+            code.line(&other);
+            code.close("end loop;");
+            return;
+        }
+
+        code.open("declare");
+        code.line(&format!(
+            "Seen : array (1 .. {}) of Boolean := [others => False];",
+            fields.len()
+        ));
+        code.next("begin");
+        code.open(&format!("while not {RUNTIME}.At_End (R) loop"));
+        for (i, field) in fields.iter().enumerate() {
+            let condition = format!(
+                "if not Seen ({}) and then {} then",
+                i + 1,
+                self.ber_matches(&field.slot)
+            );
+            if i == 0 {
+                code.open(&condition);
+            } else {
+                code.next(&format!("els{condition}"));
+            }
+            code.line(&format!("Seen ({}) := True;", i + 1));
+            if field.presence.is_some() {
+                code.line(&format!("Value.Present.{} := True;", field.name));
+            }
+            self.ber_read(code, &field.slot, &format!("Value.{}", field.name));
+        }
+        code.next("else");
+        code.line(&other);
+        code.close("end if;");
+        code.close("end loop;");
+
+        for (i, field) in fields.iter().enumerate() {
+            if field.presence.is_none() {
+                code.open(&format!("if not Seen ({}) then", i + 1));
+                code.line(&format!("raise {RUNTIME}.Decode_Error;"));
+                code.close("end if;");
+            }
+        }
+        code.close("end;");
+    }
+
+    /// Write the statements encoding the value stored in a place with its
+    /// tags
+    fn ber_write(&self, code: &mut Code, slot: &Slot, place: &str) {
+        let tagged = !slot.tags.is_empty();
+        if tagged {
+            code.open("declare");
+            code.line("Mark : constant Natural := Length;");
+            code.next("begin");
+        }
+
+        match &slot.ty {
+            SlotType::Primitive(primitive) => {
+                let call = match primitive {
+                    Primitive::Boolean => format!("Write_Boolean (Buffer, Length, {place})"),
+                    Primitive::Integer => format!("Write_Integer (Buffer, Length, {place})"),
+                    Primitive::Unsigned => unreachable!("unsigned integers are not supported"),
+                    Primitive::Real => format!("Write_Real (Buffer, Length, {place})"),
+                    Primitive::Null => String::new(),
+                    Primitive::BitString => format!("Write_Bits (Buffer, Length, {place})"),
+                    Primitive::Octets | Primitive::Chars { .. } => {
+                        format!("Write_Octets (Buffer, Length, {place})")
+                    }
+                    Primitive::Oid { relative } => {
+                        format!(
+                            "Write_Oid (Buffer, Length, {place}, {})",
+                            boolean(*relative)
+                        )
+                    }
+                    Primitive::Open => format!("Write_Open (Buffer, Length, {place})"),
+                };
+                if !call.is_empty() {
+                    code.line(&format!("{RUNTIME}.{call};"));
+                }
+            }
+            SlotType::Named { name, pointer } => {
+                let mut place = place.to_string();
+                if *pointer {
+                    code.open(&format!("if {place} = null then"));
+                    code.line(&format!("raise {RUNTIME}.Value_Error;"));
+                    code.close("end if;");
+                    place.push_str(".all");
+                }
+                code.line(&format!(
+                    "{}_Ber_Write (Buffer, Length, {place});",
+                    self.qualified(name)
+                ));
+            }
+        }
+
+        for &(tag, constructed) in slot.tags.iter().rev() {
+            code.line(&format!(
+                "{RUNTIME}.Write_Header (Buffer, Length, {}, {}, Length - Mark);",
+                tag_arguments(tag),
+                boolean(constructed)
+            ));
+        }
+        if tagged {
+            code.close("end;");
+        }
+    }
+
+    /// Write the statements decoding a value with its tags into a place
+    fn ber_read(&self, code: &mut Code, slot: &Slot, place: &str) {
+        let tagged = !slot.tags.is_empty();
+        let reader = if tagged {
+            code.open("declare");
+            let readers: Vec<_> = (0..slot.tags.len()).map(|i| format!("C{i}")).collect();
+            code.line(&format!("{} : {RUNTIME}.Ber_Reader;", readers.join(", ")));
+            code.next("begin");
+            for (i, &(tag, _)) in slot.tags.iter().enumerate() {
+                let outer = match i {
+                    0 => "R".to_string(),
+                    i => format!("C{}", i - 1),
+                };
+                code.line(&format!(
+                    "{RUNTIME}.Read_Header (Buffer, {outer}, {}, C{i});",
+                    tag_arguments(tag)
+                ));
+            }
+            format!("C{}", slot.tags.len() - 1)
+        } else {
+            "R".to_string()
+        };
+
+        match (&slot.ty, value_range(slot)) {
+            // integers are checked against their range before being stored,
+            // so that values outside of it are not valid encodings
+            (SlotType::Primitive(Primitive::Integer), Some(range)) => {
+                code.open("declare");
+                code.line(&format!("Number : {RUNTIME}.Integer_Value;"));
+                code.next("begin");
+                code.line(&format!(
+                    "{RUNTIME}.Read_Integer (Buffer, {reader}, Number);"
+                ));
+                code.open(&format!("if Number not in {range} then"));
+                code.line(&format!("raise {RUNTIME}.Decode_Error;"));
+                code.close("end if;");
+                code.line(&format!("{place} := Number;"));
+                code.close("end;");
+            }
+            (SlotType::Primitive(primitive), _) => {
+                let call = match primitive {
+                    Primitive::Boolean => format!("Read_Boolean (Buffer, {reader}, {place})"),
+                    Primitive::Integer => format!("Read_Integer (Buffer, {reader}, {place})"),
+                    Primitive::Unsigned => unreachable!("unsigned integers are not supported"),
+                    Primitive::Real => format!("Read_Real (Buffer, {reader}, {place})"),
+                    Primitive::Null => String::new(),
+                    Primitive::BitString => format!("Read_Bits (Buffer, {reader}, {place})"),
+                    Primitive::Octets | Primitive::Chars { .. } => {
+                        format!("Read_Octets (Buffer, {reader}, {place})")
+                    }
+                    Primitive::Oid { relative } => format!(
+                        "Read_Oid (Buffer, {reader}, {place}, {})",
+                        boolean(*relative)
+                    ),
+                    Primitive::Open => format!("Read_Open (Buffer, {reader}, {place})"),
+                };
+                if !call.is_empty() {
+                    code.line(&format!("{RUNTIME}.{call};"));
+                }
+            }
+            (
+                SlotType::Named {
+                    name,
+                    pointer: true,
+                },
+                _,
+            ) => {
+                // the value is read before it is allocated, as a choice
+                // allocated without a constraint cannot change its kind
+                let ty = self.qualified(name);
+                code.open("declare");
+                code.line(&format!("Pointee : {ty};"));
+                code.next("begin");
+                code.line(&format!("{ty}_Ber_Read (Buffer, {reader}, Pointee);"));
+                code.line(&format!("{place} := new {ty}'(Pointee);"));
+                code.close("end;");
+            }
+            (SlotType::Named { name, .. }, _) => {
+                code.line(&format!(
+                    "{}_Ber_Read (Buffer, {reader}, {place});",
+                    self.qualified(name)
+                ));
+            }
+        }
+
+        if tagged {
+            for i in (0..slot.tags.len()).rev() {
+                code.line(&format!("{RUNTIME}.Read_End (C{i});"));
+            }
+            code.close("end;");
+        }
+    }
+
+    /// Get the condition of the next value read by `R` being a slot, from
+    /// its first tag
+    fn ber_matches(&self, slot: &Slot) -> String {
+        match (slot.tags.first(), &slot.ty) {
+            (Some(&(tag, _)), _) => {
+                format!("{RUNTIME}.Next_Tag_Is (Buffer, R, {})", tag_arguments(tag))
+            }
+            (None, SlotType::Named { name, .. }) => {
+                format!("{}_Ber_Starts (Buffer, R)", self.qualified(name))
+            }
+            (None, SlotType::Primitive(_)) => format!("not {RUNTIME}.At_End (R)"),
+        }
+    }
+
+    /// Write statements that are only run if an optional field is present
+    fn optional(&self, code: &mut Code, field: &Field, body: impl FnOnce(&mut Code)) {
+        if field.presence.is_none() {
+            body(code);
+            return;
+        }
+        code.open(&format!("if Value.Present.{} then", field.name));
+        body(code);
+        code.close("end if;");
+    }
+}
+
+/// Get the arguments of the class and number of a tag
+fn tag_arguments(tag: Tag) -> String {
+    format!("{RUNTIME}.{}, {}", CLASSES[tag.class], tag.number)
+}
+
+fn boolean(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
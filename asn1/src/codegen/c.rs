@@ -0,0 +1,377 @@
+//! C code generated from analysed modules, with a header and source file for
+//! each module, and the runtime they are built with.
+//!
+//! Each type assignment is a C type named `Module_Name`, with functions to
+//! encode and decode its values in BER and UPER (unaligned PER) into a buffer
+//! provided by the caller.  No memory is allocated:
+//! - `SEQUENCE` and `SET` types are structs with a member for each
+//!   component.  `OPTIONAL` and `DEFAULT` components and extension additions
+//!   have a bit in the `present` bitmask, defined as `Type_name_PRESENT`.
+//! - `CHOICE` types are structs with the `present` alternative and a union
+//!   of the alternatives named `choice`.
+//! - `ENUMERATED` types are enums, and `SEQUENCE OF` and `SET OF` types are
+//!   structs with a `count` of `items`, up to `ASN1_MAX_ITEMS`.
+//! - `INTEGER` is `int64_t`, or `uint64_t` if its value range is non-negative
+//!   and reaches beyond `INT64_MAX`.  `REAL` is `double`, and strings, bit
+//!   strings and object identifiers are the fixed capacity types of the
+//!   runtime.  Open types, `EXTERNAL`, `EMBEDDED PDV` and `CHARACTER STRING`
+//!   hold encodings of their values.
+//! - Types nested within another type are named after the component or
+//!   alternative using them, or `item` for the elements of a `SEQUENCE OF`.
+//!
+//! Types that contain themselves are stored through pointers, which must be
+//! given storage before a value is decoded.  The value ranges, sizes and
+//! permitted alphabets of PER-visible constraints are part of the PER
+//! encodings, and `SEQUENCE OF` and `SET OF` types are given the size
+//! constraint of each use of them when encoded.  Values are not checked
+//! against their constraints in BER.  `DEFAULT` values are always encoded
+//! when present, and the time types other than `UTCTime` and
+//! `GeneralizedTime` are encoded as octet strings in PER.  Extension
+//! additions that are not known are skipped when decoding, except choice
+//! alternatives and enumeration items, which cannot be represented.
+
+mod ber;
+mod uper;
+
+use std::collections::BTreeSet;
+
+use convert_case::{Case, Casing};
+
+use crate::analysis::AnalysisContext;
+
+use super::{
+    model::{
+        self, identifier, Code, Definition, Field, Kind, Model, Primitive, Slot, SlotType, Tag,
+    },
+    GeneratedFile, Result,
+};
+
+/// Name of the runtime header included by every generated file
+const RUNTIME_HEADER: &str = "asn1_runtime.h";
+
+/// Tag classes as numbered by the runtime, in the canonical order of tags
+const CLASSES: [&str; 4] = [
+    "ASN1_UNIVERSAL",
+    "ASN1_APPLICATION",
+    "ASN1_CONTEXT",
+    "ASN1_PRIVATE",
+];
+
+/// Identifiers that cannot name a member or enumeration constant
+const RESERVED: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "choice", "const", "continue", "default", "do",
+    "double", "else", "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int",
+    "long", "present", "register", "restrict", "return", "short", "signed", "sizeof", "static",
+    "struct", "switch", "true", "typedef", "union", "unsigned", "void", "volatile", "while",
+];
+
+/// Generate the files of every analysed module, followed by the runtime
+pub(super) fn generate(analysis: &AnalysisContext) -> Result<Vec<GeneratedFile>> {
+    let definitions = model::definitions(analysis, member_name)?;
+    let generator = Generator {
+        model: Model::new(&definitions),
+    };
+    let mut modules: Vec<_> = analysis.modules().map(|module| &module.name).collect();
+    modules.sort();
+
+    let mut files = vec![];
+    for module in modules {
+        files.extend(generator.module(module));
+    }
+    files.push(GeneratedFile {
+        file: RUNTIME_HEADER.into(),
+        content: include_str!("c/asn1_runtime.h").into(),
+    });
+    files.push(GeneratedFile {
+        file: "asn1_runtime.c".into(),
+        content: include_str!("c/asn1_runtime.c").into(),
+    });
+    Ok(files)
+}
+
+/// Get the name of a member or enumeration item, which must not be a
+/// keyword or the name of another member of a struct
+fn member_name(name: &str) -> String {
+    let name = identifier(name);
+    if RESERVED.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+/// Get the name of the files generated for a module, without an extension
+fn file_name(module: &str) -> String {
+    identifier(module).to_case(Case::Snake)
+}
+
+impl Code {
+    /// Write a comment, if there is one
+    fn comment(&mut self, comment: Option<&str>) {
+        let Some(comment) = comment else {
+            return;
+        };
+
+        let comment = comment.replace("*/", "* /");
+        if !comment.contains('\n') {
+            self.line(&format!("/* {comment} */"));
+            return;
+        }
+        self.line("/*");
+        for line in comment.lines() {
+            self.line(format!(" * {line}").trim_end());
+        }
+        self.line(" */");
+    }
+}
+
+/// Writes the files of each module from the definitions of every module
+struct Generator<'a> {
+    model: Model<'a>,
+}
+
+impl<'a> Generator<'a> {
+    /// Write the header and source file of a module
+    fn module(&self, module: &str) -> [GeneratedFile; 2] {
+        let definitions = self.model.ordered(module);
+        let file = file_name(module);
+
+        let mut header = Code::default();
+        let guard = format!("{}_H", file.to_case(Case::UpperSnake));
+        header.line(&format!("/* Generated from the ASN.1 module `{module}` */"));
+        header.line(&format!("#ifndef {guard}"));
+        header.line(&format!("#define {guard}"));
+        header.line("");
+        header.line(&format!("#include \"{RUNTIME_HEADER}\""));
+        let by_value = definitions
+            .iter()
+            .flat_map(|def| self.model.dependencies(def, false))
+            .map(|name| self.model.definition(name).module);
+        for include in self.includes(module, by_value) {
+            header.line(&format!("#include \"{include}.h\""));
+        }
+        for def in &definitions {
+            header.line("");
+            self.declaration(&mut header, def);
+        }
+        header.line("");
+        header.line("#endif");
+
+        let mut source = Code::default();
+        source.line(&format!("/* Generated from the ASN.1 module `{module}` */"));
+        source.line(&format!("#include \"{file}.h\""));
+        let referenced = definitions
+            .iter()
+            .flat_map(|def| self.model.dependencies(def, true))
+            .map(|name| self.model.definition(name).module);
+        for include in self.includes(module, referenced) {
+            source.line(&format!("#include \"{include}.h\""));
+        }
+        for def in &definitions {
+            self.ber_functions(&mut source, def);
+            self.uper_functions(&mut source, def);
+        }
+
+        [
+            GeneratedFile {
+                file: format!("{file}.h"),
+                content: header.text,
+            },
+            GeneratedFile {
+                file: format!("{file}.c"),
+                content: source.text,
+            },
+        ]
+    }
+
+    /// Get the headers of other modules to include
+    fn includes(&self, module: &str, modules: impl Iterator<Item = &'a str>) -> BTreeSet<String> {
+        modules
+            .filter(|&other| other != module)
+            .map(file_name)
+            .collect()
+    }
+
+    /// Get the smallest tag of a slot, which orders the components of a set
+    /// and the alternatives of a choice
+    fn canonical_tag(&self, slot: &Slot) -> Tag {
+        if let Some((tag, _)) = slot.tags.first() {
+            return *tag;
+        }
+        match &slot.ty {
+            SlotType::Named { name, .. } => match &self.model.definition(name).kind {
+                Kind::Choice(choice) => choice
+                    .alternatives
+                    .iter()
+                    .map(|alternative| self.canonical_tag(&alternative.slot))
+                    .min()
+                    .unwrap_or(Tag {
+                        class: 0,
+                        number: 0,
+                    }),
+                _ => Tag {
+                    class: 0,
+                    number: 0,
+                },
+            },
+            SlotType::Primitive(_) => Tag {
+                class: 0,
+                number: 0,
+            },
+        }
+    }
+
+    /// Sort the root items of a set or choice by their tags, the order they
+    /// are encoded in PER
+    fn tag_order(&self, items: &[usize], fields: &[Field]) -> Vec<usize> {
+        let mut items = items.to_vec();
+        items.sort_by_key(|&i| self.canonical_tag(&fields[i].slot));
+        items
+    }
+
+    /// Write the C type and function prototypes of a definition
+    fn declaration(&self, code: &mut Code, def: &Definition) {
+        let name = &def.name;
+        code.comment(def.doc);
+        match &def.kind {
+            Kind::Sequence(sequence) => {
+                let bits = if sequence.presence > 32 { 64 } else { 32 };
+                if sequence.presence > 64 {
+                    code.line(&format!(
+                        "#error \"{name} has more than 64 optional components\""
+                    ));
+                }
+                code.open(&format!("typedef struct {name} {{"));
+                if sequence.presence > 0 {
+                    code.line(&format!("uint{bits}_t present;"));
+                }
+                for field in &sequence.fields {
+                    code.comment(field.doc);
+                    code.line(&format!("{};", self.member(&field.slot, &field.name)));
+                }
+                if sequence.presence == 0 && sequence.fields.is_empty() {
+                    code.line("uint8_t unused;");
+                }
+                code.close(&format!("}} {name};"));
+                for field in &sequence.fields {
+                    if let Some(bit) = field.presence {
+                        code.line(&format!(
+                            "#define {name}_{}_PRESENT (UINT{bits}_C(1) << {bit})",
+                            field.name
+                        ));
+                    }
+                }
+            }
+            Kind::Choice(choice) => {
+                code.open("typedef enum {");
+                code.line(&format!("{name}_PR_NOTHING,"));
+                for alternative in &choice.alternatives {
+                    code.line(&format!("{name}_PR_{},", alternative.name));
+                }
+                code.close(&format!("}} {name}_PR;"));
+                code.open(&format!("typedef struct {name} {{"));
+                code.line(&format!("{name}_PR present;"));
+                code.open("union {");
+                for alternative in &choice.alternatives {
+                    code.comment(alternative.doc);
+                    code.line(&format!(
+                        "{};",
+                        self.member(&alternative.slot, &alternative.name)
+                    ));
+                }
+                code.close("} choice;");
+                code.close(&format!("}} {name};"));
+            }
+            Kind::Enumerated(enumerated) => {
+                code.open("typedef enum {");
+                for (constant, number, doc) in &enumerated.items {
+                    code.comment(*doc);
+                    code.line(&format!("{constant} = {number},"));
+                }
+                code.close(&format!("}} {name};"));
+            }
+            Kind::List(slot) => {
+                code.open(&format!("typedef struct {name} {{"));
+                code.line("size_t count;");
+                code.line(&format!("{};", self.member(slot, "items[ASN1_MAX_ITEMS]")));
+                code.close(&format!("}} {name};"));
+            }
+            Kind::Alias(slot) => code.line(&format!("typedef {};", self.member(slot, name))),
+        }
+
+        let ber = "asn1_ber_writer *w";
+        if !matches!(def.kind, Kind::Alias(_)) {
+            code.line(&format!(
+                "int {name}_ber_write_contents({ber}, const {name} *value);"
+            ));
+            code.line(&format!(
+                "int {name}_ber_read_contents(asn1_ber_reader *r, {name} *value);"
+            ));
+            if matches!(def.kind, Kind::Choice(_)) {
+                code.line(&format!("bool {name}_ber_has_tag(uint32_t tag);"));
+            }
+            // lists are given the size constraint of the slot using them
+            let size = match def.kind {
+                Kind::List(_) => ", const asn1_range *size",
+                _ => "",
+            };
+            code.line(&format!(
+                "int {name}_uper_write(asn1_bit_writer *w, const {name} *value{size});"
+            ));
+            code.line(&format!(
+                "int {name}_uper_read(asn1_bit_reader *r, {name} *value{size});"
+            ));
+        }
+        if def.assignment.is_some() {
+            for rules in ["ber", "uper"] {
+                code.line(&format!(
+                    "int {name}_encode_{rules}(const {name} *value, uint8_t *buffer, size_t size, size_t *length);"
+                ));
+                code.line(&format!(
+                    "int {name}_decode_{rules}({name} *value, const uint8_t *buffer, size_t size, size_t *length);"
+                ));
+            }
+        }
+    }
+
+    /// Declare a member or type of a slot
+    fn member(&self, slot: &Slot, name: &str) -> String {
+        let ty = match &slot.ty {
+            SlotType::Named {
+                name: ty,
+                pointer: true,
+            } => return format!("struct {ty} *{name}"),
+            SlotType::Named { name: ty, .. } => ty,
+            SlotType::Primitive(primitive) => match primitive {
+                Primitive::Boolean => "bool",
+                Primitive::Integer => "int64_t",
+                Primitive::Unsigned => "uint64_t",
+                Primitive::Real => "double",
+                Primitive::Null => "asn1_null",
+                Primitive::BitString => "asn1_bits",
+                Primitive::Octets | Primitive::Chars { .. } => "asn1_octets",
+                Primitive::Oid { .. } => "asn1_oid",
+                Primitive::Open => "asn1_open",
+            },
+        };
+        format!("{ty} {name}")
+    }
+}
+
+/// Get the argument passing the value stored in a place to a function, which
+/// is the value itself for pointers and scalar types, and its address
+/// otherwise
+fn argument(slot: &Slot, place: &str) -> String {
+    match &slot.ty {
+        SlotType::Named { pointer: true, .. } => place.to_string(),
+        SlotType::Primitive(
+            Primitive::Boolean | Primitive::Integer | Primitive::Unsigned | Primitive::Real,
+        ) => place.to_string(),
+        _ => format!("&{place}"),
+    }
+}
+
+/// Get the C expression of a tag
+fn tag(tag: Tag) -> String {
+    format!("ASN1_TAG({}, {})", CLASSES[tag.class], tag.number)
+}
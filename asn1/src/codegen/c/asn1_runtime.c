@@ -0,0 +1,922 @@
+/*
+ * Runtime support for C code generated from ASN.1 modules.
+ */
+#include "asn1_runtime.h"
+
+#include <math.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Longest contents of a real value that are decoded */
+#define REAL_OCTETS 64
+
+/* Longest contents of an object identifier value */
+#define OID_OCTETS (ASN1_MAX_ARCS * 5 + 5)
+
+/* Contents octets shared by the encoding rules */
+
+/* Write the minimal two's complement octets of an integer */
+static size_t integer_contents(int64_t value, uint8_t out[8]) {
+    uint64_t bits = (uint64_t)value;
+    size_t length = 8;
+    for (size_t i = 0; i < 8; i++)
+        out[i] = (uint8_t)(bits >> (56 - 8 * i));
+
+    size_t start = 0;
+    while (start < 7 && ((out[start] == 0x00 && !(out[start + 1] & 0x80)) ||
+                         (out[start] == 0xff && (out[start + 1] & 0x80))))
+        start++;
+    memmove(out, out + start, length - start);
+    return length - start;
+}
+
+static int integer_parse(const uint8_t *data, size_t length, int64_t *value) {
+    if (length == 0)
+        return ASN1_ERR_DECODE;
+    if (length > 8)
+        return ASN1_ERR_LIMIT;
+
+    uint64_t bits = (data[0] & 0x80) ? UINT64_MAX : 0;
+    for (size_t i = 0; i < length; i++)
+        bits = (bits << 8) | data[i];
+    *value = (int64_t)bits;
+    return ASN1_OK;
+}
+
+/* Write the minimal two's complement octets of a non-negative integer,
+ * which need a leading zero octet if its top bit is set */
+static size_t unsigned_contents(uint64_t value, uint8_t out[9]) {
+    size_t length = 9;
+    out[0] = 0x00;
+    for (size_t i = 0; i < 8; i++)
+        out[i + 1] = (uint8_t)(value >> (56 - 8 * i));
+
+    size_t start = 0;
+    while (start < 8 && out[start] == 0x00 && !(out[start + 1] & 0x80))
+        start++;
+    memmove(out, out + start, length - start);
+    return length - start;
+}
+
+static int unsigned_parse(const uint8_t *data, size_t length, uint64_t *value) {
+    if (length == 0)
+        return ASN1_ERR_DECODE;
+    if ((data[0] & 0x80) || length > 9 || (length == 9 && data[0]))
+        return ASN1_ERR_LIMIT;
+
+    uint64_t bits = 0;
+    for (size_t i = 0; i < length; i++)
+        bits = (bits << 8) | data[i];
+    *value = bits;
+    return ASN1_OK;
+}
+
+/* Write the contents of a real value in base 2 */
+static size_t real_contents(double value, uint8_t out[16]) {
+    if (value == 0)
+        return signbit(value) ? (out[0] = 0x43, 1) : 0;
+    if (isnan(value))
+        return out[0] = 0x42, 1;
+    if (isinf(value))
+        return out[0] = value > 0 ? 0x40 : 0x41, 1;
+
+    int exponent;
+    double fraction = frexp(fabs(value), &exponent);
+    uint64_t mantissa = (uint64_t)ldexp(fraction, 53);
+    exponent -= 53;
+    while (!(mantissa & 1)) {
+        mantissa >>= 1;
+        exponent++;
+    }
+
+    uint8_t exponent_octets[8];
+    size_t exponent_length = integer_contents(exponent, exponent_octets);
+    size_t length = 0;
+    out[length++] = (uint8_t)(0x80 | (value < 0 ? 0x40 : 0) | (exponent_length - 1));
+    memcpy(out + length, exponent_octets, exponent_length);
+    length += exponent_length;
+
+    uint8_t mantissa_octets[8];
+    size_t mantissa_length = 0;
+    for (int shift = 56; shift >= 0; shift -= 8)
+        if (mantissa >> shift || mantissa_length)
+            mantissa_octets[mantissa_length++] = (uint8_t)(mantissa >> shift);
+    memcpy(out + length, mantissa_octets, mantissa_length);
+    return length + mantissa_length;
+}
+
+static int real_parse(const uint8_t *data, size_t length, double *value) {
+    if (length == 0) {
+        *value = 0;
+        return ASN1_OK;
+    }
+
+    uint8_t first = data[0];
+    if (first & 0x80) {
+        static const int base_bits[] = {1, 3, 4};
+        unsigned base = (first >> 4) & 3;
+        if (base == 3)
+            return ASN1_ERR_DECODE;
+
+        size_t position = 1;
+        size_t exponent_length = (first & 3) + 1;
+        if (exponent_length == 4) {
+            if (length < 2)
+                return ASN1_ERR_DECODE;
+            exponent_length = data[position++];
+        }
+        if (position + exponent_length > length)
+            return ASN1_ERR_DECODE;
+
+        int64_t exponent;
+        ASN1_CHECK(integer_parse(data + position, exponent_length, &exponent));
+        position += exponent_length;
+        if (length - position > 8)
+            return ASN1_ERR_LIMIT;
+
+        uint64_t mantissa = 0;
+        for (; position < length; position++)
+            mantissa = (mantissa << 8) | data[position];
+
+        int64_t scale = (first >> 2) & 3;
+        int64_t power = exponent * base_bits[base] + scale;
+        if (power > 4096 || power < -4096)
+            return ASN1_ERR_LIMIT;
+        *value = ldexp((double)mantissa, (int)power);
+        if (first & 0x40)
+            *value = -*value;
+        return ASN1_OK;
+    }
+
+    if (first & 0x40) {
+        switch (first) {
+        case 0x40: *value = INFINITY; return ASN1_OK;
+        case 0x41: *value = -INFINITY; return ASN1_OK;
+        case 0x42: *value = NAN; return ASN1_OK;
+        case 0x43: *value = -0.0; return ASN1_OK;
+        default: return ASN1_ERR_DECODE;
+        }
+    }
+
+    /* decimal forms, which strtod accepts after a decimal comma is replaced */
+    char text[REAL_OCTETS];
+    if (length > sizeof(text))
+        return ASN1_ERR_LIMIT;
+    for (size_t i = 1; i < length; i++)
+        text[i - 1] = data[i] == ',' ? '.' : (char)data[i];
+    text[length - 1] = '\0';
+    char *end;
+    *value = strtod(text, &end);
+    return *end == '\0' && end != text ? ASN1_OK : ASN1_ERR_DECODE;
+}
+
+static int oid_contents(const asn1_oid *value, bool relative, uint8_t *out, size_t *length) {
+    if (value->count > ASN1_MAX_ARCS || (!relative && value->count < 2))
+        return ASN1_ERR_VALUE;
+
+    size_t first = relative ? 0 : 1;
+    *length = 0;
+    for (size_t i = first; i < value->count; i++) {
+        uint64_t arc = value->arcs[i];
+        if (i == 1 && !relative) {
+            if (value->arcs[0] > 2 || (value->arcs[0] < 2 && arc >= 40))
+                return ASN1_ERR_VALUE;
+            arc += value->arcs[0] * 40;
+        }
+
+        uint8_t octets[10];
+        size_t count = 0;
+        do {
+            octets[count++] = (uint8_t)(arc & 0x7f);
+            arc >>= 7;
+        } while (arc);
+        while (count) {
+            count--;
+            out[(*length)++] = (uint8_t)(octets[count] | (count ? 0x80 : 0));
+        }
+    }
+    return ASN1_OK;
+}
+
+static int oid_parse(const uint8_t *data, size_t length, asn1_oid *value, bool relative) {
+    if (length == 0 || (data[length - 1] & 0x80))
+        return ASN1_ERR_DECODE;
+
+    value->count = 0;
+    uint64_t arc = 0;
+    for (size_t i = 0; i < length; i++) {
+        if (arc == 0 && data[i] == 0x80)
+            return ASN1_ERR_DECODE;
+        arc = (arc << 7) | (data[i] & 0x7f);
+        if (arc > UINT32_MAX + (uint64_t)80)
+            return ASN1_ERR_LIMIT;
+        if (data[i] & 0x80)
+            continue;
+
+        bool first_arcs = value->count == 0 && !relative;
+        if (value->count + (first_arcs ? 2 : 1) > ASN1_MAX_ARCS)
+            return ASN1_ERR_LIMIT;
+        if (first_arcs) {
+            uint32_t first = arc < 40 ? 0 : arc < 80 ? 1 : 2;
+            value->arcs[value->count++] = first;
+            arc -= first * 40;
+            if (arc > UINT32_MAX)
+                return ASN1_ERR_LIMIT;
+        }
+        value->arcs[value->count++] = (uint32_t)arc;
+        arc = 0;
+    }
+    return ASN1_OK;
+}
+
+/* Basic Encoding Rules */
+
+int asn1_ber_prepend(asn1_ber_writer *w, const uint8_t *data, size_t length) {
+    if (length > w->size - w->length)
+        return ASN1_ERR_BUFFER;
+    w->length += length;
+    if (length)
+        memcpy(w->data + w->size - w->length, data, length);
+    return ASN1_OK;
+}
+
+int asn1_ber_write_header(asn1_ber_writer *w, uint32_t tag, bool constructed, size_t length) {
+    uint8_t header[16];
+    size_t start = sizeof(header);
+
+    if (length < 0x80) {
+        header[--start] = (uint8_t)length;
+    } else {
+        size_t count = 0;
+        for (; length; length >>= 8, count++)
+            header[--start] = (uint8_t)length;
+        header[--start] = (uint8_t)(0x80 | count);
+    }
+
+    uint32_t number = tag & 0x3fffffff;
+    uint8_t identifier = (uint8_t)((tag >> 30) << 6 | (constructed ? 0x20 : 0));
+    if (number < 31) {
+        header[--start] = identifier | (uint8_t)number;
+    } else {
+        header[--start] = (uint8_t)(number & 0x7f);
+        for (number >>= 7; number; number >>= 7)
+            header[--start] = (uint8_t)(0x80 | (number & 0x7f));
+        header[--start] = identifier | 31;
+    }
+    return asn1_ber_prepend(w, header + start, sizeof(header) - start);
+}
+
+int asn1_ber_write_boolean(asn1_ber_writer *w, bool value) {
+    uint8_t octet = value ? 0xff : 0x00;
+    return asn1_ber_prepend(w, &octet, 1);
+}
+
+int asn1_ber_write_integer(asn1_ber_writer *w, int64_t value) {
+    uint8_t octets[8];
+    size_t length = integer_contents(value, octets);
+    return asn1_ber_prepend(w, octets, length);
+}
+
+int asn1_ber_write_unsigned(asn1_ber_writer *w, uint64_t value) {
+    uint8_t octets[9];
+    size_t length = unsigned_contents(value, octets);
+    return asn1_ber_prepend(w, octets, length);
+}
+
+int asn1_ber_write_real(asn1_ber_writer *w, double value) {
+    uint8_t octets[16];
+    size_t length = real_contents(value, octets);
+    return asn1_ber_prepend(w, octets, length);
+}
+
+int asn1_ber_write_bits(asn1_ber_writer *w, const asn1_bits *value) {
+    if (value->bits > ASN1_MAX_OCTETS * 8)
+        return ASN1_ERR_VALUE;
+    size_t octets = (value->bits + 7) / 8;
+    uint8_t unused = (uint8_t)(octets * 8 - value->bits);
+    ASN1_CHECK(asn1_ber_prepend(w, value->data, octets));
+    return asn1_ber_prepend(w, &unused, 1);
+}
+
+int asn1_ber_write_octets(asn1_ber_writer *w, const asn1_octets *value) {
+    if (value->length > ASN1_MAX_OCTETS)
+        return ASN1_ERR_VALUE;
+    return asn1_ber_prepend(w, value->data, value->length);
+}
+
+int asn1_ber_write_oid(asn1_ber_writer *w, const asn1_oid *value, bool relative) {
+    uint8_t octets[OID_OCTETS];
+    size_t length;
+    ASN1_CHECK(oid_contents(value, relative, octets, &length));
+    return asn1_ber_prepend(w, octets, length);
+}
+
+void asn1_ber_finish(asn1_ber_writer *w) {
+    memmove(w->data, w->data + w->size - w->length, w->length);
+}
+
+bool asn1_ber_at_end(const asn1_ber_reader *r) { return r->position >= r->size; }
+
+/* Read the identifier and length octets of a value at a position */
+static int read_header(const asn1_ber_reader *r, size_t *position, uint32_t *tag,
+                       size_t *length) {
+    size_t i = *position;
+    if (i >= r->size)
+        return ASN1_ERR_BUFFER;
+
+    uint8_t identifier = r->data[i++];
+    uint32_t number = identifier & 0x1f;
+    if (number == 31) {
+        number = 0;
+        do {
+            if (i >= r->size)
+                return ASN1_ERR_BUFFER;
+            if (number >> 23)
+                return ASN1_ERR_LIMIT;
+            number = (number << 7) | (r->data[i] & 0x7f);
+        } while (r->data[i++] & 0x80);
+    }
+    *tag = ASN1_TAG(identifier >> 6, number);
+
+    if (i >= r->size)
+        return ASN1_ERR_BUFFER;
+    uint8_t first = r->data[i++];
+    if (first == 0x80) {
+        /* the indefinite length form is not supported */
+        return ASN1_ERR_DECODE;
+    } else if (first & 0x80) {
+        size_t count = first & 0x7f;
+        if (count > sizeof(size_t))
+            return ASN1_ERR_LIMIT;
+        if (count > r->size - i)
+            return ASN1_ERR_BUFFER;
+        *length = 0;
+        while (count--)
+            *length = (*length << 8) | r->data[i++];
+    } else {
+        *length = first;
+    }
+
+    if (*length > r->size - i)
+        return ASN1_ERR_BUFFER;
+    *position = i;
+    return ASN1_OK;
+}
+
+int asn1_ber_peek_tag(const asn1_ber_reader *r, uint32_t *tag) {
+    size_t position = r->position;
+    size_t length;
+    return read_header(r, &position, tag, &length);
+}
+
+int asn1_ber_read_header(asn1_ber_reader *r, uint32_t tag, asn1_ber_reader *contents) {
+    size_t position = r->position;
+    uint32_t found;
+    size_t length;
+    ASN1_CHECK(read_header(r, &position, &found, &length));
+    if (found != tag)
+        return ASN1_ERR_DECODE;
+
+    contents->data = r->data + position;
+    contents->size = length;
+    contents->position = 0;
+    r->position = position + length;
+    return ASN1_OK;
+}
+
+int asn1_ber_skip(asn1_ber_reader *r) {
+    size_t position = r->position;
+    uint32_t tag;
+    size_t length;
+    ASN1_CHECK(read_header(r, &position, &tag, &length));
+    r->position = position + length;
+    return ASN1_OK;
+}
+
+int asn1_ber_read_end(const asn1_ber_reader *contents) {
+    return asn1_ber_at_end(contents) ? ASN1_OK : ASN1_ERR_DECODE;
+}
+
+/* Take the remaining contents of a value */
+static const uint8_t *remaining(asn1_ber_reader *contents, size_t *length) {
+    const uint8_t *data = contents->data + contents->position;
+    *length = contents->size - contents->position;
+    contents->position = contents->size;
+    return data;
+}
+
+int asn1_ber_read_boolean(asn1_ber_reader *contents, bool *value) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    if (length != 1)
+        return ASN1_ERR_DECODE;
+    *value = data[0] != 0;
+    return ASN1_OK;
+}
+
+int asn1_ber_read_integer(asn1_ber_reader *contents, int64_t *value) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    return integer_parse(data, length, value);
+}
+
+int asn1_ber_read_unsigned(asn1_ber_reader *contents, uint64_t *value) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    return unsigned_parse(data, length, value);
+}
+
+int asn1_ber_read_real(asn1_ber_reader *contents, double *value) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    return real_parse(data, length, value);
+}
+
+int asn1_ber_read_bits(asn1_ber_reader *contents, asn1_bits *value) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    if (length == 0 || data[0] > 7 || (length == 1 && data[0] != 0))
+        return ASN1_ERR_DECODE;
+    if (length - 1 > ASN1_MAX_OCTETS)
+        return ASN1_ERR_LIMIT;
+
+    memcpy(value->data, data + 1, length - 1);
+    value->bits = (length - 1) * 8 - data[0];
+    return ASN1_OK;
+}
+
+int asn1_ber_read_octets(asn1_ber_reader *contents, asn1_octets *value) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    if (length > ASN1_MAX_OCTETS)
+        return ASN1_ERR_LIMIT;
+
+    if (length)
+        memcpy(value->data, data, length);
+    value->length = length;
+    return ASN1_OK;
+}
+
+int asn1_ber_read_oid(asn1_ber_reader *contents, asn1_oid *value, bool relative) {
+    size_t length;
+    const uint8_t *data = remaining(contents, &length);
+    return oid_parse(data, length, value, relative);
+}
+
+int asn1_ber_read_open(asn1_ber_reader *r, asn1_open *value) {
+    size_t start = r->position;
+    ASN1_CHECK(asn1_ber_skip(r));
+    if (r->position - start > ASN1_MAX_OCTETS)
+        return ASN1_ERR_LIMIT;
+
+    value->length = r->position - start;
+    memcpy(value->data, r->data + start, value->length);
+    return ASN1_OK;
+}
+
+/* Unaligned Packed Encoding Rules */
+
+int asn1_uper_write_bits(asn1_bit_writer *w, uint64_t value, unsigned count) {
+    if (!w->data) {
+        w->bit += count;
+        return ASN1_OK;
+    }
+    if (count > w->size * 8 - w->bit)
+        return ASN1_ERR_BUFFER;
+
+    while (count--) {
+        uint8_t mask = (uint8_t)(0x80 >> (w->bit % 8));
+        if ((value >> count) & 1)
+            w->data[w->bit / 8] |= mask;
+        else
+            w->data[w->bit / 8] &= (uint8_t)~mask;
+        w->bit++;
+    }
+    return ASN1_OK;
+}
+
+int asn1_uper_read_bits(asn1_bit_reader *r, unsigned count, uint64_t *value) {
+    if (count > r->bits - r->bit)
+        return ASN1_ERR_BUFFER;
+
+    *value = 0;
+    while (count--) {
+        uint8_t octet = r->data[r->bit / 8];
+        *value = (*value << 1) | ((octet >> (7 - r->bit % 8)) & 1);
+        r->bit++;
+    }
+    return ASN1_OK;
+}
+
+int asn1_uper_pad(asn1_bit_writer *w, size_t bit) {
+    while (w->bit < bit)
+        ASN1_CHECK(asn1_uper_write_bits(w, 0, 1));
+    return ASN1_OK;
+}
+
+/* Lengths of 16K or more are fragmented, which is not supported */
+int asn1_uper_write_length(asn1_bit_writer *w, size_t length) {
+    if (length < 0x80)
+        return asn1_uper_write_bits(w, length, 8);
+    if (length < 0x4000)
+        return asn1_uper_write_bits(w, 0x8000 | length, 16);
+    return ASN1_ERR_LIMIT;
+}
+
+int asn1_uper_read_length(asn1_bit_reader *r, size_t *length) {
+    uint64_t bits;
+    ASN1_CHECK(asn1_uper_read_bits(r, 1, &bits));
+    if (!bits) {
+        ASN1_CHECK(asn1_uper_read_bits(r, 7, &bits));
+        *length = (size_t)bits;
+        return ASN1_OK;
+    }
+
+    ASN1_CHECK(asn1_uper_read_bits(r, 1, &bits));
+    if (bits)
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(asn1_uper_read_bits(r, 14, &bits));
+    *length = (size_t)bits;
+    return ASN1_OK;
+}
+
+/* Is a value within the extension root of a range */
+static bool in_range(int64_t value, const asn1_range *range) {
+    if (!range)
+        return true;
+    if ((range->flags & ASN1_LOWER) && value < range->lower)
+        return false;
+    return !(range->flags & ASN1_UPPER) || value <= range->upper;
+}
+
+/* Write the extension bit of a value of an extensible range, and find if
+ * the value is encoded as unconstrained */
+static int write_extension(asn1_bit_writer *w, int64_t value, const asn1_range *range,
+                           bool *unconstrained) {
+    bool root = in_range(value, range);
+    *unconstrained = !range;
+    if (range && (range->flags & ASN1_EXTENSIBLE)) {
+        ASN1_CHECK(asn1_uper_write_bits(w, !root, 1));
+        *unconstrained = !root;
+    } else if (!root) {
+        return ASN1_ERR_VALUE;
+    }
+    return ASN1_OK;
+}
+
+static int read_extension(asn1_bit_reader *r, const asn1_range *range, bool *unconstrained) {
+    uint64_t bit = 0;
+    if (range && (range->flags & ASN1_EXTENSIBLE))
+        ASN1_CHECK(asn1_uper_read_bits(r, 1, &bit));
+    *unconstrained = !range || bit;
+    return ASN1_OK;
+}
+
+/* Number of bits encoding the offset of a value from the lower bound of a
+ * range with both bounds */
+static unsigned span_bits(uint64_t span) {
+    unsigned bits = 0;
+    while (bits < 64 && span >> bits)
+        bits++;
+    return bits;
+}
+
+static unsigned range_bits(const asn1_range *range) {
+    return span_bits((uint64_t)range->upper - (uint64_t)range->lower);
+}
+
+/* Lengths are constrained whole numbers if the upper bound of the size is
+ * below 64K, and are otherwise encoded as if unconstrained */
+static bool constrained_size(const asn1_range *size) {
+    return (size->flags & ASN1_UPPER) && size->upper < 0x10000;
+}
+
+int asn1_uper_write_size(asn1_bit_writer *w, size_t length, const asn1_range *size) {
+    bool unconstrained;
+    ASN1_CHECK(write_extension(w, (int64_t)length, size, &unconstrained));
+    if (unconstrained || !constrained_size(size))
+        return asn1_uper_write_length(w, length);
+    return asn1_uper_write_bits(w, length - (size_t)size->lower, range_bits(size));
+}
+
+int asn1_uper_read_size(asn1_bit_reader *r, size_t *length, const asn1_range *size) {
+    bool unconstrained;
+    ASN1_CHECK(read_extension(r, size, &unconstrained));
+    if (unconstrained || !constrained_size(size)) {
+        ASN1_CHECK(asn1_uper_read_length(r, length));
+        if (!unconstrained && !in_range((int64_t)*length, size))
+            return ASN1_ERR_DECODE;
+        return ASN1_OK;
+    }
+
+    uint64_t offset;
+    ASN1_CHECK(asn1_uper_read_bits(r, range_bits(size), &offset));
+    if (offset > (uint64_t)(size->upper - size->lower))
+        return ASN1_ERR_DECODE;
+    *length = (size_t)(size->lower + (int64_t)offset);
+    return ASN1_OK;
+}
+
+/* Write octets, each in eight bits */
+static int write_octets(asn1_bit_writer *w, const uint8_t *data, size_t length) {
+    for (size_t i = 0; i < length; i++)
+        ASN1_CHECK(asn1_uper_write_bits(w, data[i], 8));
+    return ASN1_OK;
+}
+
+static int read_octets(asn1_bit_reader *r, uint8_t *data, size_t length) {
+    for (size_t i = 0; i < length; i++) {
+        uint64_t octet;
+        ASN1_CHECK(asn1_uper_read_bits(r, 8, &octet));
+        data[i] = (uint8_t)octet;
+    }
+    return ASN1_OK;
+}
+
+int asn1_uper_write_small(asn1_bit_writer *w, size_t value) {
+    if (value < 64)
+        return asn1_uper_write_bits(w, value, 7);
+
+    uint8_t octets[8];
+    size_t length = 0;
+    for (int shift = 56; shift >= 0; shift -= 8)
+        if ((uint64_t)value >> shift || length || shift == 0)
+            octets[length++] = (uint8_t)((uint64_t)value >> shift);
+    ASN1_CHECK(asn1_uper_write_bits(w, 1, 1));
+    ASN1_CHECK(asn1_uper_write_length(w, length));
+    return write_octets(w, octets, length);
+}
+
+int asn1_uper_read_small(asn1_bit_reader *r, size_t *value) {
+    uint64_t bits;
+    ASN1_CHECK(asn1_uper_read_bits(r, 1, &bits));
+    if (!bits) {
+        ASN1_CHECK(asn1_uper_read_bits(r, 6, &bits));
+        *value = (size_t)bits;
+        return ASN1_OK;
+    }
+
+    size_t length;
+    ASN1_CHECK(asn1_uper_read_length(r, &length));
+    if (length == 0)
+        return ASN1_ERR_DECODE;
+    if (length > sizeof(size_t))
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(asn1_uper_read_bits(r, (unsigned)length * 8, &bits));
+    *value = (size_t)bits;
+    return ASN1_OK;
+}
+
+int asn1_uper_read_open_header(asn1_bit_reader *r, asn1_bit_reader *contents) {
+    size_t length;
+    ASN1_CHECK(asn1_uper_read_length(r, &length));
+    if (length > (r->bits - r->bit) / 8)
+        return ASN1_ERR_BUFFER;
+
+    contents->data = r->data;
+    contents->bit = r->bit;
+    contents->bits = r->bit + length * 8;
+    r->bit = contents->bits;
+    return ASN1_OK;
+}
+
+int asn1_uper_write_boolean(asn1_bit_writer *w, bool value) {
+    return asn1_uper_write_bits(w, value ? 1 : 0, 1);
+}
+
+int asn1_uper_read_boolean(asn1_bit_reader *r, bool *value) {
+    uint64_t bits;
+    ASN1_CHECK(asn1_uper_read_bits(r, 1, &bits));
+    *value = bits != 0;
+    return ASN1_OK;
+}
+
+/* Integers with both bounds are encoded as their offset from the lower
+ * bound in the fewest bits that can hold every offset, and integers with
+ * only a lower bound as their offset in the fewest octets.  Other integers
+ * are encoded in two's complement. */
+int asn1_uper_write_integer(asn1_bit_writer *w, int64_t value, const asn1_range *range) {
+    bool unconstrained;
+    ASN1_CHECK(write_extension(w, value, range, &unconstrained));
+    if (!unconstrained && (range->flags & ASN1_LOWER)) {
+        uint64_t offset = (uint64_t)value - (uint64_t)range->lower;
+        if (range->flags & ASN1_UPPER)
+            return asn1_uper_write_bits(w, offset, range_bits(range));
+
+        uint8_t octets[8];
+        size_t length = 0;
+        for (int shift = 56; shift >= 0; shift -= 8)
+            if (offset >> shift || length || shift == 0)
+                octets[length++] = (uint8_t)(offset >> shift);
+        ASN1_CHECK(asn1_uper_write_length(w, length));
+        return write_octets(w, octets, length);
+    }
+
+    uint8_t octets[8];
+    size_t length = integer_contents(value, octets);
+    ASN1_CHECK(asn1_uper_write_length(w, length));
+    return write_octets(w, octets, length);
+}
+
+int asn1_uper_read_integer(asn1_bit_reader *r, int64_t *value, const asn1_range *range) {
+    bool unconstrained;
+    uint64_t offset = 0;
+    size_t length;
+    uint8_t octets[8];
+    ASN1_CHECK(read_extension(r, range, &unconstrained));
+    if (!unconstrained && (range->flags & ASN1_LOWER)) {
+        uint64_t largest = (range->flags & ASN1_UPPER)
+                               ? (uint64_t)range->upper - (uint64_t)range->lower
+                               : (uint64_t)INT64_MAX - (uint64_t)range->lower;
+        if (range->flags & ASN1_UPPER) {
+            ASN1_CHECK(asn1_uper_read_bits(r, range_bits(range), &offset));
+        } else {
+            ASN1_CHECK(asn1_uper_read_length(r, &length));
+            if (length == 0)
+                return ASN1_ERR_DECODE;
+            if (length > sizeof(octets))
+                return ASN1_ERR_LIMIT;
+            ASN1_CHECK(read_octets(r, octets, length));
+            for (size_t i = 0; i < length; i++)
+                offset = (offset << 8) | octets[i];
+        }
+        if (offset > largest)
+            return (range->flags & ASN1_UPPER) ? ASN1_ERR_DECODE : ASN1_ERR_LIMIT;
+        *value = (int64_t)((uint64_t)range->lower + offset);
+        return ASN1_OK;
+    }
+
+    ASN1_CHECK(asn1_uper_read_length(r, &length));
+    if (length > sizeof(octets))
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(read_octets(r, octets, length));
+    ASN1_CHECK(integer_parse(octets, length, value));
+    if (!unconstrained && !in_range(*value, range))
+        return ASN1_ERR_DECODE;
+    return ASN1_OK;
+}
+
+/* Unsigned integers are encoded as integers with both bounds, and values
+ * outside of an extensible range as unconstrained integers */
+int asn1_uper_write_unsigned(asn1_bit_writer *w, uint64_t value, const asn1_urange *range) {
+    bool root = value >= range->lower && value <= range->upper;
+    if (range->flags & ASN1_EXTENSIBLE)
+        ASN1_CHECK(asn1_uper_write_bits(w, !root, 1));
+    else if (!root)
+        return ASN1_ERR_VALUE;
+    if (root)
+        return asn1_uper_write_bits(w, value - range->lower,
+                                    span_bits(range->upper - range->lower));
+
+    uint8_t octets[9];
+    size_t length = unsigned_contents(value, octets);
+    ASN1_CHECK(asn1_uper_write_length(w, length));
+    return write_octets(w, octets, length);
+}
+
+int asn1_uper_read_unsigned(asn1_bit_reader *r, uint64_t *value, const asn1_urange *range) {
+    uint64_t extended = 0;
+    if (range->flags & ASN1_EXTENSIBLE)
+        ASN1_CHECK(asn1_uper_read_bits(r, 1, &extended));
+    if (!extended) {
+        uint64_t offset;
+        ASN1_CHECK(asn1_uper_read_bits(r, span_bits(range->upper - range->lower), &offset));
+        if (offset > range->upper - range->lower)
+            return ASN1_ERR_DECODE;
+        *value = range->lower + offset;
+        return ASN1_OK;
+    }
+
+    size_t length;
+    uint8_t octets[9];
+    ASN1_CHECK(asn1_uper_read_length(r, &length));
+    if (length > sizeof(octets))
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(read_octets(r, octets, length));
+    return unsigned_parse(octets, length, value);
+}
+
+int asn1_uper_write_real(asn1_bit_writer *w, double value) {
+    uint8_t octets[16];
+    size_t length = real_contents(value, octets);
+    ASN1_CHECK(asn1_uper_write_length(w, length));
+    return write_octets(w, octets, length);
+}
+
+int asn1_uper_read_real(asn1_bit_reader *r, double *value) {
+    size_t length;
+    uint8_t octets[REAL_OCTETS];
+    ASN1_CHECK(asn1_uper_read_length(r, &length));
+    if (length > sizeof(octets))
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(read_octets(r, octets, length));
+    return real_parse(octets, length, value);
+}
+
+int asn1_uper_write_bit_string(asn1_bit_writer *w, const asn1_bits *value,
+                               const asn1_range *size) {
+    if (value->bits > ASN1_MAX_OCTETS * 8)
+        return ASN1_ERR_VALUE;
+    ASN1_CHECK(asn1_uper_write_size(w, value->bits, size));
+    for (size_t i = 0; i < value->bits; i++)
+        ASN1_CHECK(asn1_uper_write_bits(w, (value->data[i / 8] >> (7 - i % 8)) & 1, 1));
+    return ASN1_OK;
+}
+
+int asn1_uper_read_bit_string(asn1_bit_reader *r, asn1_bits *value, const asn1_range *size) {
+    size_t bits;
+    ASN1_CHECK(asn1_uper_read_size(r, &bits, size));
+    if (bits > ASN1_MAX_OCTETS * 8)
+        return ASN1_ERR_LIMIT;
+
+    memset(value->data, 0, (bits + 7) / 8);
+    for (size_t i = 0; i < bits; i++) {
+        uint64_t bit;
+        ASN1_CHECK(asn1_uper_read_bits(r, 1, &bit));
+        value->data[i / 8] |= (uint8_t)(bit << (7 - i % 8));
+    }
+    value->bits = bits;
+    return ASN1_OK;
+}
+
+int asn1_uper_write_octets(asn1_bit_writer *w, const asn1_octets *value, const asn1_range *size) {
+    if (value->length > ASN1_MAX_OCTETS)
+        return ASN1_ERR_VALUE;
+    ASN1_CHECK(asn1_uper_write_size(w, value->length, size));
+    return write_octets(w, value->data, value->length);
+}
+
+int asn1_uper_read_octets(asn1_bit_reader *r, asn1_octets *value, const asn1_range *size) {
+    size_t length;
+    ASN1_CHECK(asn1_uper_read_size(r, &length, size));
+    if (length > ASN1_MAX_OCTETS)
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(read_octets(r, value->data, length));
+    value->length = length;
+    return ASN1_OK;
+}
+
+int asn1_uper_write_chars(asn1_bit_writer *w, const asn1_octets *value, unsigned octets,
+                          unsigned bits, const char *alphabet, const asn1_range *size) {
+    if (value->length > ASN1_MAX_OCTETS || value->length % octets)
+        return ASN1_ERR_VALUE;
+
+    size_t count = value->length / octets;
+    ASN1_CHECK(asn1_uper_write_size(w, count, size));
+    for (size_t i = 0; i < count; i++) {
+        uint32_t c = 0;
+        for (unsigned j = 0; j < octets; j++)
+            c = (c << 8) | value->data[i * octets + j];
+
+        if (alphabet) {
+            const char *found = c ? strchr(alphabet, (int)c) : NULL;
+            if (!found)
+                return ASN1_ERR_VALUE;
+            c = (uint32_t)(found - alphabet);
+        } else if (bits < 32 && c >> bits) {
+            return ASN1_ERR_VALUE;
+        }
+        ASN1_CHECK(asn1_uper_write_bits(w, c, bits));
+    }
+    return ASN1_OK;
+}
+
+int asn1_uper_read_chars(asn1_bit_reader *r, asn1_octets *value, unsigned octets, unsigned bits,
+                         const char *alphabet, const asn1_range *size) {
+    size_t count;
+    ASN1_CHECK(asn1_uper_read_size(r, &count, size));
+    if (count > ASN1_MAX_OCTETS / octets)
+        return ASN1_ERR_LIMIT;
+
+    for (size_t i = 0; i < count; i++) {
+        uint64_t c;
+        ASN1_CHECK(asn1_uper_read_bits(r, bits, &c));
+        if (alphabet) {
+            if (c >= strlen(alphabet))
+                return ASN1_ERR_DECODE;
+            c = (uint8_t)alphabet[c];
+        }
+        for (unsigned j = 0; j < octets; j++)
+            value->data[i * octets + j] = (uint8_t)(c >> (8 * (octets - 1 - j)));
+    }
+    value->length = count * octets;
+    return ASN1_OK;
+}
+
+int asn1_uper_write_oid(asn1_bit_writer *w, const asn1_oid *value, bool relative) {
+    uint8_t octets[OID_OCTETS];
+    size_t length;
+    ASN1_CHECK(oid_contents(value, relative, octets, &length));
+    ASN1_CHECK(asn1_uper_write_length(w, length));
+    return write_octets(w, octets, length);
+}
+
+int asn1_uper_read_oid(asn1_bit_reader *r, asn1_oid *value, bool relative) {
+    size_t length;
+    uint8_t octets[OID_OCTETS];
+    ASN1_CHECK(asn1_uper_read_length(r, &length));
+    if (length > sizeof(octets))
+        return ASN1_ERR_LIMIT;
+    ASN1_CHECK(read_octets(r, octets, length));
+    return oid_parse(octets, length, value, relative);
+}
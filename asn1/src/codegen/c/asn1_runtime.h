@@ -0,0 +1,201 @@
+/*
+ * Runtime support for C code generated from ASN.1 modules.
+ *
+ * Values are encoded to and decoded from buffers provided by the caller, and
+ * no memory is allocated.  Strings, bit strings, object identifiers and
+ * lists have a fixed capacity, which can be changed by defining the
+ * ASN1_MAX_* macros before including this file.  Every function returns
+ * ASN1_OK or one of the negative ASN1_ERR_* codes.
+ */
+#ifndef ASN1_RUNTIME_H
+#define ASN1_RUNTIME_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/* Capacity in octets of strings, bit strings and encodings of open types */
+#ifndef ASN1_MAX_OCTETS
+#define ASN1_MAX_OCTETS 256
+#endif
+
+/* Capacity of the elements of each SEQUENCE OF and SET OF value */
+#ifndef ASN1_MAX_ITEMS
+#define ASN1_MAX_ITEMS 16
+#endif
+
+/* Capacity of the arcs of each object identifier value */
+#ifndef ASN1_MAX_ARCS
+#define ASN1_MAX_ARCS 16
+#endif
+
+enum {
+    ASN1_OK = 0,
+    /* The output buffer is too small, or the input ends too early */
+    ASN1_ERR_BUFFER = -1,
+    /* The input is not a valid encoding of the type */
+    ASN1_ERR_DECODE = -2,
+    /* A value is larger than the capacity of the generated types */
+    ASN1_ERR_LIMIT = -3,
+    /* No storage is provided for a recursive value being decoded */
+    ASN1_ERR_STORAGE = -4,
+    /* A value cannot be encoded, such as a choice with nothing chosen */
+    ASN1_ERR_VALUE = -5
+};
+
+#define ASN1_CHECK(expr)                                                       \
+    do {                                                                       \
+        int asn1_result_ = (expr);                                             \
+        if (asn1_result_ != ASN1_OK)                                           \
+            return asn1_result_;                                               \
+    } while (0)
+
+typedef struct {
+    size_t length;
+    uint8_t data[ASN1_MAX_OCTETS];
+} asn1_octets;
+
+/* Bits from the most significant bit of the first octet */
+typedef struct {
+    size_t bits;
+    uint8_t data[ASN1_MAX_OCTETS];
+} asn1_bits;
+
+typedef struct {
+    size_t count;
+    uint32_t arcs[ASN1_MAX_ARCS];
+} asn1_oid;
+
+/* NULL has a single value, so its members are unused */
+typedef uint8_t asn1_null;
+
+/* The complete encoding of a value of an open type, in the same encoding
+ * rules as the value containing it */
+typedef asn1_octets asn1_open;
+
+/* The bounds of the extension root of a PER-visible value or size
+ * constraint, which are only used if flagged.  Values outside of an
+ * extensible range are encoded as if unconstrained. */
+#define ASN1_LOWER 1u
+#define ASN1_UPPER 2u
+#define ASN1_EXTENSIBLE 4u
+
+typedef struct {
+    int64_t lower;
+    int64_t upper;
+    unsigned flags;
+} asn1_range;
+
+/* The bounds of a range of non-negative values beyond those of int64_t,
+ * which always has both bounds */
+typedef struct {
+    uint64_t lower;
+    uint64_t upper;
+    unsigned flags;
+} asn1_urange;
+
+/* Tag classes, ordered as in the canonical order of tags */
+#define ASN1_UNIVERSAL 0u
+#define ASN1_APPLICATION 1u
+#define ASN1_CONTEXT 2u
+#define ASN1_PRIVATE 3u
+#define ASN1_TAG(class, number) (((uint32_t)(class) << 30) | (uint32_t)(number))
+
+/* Basic Encoding Rules, definite length form.  Encodings are written
+ * backwards from the end of the buffer, so that the length of each value is
+ * known before its header is written. */
+typedef struct {
+    uint8_t *data;
+    size_t size;
+    size_t length;
+} asn1_ber_writer;
+
+typedef struct {
+    const uint8_t *data;
+    size_t size;
+    size_t position;
+} asn1_ber_reader;
+
+int asn1_ber_prepend(asn1_ber_writer *w, const uint8_t *data, size_t length);
+int asn1_ber_write_header(asn1_ber_writer *w, uint32_t tag, bool constructed, size_t length);
+int asn1_ber_write_boolean(asn1_ber_writer *w, bool value);
+int asn1_ber_write_integer(asn1_ber_writer *w, int64_t value);
+int asn1_ber_write_unsigned(asn1_ber_writer *w, uint64_t value);
+int asn1_ber_write_real(asn1_ber_writer *w, double value);
+int asn1_ber_write_bits(asn1_ber_writer *w, const asn1_bits *value);
+int asn1_ber_write_octets(asn1_ber_writer *w, const asn1_octets *value);
+int asn1_ber_write_oid(asn1_ber_writer *w, const asn1_oid *value, bool relative);
+/* Move a complete encoding to the start of the buffer */
+void asn1_ber_finish(asn1_ber_writer *w);
+
+bool asn1_ber_at_end(const asn1_ber_reader *r);
+int asn1_ber_peek_tag(const asn1_ber_reader *r, uint32_t *tag);
+/* Read the header of a value with the given tag, and give a reader of its
+ * contents */
+int asn1_ber_read_header(asn1_ber_reader *r, uint32_t tag, asn1_ber_reader *contents);
+int asn1_ber_skip(asn1_ber_reader *r);
+/* Check that every octet of the contents of a value has been read */
+int asn1_ber_read_end(const asn1_ber_reader *contents);
+int asn1_ber_read_boolean(asn1_ber_reader *contents, bool *value);
+int asn1_ber_read_integer(asn1_ber_reader *contents, int64_t *value);
+int asn1_ber_read_unsigned(asn1_ber_reader *contents, uint64_t *value);
+int asn1_ber_read_real(asn1_ber_reader *contents, double *value);
+int asn1_ber_read_bits(asn1_ber_reader *contents, asn1_bits *value);
+int asn1_ber_read_octets(asn1_ber_reader *contents, asn1_octets *value);
+int asn1_ber_read_oid(asn1_ber_reader *contents, asn1_oid *value, bool relative);
+/* Read the complete encoding of a value of an open type */
+int asn1_ber_read_open(asn1_ber_reader *r, asn1_open *value);
+
+/* Unaligned Packed Encoding Rules.  A writer without data only counts the
+ * bits that would be written, to find the length of an open type.  Ranges
+ * of constraints are given by pointers, which are NULL if unconstrained. */
+typedef struct {
+    uint8_t *data;
+    size_t size;
+    size_t bit;
+} asn1_bit_writer;
+
+typedef struct {
+    const uint8_t *data;
+    size_t bits;
+    size_t bit;
+} asn1_bit_reader;
+
+int asn1_uper_write_bits(asn1_bit_writer *w, uint64_t value, unsigned count);
+int asn1_uper_read_bits(asn1_bit_reader *r, unsigned count, uint64_t *value);
+/* Write zero bits up to a position */
+int asn1_uper_pad(asn1_bit_writer *w, size_t bit);
+int asn1_uper_write_length(asn1_bit_writer *w, size_t length);
+int asn1_uper_read_length(asn1_bit_reader *r, size_t *length);
+/* The length of a value with a size constraint, which is not encoded if the
+ * size is fixed */
+int asn1_uper_write_size(asn1_bit_writer *w, size_t length, const asn1_range *size);
+int asn1_uper_read_size(asn1_bit_reader *r, size_t *length, const asn1_range *size);
+int asn1_uper_write_small(asn1_bit_writer *w, size_t value);
+int asn1_uper_read_small(asn1_bit_reader *r, size_t *value);
+/* Read the length of an open type, and give a reader of its encoding */
+int asn1_uper_read_open_header(asn1_bit_reader *r, asn1_bit_reader *contents);
+int asn1_uper_write_boolean(asn1_bit_writer *w, bool value);
+int asn1_uper_read_boolean(asn1_bit_reader *r, bool *value);
+int asn1_uper_write_integer(asn1_bit_writer *w, int64_t value, const asn1_range *range);
+int asn1_uper_read_integer(asn1_bit_reader *r, int64_t *value, const asn1_range *range);
+int asn1_uper_write_unsigned(asn1_bit_writer *w, uint64_t value, const asn1_urange *range);
+int asn1_uper_read_unsigned(asn1_bit_reader *r, uint64_t *value, const asn1_urange *range);
+int asn1_uper_write_real(asn1_bit_writer *w, double value);
+int asn1_uper_read_real(asn1_bit_reader *r, double *value);
+int asn1_uper_write_bit_string(asn1_bit_writer *w, const asn1_bits *value,
+                               const asn1_range *size);
+int asn1_uper_read_bit_string(asn1_bit_reader *r, asn1_bits *value, const asn1_range *size);
+int asn1_uper_write_octets(asn1_bit_writer *w, const asn1_octets *value, const asn1_range *size);
+int asn1_uper_read_octets(asn1_bit_reader *r, asn1_octets *value, const asn1_range *size);
+/* Known-multiplier character strings, with each character stored in the
+ * given number of octets and encoded in the given number of bits.  Characters
+ * of an alphabet are encoded as their index in it. */
+int asn1_uper_write_chars(asn1_bit_writer *w, const asn1_octets *value, unsigned octets,
+                          unsigned bits, const char *alphabet, const asn1_range *size);
+int asn1_uper_read_chars(asn1_bit_reader *r, asn1_octets *value, unsigned octets, unsigned bits,
+                         const char *alphabet, const asn1_range *size);
+int asn1_uper_write_oid(asn1_bit_writer *w, const asn1_oid *value, bool relative);
+int asn1_uper_read_oid(asn1_bit_reader *r, asn1_oid *value, bool relative);
+
+#endif
@@ -0,0 +1,407 @@
+//! Functions encoding and decoding generated types in BER.  Values are
+//! written backwards, so the contents of each value are written before its
+//! tags, and read with a reader of the contents of each tag.
+
+use super::{argument, tag, Code, Definition, Field, Generator, Kind, Primitive, Slot, SlotType};
+
+impl Generator<'_> {
+    /// Write the BER functions of a definition
+    pub(super) fn ber_functions(&self, code: &mut Code, def: &Definition) {
+        let name = &def.name;
+        match &def.kind {
+            Kind::Sequence(sequence) => {
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_write_contents(asn1_ber_writer *w, const {name} *value) {{"
+                ));
+                for field in sequence.fields.iter().rev() {
+                    self.optional(code, name, field, |code| {
+                        self.ber_write(code, &field.slot, &format!("value->{}", field.name));
+                    });
+                }
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_read_contents(asn1_ber_reader *r, {name} *value) {{"
+                ));
+                if sequence.set {
+                    self.ber_read_set(code, name, &sequence.fields, sequence.extensible);
+                } else {
+                    self.ber_read_sequence(code, name, &sequence.fields, sequence.extensible);
+                }
+                code.line("return ASN1_OK;");
+                code.close("}");
+            }
+
+            Kind::Choice(choice) => {
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_write_contents(asn1_ber_writer *w, const {name} *value) {{"
+                ));
+                code.line("switch (value->present) {");
+                for alternative in &choice.alternatives {
+                    code.open(&format!("case {name}_PR_{}:", alternative.name));
+                    let place = format!("value->choice.{}", alternative.name);
+                    self.ber_write(code, &alternative.slot, &place);
+                    code.line("break;");
+                    code.dedent();
+                }
+                code.open("default:");
+                code.line("return ASN1_ERR_VALUE;");
+                code.dedent();
+                code.line("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_read_contents(asn1_ber_reader *r, {name} *value) {{"
+                ));
+                code.line("uint32_t tag;");
+                code.line("ASN1_CHECK(asn1_ber_peek_tag(r, &tag));");
+                for (i, alternative) in choice.alternatives.iter().enumerate() {
+                    let condition = format!("if ({}) {{", self.ber_matches(&alternative.slot));
+                    if i == 0 {
+                        code.open(&condition);
+                    } else {
+                        code.next(&format!("}} else {condition}"));
+                    }
+                    code.line(&format!("value->present = {name}_PR_{};", alternative.name));
+                    let place = format!("value->choice.{}", alternative.name);
+                    self.ber_read(code, &alternative.slot, &place);
+                }
+                code.next("} else {");
+                code.line("return ASN1_ERR_DECODE;");
+                code.close("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!("bool {name}_ber_has_tag(uint32_t tag) {{"));
+                let conditions: Vec<_> = choice
+                    .alternatives
+                    .iter()
+                    .map(|alternative| self.ber_matches(&alternative.slot))
+                    .collect();
+                code.line(&format!("return {};", conditions.join(" || ")));
+                code.close("}");
+            }
+
+            Kind::Enumerated(enumerated) => {
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_write_contents(asn1_ber_writer *w, const {name} *value) {{"
+                ));
+                code.line("return asn1_ber_write_integer(w, (int64_t)*value);");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_read_contents(asn1_ber_reader *r, {name} *value) {{"
+                ));
+                code.line("int64_t number;");
+                code.line("ASN1_CHECK(asn1_ber_read_integer(r, &number));");
+                code.line("switch (number) {");
+                for (constant, _, _) in &enumerated.items {
+                    code.open(&format!("case {constant}:"));
+                    code.line(&format!("*value = {constant};"));
+                    code.line("return ASN1_OK;");
+                    code.dedent();
+                }
+                code.open("default:");
+                code.line("return ASN1_ERR_DECODE;");
+                code.dedent();
+                code.line("}");
+                code.close("}");
+            }
+
+            Kind::List(slot) => {
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_write_contents(asn1_ber_writer *w, const {name} *value) {{"
+                ));
+                code.line("size_t i;");
+                code.line("if (value->count > ASN1_MAX_ITEMS)");
+                code.line("    return ASN1_ERR_VALUE;");
+                code.open("for (i = value->count; i-- > 0;) {");
+                self.ber_write(code, slot, "value->items[i]");
+                code.close("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_ber_read_contents(asn1_ber_reader *r, {name} *value) {{"
+                ));
+                code.line("value->count = 0;");
+                code.open("while (!asn1_ber_at_end(r)) {");
+                code.line("if (value->count == ASN1_MAX_ITEMS)");
+                code.line("    return ASN1_ERR_LIMIT;");
+                self.ber_read(code, slot, "value->items[value->count]");
+                code.line("value->count++;");
+                code.close("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+            }
+
+            Kind::Alias(_) => (),
+        }
+
+        let Some(slot) = &def.assignment else {
+            return;
+        };
+        code.line("");
+        code.open(&format!(
+            "int {name}_encode_ber(const {name} *value, uint8_t *buffer, size_t size, size_t *length) {{"
+        ));
+        code.line("asn1_ber_writer writer;");
+        code.line("asn1_ber_writer *w = &writer;");
+        code.line("writer.data = buffer;");
+        code.line("writer.size = size;");
+        code.line("writer.length = 0;");
+        self.ber_write(code, slot, "(*value)");
+        code.line("asn1_ber_finish(w);");
+        code.line("*length = w->length;");
+        code.line("return ASN1_OK;");
+        code.close("}");
+
+        code.line("");
+        code.open(&format!(
+            "int {name}_decode_ber({name} *value, const uint8_t *buffer, size_t size, size_t *length) {{"
+        ));
+        code.line("asn1_ber_reader reader;");
+        code.line("asn1_ber_reader *r = &reader;");
+        code.line("reader.data = buffer;");
+        code.line("reader.size = size;");
+        code.line("reader.position = 0;");
+        self.ber_read(code, slot, "(*value)");
+        code.line("*length = r->position;");
+        code.line("return ASN1_OK;");
+        code.close("}");
+    }
+
+    /// Read the components of a sequence in order, skipping optional
+    /// components with other tags
+    fn ber_read_sequence(&self, code: &mut Code, name: &str, fields: &[Field], extensible: bool) {
+        if fields.iter().any(|field| field.presence.is_some()) {
+            code.line("uint32_t tag;");
+            code.line("value->present = 0;");
+        }
+        for field in fields {
+            let place = format!("value->{}", field.name);
+            if field.presence.is_none() {
+                self.ber_read(code, &field.slot, &place);
+                continue;
+            }
+
+            code.open(&format!(
+                "if (!asn1_ber_at_end(r) && asn1_ber_peek_tag(r, &tag) == ASN1_OK && ({})) {{",
+                self.ber_matches(&field.slot)
+            ));
+            code.line(&format!("value->present |= {name}_{}_PRESENT;", field.name));
+            self.ber_read(code, &field.slot, &place);
+            code.close("}");
+        }
+        if extensible {
+            code.line("while (!asn1_ber_at_end(r))");
+            code.line("    ASN1_CHECK(asn1_ber_skip(r));");
+        }
+    }
+
+    /// Read the components of a set in any order
+    fn ber_read_set(&self, code: &mut Code, name: &str, fields: &[Field], extensible: bool) {
+        code.line("uint32_t tag;");
+        if !fields.is_empty() {
+            code.line(&format!("bool seen[{}] = {{false}};", fields.len()));
+        }
+        if fields.iter().any(|field| field.presence.is_some()) {
+            code.line("value->present = 0;");
+        }
+        code.open("while (!asn1_ber_at_end(r)) {");
+        code.line("ASN1_CHECK(asn1_ber_peek_tag(r, &tag));");
+        for (i, field) in fields.iter().enumerate() {
+            let condition = format!("if (!seen[{i}] && ({})) {{", self.ber_matches(&field.slot));
+            if i == 0 {
+                code.open(&condition);
+            } else {
+                code.next(&format!("}} else {condition}"));
+            }
+            code.line(&format!("seen[{i}] = true;"));
+            if field.presence.is_some() {
+                code.line(&format!("value->present |= {name}_{}_PRESENT;", field.name));
+            }
+            self.ber_read(code, &field.slot, &format!("value->{}", field.name));
+        }
+        let other = if extensible {
+            "ASN1_CHECK(asn1_ber_skip(r));"
+        } else {
+            "return ASN1_ERR_DECODE;"
+        };
+        if fields.is_empty() {
+            code.line(other);
+        } else {
+            code.next("} else {");
+            code.line(other);
+            code.close("}");
+        }
+        code.close("}");
+
+        for (i, field) in fields.iter().enumerate() {
+            if field.presence.is_none() {
+                code.line(&format!("if (!seen[{i}])"));
+                code.line("    return ASN1_ERR_DECODE;");
+            }
+        }
+    }
+
+    /// Write the code encoding the value stored in a place with its tags
+    fn ber_write(&self, code: &mut Code, slot: &Slot, place: &str) {
+        let tagged = !slot.tags.is_empty();
+        if tagged {
+            code.open("{");
+            code.line("size_t mark = w->length;");
+        }
+
+        let value = argument(slot, place);
+        match &slot.ty {
+            SlotType::Primitive(primitive) => {
+                let call = match primitive {
+                    Primitive::Boolean => format!("asn1_ber_write_boolean(w, {value})"),
+                    Primitive::Integer => format!("asn1_ber_write_integer(w, {value})"),
+                    Primitive::Unsigned => format!("asn1_ber_write_unsigned(w, {value})"),
+                    Primitive::Real => format!("asn1_ber_write_real(w, {value})"),
+                    Primitive::Null => String::new(),
+                    Primitive::BitString => format!("asn1_ber_write_bits(w, {value})"),
+                    Primitive::Octets | Primitive::Chars { .. } => {
+                        format!("asn1_ber_write_octets(w, {value})")
+                    }
+                    Primitive::Oid { relative } => {
+                        format!("asn1_ber_write_oid(w, {value}, {relative})")
+                    }
+                    Primitive::Open => {
+                        format!("asn1_ber_prepend(w, {place}.data, {place}.length)")
+                    }
+                };
+                if !call.is_empty() {
+                    code.line(&format!("ASN1_CHECK({call});"));
+                }
+            }
+            SlotType::Named { name, pointer } => {
+                if *pointer {
+                    code.line(&format!("if (!{place})"));
+                    code.line("    return ASN1_ERR_VALUE;");
+                }
+                code.line(&format!(
+                    "ASN1_CHECK({name}_ber_write_contents(w, {value}));"
+                ));
+            }
+        }
+
+        for &(t, constructed) in slot.tags.iter().rev() {
+            code.line(&format!(
+                "ASN1_CHECK(asn1_ber_write_header(w, {}, {constructed}, w->length - mark));",
+                tag(t)
+            ));
+        }
+        if tagged {
+            code.close("}");
+        }
+    }
+
+    /// Write the code decoding a value with its tags into a place
+    fn ber_read(&self, code: &mut Code, slot: &Slot, place: &str) {
+        let tagged = !slot.tags.is_empty();
+        let reader = if tagged {
+            code.open("{");
+            let readers: Vec<_> = (0..slot.tags.len()).map(|i| format!("c{i}")).collect();
+            code.line(&format!("asn1_ber_reader {};", readers.join(", ")));
+            for (i, &(t, _)) in slot.tags.iter().enumerate() {
+                let outer = match i {
+                    0 => "r".to_string(),
+                    i => format!("&c{}", i - 1),
+                };
+                code.line(&format!(
+                    "ASN1_CHECK(asn1_ber_read_header({outer}, {}, &c{i}));",
+                    tag(t)
+                ));
+            }
+            format!("&c{}", slot.tags.len() - 1)
+        } else {
+            "r".to_string()
+        };
+
+        match &slot.ty {
+            SlotType::Primitive(primitive) => {
+                let call = match primitive {
+                    Primitive::Boolean => format!("asn1_ber_read_boolean({reader}, &{place})"),
+                    Primitive::Integer => format!("asn1_ber_read_integer({reader}, &{place})"),
+                    Primitive::Unsigned => {
+                        format!("asn1_ber_read_unsigned({reader}, &{place})")
+                    }
+                    Primitive::Real => format!("asn1_ber_read_real({reader}, &{place})"),
+                    Primitive::Null => String::new(),
+                    Primitive::BitString => format!("asn1_ber_read_bits({reader}, &{place})"),
+                    Primitive::Octets | Primitive::Chars { .. } => {
+                        format!("asn1_ber_read_octets({reader}, &{place})")
+                    }
+                    Primitive::Oid { relative } => {
+                        format!("asn1_ber_read_oid({reader}, &{place}, {relative})")
+                    }
+                    Primitive::Open => format!("asn1_ber_read_open({reader}, &{place})"),
+                };
+                if !call.is_empty() {
+                    code.line(&format!("ASN1_CHECK({call});"));
+                }
+            }
+            SlotType::Named { name, pointer } => {
+                if *pointer {
+                    code.line(&format!("if (!{place})"));
+                    code.line("    return ASN1_ERR_STORAGE;");
+                }
+                code.line(&format!(
+                    "ASN1_CHECK({name}_ber_read_contents({reader}, {}));",
+                    argument(slot, place)
+                ));
+            }
+        }
+
+        if tagged {
+            for i in (0..slot.tags.len()).rev() {
+                code.line(&format!("ASN1_CHECK(asn1_ber_read_end(&c{i}));"));
+            }
+            code.close("}");
+        }
+    }
+
+    /// Get the condition of the `tag` variable being the first tag of a
+    /// slot
+    fn ber_matches(&self, slot: &Slot) -> String {
+        match (slot.tags.first(), &slot.ty) {
+            (Some(&(t, _)), _) => format!("tag == {}", tag(t)),
+            (None, SlotType::Named { name, .. }) => format!("{name}_ber_has_tag(tag)"),
+            (None, SlotType::Primitive(_)) => "true".to_string(),
+        }
+    }
+
+    /// Write code that is only run if an optional field is present
+    pub(super) fn optional(
+        &self,
+        code: &mut Code,
+        name: &str,
+        field: &Field,
+        body: impl FnOnce(&mut Code),
+    ) {
+        if field.presence.is_none() {
+            body(code);
+            return;
+        }
+        code.open(&format!(
+            "if (value->present & {name}_{}_PRESENT) {{",
+            field.name
+        ));
+        body(code);
+        code.close("}");
+    }
+}
@@ -0,0 +1,637 @@
+//! Functions encoding and decoding generated types in unaligned PER, with
+//! the value ranges, sizes and permitted alphabets of their PER-visible
+//! constraints.  Extension additions are written as open types, whose length
+//! is found by first writing them with a writer that only counts bits.
+
+use super::{
+    argument, model::Range, Code, Definition, Field, Generator, Kind, Primitive, Slot, SlotType,
+};
+
+impl Generator<'_> {
+    /// Write the UPER functions of a definition
+    pub(super) fn uper_functions(&self, code: &mut Code, def: &Definition) {
+        let name = &def.name;
+        match &def.kind {
+            Kind::Sequence(sequence) => {
+                let root = match sequence.set {
+                    true => self.tag_order(&sequence.root, &sequence.fields),
+                    false => sequence.root.clone(),
+                };
+                let optional: Vec<_> = root
+                    .iter()
+                    .filter(|&&i| sequence.fields[i].optional)
+                    .collect();
+                let additions: Vec<_> = sequence
+                    .additions
+                    .iter()
+                    .map(|addition| {
+                        let bits: Vec<_> = addition
+                            .fields
+                            .iter()
+                            .map(|&i| presence(name, &sequence.fields[i]))
+                            .collect();
+                        format!("({})", bits.join(" | "))
+                    })
+                    .collect();
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_write(asn1_bit_writer *w, const {name} *value) {{"
+                ));
+                if sequence.extensible {
+                    if additions.is_empty() {
+                        code.line("ASN1_CHECK(asn1_uper_write_bits(w, 0, 1));");
+                    } else {
+                        code.line(&format!(
+                            "bool extended = (value->present & ({})) != 0;",
+                            additions.join(" | ")
+                        ));
+                        code.line("ASN1_CHECK(asn1_uper_write_bits(w, extended, 1));");
+                    }
+                }
+                for &&i in &optional {
+                    code.line(&format!(
+                        "ASN1_CHECK(asn1_uper_write_bits(w, (value->present & {}) != 0, 1));",
+                        presence(name, &sequence.fields[i])
+                    ));
+                }
+                for &i in &root {
+                    let field = &sequence.fields[i];
+                    self.optional(code, name, field, |code| {
+                        self.uper_write(code, &field.slot, &format!("value->{}", field.name), "w");
+                    });
+                }
+                if !additions.is_empty() {
+                    code.open("if (extended) {");
+                    code.line(&format!(
+                        "ASN1_CHECK(asn1_uper_write_small(w, {}));",
+                        additions.len() - 1
+                    ));
+                    for bits in &additions {
+                        code.line(&format!(
+                            "ASN1_CHECK(asn1_uper_write_bits(w, (value->present & {bits}) != 0, 1));"
+                        ));
+                    }
+                    for (addition, bits) in sequence.additions.iter().zip(&additions) {
+                        code.open(&format!("if (value->present & {bits}) {{"));
+                        open_type(code, |code, writer| {
+                            let fields: Vec<_> = addition
+                                .fields
+                                .iter()
+                                .map(|&i| &sequence.fields[i])
+                                .collect();
+                            if addition.group {
+                                for field in fields.iter().filter(|field| field.optional) {
+                                    code.line(&format!(
+                                        "ASN1_CHECK(asn1_uper_write_bits({writer}, (value->present & {}) != 0, 1));",
+                                        presence(name, field)
+                                    ));
+                                }
+                            }
+                            for field in fields {
+                                let place = format!("value->{}", field.name);
+                                if addition.group && field.optional {
+                                    self.optional(code, name, field, |code| {
+                                        self.uper_write(code, &field.slot, &place, writer);
+                                    });
+                                } else {
+                                    self.uper_write(code, &field.slot, &place, writer);
+                                }
+                            }
+                        });
+                        code.close("}");
+                    }
+                    code.close("}");
+                }
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_read(asn1_bit_reader *r, {name} *value) {{"
+                ));
+                if sequence.extensible {
+                    code.line("uint64_t extended;");
+                }
+                if !optional.is_empty() {
+                    code.line("uint64_t preamble;");
+                }
+                if sequence.presence > 0 {
+                    code.line("value->present = 0;");
+                }
+                if sequence.extensible {
+                    code.line("ASN1_CHECK(asn1_uper_read_bits(r, 1, &extended));");
+                }
+                if !optional.is_empty() {
+                    code.line(&format!(
+                        "ASN1_CHECK(asn1_uper_read_bits(r, {}, &preamble));",
+                        optional.len()
+                    ));
+                }
+                let mut bit = optional.len();
+                for &i in &root {
+                    let field = &sequence.fields[i];
+                    let place = format!("value->{}", field.name);
+                    if !field.optional {
+                        self.uper_read(code, &field.slot, &place, "r");
+                        continue;
+                    }
+                    bit -= 1;
+                    code.open(&format!("if ((preamble >> {bit}) & 1) {{"));
+                    code.line(&format!("value->present |= {};", presence(name, field)));
+                    self.uper_read(code, &field.slot, &place, "r");
+                    code.close("}");
+                }
+                if sequence.extensible {
+                    self.uper_read_additions(code, name, &sequence.fields, &sequence.additions);
+                }
+                code.line("return ASN1_OK;");
+                code.close("}");
+            }
+
+            Kind::Choice(choice) => {
+                let root = self.tag_order(&choice.root, &choice.alternatives);
+                let bits = index_bits(root.len());
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_write(asn1_bit_writer *w, const {name} *value) {{"
+                ));
+                code.line("switch (value->present) {");
+                for (index, &i) in root.iter().enumerate() {
+                    let alternative = &choice.alternatives[i];
+                    code.open(&format!("case {name}_PR_{}:", alternative.name));
+                    if choice.extensible {
+                        code.line("ASN1_CHECK(asn1_uper_write_bits(w, 0, 1));");
+                    }
+                    code.line(&format!(
+                        "ASN1_CHECK(asn1_uper_write_bits(w, {index}, {bits}));"
+                    ));
+                    let place = format!("value->choice.{}", alternative.name);
+                    self.uper_write(code, &alternative.slot, &place, "w");
+                    code.line("break;");
+                    code.dedent();
+                }
+                for (index, &i) in choice.additions.iter().enumerate() {
+                    let alternative = &choice.alternatives[i];
+                    code.open(&format!("case {name}_PR_{}:", alternative.name));
+                    code.line("ASN1_CHECK(asn1_uper_write_bits(w, 1, 1));");
+                    code.line(&format!("ASN1_CHECK(asn1_uper_write_small(w, {index}));"));
+                    let place = format!("value->choice.{}", alternative.name);
+                    open_type(code, |code, writer| {
+                        self.uper_write(code, &alternative.slot, &place, writer);
+                    });
+                    code.line("break;");
+                    code.dedent();
+                }
+                code.open("default:");
+                code.line("return ASN1_ERR_VALUE;");
+                code.dedent();
+                code.line("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_read(asn1_bit_reader *r, {name} *value) {{"
+                ));
+                code.line("uint64_t position;");
+                if choice.extensible {
+                    code.line("uint64_t extended;");
+                    code.line("ASN1_CHECK(asn1_uper_read_bits(r, 1, &extended));");
+                    code.open("if (!extended) {");
+                }
+                code.line(&format!(
+                    "ASN1_CHECK(asn1_uper_read_bits(r, {bits}, &position));"
+                ));
+                code.line("switch (position) {");
+                for (index, &i) in root.iter().enumerate() {
+                    let alternative = &choice.alternatives[i];
+                    code.open(&format!("case {index}:"));
+                    code.line(&format!("value->present = {name}_PR_{};", alternative.name));
+                    let place = format!("value->choice.{}", alternative.name);
+                    self.uper_read(code, &alternative.slot, &place, "r");
+                    code.line("return ASN1_OK;");
+                    code.dedent();
+                }
+                code.open("default:");
+                code.line("return ASN1_ERR_DECODE;");
+                code.dedent();
+                code.line("}");
+                if choice.extensible {
+                    code.close("}");
+                    code.open("{");
+                    code.line("size_t addition;");
+                    code.line("asn1_bit_reader contents;");
+                    code.line("ASN1_CHECK(asn1_uper_read_small(r, &addition));");
+                    code.line("ASN1_CHECK(asn1_uper_read_open_header(r, &contents));");
+                    code.line("switch (addition) {");
+                    for (index, &i) in choice.additions.iter().enumerate() {
+                        let alternative = &choice.alternatives[i];
+                        code.open(&format!("case {index}:"));
+                        code.line(&format!("value->present = {name}_PR_{};", alternative.name));
+                        let place = format!("value->choice.{}", alternative.name);
+                        self.uper_read(code, &alternative.slot, &place, "&contents");
+                        code.line("return ASN1_OK;");
+                        code.dedent();
+                    }
+                    code.open("default:");
+                    code.line("return ASN1_ERR_DECODE;");
+                    code.dedent();
+                    code.line("}");
+                    code.close("}");
+                }
+                code.close("}");
+            }
+
+            Kind::Enumerated(enumerated) => {
+                let bits = index_bits(enumerated.root.len());
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_write(asn1_bit_writer *w, const {name} *value) {{"
+                ));
+                code.line("switch (*value) {");
+                for (index, &i) in enumerated.root.iter().enumerate() {
+                    code.open(&format!("case {}:", enumerated.items[i].0));
+                    if enumerated.extensible {
+                        code.line("ASN1_CHECK(asn1_uper_write_bits(w, 0, 1));");
+                    }
+                    code.line(&format!("return asn1_uper_write_bits(w, {index}, {bits});"));
+                    code.dedent();
+                }
+                for (index, &i) in enumerated.additions.iter().enumerate() {
+                    code.open(&format!("case {}:", enumerated.items[i].0));
+                    code.line("ASN1_CHECK(asn1_uper_write_bits(w, 1, 1));");
+                    code.line(&format!("return asn1_uper_write_small(w, {index});"));
+                    code.dedent();
+                }
+                code.open("default:");
+                code.line("return ASN1_ERR_VALUE;");
+                code.dedent();
+                code.line("}");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_read(asn1_bit_reader *r, {name} *value) {{"
+                ));
+                code.line("uint64_t position;");
+                if enumerated.extensible {
+                    code.line("uint64_t extended;");
+                    code.line("size_t addition;");
+                    code.line("ASN1_CHECK(asn1_uper_read_bits(r, 1, &extended));");
+                    code.open("if (!extended) {");
+                }
+                code.line(&format!(
+                    "ASN1_CHECK(asn1_uper_read_bits(r, {bits}, &position));"
+                ));
+                code.line("switch (position) {");
+                for (index, &i) in enumerated.root.iter().enumerate() {
+                    code.open(&format!("case {index}:"));
+                    code.line(&format!("*value = {};", enumerated.items[i].0));
+                    code.line("return ASN1_OK;");
+                    code.dedent();
+                }
+                code.open("default:");
+                code.line("return ASN1_ERR_DECODE;");
+                code.dedent();
+                code.line("}");
+                if enumerated.extensible {
+                    code.close("}");
+                    code.line("ASN1_CHECK(asn1_uper_read_small(r, &addition));");
+                    code.line("switch (addition) {");
+                    for (index, &i) in enumerated.additions.iter().enumerate() {
+                        code.open(&format!("case {index}:"));
+                        code.line(&format!("*value = {};", enumerated.items[i].0));
+                        code.line("return ASN1_OK;");
+                        code.dedent();
+                    }
+                    code.open("default:");
+                    code.line("return ASN1_ERR_DECODE;");
+                    code.dedent();
+                    code.line("}");
+                }
+                code.close("}");
+            }
+
+            // the size constraint of a list is given by the slot using it
+            Kind::List(slot) => {
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_write(asn1_bit_writer *w, const {name} *value, const asn1_range *size) {{"
+                ));
+                code.line("size_t i;");
+                code.line("if (value->count > ASN1_MAX_ITEMS)");
+                code.line("    return ASN1_ERR_VALUE;");
+                code.line("ASN1_CHECK(asn1_uper_write_size(w, value->count, size));");
+                code.open("for (i = 0; i < value->count; i++) {");
+                self.uper_write(code, slot, "value->items[i]", "w");
+                code.close("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+
+                code.line("");
+                code.open(&format!(
+                    "int {name}_uper_read(asn1_bit_reader *r, {name} *value, const asn1_range *size) {{"
+                ));
+                code.line("size_t i;");
+                code.line("ASN1_CHECK(asn1_uper_read_size(r, &value->count, size));");
+                code.line("if (value->count > ASN1_MAX_ITEMS)");
+                code.line("    return ASN1_ERR_LIMIT;");
+                code.open("for (i = 0; i < value->count; i++) {");
+                self.uper_read(code, slot, "value->items[i]", "r");
+                code.close("}");
+                code.line("return ASN1_OK;");
+                code.close("}");
+            }
+
+            Kind::Alias(_) => (),
+        }
+
+        let Some(slot) = &def.assignment else {
+            return;
+        };
+        code.line("");
+        code.open(&format!(
+            "int {name}_encode_uper(const {name} *value, uint8_t *buffer, size_t size, size_t *length) {{"
+        ));
+        code.line("asn1_bit_writer writer;");
+        code.line("asn1_bit_writer *w = &writer;");
+        code.line("writer.data = buffer;");
+        code.line("writer.size = size;");
+        code.line("writer.bit = 0;");
+        self.uper_write(code, slot, "(*value)", "w");
+        code.line("/* a complete encoding is at least one octet */");
+        code.line("if (w->bit == 0)");
+        code.line("    ASN1_CHECK(asn1_uper_write_bits(w, 0, 8));");
+        code.line("ASN1_CHECK(asn1_uper_pad(w, (w->bit + 7) / 8 * 8));");
+        code.line("*length = w->bit / 8;");
+        code.line("return ASN1_OK;");
+        code.close("}");
+
+        code.line("");
+        code.open(&format!(
+            "int {name}_decode_uper({name} *value, const uint8_t *buffer, size_t size, size_t *length) {{"
+        ));
+        code.line("asn1_bit_reader reader;");
+        code.line("asn1_bit_reader *r = &reader;");
+        code.line("reader.data = buffer;");
+        code.line("reader.bits = size * 8;");
+        code.line("reader.bit = 0;");
+        self.uper_read(code, slot, "(*value)", "r");
+        code.line("*length = r->bit == 0 ? 1 : (r->bit + 7) / 8;");
+        code.line("return ASN1_OK;");
+        code.close("}");
+    }
+
+    /// Read the extension additions of a sequence, skipping any that are not
+    /// known
+    fn uper_read_additions(
+        &self,
+        code: &mut Code,
+        name: &str,
+        fields: &[Field],
+        additions: &[super::model::Addition],
+    ) {
+        code.open("if (extended) {");
+        code.line("size_t count, i;");
+        code.line("asn1_bit_reader bitmap;");
+        code.line("ASN1_CHECK(asn1_uper_read_small(r, &count));");
+        code.line("count++;");
+        code.line("if (count > r->bits - r->bit)");
+        code.line("    return ASN1_ERR_BUFFER;");
+        code.line("bitmap = *r;");
+        code.line("r->bit += count;");
+        code.open("for (i = 0; i < count; i++) {");
+        code.line("uint64_t present;");
+        code.line("asn1_bit_reader contents;");
+        code.line("ASN1_CHECK(asn1_uper_read_bits(&bitmap, 1, &present));");
+        code.line("if (!present)");
+        code.line("    continue;");
+        code.line("ASN1_CHECK(asn1_uper_read_open_header(r, &contents));");
+        if !additions.is_empty() {
+            code.line("switch (i) {");
+            for (index, addition) in additions.iter().enumerate() {
+                code.open(&format!("case {index}: {{"));
+                let group: Vec<_> = addition.fields.iter().map(|&i| &fields[i]).collect();
+                let optional = group
+                    .iter()
+                    .filter(|field| addition.group && field.optional)
+                    .count();
+                if optional > 0 {
+                    code.line("uint64_t group;");
+                    code.line(&format!(
+                        "ASN1_CHECK(asn1_uper_read_bits(&contents, {optional}, &group));"
+                    ));
+                }
+                let mut bit = optional;
+                for field in group {
+                    let place = format!("value->{}", field.name);
+                    if addition.group && field.optional {
+                        bit -= 1;
+                        code.open(&format!("if ((group >> {bit}) & 1) {{"));
+                        code.line(&format!("value->present |= {};", presence(name, field)));
+                        self.uper_read(code, &field.slot, &place, "&contents");
+                        code.close("}");
+                    } else {
+                        code.line(&format!("value->present |= {};", presence(name, field)));
+                        self.uper_read(code, &field.slot, &place, "&contents");
+                    }
+                }
+                code.line("break;");
+                code.close("}");
+            }
+            code.open("default:");
+            code.line("break;");
+            code.dedent();
+            code.line("}");
+        }
+        code.close("}");
+        code.close("}");
+    }
+
+    /// Write the code encoding the value stored in a place
+    fn uper_write(&self, code: &mut Code, slot: &Slot, place: &str, writer: &str) {
+        let value = argument(slot, place);
+        let range = range_argument(slot.value);
+        let size = range_argument(slot.size);
+        let call = match &slot.ty {
+            SlotType::Primitive(primitive) => match primitive {
+                Primitive::Boolean => format!("asn1_uper_write_boolean({writer}, {value})"),
+                Primitive::Integer => {
+                    format!("asn1_uper_write_integer({writer}, {value}, {range})")
+                }
+                Primitive::Unsigned => {
+                    format!("asn1_uper_write_unsigned({writer}, {value}, {range})")
+                }
+                Primitive::Real => format!("asn1_uper_write_real({writer}, {value})"),
+                Primitive::Null => return,
+                Primitive::BitString => {
+                    format!("asn1_uper_write_bit_string({writer}, {value}, {size})")
+                }
+                Primitive::Octets | Primitive::Open => {
+                    format!("asn1_uper_write_octets({writer}, {value}, {size})")
+                }
+                Primitive::Chars {
+                    octets,
+                    bits,
+                    alphabet,
+                } => format!(
+                    "asn1_uper_write_chars({writer}, {value}, {octets}, {bits}, {}, {size})",
+                    alphabet_argument(alphabet.as_deref())
+                ),
+                Primitive::Oid { relative } => {
+                    format!("asn1_uper_write_oid({writer}, {value}, {relative})")
+                }
+            },
+            SlotType::Named { name, pointer } => {
+                if *pointer {
+                    code.line(&format!("if (!{place})"));
+                    code.line("    return ASN1_ERR_VALUE;");
+                }
+                match self.model.definition(name).kind {
+                    Kind::List(_) => format!("{name}_uper_write({writer}, {value}, {size})"),
+                    _ => format!("{name}_uper_write({writer}, {value})"),
+                }
+            }
+        };
+        code.line(&format!("ASN1_CHECK({call});"));
+    }
+
+    /// Write the code decoding a value into a place
+    fn uper_read(&self, code: &mut Code, slot: &Slot, place: &str, reader: &str) {
+        let range = range_argument(slot.value);
+        let size = range_argument(slot.size);
+        let call = match &slot.ty {
+            SlotType::Primitive(primitive) => match primitive {
+                Primitive::Boolean => format!("asn1_uper_read_boolean({reader}, &{place})"),
+                Primitive::Integer => {
+                    format!("asn1_uper_read_integer({reader}, &{place}, {range})")
+                }
+                Primitive::Unsigned => {
+                    format!("asn1_uper_read_unsigned({reader}, &{place}, {range})")
+                }
+                Primitive::Real => format!("asn1_uper_read_real({reader}, &{place})"),
+                Primitive::Null => return,
+                Primitive::BitString => {
+                    format!("asn1_uper_read_bit_string({reader}, &{place}, {size})")
+                }
+                Primitive::Octets | Primitive::Open => {
+                    format!("asn1_uper_read_octets({reader}, &{place}, {size})")
+                }
+                Primitive::Chars {
+                    octets,
+                    bits,
+                    alphabet,
+                } => format!(
+                    "asn1_uper_read_chars({reader}, &{place}, {octets}, {bits}, {}, {size})",
+                    alphabet_argument(alphabet.as_deref())
+                ),
+                Primitive::Oid { relative } => {
+                    format!("asn1_uper_read_oid({reader}, &{place}, {relative})")
+                }
+            },
+            SlotType::Named { name, pointer } => {
+                if *pointer {
+                    code.line(&format!("if (!{place})"));
+                    code.line("    return ASN1_ERR_STORAGE;");
+                }
+                let value = argument(slot, place);
+                match self.model.definition(name).kind {
+                    Kind::List(_) => format!("{name}_uper_read({reader}, {value}, {size})"),
+                    _ => format!("{name}_uper_read({reader}, {value})"),
+                }
+            }
+        };
+        code.line(&format!("ASN1_CHECK({call});"));
+    }
+}
+
+/// Write the code encoding a value as an open type, first with a writer
+/// counting its bits to find its length
+fn open_type(code: &mut Code, body: impl Fn(&mut Code, &str)) {
+    code.open("{");
+    code.line("asn1_bit_writer counter = {NULL, 0, 0};");
+    code.line("size_t start, octets;");
+    body(code, "&counter");
+    code.line("octets = counter.bit == 0 ? 1 : (counter.bit + 7) / 8;");
+    code.line("ASN1_CHECK(asn1_uper_write_length(w, octets));");
+    code.line("start = w->bit;");
+    body(code, "w");
+    code.line("ASN1_CHECK(asn1_uper_pad(w, start + octets * 8));");
+    code.close("}");
+}
+
+/// Get the name of the presence bit of a field
+fn presence(name: &str, field: &Field) -> String {
+    format!("{name}_{}_PRESENT", field.name)
+}
+
+/// Get the number of bits encoding an index of the root items of a choice
+/// or enumerated type
+fn index_bits(count: usize) -> u32 {
+    match count {
+        0 | 1 => 0,
+        count => usize::BITS - (count - 1).leading_zeros(),
+    }
+}
+
+/// Get the C string of the alphabet characters are numbered in, escaping
+/// characters that cannot be written within it
+fn alphabet_argument(alphabet: Option<&str>) -> String {
+    let Some(alphabet) = alphabet else {
+        return "NULL".to_string();
+    };
+    let mut string = String::from("\"");
+    for c in alphabet.chars() {
+        match c {
+            '"' | '\\' => {
+                string.push('\\');
+                string.push(c);
+            }
+            ' '..='~' => string.push(c),
+            c => string.push_str(&format!("\\{:03o}", u32::from(c))),
+        }
+    }
+    string.push('"');
+    string
+}
+
+/// Get the argument passing the range of a constraint to the runtime, which
+/// is an unsigned range if its upper bound is beyond those of signed integers
+fn range_argument(range: Option<Range>) -> String {
+    let Some(range) = range else {
+        return "NULL".to_string();
+    };
+    let bound = |bound: Option<i128>| match bound {
+        Some(bound) if bound == i64::MIN.into() => "INT64_MIN".to_string(),
+        Some(bound) if bound == u64::MAX.into() => "UINT64_MAX".to_string(),
+        Some(bound) if bound > i64::MAX.into() => format!("{bound}u"),
+        Some(bound) => bound.to_string(),
+        None => "0".to_string(),
+    };
+    let ty = match range.upper {
+        Some(upper) if upper > i64::MAX.into() => "asn1_urange",
+        _ => "asn1_range",
+    };
+    let mut flags = vec![];
+    if range.lower.is_some() {
+        flags.push("ASN1_LOWER");
+    }
+    if range.upper.is_some() {
+        flags.push("ASN1_UPPER");
+    }
+    if range.extensible {
+        flags.push("ASN1_EXTENSIBLE");
+    }
+    if flags.is_empty() {
+        flags.push("0");
+    }
+    format!(
+        "&(const {ty}){{{}, {}, {}}}",
+        bound(range.lower),
+        bound(range.upper),
+        flags.join(" | ")
+    )
+}
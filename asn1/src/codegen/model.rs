@@ -0,0 +1,948 @@
+//! The types generated by the C and Ada targets, built from the types of
+//! the analysed modules, and the source text they are written as.
+
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigInt;
+
+use crate::{
+    analysis::{enum_numbers, AnalysisContext, Bounds, Environment},
+    ast::{
+        ComponentType, ExtensionAddition, TagClass, TagDefault, TagKind, Type, TypeOrValue, Value,
+    },
+    cst::AsnNodeId,
+    graph::GraphLevel,
+};
+
+use super::{CodegenError, Result};
+
+/// Build the definitions of the types of every analysed module, with
+/// members and enumeration items named by the target language
+pub(super) fn definitions<'a>(
+    analysis: &'a AnalysisContext<'a>,
+    member_name: fn(&str) -> String,
+) -> Result<Vec<Definition<'a>>> {
+    let graph = analysis.dependency_graph(GraphLevel::Types);
+    let mut cycles = HashMap::new();
+    for (cycle, nodes) in graph.cycles.iter().enumerate() {
+        for &node in nodes {
+            cycles.insert(graph.nodes[node].name.clone(), cycle);
+        }
+    }
+
+    let mut builder = Builder {
+        analysis,
+        cycles,
+        member_name,
+        instance: None,
+        definitions: vec![],
+    };
+    let mut modules: Vec<_> = analysis.modules().collect();
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+    for module in modules {
+        builder.module(module)?;
+    }
+    Ok(builder.definitions)
+}
+
+/// A BER tag, with its class numbered in the canonical order of tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct Tag {
+    pub(super) class: usize,
+    pub(super) number: u32,
+}
+
+/// A value within a generated type, and how it is encoded
+#[derive(Debug, Clone)]
+pub(super) struct Slot {
+    /// The tags encoded in BER, outermost first, with whether the encoding
+    /// with each tag is constructed.  Only untagged choice and open types
+    /// have no tags.
+    pub(super) tags: Vec<(Tag, bool)>,
+    pub(super) ty: SlotType,
+
+    /// The values of an integer permitted by its PER-visible constraints
+    pub(super) value: Option<Range>,
+
+    /// The number of bits, octets, characters or items of a value permitted
+    /// by its PER-visible constraints
+    pub(super) size: Option<Range>,
+}
+
+/// The bounds of the extension root of a PER-visible constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Range {
+    pub(super) lower: Option<i128>,
+    pub(super) upper: Option<i128>,
+    pub(super) extensible: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum SlotType {
+    Primitive(Primitive),
+
+    /// A generated type, stored through a pointer if it contains the type
+    /// the value is within
+    Named {
+        name: String,
+        pointer: bool,
+    },
+}
+
+/// A type represented by a type of the runtime
+#[derive(Debug, Clone)]
+pub(super) enum Primitive {
+    Boolean,
+    Integer,
+
+    /// An integer whose permitted values are all non-negative, and include
+    /// values beyond those of a signed 64 bit integer
+    Unsigned,
+    Real,
+    Null,
+    BitString,
+    Octets,
+
+    /// A character string with a fixed number of octets for each character,
+    /// encoded in PER in the given number of bits, as the index of the
+    /// character in an alphabet if there is one
+    Chars {
+        octets: u32,
+        bits: u32,
+        alphabet: Option<String>,
+    },
+    Oid {
+        relative: bool,
+    },
+    Open,
+}
+
+/// A type generated for a type assignment or a type nested within one
+pub(super) struct Definition<'a> {
+    pub(super) name: String,
+    pub(super) module: &'a str,
+    pub(super) doc: Option<&'a str>,
+
+    /// The type assigned by a type assignment, which is given functions to
+    /// encode and decode complete values
+    pub(super) assignment: Option<Slot>,
+    pub(super) kind: Kind<'a>,
+}
+
+impl Definition<'_> {
+    /// Get the slots of the values within the type
+    pub(super) fn slots(&self) -> Vec<&Slot> {
+        match &self.kind {
+            Kind::Sequence(sequence) => sequence.fields.iter().map(|f| &f.slot).collect(),
+            Kind::Choice(choice) => choice.alternatives.iter().map(|f| &f.slot).collect(),
+            Kind::Enumerated(_) => vec![],
+            Kind::List(slot) | Kind::Alias(slot) => vec![slot],
+        }
+    }
+}
+
+pub(super) enum Kind<'a> {
+    Sequence(Sequence<'a>),
+    Choice(Choice<'a>),
+    Enumerated(Enumerated<'a>),
+    List(Slot),
+
+    /// Another name for a type of the runtime or a generated type
+    Alias(Slot),
+}
+
+pub(super) struct Sequence<'a> {
+    pub(super) set: bool,
+    pub(super) fields: Vec<Field<'a>>,
+
+    /// The fields in the extension root, in the order they are written
+    pub(super) root: Vec<usize>,
+    pub(super) additions: Vec<Addition>,
+    pub(super) extensible: bool,
+
+    /// The number of bits in the presence bitmask
+    pub(super) presence: usize,
+}
+
+/// A component of a sequence or set, or an alternative of a choice
+pub(super) struct Field<'a> {
+    pub(super) name: String,
+    pub(super) doc: Option<&'a str>,
+    pub(super) slot: Slot,
+
+    /// The bit of the field in the presence bitmask
+    pub(super) presence: Option<usize>,
+
+    /// Is the component `OPTIONAL` or has a `DEFAULT` value
+    pub(super) optional: bool,
+}
+
+/// An extension addition of a sequence or set, encoded in PER as a single
+/// open type
+pub(super) struct Addition {
+    pub(super) fields: Vec<usize>,
+
+    /// Is the addition an extension addition group, which is encoded as a
+    /// sequence of its components
+    pub(super) group: bool,
+}
+
+pub(super) struct Choice<'a> {
+    pub(super) alternatives: Vec<Field<'a>>,
+
+    /// The alternatives in the extension root, in the order they are written
+    pub(super) root: Vec<usize>,
+    pub(super) additions: Vec<usize>,
+    pub(super) extensible: bool,
+}
+
+pub(super) struct Enumerated<'a> {
+    /// The constant, number and documentation of each item
+    pub(super) items: Vec<(String, i128, Option<&'a str>)>,
+
+    /// The items in the extension root, ordered by number
+    pub(super) root: Vec<usize>,
+    pub(super) additions: Vec<usize>,
+    pub(super) extensible: bool,
+}
+
+/// Builds the definitions of the types of every module
+struct Builder<'a> {
+    analysis: &'a AnalysisContext<'a>,
+
+    /// The cycle each assignment that refers to itself is part of, by its
+    /// qualified name
+    cycles: HashMap<String, usize>,
+
+    /// Names a member or enumeration item in the target language
+    member_name: fn(&str) -> String,
+
+    /// The instance of a parameterized type being defined, whose parameters
+    /// are used by the constraints within it
+    instance: Option<&'a str>,
+    definitions: Vec<Definition<'a>>,
+}
+
+impl<'a> Builder<'a> {
+    fn module(&mut self, module: &'a Environment) -> Result {
+        let mut assignments: Vec<_> = module.expanded.iter().collect();
+        assignments.sort_by_key(|(name, _)| *name);
+
+        for (name, var) in assignments {
+            let TypeOrValue::Type(ty) = &*var.value else {
+                continue;
+            };
+            if matches!(ty, Type::Class(_)) {
+                continue;
+            }
+
+            let c_name = type_name(&module.name, name);
+            let qualified = format!("{}.{}", module.name, name);
+            self.instance = module.instances.contains_key(name).then_some(name.as_str());
+            let mut slot = self.slot(module, &qualified, &c_name, ty, var.value.id, None)?;
+
+            // the assigned type is never stored through a pointer
+            if let SlotType::Named { pointer, .. } = &mut slot.ty {
+                *pointer = false;
+            }
+            let doc = module.comments.get(&var.id).map(String::as_str);
+
+            // a type defined by the assignment is given its functions, and
+            // other types are given another name
+            let defined = self.definitions.last_mut().filter(|def| def.name == c_name);
+            match defined {
+                Some(definition) => {
+                    definition.doc = doc;
+                    definition.assignment = Some(slot);
+                }
+                None => self.definitions.push(Definition {
+                    name: c_name,
+                    module: &module.name,
+                    doc,
+                    assignment: Some(slot.clone()),
+                    kind: Kind::Alias(slot),
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the slot of a type used within a type assignment, given the node
+    /// ID of the type, defining the type with the given name if it is not a
+    /// reference.  References to assignments that refer back to the
+    /// assignment are pointers.
+    fn slot(
+        &mut self,
+        module: &'a Environment,
+        assignment: &str,
+        name: &str,
+        ty: &'a Type,
+        id: AsnNodeId,
+        automatic: Option<u32>,
+    ) -> Result<Slot> {
+        let constraints = match self.instance {
+            Some(instance) => self
+                .analysis
+                .constraints_within(module, instance, ty, id, true),
+            None => self.analysis.constraints_of(module, ty, id, true),
+        };
+
+        let mut raw = vec![];
+        let mut current = module;
+        let mut ty = ty;
+        let mut target = None;
+        let mut pointer = false;
+
+        loop {
+            match ty {
+                Type::Tagged(tagged) => {
+                    let explicit = match tagged.kind {
+                        TagKind::Explicit => true,
+                        TagKind::Implicit => false,
+                        // a choice or open type has no tag of its own to replace
+                        TagKind::Default => {
+                            current.tag_default == TagDefault::Explicit
+                                || self.untagged(current, &tagged.ty)
+                        }
+                    };
+                    let tag = Tag {
+                        class: class(tagged.class),
+                        number: self.tag_number(current, &tagged.number)?,
+                    };
+                    raw.push((tag, explicit));
+                    ty = &tagged.ty;
+                }
+                Type::Reference(reference) => {
+                    let (referenced, var) = self
+                        .analysis
+                        .resolve_expanded(current, reference)
+                        .ok_or(CodegenError::AnalysisErrors)?;
+                    if target.is_none() {
+                        let to = format!("{}.{}", referenced.name, *reference.name);
+                        pointer = self.cycles.get(assignment).is_some_and(|cycle| {
+                            self.cycles.get(&to).is_some_and(|other| cycle == other)
+                        });
+                    }
+                    target = Some(type_name(&referenced.name, &reference.name));
+                    current = referenced;
+                    ty = match &*var.value {
+                        TypeOrValue::Type(ty) => ty,
+                        TypeOrValue::Value(_) => return Err(CodegenError::AnalysisErrors),
+                    };
+                }
+                _ => break,
+            }
+        }
+
+        let constraints = constraints.per_visible(ty);
+        let mut ty_slot = match ty {
+            Type::Sequence(_)
+            | Type::Set(_)
+            | Type::Choice(_)
+            | Type::Enumerated(_)
+            | Type::SequenceOf(_)
+            | Type::SetOf(_) => {
+                let name = match target {
+                    Some(target) => target,
+                    None => {
+                        self.definition(current, assignment, name, ty)?;
+                        name.to_string()
+                    }
+                };
+                SlotType::Named { name, pointer }
+            }
+            ty => SlotType::Primitive(match &constraints.alphabet {
+                Some(permitted) => permitted_alphabet(primitive(ty), permitted)?,
+                None => primitive(ty),
+            }),
+        };
+
+        let value = match &constraints.value {
+            Some(values) => Some(range(values).ok_or(CodegenError::Unsupported(
+                "integer constraints beyond 64 bits",
+            ))?),
+            None => None,
+        };
+        if let (SlotType::Primitive(primitive @ Primitive::Integer), Some(range)) =
+            (&mut ty_slot, value)
+        {
+            if range.upper.is_some_and(|upper| upper > i64::MAX.into()) {
+                *primitive = Primitive::Unsigned;
+            }
+        }
+
+        // sizes are never negative, and larger sizes than can be stored are
+        // encoded as if unbounded
+        let bound = |n: &BigInt| i64::try_from(n).ok().map(i128::from);
+        let size = constraints.size.as_ref().map(|sizes| Range {
+            lower: Some(sizes.lower().and_then(bound).unwrap_or(0)),
+            upper: sizes.upper().and_then(bound),
+            extensible: sizes.extensible,
+        });
+
+        let universal = ty.universal_tag();
+        let constructed = matches!(
+            ty,
+            Type::Sequence(_)
+                | Type::Set(_)
+                | Type::SequenceOf(_)
+                | Type::SetOf(_)
+                | Type::External
+                | Type::EmbeddedPdv
+                | Type::CharacterString
+        );
+
+        // an automatic tag replaces the first tag, unless the type is an
+        // untagged choice or open type
+        let mut all = vec![];
+        if let Some(number) = automatic {
+            let tag = Tag {
+                class: class(TagClass::ContextSpecific),
+                number,
+            };
+            all.push((tag, raw.is_empty() && universal.is_none()));
+        }
+        all.extend(raw);
+        if let Some(number) = universal {
+            all.push((Tag { class: 0, number }, false));
+        }
+
+        // tags replaced by an implicit tag are not encoded
+        let mut tags = vec![];
+        let mut replaced = false;
+        for (tag, explicit) in all {
+            if !replaced {
+                tags.push((tag, explicit || constructed));
+            }
+            replaced = !explicit;
+        }
+
+        Ok(Slot {
+            tags,
+            ty: ty_slot,
+            value,
+            size,
+        })
+    }
+
+    /// Define a sequence, set, choice, enumerated or list type
+    fn definition(
+        &mut self,
+        module: &'a Environment,
+        assignment: &str,
+        name: &str,
+        ty: &'a Type,
+    ) -> Result {
+        let kind = match ty {
+            Type::Sequence(components) | Type::Set(components) => {
+                let automatic = module.automatic_tags(components, |c| &c.ty().value);
+                let named = |component: &'a ComponentType| match component {
+                    ComponentType::Named(component) => Some(component),
+                    ComponentType::ComponentsOf(_) => None,
+                };
+
+                // automatic tags number the root components first
+                let additions: Vec<Vec<_>> = components
+                    .extension
+                    .iter()
+                    .flat_map(|ext| &ext.additions)
+                    .map(|addition| addition.items().iter().filter_map(named).collect())
+                    .collect();
+                let numbered: Vec<_> = components
+                    .root_items()
+                    .filter_map(named)
+                    .chain(additions.iter().flatten().copied())
+                    .map(|component| component.name.id)
+                    .collect();
+                let in_additions: HashSet<_> =
+                    additions.iter().flatten().map(|c| c.name.id).collect();
+
+                let mut fields = vec![];
+                let mut presence = 0;
+                for component in components.items().filter_map(named) {
+                    let number = numbered.iter().position(|&id| id == component.name.id);
+                    let field_name = (self.member_name)(&component.name);
+                    let slot = self.slot(
+                        module,
+                        assignment,
+                        &format!("{name}_{field_name}"),
+                        &component.ty,
+                        component.ty.id,
+                        number.filter(|_| automatic).map(|n| n as u32),
+                    )?;
+
+                    let optional = component.optional || component.default.is_some();
+                    let bit = (optional || in_additions.contains(&component.name.id)).then(|| {
+                        presence += 1;
+                        presence - 1
+                    });
+                    fields.push((
+                        component.name.id,
+                        Field {
+                            name: field_name,
+                            doc: module.comments.get(&component.name.id).map(String::as_str),
+                            slot,
+                            presence: bit,
+                            optional,
+                        },
+                    ));
+                }
+
+                let index = |id| fields.iter().position(|(field, _)| *field == id).unwrap();
+                let root = components
+                    .root_items()
+                    .filter_map(named)
+                    .map(|c| index(c.name.id))
+                    .collect();
+                let additions = components
+                    .extension
+                    .iter()
+                    .flat_map(|ext| &ext.additions)
+                    .zip(&additions)
+                    .map(|(addition, components)| Addition {
+                        fields: components.iter().map(|c| index(c.name.id)).collect(),
+                        group: matches!(addition, ExtensionAddition::Group(_)),
+                    })
+                    .collect();
+
+                Kind::Sequence(Sequence {
+                    set: matches!(ty, Type::Set(_)),
+                    fields: fields.into_iter().map(|(_, field)| field).collect(),
+                    root,
+                    additions,
+                    extensible: components.extension.is_some() || module.extensibility_implied,
+                    presence,
+                })
+            }
+
+            Type::Choice(alternatives) => {
+                let automatic = module.automatic_tags(alternatives, |a| &a.ty.value);
+                let root_count = alternatives.root_items().count();
+                let root: Vec<_> = alternatives.root_items().map(|a| a.name.id).collect();
+
+                let mut fields = vec![];
+                let mut root_indices = vec![];
+                let mut additions = vec![];
+                for alternative in alternatives.items() {
+                    // automatic tags number the root alternatives first
+                    let number = match root.iter().position(|&id| id == alternative.name.id) {
+                        Some(position) => {
+                            root_indices.push(fields.len());
+                            position
+                        }
+                        None => {
+                            additions.push(fields.len());
+                            root_count + additions.len() - 1
+                        }
+                    };
+                    let field_name = (self.member_name)(&alternative.name);
+                    let slot = self.slot(
+                        module,
+                        assignment,
+                        &format!("{name}_{field_name}"),
+                        &alternative.ty,
+                        alternative.ty.id,
+                        automatic.then_some(number as u32),
+                    )?;
+                    fields.push(Field {
+                        name: field_name,
+                        doc: module
+                            .comments
+                            .get(&alternative.name.id)
+                            .map(String::as_str),
+                        slot,
+                        presence: None,
+                        optional: false,
+                    });
+                }
+
+                Kind::Choice(Choice {
+                    alternatives: fields,
+                    root: root_indices,
+                    additions,
+                    extensible: alternatives.extension.is_some() || module.extensibility_implied,
+                })
+            }
+
+            Type::Enumerated(items) => {
+                let numbers: HashMap<_, _> = enum_numbers(items)
+                    .into_iter()
+                    .map(|(name, number, _)| (name.id, number))
+                    .collect();
+                let number = |id| {
+                    let number = numbers.get(&id).ok_or(CodegenError::Unsupported(
+                        "enumeration items numbered by references to values",
+                    ))?;
+                    i128::try_from(number).map_err(|_| {
+                        CodegenError::Unsupported("enumeration items numbered beyond 128 bits")
+                    })
+                };
+
+                let mut constants = vec![];
+                for item in items.items() {
+                    constants.push((
+                        format!("{name}_{}", (self.member_name)(&item.name)),
+                        number(item.name.id)?,
+                        module.comments.get(&item.name.id).map(String::as_str),
+                    ));
+                }
+                let index = |id| {
+                    let item = items.items().position(|item| item.name.id == id);
+                    item.unwrap()
+                };
+                let mut root: Vec<_> = items.root_items().map(|i| index(i.name.id)).collect();
+                root.sort_by_key(|&i| constants[i].1);
+                let additions = items
+                    .extension
+                    .iter()
+                    .flat_map(|ext| ext.additions.iter().flat_map(ExtensionAddition::items))
+                    .map(|item| index(item.name.id))
+                    .collect();
+
+                Kind::Enumerated(Enumerated {
+                    items: constants,
+                    root,
+                    additions,
+                    extensible: items.extension.is_some() || module.extensibility_implied,
+                })
+            }
+
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                let item = format!("{name}_item");
+                let slot = self.slot(module, assignment, &item, &of.ty, of.ty.id, None)?;
+                Kind::List(slot)
+            }
+
+            _ => unreachable!("only structured types are defined"),
+        };
+
+        self.definitions.push(Definition {
+            name: name.to_string(),
+            module: &module.name,
+            doc: None,
+            assignment: None,
+            kind,
+        });
+        Ok(())
+    }
+
+    /// Is a type an untagged choice or open type, which is always tagged
+    /// explicitly
+    fn untagged(&self, module: &'a Environment, ty: &'a Type) -> bool {
+        match ty {
+            Type::Choice(_) | Type::Field(_) => true,
+            Type::Reference(reference) => match self.analysis.resolve_expanded(module, reference) {
+                Some((module, var)) => match &*var.value {
+                    TypeOrValue::Type(ty) => self.untagged(module, ty),
+                    TypeOrValue::Value(_) => false,
+                },
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Get the number of a tag, which is either a number or a reference to
+    /// an integer value
+    fn tag_number(&self, module: &'a Environment, value: &'a Value) -> Result<u32> {
+        let unsupported = CodegenError::Unsupported("tag numbers that are not integers");
+        match value {
+            Value::Number(number) => number.parse().map_err(|_| unsupported),
+            Value::Reference(reference) => {
+                let (module, var) = self
+                    .analysis
+                    .resolve_expanded(module, reference)
+                    .ok_or(unsupported)?;
+                match &*var.value {
+                    TypeOrValue::Value(value) => self.tag_number(module, value),
+                    TypeOrValue::Type(_) => Err(unsupported),
+                }
+            }
+            _ => Err(unsupported),
+        }
+    }
+}
+
+/// Get the runtime type used for a type that is not generated
+pub(super) fn primitive(ty: &Type) -> Primitive {
+    match ty {
+        Type::Boolean => Primitive::Boolean,
+        Type::Integer(_) => Primitive::Integer,
+        Type::Real => Primitive::Real,
+        Type::Null => Primitive::Null,
+        Type::BitString(_) => Primitive::BitString,
+        Type::ObjectIdentifier => Primitive::Oid { relative: false },
+        Type::RelativeOid => Primitive::Oid { relative: true },
+
+        // characters of these strings are encoded in PER by their values in
+        // seven bits
+        Type::IA5String
+        | Type::VisibleString
+        | Type::ISO64String
+        | Type::PrintableString
+        | Type::GeneralizedTime
+        | Type::UTCTime => Primitive::Chars {
+            octets: 1,
+            bits: 7,
+            alphabet: None,
+        },
+        Type::NumericString => Primitive::Chars {
+            octets: 1,
+            bits: 4,
+            alphabet: Some(" 0123456789".into()),
+        },
+        Type::BmpString => Primitive::Chars {
+            octets: 2,
+            bits: 16,
+            alphabet: None,
+        },
+        Type::UniversalString => Primitive::Chars {
+            octets: 4,
+            bits: 32,
+            alphabet: None,
+        },
+
+        Type::OctetString
+        | Type::ObjectDescriptor
+        | Type::UTF8String
+        | Type::GeneralString
+        | Type::GraphicString
+        | Type::TeletexString
+        | Type::T61String
+        | Type::VideotexString
+        | Type::OidIri
+        | Type::RelativeOidIri
+        | Type::Time
+        | Type::Date
+        | Type::TimeOfDay
+        | Type::DateTime
+        | Type::Duration
+        | Type::External
+        | Type::EmbeddedPdv
+        | Type::CharacterString => Primitive::Octets,
+
+        _ => Primitive::Open,
+    }
+}
+
+/// Get the range of the extension root of a value constraint, if its bounds
+/// are signed 64 bit integers, or it is a range of non-negative values whose
+/// upper bound is an unsigned 64 bit integer
+fn range(values: &Bounds<BigInt>) -> Option<Range> {
+    let bound = |bound: Option<&BigInt>| match bound {
+        Some(bound) => i128::try_from(bound).ok().map(Some),
+        None => Some(None),
+    };
+    let lower = bound(values.lower())?;
+    let upper = bound(values.upper())?;
+
+    let signed = |bound: Option<i128>| bound.is_none_or(|bound| i64::try_from(bound).is_ok());
+    let unsigned = lower.is_some_and(|lower| lower >= 0)
+        && upper.is_some_and(|upper| upper <= u64::MAX.into());
+    (signed(lower) && signed(upper) || unsigned).then_some(Range {
+        lower,
+        upper,
+        extensible: values.extensible,
+    })
+}
+
+/// Get the runtime type of a known-multiplier character string restricted to
+/// a permitted alphabet, whose characters are numbered in the fewest bits
+/// that can number every character of the alphabet.  The characters are
+/// encoded as their own values if those fit in the bits, and as their index
+/// in the alphabet otherwise.
+fn permitted_alphabet(primitive: Primitive, permitted: &Bounds<char>) -> Result<Primitive> {
+    let Primitive::Chars { octets, .. } = primitive else {
+        return Ok(primitive);
+    };
+    let mut count = 0;
+    for interval in &permitted.intervals {
+        let (Some(lower), Some(upper)) = (interval.lower, interval.upper) else {
+            return Ok(primitive);
+        };
+        count += u64::from(upper) - u64::from(lower) + 1;
+    }
+    let Some(largest) = permitted.upper() else {
+        return Ok(primitive);
+    };
+
+    let bits = match count {
+        0 => return Ok(primitive),
+        1 => 0,
+        count => u64::BITS - (count - 1).leading_zeros(),
+    };
+    if u64::from(*largest) >> bits == 0 {
+        return Ok(Primitive::Chars {
+            octets,
+            bits,
+            alphabet: None,
+        });
+    }
+
+    // the runtime finds indices in a C string
+    let unsupported = CodegenError::Unsupported("permitted alphabets beyond ASCII");
+    let mut alphabet = String::new();
+    for interval in &permitted.intervals {
+        let (Some(lower), Some(upper)) = (interval.lower, interval.upper) else {
+            return Err(unsupported);
+        };
+        if lower == '\0' || !upper.is_ascii() {
+            return Err(unsupported);
+        }
+        alphabet.extend(lower..=upper);
+    }
+    Ok(Primitive::Chars {
+        octets,
+        bits,
+        alphabet: Some(alphabet),
+    })
+}
+
+pub(super) fn class(class: TagClass) -> usize {
+    match class {
+        TagClass::Universal => 0,
+        TagClass::Application => 1,
+        TagClass::ContextSpecific => 2,
+        TagClass::Private => 3,
+    }
+}
+
+/// Replace every run of characters that cannot be part of a C identifier
+/// with an underscore
+pub(super) fn identifier(name: &str) -> String {
+    let mut result = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            result.push(c);
+        } else if !result.is_empty() && !result.ends_with('_') {
+            result.push('_');
+        }
+    }
+    result.trim_end_matches('_').to_string()
+}
+
+/// Get the name of the type generated for an assignment or an instance of a
+/// parameterized type
+pub(super) fn type_name(module: &str, name: &str) -> String {
+    format!("{}_{}", identifier(module), identifier(name))
+}
+
+/// Source text, indented by the blocks it is within
+pub(super) struct Code {
+    pub(super) text: String,
+    depth: usize,
+
+    /// Number of spaces each block is indented by
+    indent: usize,
+}
+
+impl Default for Code {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl Code {
+    pub(super) fn new(indent: usize) -> Self {
+        Self {
+            text: String::new(),
+            depth: 0,
+            indent,
+        }
+    }
+
+    pub(super) fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.depth * self.indent {
+                self.text.push(' ');
+            }
+            self.text.push_str(line);
+        }
+        self.text.push('\n');
+    }
+
+    /// Write a line starting a block
+    pub(super) fn open(&mut self, line: &str) {
+        self.line(line);
+        self.depth += 1;
+    }
+
+    /// Write a line ending a block
+    pub(super) fn close(&mut self, line: &str) {
+        self.depth -= 1;
+        self.line(line);
+    }
+
+    /// End a block without a line, such as a `case` of a `switch`
+    pub(super) fn dedent(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Write a line ending a block and starting another, such as `else`
+    pub(super) fn next(&mut self, line: &str) {
+        self.close(line);
+        self.depth += 1;
+    }
+}
+
+/// The definitions of every module, indexed by name
+pub(super) struct Model<'a> {
+    pub(super) definitions: &'a [Definition<'a>],
+    index: HashMap<&'a str, &'a Definition<'a>>,
+}
+
+impl<'a> Model<'a> {
+    pub(super) fn new(definitions: &'a [Definition<'a>]) -> Self {
+        let index = definitions
+            .iter()
+            .map(|def| (def.name.as_str(), def))
+            .collect();
+        Self { definitions, index }
+    }
+
+    /// Get the definitions of a module, with each definition after the
+    /// definitions it contains by value
+    pub(super) fn ordered(&self, module: &str) -> Vec<&'a Definition<'a>> {
+        let mut ordered = vec![];
+        let mut visited = HashSet::new();
+        for def in self.definitions.iter().filter(|def| def.module == module) {
+            self.visit(def, &mut visited, &mut ordered);
+        }
+        ordered
+    }
+
+    fn visit(
+        &self,
+        def: &'a Definition<'a>,
+        visited: &mut HashSet<&'a str>,
+        ordered: &mut Vec<&'a Definition<'a>>,
+    ) {
+        if !visited.insert(&def.name) {
+            return;
+        }
+        for name in self.dependencies(def, false) {
+            let dependency = self.index[name];
+            if dependency.module == def.module {
+                self.visit(dependency, visited, ordered);
+            }
+        }
+        ordered.push(def);
+    }
+
+    /// Get the names of the generated types used by a definition, including
+    /// those used through pointers if requested
+    pub(super) fn dependencies(&self, def: &'a Definition<'a>, pointers: bool) -> Vec<&'a str> {
+        def.slots()
+            .into_iter()
+            .filter_map(|slot| match &slot.ty {
+                SlotType::Named { name, pointer } if pointers || !pointer => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the definition of a named slot
+    pub(super) fn definition(&self, name: &str) -> &'a Definition<'a> {
+        self.index[name]
+    }
+}
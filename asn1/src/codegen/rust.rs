@@ -0,0 +1,1406 @@
+//! Rust code generated from analysed modules, with a rust module for each
+//! ASN.1 module, written to a single file or to a file for each module.
+//!
+//! Each type assignment is a rust type named after it in the module of its
+//! ASN.1 module:
+//! - `SEQUENCE` and `SET` types are structs with a field for each component.
+//!   `OPTIONAL` and `DEFAULT` components and extension additions are
+//!   `Option`s.
+//! - `CHOICE` types are enums with a variant for each alternative, and
+//!   `ENUMERATED` types are enums of unit variants.
+//! - `SEQUENCE OF` and `SET OF` types are `Vec`s, and other assignments are
+//!   aliases of the types they refer to.
+//! - `INTEGER` is the first of `i64` and `u64` that holds every value
+//!   permitted by its constraints, otherwise `i64`, or `num_bigint::BigInt`
+//!   if chosen by the options.  `REAL` is `f64`, `BIT STRING` is `Vec<bool>`,
+//!   character strings are `String`, and object identifiers are
+//!   `Vec<u64>`.  Times are `String`, or the types of a `time_values` module
+//!   written with the modules if chosen by the options.  Open types, `OCTET STRING`, `EXTERNAL`, `EMBEDDED PDV` and
+//!   `CHARACTER STRING` are `Vec<u8>`.
+//! - Types nested within another type are named after the assignment and
+//!   the components or alternatives using them, or `item` for the elements
+//!   of a `SEQUENCE OF`.
+//!
+//! Each `INTEGER` and `REAL` value assignment is a constant, written before
+//! the types of its module.  Integers are the first of `i64` and `u64` that
+//! holds the value, otherwise `i128`, or a `num_bigint::BigInt` static if
+//! chosen by the options.  Reals are the nearest `f64`.
+//!
+//! Names are converted to the cases chosen by the options, and names that
+//! are the same after conversion are given numbered suffixes.  Values that
+//! would contain a value of their own type are boxed, and lists that contain
+//! themselves are tuple structs, as type aliases cannot refer to themselves.
+//!
+//! With serde enabled, every type derives `Serialize` and `Deserialize`, and
+//! fields and variants are renamed to their ASN.1 identifiers.  Choices are
+//! externally tagged, an object with a single member named by the
+//! alternative, so `NULL` alternatives are variants with a `()` value rather
+//! than unit variants.  Integers, reals, bit strings and octets have the
+//! representations of a `serde_values` module written with the modules,
+//! which round-trip through every format.  The arguments of generic types
+//! have the representations derived for them.
+
+use std::collections::{HashMap, HashSet};
+
+use convert_case::{Case, Casing};
+
+use num_bigint::BigInt;
+
+use crate::{
+    analysis::{enum_numbers, AnalysisContext, Environment, Real},
+    ast::{ComponentType, EnumItem, Extensible, ExtensionAddition, NamedType, Parameter, Type},
+    ast::{ParameterizedReference, TypeOrValue},
+    cst::AsnNodeId,
+    graph::strongly_connected,
+};
+
+use super::{
+    model::Code, CodegenError, CodegenOptions, GeneratedFile, IntegerType, ModuleLayout, NameCase,
+    ParameterizedTypes, Result,
+};
+
+/// Keywords that are written as raw identifiers when used as names
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be raw identifiers, which are given a suffix instead
+const PATH_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Types used by generated code, which generated types must not shadow
+const PRELUDE: &[&str] = &["Box", "Option", "String", "Vec"];
+
+/// Traits that cannot be derived by types containing `f64`
+const FLOAT_UNSUPPORTED: &[&str] = &["Eq", "Hash", "Ord"];
+
+/// The module giving values their serde representations
+const SERDE_VALUES: &str = include_str!("rust/serde_values.rs");
+
+/// The representation of `BigInt` values, added to the serde module when
+/// integers are `BigInt`s
+const SERDE_BIGINT: &str = include_str!("rust/serde_bigint.rs");
+
+/// The representations of time values, added to the serde module when times
+/// have types of their own
+const SERDE_TIMES: &str = include_str!("rust/serde_times.rs");
+
+/// The types of time values, written with the modules when times have types
+/// of their own
+const TIME_VALUES: &str = include_str!("../time.rs");
+
+/// Types whose values have the representations of the serde module
+const SERDE_REPRESENTED: &[&str] = &[
+    "i64",
+    "u64",
+    "f64",
+    "Vec<bool>",
+    "Vec<u8>",
+    "num_bigint::BigInt",
+];
+
+/// Generate the rust modules of every analysed module
+pub(super) fn generate(
+    analysis: &AnalysisContext,
+    options: &CodegenOptions,
+) -> Result<Vec<GeneratedFile>> {
+    let mut modules: Vec<_> = analysis.modules().collect();
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut generator = Generator {
+        analysis,
+        options,
+        definitions: vec![],
+        index: HashMap::new(),
+        instances: HashMap::new(),
+        instance: None,
+        modules: HashMap::new(),
+        serde: None,
+        times: None,
+        constants: vec![],
+    };
+    generator.build(&modules)?;
+    generator.constants(&modules)?;
+    generator.parameters();
+    generator.newtypes();
+    generator.boxes();
+    generator.names(&modules);
+
+    let vis = options.visibility.keyword();
+    let mut files = vec![];
+    let mut root = Code::default();
+    for (i, module) in modules.iter().enumerate() {
+        let name = &generator.modules[module.name.as_str()];
+        match options.layout {
+            ModuleLayout::SingleFile => {
+                if i > 0 {
+                    root.line("");
+                }
+                root.open(&format!("{vis} mod {name} {{"));
+                generator.module(&mut root, &module.name);
+                root.close("}");
+            }
+            ModuleLayout::FilePerModule => {
+                root.line(&format!("{vis} mod {name};"));
+                let mut code = Code::default();
+                generator.module(&mut code, &module.name);
+                files.push(GeneratedFile {
+                    file: format!("{}.rs", name.trim_start_matches("r#")),
+                    content: code.text,
+                });
+            }
+        }
+    }
+
+    if let Some(times) = &generator.times {
+        match options.layout {
+            ModuleLayout::SingleFile => {
+                root.line("");
+                root.open(&format!("{vis} mod {times} {{"));
+                for line in TIME_VALUES.lines() {
+                    root.line(line);
+                }
+                root.close("}");
+            }
+            ModuleLayout::FilePerModule => {
+                root.line(&format!("{vis} mod {times};"));
+                files.push(GeneratedFile {
+                    file: format!("{times}.rs"),
+                    content: TIME_VALUES.into(),
+                });
+            }
+        }
+    }
+
+    if let Some(serde) = &generator.serde {
+        let mut content = SERDE_VALUES.to_string();
+        if options.integers == IntegerType::BigInt {
+            content = format!("{content}\n{SERDE_BIGINT}");
+        }
+        if let Some(times) = &generator.times {
+            let impls = SERDE_TIMES.replace("super::time_values", &format!("super::{times}"));
+            content = format!("{content}\n{impls}");
+        }
+        match options.layout {
+            ModuleLayout::SingleFile => {
+                root.line("");
+                root.open(&format!("mod {serde} {{"));
+                for line in content.lines() {
+                    root.line(line);
+                }
+                root.close("}");
+            }
+            ModuleLayout::FilePerModule => {
+                root.line(&format!("mod {serde};"));
+                files.push(GeneratedFile {
+                    file: format!("{serde}.rs"),
+                    content,
+                });
+            }
+        }
+    }
+
+    let file = match options.layout {
+        ModuleLayout::SingleFile => "asn1.rs",
+        ModuleLayout::FilePerModule => "mod.rs",
+    };
+    files.insert(
+        0,
+        GeneratedFile {
+            file: file.into(),
+            content: root.text,
+        },
+    );
+    Ok(files)
+}
+
+/// A rust type used by a generated type
+#[derive(Debug, Clone)]
+enum RustType {
+    /// A type of the standard library, such as `i64` or `Vec<u8>`
+    Builtin(&'static str),
+    Vec(Box<RustType>),
+
+    /// A type of the time module, such as `GeneralizedTime`
+    Time(&'static str),
+
+    /// A generated type, with the type arguments of a generic type named by
+    /// the parameters they are given for.  Only the arguments of parameters
+    /// used by the type are written.
+    Defined(usize, Vec<(String, RustType)>),
+
+    /// A type parameter of the generic type it is used within
+    Parameter(String),
+}
+
+/// A type generated for a type assignment or a type nested within one
+struct Definition<'a> {
+    module: &'a str,
+
+    /// The definition of the assignment the type is defined by or nested
+    /// within, and the names of the components or alternatives it is nested
+    /// within
+    assignment: usize,
+    members: Vec<&'a str>,
+
+    /// The ASN.1 names of the module, assignment and members the type is
+    /// nested within, separated by dots, which are the keys of renames
+    path: String,
+
+    /// The rust name, given once every type is defined
+    name: String,
+    doc: Option<&'a str>,
+
+    /// The dummy references the type is defined within, and the parameters
+    /// of those that are used by the type
+    dummies: Vec<String>,
+    parameters: Vec<String>,
+    kind: Kind<'a>,
+}
+
+enum Kind<'a> {
+    Struct(Vec<Member<'a>>),
+    Choice(Vec<Member<'a>>),
+    Enumerated(Vec<Member<'a>>),
+    Alias(RustType),
+
+    /// A tuple struct wrapping a type that would refer to itself as an alias
+    Newtype(RustType),
+}
+
+/// A field of a struct, or a variant of a choice or enumerated type
+struct Member<'a> {
+    asn1: &'a str,
+    name: String,
+    doc: Option<&'a str>,
+
+    /// The type of the value, or none for a unit variant
+    ty: Option<RustType>,
+    optional: bool,
+
+    /// Is the value boxed, as it would otherwise contain itself
+    boxed: bool,
+
+    /// The number of an enumeration item
+    number: Option<i128>,
+}
+
+/// A constant generated for an `INTEGER` or `REAL` value assignment
+struct Constant<'a> {
+    module: &'a str,
+    asn1: &'a str,
+
+    /// The rust name, given once every type is named
+    name: String,
+    doc: Option<&'a str>,
+    value: ConstantValue<'a>,
+}
+
+enum ConstantValue<'a> {
+    Integer(&'a BigInt),
+    Real(&'a Real),
+}
+
+/// Names used within a scope, which names are added to with a numbered
+/// suffix if they are already used
+#[derive(Default)]
+struct Scope(HashSet<String>);
+
+impl Scope {
+    fn claim(&mut self, name: String, case: NameCase) -> String {
+        let separator = match case {
+            NameCase::Snake | NameCase::ScreamingSnake => "_",
+            NameCase::Pascal | NameCase::Camel => "",
+        };
+        let mut candidate = name.clone();
+        let mut n = 1;
+        while !self.0.insert(candidate.clone()) {
+            n += 1;
+            candidate = format!("{name}{separator}{n}");
+        }
+        candidate
+    }
+}
+
+/// Builds and writes the types of every module
+struct Generator<'a> {
+    analysis: &'a AnalysisContext<'a>,
+    options: &'a CodegenOptions,
+    definitions: Vec<Definition<'a>>,
+
+    /// The definition of each assignment, by module and assignment name
+    index: HashMap<(&'a str, &'a str), usize>,
+
+    /// The types of instances of generic types, by module and instance name
+    instances: HashMap<(&'a str, &'a str), RustType>,
+
+    /// The instance of a parameterized type whose definition is being
+    /// built, whose dummy references in constraints are its actual
+    /// parameters
+    instance: Option<&'a str>,
+
+    /// The rust names of modules, by ASN.1 name
+    modules: HashMap<&'a str, String>,
+
+    /// The name of the serde module, if serde is enabled
+    serde: Option<String>,
+
+    /// The name of the time module, if times have types of their own
+    times: Option<String>,
+
+    /// The constants of every module, ordered by module and name
+    constants: Vec<Constant<'a>>,
+}
+
+impl<'a> Generator<'a> {
+    /// Define a constant for every `INTEGER` and `REAL` value assignment
+    fn constants(&mut self, modules: &[&'a Environment]) -> Result {
+        for &module in modules {
+            let integers = module
+                .integers
+                .iter()
+                .map(|(name, value)| (name, ConstantValue::Integer(value)));
+            let reals = module
+                .reals
+                .iter()
+                .map(|(name, value)| (name, ConstantValue::Real(value)));
+            let mut values: Vec<_> = integers.chain(reals).collect();
+            values.sort_by_key(|(name, _)| *name);
+
+            for (name, value) in values {
+                if let ConstantValue::Integer(value) = value {
+                    if self.options.integers == IntegerType::I64 && i128::try_from(value).is_err() {
+                        return Err(CodegenError::Unsupported(
+                            "integer values beyond 128 bits without big integers",
+                        ));
+                    }
+                }
+                let doc = module.comments.get(&module.variables[name].id);
+                self.constants.push(Constant {
+                    module: &module.name,
+                    asn1: name,
+                    name: String::new(),
+                    doc: doc.map(String::as_str),
+                    value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Define the type of every type assignment and the types nested within
+    /// them.  Every assignment is indexed first, so that references to
+    /// assignments are known wherever they are.
+    fn build(&mut self, modules: &[&'a Environment]) -> Result {
+        let generic = self.options.parameterized == ParameterizedTypes::Generic;
+        let mut assignments = vec![];
+        for &module in modules {
+            let mut expanded: Vec<_> = module.expanded.iter().collect();
+            expanded.sort_by_key(|(name, _)| *name);
+            for (name, var) in expanded {
+                let TypeOrValue::Type(ty) = &*var.value else {
+                    continue;
+                };
+                if matches!(ty, Type::Class(_)) || !var.parameters.is_empty() {
+                    continue;
+                }
+
+                // instances of generic types are used through the generic type
+                if let Some(instance) = module.instances.get(name) {
+                    let definition = self
+                        .analysis
+                        .module_named(&instance.definition.module)
+                        .and_then(|module| module.variables.get(&instance.definition.name));
+                    if generic && definition.is_some_and(|var| is_generic(&var.parameters)) {
+                        continue;
+                    }
+                }
+                let doc = module.comments.get(&var.id).map(String::as_str);
+                assignments.push((module, name.as_str(), ty, var.value.id, doc, vec![]));
+            }
+
+            if generic {
+                let mut variables: Vec<_> = module.variables.iter().collect();
+                variables.sort_by_key(|(name, _)| *name);
+                for (name, var) in variables {
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        continue;
+                    };
+                    if var.parameters.is_empty() || !is_generic(&var.parameters) {
+                        continue;
+                    }
+                    let dummies = var.parameters.iter().map(|p| p.name.value.clone());
+                    let doc = module.comments.get(&var.id).map(String::as_str);
+                    let dummies = dummies.collect();
+                    assignments.push((module, name.as_str(), ty, var.value.id, doc, dummies));
+                }
+            }
+        }
+
+        for (module, name, _, _, doc, dummies) in &assignments {
+            let index = self.definitions.len();
+            self.index.insert((module.name.as_str(), name), index);
+            self.definitions.push(Definition {
+                module: &module.name,
+                assignment: index,
+                members: vec![],
+                path: format!("{}.{name}", module.name),
+                name: String::new(),
+                doc: *doc,
+                dummies: dummies.clone(),
+                parameters: vec![],
+                kind: Kind::Alias(RustType::Builtin("()")),
+            });
+        }
+        for (index, (module, name, ty, id, _, dummies)) in assignments.into_iter().enumerate() {
+            self.instance = module.instances.contains_key(name).then_some(name);
+            self.definitions[index].kind = self.kind(module, index, ty, id, &dummies)?;
+        }
+        self.instance = None;
+        Ok(())
+    }
+
+    /// Get the kind of type defining a type assignment or nested type
+    fn kind(
+        &mut self,
+        module: &'a Environment,
+        definition: usize,
+        ty: &'a Type,
+        id: AsnNodeId,
+        dummies: &[String],
+    ) -> Result<Kind<'a>> {
+        Ok(match ty {
+            Type::Tagged(tagged) => {
+                self.kind(module, definition, &tagged.ty, tagged.ty.id, dummies)?
+            }
+            Type::Sequence(components) | Type::Set(components) => {
+                Kind::Struct(self.components(module, definition, components, dummies)?)
+            }
+            Type::Choice(alternatives) => {
+                Kind::Choice(self.alternatives(module, definition, alternatives, dummies)?)
+            }
+            Type::Enumerated(items) => Kind::Enumerated(self.items(module, items)),
+            ty => Kind::Alias(self.ty(module, definition, &[], ty, id, dummies)?),
+        })
+    }
+
+    fn components(
+        &mut self,
+        module: &'a Environment,
+        definition: usize,
+        components: &'a Extensible<ComponentType>,
+        dummies: &[String],
+    ) -> Result<Vec<Member<'a>>> {
+        let named = |component: &'a ComponentType| match component {
+            ComponentType::Named(component) => Some(component),
+            ComponentType::ComponentsOf(_) => None,
+        };
+        let additions: HashSet<_> = components
+            .extension
+            .iter()
+            .flat_map(|ext| &ext.additions)
+            .flat_map(ExtensionAddition::items)
+            .filter_map(named)
+            .map(|component| component.name.id)
+            .collect();
+
+        let mut fields = vec![];
+        for component in components.items().filter_map(named) {
+            let ty = self.ty(
+                module,
+                definition,
+                &[&component.name],
+                &component.ty,
+                component.ty.id,
+                dummies,
+            )?;
+            fields.push(Member {
+                asn1: &component.name,
+                name: String::new(),
+                doc: module.comments.get(&component.name.id).map(String::as_str),
+                ty: Some(ty),
+                optional: component.optional
+                    || component.default.is_some()
+                    || additions.contains(&component.name.id),
+                boxed: false,
+                number: None,
+            });
+        }
+        Ok(fields)
+    }
+
+    fn alternatives(
+        &mut self,
+        module: &'a Environment,
+        definition: usize,
+        alternatives: &'a Extensible<NamedType>,
+        dummies: &[String],
+    ) -> Result<Vec<Member<'a>>> {
+        let mut variants = vec![];
+        for alternative in alternatives.items() {
+            // alternatives without a value are unit variants
+            let ty = match untagged(&alternative.ty) {
+                Type::Null => None,
+                _ => Some(self.ty(
+                    module,
+                    definition,
+                    &[&alternative.name],
+                    &alternative.ty,
+                    alternative.ty.id,
+                    dummies,
+                )?),
+            };
+            variants.push(Member {
+                asn1: &alternative.name,
+                name: String::new(),
+                doc: module
+                    .comments
+                    .get(&alternative.name.id)
+                    .map(String::as_str),
+                ty,
+                optional: false,
+                boxed: false,
+                number: None,
+            });
+        }
+        Ok(variants)
+    }
+
+    fn items(&self, module: &'a Environment, items: &'a Extensible<EnumItem>) -> Vec<Member<'a>> {
+        let numbers: HashMap<_, _> = enum_numbers(items)
+            .into_iter()
+            .map(|(name, number, _)| (name.id, number))
+            .collect();
+        items
+            .items()
+            .map(|item| Member {
+                asn1: &item.name,
+                name: String::new(),
+                doc: module.comments.get(&item.name.id).map(String::as_str),
+                ty: None,
+                optional: false,
+                boxed: false,
+                number: numbers
+                    .get(&item.name.id)
+                    .and_then(|n| i128::try_from(n).ok()),
+            })
+            .collect()
+    }
+
+    /// Get the rust type of a type used within a definition, defining a
+    /// nested type named after the members it is within if it is not a
+    /// reference
+    fn ty(
+        &mut self,
+        module: &'a Environment,
+        definition: usize,
+        members: &[&'a str],
+        ty: &'a Type,
+        id: AsnNodeId,
+        dummies: &[String],
+    ) -> Result<RustType> {
+        Ok(match ty {
+            Type::Tagged(tagged) => self.ty(
+                module,
+                definition,
+                members,
+                &tagged.ty,
+                tagged.ty.id,
+                dummies,
+            )?,
+            Type::Sequence(_) | Type::Set(_) | Type::Choice(_) | Type::Enumerated(_) => {
+                let parent = &self.definitions[definition];
+                let assignment = parent.assignment;
+                let mut nested = parent.members.clone();
+                nested.extend(members);
+                let path = format!("{}.{}", parent.path, members.join("."));
+
+                let index = self.definitions.len();
+                self.definitions.push(Definition {
+                    module: &module.name,
+                    assignment,
+                    members: nested,
+                    path,
+                    name: String::new(),
+                    doc: None,
+                    dummies: dummies.to_vec(),
+                    parameters: vec![],
+                    kind: Kind::Alias(RustType::Builtin("()")),
+                });
+                self.definitions[index].kind = self.kind(module, index, ty, id, dummies)?;
+
+                let arguments = dummies
+                    .iter()
+                    .map(|dummy| (dummy.clone(), RustType::Parameter(dummy.clone())))
+                    .collect();
+                RustType::Defined(index, arguments)
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                let item = of.name.as_ref().map_or("item", |name| name.value.as_str());
+                let mut nested = members.to_vec();
+                nested.push(item);
+                let item = self.ty(module, definition, &nested, &of.ty, of.ty.id, dummies)?;
+                RustType::Vec(Box::new(item))
+            }
+            Type::Reference(reference) => {
+                if reference.module.is_none() && dummies.contains(&reference.name.value) {
+                    return Ok(RustType::Parameter(reference.name.value.clone()));
+                }
+
+                let (referenced, var) = self
+                    .analysis
+                    .resolve_expanded(module, reference)
+                    .ok_or(CodegenError::AnalysisErrors)?;
+                let key = (referenced.name.as_str(), reference.name.value.as_str());
+                if let Some(&index) = self.index.get(&key) {
+                    return Ok(RustType::Defined(index, vec![]));
+                }
+                if let Some(ty) = self.instances.get(&key) {
+                    return Ok(ty.clone());
+                }
+
+                // an instance of a generic type that is not generated
+                if let Some(instance) = referenced.instances.get(&reference.name.value) {
+                    let generic = &instance.definition;
+                    let parameterized = self
+                        .analysis
+                        .module_named(&generic.module)
+                        .and_then(|module| module.variables.get(&generic.name));
+                    let generic = (generic.module.as_str(), generic.name.as_str());
+                    if let (Some(&index), Some(var)) = (self.index.get(&generic), parameterized) {
+                        let mut arguments = vec![];
+                        for (dummy, parameter) in var.parameters.iter().zip(&instance.parameters) {
+                            let TypeOrValue::Type(ty) = &parameter.value else {
+                                return Err(CodegenError::AnalysisErrors);
+                            };
+                            // nested types are named after the type using the instance
+                            let mut nested = members.to_vec();
+                            nested.push(&dummy.name);
+                            let ty =
+                                self.ty(referenced, definition, &nested, ty, parameter.id, &[])?;
+                            arguments.push((dummy.name.value.clone(), ty));
+                        }
+                        let ty = RustType::Defined(index, arguments);
+                        self.instances.insert(key, ty.clone());
+                        return Ok(ty);
+                    }
+                }
+
+                match &*var.value {
+                    TypeOrValue::Type(Type::Class(_)) => RustType::Builtin("Vec<u8>"),
+                    TypeOrValue::Type(ty) => {
+                        self.ty(referenced, definition, members, ty, var.value.id, &[])?
+                    }
+                    TypeOrValue::Value(_) => return Err(CodegenError::AnalysisErrors),
+                }
+            }
+            Type::Parameterized(parameterized) => {
+                self.parameterized(module, definition, members, parameterized, dummies)?
+            }
+            Type::Integer(_) => RustType::Builtin(self.integer(module, ty, id)),
+            ty => match time_type(ty) {
+                Some(time) if self.options.time_types => RustType::Time(time),
+                _ => RustType::Builtin(builtin(ty)),
+            },
+        })
+    }
+
+    /// Get the rust type of an `INTEGER` type, the first of `i64` and `u64`
+    /// that holds every value permitted by its constraints, or the type
+    /// chosen by the options if neither does
+    fn integer(&self, module: &'a Environment, ty: &'a Type, id: AsnNodeId) -> &'static str {
+        let constraints = match self.instance {
+            Some(instance) => self
+                .analysis
+                .constraints_within(module, instance, ty, id, true),
+            None => self.analysis.constraints_of(module, ty, id, true),
+        };
+        if let Some(values) = constraints.value.filter(|values| !values.extensible) {
+            let within = |min: BigInt, max: BigInt| {
+                values.lower().is_some_and(|lower| *lower >= min)
+                    && values.upper().is_some_and(|upper| *upper <= max)
+            };
+            if within(i64::MIN.into(), i64::MAX.into()) {
+                return "i64";
+            }
+            if within(0.into(), u64::MAX.into()) {
+                return "u64";
+            }
+        }
+
+        match self.options.integers {
+            IntegerType::I64 => "i64",
+            IntegerType::BigInt => "num_bigint::BigInt",
+        }
+    }
+
+    /// Get the type of an instance of a parameterized type within the body
+    /// of a generic type, which is only generated as a generic type
+    fn parameterized(
+        &mut self,
+        module: &'a Environment,
+        definition: usize,
+        members: &[&'a str],
+        parameterized: &'a ParameterizedReference,
+        dummies: &[String],
+    ) -> Result<RustType> {
+        let unsupported = CodegenError::Unsupported(
+            "instances of parameterized types with value parameters within generic types",
+        );
+        let (referenced, _) = self
+            .analysis
+            .resolve(module, &parameterized.reference)
+            .ok_or(CodegenError::AnalysisErrors)?;
+        let key = (
+            referenced.name.as_str(),
+            parameterized.reference.name.value.as_str(),
+        );
+        let index = *self.index.get(&key).ok_or(unsupported)?;
+
+        let generic = self.definitions[index].dummies.clone();
+        let mut arguments = vec![];
+        for (dummy, parameter) in generic.into_iter().zip(&parameterized.parameters) {
+            let TypeOrValue::Type(ty) = &parameter.value else {
+                return Err(CodegenError::AnalysisErrors);
+            };
+            let ty = self.ty(module, definition, members, ty, parameter.id, dummies)?;
+            arguments.push((dummy, ty));
+        }
+        Ok(RustType::Defined(index, arguments))
+    }
+
+    /// Find the parameters used by each generic type and the types nested
+    /// within them, as generic types cannot have unused parameters
+    fn parameters(&mut self) {
+        loop {
+            let mut changed = false;
+            for i in 0..self.definitions.len() {
+                let definition = &self.definitions[i];
+                if definition.dummies.is_empty() {
+                    continue;
+                }
+
+                let mut used = HashSet::new();
+                for ty in definition.kind.types() {
+                    self.used_parameters(ty, &mut used);
+                }
+                let parameters: Vec<_> = definition
+                    .dummies
+                    .iter()
+                    .filter(|dummy| used.contains(dummy.as_str()))
+                    .cloned()
+                    .collect();
+                if parameters != definition.parameters {
+                    self.definitions[i].parameters = parameters;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn used_parameters<'t>(&'t self, ty: &'t RustType, used: &mut HashSet<&'t str>) {
+        match ty {
+            RustType::Builtin(_) | RustType::Time(_) => (),
+            RustType::Vec(item) => self.used_parameters(item, used),
+            RustType::Defined(index, arguments) => {
+                let parameters = &self.definitions[*index].parameters;
+                for (parameter, argument) in arguments {
+                    if parameters.contains(parameter) {
+                        self.used_parameters(argument, used);
+                    }
+                }
+            }
+            RustType::Parameter(parameter) => {
+                used.insert(parameter);
+            }
+        }
+    }
+
+    /// Make aliases that refer to themselves through other aliases tuple
+    /// structs
+    fn newtypes(&mut self) {
+        let mut successors = vec![vec![]; self.definitions.len()];
+        for (i, definition) in self.definitions.iter().enumerate() {
+            if let Kind::Alias(ty) = &definition.kind {
+                let mut defined = vec![];
+                self.defined(ty, false, &mut defined);
+                successors[i] = defined
+                    .into_iter()
+                    .filter(|&j| matches!(self.definitions[j].kind, Kind::Alias(_)))
+                    .collect();
+            }
+        }
+
+        for i in cyclic(&successors) {
+            let definition = &mut self.definitions[i];
+            if let Kind::Alias(ty) = &definition.kind {
+                definition.kind = Kind::Newtype(ty.clone());
+            }
+        }
+    }
+
+    /// Box the members of types that contain a value of a type that contains
+    /// the type itself
+    fn boxes(&mut self) {
+        let mut successors = vec![vec![]; self.definitions.len()];
+        for (i, definition) in self.definitions.iter().enumerate() {
+            for ty in definition.kind.types() {
+                self.defined(ty, true, &mut successors[i]);
+            }
+        }
+
+        let components = strongly_connected(&successors);
+        let mut component = vec![0; self.definitions.len()];
+        for (c, members) in components.iter().enumerate() {
+            for &member in members {
+                component[member] = c;
+            }
+        }
+
+        for i in 0..self.definitions.len() {
+            let mut boxed = vec![];
+            if let Kind::Struct(members) | Kind::Choice(members) = &self.definitions[i].kind {
+                for member in members {
+                    let mut defined = vec![];
+                    if let Some(ty) = &member.ty {
+                        self.defined(ty, true, &mut defined);
+                    }
+                    boxed.push(defined.iter().any(|&j| component[j] == component[i]));
+                }
+            }
+            if let Kind::Struct(members) | Kind::Choice(members) = &mut self.definitions[i].kind {
+                for (member, boxed) in members.iter_mut().zip(boxed) {
+                    member.boxed = boxed;
+                }
+            }
+        }
+    }
+
+    /// Find the generated types used by a type, only finding those contained
+    /// by value if requested.  The arguments of generic types are taken to
+    /// be contained by value.
+    fn defined(&self, ty: &RustType, by_value: bool, defined: &mut Vec<usize>) {
+        match ty {
+            RustType::Builtin(_) | RustType::Time(_) | RustType::Parameter(_) => (),
+            RustType::Vec(item) => {
+                if !by_value {
+                    self.defined(item, by_value, defined);
+                }
+            }
+            RustType::Defined(index, arguments) => {
+                defined.push(*index);
+                for (_, argument) in arguments {
+                    self.defined(argument, by_value, defined);
+                }
+            }
+        }
+    }
+
+    /// Name every module, type and member, resolving names that are the same
+    /// after case conversion
+    fn names(&mut self, modules: &[&'a Environment]) {
+        let naming = &self.options.naming;
+        let mut scope = Scope::default();
+        if self.options.serde {
+            self.serde = Some(scope.claim("serde_values".into(), naming.modules));
+        }
+        if self.options.time_types {
+            self.times = Some(scope.claim("time_values".into(), naming.modules));
+        }
+        for module in modules {
+            let name = self.name(&module.name, &module.name, naming.modules, &mut scope);
+            self.modules.insert(&module.name, name);
+        }
+
+        let mut scopes: HashMap<&str, Scope> = HashMap::new();
+        for i in 0..self.definitions.len() {
+            let definition = &self.definitions[i];
+            let scope = scopes
+                .entry(definition.module)
+                .or_insert_with(|| Scope(PRELUDE.iter().map(|name| name.to_string()).collect()));
+
+            // nested types are named after their assignment and members
+            let name = match definition.members.is_empty() {
+                true => {
+                    let assignment = definition.path.split_once('.').map_or("", |(_, a)| a);
+                    self.name(&definition.path, assignment, naming.types, scope)
+                }
+                false => {
+                    let assignment = &self.definitions[definition.assignment].name;
+                    let name = format!("{assignment} {}", definition.members.join(" "));
+                    escape(scope.claim(convert(&name, naming.types), naming.types))
+                }
+            };
+
+            let path = definition.path.clone();
+            let case = match &definition.kind {
+                Kind::Struct(_) => naming.fields,
+                _ => naming.variants,
+            };
+            let mut members = vec![];
+            if let Kind::Struct(fields) | Kind::Choice(fields) | Kind::Enumerated(fields) =
+                &definition.kind
+            {
+                let mut scope = Scope::default();
+                for field in fields {
+                    let key = format!("{path}.{}", field.asn1);
+                    members.push(self.name(&key, field.asn1, case, &mut scope));
+                }
+            }
+
+            let definition = &mut self.definitions[i];
+            definition.name = name;
+            if let Kind::Struct(fields) | Kind::Choice(fields) | Kind::Enumerated(fields) =
+                &mut definition.kind
+            {
+                for (field, name) in fields.iter_mut().zip(members) {
+                    field.name = name;
+                }
+            }
+        }
+
+        // constants share the names of tuple structs, so are named after types
+        for i in 0..self.constants.len() {
+            let constant = &self.constants[i];
+            let scope = scopes
+                .entry(constant.module)
+                .or_insert_with(|| Scope(PRELUDE.iter().map(|name| name.to_string()).collect()));
+            let key = format!("{}.{}", constant.module, constant.asn1);
+            let name = self.name(&key, constant.asn1, naming.constants, scope);
+            self.constants[i].name = name;
+        }
+    }
+
+    /// Get the name of a module, type or member, which is either renamed or
+    /// its ASN.1 name converted to a case
+    fn name(&self, key: &str, asn1: &str, case: NameCase, scope: &mut Scope) -> String {
+        let name = match self.options.renames.get(key) {
+            Some(name) => name.clone(),
+            None => convert(asn1, case),
+        };
+        escape(scope.claim(name, case))
+    }
+
+    /// Write the constants and types of a module
+    fn module(&self, code: &mut Code, module: &str) {
+        let constants: Vec<_> = self
+            .constants
+            .iter()
+            .filter(|constant| constant.module == module)
+            .collect();
+        for constant in &constants {
+            self.constant(code, constant);
+        }
+
+        let mut definitions: Vec<_> = (0..self.definitions.len())
+            .filter(|&i| self.definitions[i].module == module)
+            .collect();
+        definitions.sort_by_key(|&i| (self.definitions[i].assignment, i));
+
+        for (n, &i) in definitions.iter().enumerate() {
+            if n > 0 || !constants.is_empty() {
+                code.line("");
+            }
+            self.definition(code, i);
+        }
+    }
+
+    /// Write a constant, as a static if its type cannot be constructed in a
+    /// constant
+    fn constant(&self, code: &mut Code, constant: &Constant) {
+        let vis = self.options.visibility.keyword();
+        let name = &constant.name;
+        comment(code, constant.doc);
+        match constant.value {
+            ConstantValue::Integer(value) if i64::try_from(value).is_ok() => {
+                code.line(&format!("{vis} const {name}: i64 = {value};"))
+            }
+            ConstantValue::Integer(value) if u64::try_from(value).is_ok() => {
+                code.line(&format!("{vis} const {name}: u64 = {value};"))
+            }
+            ConstantValue::Integer(value) if self.options.integers == IntegerType::I64 => {
+                code.line(&format!("{vis} const {name}: i128 = {value};"))
+            }
+            ConstantValue::Integer(value) => {
+                code.open(&format!(
+                    "{vis} static {name}: std::sync::LazyLock<num_bigint::BigInt> ="
+                ));
+                code.line(&format!(
+                    "std::sync::LazyLock::new(|| \"{value}\".parse().unwrap());"
+                ));
+                code.dedent();
+            }
+            ConstantValue::Real(value) => {
+                let value = match value.to_f64() {
+                    value if value.is_nan() => "f64::NAN".to_string(),
+                    f64::INFINITY => "f64::INFINITY".to_string(),
+                    f64::NEG_INFINITY => "f64::NEG_INFINITY".to_string(),
+                    value => format!("{value:?}"),
+                };
+                code.line(&format!("{vis} const {name}: f64 = {value};"));
+            }
+        }
+    }
+
+    fn definition(&self, code: &mut Code, index: usize) {
+        let definition = &self.definitions[index];
+        let vis = self.options.visibility.keyword();
+        let name = self.generic_name(definition);
+        comment(code, definition.doc);
+
+        match &definition.kind {
+            Kind::Struct(fields) => {
+                code.line(&self.derives(index, false));
+                code.open(&format!("{vis} struct {name} {{"));
+                for field in fields {
+                    comment(code, field.doc);
+                    self.serde_attribute(code, field);
+                    let ty = self.member_type(definition, field);
+                    code.line(&format!("{vis} {}: {ty},", field.name));
+                }
+                code.close("}");
+            }
+            Kind::Choice(variants) => {
+                code.line(&self.derives(index, false));
+                code.open(&format!("{vis} enum {name} {{"));
+                for variant in variants {
+                    comment(code, variant.doc);
+                    self.serde_attribute(code, variant);
+                    match variant.ty {
+                        Some(_) => {
+                            let ty = self.member_type(definition, variant);
+                            code.line(&format!("{}({ty}),", variant.name));
+                        }
+                        // externally tagged with a value, as other alternatives are
+                        None if self.serde.is_some() => {
+                            code.line(&format!("{}(()),", variant.name))
+                        }
+                        None => code.line(&format!("{},", variant.name)),
+                    }
+                }
+                code.close("}");
+            }
+            Kind::Enumerated(items) => {
+                // discriminants are only written if they fit every target
+                let numbered = items
+                    .iter()
+                    .all(|item| item.number.is_some_and(|n| i32::try_from(n).is_ok()));
+                code.line(&self.derives(index, true));
+                code.open(&format!("{vis} enum {name} {{"));
+                for item in items {
+                    comment(code, item.doc);
+                    self.serde_attribute(code, item);
+                    match item.number {
+                        Some(number) if numbered => {
+                            code.line(&format!("{} = {number},", item.name))
+                        }
+                        _ => code.line(&format!("{},", item.name)),
+                    }
+                }
+                code.close("}");
+            }
+            Kind::Alias(ty) => {
+                let ty = self.rust_type(definition.module, ty);
+                code.line(&format!("{vis} type {name} = {ty};"));
+            }
+            Kind::Newtype(ty) => {
+                let ty = self.rust_type(definition.module, ty);
+                code.line(&self.derives(index, false));
+                if self.serde.is_some() {
+                    code.line("#[serde(transparent)]");
+                }
+                code.line(&format!("{vis} struct {name}({vis} {ty});"));
+            }
+        }
+    }
+
+    /// Get the name of a type with its type parameters
+    fn generic_name(&self, definition: &Definition) -> String {
+        if definition.parameters.is_empty() {
+            return definition.name.clone();
+        }
+        let parameters: Vec<_> = definition
+            .parameters
+            .iter()
+            .map(|p| self.parameter_name(p))
+            .collect();
+        format!("{}<{}>", definition.name, parameters.join(", "))
+    }
+
+    fn parameter_name(&self, dummy: &str) -> String {
+        escape(convert(dummy, self.options.naming.types))
+    }
+
+    /// Get the `derive` attribute of a type, leaving out the traits that
+    /// `f64` does not implement from types containing it
+    fn derives(&self, index: usize, enumerated: bool) -> String {
+        let mut derives = match enumerated {
+            true => vec!["Debug", "Clone", "Copy", "PartialEq", "Eq", "Hash"],
+            false => vec!["Debug", "Clone", "PartialEq"],
+        };
+        if self.serde.is_some() {
+            derives.extend(["serde::Serialize", "serde::Deserialize"]);
+        }
+        let float = !enumerated && self.contains_float(index, &mut HashSet::new());
+        for derive in &self.options.derives {
+            let name = trait_name(derive);
+            if derives.iter().any(|&derived| trait_name(derived) == name)
+                || (float && FLOAT_UNSUPPORTED.contains(&name))
+            {
+                continue;
+            }
+            derives.push(derive);
+        }
+        format!("#[derive({})]", derives.join(", "))
+    }
+
+    /// Write the serde attribute of a field or variant, naming it by its
+    /// ASN.1 identifier and giving its value the representation of the serde
+    /// module if it has one
+    fn serde_attribute(&self, code: &mut Code, member: &Member) {
+        let Some(serde) = &self.serde else {
+            return;
+        };
+        let mut attributes = vec![];
+        if member.name.trim_start_matches("r#") != member.asn1 {
+            attributes.push(format!("rename = \"{}\"", member.asn1));
+        }
+        // absent components are left out by self-describing formats
+        if member.optional {
+            attributes.push("default".to_string());
+        }
+        if member
+            .ty
+            .as_ref()
+            .is_some_and(|ty| self.serde_represented(ty, &[]))
+        {
+            attributes.push(format!("with = \"super::{serde}\""));
+        }
+        if !attributes.is_empty() {
+            code.line(&format!("#[serde({})]", attributes.join(", ")));
+        }
+    }
+
+    /// Does a type have values with the representations of the serde module,
+    /// looking through aliases.  The type parameters of an alias are given
+    /// by its arguments.
+    fn serde_represented(&self, ty: &RustType, arguments: &[(String, RustType)]) -> bool {
+        match ty {
+            RustType::Builtin(builtin) => SERDE_REPRESENTED.contains(builtin),
+            RustType::Time(_) => true,
+            RustType::Vec(item) => self.serde_represented(item, arguments),
+            RustType::Defined(index, arguments) => match &self.definitions[*index].kind {
+                Kind::Alias(ty) => self.serde_represented(ty, arguments),
+                _ => false,
+            },
+            RustType::Parameter(dummy) => arguments
+                .iter()
+                .find(|(parameter, _)| parameter == dummy)
+                .is_some_and(|(_, argument)| self.serde_represented(argument, &[])),
+        }
+    }
+
+    /// Does a generated type contain an `f64`
+    fn contains_float(&self, index: usize, visited: &mut HashSet<usize>) -> bool {
+        if !visited.insert(index) {
+            return false;
+        }
+        let types: Vec<_> = self.definitions[index].kind.types().collect();
+        types
+            .into_iter()
+            .any(|ty| self.type_contains_float(ty, visited))
+    }
+
+    fn type_contains_float(&self, ty: &RustType, visited: &mut HashSet<usize>) -> bool {
+        match ty {
+            RustType::Builtin(builtin) => *builtin == "f64",
+            RustType::Time(_) => false,
+            RustType::Vec(item) => self.type_contains_float(item, visited),
+            RustType::Defined(index, arguments) => {
+                self.contains_float(*index, visited)
+                    || arguments
+                        .iter()
+                        .any(|(_, argument)| self.type_contains_float(argument, visited))
+            }
+            RustType::Parameter(_) => false,
+        }
+    }
+
+    /// Get the type of a field or variant, boxed or optional as required
+    fn member_type(&self, definition: &Definition, member: &Member) -> String {
+        let ty = member.ty.as_ref().expect("only unit variants have no type");
+        let mut ty = self.rust_type(definition.module, ty);
+        if member.boxed {
+            ty = format!("Box<{ty}>");
+        }
+        if member.optional {
+            ty = format!("Option<{ty}>");
+        }
+        ty
+    }
+
+    /// Write a type used within a module
+    fn rust_type(&self, module: &str, ty: &RustType) -> String {
+        match ty {
+            RustType::Builtin(builtin) => builtin.to_string(),
+            RustType::Time(time) => {
+                let times = self.times.as_deref().expect("times have types");
+                format!("super::{times}::{time}")
+            }
+            RustType::Vec(item) => format!("Vec<{}>", self.rust_type(module, item)),
+            RustType::Defined(index, arguments) => {
+                let definition = &self.definitions[*index];
+                let mut name = match definition.module == module {
+                    true => definition.name.clone(),
+                    false => format!(
+                        "super::{}::{}",
+                        self.modules[definition.module], definition.name
+                    ),
+                };
+                let arguments: Vec<_> = definition
+                    .parameters
+                    .iter()
+                    .filter_map(|p| arguments.iter().find(|(dummy, _)| dummy == p))
+                    .map(|(_, argument)| self.rust_type(module, argument))
+                    .collect();
+                if !arguments.is_empty() {
+                    name = format!("{name}<{}>", arguments.join(", "));
+                }
+                name
+            }
+            RustType::Parameter(dummy) => self.parameter_name(dummy),
+        }
+    }
+}
+
+impl Kind<'_> {
+    /// Get the types used by a type
+    fn types(&self) -> impl Iterator<Item = &RustType> {
+        let (members, ty) = match self {
+            Kind::Struct(members) | Kind::Choice(members) | Kind::Enumerated(members) => {
+                (&members[..], None)
+            }
+            Kind::Alias(ty) | Kind::Newtype(ty) => (&[][..], Some(ty)),
+        };
+        members.iter().filter_map(|m| m.ty.as_ref()).chain(ty)
+    }
+}
+
+/// Can a parameterized type with the given dummy references be generated as
+/// a generic type, which requires every parameter to be a type
+fn is_generic(parameters: &[Parameter]) -> bool {
+    parameters.iter().all(|p| p.governor.is_none())
+}
+
+/// Get a type without its tags
+fn untagged(ty: &Type) -> &Type {
+    match ty {
+        Type::Tagged(tagged) => untagged(&tagged.ty),
+        ty => ty,
+    }
+}
+
+/// Get the rust type of a type that is not generated
+fn builtin(ty: &Type) -> &'static str {
+    match ty {
+        Type::Boolean => "bool",
+        Type::Integer(_) => unreachable!("integer types depend on their constraints"),
+        Type::Real => "f64",
+        Type::Null => "()",
+        Type::BitString(_) => "Vec<bool>",
+        Type::ObjectIdentifier | Type::RelativeOid => "Vec<u64>",
+
+        Type::OidIri
+        | Type::RelativeOidIri
+        | Type::ObjectDescriptor
+        | Type::GeneralizedTime
+        | Type::UTCTime
+        | Type::Time
+        | Type::Date
+        | Type::TimeOfDay
+        | Type::DateTime
+        | Type::Duration
+        | Type::BmpString
+        | Type::GeneralString
+        | Type::GraphicString
+        | Type::IA5String
+        | Type::ISO64String
+        | Type::NumericString
+        | Type::PrintableString
+        | Type::TeletexString
+        | Type::T61String
+        | Type::UniversalString
+        | Type::UTF8String
+        | Type::VideotexString
+        | Type::VisibleString => "String",
+
+        _ => "Vec<u8>",
+    }
+}
+
+/// Get the name of the type of the time module representing a time type
+fn time_type(ty: &Type) -> Option<&'static str> {
+    Some(match ty {
+        Type::UTCTime => "UtcTime",
+        Type::GeneralizedTime => "GeneralizedTime",
+        Type::Time => "Time",
+        Type::Date => "Date",
+        Type::TimeOfDay => "TimeOfDay",
+        Type::DateTime => "DateTime",
+        Type::Duration => "Duration",
+        _ => return None,
+    })
+}
+
+/// Get the name of a trait from its path
+fn trait_name(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Convert an ASN.1 name to a case, separating words at characters that
+/// cannot be part of an identifier, such as hyphens and the braces of the
+/// names of instances of parameterized types
+fn convert(name: &str, case: NameCase) -> String {
+    let case = match case {
+        NameCase::Snake => Case::Snake,
+        NameCase::ScreamingSnake => Case::ScreamingSnake,
+        NameCase::Pascal => Case::Pascal,
+        NameCase::Camel => Case::Camel,
+    };
+    name.replace(|c: char| !c.is_alphanumeric(), " ")
+        .to_case(case)
+}
+
+/// Make a name that is a keyword a raw identifier, or add a suffix to it if
+/// it cannot be a raw identifier
+fn escape(name: String) -> String {
+    if PATH_KEYWORDS.contains(&name.as_str()) {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else {
+        name
+    }
+}
+
+/// Find the nodes of a graph that are part of a cycle
+fn cyclic(successors: &[Vec<usize>]) -> Vec<usize> {
+    strongly_connected(successors)
+        .into_iter()
+        .filter(|members| members.len() > 1 || successors[members[0]].contains(&members[0]))
+        .flatten()
+        .collect()
+}
+
+/// Write a doc comment, if there is one
+fn comment(code: &mut Code, comment: Option<&str>) {
+    for line in comment.iter().flat_map(|comment| comment.lines()) {
+        code.line(format!("/// {line}").trim_end());
+    }
+}
@@ -0,0 +1,49 @@
+// `INTEGER` values of any size have the same representations as `i64` values
+// in human readable formats, and are two's complement octets in other formats.
+impl Value for num_bigint::BigInt {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_bytes(&self.to_signed_bytes_be());
+        }
+        match i64::try_from(self) {
+            Ok(value) => value.serialize_value(serializer),
+            Err(_) => serializer.collect_str(self),
+        }
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BigIntVisitor;
+
+        impl Visitor<'_> for BigIntVisitor {
+            type Value = num_bigint::BigInt;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an integer or a decimal string")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(value.into())
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(value.into())
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value
+                    .parse()
+                    .map_err(|_| E::custom(format!("invalid integer `{value}`")))
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                Ok(num_bigint::BigInt::from_signed_bytes_be(value))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BigIntVisitor)
+        } else {
+            deserializer.deserialize_bytes(BigIntVisitor)
+        }
+    }
+}
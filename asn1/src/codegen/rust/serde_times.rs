@@ -0,0 +1,17 @@
+// Time values are strings of their value notation in every format
+macro_rules! time_values {
+    ($($name:ident),*) => {$(
+        impl Value for super::time_values::$name {
+            fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+
+            fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                text.parse().map_err(de::Error::custom)
+            }
+        }
+    )*};
+}
+
+time_values!(UtcTime, GeneralizedTime, Time, Date, TimeOfDay, DateTime, Duration);
@@ -0,0 +1,365 @@
+//! Serde representations of the values whose derived representations would
+//! not round-trip through every format, used by the generated types with
+//! `#[serde(with = "...")]`.  Human readable formats get the representations
+//! of the JSON encoding rules, other formats get compact ones:
+//! - `INTEGER` values are numbers, or decimal strings if they cannot be
+//!   represented exactly by a double.
+//! - `REAL` values are numbers, or `"INF"`, `"-INF"`, `"NaN"` or `"-0"`.
+//! - `BIT STRING` values are objects with the bits as hexadecimal digits and
+//!   the number of bits, `{"value": "A8", "length": 5}`.
+//! - `OCTET STRING` and other octet values are hexadecimal digits.
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// The largest integer every integer up to which a double represents exactly
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+pub(super) fn serialize<T: Value, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value.serialize_value(serializer)
+}
+
+pub(super) fn deserialize<'de, T: Value, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    T::deserialize_value(deserializer)
+}
+
+/// A value with a representation of its own
+pub(super) trait Value: Sized {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Serializes a value with its own representation
+struct Wrap<'v, T>(&'v T);
+
+impl<T: Value> Serialize for Wrap<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_value(serializer)
+    }
+}
+
+/// Deserializes a value with its own representation
+struct Unwrap<T>(T);
+
+impl<'de, T: Value> Deserialize<'de> for Unwrap<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize_value(deserializer).map(Unwrap)
+    }
+}
+
+impl Value for i64 {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(self)
+        {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_i64(*self)
+        }
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IntegerVisitor;
+
+        impl Visitor<'_> for IntegerVisitor {
+            type Value = i64;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an integer or a decimal string")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+                Ok(value)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+                i64::try_from(value).map_err(|_| E::custom(format!("integer {value} is too large")))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+                value
+                    .parse()
+                    .map_err(|_| E::custom(format!("invalid integer `{value}`")))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(IntegerVisitor)
+        } else {
+            deserializer.deserialize_i64(IntegerVisitor)
+        }
+    }
+}
+
+impl Value for u64 {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && *self > MAX_SAFE_INTEGER as u64 {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_u64(*self)
+        }
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IntegerVisitor;
+
+        impl Visitor<'_> for IntegerVisitor {
+            type Value = u64;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a non-negative integer or a decimal string")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+                u64::try_from(value).map_err(|_| E::custom(format!("integer {value} is negative")))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+                Ok(value)
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+                value
+                    .parse()
+                    .map_err(|_| E::custom(format!("invalid integer `{value}`")))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(IntegerVisitor)
+        } else {
+            deserializer.deserialize_u64(IntegerVisitor)
+        }
+    }
+}
+
+impl Value for f64 {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_f64(*self);
+        }
+        match *self {
+            value if value.is_nan() => serializer.serialize_str("NaN"),
+            f64::INFINITY => serializer.serialize_str("INF"),
+            f64::NEG_INFINITY => serializer.serialize_str("-INF"),
+            value if value == 0.0 && value.is_sign_negative() => serializer.serialize_str("-0"),
+            value => serializer.serialize_f64(value),
+        }
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RealVisitor;
+
+        impl Visitor<'_> for RealVisitor {
+            type Value = f64;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number, \"INF\", \"-INF\", \"NaN\" or \"-0\"")
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<f64, E> {
+                Ok(value)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<f64, E> {
+                Ok(value as f64)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<f64, E> {
+                Ok(value as f64)
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<f64, E> {
+                match value {
+                    "INF" => Ok(f64::INFINITY),
+                    "-INF" => Ok(f64::NEG_INFINITY),
+                    "NaN" => Ok(f64::NAN),
+                    "-0" => Ok(-0.0),
+                    _ => Err(E::custom(format!("invalid real `{value}`"))),
+                }
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(RealVisitor)
+        } else {
+            deserializer.deserialize_f64(RealVisitor)
+        }
+    }
+}
+
+impl Value for Vec<bool> {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut octets = vec![0u8; self.len().div_ceil(8)];
+        for (i, _) in self.iter().enumerate().filter(|(_, bit)| **bit) {
+            octets[i / 8] |= 0x80 >> (i % 8);
+        }
+        let human_readable = serializer.is_human_readable();
+        let mut bits = serializer.serialize_struct("BitString", 2)?;
+        match human_readable {
+            true => bits.serialize_field("value", &hex(&octets))?,
+            false => bits.serialize_field("value", &Wrap(&octets))?,
+        }
+        bits.serialize_field("length", &self.len())?;
+        bits.end()
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BitStringVisitor;
+
+        impl<'de> Visitor<'de> for BitStringVisitor {
+            type Value = Vec<bool>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a bit string with a value and a length")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<bool>, A::Error> {
+                let Unwrap(octets) = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let length = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                bits(octets, length)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Vec<bool>, A::Error> {
+                let mut octets = None;
+                let mut length = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "value" => octets = Some(map.next_value::<Unwrap<Vec<u8>>>()?.0),
+                        "length" => length = Some(map.next_value()?),
+                        _ => return Err(de::Error::unknown_field(&key, &["value", "length"])),
+                    }
+                }
+                let octets = octets.ok_or_else(|| de::Error::missing_field("value"))?;
+                let length = length.ok_or_else(|| de::Error::missing_field("length"))?;
+                bits(octets, length)
+            }
+        }
+
+        deserializer.deserialize_struct("BitString", &["value", "length"], BitStringVisitor)
+    }
+}
+
+/// Get the bits of a bit string from its octets and its number of bits
+fn bits<E: de::Error>(octets: Vec<u8>, length: usize) -> Result<Vec<bool>, E> {
+    if octets.len() != length.div_ceil(8) {
+        return Err(E::custom(format!(
+            "{} octets cannot hold {length} bits",
+            octets.len()
+        )));
+    }
+    Ok((0..length)
+        .map(|i| octets[i / 8] & (0x80 >> (i % 8)) != 0)
+        .collect())
+}
+
+impl Value for Vec<u8> {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match serializer.is_human_readable() {
+            true => serializer.serialize_str(&hex(self)),
+            false => serializer.serialize_bytes(self),
+        }
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OctetsVisitor;
+
+        impl<'de> Visitor<'de> for OctetsVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("octets or hexadecimal digits")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Vec<u8>, E> {
+                let digits: Vec<_> = value
+                    .chars()
+                    .map(|c| c.to_digit(16).map(|digit| digit as u8))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| E::custom(format!("invalid hexadecimal digits `{value}`")))?;
+                if digits.len() % 2 != 0 {
+                    return Err(E::custom("odd number of hexadecimal digits"));
+                }
+                Ok(digits
+                    .chunks(2)
+                    .map(|pair| pair[0] << 4 | pair[1])
+                    .collect())
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(value.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(value)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut octets = vec![];
+                while let Some(octet) = seq.next_element()? {
+                    octets.push(octet);
+                }
+                Ok(octets)
+            }
+        }
+
+        match deserializer.is_human_readable() {
+            true => deserializer.deserialize_str(OctetsVisitor),
+            false => deserializer.deserialize_byte_buf(OctetsVisitor),
+        }
+    }
+}
+
+/// Write octets as upper case hexadecimal digits
+fn hex(octets: &[u8]) -> String {
+    octets.iter().map(|octet| format!("{octet:02X}")).collect()
+}
+
+impl<T: Value> Value for Option<T> {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => serializer.serialize_some(&Wrap(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: Option<Unwrap<T>> = Deserialize::deserialize(deserializer)?;
+        Ok(value.map(|Unwrap(value)| value))
+    }
+}
+
+impl<T: Value> Value for Box<T> {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize_value(serializer)
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize_value(deserializer).map(Box::new)
+    }
+}
+
+impl<T: Value> Value for Vec<T> {
+    fn serialize_value<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for value in self {
+            seq.serialize_element(&Wrap(value))?;
+        }
+        seq.end()
+    }
+
+    fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values: Vec<Unwrap<T>> = Deserialize::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|Unwrap(value)| value).collect())
+    }
+}
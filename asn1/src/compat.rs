@@ -0,0 +1,1375 @@
+//! Comparison of two versions of a schema, reporting the changes that stop
+//! values encoded with one version from being decoded with the other.
+//!
+//! Types are compared by their expanded form, so changes to `COMPONENTS OF`,
+//! selection types and parameterized types are seen in the types using them.
+//! Constraints are compared by the values they permit: a change that only
+//! removes values or only adds them is reported as tightening or loosening
+//! the type, and breaks PER and OER only if it changes the constraints that
+//! those rules encode.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    mem::discriminant,
+};
+
+use num_bigint::{BigInt, Sign};
+
+use crate::{
+    analysis::{AnalysisContext, Bounds, Discrete, EffectiveConstraints, Environment},
+    ast::{
+        ComponentType, EnumItem, Extensible, ExtensionAddition, NamedType, TagClass, TagDefault,
+        TagKind, Type, TypeOrValue, Value, WithId,
+    },
+    cst::{Asn1Tag, AsnNodeId},
+    diagnostic::Label,
+    token::TokenKind,
+    Diagnostic,
+};
+
+// severities are given for BER, PER and OER in that order
+use Severity::{Breaking as B, Compatible as C, Warning as W};
+
+/// How a change affects the values encoded with a set of encoding rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Values can be exchanged between the old and new schema
+    Compatible,
+
+    /// Values can be exchanged only in some cases, such as when an optional
+    /// component is absent or when the decoder ignores unknown extensions
+    Warning,
+
+    /// Values encoded with one schema cannot be decoded with the other
+    Breaking,
+}
+
+/// The kind of change made to a schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// A module or type assignment was removed
+    Removed,
+
+    /// A type was replaced by a different kind of type
+    TypeChanged,
+
+    /// The tags of a type changed, whether written or automatic
+    TagChanged,
+
+    /// An extension marker was added or removed
+    ExtensibilityChanged,
+
+    /// A component was added to a sequence or set type
+    ComponentAdded,
+
+    /// A component was removed from a sequence or set type
+    ComponentRemoved,
+
+    /// A component became optional or required
+    OptionalityChanged,
+
+    /// An alternative was added to a choice type
+    AlternativeAdded,
+
+    /// An alternative was removed from a choice type
+    AlternativeRemoved,
+
+    /// A component, alternative or item moved between the extension root and
+    /// the extension additions
+    MovedAcrossExtension,
+
+    /// Components or extension additions were reordered
+    Reordered,
+
+    /// An item of an enumerated type was added, removed or renumbered
+    EnumerationChanged,
+
+    /// The constraints of a type were changed to permit fewer values
+    ConstraintTightened,
+
+    /// The constraints of a type were changed to permit more values
+    ConstraintLoosened,
+
+    /// The constraints of a type were changed to permit some values that
+    /// were not permitted before, and to forbid others
+    ConstraintChanged,
+}
+
+/// A change between two versions of a schema that affects the compatibility
+/// of encoded values
+#[derive(Debug, Clone)]
+pub struct Change {
+    /// What changed
+    pub kind: ChangeKind,
+
+    /// The module, assignment and identifiers of the changed type,
+    /// `Module.Type.component`
+    pub path: String,
+
+    /// Effect of the change on the Basic Encoding Rules
+    pub ber: Severity,
+
+    /// Effect of the change on the Packed Encoding Rules
+    pub per: Severity,
+
+    /// Effect of the change on the Octet Encoding Rules
+    pub oer: Severity,
+
+    /// The change, labelled in the new schema.  Changes breaking any of the
+    /// encoding rules are errors, the rest are warnings.
+    pub diagnostic: Diagnostic,
+}
+
+impl Change {
+    /// Get the highest severity of the change for any of the encoding rules
+    pub fn severity(&self) -> Severity {
+        self.ber.max(self.per).max(self.oer)
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Compatible => "compatible",
+            Severity::Warning => "compatible in some cases",
+            Severity::Breaking => "incompatible",
+        })
+    }
+}
+
+impl AnalysisContext<'_> {
+    /// Compare this schema with a newer version of it, and get the changes
+    /// that affect whether values encoded with one can be decoded with the
+    /// other using BER, PER or OER.  Modules are matched by name, and types
+    /// by the name of their assignment.
+    pub fn compatibility(&self, newer: &AnalysisContext) -> Vec<Change> {
+        let mut comparison = Comparison {
+            old: self,
+            new: newer,
+            changes: vec![],
+            compared: HashSet::new(),
+        };
+        comparison.run();
+        comparison.changes
+    }
+}
+
+/// State of a comparison between two versions of a schema
+struct Comparison<'a> {
+    old: &'a AnalysisContext<'a>,
+    new: &'a AnalysisContext<'a>,
+    changes: Vec<Change>,
+
+    /// Pairs of old and new types already compared, by qualified name, so
+    /// types referred to more than once or recursively are compared once.
+    compared: HashSet<(String, String)>,
+}
+
+/// A type in one of the schemas with the module it is in
+#[derive(Clone, Copy)]
+struct Side<'a> {
+    module: &'a Environment,
+    ty: &'a Type,
+    id: AsnNodeId,
+}
+
+/// Where a type being compared is used, which decides how a change to its
+/// tags affects each of the encoding rules
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Assignment,
+    Component { set: bool },
+    Alternative,
+    Element,
+}
+
+/// A tag of a type with its kind resolved from the module's tag default
+#[derive(PartialEq, Eq)]
+struct Tag {
+    class: TagClass,
+    number: String,
+    explicit: bool,
+}
+
+/// A constraint of a type, compared by the text of its tokens so that
+/// comments and layout are ignored
+struct Constraint {
+    tokens: Vec<String>,
+
+    /// The source text of the constraint, for describing it
+    text: String,
+}
+
+/// Where an item of a sequence, set, choice or enumerated type is placed
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Place {
+    Root,
+
+    /// Within the extension addition with the given index
+    Addition(usize),
+}
+
+/// The named items of a sequence, set, choice or enumerated type
+struct Items<'a, T> {
+    /// Each item with its name and place, the root items first and then the
+    /// extension additions.  This is the order of automatic tags.
+    list: Vec<(&'a str, &'a T, Place)>,
+
+    /// Does the type have an extension marker, written or implied
+    extensible: bool,
+
+    /// The number of extension additions
+    additions: usize,
+}
+
+impl<'a> Comparison<'a> {
+    fn run(&mut self) {
+        let mut modules: Vec<_> = self.old.modules().collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for old in modules {
+            let Some(new) = self.new.module_named(&old.name) else {
+                self.change(
+                    ChangeKind::Removed,
+                    &old.name,
+                    None,
+                    format!("Module `{}` was removed", old.name),
+                    [W, W, W],
+                );
+                continue;
+            };
+
+            let mut names: Vec<_> = old
+                .expanded
+                .iter()
+                .filter(|(name, var)| {
+                    matches!(var.value.value, TypeOrValue::Type(_))
+                        && !old.instances.contains_key(*name)
+                })
+                .map(|(name, _)| name)
+                .collect();
+            names.sort();
+
+            for name in names {
+                let path = format!("{}.{name}", old.name);
+                let TypeOrValue::Type(old_ty) = &old.expanded[name].value.value else {
+                    continue;
+                };
+                let Some(new_var) = new.expanded.get(name) else {
+                    self.change(
+                        ChangeKind::Removed,
+                        &path,
+                        Some(new.name_id),
+                        format!("Type `{path}` was removed"),
+                        [W, W, W],
+                    );
+                    continue;
+                };
+                let TypeOrValue::Type(new_ty) = &new_var.value.value else {
+                    continue;
+                };
+
+                self.compared.insert((path.clone(), path.clone()));
+                self.compare(
+                    &path,
+                    Position::Assignment,
+                    Side {
+                        module: old,
+                        ty: old_ty,
+                        id: old.expanded[name].value.id,
+                    },
+                    Side {
+                        module: new,
+                        ty: new_ty,
+                        id: new_var.value.id,
+                    },
+                    (None, None),
+                );
+            }
+        }
+    }
+
+    /// Record a change, unless it is compatible with every encoding rule
+    fn change(
+        &mut self,
+        kind: ChangeKind,
+        path: &str,
+        node: Option<AsnNodeId>,
+        message: String,
+        severity: [Severity; 3],
+    ) {
+        let label = node.map(|node| self.new.label(node));
+        self.change_at(kind, path, label, message, severity);
+    }
+
+    /// Record a change pointing at a label of the new schema, unless it is
+    /// compatible with every encoding rule
+    fn change_at(
+        &mut self,
+        kind: ChangeKind,
+        path: &str,
+        label: Option<Label>,
+        message: String,
+        [ber, per, oer]: [Severity; 3],
+    ) {
+        let severity = ber.max(per).max(oer);
+        if severity == C {
+            return;
+        }
+
+        let code = format!("Asn1::Compat::{kind:?}");
+        let mut diagnostic = match severity {
+            B => Diagnostic::error(code),
+            _ => Diagnostic::warning(code),
+        }
+        .name(message);
+        if let Some(label) = label {
+            diagnostic = diagnostic.label(label.message("Changed here"));
+        }
+        diagnostic = diagnostic.label(format!("BER: {ber}, PER: {per}, OER: {oer}"));
+
+        self.changes.push(Change {
+            kind,
+            path: path.to_string(),
+            ber,
+            per,
+            oer,
+            diagnostic,
+        });
+    }
+
+    /// Compare an old and new type.  A component's type that refers to the
+    /// same assignment in both schemas is compared with that assignment, so
+    /// only the tags written on the component are compared here.  Types of
+    /// components with automatic tags are given the number of their tag.
+    fn compare(
+        &mut self,
+        path: &str,
+        position: Position,
+        old: Side<'a>,
+        new: Side<'a>,
+        automatic: (Option<usize>, Option<usize>),
+    ) {
+        if position != Position::Assignment {
+            let (Some((old_tags, old_inner, _)), Some((new_tags, new_inner, _))) =
+                (tags(self.old, old, false), tags(self.new, new, false))
+            else {
+                return;
+            };
+            let old_name = assignment(self.old, old_inner);
+            if old_name.is_some() && old_name == assignment(self.new, new_inner) {
+                self.tags(path, position, new, old_tags, new_tags, automatic);
+                self.constraints(path, (old, new), false);
+                return;
+            }
+        }
+
+        let outer = (old, new);
+        let (Some((old_tags, old, old_name)), Some((new_tags, new, new_name))) =
+            (tags(self.old, old, true), tags(self.new, new, true))
+        else {
+            return;
+        };
+        self.tags(path, position, outer.1, old_tags, new_tags, automatic);
+        if discriminant(old.ty) == discriminant(new.ty) {
+            self.constraints(path, outer, true);
+        }
+
+        if let (Some(old_name), Some(new_name)) = (old_name, new_name) {
+            if !self.compared.insert((old_name, new_name)) {
+                return;
+            }
+        }
+
+        if discriminant(old.ty) != discriminant(new.ty) {
+            self.change(
+                ChangeKind::TypeChanged,
+                path,
+                Some(new.id),
+                format!(
+                    "Type of `{path}` changed from {} to {}",
+                    keyword(old.ty),
+                    keyword(new.ty)
+                ),
+                [B, B, B],
+            );
+            return;
+        }
+
+        match (old.ty, new.ty) {
+            (Type::Sequence(old_items), Type::Sequence(new_items)) => {
+                self.components(path, false, (old, old_items), (new, new_items))
+            }
+            (Type::Set(old_items), Type::Set(new_items)) => {
+                self.components(path, true, (old, old_items), (new, new_items))
+            }
+            (Type::Choice(old_items), Type::Choice(new_items)) => {
+                self.alternatives(path, (old, old_items), (new, new_items))
+            }
+            (Type::Enumerated(old_items), Type::Enumerated(new_items)) => {
+                self.enumerated(path, (old, old_items), (new, new_items))
+            }
+            (Type::SequenceOf(old_of), Type::SequenceOf(new_of))
+            | (Type::SetOf(old_of), Type::SetOf(new_of)) => self.compare(
+                &format!("{path}[]"),
+                Position::Element,
+                side(old.module, &old_of.ty),
+                side(new.module, &new_of.ty),
+                (None, None),
+            ),
+            _ => (),
+        }
+    }
+
+    /// Compare the tags of a type, including any automatic tag
+    fn tags(
+        &mut self,
+        path: &str,
+        position: Position,
+        ty: Side<'a>,
+        old: Vec<Tag>,
+        new: Vec<Tag>,
+        automatic: (Option<usize>, Option<usize>),
+    ) {
+        let old = with_automatic(old, automatic.0);
+        let new = with_automatic(new, automatic.1);
+        if old == new {
+            return;
+        }
+
+        // the order of set components and choice alternatives in PER and OER
+        // is the order of their tags
+        let severity = match position {
+            Position::Assignment | Position::Element | Position::Component { set: false } => {
+                [B, C, C]
+            }
+            Position::Component { set: true } => [B, W, W],
+            Position::Alternative => [B, W, B],
+        };
+        self.change_at(
+            ChangeKind::TagChanged,
+            path,
+            Some(tag_label(self.new, ty)),
+            format!(
+                "Tags of `{path}` changed from {} to {}",
+                describe_tags(&old),
+                describe_tags(&new)
+            ),
+            severity,
+        );
+    }
+
+    /// Compare the constraints of a type, including the constraints of the
+    /// types it refers to if `follow` is set
+    fn constraints(&mut self, path: &str, (old, new): (Side<'a>, Side<'a>), follow: bool) {
+        let node = new.id;
+        let (Some(old_text), Some(new_text)) = (
+            constraints(self.old, old, follow),
+            constraints(self.new, new, follow),
+        ) else {
+            return;
+        };
+        if old_text
+            .iter()
+            .map(|c| &c.tokens)
+            .eq(new_text.iter().map(|c| &c.tokens))
+        {
+            return;
+        }
+
+        let (Some((_, builtin, _)), Some(_)) =
+            (tags(self.old, old, true), tags(self.new, new, true))
+        else {
+            return;
+        };
+        let old = self.old.constraints_of(old.module, old.ty, old.id, follow);
+        let new = self.new.constraints_of(new.module, new.ty, new.id, follow);
+
+        let (kind, change) = match (is_tighter(&new, &old), is_tighter(&old, &new)) {
+            (true, true) => (ChangeKind::ConstraintChanged, "changed"),
+            (true, false) => (ChangeKind::ConstraintTightened, "tightened"),
+            (false, true) => (ChangeKind::ConstraintLoosened, "loosened"),
+            (false, false) => (ChangeKind::ConstraintChanged, "changed"),
+        };
+
+        let per = match old.per_visible(builtin.ty) == new.per_visible(builtin.ty) {
+            true => C,
+            false => B,
+        };
+        let oer = match oer_visible(&old, builtin.ty) == oer_visible(&new, builtin.ty) {
+            true => C,
+            false => B,
+        };
+        let visibility = match (per, oer) {
+            (C, C) => ", which is not visible to PER or OER",
+            (C, _) => ", which is not visible to PER",
+            _ => "",
+        };
+
+        self.change(
+            kind,
+            path,
+            Some(node),
+            format!(
+                "Constraints of `{path}` {change} from {} to {}{visibility}",
+                describe_constraints(&old_text),
+                describe_constraints(&new_text)
+            ),
+            // BER values are only rejected by decoders checking constraints
+            [W, per, oer],
+        );
+    }
+
+    /// Report an extension marker that was added or removed
+    fn extensibility(&mut self, path: &str, node: AsnNodeId, old: bool, new: bool) {
+        match (old, new) {
+            (false, true) => self.change(
+                ChangeKind::ExtensibilityChanged,
+                path,
+                Some(node),
+                format!("`{path}` became extensible"),
+                [C, B, B],
+            ),
+            (true, false) => self.change(
+                ChangeKind::ExtensibilityChanged,
+                path,
+                Some(node),
+                format!("`{path}` is no longer extensible"),
+                [W, B, B],
+            ),
+            _ => (),
+        }
+    }
+
+    /// Report the items present in both versions of a type whose order
+    /// changed, either within the extension root or between the extension
+    /// additions
+    fn reordered<T>(
+        &mut self,
+        path: &str,
+        node: AsnNodeId,
+        (old, new): (&Items<T>, &Items<T>),
+        (root, additions): ([Severity; 3], [Severity; 3]),
+    ) {
+        let old_root: Vec<_> = common(old, new, true).into_iter().map(|(n, _)| n).collect();
+        let new_root: Vec<_> = common(new, old, true).into_iter().map(|(n, _)| n).collect();
+        if old_root != new_root {
+            self.change(
+                ChangeKind::Reordered,
+                path,
+                Some(node),
+                format!("The root components of `{path}` were reordered"),
+                root,
+            );
+        }
+
+        // items within the same group can be reordered, so only the order of
+        // the groups is compared
+        let old_additions = common(old, new, false);
+        let mut new_positions = old_additions.iter().map(|(name, _)| new.get(name));
+        let mut last = 0;
+        let in_order = new_positions.all(|item| match item {
+            Some((_, _, Place::Addition(index))) => {
+                let ordered = index >= last;
+                last = index;
+                ordered
+            }
+            _ => true,
+        });
+        if !in_order {
+            self.change(
+                ChangeKind::Reordered,
+                path,
+                Some(node),
+                format!("The extension additions of `{path}` were reordered"),
+                additions,
+            );
+        }
+    }
+
+    /// Get the index of the last extension addition of a new type that is
+    /// also in the old type.  Additions after it were appended.
+    fn last_common<T>(old: &Items<T>, new: &Items<T>) -> Option<usize> {
+        new.list
+            .iter()
+            .filter(|(name, _, _)| old.get(name).is_some())
+            .filter_map(|(_, _, place)| match place {
+                Place::Addition(index) => Some(*index),
+                Place::Root => None,
+            })
+            .max()
+    }
+
+    /// Compare the components of a sequence or set type
+    fn components(
+        &mut self,
+        path: &str,
+        set: bool,
+        (old, old_components): (Side<'a>, &'a Extensible<ComponentType>),
+        (new, new_components): (Side<'a>, &'a Extensible<ComponentType>),
+    ) {
+        let old_items = items(old.module, old_components, component_name);
+        let new_items = items(new.module, new_components, component_name);
+        self.extensibility(path, new.id, old_items.extensible, new_items.extensible);
+
+        let old_automatic = old.module.automatic_tags(old_components, |c| &c.ty().value);
+        let new_automatic = new.module.automatic_tags(new_components, |c| &c.ty().value);
+
+        for (index, (name, component, place)) in old_items.list.iter().enumerate() {
+            let ComponentType::Named(component) = component else {
+                continue;
+            };
+            let item_path = format!("{path}.{name}");
+
+            let Some((new_index, ComponentType::Named(new_component), new_place)) =
+                new_items.get(name)
+            else {
+                // removing an extension addition renumbers the ones after it
+                let severity = match place {
+                    Place::Root => [B, B, B],
+                    Place::Addition(index) if index + 1 < old_items.additions => [W, B, B],
+                    Place::Addition(_) => [W, W, W],
+                };
+                self.change(
+                    ChangeKind::ComponentRemoved,
+                    &item_path,
+                    Some(new.id),
+                    format!("Component `{name}` was removed from `{path}`"),
+                    severity,
+                );
+                continue;
+            };
+
+            if (*place == Place::Root) != (new_place == Place::Root) {
+                self.change(
+                    ChangeKind::MovedAcrossExtension,
+                    &item_path,
+                    Some(new_component.name.id),
+                    format!("Component `{name}` of `{path}` {}", moved(*place)),
+                    [C, B, B],
+                );
+            }
+
+            let old_optional = component.optional || component.default.is_some();
+            let new_optional = new_component.optional || new_component.default.is_some();
+            match (old_optional, new_optional) {
+                (true, false) => self.change(
+                    ChangeKind::OptionalityChanged,
+                    &item_path,
+                    Some(new_component.name.id),
+                    format!("Component `{name}` of `{path}` is no longer optional"),
+                    [B, B, B],
+                ),
+                (false, true) => {
+                    // the presence of root components is encoded in a bitmap
+                    let packed = match new_place {
+                        Place::Root => B,
+                        Place::Addition(_) => W,
+                    };
+                    self.change(
+                        ChangeKind::OptionalityChanged,
+                        &item_path,
+                        Some(new_component.name.id),
+                        format!("Component `{name}` of `{path}` became optional"),
+                        [C, packed, packed],
+                    )
+                }
+                _ => (),
+            }
+
+            self.compare(
+                &item_path,
+                Position::Component { set },
+                side(old.module, &component.ty),
+                side(new.module, &new_component.ty),
+                (
+                    old_automatic.then_some(index),
+                    new_automatic.then_some(new_index),
+                ),
+            );
+        }
+
+        // the components of a set are encoded in the order of their tags
+        let root = if set { [C, C, C] } else { [B, B, B] };
+        self.reordered(path, new.id, (&old_items, &new_items), (root, [W, B, B]));
+
+        let last_common = Self::last_common(&old_items, &new_items);
+        for (name, component, place) in &new_items.list {
+            let ComponentType::Named(component) = component else {
+                continue;
+            };
+            if old_items.get(name).is_some() {
+                continue;
+            }
+
+            let optional = component.optional || component.default.is_some();
+            let (message, severity) = match place {
+                Place::Root if optional => ("Optional component", [W, B, B]),
+                Place::Root => ("Component", [B, B, B]),
+                Place::Addition(index)
+                    if old_items.extensible && last_common.is_some_and(|last| *index <= last) =>
+                {
+                    ("Extension addition", [W, B, B])
+                }
+                Place::Addition(_) => continue,
+            };
+            self.change(
+                ChangeKind::ComponentAdded,
+                &format!("{path}.{name}"),
+                Some(component.name.id),
+                match place {
+                    Place::Root => format!("{message} `{name}` was added to the root of `{path}`"),
+                    Place::Addition(_) => format!(
+                        "{message} `{name}` was inserted before existing additions of `{path}`"
+                    ),
+                },
+                severity,
+            );
+        }
+    }
+
+    /// Compare the alternatives of a choice type
+    fn alternatives(
+        &mut self,
+        path: &str,
+        (old, old_alternatives): (Side<'a>, &'a Extensible<NamedType>),
+        (new, new_alternatives): (Side<'a>, &'a Extensible<NamedType>),
+    ) {
+        let old_items = items(old.module, old_alternatives, |a| Some(&a.name));
+        let new_items = items(new.module, new_alternatives, |a| Some(&a.name));
+        self.extensibility(path, new.id, old_items.extensible, new_items.extensible);
+
+        let old_automatic = old.module.automatic_tags(old_alternatives, |a| &a.ty.value);
+        let new_automatic = new.module.automatic_tags(new_alternatives, |a| &a.ty.value);
+
+        for (index, (name, alternative, place)) in old_items.list.iter().enumerate() {
+            let item_path = format!("{path}.{name}");
+            let Some((new_index, new_alternative, new_place)) = new_items.get(name) else {
+                self.change(
+                    ChangeKind::AlternativeRemoved,
+                    &item_path,
+                    Some(new.id),
+                    format!("Alternative `{name}` was removed from `{path}`"),
+                    [B, B, B],
+                );
+                continue;
+            };
+
+            // the index of an alternative in PER counts the root and the
+            // extension additions separately, and OER wraps additions
+            if (*place == Place::Root) != (new_place == Place::Root) {
+                self.change(
+                    ChangeKind::MovedAcrossExtension,
+                    &item_path,
+                    Some(new_alternative.name.id),
+                    format!("Alternative `{name}` of `{path}` {}", moved(*place)),
+                    [C, B, B],
+                );
+            }
+
+            self.compare(
+                &item_path,
+                Position::Alternative,
+                side(old.module, &alternative.ty),
+                side(new.module, &new_alternative.ty),
+                (
+                    old_automatic.then_some(index),
+                    new_automatic.then_some(new_index),
+                ),
+            );
+        }
+
+        // alternatives are ordered by their tags, which are compared above
+        self.reordered(
+            path,
+            new.id,
+            (&old_items, &new_items),
+            ([C, C, C], [C, B, C]),
+        );
+
+        let last_common = Self::last_common(&old_items, &new_items);
+        for (name, alternative, place) in &new_items.list {
+            if old_items.get(name).is_some() {
+                continue;
+            }
+
+            let (message, severity) = match place {
+                Place::Root => (
+                    format!("Alternative `{name}` was added to the root of `{path}`"),
+                    [W, B, W],
+                ),
+                Place::Addition(index)
+                    if old_items.extensible && last_common.is_some_and(|last| *index <= last) =>
+                {
+                    (
+                        format!(
+                            "Alternative `{name}` was inserted before existing additions of `{path}`"
+                        ),
+                        [C, B, C],
+                    )
+                }
+                Place::Addition(_) => continue,
+            };
+            self.change(
+                ChangeKind::AlternativeAdded,
+                &format!("{path}.{name}"),
+                Some(alternative.name.id),
+                message,
+                severity,
+            );
+        }
+    }
+
+    /// Compare the items of an enumerated type by their numbers.  PER
+    /// encodes the index of an item in the sorted numbers of the root or of
+    /// the extension additions, BER and OER encode the number.
+    fn enumerated(
+        &mut self,
+        path: &str,
+        (old, old_enumeration): (Side<'a>, &'a Extensible<EnumItem>),
+        (new, new_enumeration): (Side<'a>, &'a Extensible<EnumItem>),
+    ) {
+        let old_items = items(old.module, old_enumeration, |i| Some(&i.name));
+        let new_items = items(new.module, new_enumeration, |i| Some(&i.name));
+        self.extensibility(path, new.id, old_items.extensible, new_items.extensible);
+
+        let old_numbers = numbers(&old_items);
+        let new_numbers = numbers(&new_items);
+
+        for (index, (name, _, place)) in old_items.list.iter().enumerate() {
+            let item_path = format!("{path}.{name}");
+            let Some((new_index, new_item, new_place)) = new_items.get(name) else {
+                self.change(
+                    ChangeKind::EnumerationChanged,
+                    &item_path,
+                    Some(new.id),
+                    format!("Item `{name}` was removed from `{path}`"),
+                    [B, B, B],
+                );
+                continue;
+            };
+
+            if (*place == Place::Root) != (new_place == Place::Root) {
+                self.change(
+                    ChangeKind::MovedAcrossExtension,
+                    &item_path,
+                    Some(new_item.name.id),
+                    format!("Item `{name}` of `{path}` {}", moved(*place)),
+                    [C, B, C],
+                );
+                continue;
+            }
+
+            let (Some(old_number), Some(new_number)) = (old_numbers[index], new_numbers[new_index])
+            else {
+                continue;
+            };
+            let number = if old_number == new_number { C } else { B };
+            let index = if rank(&old_items, &old_numbers, index)
+                == rank(&new_items, &new_numbers, new_index)
+            {
+                C
+            } else {
+                B
+            };
+            let message = if old_number == new_number {
+                format!("Item `{name}` of `{path}` changed its position among the other items")
+            } else {
+                format!("Item `{name}` of `{path}` changed from {old_number} to {new_number}")
+            };
+            self.change(
+                ChangeKind::EnumerationChanged,
+                &item_path,
+                Some(new_item.name.id),
+                message,
+                [number, index, number],
+            );
+        }
+
+        // additions are appended by giving them numbers greater than those of
+        // the existing additions
+        let last_common = new_items
+            .list
+            .iter()
+            .zip(&new_numbers)
+            .filter(|((name, _, place), _)| *place != Place::Root && old_items.get(name).is_some())
+            .filter_map(|(_, number)| *number)
+            .max();
+        for ((name, item, place), number) in new_items.list.iter().zip(&new_numbers) {
+            if old_items.get(name).is_some() {
+                continue;
+            }
+
+            let (message, severity) = match place {
+                Place::Root => (
+                    format!("Item `{name}` was added to the root of `{path}`"),
+                    [W, B, W],
+                ),
+                Place::Addition(_)
+                    if old_items.extensible
+                        && last_common.is_some_and(|last| number.is_none_or(|n| n < last)) =>
+                {
+                    (
+                        format!("Item `{name}` was inserted before existing additions of `{path}`"),
+                        [C, B, C],
+                    )
+                }
+                Place::Addition(_) => continue,
+            };
+            self.change(
+                ChangeKind::EnumerationChanged,
+                &format!("{path}.{name}"),
+                Some(item.name.id),
+                message,
+                severity,
+            );
+        }
+    }
+}
+
+impl<'a, T> Items<'a, T> {
+    /// Find an item by name, with its index in the list
+    fn get(&self, name: &str) -> Option<(usize, &'a T, Place)> {
+        self.list
+            .iter()
+            .enumerate()
+            .find(|(_, (n, _, _))| *n == name)
+            .map(|(index, (_, item, place))| (index, *item, *place))
+    }
+}
+
+/// Get the items in the root or in the extension additions of one version of
+/// a type that are in the same part of the other version
+fn common<'a, T>(items: &Items<'a, T>, other: &Items<T>, root: bool) -> Vec<(&'a str, Place)> {
+    items
+        .list
+        .iter()
+        .filter(|(name, _, place)| {
+            (*place == Place::Root) == root
+                && other
+                    .get(name)
+                    .is_some_and(|(_, _, other)| (other == Place::Root) == root)
+        })
+        .map(|(name, _, place)| (*name, *place))
+        .collect()
+}
+
+/// Get the named items of a sequence, set, choice or enumerated type
+fn items<'a, T>(
+    module: &Environment,
+    items: &'a Extensible<T>,
+    name: impl Fn(&'a T) -> Option<&'a WithId<String>>,
+) -> Items<'a, T> {
+    let mut list = vec![];
+    for item in items.root_items() {
+        if let Some(n) = name(item) {
+            list.push((n.as_str(), item, Place::Root));
+        }
+    }
+
+    let additions = items.extension.as_ref().map_or(&[][..], |e| &e.additions);
+    for (index, addition) in additions.iter().enumerate() {
+        for item in ExtensionAddition::items(addition) {
+            if let Some(n) = name(item) {
+                list.push((n.as_str(), item, Place::Addition(index)));
+            }
+        }
+    }
+
+    Items {
+        list,
+        extensible: items.extension.is_some() || module.extensibility_implied,
+        additions: additions.len(),
+    }
+}
+
+/// Get the name of a sequence or set component
+fn component_name(component: &ComponentType) -> Option<&WithId<String>> {
+    match component {
+        ComponentType::Named(component) => Some(&component.name),
+        ComponentType::ComponentsOf(_) => None,
+    }
+}
+
+/// Describe an item moving between the extension root and the additions
+fn moved(from: Place) -> &'static str {
+    match from {
+        Place::Root => "moved from the extension root to the extension additions",
+        Place::Addition(_) => "moved from the extension additions to the extension root",
+    }
+}
+
+/// Get the number of each item of an enumerated type.  Root items without a
+/// number take the lowest number not used by the root, and additions without
+/// a number take one more than any number before them.
+fn numbers(items: &Items<EnumItem>) -> Vec<Option<i128>> {
+    let explicit: Vec<_> = items
+        .list
+        .iter()
+        .map(|(_, item, _)| match item.value.as_deref() {
+            Some(Value::Number(number)) => number.parse::<i128>().ok(),
+            _ => None,
+        })
+        .collect();
+    let used: HashSet<_> = items
+        .list
+        .iter()
+        .zip(&explicit)
+        .filter(|((_, _, place), _)| *place == Place::Root)
+        .filter_map(|(_, number)| *number)
+        .collect();
+
+    let mut numbers = vec![];
+    let mut next = 0;
+    for ((_, item, place), number) in items.list.iter().zip(explicit) {
+        let number = match (number, place) {
+            (Some(number), _) => Some(number),
+            // numbers given by references to values are not known
+            _ if item.value.is_some() => None,
+            (None, Place::Root) => {
+                while used.contains(&next) {
+                    next += 1;
+                }
+                next += 1;
+                Some(next - 1)
+            }
+            (None, Place::Addition(_)) => numbers.iter().flatten().max().map(|n| n + 1).or(Some(0)),
+        };
+        numbers.push(number);
+    }
+    numbers
+}
+
+/// Get the index of an enumerated item in the sorted numbers of the items in
+/// the same part of the type, the root or the extension additions
+fn rank(items: &Items<EnumItem>, numbers: &[Option<i128>], index: usize) -> Option<usize> {
+    let root = items.list[index].2 == Place::Root;
+    let number = numbers[index]?;
+    Some(
+        items
+            .list
+            .iter()
+            .zip(numbers)
+            .filter(|((_, _, place), n)| {
+                (*place == Place::Root) == root && n.is_some_and(|n| n < number)
+            })
+            .count(),
+    )
+}
+
+fn side<'a>(module: &'a Environment, ty: &'a WithId<Type>) -> Side<'a> {
+    Side {
+        module,
+        ty: &ty.value,
+        id: ty.id,
+    }
+}
+
+/// Get the tags of a type and the type within them, following references if
+/// requested.  The qualified name of the last assignment followed is also
+/// given, if any were.
+fn tags<'a>(
+    context: &'a AnalysisContext<'a>,
+    mut side: Side<'a>,
+    follow: bool,
+) -> Option<(Vec<Tag>, Side<'a>, Option<String>)> {
+    let mut tags = vec![];
+    let mut name = None;
+
+    // references that loop are reported by analysis, so give up on them
+    for _ in 0..256 {
+        match side.ty {
+            Type::Tagged(tagged) => {
+                let inner = self::side(side.module, &tagged.ty);
+                let explicit = match tagged.kind {
+                    TagKind::Explicit => true,
+                    TagKind::Implicit => false,
+                    // a choice type has no tag of its own to replace
+                    TagKind::Default => {
+                        side.module.tag_default == TagDefault::Explicit || is_choice(context, inner)
+                    }
+                };
+                tags.push(Tag {
+                    class: tagged.class,
+                    number: tagged.number.to_string(),
+                    explicit,
+                });
+                side = inner;
+            }
+            Type::Reference(reference) if follow => {
+                let (module, var) = context.resolve_expanded(side.module, reference)?;
+                let TypeOrValue::Type(ty) = &var.value.value else {
+                    return None;
+                };
+                name = Some(format!("{}.{}", module.name, *reference.name));
+                side = Side {
+                    module,
+                    ty,
+                    id: var.value.id,
+                };
+            }
+            _ => return Some((tags, side, name)),
+        }
+    }
+    None
+}
+
+/// Get a label for the tag written in front of a type, `[APPLICATION 2]`, or
+/// for the whole type if its tags come from a type it refers to
+fn tag_label(context: &AnalysisContext, side: Side) -> Label {
+    let Type::Tagged(tagged) = side.ty else {
+        return context.label(side.id);
+    };
+    let number = tagged.number.id;
+    let tree = &context.source(number.source()).tree;
+    let mut brackets = tree.parent(number).into_iter().flat_map(|prefix| {
+        tree.children(prefix).filter(|&node| {
+            tree.token(node).is_some_and(|tok| {
+                matches!(tok.kind, TokenKind::LeftSquare | TokenKind::RightSquare)
+            })
+        })
+    });
+    match (brackets.next(), brackets.next()) {
+        (Some(open), Some(close)) => Label::new()
+            .source(number.source())
+            .loc(tree.span(open).start..tree.span(close).end),
+        _ => context.label(side.id),
+    }
+}
+
+/// Get the constraints applied to a type and the types within its tags,
+/// following references if requested
+fn constraints<'a>(
+    context: &'a AnalysisContext<'a>,
+    mut side: Side<'a>,
+    follow: bool,
+) -> Option<Vec<Constraint>> {
+    let mut constraints = vec![];
+
+    for _ in 0..256 {
+        let tree = &context.source(side.id.source()).tree;
+        for child in tree.children(side.id) {
+            match tree.tree_tag(child) {
+                Some(Asn1Tag::Constraint) => constraints.push(Constraint::new(context, child)),
+                // the constraint before `OF`, `SEQUENCE SIZE (1..5) OF Type`
+                Some(Asn1Tag::SequenceType | Asn1Tag::SetType) => {
+                    let mut size = false;
+                    for node in tree.children(child) {
+                        if tree
+                            .token(node)
+                            .is_some_and(|tok| tok.kind == TokenKind::KwSize)
+                        {
+                            size = true;
+                        } else if tree.tree_tag(node) == Some(Asn1Tag::Constraint) {
+                            let mut constraint = Constraint::new(context, node);
+                            if size {
+                                constraint.tokens.insert(0, "SIZE".into());
+                                constraint.text.insert_str(0, "SIZE ");
+                            }
+                            constraints.push(constraint);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        match side.ty {
+            Type::Tagged(tagged) => side = self::side(side.module, &tagged.ty),
+            Type::Reference(reference) if follow => {
+                let (module, var) = context.resolve_expanded(side.module, reference)?;
+                let TypeOrValue::Type(ty) = &var.value.value else {
+                    return None;
+                };
+                side = Side {
+                    module,
+                    ty,
+                    id: var.value.id,
+                };
+            }
+            _ => return Some(constraints),
+        }
+    }
+    None
+}
+
+/// Are there values permitted by one set of constraints that are not permitted
+/// by another, as far as the constraints can be compared
+fn is_tighter(a: &EffectiveConstraints, b: &EffectiveConstraints) -> bool {
+    fn tighter<T: Discrete>(a: &Option<Bounds<T>>, b: &Option<Bounds<T>>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => !b.is_subset(a) || (b.extensible && !a.extensible),
+            (Some(_), None) => false,
+            (None, Some(_)) => true,
+            (None, None) => false,
+        }
+    }
+
+    tighter(&a.value, &b.value)
+        || tighter(&a.size, &b.size)
+        || tighter(&a.alphabet, &b.alphabet)
+        || b.patterns.iter().any(|p| !a.patterns.contains(p))
+        || b.settings.iter().any(|s| !a.settings.contains(s))
+}
+
+/// The form of the encoding of a type chosen by its constraints in OER.
+/// Integers are encoded in a fixed number of octets if their range fits,
+/// and strings and lists of a fixed size have no length.  Extensible
+/// constraints are not visible to OER.
+#[derive(PartialEq, Eq)]
+enum OerForm {
+    Unconstrained,
+    Integer { signed: bool, octets: u32 },
+    FixedSize(BigInt),
+}
+
+fn oer_visible(constraints: &EffectiveConstraints, ty: &Type) -> OerForm {
+    let visible = |bounds: &Option<Bounds<BigInt>>| bounds.clone().filter(|b| !b.extensible);
+    match ty {
+        Type::Integer(_) => {
+            let Some(bounds) = visible(&constraints.value) else {
+                return OerForm::Unconstrained;
+            };
+            let (Some(lower), Some(upper)) = (bounds.lower(), bounds.upper()) else {
+                return OerForm::Unconstrained;
+            };
+            let signed = lower.sign() == Sign::Minus;
+            for octets in [1, 2, 4, 8] {
+                let half: BigInt = BigInt::from(1) << (octets * 8 - 1);
+                let (min, max) = match signed {
+                    true => (-half.clone(), half),
+                    false => (BigInt::ZERO, half * 2),
+                };
+                if *lower >= min && *upper < max {
+                    return OerForm::Integer { signed, octets };
+                }
+            }
+            OerForm::Unconstrained
+        }
+        Type::Real | Type::Boolean | Type::Null | Type::Enumerated(_) => OerForm::Unconstrained,
+        _ => match visible(&constraints.size) {
+            Some(bounds) if bounds.lower().is_some() && bounds.lower() == bounds.upper() => {
+                OerForm::FixedSize(bounds.upper().cloned().unwrap_or_default())
+            }
+            _ => OerForm::Unconstrained,
+        },
+    }
+}
+
+/// Get the text of every token within a node, not including comments
+fn tokens(context: &AnalysisContext, node: AsnNodeId) -> Vec<String> {
+    let source = context.source(node.source());
+    if let Some(tok) = source.tree.token(node) {
+        return vec![source.source[tok.span()].to_string()];
+    }
+
+    source
+        .tree
+        .children(node)
+        .filter(|&child| !source.tree.is_trivia(child))
+        .flat_map(|child| tokens(context, child))
+        .collect()
+}
+
+/// Describe a list of constraints in ASN.1 notation
+fn describe_constraints(constraints: &[Constraint]) -> String {
+    if constraints.is_empty() {
+        return "no constraints".into();
+    }
+
+    let text: Vec<_> = constraints.iter().map(|c| c.text.as_str()).collect();
+    format!("`{}`", text.join(" "))
+}
+
+impl Constraint {
+    fn new(context: &AnalysisContext, node: AsnNodeId) -> Self {
+        let source = context.source(node.source());
+        let text = source.source[source.tree.span(node)].split_whitespace();
+        Constraint {
+            tokens: tokens(context, node),
+            text: text.collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+/// Is a type a choice type, without any tags of its own
+fn is_choice<'a>(context: &'a AnalysisContext<'a>, side: Side<'a>) -> bool {
+    match tags(context, side, true) {
+        Some((tags, side, _)) => tags.is_empty() && matches!(side.ty, Type::Choice(_)),
+        None => false,
+    }
+}
+
+/// Get the qualified name of the type assignment a type refers to, if it is
+/// a reference to an assignment rather than to an instance of a
+/// parameterized type
+fn assignment<'a>(context: &'a AnalysisContext<'a>, side: Side<'a>) -> Option<String> {
+    let Type::Reference(reference) = side.ty else {
+        return None;
+    };
+    let (module, _) = context.resolve_expanded(side.module, reference)?;
+    if module.instances.contains_key(&reference.name.value) {
+        return None;
+    }
+    Some(format!("{}.{}", module.name, *reference.name))
+}
+
+/// Add the automatic tag of a component or alternative to its tags
+fn with_automatic(mut tags: Vec<Tag>, automatic: Option<usize>) -> Vec<Tag> {
+    if let Some(number) = automatic {
+        tags.insert(
+            0,
+            Tag {
+                class: TagClass::ContextSpecific,
+                number: number.to_string(),
+                explicit: false,
+            },
+        );
+    }
+    tags
+}
+
+/// Describe a list of tags in ASN.1 notation
+fn describe_tags(tags: &[Tag]) -> String {
+    if tags.is_empty() {
+        return "no tags".into();
+    }
+
+    let tags: Vec<_> = tags.iter().map(Tag::to_string).collect();
+    format!("`{}`", tags.join(" "))
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let class = match self.class {
+            TagClass::Universal => "UNIVERSAL ",
+            TagClass::Application => "APPLICATION ",
+            TagClass::Private => "PRIVATE ",
+            TagClass::ContextSpecific => "",
+        };
+        let kind = if self.explicit {
+            "EXPLICIT"
+        } else {
+            "IMPLICIT"
+        };
+        write!(f, "[{class}{}] {kind}", self.number)
+    }
+}
+
+/// Get the keyword of a type, without its components
+fn keyword(ty: &Type) -> String {
+    let keyword = match ty {
+        Type::Integer(_) => "INTEGER",
+        Type::BitString(_) => "BIT STRING",
+        Type::Enumerated(_) => "ENUMERATED",
+        Type::Sequence(_) => "SEQUENCE",
+        Type::Set(_) => "SET",
+        Type::SequenceOf(_) => "SEQUENCE OF",
+        Type::SetOf(_) => "SET OF",
+        Type::Choice(_) => "CHOICE",
+        Type::Class(_) => "CLASS",
+        ty => return format!("`{ty}`"),
+    };
+    format!("`{keyword}`")
+}
@@ -3,7 +3,7 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::{
-    analysis::AnalysisContext,
+    analysis::{AnalysisCache, AnalysisContext, SearchPath},
     cst::{Asn1, Asn1Formatter},
     diagnostic::Result,
     Diagnostic,
@@ -13,14 +13,18 @@ use crate::{
 /// multiple files, analysis and code generation.
 #[derive(Debug, Clone, Default)]
 pub struct AsnCompiler {
-    /// List of all included source files.
-    sources: Vec<Source>,
+    /// List of all included source files.  Removed files are kept as `None`
+    /// so that source IDs remain stable.
+    sources: Vec<Option<Source>>,
 
     /// The enabled features.
     pub(crate) features: Features,
 
-    /// Errors reported outside of analysis.
-    pub(crate) diagnostics: Vec<Diagnostic>,
+    /// Results of previous analysis runs that can be re-used after an edit.
+    pub(crate) cache: AnalysisCache,
+
+    /// Directories to search for imported modules
+    pub(crate) search_path: SearchPath,
 }
 
 /// All features that can be enabled within the compiler.
@@ -66,59 +70,156 @@ impl AsnCompiler {
     /// not run any analysis that is required to check that the source files
     /// are valid.
     pub fn add_file(&mut self, file_name: String, source: String) -> Result<SourceId> {
+        let id = self.push_source(file_name, source);
+        self.parse(id)?;
+
+        Ok(id)
+    }
+
+    /// Add a new file to the compiler without parsing it
+    pub(crate) fn push_source(&mut self, file_name: String, source: String) -> SourceId {
         let id = SourceId(self.sources.len());
 
         // push with a dummy tree which will get replaced later, so that any errors
         // reported during the parsing of this file can find the source text of the file.
-        self.sources.push(Source {
+        self.sources.push(Some(Source {
             file_name,
             source,
-            tree: Asn1::new(id, vec![], 0),
+            tree: Asn1::empty(id),
             id,
-        });
+        }));
+
+        id
+    }
 
-        let tree = self.parser(id).run()?;
+    /// Replace the text of a previously added file.  The file is re-parsed
+    /// immediately, however re-analysis is deferred until the next call to
+    /// [`AsnCompiler::analysis`], which will only re-analyse the modules in this
+    /// file and the modules that import them.  If the text is unchanged, the
+    /// existing tree is kept and nothing will be re-analysed.  If the new text
+    /// cannot be parsed, its modules are left out of analysis and the error is
+    /// reported by every analysis until the file is updated again.
+    pub fn update_file(&mut self, file: SourceId, source: String) -> Result {
+        let Some(current) = self.sources.get_mut(file.0).and_then(Option::as_mut) else {
+            return Err(Diagnostic::error("Asn1::Compiler::RemovedSource")
+                .name("The source file has been removed from the compiler"));
+        };
+        if current.source == source {
+            return Ok(());
+        }
 
-        self.sources[id.0].tree = tree;
+        current.source = source;
+        current.tree = Asn1::empty(file);
+        self.search_path.invalidate();
 
-        Ok(id)
+        self.parse(file)
     }
 
-    /// Get an iterator over all source IDs
-    pub(crate) fn all_sources(&self) -> impl Iterator<Item = SourceId> + '_ {
-        self.sources.iter().map(|s| s.id)
+    /// Remove a file from the compiler.  Its source ID will not be re-used, and
+    /// the methods getting its text or tree will return `None` for it.
+    pub fn remove_file(&mut self, file: SourceId) {
+        if let Some(source) = self.sources.get_mut(file.0) {
+            if let Some(source) = source.take() {
+                self.cache.changed(file);
+                self.cache.parse_diagnostics.remove(&file);
+                self.search_path.remove_file(&source.file_name);
+            }
+        }
     }
 
-    /// Get the source associated with a source id
+    /// Parse the current text of a source file, replacing its tree if the parse
+    /// succeeds.  The file is marked as changed either way, as its previous
+    /// tree has been discarded.  A parse error is kept by the analysis cache
+    /// until the file is parsed again.
+    pub(crate) fn parse(&mut self, file: SourceId) -> Result {
+        self.cache.changed(file);
+
+        match self.parser(file).run() {
+            Ok(tree) => {
+                self.cache.parse_diagnostics.remove(&file);
+                if let Some(source) = self.sources[file.0].as_mut() {
+                    source.tree = tree;
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.cache.parse_diagnostics.insert(file, e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Is the source with the given ID still part of the compiler
+    pub(crate) fn has_source(&self, file: SourceId) -> bool {
+        matches!(self.sources.get(file.0), Some(Some(_)))
+    }
+
+    /// Get the source associated with a source id.  Analysis only reaches the
+    /// sources of modules that are still loaded, which are never removed.
     #[inline]
     pub(crate) fn source(&self, file: SourceId) -> &Source {
-        &self.sources[file.0]
+        self.sources[file.0]
+            .as_ref()
+            .expect("analysed source file has been removed")
     }
 
-    /// Convert the CST of a file into a string
-    pub fn print_cst(&self, file: SourceId) -> String {
-        let source = &self.sources[file.0];
+    /// Get the source associated with a source id, if it has not been removed
+    fn get_source(&self, file: SourceId) -> Option<&Source> {
+        self.sources.get(file.0)?.as_ref()
+    }
 
-        Asn1Formatter {
-            tree: &source.tree,
-            source: &source.source,
-        }
-        .to_string()
+    /// Convert the CST of a file into a string, or `None` if the file has been
+    /// removed
+    pub fn print_cst(&self, file: SourceId) -> Option<String> {
+        let source = self.get_source(file)?;
+
+        Some(
+            Asn1Formatter {
+                tree: &source.tree,
+                source: &source.source,
+            }
+            .to_string(),
+        )
     }
 
-    /// Run static analysis of all the provided source files.
+    /// Run static analysis of all the provided source files.  Results from any
+    /// previous analysis are re-used for files that have not changed since.
     pub fn analysis(&mut self) -> AnalysisContext {
         AnalysisContext::new(self)
     }
 
-    /// Get the text content of a source file
-    pub fn source_text(&self, file: SourceId) -> &str {
-        &self.source(file).source
+    /// Get the concrete syntax tree of a source file, or `None` if the file
+    /// has been removed
+    pub fn tree(&self, file: SourceId) -> Option<&Asn1> {
+        Some(&self.get_source(file)?.tree)
+    }
+
+    /// Get the text content of a source file, or `None` if the file has been
+    /// removed
+    pub fn source_text(&self, file: SourceId) -> Option<&str> {
+        Some(&self.get_source(file)?.source)
+    }
+
+    /// Get the file name of a source, or `None` if the file has been removed
+    pub fn source_name(&self, file: SourceId) -> Option<&str> {
+        Some(&self.get_source(file)?.file_name)
+    }
+
+    /// Get every source file of the compiler, including the files loaded from
+    /// the include directories during analysis
+    pub fn sources(&self) -> impl Iterator<Item = SourceId> + '_ {
+        self.sources.iter().flatten().map(|source| source.id)
     }
 
-    /// Get the file name of a source
-    pub fn source_name(&self, file: SourceId) -> &str {
-        &self.source(file).file_name
+    /// Get the line and column of a byte offset within a source file, both
+    /// counted from one.  Columns are counted in characters.  Returns `None`
+    /// if the file has been removed.
+    pub fn line_column(&self, file: SourceId, offset: usize) -> Option<(usize, usize)> {
+        let text = &self.get_source(file)?.source;
+        let before = text.get(..offset).unwrap_or(text);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count() + 1;
+        Some((line, before[line_start..].chars().count() + 1))
     }
 }
 
@@ -6,6 +6,9 @@
 //! an AST, therefore an AST is also implemented as a view over this CST (in
 //! another module).
 
+mod node;
+mod visit;
+
 use std::{fmt::Display, ops::Range};
 
 use crate::{
@@ -14,6 +17,8 @@ use crate::{
     token::{Token, TokenKind},
 };
 
+pub use self::{node::*, visit::Visitor};
+
 /// A whole ASN.1 file including all modules
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Asn1 {
@@ -23,6 +28,9 @@ pub struct Asn1 {
     /// Flattened representation of all data contained within the tree
     data: Vec<TreeContent>,
 
+    /// Index of the parent of each node in `data`, `u32::MAX` for the root
+    parents: Vec<u32>,
+
     /// ID of the source file the tree was created from
     id: SourceId,
 }
@@ -127,6 +135,16 @@ pub enum Asn1Tag {
     ChoiceExtensionList,
     ChoiceExtensionItem,
 
+    // constraint
+    Constraint,
+    ElementSetSpecs,
+    ElementSetSpec,
+    SubtypeElement,
+    MultipleTypeConstraints,
+    NamedConstraint,
+    ContentsConstraint,
+    UserDefinedConstraint,
+
     // object
     ObjectClass,
     FieldSpecList,
@@ -157,19 +175,50 @@ pub enum Asn1Tag {
     SymbolsFromModuleList,
 
     // parameterized
+    ParameterList,
+    Parameter,
     ActualParameterList,
 }
 
 impl Asn1 {
     /// Create a CST from an externally constructed tree.
     pub fn new(id: SourceId, data: Vec<TreeContent>, root_idx: usize) -> Asn1 {
+        let mut parents = vec![u32::MAX; data.len()];
+        for (idx, node) in data.iter().enumerate() {
+            if let TreeContent::Tree {
+                start_node: start,
+                node_count: count,
+                ..
+            } = *node
+            {
+                for child in start..start + count {
+                    parents[child as usize] = idx as u32;
+                }
+            }
+        }
+
         Asn1 {
             root: AsnNodeId(root_idx.try_into().unwrap(), id),
             data,
+            parents,
             id,
         }
     }
 
+    /// Create a CST containing only an empty root node, used for files that
+    /// have not been parsed successfully.
+    pub fn empty(id: SourceId) -> Asn1 {
+        let root = TreeContent::Tree {
+            tag: Asn1Tag::Root,
+            start_node: 0,
+            node_count: 0,
+            byte_length: 0,
+            source_offset: 0,
+        };
+
+        Asn1::new(id, vec![root], 0)
+    }
+
     /// Create an iterator over the nested node contents of a tree node.  Returns
     /// `None` if the chosen node is a token node, not a tree node.
     pub fn iter_tree(&self, node: AsnNodeId) -> Option<CstIter> {
@@ -196,6 +245,23 @@ impl Asn1 {
         }
     }
 
+    /// Get all nodes directly contained within a tree node, including comment
+    /// tokens.  Returns an empty iterator if the chosen node is a token node.
+    pub fn children(&self, node: AsnNodeId) -> impl Iterator<Item = AsnNodeId> + '_ {
+        debug_assert_eq!(node.1, self.id);
+
+        let range = match self.data[node.0 as usize] {
+            TreeContent::Tree {
+                start_node: start,
+                node_count: count,
+                ..
+            } => start..start + count,
+            TreeContent::Token { .. } => 0..0,
+        };
+
+        range.map(|idx| AsnNodeId(idx, self.id))
+    }
+
     /// Get the tag of a provided tree node.  Returns
     /// `None` if the chosen node is a token node, not a tree node.
     pub fn tree_tag(&self, node: AsnNodeId) -> Option<Asn1Tag> {
@@ -228,18 +294,109 @@ impl Asn1 {
 
     /// Construct a diagnostic label that references a given tree node
     pub fn label(&self, node: AsnNodeId) -> Label {
+        Label::new().source(self.id).loc(self.span(node))
+    }
+
+    /// Get the byte range of the source file covered by a node
+    pub fn span(&self, node: AsnNodeId) -> Range<usize> {
         debug_assert_eq!(node.1, self.id);
 
-        let (TreeContent::Tree {
-            byte_length: length,
-            source_offset: offset,
-            ..
+        self.data[node.0 as usize].span()
+    }
+
+    /// Get the tree node that directly contains a node.  Returns `None` for
+    /// the root node.
+    pub fn parent(&self, node: AsnNodeId) -> Option<AsnNodeId> {
+        debug_assert_eq!(node.1, self.id);
+
+        match self.parents[node.0 as usize] {
+            u32::MAX => None,
+            idx => Some(AsnNodeId(idx, self.id)),
         }
-        | TreeContent::Token { length, offset, .. }) = self.data[node.0 as usize];
+    }
+
+    /// Iterate over the parent of a node, then its parent, up to and including
+    /// the root node.
+    pub fn ancestors(&self, node: AsnNodeId) -> impl Iterator<Item = AsnNodeId> + '_ {
+        std::iter::successors(self.parent(node), |&node| self.parent(node))
+    }
+
+    /// Get the next non-trivia node within the same parent node
+    pub fn next_sibling(&self, node: AsnNodeId) -> Option<AsnNodeId> {
+        let parent = self.parent(node)?;
+        self.children(parent)
+            .skip_while(|&n| n != node)
+            .skip(1)
+            .find(|&n| !self.is_trivia(n))
+    }
+
+    /// Get the previous non-trivia node within the same parent node
+    pub fn prev_sibling(&self, node: AsnNodeId) -> Option<AsnNodeId> {
+        let parent = self.parent(node)?;
+        self.children(parent)
+            .take_while(|&n| n != node)
+            .filter(|&n| !self.is_trivia(n))
+            .last()
+    }
+
+    /// Find the innermost node containing the given byte offset of the source
+    /// file.  Returns a token node if the offset is within a token, otherwise
+    /// the smallest tree node around the offset, or `None` if the offset is
+    /// not within any module.
+    pub fn node_at(&self, offset: usize) -> Option<AsnNodeId> {
+        let mut node = self.root;
+        let mut found = None;
+
+        loop {
+            let child = self.children(node).find(|&child| {
+                let span = self.span(child);
+                span.contains(&offset)
+            });
+
+            match child {
+                Some(child) => {
+                    found = Some(child);
+                    node = child;
+                }
+                None => return found,
+            }
+        }
+    }
+
+    /// Is the node a comment token
+    pub fn is_trivia(&self, node: AsnNodeId) -> bool {
+        matches!(
+            self.token(node).map(|tok| tok.kind),
+            Some(TokenKind::SingleComment | TokenKind::MultiComment)
+        )
+    }
+
+    /// Walk all nodes in the tree in source order, calling the visitor's
+    /// hooks for each node.
+    pub fn walk(&self, visitor: &mut impl Visitor) {
+        self.walk_from(self.root, visitor);
+    }
+
+    /// Walk a node and all nodes it contains in source order, calling the
+    /// visitor's hooks for each node.
+    pub fn walk_from(&self, node: AsnNodeId, visitor: &mut impl Visitor) {
+        if let Some(tok) = self.token(node) {
+            visitor.token(self, node, tok);
+            return;
+        }
+
+        let Some(tag) = self.tree_tag(node) else {
+            return;
+        };
 
-        Label::new()
-            .source(self.id)
-            .loc(offset..offset + length as usize)
+        let descend = visitor.enter(self, node, tag);
+        if visit::typed_hook(visitor, self, node, tag, true) && descend {
+            for child in self.children(node) {
+                self.walk_from(child, visitor);
+            }
+        }
+        visit::typed_hook(visitor, self, node, tag, false);
+        visitor.exit(self, node, tag);
     }
 }
 
@@ -253,12 +410,24 @@ impl TreeContent {
             offset: tok.offset,
         }
     }
+
+    /// Get the byte range of the source file covered by this node
+    pub(crate) fn span(&self) -> Range<usize> {
+        let (TreeContent::Tree {
+            byte_length: length,
+            source_offset: offset,
+            ..
+        }
+        | TreeContent::Token { length, offset, .. }) = *self;
+
+        offset..offset + length as usize
+    }
 }
 
 impl AsnNodeId {
     /// Get the source ID from a node ID
     #[inline]
-    pub(crate) fn source(&self) -> SourceId {
+    pub fn source(&self) -> SourceId {
         self.1
     }
 }
@@ -378,9 +547,11 @@ pub struct CstIter<'a> {
     tree: &'a Asn1,
 }
 
-impl CstIter<'_> {
+impl Iterator for CstIter<'_> {
+    type Item = AsnNodeId;
+
     /// Get the next non-trivia node
-    pub fn next(&mut self) -> Option<AsnNodeId> {
+    fn next(&mut self) -> Option<AsnNodeId> {
         if let Some(node) = self.peek.take() {
             return Some(node);
         }
@@ -400,7 +571,9 @@ impl CstIter<'_> {
 
         None
     }
+}
 
+impl CstIter<'_> {
     /// Try to get the next non-trivia node ID without consuming it
     pub fn peek(&mut self) -> Option<AsnNodeId> {
         if let Some(node) = self.peek {
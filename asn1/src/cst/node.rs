@@ -0,0 +1,552 @@
+//! Typed wrappers around tree nodes of the CST, providing accessors for the
+//! interesting parts of each kind of node.  The wrappers do not check that the
+//! contents of a node are valid, so all accessors return options; a node from
+//! a successfully parsed file will always have the required contents.
+
+use std::ops::Range;
+
+use crate::{
+    parser::symbols_from_modules,
+    token::{Token, TokenKind},
+};
+
+use super::{Asn1, Asn1Tag, AsnNodeId};
+
+/// A tree node with a known tag
+pub trait CstNode<'a>: Sized + Copy {
+    /// The tag of the tree nodes represented by this type
+    const TAG: Asn1Tag;
+
+    /// Wrap a node, returns `None` if the node does not have the correct tag
+    fn cast(tree: &'a Asn1, node: AsnNodeId) -> Option<Self>;
+
+    /// The tree containing this node
+    fn tree(&self) -> &'a Asn1;
+
+    /// The ID of the wrapped node
+    fn id(&self) -> AsnNodeId;
+
+    /// The byte range of the source file covered by this node
+    fn span(&self) -> Range<usize> {
+        self.tree().span(self.id())
+    }
+
+    /// The tree node that directly contains this node
+    fn parent(&self) -> Option<AsnNodeId> {
+        self.tree().parent(self.id())
+    }
+}
+
+/// Declare a wrapper type for tree nodes with the given tag
+macro_rules! cst_node {
+    ($(#[$meta:meta])* $name:ident => $tag:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name<'a> {
+            tree: &'a Asn1,
+            id: AsnNodeId,
+        }
+
+        impl<'a> CstNode<'a> for $name<'a> {
+            const TAG: Asn1Tag = Asn1Tag::$tag;
+
+            fn cast(tree: &'a Asn1, node: AsnNodeId) -> Option<Self> {
+                (tree.tree_tag(node) == Some(Self::TAG)).then_some(Self { tree, id: node })
+            }
+
+            fn tree(&self) -> &'a Asn1 {
+                self.tree
+            }
+
+            fn id(&self) -> AsnNodeId {
+                self.id
+            }
+        }
+    };
+}
+
+cst_node! {
+    /// `ModuleName DEFINITIONS ::= BEGIN ... END`
+    ModuleDefinitionNode => ModuleDefinition
+}
+
+cst_node! {
+    /// The name and object identifier of a module
+    ModuleIdentifierNode => ModuleIdentifier
+}
+
+cst_node! {
+    /// `EXPORTS a, B;` or `EXPORTS ALL;`
+    ExportsNode => Exports
+}
+
+cst_node! {
+    /// `IMPORTS a, B FROM Module;`
+    ImportsNode => Imports
+}
+
+cst_node! {
+    /// Any assignment within a module, `name ::= ...`
+    AssignmentNode => Assignment
+}
+
+cst_node! {
+    /// The part of an assignment after a type reference
+    TypeAssignmentNode => TypeAssignment
+}
+
+cst_node! {
+    /// The part of an assignment after a value reference
+    ValueAssignmentNode => ValueAssignment
+}
+
+cst_node! {
+    /// A type or value, the parser does not always know which is being parsed
+    TypeOrValueNode => TypeOrValue
+}
+
+cst_node! {
+    /// A reference to another definition or a named type, `name Type`
+    DefinedNode => Defined
+}
+
+cst_node! {
+    /// `SEQUENCE { ... }` or `SEQUENCE OF Type`
+    SequenceTypeNode => SequenceType
+}
+
+cst_node! {
+    /// `SET { ... }` or `SET OF Type`
+    SetTypeNode => SetType
+}
+
+cst_node! {
+    /// `CHOICE { ... }`
+    ChoiceTypeNode => ChoiceType
+}
+
+cst_node! {
+    /// A single component of a sequence or set type
+    ComponentTypeNode => ComponentType
+}
+
+cst_node! {
+    /// `ENUMERATED { ... }`
+    EnumeratedTypeNode => EnumeratedType
+}
+
+cst_node! {
+    /// A single item of an enumerated type
+    EnumItemNode => EnumItem
+}
+
+cst_node! {
+    /// `[tag] IMPLICIT Type`
+    PrefixTypeNode => PrefixType
+}
+
+cst_node! {
+    /// `CLASS { ... }`
+    ObjectClassNode => ObjectClass
+}
+
+cst_node! {
+    /// A single field of an information object class
+    FieldSpecNode => FieldSpec
+}
+
+cst_node! {
+    /// A constraint following a type, `(0..255)` or `(SIZE (1..10))`
+    ConstraintNode => Constraint
+}
+
+impl Asn1 {
+    /// Wrap a node in a typed wrapper, returns `None` if the node has a
+    /// different tag.
+    pub fn cast<'a, T: CstNode<'a>>(&'a self, node: AsnNodeId) -> Option<T> {
+        T::cast(self, node)
+    }
+
+    /// Find the closest ancestor of a node with the given type, e.g. to find
+    /// the assignment containing a node returned by [`Asn1::node_at`].
+    pub fn ancestor<'a, T: CstNode<'a>>(&'a self, node: AsnNodeId) -> Option<T> {
+        self.ancestors(node).find_map(|node| T::cast(self, node))
+    }
+
+    /// All module definitions within the file
+    pub fn modules(&self) -> impl Iterator<Item = ModuleDefinitionNode<'_>> {
+        children(self, self.root)
+    }
+}
+
+impl<'a> ModuleDefinitionNode<'a> {
+    /// The module identifier
+    pub fn identifier(&self) -> Option<ModuleIdentifierNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// The name of the module
+    pub fn name(&self) -> Option<Token> {
+        self.identifier()?.name()
+    }
+
+    /// The exports list, if present
+    pub fn exports(&self) -> Option<ExportsNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// The imports list, if present
+    pub fn imports(&self) -> Option<ImportsNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// All assignments within the module body
+    pub fn assignments(&self) -> impl Iterator<Item = AssignmentNode<'a>> + 'a {
+        children(self.tree, self.id)
+    }
+}
+
+impl ModuleIdentifierNode<'_> {
+    /// The name of the module
+    pub fn name(&self) -> Option<Token> {
+        token(self.tree, self.id, &[TokenKind::TypeOrModuleRef])
+    }
+
+    /// The object identifier after the name of the module, if present
+    pub fn definitive_oid(&self) -> Option<AsnNodeId> {
+        find(self.tree, self.id, Asn1Tag::DefinitiveOID)
+    }
+}
+
+impl ExportsNode<'_> {
+    /// Is everything exported with `EXPORTS ALL`
+    pub fn is_all(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwAll]).is_some()
+    }
+
+    /// The names of all exported symbols
+    pub fn symbols(&self) -> Vec<Token> {
+        let Some(list) = find(self.tree, self.id, Asn1Tag::SymbolList) else {
+            return vec![];
+        };
+
+        self.tree
+            .children(list)
+            .filter_map(|symbol| find(self.tree, symbol, Asn1Tag::Reference))
+            .filter_map(|reference| {
+                token(
+                    self.tree,
+                    reference,
+                    &[TokenKind::TypeOrModuleRef, TokenKind::ValueRefOrIdent],
+                )
+            })
+            .collect()
+    }
+}
+
+impl ImportsNode<'_> {
+    /// The names of all modules that symbols are imported from
+    pub fn modules(&self) -> Vec<Token> {
+        let Some(list) = find(self.tree, self.id, Asn1Tag::SymbolsFromModuleList) else {
+            return vec![];
+        };
+
+        let tokens: Vec<_> = self
+            .tree
+            .children(list)
+            .filter_map(|node| self.tree.token(node))
+            .filter(|tok| !matches!(tok.kind, TokenKind::SingleComment | TokenKind::MultiComment))
+            .collect();
+        let kinds: Vec<_> = tokens.iter().map(|tok| tok.kind).collect();
+
+        symbols_from_modules(&kinds)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|group| tokens[group.module])
+            .collect()
+    }
+}
+
+impl<'a> AssignmentNode<'a> {
+    /// The name being assigned to
+    pub fn name(&self) -> Option<Token> {
+        token(
+            self.tree,
+            self.id,
+            &[TokenKind::TypeOrModuleRef, TokenKind::ValueRefOrIdent],
+        )
+    }
+
+    /// The contents of the assignment if it is a type assignment
+    pub fn type_assignment(&self) -> Option<TypeAssignmentNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// The contents of the assignment if it is a value assignment
+    pub fn value_assignment(&self) -> Option<ValueAssignmentNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+}
+
+impl<'a> TypeAssignmentNode<'a> {
+    /// The type being assigned
+    pub fn ty(&self) -> Option<TypeOrValueNode<'a>> {
+        after_assignment(self.tree, self.id)
+    }
+}
+
+impl<'a> ValueAssignmentNode<'a> {
+    /// The type of the value
+    pub fn ty(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// The value being assigned
+    pub fn value(&self) -> Option<TypeOrValueNode<'a>> {
+        after_assignment(self.tree, self.id)
+    }
+}
+
+impl<'a> TypeOrValueNode<'a> {
+    /// The node contained within this type or value.  This will be a tree node
+    /// for most types and values or a token for simple types and values, such
+    /// as `BOOLEAN` or `TRUE`.
+    pub fn inner(&self) -> Option<AsnNodeId> {
+        self.tree
+            .children(self.id)
+            .find(|&node| !self.tree.is_trivia(node))
+    }
+
+    /// The tag of the contained node if it is a tree node
+    pub fn tag(&self) -> Option<Asn1Tag> {
+        self.tree.tree_tag(self.inner()?)
+    }
+
+    /// The contained token, if this is a simple type or value
+    pub fn token(&self) -> Option<Token> {
+        self.tree.token(self.inner()?)
+    }
+
+    /// The constraints following a type, in the order they are applied
+    pub fn constraints(&self) -> Vec<ConstraintNode<'a>> {
+        children(self.tree, self.id).collect()
+    }
+}
+
+impl<'a> DefinedNode<'a> {
+    /// The referenced name, or the name of a named type
+    pub fn name(&self) -> Option<Token> {
+        token(
+            self.tree,
+            self.id,
+            &[TokenKind::TypeOrModuleRef, TokenKind::ValueRefOrIdent],
+        )
+    }
+
+    /// The type of a named type, `name Type`
+    pub fn ty(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+}
+
+impl<'a> SequenceTypeNode<'a> {
+    /// Is this a `SEQUENCE OF` type
+    pub fn is_of(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwOf]).is_some()
+    }
+
+    /// The element type of a `SEQUENCE OF` type
+    pub fn element(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// The constraint written before `OF`, `SEQUENCE SIZE (1..5) OF Type`
+    pub fn of_constraint(&self) -> Option<ConstraintNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// All components of the sequence, including extension additions
+    pub fn components(&self) -> Vec<ComponentTypeNode<'a>> {
+        descendants(self.tree, self.id)
+    }
+}
+
+impl<'a> SetTypeNode<'a> {
+    /// Is this a `SET OF` type
+    pub fn is_of(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwOf]).is_some()
+    }
+
+    /// The element type of a `SET OF` type
+    pub fn element(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// The constraint written before `OF`, `SET SIZE (1..5) OF Type`
+    pub fn of_constraint(&self) -> Option<ConstraintNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// All components of the set, including extension additions
+    pub fn components(&self) -> Vec<ComponentTypeNode<'a>> {
+        descendants(self.tree, self.id)
+    }
+}
+
+impl<'a> ChoiceTypeNode<'a> {
+    /// All alternatives of the choice, including extension additions.  Each
+    /// alternative should contain a named type.
+    pub fn alternatives(&self) -> Vec<TypeOrValueNode<'a>> {
+        descendants(self.tree, self.id)
+    }
+}
+
+impl<'a> ComponentTypeNode<'a> {
+    /// The name of the component, `None` for `COMPONENTS OF Type`
+    pub fn name(&self) -> Option<Token> {
+        token(self.tree, self.id, &[TokenKind::ValueRefOrIdent])
+    }
+
+    /// The type of the component
+    pub fn ty(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+
+    /// Is this a `COMPONENTS OF Type` component
+    pub fn is_components_of(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwComponents]).is_some()
+    }
+
+    /// Is the component marked as `OPTIONAL`
+    pub fn is_optional(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwOptional]).is_some()
+    }
+
+    /// The default value of the component, if present
+    pub fn default(&self) -> Option<TypeOrValueNode<'a>> {
+        token(self.tree, self.id, &[TokenKind::KwDefault])?;
+        children(self.tree, self.id).nth(1)
+    }
+}
+
+impl<'a> EnumeratedTypeNode<'a> {
+    /// All items of the enumeration, including extension additions
+    pub fn items(&self) -> Vec<EnumItemNode<'a>> {
+        descendants(self.tree, self.id)
+    }
+}
+
+impl<'a> EnumItemNode<'a> {
+    /// The name of the item
+    pub fn name(&self) -> Option<Token> {
+        token(self.tree, self.id, &[TokenKind::ValueRefOrIdent])
+    }
+
+    /// The explicit value of the item, `name(value)`, if present
+    pub fn value(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).next()
+    }
+}
+
+impl<'a> PrefixTypeNode<'a> {
+    /// Is the prefix marked as `IMPLICIT`
+    pub fn is_implicit(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwImplicit]).is_some()
+    }
+
+    /// Is the prefix marked as `EXPLICIT`
+    pub fn is_explicit(&self) -> bool {
+        token(self.tree, self.id, &[TokenKind::KwExplicit]).is_some()
+    }
+
+    /// The type being prefixed
+    pub fn ty(&self) -> Option<TypeOrValueNode<'a>> {
+        children(self.tree, self.id).last()
+    }
+}
+
+impl<'a> ObjectClassNode<'a> {
+    /// All fields of the class
+    pub fn fields(&self) -> Vec<FieldSpecNode<'a>> {
+        find(self.tree, self.id, Asn1Tag::FieldSpecList)
+            .map(|list| children(self.tree, list).collect())
+            .unwrap_or_default()
+    }
+
+    /// The `WITH SYNTAX` specification, if present
+    pub fn syntax(&self) -> Option<AsnNodeId> {
+        find(self.tree, self.id, Asn1Tag::SyntaxSpec)
+    }
+}
+
+impl FieldSpecNode<'_> {
+    /// The name of the field, `&field`
+    pub fn name(&self) -> Option<Token> {
+        let spec = self.tree.children(self.id).next()?;
+        token(
+            self.tree,
+            spec,
+            &[TokenKind::TypeField, TokenKind::ValueField],
+        )
+    }
+
+    /// Is the field marked as `OPTIONAL`
+    pub fn is_optional(&self) -> bool {
+        self.optionality()
+            .is_some_and(|spec| token(self.tree, spec, &[TokenKind::KwOptional]).is_some())
+    }
+
+    /// Does the field have a default value
+    pub fn has_default(&self) -> bool {
+        self.optionality()
+            .is_some_and(|spec| token(self.tree, spec, &[TokenKind::KwDefault]).is_some())
+    }
+
+    /// The optionality specification of the field
+    fn optionality(&self) -> Option<AsnNodeId> {
+        let spec = self.tree.children(self.id).next()?;
+        find(self.tree, spec, Asn1Tag::OptionalitySpec)
+    }
+}
+
+/// All direct children of a node with the given type
+fn children<'a, T: CstNode<'a>>(tree: &'a Asn1, node: AsnNodeId) -> impl Iterator<Item = T> + 'a {
+    tree.children(node)
+        .filter_map(move |child| T::cast(tree, child))
+}
+
+/// Find all nodes of the given type contained within a node, without looking
+/// inside of nested types or values.
+fn descendants<'a, T: CstNode<'a>>(tree: &'a Asn1, node: AsnNodeId) -> Vec<T> {
+    let mut result = vec![];
+
+    for child in tree.children(node) {
+        if let Some(child) = T::cast(tree, child) {
+            result.push(child);
+        } else if !matches!(tree.tree_tag(child), None | Some(Asn1Tag::TypeOrValue)) {
+            result.extend(descendants::<T>(tree, child));
+        }
+    }
+
+    result
+}
+
+/// Find the first direct child of a node with the given tag
+fn find(tree: &Asn1, node: AsnNodeId, tag: Asn1Tag) -> Option<AsnNodeId> {
+    tree.children(node)
+        .find(|&child| tree.tree_tag(child) == Some(tag))
+}
+
+/// Find the first direct child token of a node with one of the given kinds
+fn token(tree: &Asn1, node: AsnNodeId, kinds: &[TokenKind]) -> Option<Token> {
+    tree.children(node)
+        .filter_map(|child| tree.token(child))
+        .find(|tok| kinds.contains(&tok.kind))
+}
+
+/// Find the type or value after the `::=` token within a node
+fn after_assignment<'a>(tree: &'a Asn1, node: AsnNodeId) -> Option<TypeOrValueNode<'a>> {
+    tree.children(node)
+        .skip_while(|&child| tree.token(child).map(|tok| tok.kind) != Some(TokenKind::Assignment))
+        .find_map(|child| TypeOrValueNode::cast(tree, child))
+}
@@ -0,0 +1,102 @@
+use crate::token::Token;
+
+use super::{
+    Asn1, Asn1Tag, AsnNodeId, AssignmentNode, ChoiceTypeNode, ComponentTypeNode, ConstraintNode,
+    CstNode, DefinedNode, EnumItemNode, EnumeratedTypeNode, ExportsNode, FieldSpecNode,
+    ImportsNode, ModuleDefinitionNode, ModuleIdentifierNode, ObjectClassNode, PrefixTypeNode,
+    SequenceTypeNode, SetTypeNode, TypeAssignmentNode, TypeOrValueNode, ValueAssignmentNode,
+};
+
+/// Declare the visitor trait, with a pair of hooks for each typed node
+/// wrapper in addition to the untyped hooks, and the function calling the
+/// typed hooks for a node.
+macro_rules! visitor {
+    ($($node:ident: $visit:ident, $leave:ident;)*) => {
+        /// Callbacks used when walking over a CST with [`Asn1::walk`].  Every
+        /// method has a default implementation that does nothing, so visitors
+        /// only need to implement the hooks they are interested in.
+        ///
+        /// Each tree node with a typed wrapper is passed to its `visit_` hook
+        /// after `enter`, and to its `leave_` hook before `exit`.
+        pub trait Visitor {
+            /// Called before visiting the contents of a tree node.  Return
+            /// false to skip over the contents of the node, `exit` will still
+            /// be called.
+            fn enter(&mut self, tree: &Asn1, node: AsnNodeId, tag: Asn1Tag) -> bool {
+                let _ = (tree, node, tag);
+                true
+            }
+
+            /// Called after visiting the contents of a tree node
+            fn exit(&mut self, tree: &Asn1, node: AsnNodeId, tag: Asn1Tag) {
+                let _ = (tree, node, tag);
+            }
+
+            /// Called for every token in the tree, including comments
+            fn token(&mut self, tree: &Asn1, node: AsnNodeId, token: Token) {
+                let _ = (tree, node, token);
+            }
+
+            $(
+                #[doc = concat!("Called before visiting the contents of a [`", stringify!($node), "`].")]
+                /// Return false to skip over its contents.
+                fn $visit(&mut self, node: $node<'_>) -> bool {
+                    let _ = node;
+                    true
+                }
+
+                #[doc = concat!("Called after visiting the contents of a [`", stringify!($node), "`]")]
+                fn $leave(&mut self, node: $node<'_>) {
+                    let _ = node;
+                }
+            )*
+        }
+
+        /// Call the typed hook for entering or leaving a node, if it has a typed
+        /// wrapper.  Returns false if the contents of the node are to be
+        /// skipped.
+        pub(super) fn typed_hook(
+            visitor: &mut impl Visitor,
+            tree: &Asn1,
+            node: AsnNodeId,
+            tag: Asn1Tag,
+            entering: bool,
+        ) -> bool {
+            $(
+                if tag == <$node as CstNode>::TAG {
+                    let Some(node) = $node::cast(tree, node) else {
+                        return true;
+                    };
+                    if entering {
+                        return visitor.$visit(node);
+                    }
+                    visitor.$leave(node);
+                    return true;
+                }
+            )*
+            true
+        }
+    };
+}
+
+visitor! {
+    ModuleDefinitionNode: visit_module_definition, leave_module_definition;
+    ModuleIdentifierNode: visit_module_identifier, leave_module_identifier;
+    ExportsNode: visit_exports, leave_exports;
+    ImportsNode: visit_imports, leave_imports;
+    AssignmentNode: visit_assignment, leave_assignment;
+    TypeAssignmentNode: visit_type_assignment, leave_type_assignment;
+    ValueAssignmentNode: visit_value_assignment, leave_value_assignment;
+    TypeOrValueNode: visit_type_or_value, leave_type_or_value;
+    DefinedNode: visit_defined, leave_defined;
+    SequenceTypeNode: visit_sequence_type, leave_sequence_type;
+    SetTypeNode: visit_set_type, leave_set_type;
+    ChoiceTypeNode: visit_choice_type, leave_choice_type;
+    ComponentTypeNode: visit_component_type, leave_component_type;
+    EnumeratedTypeNode: visit_enumerated_type, leave_enumerated_type;
+    EnumItemNode: visit_enum_item, leave_enum_item;
+    PrefixTypeNode: visit_prefix_type, leave_prefix_type;
+    ObjectClassNode: visit_object_class, leave_object_class;
+    FieldSpecNode: visit_field_spec, leave_field_spec;
+    ConstraintNode: visit_constraint, leave_constraint;
+}
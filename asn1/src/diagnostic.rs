@@ -1,6 +1,6 @@
 use std::{error::Error, fmt::Display, ops::Range};
 
-use crate::compiler::SourceId;
+use crate::{compiler::SourceId, AsnCompiler};
 
 /// Any kind of error reported by the compiler
 #[derive(Debug, Clone)]
@@ -69,6 +69,11 @@ impl Diagnostic {
         Self::new(Level::Error, code.into())
     }
 
+    /// Create a warning diagnostic
+    pub(crate) fn warning(code: impl Into<String>) -> Self {
+        Self::new(Level::Warning, code.into())
+    }
+
     /// Set the descriptive name of an error
     pub(crate) fn name(self, value: impl Into<String>) -> Self {
         Self {
@@ -148,8 +153,45 @@ impl Display for Diagnostic {
 
 impl Error for Diagnostic {}
 
+impl AsnCompiler {
+    /// Write a diagnostic as plain text, with the file name, line and column
+    /// of each label located in a source file, for output that cannot show
+    /// the source text such as build script errors.
+    pub fn render_diagnostic(&self, diagnostic: &Diagnostic) -> String {
+        let level = match diagnostic.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Note => "note",
+        };
+        let mut text = format!("{level}[{}]: {}", diagnostic.error_code, diagnostic.name);
+
+        for label in &diagnostic.labels {
+            let start = label.location.as_ref().map_or(0, |location| location.start);
+            let located = label.source.and_then(|source| {
+                Some((self.source_name(source)?, self.line_column(source, start)?))
+            });
+            let Some((name, (line, column))) = located else {
+                text.push_str(&format!("\n  = note: {}", label.message));
+                continue;
+            };
+            text.push_str(&format!("\n  --> {name}:{line}:{column}"));
+            if !label.message.is_empty() {
+                text.push_str(&format!(": {}", label.message));
+            }
+        }
+
+        text
+    }
+}
+
 impl From<&str> for Label {
     fn from(value: &str) -> Self {
         Label::new().message(value)
     }
 }
+
+impl From<String> for Label {
+    fn from(value: String) -> Self {
+        Label::new().message(value)
+    }
+}
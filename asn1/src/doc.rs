@@ -0,0 +1,613 @@
+//! Browsable documentation of analysed modules, as HTML or Markdown pages.
+//! Each module has a page with an anchor for each of its assignments, and an
+//! index page lists the modules and the tree of defined object identifiers.
+
+use crate::{
+    analysis::{AnalysisContext, Bounds, Environment, Oid},
+    ast::{ComponentType, Extensible, TagClass, TagDefault, TagKind, Type, TypeOrValue},
+    cst::AsnNodeId,
+};
+
+/// The format of generated documentation pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum DocFormat {
+    /// Standalone HTML pages
+    #[default]
+    Html,
+
+    /// Markdown pages.  Definitions are written as inline HTML so that the
+    /// references within them can be links.
+    Markdown,
+}
+
+/// A page of generated documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocPage {
+    /// File name of the page, `index` or the name of the module, with the
+    /// extension of the format
+    pub file: String,
+
+    /// Content of the page
+    pub content: String,
+}
+
+impl AnalysisContext<'_> {
+    /// Generate documentation pages for every module, and an index page.
+    /// References within definitions link to the assignments they refer to,
+    /// and the comments before each assignment are its description.  Types
+    /// are summarised with the values permitted by their constraints.
+    pub fn documentation(&self, format: DocFormat) -> Vec<DocPage> {
+        let docs = Documentation {
+            analysis: self,
+            format,
+        };
+
+        let mut modules: Vec<_> = self.modules().collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut pages = vec![docs.index(&modules)];
+        pages.extend(modules.into_iter().map(|module| docs.module(module)));
+        pages
+    }
+}
+
+/// Generate documentation pages from a successful analysis context
+struct Documentation<'a> {
+    analysis: &'a AnalysisContext<'a>,
+    format: DocFormat,
+}
+
+impl Documentation<'_> {
+    /// Generate the index page, listing the modules and every defined object
+    /// identifier as a tree
+    fn index(&self, modules: &[&Environment]) -> DocPage {
+        let mut body = self.heading(1, None, "ASN.1 modules");
+
+        let items: Vec<_> = modules
+            .iter()
+            .map(|module| {
+                let link = self.link(&self.file(&module.name), &self.code(&module.name));
+                match &module.oid {
+                    Some(oid) => format!("{link} {}", self.code(&oid.to_asn1())),
+                    None => link,
+                }
+            })
+            .collect();
+        body.push_str(&self.list(items.into_iter().map(|item| (0, item))));
+
+        let registry = self.analysis.oid_registry();
+        if !registry.is_empty() {
+            body.push_str(&self.heading(2, Some("oids"), "Object identifiers"));
+
+            // identifiers are in order, so the ancestors of each are just
+            // before it
+            let mut ancestors: Vec<&Oid> = vec![];
+            let mut items = vec![];
+            for (oid, entries) in registry.iter() {
+                while ancestors.last().is_some_and(|a| !a.is_prefix_of(oid)) {
+                    ancestors.pop();
+                }
+
+                let names: Vec<_> = entries
+                    .iter()
+                    .map(|entry| match &entry.name {
+                        Some(name) => self.link(
+                            &format!("{}#{name}", self.file(&entry.module)),
+                            &self.code(&format!("{}.{name}", entry.module)),
+                        ),
+                        None => self.link(&self.file(&entry.module), &self.code(&entry.module)),
+                    })
+                    .collect();
+                let text = format!("{} {}", self.code(&oid.to_asn1()), names.join(", "));
+                items.push((ancestors.len(), text));
+                ancestors.push(oid);
+            }
+            body.push_str(&self.list(items.into_iter()));
+        }
+
+        self.page("index", "ASN.1 modules", body)
+    }
+
+    /// Generate the page of a module
+    fn module(&self, module: &Environment) -> DocPage {
+        let mut body = self.heading(1, None, &format!("Module {}", self.code(&module.name)));
+
+        let mut facts = vec![];
+        if let Some(oid) = &module.oid {
+            facts.push(format!(
+                "Object identifier: {}",
+                self.code(&format!("{} ({oid})", oid.to_asn1()))
+            ));
+        }
+        if let Some(iri) = &module.iri {
+            facts.push(format!("IRI: {}", self.code(&iri.to_string())));
+        }
+        let tags = match module.tag_default {
+            TagDefault::Automatic => "AUTOMATIC",
+            TagDefault::Implicit => "IMPLICIT",
+            TagDefault::Explicit => "EXPLICIT",
+        };
+        facts.push(format!("Tags: {}", self.code(tags)));
+        if module.extensibility_implied {
+            facts.push("Extensibility implied".into());
+        }
+        body.push_str(&self.list(facts.into_iter().map(|fact| (0, fact))));
+
+        if !module.imports.is_empty() {
+            body.push_str(&self.heading(2, Some("imports"), "Imports"));
+            let items = module.imports.iter().map(|import| {
+                let symbols: Vec<_> = import
+                    .symbols
+                    .iter()
+                    .map(|symbol| match self.analysis.lookup(module, symbol) {
+                        Some((from, _)) => {
+                            self.link(&self.href(module, &from.name, symbol), &self.code(symbol))
+                        }
+                        None => self.code(symbol),
+                    })
+                    .collect();
+                let from = self.link(&self.file(&import.module), &self.code(&import.module));
+                (0, format!("From {from}: {}", symbols.join(", ")))
+            });
+            body.push_str(&self.list(items));
+        }
+
+        let mut assignments: Vec<_> = module.variables.iter().collect();
+        assignments.sort_by_key(|(_, var)| var.id);
+        if !assignments.is_empty() {
+            body.push_str(&self.heading(2, Some("assignments"), "Assignments"));
+        }
+        for (name, var) in assignments {
+            body.push_str(&self.heading(3, Some(name), &self.code(name)));
+            body.push_str(&self.description(module, var.id));
+            body.push_str(&self.definition(module, var.id));
+
+            if let Some(oid) = module.oids.get(name) {
+                let oid = self.code(&format!("{} ({oid})", oid.to_asn1()));
+                body.push_str(&self.paragraph(&format!("Object identifier: {oid}"), true));
+            }
+            if let Some(expanded) = module.expanded.get(name) {
+                if let TypeOrValue::Type(ty) = &*expanded.value {
+                    body.push_str(&self.summary(module, ty, expanded.value.id));
+                }
+            }
+        }
+
+        let title = format!("Module {}", module.name);
+        self.page(&module.name, &title, body)
+    }
+
+    /// Write the comments documenting a name as paragraphs
+    fn description(&self, module: &Environment, name: AsnNodeId) -> String {
+        let Some(comments) = module.comments.get(&name) else {
+            return String::new();
+        };
+
+        let lines: Vec<_> = comments.lines().collect();
+        let paragraphs = lines.split(|line| line.is_empty());
+        paragraphs
+            .map(|paragraph| self.paragraph(&paragraph.join(" "), false))
+            .collect()
+    }
+
+    /// Write the source text of an assignment, without the comments before
+    /// it, with a link for each reference to another assignment
+    fn definition(&self, module: &Environment, name: AsnNodeId) -> String {
+        let source = self.analysis.source(name.source());
+        let tree = &source.tree;
+        let start = tree.span(name).start;
+        let end = tree
+            .parent(name)
+            .map_or(start, |parent| tree.span(parent).end);
+
+        let mut references: Vec<_> = module
+            .references
+            .iter()
+            .filter(|(node, _)| node.source() == name.source())
+            .map(|(node, definition)| (tree.span(*node), definition))
+            .filter(|(span, _)| start <= span.start && span.end <= end)
+            .collect();
+        references.sort_by_key(|(span, _)| span.start);
+
+        let mut result = String::from("<pre><code>");
+        let mut position = start;
+        for (span, definition) in references {
+            if span.start < position {
+                continue;
+            }
+            result.push_str(&escape(&source.source[position..span.start]));
+            let href = self.href(module, &definition.module, &definition.name);
+            let text = escape(&source.source[span.clone()]);
+            result.push_str(&format!("<a href=\"{}\">{text}</a>", escape(&href)));
+            position = span.end;
+        }
+        result.push_str(&escape(&source.source[position..end]));
+        result.push_str("</code></pre>\n");
+        if self.format == DocFormat::Markdown {
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Summarise the tags and constraints of a type, given its node ID, and
+    /// of its components or alternatives
+    fn summary(&self, module: &Environment, ty: &Type, id: AsnNodeId) -> String {
+        let mut result = String::new();
+        if let Some(tag) = self.tag(module, ty) {
+            result.push_str(&self.paragraph(&format!("Tag: {}", self.code(&tag)), true));
+        }
+        let constraints = self.constraints(module, ty, id);
+        if !constraints.is_empty() {
+            let text = format!("Constraints: {constraints}");
+            result.push_str(&self.paragraph(&text, true));
+        }
+
+        let rows = match ty {
+            Type::Sequence(components) | Type::Set(components) => {
+                let automatic = module.automatic_tags(components, |c| &c.ty().value);
+                rows(components, |index, component| {
+                    let ComponentType::Named(component) = component else {
+                        return None;
+                    };
+                    let presence = match (&component.default, component.optional) {
+                        (Some(default), _) => self.code(&format!("DEFAULT {}", **default)),
+                        (None, true) => self.code("OPTIONAL"),
+                        (None, false) => String::new(),
+                    };
+                    let tag = self.item_tag(module, &component.ty.value, automatic, index);
+                    Some([
+                        self.code(&component.name),
+                        self.type_link(module, &component.ty.value),
+                        self.constraints(module, &component.ty, component.ty.id),
+                        tag,
+                        presence,
+                        self.comment(module, component.name.id),
+                    ])
+                })
+            }
+            Type::Choice(alternatives) => {
+                let automatic = module.automatic_tags(alternatives, |a| &a.ty.value);
+                rows(alternatives, |index, alternative| {
+                    Some([
+                        self.code(&alternative.name),
+                        self.type_link(module, &alternative.ty.value),
+                        self.constraints(module, &alternative.ty, alternative.ty.id),
+                        self.item_tag(module, &alternative.ty.value, automatic, index),
+                        String::new(),
+                        self.comment(module, alternative.name.id),
+                    ])
+                })
+            }
+            _ => return result,
+        };
+
+        let header = [
+            "Identifier",
+            "Type",
+            "Constraints",
+            "Tag",
+            "Presence",
+            "Description",
+        ];
+        let header = header.map(String::from);
+        result.push_str(&self.table(header, rows));
+        result
+    }
+
+    /// Describe the values of a type permitted by its constraints, including
+    /// those of the types it refers to, in the notation of constraints
+    fn constraints(&self, module: &Environment, ty: &Type, id: AsnNodeId) -> String {
+        let constraints = self.analysis.constraints_of(module, ty, id, true);
+        let mut parts = vec![];
+        if let Some(values) = &constraints.value {
+            parts.push(bounds(values, |n| n.to_string()));
+        }
+        if let Some(sizes) = &constraints.size {
+            parts.push(format!("SIZE ({})", bounds(sizes, |n| n.to_string())));
+        }
+        if let Some(alphabet) = &constraints.alphabet {
+            let text = bounds(alphabet, |c| quote(&c.to_string()));
+            parts.push(format!("FROM ({text})"));
+        }
+        for pattern in &constraints.patterns {
+            parts.push(format!("PATTERN {}", quote(pattern)));
+        }
+        for settings in &constraints.settings {
+            parts.push(format!("SETTINGS {}", quote(settings)));
+        }
+
+        let parts: Vec<_> = parts.iter().map(|part| self.code(part)).collect();
+        parts.join(", ")
+    }
+
+    /// Get the comments documenting a name as a single line of text
+    fn comment(&self, module: &Environment, name: AsnNodeId) -> String {
+        let comments = module.comments.get(&name).map_or("", String::as_str);
+        let text = comments.split_whitespace().collect::<Vec<_>>().join(" ");
+        match self.format {
+            DocFormat::Html => escape(&text),
+            DocFormat::Markdown => escape(&text).replace('|', "\\|"),
+        }
+    }
+
+    /// Describe the outermost tag of a type, such as `[APPLICATION 1]
+    /// IMPLICIT` or the universal tag of a built-in type.  Choice types and
+    /// open types do not have a tag of their own.
+    fn tag(&self, module: &Environment, ty: &Type) -> Option<String> {
+        let mut module = module;
+        let mut ty = ty;
+
+        // references that loop are reported by analysis, so give up on them
+        for _ in 0..256 {
+            match ty {
+                Type::Tagged(tagged) => {
+                    let class = match tagged.class {
+                        TagClass::Universal => "UNIVERSAL ",
+                        TagClass::Application => "APPLICATION ",
+                        TagClass::Private => "PRIVATE ",
+                        TagClass::ContextSpecific => "",
+                    };
+                    let explicit = match tagged.kind {
+                        TagKind::Explicit => true,
+                        TagKind::Implicit => false,
+                        TagKind::Default => {
+                            module.tag_default == TagDefault::Explicit
+                                || self.tag(module, &tagged.ty).is_none()
+                        }
+                    };
+                    let kind = if explicit { "EXPLICIT" } else { "IMPLICIT" };
+                    return Some(format!("[{class}{}] {kind}", *tagged.number));
+                }
+                Type::Reference(reference) => {
+                    let (definition, var) = self.analysis.resolve_expanded(module, reference)?;
+                    let TypeOrValue::Type(inner) = &*var.value else {
+                        return None;
+                    };
+                    module = definition;
+                    ty = inner;
+                }
+                ty => {
+                    return ty
+                        .universal_tag()
+                        .map(|number| format!("[UNIVERSAL {number}]"))
+                }
+            }
+        }
+        None
+    }
+
+    /// Describe the tag of a component or alternative, which is given by its
+    /// position when tagged automatically
+    fn item_tag(&self, module: &Environment, ty: &Type, automatic: bool, index: usize) -> String {
+        if automatic {
+            let kind = match self.tag(module, ty) {
+                Some(_) => "IMPLICIT",
+                None => "EXPLICIT",
+            };
+            return format!("{} (automatic)", self.code(&format!("[{index}] {kind}")));
+        }
+
+        match self.tag(module, ty) {
+            Some(tag) => self.code(&tag),
+            None => String::new(),
+        }
+    }
+
+    /// Write a type without its tags, linked to its assignment if it is a
+    /// reference to one
+    fn type_link(&self, module: &Environment, mut ty: &Type) -> String {
+        while let Type::Tagged(tagged) = ty {
+            ty = &tagged.ty;
+        }
+
+        if let Type::Reference(reference) = ty {
+            let definition = self.analysis.resolve_expanded(module, reference);
+            // instances of parameterized types are not assignments of their own
+            if let Some((definition, _)) =
+                definition.filter(|(d, _)| !d.instances.contains_key(&reference.name.value))
+            {
+                let href = self.href(module, &definition.name, &reference.name);
+                return self.link(&href, &self.code(&reference.to_string()));
+            }
+        }
+        self.code(&ty.to_string())
+    }
+
+    /// Get the file name of the page of a module
+    fn file(&self, module: &str) -> String {
+        match self.format {
+            DocFormat::Html => format!("{module}.html"),
+            DocFormat::Markdown => format!("{module}.md"),
+        }
+    }
+
+    /// Get the link from the page of one module to an assignment
+    fn href(&self, from: &Environment, module: &str, name: &str) -> String {
+        if from.name == module {
+            format!("#{name}")
+        } else {
+            format!("{}#{name}", self.file(module))
+        }
+    }
+
+    /// Wrap the body of a page with the page header
+    fn page(&self, name: &str, title: &str, body: String) -> DocPage {
+        let content = match self.format {
+            DocFormat::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+                 <style>\n{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+                escape(title)
+            ),
+            DocFormat::Markdown => body,
+        };
+        DocPage {
+            file: self.file(name),
+            content,
+        }
+    }
+
+    fn heading(&self, level: usize, anchor: Option<&str>, text: &str) -> String {
+        match (self.format, anchor) {
+            (DocFormat::Html, Some(anchor)) => {
+                format!("<h{level} id=\"{}\">{text}</h{level}>\n", escape(anchor))
+            }
+            (DocFormat::Html, None) => format!("<h{level}>{text}</h{level}>\n"),
+            (DocFormat::Markdown, Some(anchor)) => format!(
+                "<a id=\"{}\"></a>\n\n{} {text}\n\n",
+                escape(anchor),
+                "#".repeat(level)
+            ),
+            (DocFormat::Markdown, None) => format!("{} {text}\n\n", "#".repeat(level)),
+        }
+    }
+
+    /// Write a paragraph, either of markup from this module or of plain text
+    fn paragraph(&self, text: &str, markup: bool) -> String {
+        match (self.format, markup) {
+            (DocFormat::Html, true) => format!("<p>{text}</p>\n"),
+            (DocFormat::Html, false) => format!("<p>{}</p>\n", escape(text)),
+            (DocFormat::Markdown, true) => format!("{text}\n\n"),
+            (DocFormat::Markdown, false) => format!("{}\n\n", escape(text)),
+        }
+    }
+
+    /// Write a list, with the nesting depth of each item
+    fn list(&self, items: impl Iterator<Item = (usize, String)>) -> String {
+        let mut result = String::new();
+        match self.format {
+            DocFormat::Html => {
+                result.push_str("<ul>\n");
+                for (depth, item) in items {
+                    match depth {
+                        0 => result.push_str(&format!("<li>{item}</li>\n")),
+                        _ => result.push_str(&format!(
+                            "<li style=\"margin-left: {}em\">{item}</li>\n",
+                            depth * 2
+                        )),
+                    }
+                }
+                result.push_str("</ul>\n");
+            }
+            DocFormat::Markdown => {
+                for (depth, item) in items {
+                    result.push_str(&format!("{}- {item}\n", "  ".repeat(depth)));
+                }
+                result.push('\n');
+            }
+        }
+        result
+    }
+
+    fn table<const N: usize>(&self, header: [String; N], rows: Vec<[String; N]>) -> String {
+        match self.format {
+            DocFormat::Html => {
+                let row = |cell: &str, row: [String; N]| {
+                    let cells: String = row
+                        .iter()
+                        .map(|c| format!("<{cell}>{c}</{cell}>"))
+                        .collect();
+                    format!("<tr>{cells}</tr>\n")
+                };
+                let mut result = String::from("<table>\n");
+                result.push_str(&row("th", header));
+                for cells in rows {
+                    result.push_str(&row("td", cells));
+                }
+                result.push_str("</table>\n");
+                result
+            }
+            DocFormat::Markdown => {
+                let row = |row: [String; N]| format!("| {} |\n", row.join(" | "));
+                let mut result = row(header);
+                result.push_str(&format!("|{}\n", " --- |".repeat(N)));
+                for cells in rows {
+                    result.push_str(&row(cells));
+                }
+                result.push('\n');
+                result
+            }
+        }
+    }
+
+    fn link(&self, href: &str, text: &str) -> String {
+        match self.format {
+            DocFormat::Html => format!("<a href=\"{}\">{text}</a>", escape(href)),
+            DocFormat::Markdown => format!("[{text}]({href})"),
+        }
+    }
+
+    fn code(&self, text: &str) -> String {
+        match self.format {
+            DocFormat::Html => format!("<code>{}</code>", escape(text)),
+            DocFormat::Markdown => format!("`{}`", text.replace('|', "\\|")),
+        }
+    }
+}
+
+/// Styling of HTML pages
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: auto; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; }
+";
+
+/// Get the table rows of the items of a sequence, set or choice type, with
+/// the extension additions marked in their presence
+fn rows<T>(
+    items: &Extensible<T>,
+    row: impl Fn(usize, &T) -> Option<[String; 6]>,
+) -> Vec<[String; 6]> {
+    let root = items.root_items().map(|item| (item, false));
+    let additions = items
+        .extension
+        .iter()
+        .flat_map(|extension| extension.additions.iter().flat_map(|a| a.items()))
+        .map(|item| (item, true));
+
+    root.chain(additions)
+        .enumerate()
+        .filter_map(|(index, (item, addition))| {
+            let mut cells = row(index, item)?;
+            if addition {
+                let presence = &mut cells[4];
+                if !presence.is_empty() {
+                    presence.push_str(", ");
+                }
+                presence.push_str("extension addition");
+            }
+            Some(cells)
+        })
+        .collect()
+}
+
+/// Write permitted values in the notation of a constraint, `0..7 | 10, ...`
+fn bounds<T: PartialEq>(bounds: &Bounds<T>, show: impl Fn(&T) -> String) -> String {
+    let end = |value: &Option<T>, limit: &str| value.as_ref().map_or(limit.to_string(), &show);
+    let intervals: Vec<_> = bounds
+        .intervals
+        .iter()
+        .map(|interval| match (&interval.lower, &interval.upper) {
+            (Some(lower), Some(upper)) if lower == upper => show(lower),
+            (lower, upper) => format!("{}..{}", end(lower, "MIN"), end(upper, "MAX")),
+        })
+        .collect();
+
+    let mut text = intervals.join(" | ");
+    if bounds.extensible {
+        text.push_str(", ...");
+    }
+    text
+}
+
+/// Quote a character string value
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Escape text for HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,991 @@
+//! Re-emit the analysed modules as a single flattened ASN.1 module, for
+//! handing simplified schemas to users of the modules.
+//!
+//! The module is written from the expanded assignments, so instances of
+//! parameterized types are written as types of their own, and `COMPONENTS
+//! OF` types and selection types are replaced by what they include.  The
+//! tags of the types are written as they are applied:
+//! - Every tag is marked `IMPLICIT` or `EXPLICIT`, following the tag default
+//!   of the module defining it, so the module is written with `EXPLICIT
+//!   TAGS`.
+//! - The components and alternatives of types in modules with `AUTOMATIC
+//!   TAGS` are given the tags they are automatically tagged with.
+//! - Types of modules with `EXTENSIBILITY IMPLIED` are given an extension
+//!   marker.
+//!
+//! Assignments are kept if they are reachable from the root assignments,
+//! and keep their names unless several modules define the same name, in
+//! which case they are prefixed with the name of their module.  Values that
+//! are not interpreted, such as the braced values of sequence types, are
+//! written as they are in the source, so references within them are
+//! neither renamed nor kept.
+//!
+//! Constraints are written following their types, with the references
+//! within them renamed, and the parameters of instances replaced by their
+//! actual parameters.  Information objects are not written, so table
+//! constraints are left out, and the constraints following a dummy type are
+//! lost when it is replaced by its actual parameter.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use crate::{
+    analysis::{AnalysisContext, Environment},
+    ast::{
+        ComponentType, Constraint, ElementSet, ElementSets, Endpoint, EnumItem, Extensible,
+        ExtensionAddition, FieldKind, FieldSource, NamedNumber, NamedType, ObjIdComponent,
+        ObjectClass, Optionality, Presence, Reference, SubtypeElement, SyntaxItem, TagClass,
+        TagDefault, TagKind, Type, TypeOrValue, Value, WithId,
+    },
+    cst::AsnNodeId,
+    diagnostic::Result,
+    Diagnostic,
+};
+
+/// An assignment of an analysed module, by module and assignment name
+type Key = (String, String);
+
+impl AnalysisContext<'_> {
+    /// Write the assignments reachable from the root assignments as a single
+    /// module with the given name.  Roots are named either `Module.name` or
+    /// just `name` if a single module defines it, and every assignment is
+    /// kept if there are no roots.  The analysis must not have found any
+    /// errors.
+    pub fn flatten(&self, module: &str, roots: &[String]) -> Result<String> {
+        let mut emitter = Emitter {
+            analysis: self,
+            names: HashMap::new(),
+            found: vec![],
+            seen: HashSet::new(),
+            result: String::new(),
+            implied: false,
+            dummies: HashMap::new(),
+        };
+
+        if roots.is_empty() {
+            let mut modules: Vec<_> = self.modules().collect();
+            modules.sort_by(|a, b| a.name.cmp(&b.name));
+            for env in modules {
+                let mut names: Vec<_> = env.expanded.keys().collect();
+                names.sort();
+                for name in names {
+                    emitter.find(&env.name, name);
+                }
+            }
+        }
+        for root in roots {
+            let (env, name) = self.root(root)?;
+            emitter.find(&env.name, name);
+        }
+
+        // writing the assignments finds the assignments they refer to, so
+        // they are written once to find every assignment, named, and then
+        // written again with their names
+        let mut next = 0;
+        while let Some((env, name)) = emitter.found.get(next).cloned() {
+            emitter.assignment(&env, &name);
+            next += 1;
+        }
+        emitter.names = names(&emitter.found);
+
+        emitter.result = String::new();
+        for (env, name) in emitter.found.clone() {
+            emitter.assignment(&env, &name);
+        }
+
+        let mut content = format!("{module} DEFINITIONS EXPLICIT TAGS ::=\nBEGIN\n");
+        content.push_str(&emitter.result);
+        content.push_str("\nEND\n");
+        Ok(content)
+    }
+
+    /// Find the module and name of a root assignment
+    fn root<'s>(&'s self, root: &'s str) -> Result<(&'s Environment, &'s str)> {
+        if let Some((module, name)) = root.split_once('.') {
+            return match self.module_named(module) {
+                Some(env) if env.expanded.contains_key(name) => Ok((env, name)),
+                Some(_) => Err(
+                    Diagnostic::error("Asn1::Flatten::UnknownRoot").name(format!(
+                        "Module `{module}` has no assignment named `{name}`"
+                    )),
+                ),
+                None => Err(Diagnostic::error("Asn1::Flatten::UnknownRoot")
+                    .name(format!("No module named `{module}`"))),
+            };
+        }
+
+        let mut modules: Vec<_> = self
+            .modules()
+            .filter(|env| env.expanded.contains_key(root))
+            .collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        match modules[..] {
+            [env] => Ok((env, root)),
+            [] => Err(Diagnostic::error("Asn1::Flatten::UnknownRoot")
+                .name(format!("No module has an assignment named `{root}`"))),
+            _ => {
+                let names: Vec<_> = modules
+                    .iter()
+                    .map(|env| format!("`{}`", env.name))
+                    .collect();
+                Err(
+                    Diagnostic::error("Asn1::Flatten::AmbiguousRoot").name(format!(
+                        "`{root}` is assigned by modules {}, name one as `Module.{root}`",
+                        names.join(", ")
+                    )),
+                )
+            }
+        }
+    }
+}
+
+/// Name the assignments written to the flattened module.  Instances of
+/// parameterized types are named after their notation, and names used by
+/// several assignments are prefixed with the names of their modules.
+fn names(found: &[Key]) -> HashMap<Key, String> {
+    let base = |name: &str| {
+        name.split(|c: char| !c.is_alphanumeric() && c != '-')
+            .flat_map(|part| part.split('-'))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    };
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, name) in found {
+        *counts.entry(base(name)).or_default() += 1;
+    }
+
+    let mut taken = HashSet::new();
+    let mut names = HashMap::new();
+    for key @ (module, name) in found {
+        let mut name = base(name);
+        if counts[&name] > 1 {
+            let module = base(module);
+            name = match name.starts_with(char::is_lowercase) {
+                true => {
+                    let mut chars = module.chars();
+                    let first = chars.next().map(|c| c.to_lowercase().to_string());
+                    format!("{}{}-{name}", first.unwrap_or_default(), chars.as_str())
+                }
+                false => format!("{module}-{name}"),
+            };
+        }
+
+        let mut unique = name.clone();
+        let mut suffix = 1;
+        while !taken.insert(unique.clone()) {
+            suffix += 1;
+            unique = format!("{name}-{suffix}");
+        }
+        names.insert(key.clone(), unique);
+    }
+    names
+}
+
+/// Writes assignments of the analysed modules in the flattened module
+struct Emitter<'a> {
+    analysis: &'a AnalysisContext<'a>,
+
+    /// The name of each assignment in the flattened module, once every
+    /// assignment has been found
+    names: HashMap<Key, String>,
+
+    /// The assignments written to the flattened module, in the order they
+    /// were found
+    found: Vec<Key>,
+    seen: HashSet<Key>,
+
+    result: String,
+
+    /// Does the module of the assignment being written imply an extension
+    /// marker in its types
+    implied: bool,
+
+    /// The actual parameters of the instance being written, keyed by the
+    /// name of their dummy reference, with the module they are given in
+    dummies: HashMap<String, (&'a Environment, &'a WithId<TypeOrValue>)>,
+}
+
+impl<'a> Emitter<'a> {
+    /// Add an assignment to those written to the flattened module
+    fn find(&mut self, module: &str, name: &str) {
+        let key = (module.to_string(), name.to_string());
+        if self.seen.insert(key.clone()) {
+            self.found.push(key);
+        }
+    }
+
+    /// Get the name an assignment is written with
+    fn name(&self, module: &str, name: &str) -> String {
+        let key = (module.to_string(), name.to_string());
+        self.names.get(&key).cloned().unwrap_or(key.1)
+    }
+
+    fn assignment(&mut self, module: &str, name: &str) {
+        let Some(env) = self.analysis.module_named(module) else {
+            return;
+        };
+        let Some(var) = env.expanded.get(name) else {
+            return;
+        };
+        self.implied = env.extensibility_implied;
+        self.instance(env, name);
+
+        self.result.push('\n');
+        self.comment(env, 0, var.id);
+        let name = self.name(module, name);
+        match (&*var.value, &var.ty) {
+            (TypeOrValue::Type(ty), _) => {
+                let _ = write!(self.result, "{name} ::= ");
+                self.ty(env, 0, ty, var.value.id);
+            }
+            (TypeOrValue::Value(value), Some(ty)) => {
+                let _ = write!(self.result, "{name} ");
+                self.ty(env, 0, ty, ty.id);
+                self.result.push_str(" ::= ");
+                self.value(env, value, var.value.id);
+            }
+            (TypeOrValue::Value(value), None) => {
+                let _ = write!(self.result, "{name} ::= ");
+                self.value(env, value, var.value.id);
+            }
+        }
+        self.result.push('\n');
+    }
+
+    /// Record the actual parameters of an instance of a parameterized type,
+    /// given its name within a module, so that the dummy references within
+    /// its constraints can be replaced
+    fn instance(&mut self, module: &'a Environment, name: &str) {
+        self.dummies.clear();
+        let Some(instance) = module.instances.get(name) else {
+            return;
+        };
+        let Some(definition) = self
+            .analysis
+            .module_named(&instance.definition.module)
+            .and_then(|env| env.variables.get(&instance.definition.name))
+        else {
+            return;
+        };
+
+        for (dummy, actual) in definition.parameters.iter().zip(&instance.parameters) {
+            self.dummies
+                .insert(dummy.name.value.clone(), (module, actual));
+        }
+    }
+
+    /// Write a type followed by its constraints, given the node ID of the
+    /// type, with any components on the following lines indented by one more
+    /// level than the first line
+    fn ty(&mut self, module: &'a Environment, indent: usize, ty: &Type, id: AsnNodeId) {
+        match ty {
+            Type::Tagged(tagged) => {
+                self.result.push('[');
+                match tagged.class {
+                    TagClass::Universal => self.result.push_str("UNIVERSAL "),
+                    TagClass::Application => self.result.push_str("APPLICATION "),
+                    TagClass::Private => self.result.push_str("PRIVATE "),
+                    TagClass::ContextSpecific => (),
+                }
+                self.value(module, &tagged.number, tagged.number.id);
+                // a choice or open type has no tag of its own to replace
+                let explicit = match tagged.kind {
+                    TagKind::Explicit => true,
+                    TagKind::Implicit => false,
+                    TagKind::Default => {
+                        module.tag_default == TagDefault::Explicit
+                            || self.untagged(module, &tagged.ty)
+                    }
+                };
+                self.result.push_str(match explicit {
+                    true => "] EXPLICIT ",
+                    false => "] IMPLICIT ",
+                });
+                self.ty(module, indent, &tagged.ty, tagged.ty.id);
+            }
+            Type::Reference(reference) => match self.analysis.resolve_expanded(module, reference) {
+                Some((env, _)) => {
+                    self.find(&env.name, &reference.name);
+                    let name = self.name(&env.name, &reference.name);
+                    self.result.push_str(&name);
+                }
+                None => {
+                    let _ = write!(self.result, "{reference}");
+                }
+            },
+            Type::Integer(numbers) => self.named_numbers(module, "INTEGER", numbers),
+            Type::BitString(numbers) => self.named_numbers(module, "BIT STRING", numbers),
+            Type::Enumerated(items) => self.enumerated(module, indent, items),
+            Type::Sequence(components) | Type::Set(components) => {
+                let keyword = match ty {
+                    Type::Sequence(_) => "SEQUENCE",
+                    _ => "SET",
+                };
+                let automatic = module.automatic_tags(components, |c| &c.ty().value);
+                let numbers = automatic.then(|| {
+                    numbered(components, |c| match c {
+                        ComponentType::Named(component) => Some(component.name.id),
+                        ComponentType::ComponentsOf(_) => None,
+                    })
+                });
+                self.braced(
+                    keyword,
+                    indent,
+                    components,
+                    |this, indent, width, component| {
+                        let ComponentType::Named(component) = component else {
+                            this.line_start(indent);
+                            this.result.push_str("COMPONENTS OF ");
+                            let ty = component.ty();
+                            this.ty(module, indent, ty, ty.id);
+                            return;
+                        };
+                        this.comment(module, indent, component.name.id);
+                        this.line_start(indent);
+                        let _ = write!(this.result, "{:width$} ", *component.name);
+                        let number = numbers.as_ref().map(|numbers| numbers[&component.name.id]);
+                        this.automatic(module, indent, number, &component.ty);
+                        if component.optional {
+                            this.result.push_str(" OPTIONAL");
+                        }
+                        if let Some(default) = &component.default {
+                            this.result.push_str(" DEFAULT ");
+                            this.value(module, default, default.id);
+                        }
+                    },
+                );
+            }
+            Type::Choice(alternatives) => {
+                let automatic = module.automatic_tags(alternatives, |a| &a.ty.value);
+                let numbers = automatic.then(|| numbered(alternatives, |a| Some(a.name.id)));
+                self.braced(
+                    "CHOICE",
+                    indent,
+                    alternatives,
+                    |this, indent, width, alternative| {
+                        this.comment(module, indent, alternative.name.id);
+                        this.line_start(indent);
+                        let _ = write!(this.result, "{:width$} ", *alternative.name);
+                        let number = numbers
+                            .as_ref()
+                            .map(|numbers| numbers[&alternative.name.id]);
+                        this.automatic(module, indent, number, &alternative.ty);
+                    },
+                );
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                self.result.push_str(match ty {
+                    Type::SequenceOf(_) => "SEQUENCE",
+                    _ => "SET",
+                });
+                // a constraint following the type would constrain its items
+                self.constraints(indent, id);
+                self.result.push_str(" OF ");
+                if let Some(name) = &of.name {
+                    let _ = write!(self.result, "{} ", **name);
+                }
+                self.ty(module, indent, &of.ty, of.ty.id);
+                return;
+            }
+            Type::Selection(name, ty) => {
+                let _ = write!(self.result, "{} < ", **name);
+                self.ty(module, indent, ty, ty.id);
+            }
+            Type::Class(class) => self.class(module, indent, class),
+            Type::Field(field) => match &field.source {
+                FieldSource::Class(class) => {
+                    self.ty(module, indent, class, class.id);
+                    let _ = write!(self.result, ".{}", *field.field);
+                }
+                FieldSource::Object(_) => {
+                    let _ = write!(self.result, "{field}");
+                }
+            },
+            ty => {
+                let _ = write!(self.result, "{ty}");
+            }
+        }
+        self.constraints(indent, id);
+    }
+
+    /// Write the type of a component or alternative, with the tag it is
+    /// automatically tagged with if it has one
+    fn automatic(
+        &mut self,
+        module: &'a Environment,
+        indent: usize,
+        number: Option<usize>,
+        ty: &WithId<Type>,
+    ) {
+        if let Some(number) = number {
+            // an untagged choice or open type is tagged explicitly
+            let kind = match self.untagged(module, ty) {
+                true => "EXPLICIT",
+                false => "IMPLICIT",
+            };
+            let _ = write!(self.result, "[{number}] {kind} ");
+        }
+        self.ty(module, indent, ty, ty.id);
+    }
+
+    /// Write the constraints following a type, given the node ID of the type
+    fn constraints(&mut self, indent: usize, id: AsnNodeId) {
+        let Some((module, constraints)) = self.analysis.constraints(id) else {
+            return;
+        };
+        for constraint in constraints {
+            if matches!(constraint, Constraint::Table(_)) {
+                continue;
+            }
+            self.result.push_str(" (");
+            self.constraint(module, indent, constraint);
+            self.result.push(')');
+        }
+    }
+
+    /// Write a single constraint, without its parentheses
+    fn constraint(&mut self, module: &'a Environment, indent: usize, constraint: &Constraint) {
+        match constraint {
+            Constraint::Subtype(sets) | Constraint::Table(sets) => {
+                self.element_sets(module, indent, sets);
+            }
+            Constraint::Contents(contents) => {
+                if let Some(ty) = &contents.containing {
+                    self.result.push_str("CONTAINING ");
+                    self.constraint_type(module, indent, ty);
+                }
+                if let Some(value) = &contents.encoded_by {
+                    if contents.containing.is_some() {
+                        self.result.push(' ');
+                    }
+                    self.result.push_str("ENCODED BY ");
+                    self.constraint_value(module, indent, value);
+                }
+            }
+            Constraint::UserDefined => self.result.push_str("CONSTRAINED BY {}"),
+        }
+    }
+
+    fn element_sets(&mut self, module: &'a Environment, indent: usize, sets: &ElementSets) {
+        if let Some(root) = &sets.root {
+            self.element_set(module, indent, root);
+            if sets.extensible {
+                self.result.push_str(", ");
+            }
+        }
+        if sets.extensible {
+            self.result.push_str("...");
+        }
+        if let Some(additions) = &sets.additions {
+            self.result.push_str(", ");
+            self.element_set(module, indent, additions);
+        }
+    }
+
+    /// Write a set of elements, with parentheses around the sets combined
+    /// with it that would otherwise take a different precedence
+    fn element_set(&mut self, module: &'a Environment, indent: usize, set: &ElementSet) {
+        match set {
+            ElementSet::Element(element) => self.element(module, indent, element),
+            ElementSet::Union(sets) => {
+                for (i, set) in sets.iter().enumerate() {
+                    if i > 0 {
+                        self.result.push_str(" | ");
+                    }
+                    let bare = !matches!(set, ElementSet::Union(_) | ElementSet::AllExcept(_));
+                    self.operand(module, indent, set, bare);
+                }
+            }
+            ElementSet::Intersection(sets) => {
+                for (i, set) in sets.iter().enumerate() {
+                    if i > 0 {
+                        self.result.push_str(" ^ ");
+                    }
+                    let bare = matches!(set, ElementSet::Element(_) | ElementSet::Except(..));
+                    self.operand(module, indent, set, bare);
+                }
+            }
+            ElementSet::Except(set, except) => {
+                let bare = matches!(**set, ElementSet::Element(_));
+                self.operand(module, indent, set, bare);
+                self.result.push_str(" EXCEPT ");
+                let bare = matches!(**except, ElementSet::Element(_));
+                self.operand(module, indent, except, bare);
+            }
+            ElementSet::AllExcept(set) => {
+                self.result.push_str("ALL EXCEPT ");
+                let bare = matches!(**set, ElementSet::Element(_));
+                self.operand(module, indent, set, bare);
+            }
+        }
+    }
+
+    fn operand(&mut self, module: &'a Environment, indent: usize, set: &ElementSet, bare: bool) {
+        if !bare {
+            self.result.push('(');
+        }
+        self.element_set(module, indent, set);
+        if !bare {
+            self.result.push(')');
+        }
+    }
+
+    fn element(&mut self, module: &'a Environment, indent: usize, element: &SubtypeElement) {
+        match element {
+            SubtypeElement::Value(value) => self.constraint_value(module, indent, value),
+            SubtypeElement::Range(lower, upper) => {
+                self.endpoint(module, indent, lower, "MIN");
+                if lower.open {
+                    self.result.push('<');
+                }
+                self.result.push_str("..");
+                if upper.open {
+                    self.result.push('<');
+                }
+                self.endpoint(module, indent, upper, "MAX");
+            }
+            SubtypeElement::Type(ty) => {
+                self.result.push_str("INCLUDES ");
+                self.constraint_type(module, indent, ty);
+            }
+            SubtypeElement::Size(constraint)
+            | SubtypeElement::From(constraint)
+            | SubtypeElement::Component(constraint) => {
+                self.result.push_str(match element {
+                    SubtypeElement::Size(_) => "SIZE (",
+                    SubtypeElement::From(_) => "FROM (",
+                    _ => "WITH COMPONENT (",
+                });
+                self.constraint(module, indent, constraint);
+                self.result.push(')');
+            }
+            SubtypeElement::Components(components) => {
+                self.result.push_str("WITH COMPONENTS {");
+                if components.partial {
+                    self.result.push_str(" ...");
+                }
+                for (i, component) in components.components.iter().enumerate() {
+                    if i > 0 || components.partial {
+                        self.result.push(',');
+                    }
+                    let _ = write!(self.result, " {}", *component.name);
+                    if let Some(constraint) = &component.constraint {
+                        self.result.push_str(" (");
+                        self.constraint(module, indent, constraint);
+                        self.result.push(')');
+                    }
+                    match component.presence {
+                        Some(Presence::Present) => self.result.push_str(" PRESENT"),
+                        Some(Presence::Absent) => self.result.push_str(" ABSENT"),
+                        Some(Presence::Optional) => self.result.push_str(" OPTIONAL"),
+                        None => (),
+                    }
+                }
+                self.result.push_str(" }");
+            }
+            SubtypeElement::Pattern(value) => {
+                self.result.push_str("PATTERN ");
+                self.constraint_value(module, indent, value);
+            }
+            SubtypeElement::Settings(settings) => {
+                let _ = write!(self.result, "SETTINGS {}", **settings);
+            }
+        }
+    }
+
+    fn endpoint(&mut self, module: &'a Environment, indent: usize, end: &Endpoint, limit: &str) {
+        match &end.value {
+            Some(value) => self.constraint_value(module, indent, value),
+            None => self.result.push_str(limit),
+        }
+    }
+
+    /// Write a type within a constraint, replacing a dummy reference by the
+    /// actual parameter of the instance being written
+    fn constraint_type(&mut self, module: &'a Environment, indent: usize, ty: &WithId<Type>) {
+        if let Type::Reference(reference) = &**ty {
+            if self.actual(indent, reference) {
+                return;
+            }
+        }
+        self.ty(module, indent, ty, ty.id);
+    }
+
+    /// Write a value within a constraint.  Dummy references are replaced by
+    /// the actual parameters of the instance being written, and a reference
+    /// might be to a type, as a single value cannot be told apart from a
+    /// contained subtype.
+    fn constraint_value(&mut self, module: &'a Environment, indent: usize, value: &WithId<Value>) {
+        let Value::Reference(reference) = &**value else {
+            return self.value(module, value, value.id);
+        };
+        if self.actual(indent, reference) {
+            return;
+        }
+
+        match self.analysis.resolve_expanded(module, reference) {
+            Some((_, var)) if matches!(&*var.value, TypeOrValue::Type(_)) => {
+                let ty = Type::Reference(reference.clone());
+                self.ty(module, indent, &ty, value.id);
+            }
+            _ => self.value(module, value, value.id),
+        }
+    }
+
+    /// Write the actual parameter given for a dummy reference, returning
+    /// whether the reference is a dummy reference
+    fn actual(&mut self, indent: usize, reference: &Reference) -> bool {
+        if reference.module.is_some() {
+            return false;
+        }
+        let Some(&(module, actual)) = self.dummies.get(&reference.name.value) else {
+            return false;
+        };
+        match &actual.value {
+            TypeOrValue::Type(ty) => self.ty(module, indent, ty, actual.id),
+            TypeOrValue::Value(value) => self.value(module, value, actual.id),
+        }
+        true
+    }
+
+    /// Write a keyword with its braced list of items, each on a new line.
+    /// Items are written by a function given their indentation and the width
+    /// their names are aligned to.
+    fn braced<T: Named>(
+        &mut self,
+        keyword: &str,
+        indent: usize,
+        items: &Extensible<T>,
+        mut item: impl FnMut(&mut Self, usize, usize, &T),
+    ) {
+        let width = items.items().map(|i| i.name().chars().count()).max();
+        let width = width.unwrap_or(0);
+
+        let mut lines: Vec<Line<T>> = items.root.iter().map(Line::Item).collect();
+        match &items.extension {
+            Some(extension) => {
+                lines.push(Line::Marker);
+                lines.extend(extension.additions.iter().map(|addition| match addition {
+                    ExtensionAddition::Single(value) => Line::Item(value),
+                    ExtensionAddition::Group(group) => {
+                        Line::Group(group.version.as_ref().map(|v| v.as_str()), &group.items)
+                    }
+                }));
+                if !extension.root.is_empty() {
+                    lines.push(Line::Marker);
+                    lines.extend(extension.root.iter().map(Line::Item));
+                }
+            }
+            None if self.implied => lines.push(Line::Marker),
+            None => (),
+        }
+
+        if lines.is_empty() {
+            let _ = write!(self.result, "{keyword} {{}}");
+            return;
+        }
+
+        let _ = write!(self.result, "{keyword} {{");
+        let count = lines.len();
+        for (i, line) in lines.into_iter().enumerate() {
+            self.result.push('\n');
+            match line {
+                Line::Item(value) => item(self, indent + 1, width, value),
+                Line::Marker => {
+                    self.line_start(indent + 1);
+                    self.result.push_str("...");
+                }
+                Line::Group(version, values) => {
+                    self.line_start(indent + 1);
+                    self.result.push_str("[[");
+                    if let Some(version) = version {
+                        let _ = write!(self.result, " {version}:");
+                    }
+                    for (j, value) in values.iter().enumerate() {
+                        self.result.push('\n');
+                        item(self, indent + 2, width, value);
+                        if j + 1 < values.len() {
+                            self.result.push(',');
+                        }
+                    }
+                    self.result.push('\n');
+                    self.line_start(indent + 1);
+                    self.result.push_str("]]");
+                }
+            }
+            if i + 1 < count {
+                self.result.push(',');
+            }
+        }
+        self.result.push('\n');
+        self.line_start(indent);
+        self.result.push('}');
+    }
+
+    fn enumerated(&mut self, module: &'a Environment, indent: usize, items: &Extensible<EnumItem>) {
+        self.braced("ENUMERATED", indent, items, |this, indent, _, item| {
+            this.comment(module, indent, item.name.id);
+            this.line_start(indent);
+            this.result.push_str(&item.name);
+            if let Some(value) = &item.value {
+                this.result.push('(');
+                this.value(module, value, value.id);
+                this.result.push(')');
+            }
+        });
+    }
+
+    fn named_numbers(&mut self, module: &'a Environment, keyword: &str, numbers: &[NamedNumber]) {
+        self.result.push_str(keyword);
+        if numbers.is_empty() {
+            return;
+        }
+
+        self.result.push_str(" {");
+        for (i, number) in numbers.iter().enumerate() {
+            if i > 0 {
+                self.result.push(',');
+            }
+            let _ = write!(self.result, " {}(", *number.name);
+            self.value(module, &number.value, number.value.id);
+            self.result.push(')');
+        }
+        self.result.push_str(" }");
+    }
+
+    /// Write an information object class, with the syntax for defining its
+    /// objects
+    fn class(&mut self, module: &'a Environment, indent: usize, class: &ObjectClass) {
+        self.result.push_str("CLASS {");
+        let width = class.fields.iter().map(|f| f.name.chars().count()).max();
+        let width = width.unwrap_or(0);
+        for (i, field) in class.fields.iter().enumerate() {
+            if i > 0 {
+                self.result.push(',');
+            }
+            self.result.push('\n');
+            self.line_start(indent + 1);
+            self.result.push_str(&field.name);
+            match &field.kind {
+                FieldKind::Type => (),
+                FieldKind::Value { ty, unique } => {
+                    let _ = write!(
+                        self.result,
+                        "{:1$} ",
+                        "",
+                        width - field.name.chars().count()
+                    );
+                    self.ty(module, indent + 1, ty, ty.id);
+                    if *unique {
+                        self.result.push_str(" UNIQUE");
+                    }
+                }
+                FieldKind::ValueSet(ty) => {
+                    let _ = write!(
+                        self.result,
+                        "{:1$} ",
+                        "",
+                        width - field.name.chars().count()
+                    );
+                    self.ty(module, indent + 1, ty, ty.id);
+                }
+                FieldKind::VariableTypeValue(path) | FieldKind::VariableTypeValueSet(path) => {
+                    let path: Vec<_> = path.iter().map(|name| name.as_str()).collect();
+                    let _ = write!(
+                        self.result,
+                        "{:1$} {path}",
+                        "",
+                        width - field.name.chars().count(),
+                        path = path.join(".")
+                    );
+                }
+            }
+            match &field.optionality {
+                Optionality::Required => (),
+                Optionality::Optional => self.result.push_str(" OPTIONAL"),
+                Optionality::Default(default) => {
+                    self.result.push_str(" DEFAULT ");
+                    match &default.value {
+                        TypeOrValue::Type(ty) => self.ty(module, indent + 1, ty, default.id),
+                        TypeOrValue::Value(value) => self.value(module, value, default.id),
+                    }
+                }
+            }
+        }
+        self.result.push('\n');
+        self.line_start(indent);
+        self.result.push('}');
+
+        if let Some(syntax) = &class.syntax {
+            self.result.push_str(" WITH SYNTAX {");
+            syntax_items(&mut self.result, syntax);
+            self.result.push_str(" }");
+        }
+    }
+
+    fn value(&mut self, module: &'a Environment, value: &Value, id: AsnNodeId) {
+        match value {
+            Value::Reference(reference) => {
+                let name = self.value_reference(module, reference);
+                self.result.push_str(&name);
+            }
+            Value::ObjectIdentifier(components) => {
+                self.result.push('{');
+                for component in components {
+                    self.result.push(' ');
+                    match component {
+                        ObjIdComponent::Name(name) => {
+                            let reference = Reference {
+                                module: None,
+                                name: name.clone(),
+                            };
+                            let name = self.value_reference(module, &reference);
+                            self.result.push_str(&name);
+                        }
+                        ObjIdComponent::Number(number) => self.result.push_str(number),
+                        ObjIdComponent::NameAndNumber(name, number) => {
+                            let _ = write!(self.result, "{}({})", **name, **number);
+                        }
+                        ObjIdComponent::External(external, name) => {
+                            let reference = Reference {
+                                module: Some(external.clone()),
+                                name: name.clone(),
+                            };
+                            let name = self.value_reference(module, &reference);
+                            self.result.push_str(&name);
+                        }
+                    }
+                }
+                self.result.push_str(" }");
+            }
+            Value::Unparsed => {
+                let source = self.analysis.source(id.source());
+                let span = source.tree.span(id);
+                self.result.push_str(source.source[span].trim());
+            }
+            value => {
+                let _ = write!(self.result, "{value}");
+            }
+        }
+    }
+
+    /// Get the name of a referenced value.  Identifiers that are not value
+    /// assignments, such as named numbers and enumeration items, and the
+    /// names of well known arcs of object identifiers, are kept as they are.
+    fn value_reference(&mut self, module: &'a Environment, reference: &Reference) -> String {
+        match self.analysis.resolve_expanded(module, reference) {
+            Some((env, var)) if matches!(&*var.value, TypeOrValue::Value(_)) => {
+                self.find(&env.name, &reference.name);
+                self.name(&env.name, &reference.name)
+            }
+            _ => reference.name.to_string(),
+        }
+    }
+
+    /// Is a type an untagged choice or open type, which is always tagged
+    /// explicitly
+    fn untagged(&self, module: &'a Environment, ty: &Type) -> bool {
+        match ty {
+            Type::Choice(_) | Type::Field(_) => true,
+            Type::Reference(reference) => match self.analysis.resolve_expanded(module, reference) {
+                Some((module, var)) => match &*var.value {
+                    TypeOrValue::Type(ty) => self.untagged(module, ty),
+                    TypeOrValue::Value(_) => false,
+                },
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Write the comments documenting a name in the source
+    fn comment(&mut self, module: &Environment, indent: usize, name: AsnNodeId) {
+        let Some(comments) = module.comments.get(&name) else {
+            return;
+        };
+        for line in comments.lines() {
+            self.line_start(indent);
+            match line {
+                "" => self.result.push_str("--\n"),
+                line => {
+                    let _ = writeln!(self.result, "-- {line}");
+                }
+            }
+        }
+    }
+
+    fn line_start(&mut self, indent: usize) {
+        let _ = write!(self.result, "{:1$}", "", indent * 4);
+    }
+}
+
+/// A line of the braced list of a type
+enum Line<'t, T> {
+    Item(&'t T),
+    Marker,
+    Group(Option<&'t str>, &'t [T]),
+}
+
+/// The items of a braced list of a type, which are aligned by their names
+trait Named {
+    fn name(&self) -> &str;
+}
+
+impl Named for ComponentType {
+    fn name(&self) -> &str {
+        match self {
+            ComponentType::Named(component) => &component.name,
+            ComponentType::ComponentsOf(_) => "",
+        }
+    }
+}
+
+impl Named for NamedType {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Named for EnumItem {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Number the items of a type for automatic tagging, the root items first,
+/// keyed by the node ID of their names
+fn numbered<T>(
+    items: &Extensible<T>,
+    id: impl Fn(&T) -> Option<AsnNodeId>,
+) -> HashMap<AsnNodeId, usize> {
+    let additions = items
+        .extension
+        .iter()
+        .flat_map(|ext| ext.additions.iter().flat_map(ExtensionAddition::items));
+    items
+        .root_items()
+        .chain(additions)
+        .filter_map(id)
+        .zip(0..)
+        .collect()
+}
+
+/// Write the items of the syntax of an information object class
+fn syntax_items(result: &mut String, items: &[SyntaxItem]) {
+    for item in items {
+        match item {
+            SyntaxItem::Literal(word) | SyntaxItem::Field(word) => {
+                let _ = write!(result, " {}", **word);
+            }
+            SyntaxItem::Optional(items) => {
+                result.push_str(" [");
+                syntax_items(result, items);
+                result.push_str(" ]");
+            }
+        }
+    }
+}
@@ -0,0 +1,714 @@
+//! Canonical source formatter for ASN.1 files.  Works directly on the CST so
+//! that every token and comment in the source file is kept, only the layout
+//! (whitespace) and optionally the spelling of keywords is changed.
+
+use crate::{
+    compiler::SourceId,
+    cst::{Asn1, Asn1Tag, AsnNodeId},
+    parser::symbols_from_modules,
+    token::{Token, TokenKind, KEYWORD_DATA},
+    AsnCompiler,
+};
+
+/// Options to control the layout produced by the formatter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormatOptions {
+    /// Number of spaces used for each indentation level
+    pub indent: usize,
+
+    /// Align the types of components within sequence, set and choice types
+    /// and the fields of object classes.
+    pub align_components: bool,
+
+    /// Place each imported symbol on its own line, rather than placing all
+    /// symbols imported from a module on a single line.
+    pub one_import_per_line: bool,
+
+    /// How the case of keywords should be changed
+    pub keyword_case: KeywordCase,
+}
+
+/// Case normalisation to apply to keywords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum KeywordCase {
+    /// Keep keywords as they are written in the source file
+    #[default]
+    Preserve,
+
+    /// Use the spelling of keywords as defined in X.680
+    Upper,
+
+    /// Use the lowercase spelling of keywords.  The output will only be valid
+    /// if the `lowercase_keywords` feature is enabled.
+    Lower,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            align_components: true,
+            one_import_per_line: false,
+            keyword_case: KeywordCase::Preserve,
+        }
+    }
+}
+
+impl AsnCompiler {
+    /// Reprint a source file with a consistent layout.  All comments in the
+    /// file are kept.  If the file could not be parsed, the output will be
+    /// empty.
+    pub fn format(&self, file: SourceId, options: &FormatOptions) -> String {
+        let source = self.source(file);
+
+        let mut fmt = Formatter {
+            tree: &source.tree,
+            source: &source.source,
+            options,
+            out: String::new(),
+            indent: 0,
+            prev: None,
+            last_end: 0,
+            line_start: true,
+            force_newline: false,
+            breaks: 0,
+            align: None,
+            in_prefix: false,
+            in_constraint: false,
+            constraint_start: false,
+        };
+
+        fmt.root();
+        fmt.out
+    }
+}
+
+/// State used while formatting a single file
+struct Formatter<'a> {
+    /// The tree being formatted
+    tree: &'a Asn1,
+
+    /// The source text the tree was created from
+    source: &'a str,
+
+    /// User settings
+    options: &'a FormatOptions,
+
+    /// The formatted output
+    out: String,
+
+    /// The current indentation level
+    indent: usize,
+
+    /// The kind of the previous token written to the output on this line
+    prev: Option<TokenKind>,
+
+    /// The byte offset in the source of the end of the previous token written
+    last_end: usize,
+
+    /// Is the output at the start of a line, before any indentation
+    line_start: bool,
+
+    /// Must the next token be placed on a new line, e.g. after a single line
+    /// comment that is ended by a newline.
+    force_newline: bool,
+
+    /// Number of line breaks to insert before the next token, 1 to start a new
+    /// line or 2 to also leave a blank line.  Breaks are delayed until the next
+    /// token so that comments trailing the previous line stay on that line.
+    breaks: usize,
+
+    /// Pad the next non-comment token with spaces to this width
+    align: Option<usize>,
+
+    /// Are tokens being written within the square brackets of a prefixed type
+    in_prefix: bool,
+
+    /// Are tokens being written within the parentheses of a constraint
+    in_constraint: bool,
+
+    /// Is the next token the opening parenthesis of a constraint
+    constraint_start: bool,
+}
+
+impl<'a> Formatter<'a> {
+    /// Format the root node of the tree
+    fn root(&mut self) {
+        for node in self.tree.children(self.tree.root) {
+            if self.tree.tree_tag(node) == Some(Asn1Tag::ModuleDefinition) {
+                self.breaks = 2;
+            }
+            self.node(node);
+        }
+
+        self.line();
+    }
+
+    /// Format any node using the layout for its tag
+    fn node(&mut self, node: AsnNodeId) {
+        let Some(tag) = self.tree.tree_tag(node) else {
+            self.token(node);
+            return;
+        };
+
+        match tag {
+            Asn1Tag::ModuleDefinition => self.module(node),
+            Asn1Tag::Imports => self.imports(node),
+            Asn1Tag::SequenceType | Asn1Tag::SetType => self.sequence(node),
+            Asn1Tag::ChoiceType => self.choice(node),
+            Asn1Tag::ObjectClass => self.object_class(node),
+            Asn1Tag::SyntaxSpec => self.syntax_spec(node),
+            Asn1Tag::PrefixType => self.prefix_type(node),
+            Asn1Tag::Constraint => self.constraint(node),
+            Asn1Tag::XMLValue | Asn1Tag::EncodingControlSection => self.verbatim(node),
+            _ => self.children(node),
+        }
+    }
+
+    /// Format all children of a node without any extra layout
+    fn children(&mut self, node: AsnNodeId) {
+        for child in self.tree.children(node) {
+            self.node(child);
+        }
+    }
+
+    /// Format a module definition, placing each assignment on its own line.
+    fn module(&mut self, node: AsnNodeId) {
+        let mut after_header = false;
+
+        for child in self.tree.children(node) {
+            match (self.tree.tree_tag(child), self.kind(child)) {
+                (Some(Asn1Tag::Exports | Asn1Tag::Imports), _) => {
+                    self.break_line();
+                    self.node(child);
+                    after_header = true;
+                }
+                (Some(Asn1Tag::Assignment), _) => {
+                    if after_header {
+                        self.breaks = 2;
+                        after_header = false;
+                    }
+                    self.item(child);
+                }
+                (Some(Asn1Tag::EncodingControl), _) => {
+                    self.breaks = 2;
+                    for section in self.tree.children(child) {
+                        if self.tree.tree_tag(section).is_some() {
+                            self.break_line();
+                        }
+                        self.node(section);
+                    }
+                }
+                (_, Some(TokenKind::KwBegin)) => {
+                    self.node(child);
+                    self.indent += 1;
+                }
+                (_, Some(TokenKind::KwEnd)) => {
+                    self.indent = self.indent.saturating_sub(1);
+                    self.break_line();
+                    self.node(child);
+                }
+                _ => self.node(child),
+            }
+        }
+    }
+
+    /// Format an imports list, placing the symbols from each module on their
+    /// own line.
+    fn imports(&mut self, node: AsnNodeId) {
+        for child in self.tree.children(node) {
+            if self.tree.tree_tag(child) != Some(Asn1Tag::SymbolsFromModuleList) {
+                self.node(child);
+                continue;
+            }
+
+            let nodes: Vec<_> = self
+                .tree
+                .children(child)
+                .filter(|&n| !self.is_comment(n))
+                .collect();
+            let kinds: Vec<_> = nodes.iter().filter_map(|&n| self.kind(n)).collect();
+            let groups = symbols_from_modules(&kinds).unwrap_or_default();
+
+            // the start index of each line in the list of non-comment nodes
+            let mut lines = vec![];
+            for group in groups {
+                lines.push(group.symbols.start);
+                if self.options.one_import_per_line {
+                    let mut depth = 0;
+                    for idx in group.symbols.clone() {
+                        match kinds[idx] {
+                            TokenKind::LeftCurly => depth += 1,
+                            TokenKind::RightCurly => depth -= 1,
+                            TokenKind::TypeOrModuleRef | TokenKind::ValueRefOrIdent
+                                if depth == 0 =>
+                            {
+                                lines.push(idx)
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+            }
+
+            self.indent += 1;
+            let mut idx = 0;
+            for node in self.tree.children(child) {
+                if !self.is_comment(node) {
+                    if lines.contains(&idx) {
+                        self.break_line();
+                    }
+                    idx += 1;
+                }
+                self.node(node);
+            }
+            self.indent -= 1;
+        }
+    }
+
+    /// Format a sequence or set type, placing each component on its own line.
+    fn sequence(&mut self, node: AsnNodeId) {
+        let items: Vec<_> = self
+            .tree
+            .children(node)
+            .flat_map(|child| match self.tree.tree_tag(child) {
+                Some(Asn1Tag::ComponentTypeList | Asn1Tag::ExtensionAdditions) => {
+                    self.tree.children(child).collect()
+                }
+                _ => vec![],
+            })
+            .filter(|&n| {
+                matches!(
+                    self.tree.tree_tag(n),
+                    Some(Asn1Tag::ComponentType | Asn1Tag::ExtensionAddition)
+                )
+            })
+            .collect();
+        let width = self.name_width(&items);
+
+        for child in self.tree.children(node) {
+            match self.tree.tree_tag(child) {
+                Some(Asn1Tag::ComponentTypeList | Asn1Tag::ExtensionAdditions) => {
+                    for item in self.tree.children(child) {
+                        if items.contains(&item) {
+                            self.aligned_item(item, width);
+                        } else {
+                            self.node(item);
+                        }
+                    }
+                }
+                Some(Asn1Tag::ExtensionAndException) => self.item(child),
+                _ => self.block_token(child),
+            }
+        }
+    }
+
+    /// Format a choice type, placing each alternative on its own line.
+    fn choice(&mut self, node: AsnNodeId) {
+        let mut items = vec![];
+        for child in self.tree.children(node) {
+            match self.tree.tree_tag(child) {
+                Some(Asn1Tag::TypeList) => items.extend(
+                    self.tree
+                        .children(child)
+                        .filter(|&n| self.tree.tree_tag(n) == Some(Asn1Tag::TypeOrValue)),
+                ),
+                Some(Asn1Tag::ChoiceExtension) => {
+                    for ext in self.tree.children(child) {
+                        if self.tree.tree_tag(ext) == Some(Asn1Tag::ChoiceExtensionList) {
+                            items.extend(self.tree.children(ext).filter(|&n| {
+                                self.tree.tree_tag(n) == Some(Asn1Tag::ChoiceExtensionItem)
+                            }));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        let width = self.name_width(&items);
+
+        for child in self.tree.children(node) {
+            match self.tree.tree_tag(child) {
+                Some(Asn1Tag::TypeList) => {
+                    for item in self.tree.children(child) {
+                        if items.contains(&item) {
+                            self.aligned_item(item, width);
+                        } else {
+                            self.node(item);
+                        }
+                    }
+                }
+                Some(Asn1Tag::ChoiceExtension) => {
+                    for ext in self.tree.children(child) {
+                        match self.tree.tree_tag(ext) {
+                            Some(Asn1Tag::ExtensionAndException) => self.item(ext),
+                            Some(Asn1Tag::ChoiceExtensionList) => {
+                                for item in self.tree.children(ext) {
+                                    if items.contains(&item) {
+                                        self.aligned_item(item, width);
+                                    } else {
+                                        self.node(item);
+                                    }
+                                }
+                            }
+                            _ => self.block_token(ext),
+                        }
+                    }
+                }
+                _ => self.block_token(child),
+            }
+        }
+    }
+
+    /// Format an object class definition, placing each field on its own line.
+    fn object_class(&mut self, node: AsnNodeId) {
+        for child in self.tree.children(node) {
+            if self.tree.tree_tag(child) != Some(Asn1Tag::FieldSpecList) {
+                self.block_token(child);
+                continue;
+            }
+
+            let items: Vec<_> = self
+                .tree
+                .children(child)
+                .filter(|&n| self.tree.tree_tag(n) == Some(Asn1Tag::FieldSpec))
+                .collect();
+            let width = self.name_width(&items);
+
+            for item in self.tree.children(child) {
+                if items.contains(&item) {
+                    self.aligned_item(item, width);
+                } else {
+                    self.node(item);
+                }
+            }
+        }
+    }
+
+    /// Format a `WITH SYNTAX` specification, placing each group of literals
+    /// and fields on its own line.
+    fn syntax_spec(&mut self, node: AsnNodeId) {
+        for child in self.tree.children(node) {
+            if self.tree.tree_tag(child) != Some(Asn1Tag::SyntaxSpecList) {
+                self.block_token(child);
+                continue;
+            }
+
+            for item in self.tree.children(child) {
+                let new_line = match (self.tree.tree_tag(item), self.kind(item)) {
+                    (Some(Asn1Tag::OptionalSyntaxSpec), _) => true,
+                    (_, Some(TokenKind::TypeOrModuleRef)) => !matches!(
+                        self.prev,
+                        Some(TokenKind::TypeOrModuleRef | TokenKind::Comma)
+                    ),
+                    _ => false,
+                };
+                if new_line {
+                    self.item(item);
+                } else {
+                    self.node(item);
+                }
+            }
+        }
+    }
+
+    /// Format a prefixed type, `[encoding tag] IMPLICIT Type`
+    fn prefix_type(&mut self, node: AsnNodeId) {
+        self.in_prefix = true;
+        for child in self.tree.children(node) {
+            if self.kind(child) == Some(TokenKind::RightSquare) {
+                self.in_prefix = false;
+            }
+            self.node(child);
+        }
+        self.in_prefix = false;
+    }
+
+    /// Format a constraint.  The opening parenthesis is separated from a
+    /// preceding identifier, `a (SIZE (1))`, unlike in named numbers.
+    fn constraint(&mut self, node: AsnNodeId) {
+        let outer = self.in_constraint;
+        self.in_constraint = true;
+        self.constraint_start = true;
+        self.children(node);
+        self.in_constraint = outer;
+    }
+
+    /// Format a token within a braced block, increasing the indentation level
+    /// after an opening brace and decreasing it before a closing brace, unless
+    /// the braces are empty.
+    fn block_token(&mut self, node: AsnNodeId) {
+        match self.kind(node) {
+            Some(TokenKind::LeftCurly) => {
+                self.node(node);
+                self.indent += 1;
+            }
+            Some(TokenKind::RightCurly) => {
+                self.indent -= 1;
+                if self.prev != Some(TokenKind::LeftCurly) {
+                    self.break_line();
+                }
+                self.node(node);
+            }
+            Some(TokenKind::Ellipsis) => self.item(node),
+            _ => self.node(node),
+        }
+    }
+
+    /// Format a node on a new line, keeping a blank line before it if there was
+    /// one in the source.
+    fn item(&mut self, node: AsnNodeId) {
+        self.break_line();
+        self.node(node);
+    }
+
+    /// Format a node on a new line, padding its leading identifier so that
+    /// the rest of the node is aligned to the given width.
+    fn aligned_item(&mut self, node: AsnNodeId, width: usize) {
+        if self.name(node).is_some() && self.options.align_components {
+            self.align = Some(width);
+        }
+        self.item(node);
+        self.align = None;
+    }
+
+    /// Get the maximum width of the leading identifiers of a list of nodes
+    fn name_width(&self, nodes: &[AsnNodeId]) -> usize {
+        nodes
+            .iter()
+            .filter_map(|&n| self.name(n))
+            .map(|s| s.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Get the leading identifier or field name of a node, if it has one.
+    fn name(&self, node: AsnNodeId) -> Option<&'a str> {
+        let tok = *self.tokens(node).iter().find(|t| !is_comment(t.kind))?;
+
+        match tok.kind {
+            TokenKind::ValueRefOrIdent | TokenKind::TypeField | TokenKind::ValueField => {
+                Some(self.text(tok))
+            }
+            _ => None,
+        }
+    }
+
+    /// Copy the source of a node to the output without changing its layout
+    fn verbatim(&mut self, node: AsnNodeId) {
+        let tokens = self.tokens(node);
+        let Some(first) = tokens.iter().position(|t| !is_comment(t.kind)) else {
+            self.children(node);
+            return;
+        };
+        let last = tokens
+            .iter()
+            .rposition(|t| !is_comment(t.kind))
+            .unwrap_or(first);
+
+        for &tok in &tokens[..first] {
+            self.comment(tok);
+        }
+
+        let (start, end) = (tokens[first], tokens[last]);
+        let source = self.source;
+        let text = &source[start.offset..end.offset + end.length as usize];
+        self.write(start, text);
+        self.prev = Some(end.kind);
+        self.last_end = end.offset + end.length as usize;
+
+        for &tok in &tokens[last + 1..] {
+            self.comment(tok);
+        }
+    }
+
+    /// Write a single token to the output
+    fn token(&mut self, node: AsnNodeId) {
+        let Some(tok) = self.tree.token(node) else {
+            return;
+        };
+
+        if is_comment(tok.kind) {
+            self.comment(tok);
+            return;
+        }
+
+        let text = self.keyword(tok);
+        self.write(tok, text);
+        if let Some(width) = self.align.take() {
+            let pad = width.saturating_sub(text.chars().count());
+            self.out.push_str(&" ".repeat(pad));
+        }
+
+        self.prev = Some(tok.kind);
+        self.last_end = tok.offset + tok.length as usize;
+        self.constraint_start = false;
+    }
+
+    /// Write some text to the output, adding the required whitespace before
+    /// it.  `tok` is the first token in the text.
+    fn write(&mut self, tok: Token, text: &str) {
+        if self.force_newline {
+            self.breaks = self.breaks.max(1);
+        }
+        self.apply_breaks(tok.offset);
+
+        if !self.line_start && self.space(tok.kind) {
+            self.out.push(' ');
+        }
+        self.write_indent();
+        self.out.push_str(text);
+    }
+
+    /// Write a comment token to the output.  Comments that were on their own
+    /// line in the source will be kept on their own line.
+    fn comment(&mut self, tok: Token) {
+        let before = &self.source[..tok.offset];
+        let line = before.rfind('\n').map_or(before, |idx| &before[idx + 1..]);
+        let own_line = line.trim().is_empty();
+
+        if own_line {
+            self.breaks = self.breaks.max(1);
+            self.apply_breaks(tok.offset);
+        } else if self.force_newline {
+            self.line();
+        } else if !self.line_start {
+            self.out.push(' ');
+        }
+
+        self.write_indent();
+        let text = self.text(tok).trim_end();
+        self.out.push_str(text);
+
+        // single line comments ending with a newline rather than a pair of
+        // hyphens must be followed by a newline.
+        let mut end = text.chars().rev();
+        let terminated = text.chars().count() >= 4
+            && matches!(end.next(), Some('-' | '\u{2011}'))
+            && matches!(end.next(), Some('-' | '\u{2011}'));
+        if own_line || (tok.kind == TokenKind::SingleComment && !terminated) {
+            self.force_newline = true;
+        }
+
+        self.prev = Some(tok.kind);
+        self.last_end = tok.offset + tok.length as usize;
+    }
+
+    /// Should there be a space between the previous token and a token of the
+    /// given kind.
+    fn space(&self, next: TokenKind) -> bool {
+        match (self.prev, next) {
+            (None, _) => false,
+            (_, TokenKind::Comma | TokenKind::SemiColon | TokenKind::RightParen) => false,
+            (_, TokenKind::Dot | TokenKind::RightSquare | TokenKind::VersionClose) => false,
+            (Some(TokenKind::Dot | TokenKind::LeftParen | TokenKind::Hyphen), _) => false,
+            (Some(TokenKind::LeftSquare | TokenKind::VersionOpen), _) => false,
+            (Some(TokenKind::RangeSeparator), _) | (_, TokenKind::RangeSeparator) => false,
+            (Some(TokenKind::Less), _) | (_, TokenKind::Less) if self.in_constraint => false,
+            (Some(TokenKind::ValueRefOrIdent), TokenKind::LeftParen) => self.constraint_start,
+            (Some(TokenKind::LeftCurly), TokenKind::RightCurly) => false,
+            (Some(TokenKind::Number), TokenKind::Colon) => false,
+            (_, TokenKind::Colon) => !self.in_prefix,
+            _ => true,
+        }
+    }
+
+    /// Get the text of a token, changing the case of keywords if requested.
+    fn keyword(&self, tok: Token) -> &'a str {
+        let data = KEYWORD_DATA.iter().find(|(_, kind, _)| *kind == tok.kind);
+
+        match (self.options.keyword_case, data) {
+            (KeywordCase::Upper, Some((upper, _, _))) => upper,
+            (KeywordCase::Lower, Some((_, _, lower))) => lower,
+            _ => self.text(tok),
+        }
+    }
+
+    /// Write the current indentation if at the start of a line
+    fn write_indent(&mut self) {
+        if self.line_start {
+            let width = self.indent * self.options.indent;
+            self.out.push_str(&" ".repeat(width));
+            self.line_start = false;
+        }
+    }
+
+    /// Start the next token on a new line, keeping a blank line before it if
+    /// there was one in the source.
+    fn break_line(&mut self) {
+        self.breaks = self.breaks.max(1);
+    }
+
+    /// Insert any pending line breaks before a token at the given offset
+    fn apply_breaks(&mut self, offset: usize) {
+        let breaks = std::mem::take(&mut self.breaks);
+        if breaks >= 2 || (breaks == 1 && self.is_blank(offset)) {
+            self.blank_line();
+        } else if breaks == 1 {
+            self.line();
+        }
+    }
+
+    /// Move to the start of a new line, unless already at the start of a line
+    fn line(&mut self) {
+        self.force_newline = false;
+        if self.line_start {
+            return;
+        }
+
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+        self.out.push('\n');
+        self.line_start = true;
+        self.prev = None;
+    }
+
+    /// Ensure that there is an empty line before the next token, unless at the
+    /// start of the output
+    fn blank_line(&mut self) {
+        self.line();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    /// Is there an empty line in the source between the previous token and
+    /// the given offset
+    fn is_blank(&self, offset: usize) -> bool {
+        let gap = self.source.get(self.last_end..offset).unwrap_or("");
+        gap.matches('\n').count() >= 2
+    }
+
+    /// Get all tokens within a node in source order, including comments
+    fn tokens(&self, node: AsnNodeId) -> Vec<Token> {
+        if let Some(tok) = self.tree.token(node) {
+            return vec![tok];
+        }
+
+        self.tree
+            .children(node)
+            .flat_map(|child| self.tokens(child))
+            .collect()
+    }
+
+    /// Get the kind of a token node
+    fn kind(&self, node: AsnNodeId) -> Option<TokenKind> {
+        self.tree.token(node).map(|t| t.kind)
+    }
+
+    /// Is the node a comment token
+    fn is_comment(&self, node: AsnNodeId) -> bool {
+        self.kind(node).is_some_and(is_comment)
+    }
+
+    /// Get the source text of a token
+    fn text(&self, tok: Token) -> &'a str {
+        let source = self.source;
+        &source[tok.offset..tok.offset + tok.length as usize]
+    }
+}
+
+/// Is the token kind a comment
+fn is_comment(kind: TokenKind) -> bool {
+    matches!(kind, TokenKind::SingleComment | TokenKind::MultiComment)
+}
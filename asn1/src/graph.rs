@@ -0,0 +1,395 @@
+//! Dependency graphs of analysed modules, either of the modules and their
+//! imports or of the assignments and the references between them.  Cycles
+//! and imports that cannot be resolved are marked, and graphs can be written
+//! as Graphviz DOT or JSON documents.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{analysis::AnalysisContext, json::Json};
+
+/// What the nodes of a dependency graph are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum GraphLevel {
+    /// A node for each module, with an edge for each of its imports
+    #[default]
+    Modules,
+
+    /// A node for each assignment, with an edge for each assignment it
+    /// references
+    Types,
+}
+
+/// A directed dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// What the nodes of the graph are
+    pub level: GraphLevel,
+
+    /// All nodes, sorted by name
+    pub nodes: Vec<GraphNode>,
+
+    /// All edges, sorted by the nodes they connect
+    pub edges: Vec<GraphEdge>,
+
+    /// The nodes of each cycle, as indices into the nodes.  A cycle is a set
+    /// of nodes that can all reach each other.
+    pub cycles: Vec<Vec<usize>>,
+}
+
+/// A module or assignment in a dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    /// Name of the module, or the module and assignment names separated by a
+    /// dot
+    pub name: String,
+
+    /// Is the node defined by the analysed modules.  Modules that are
+    /// imported but could not be found are unresolved.
+    pub resolved: bool,
+
+    /// Is the node part of a cycle
+    pub cyclic: bool,
+}
+
+/// A dependency of one node on another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    /// Index of the dependent node
+    pub from: usize,
+
+    /// Index of the node depended on
+    pub to: usize,
+
+    /// Symbols imported by a module dependency.  Empty for assignments.
+    pub symbols: Vec<String>,
+
+    /// Imported symbols that are not defined by the module they are
+    /// imported from, or that module could not be found
+    pub unresolved: Vec<String>,
+
+    /// Are both nodes part of the same cycle
+    pub cyclic: bool,
+}
+
+impl AnalysisContext<'_> {
+    /// Build the dependency graph of the analysed modules or of their
+    /// assignments.  Analysis errors do not prevent building a graph, so
+    /// that the unresolved imports causing them are shown.
+    pub fn dependency_graph(&self, level: GraphLevel) -> DependencyGraph {
+        let mut builder = Builder::default();
+        match level {
+            GraphLevel::Modules => self.module_edges(&mut builder),
+            GraphLevel::Types => self.type_edges(&mut builder),
+        }
+        builder.finish(level)
+    }
+
+    fn module_edges(&self, builder: &mut Builder) {
+        for module in self.modules() {
+            builder.node(&module.name, true);
+            for import in &module.imports {
+                let imported = self.module_named(&import.module);
+                builder.node(&import.module, imported.is_some());
+
+                let edge = builder.edge(&module.name, &import.module);
+                for symbol in &import.symbols {
+                    edge.symbols.push(symbol.value.clone());
+                    let defined = imported
+                        .is_some_and(|imported| self.lookup(imported, &symbol.value).is_some());
+                    if !defined {
+                        edge.unresolved.push(symbol.value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    fn type_edges(&self, builder: &mut Builder) {
+        for module in self.modules() {
+            let tree = &self.source(module.node.source()).tree;
+
+            // references are found within the assignments containing them
+            let mut assignments = HashMap::new();
+            for (name, var) in &module.variables {
+                let name = format!("{}.{}", module.name, name);
+                builder.node(&name, true);
+                if let Some(assignment) = tree.parent(var.id) {
+                    assignments.insert(assignment, name);
+                }
+            }
+
+            for (&node, definition) in &module.references {
+                let Some(from) = tree
+                    .ancestors(node)
+                    .find_map(|ancestor| assignments.get(&ancestor))
+                else {
+                    continue;
+                };
+                let to = format!("{}.{}", definition.module, definition.name);
+                builder.node(&to, true);
+                builder.edge(from, &to);
+            }
+        }
+    }
+}
+
+/// Collects the nodes and edges of a graph by name
+#[derive(Default)]
+struct Builder {
+    nodes: BTreeMap<String, bool>,
+    edges: BTreeMap<(String, String), GraphEdge>,
+}
+
+impl Builder {
+    /// Add a node, which is resolved if it is resolved wherever it is added
+    fn node(&mut self, name: &str, resolved: bool) {
+        *self.nodes.entry(name.to_string()).or_default() |= resolved;
+    }
+
+    fn edge(&mut self, from: &str, to: &str) -> &mut GraphEdge {
+        self.edges
+            .entry((from.to_string(), to.to_string()))
+            .or_insert(GraphEdge {
+                from: 0,
+                to: 0,
+                symbols: vec![],
+                unresolved: vec![],
+                cyclic: false,
+            })
+    }
+
+    fn finish(self, level: GraphLevel) -> DependencyGraph {
+        let index: HashMap<&String, usize> =
+            self.nodes.keys().enumerate().map(|(i, n)| (n, i)).collect();
+        let mut nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|(name, &resolved)| GraphNode {
+                name: name.clone(),
+                resolved,
+                cyclic: false,
+            })
+            .collect();
+        let mut edges: Vec<_> = self
+            .edges
+            .iter()
+            .map(|((from, to), edge)| GraphEdge {
+                from: index[from],
+                to: index[to],
+                ..edge.clone()
+            })
+            .collect();
+
+        let mut successors = vec![vec![]; nodes.len()];
+        for edge in &edges {
+            successors[edge.from].push(edge.to);
+        }
+        let components = strongly_connected(&successors);
+
+        // a single node is only a cycle if it depends on itself
+        let mut component = vec![usize::MAX; nodes.len()];
+        let mut cycles = vec![];
+        for members in components {
+            let single = members[0];
+            if members.len() == 1 && !successors[single].contains(&single) {
+                continue;
+            }
+            for &node in &members {
+                nodes[node].cyclic = true;
+                component[node] = cycles.len();
+            }
+            cycles.push(members);
+        }
+        for edge in &mut edges {
+            edge.cyclic =
+                component[edge.from] != usize::MAX && component[edge.from] == component[edge.to];
+        }
+        for cycle in &mut cycles {
+            cycle.sort_unstable();
+        }
+        cycles.sort();
+
+        DependencyGraph {
+            level,
+            nodes,
+            edges,
+            cycles,
+        }
+    }
+}
+
+/// Find the strongly connected components of a graph with Tarjan's
+/// algorithm.  The graph is walked with an explicit stack, as type graphs can
+/// be deep enough to overflow the call stack.
+pub(crate) fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut index = vec![usize::MAX; successors.len()];
+    let mut low = vec![0; successors.len()];
+    let mut on_stack = vec![false; successors.len()];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut next = 0;
+
+    for root in 0..successors.len() {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        // each frame is a node and the position of its next successor
+        let mut frames = vec![(root, 0)];
+        index[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (node, ref mut position)) = frames.last_mut() {
+            if let Some(&successor) = successors[node].get(*position) {
+                *position += 1;
+                if index[successor] == usize::MAX {
+                    index[successor] = next;
+                    low[successor] = next;
+                    next += 1;
+                    stack.push(successor);
+                    on_stack[successor] = true;
+                    frames.push((successor, 0));
+                } else if on_stack[successor] {
+                    low[node] = low[node].min(index[successor]);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+impl DependencyGraph {
+    /// Write the graph as a Graphviz DOT document.  Cyclic nodes and edges
+    /// are red, and unresolved modules and imports are dashed.
+    pub fn to_dot(&self) -> String {
+        let name = match self.level {
+            GraphLevel::Modules => "modules",
+            GraphLevel::Types => "types",
+        };
+        let mut dot = format!("digraph {name} {{\n\trankdir=LR;\n\tnode [shape=box];\n");
+
+        for node in &self.nodes {
+            let mut attributes = vec![];
+            if !node.resolved {
+                attributes.push("style=dashed".to_string());
+                attributes.push(format!("tooltip={}", dot_string("not found")));
+            }
+            if node.cyclic {
+                attributes.push("color=red".to_string());
+            }
+            dot.push('\t');
+            dot.push_str(&dot_string(&node.name));
+            push_attributes(&mut dot, &attributes);
+        }
+
+        for edge in &self.edges {
+            let mut attributes = vec![];
+            if !edge.symbols.is_empty() {
+                let symbols = edge.symbols.join(", ");
+                attributes.push(format!("tooltip={}", dot_string(&symbols)));
+            }
+            if !edge.unresolved.is_empty() {
+                let unresolved = edge.unresolved.join("\n");
+                attributes.push("style=dashed".to_string());
+                attributes.push(format!("label={}", dot_string(&unresolved)));
+            }
+            if edge.cyclic {
+                attributes.push("color=red".to_string());
+            }
+            dot.push('\t');
+            dot.push_str(&dot_string(&self.nodes[edge.from].name));
+            dot.push_str(" -> ");
+            dot.push_str(&dot_string(&self.nodes[edge.to].name));
+            push_attributes(&mut dot, &attributes);
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Write the graph as a JSON document, with an object for each node and
+    /// edge, and the node names of each cycle.  Edges refer to nodes by name.
+    pub fn to_json(&self) -> String {
+        let name = |index: usize| Json::from(self.nodes[index].name.as_str());
+        let level = match self.level {
+            GraphLevel::Modules => "modules",
+            GraphLevel::Types => "types",
+        };
+
+        let nodes = self.nodes.iter().map(|node| {
+            Json::object([
+                ("name", Json::from(node.name.as_str())),
+                ("resolved", node.resolved.into()),
+                ("cyclic", node.cyclic.into()),
+            ])
+        });
+        let edges = self.edges.iter().map(|edge| {
+            let mut members = vec![("from", name(edge.from)), ("to", name(edge.to))];
+            if self.level == GraphLevel::Modules {
+                members.push(("symbols", Json::strings(&edge.symbols)));
+                members.push(("unresolved", Json::strings(&edge.unresolved)));
+            }
+            members.push(("cyclic", edge.cyclic.into()));
+            Json::object(members)
+        });
+        let cycles = self
+            .cycles
+            .iter()
+            .map(|cycle| Json::Array(cycle.iter().map(|&node| name(node)).collect()));
+
+        let graph = Json::object([
+            ("level", Json::from(level)),
+            ("nodes", Json::Array(nodes.collect())),
+            ("edges", Json::Array(edges.collect())),
+            ("cycles", Json::Array(cycles.collect())),
+        ]);
+        format!("{graph}\n")
+    }
+}
+
+/// Quote a DOT identifier
+fn dot_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn push_attributes(dot: &mut String, attributes: &[String]) {
+    if !attributes.is_empty() {
+        dot.push_str(" [");
+        dot.push_str(&attributes.join(", "));
+        dot.push(']');
+    }
+    dot.push_str(";\n");
+}
@@ -0,0 +1,489 @@
+//! JSON Schema (draft 2020-12) definitions of the JSON Encoding Rules
+//! (X.697) encodings of analysed types, for validating JER encoded values.
+
+use std::collections::HashSet;
+
+use num_bigint::BigInt;
+
+use crate::{
+    analysis::{restricted_string, AnalysisContext, Bounds, EffectiveConstraints, Environment},
+    ast::{ComponentType, Type, TypeOrValue},
+    cst::AsnNodeId,
+    json::Json,
+};
+
+/// The dialect of the generated schemas
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl AnalysisContext<'_> {
+    /// Generate a JSON Schema document defining every type assignment and
+    /// instance of a parameterized type, in `$defs` named `Module.Name`.
+    /// The comments before an assignment or component are its description.
+    /// The value ranges, sizes, permitted alphabets and patterns of
+    /// constraints are part of the schemas, unless they are extensible.
+    pub fn json_schema(&self) -> String {
+        let mut modules: Vec<_> = self.modules().collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut definitions = vec![];
+        for module in modules {
+            let mut assignments: Vec<_> = module.expanded.iter().collect();
+            assignments.sort_by_key(|(name, _)| *name);
+
+            for (name, var) in assignments {
+                let TypeOrValue::Type(ty) = &*var.value else {
+                    continue;
+                };
+                if matches!(ty, Type::Class(_)) {
+                    continue;
+                }
+
+                // constraints within instances refer to their parameters
+                let instance = module.instances.contains_key(name).then_some(name.as_str());
+                let mut schema = self.schema(module, instance, ty, var.value.id);
+                describe(&mut schema, module.comments.get(&var.id));
+                definitions.push((definition_name(&module.name, name), schema));
+            }
+        }
+
+        let document = Json::object([
+            ("$schema", Json::from(DIALECT)),
+            ("$defs", Json::Object(definitions)),
+        ]);
+        format!("{document}\n")
+    }
+
+    /// Get the schema of the JER encoding of a type within a module, and the
+    /// instance of a parameterized type it is part of, given the node ID of
+    /// the type.  The constraints of referenced types are part of the schemas
+    /// they refer to, so only those written on the type itself are added.
+    fn schema(
+        &self,
+        module: &Environment,
+        instance: Option<&str>,
+        ty: &Type,
+        id: AsnNodeId,
+    ) -> Json {
+        let Some(builtin) = self.expanded_builtin_type(module, ty) else {
+            return self.type_schema(module, instance, ty);
+        };
+
+        let constraints = |follow| match instance {
+            Some(instance) => self.constraints_within(module, instance, ty, id, follow),
+            None => self.constraints_of(module, ty, id, follow),
+        };
+        let own = constraints(false);
+
+        // bit strings of a fixed size are encoded without their length, so
+        // the schema of a reference to one is still that of the referenced type
+        if let Type::BitString(_) = builtin {
+            if own.size.is_some() || !matches!(ty, Type::Reference(_)) {
+                if let Some(bits) = fixed_size(&constraints(true)) {
+                    let digits = bits.div_ceil(8) * 2;
+                    return pattern(&format!("^[0-9A-Fa-f]{{{digits}}}$"));
+                }
+            }
+        }
+
+        let mut schema = self.type_schema(module, instance, ty);
+        constrain(&mut schema, builtin, &own);
+        schema
+    }
+
+    /// Get the schema of the JER encoding of a type, without its constraints
+    fn type_schema(&self, module: &Environment, instance: Option<&str>, ty: &Type) -> Json {
+        match ty {
+            Type::Boolean => typed("boolean", []),
+            Type::Null => typed("null", []),
+            Type::Integer(_) => typed("integer", []),
+
+            // special values are encoded as strings
+            Type::Real => Json::object([(
+                "anyOf",
+                Json::Array(vec![
+                    typed("number", []),
+                    Json::object([("enum", Json::strings(["INF", "-INF", "NaN", "-0"]))]),
+                ]),
+            )]),
+
+            Type::Enumerated(items) => typed(
+                "string",
+                [(
+                    "enum",
+                    Json::strings(items.items().map(|item| item.name.as_str())),
+                )],
+            ),
+
+            // bit strings without a fixed size are encoded with their length
+            Type::BitString(_) => Json::object([
+                ("type", Json::from("object")),
+                (
+                    "properties",
+                    Json::object([
+                        ("value", hex_string()),
+                        (
+                            "length",
+                            typed("integer", [("minimum", Json::Number("0".into()))]),
+                        ),
+                    ]),
+                ),
+                ("required", Json::strings(["value", "length"])),
+                ("additionalProperties", false.into()),
+            ]),
+            Type::OctetString => hex_string(),
+
+            Type::ObjectIdentifier => pattern(r"^[0-2](\.(0|[1-9][0-9]*))+$"),
+            Type::RelativeOid => pattern(r"^(0|[1-9][0-9]*)(\.(0|[1-9][0-9]*))*$"),
+            Type::OidIri => pattern(r"^(/[^/]+)+$"),
+            Type::RelativeOidIri => pattern(r"^[^/]+(/[^/]+)*$"),
+
+            Type::Date => typed("string", [("format", Json::from("date"))]),
+            Type::Duration => typed("string", [("format", Json::from("duration"))]),
+            Type::GeneralizedTime
+            | Type::UTCTime
+            | Type::Time
+            | Type::TimeOfDay
+            | Type::DateTime
+            | Type::ObjectDescriptor
+            | Type::BmpString
+            | Type::GeneralString
+            | Type::GraphicString
+            | Type::IA5String
+            | Type::ISO64String
+            | Type::NumericString
+            | Type::PrintableString
+            | Type::TeletexString
+            | Type::T61String
+            | Type::UniversalString
+            | Type::UTF8String
+            | Type::VideotexString
+            | Type::VisibleString => typed("string", []),
+
+            // members of extensible types that are not known are ignored
+            Type::Sequence(components) | Type::Set(components) => {
+                // extension additions are absent in values of earlier
+                // versions of the type, so are never required
+                let extension_root = components.extension.iter().flat_map(|e| &e.root);
+                let root: HashSet<_> = components
+                    .root
+                    .iter()
+                    .chain(extension_root)
+                    .filter_map(|component| match component {
+                        ComponentType::Named(component) => Some(component.name.id),
+                        ComponentType::ComponentsOf(_) => None,
+                    })
+                    .collect();
+
+                let mut properties = vec![];
+                let mut required = vec![];
+                for component in components.items() {
+                    let ComponentType::Named(component) = component else {
+                        continue;
+                    };
+                    let ty = &component.ty;
+                    let mut schema = self.schema(module, instance, ty, ty.id);
+                    describe(&mut schema, module.comments.get(&component.name.id));
+                    properties.push((component.name.value.clone(), schema));
+
+                    let id = component.name.id;
+                    if root.contains(&id) && !component.optional && component.default.is_none() {
+                        required.push(component.name.value.clone());
+                    }
+                }
+
+                let mut members = vec![
+                    ("type", Json::from("object")),
+                    ("properties", Json::Object(properties)),
+                ];
+                if !required.is_empty() {
+                    members.push(("required", Json::strings(required)));
+                }
+                if components.extension.is_none() && !module.extensibility_implied {
+                    members.push(("additionalProperties", false.into()));
+                }
+                Json::object(members)
+            }
+
+            Type::SequenceOf(of) | Type::SetOf(of) => typed(
+                "array",
+                [("items", self.schema(module, instance, &of.ty, of.ty.id))],
+            ),
+
+            // a choice is encoded as an object with a member named by the
+            // chosen alternative
+            Type::Choice(alternatives) => {
+                let alternatives = alternatives.items().map(|alternative| {
+                    let ty = &alternative.ty;
+                    let mut schema = self.schema(module, instance, ty, ty.id);
+                    describe(&mut schema, module.comments.get(&alternative.name.id));
+                    Json::object([
+                        ("type", Json::from("object")),
+                        (
+                            "properties",
+                            Json::object([(alternative.name.value.clone(), schema)]),
+                        ),
+                        ("required", Json::strings([alternative.name.as_str()])),
+                        ("additionalProperties", false.into()),
+                    ])
+                });
+                Json::object([("oneOf", Json::Array(alternatives.collect()))])
+            }
+
+            // tags are not part of JSON encodings
+            Type::Tagged(tagged) => self.type_schema(module, instance, &tagged.ty),
+
+            Type::Reference(reference) => match self.resolve_expanded(module, reference) {
+                Some((definition, _)) => {
+                    let name = definition_name(&definition.name, &reference.name);
+                    Json::object([("$ref", Json::from(format!("#/$defs/{}", uri(&name))))])
+                }
+                None => Json::object::<&str>([]),
+            },
+
+            // open types encode values of any type, and the remaining types
+            // have no simple JSON encoding, so their values are unconstrained
+            Type::External
+            | Type::EmbeddedPdv
+            | Type::CharacterString
+            | Type::Selection(..)
+            | Type::Class(_)
+            | Type::Field(_)
+            | Type::Parameterized(_) => Json::object::<&str>([]),
+        }
+    }
+}
+
+/// Add the keywords of the constraints of a type to its schema, unless they
+/// are extensible, as values outside of them are values of extensions
+fn constrain(schema: &mut Json, builtin: &Type, constraints: &EffectiveConstraints) {
+    let Json::Object(members) = schema else {
+        return;
+    };
+    let mut keywords = vec![];
+    let mut patterns = vec![];
+
+    let sizes = fixed(&constraints.size);
+    match builtin {
+        Type::Integer(_) => {
+            if let Some(values) = fixed(&constraints.value) {
+                match &values.intervals[..] {
+                    [interval] => {
+                        keywords.extend(bound("minimum", &interval.lower));
+                        keywords.extend(bound("maximum", &interval.upper));
+                    }
+                    intervals => {
+                        let ranges = intervals.iter().map(|interval| {
+                            Json::object(
+                                bound("minimum", &interval.lower)
+                                    .into_iter()
+                                    .chain(bound("maximum", &interval.upper)),
+                            )
+                        });
+                        keywords.push(("anyOf", Json::Array(ranges.collect())));
+                    }
+                }
+            }
+        }
+        Type::SequenceOf(_) | Type::SetOf(_) => {
+            if let Some(sizes) = sizes {
+                keywords.extend(bound("minItems", &sizes.lower().cloned()));
+                keywords.extend(bound("maxItems", &sizes.upper().cloned()));
+            }
+        }
+        // each octet is written as two hexadecimal digits
+        Type::OctetString => {
+            if let Some(sizes) = sizes {
+                keywords.extend(bound("minLength", &sizes.lower().map(|n| n * 2)));
+                keywords.extend(bound("maxLength", &sizes.upper().map(|n| n * 2)));
+            }
+        }
+        Type::BitString(_) => {
+            let Some(sizes) = sizes else {
+                return;
+            };
+            let lower = sizes.lower().cloned().unwrap_or_default();
+            let length = Json::object(
+                [("type", Json::from("integer"))]
+                    .into_iter()
+                    .chain(bound("minimum", &Some(lower)))
+                    .chain(bound("maximum", &sizes.upper().cloned())),
+            );
+            let properties = members.iter_mut().find(|(key, _)| key == "properties");
+            let Some((_, Json::Object(properties))) = properties else {
+                return;
+            };
+            if let Some((_, existing)) = properties.iter_mut().find(|(key, _)| key == "length") {
+                *existing = length;
+            }
+        }
+        ty if restricted_string(ty).is_some() => {
+            if let Some(sizes) = sizes {
+                keywords.extend(bound("minLength", &sizes.lower().cloned()));
+                keywords.extend(bound("maxLength", &sizes.upper().cloned()));
+            }
+            if let Some(alphabet) = fixed(&constraints.alphabet) {
+                patterns.push(alphabet_pattern(alphabet));
+            }
+            patterns.extend(constraints.patterns.iter().map(|p| regex(p)));
+        }
+        _ => (),
+    }
+
+    // a schema has one pattern, so further patterns are in subschemas
+    let mut patterns = patterns.into_iter();
+    if let Some(first) = patterns.next() {
+        keywords.push(("pattern", Json::from(first)));
+    }
+    let rest: Vec<_> = patterns
+        .map(|pattern| Json::object([("pattern", Json::from(pattern))]))
+        .collect();
+    if !rest.is_empty() {
+        keywords.push(("allOf", Json::Array(rest)));
+    }
+
+    members.extend(
+        keywords
+            .into_iter()
+            .map(|(keyword, value)| (keyword.to_string(), value)),
+    );
+}
+
+/// Get the bounds of a constraint, unless it is extensible
+fn fixed<T>(bounds: &Option<Bounds<T>>) -> Option<&Bounds<T>> {
+    bounds.as_ref().filter(|bounds| !bounds.extensible)
+}
+
+/// Get the size of values of a type constrained to a single size
+fn fixed_size(constraints: &EffectiveConstraints) -> Option<u64> {
+    let sizes = fixed(&constraints.size)?;
+    match sizes.intervals.as_slice() {
+        [interval] if interval.lower == interval.upper => interval.lower.as_ref()?.try_into().ok(),
+        _ => None,
+    }
+}
+
+/// Create a keyword for one end of a range, if it is bounded
+fn bound(keyword: &'static str, value: &Option<BigInt>) -> Option<(&'static str, Json)> {
+    Some((keyword, Json::Number(value.as_ref()?.to_string())))
+}
+
+/// Create a regular expression matching strings of the characters of a
+/// permitted alphabet
+fn alphabet_pattern(alphabet: &Bounds<char>) -> String {
+    let mut class = String::new();
+    for interval in &alphabet.intervals {
+        let lower = interval.lower.unwrap_or('\0');
+        let upper = interval.upper.unwrap_or(char::MAX);
+        escape_class(&mut class, lower);
+        if upper != lower {
+            class.push('-');
+            escape_class(&mut class, upper);
+        }
+    }
+    format!("^[{class}]*$")
+}
+
+/// Write a character within a character class of a regular expression
+fn escape_class(class: &mut String, c: char) {
+    match c {
+        '\\' | ']' | '[' | '^' | '-' => {
+            class.push('\\');
+            class.push(c);
+        }
+        c if c.is_control() || u32::from(c) > 0xffff => {
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                class.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+        c => class.push(c),
+    }
+}
+
+/// Convert an ASN.1 regular expression, `PATTERN`, to an ECMA-262 regular
+/// expression matching the whole string.  The quantifiers `#n` and `#(n,m)`
+/// are written with braces, and the rest of the syntax is the same.
+fn regex(pattern: &str) -> String {
+    let mut regex = String::from("^(?:");
+    let mut chars = pattern.chars().peekable();
+    let mut class = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                regex.push(c);
+                regex.extend(chars.next());
+            }
+            '[' => {
+                class = true;
+                regex.push(c);
+            }
+            ']' => {
+                class = false;
+                regex.push(c);
+            }
+            '#' if !class && chars.peek() == Some(&'(') => {
+                chars.next();
+                regex.push('{');
+                for c in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                    regex.push(c);
+                }
+                regex.push('}');
+            }
+            '#' if !class && chars.peek().is_some_and(char::is_ascii_digit) => {
+                regex.push('{');
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    regex.push(digit);
+                }
+                regex.push('}');
+            }
+            c => regex.push(c),
+        }
+    }
+    regex.push_str(")$");
+    regex
+}
+
+/// Get the name of the definition of an assignment in `$defs`
+fn definition_name(module: &str, name: &str) -> String {
+    format!("{module}.{name}")
+}
+
+/// Create a schema of a JSON type with additional keywords
+fn typed<const N: usize>(ty: &str, keywords: [(&str, Json); N]) -> Json {
+    Json::object([("type", Json::from(ty))].into_iter().chain(keywords))
+}
+
+/// Create a schema of a string matching a regular expression
+fn pattern(pattern: &str) -> Json {
+    typed("string", [("pattern", Json::from(pattern))])
+}
+
+/// Create a schema of a string of hexadecimal octets
+fn hex_string() -> Json {
+    pattern("^([0-9A-Fa-f]{2})*$")
+}
+
+/// Add a description to a schema
+fn describe(schema: &mut Json, description: Option<&String>) {
+    if let (Json::Object(members), Some(description)) = (schema, description) {
+        members.push(("description".to_string(), description.as_str().into()));
+    }
+}
+
+/// Percent-encode the characters of a definition name that cannot be part
+/// of a URI fragment, such as the spaces and braces in the names of
+/// instances of parameterized types
+fn uri(name: &str) -> String {
+    let mut encoded = String::new();
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -112,7 +112,7 @@ impl<'a> Lexer<'a> {
             '-' | '\u{2011}' => self.simple_token(TokenKind::Hyphen, offset),
 
             ':' => self.multi_token(TokenKind::Colon, TokenKind::Assignment, offset, "::="),
-            '.' => self.multi_token(TokenKind::Dot, TokenKind::Ellipsis, offset, "..."),
+            '.' => self.dots(offset),
 
             '[' if self.square_bracket_mode == SquareBracketMode::Join => {
                 self.multi_token(TokenKind::LeftSquare, TokenKind::VersionOpen, offset, "[[")
@@ -164,10 +164,7 @@ impl<'a> Lexer<'a> {
 
         match peek {
             Ok(t) => {
-                for _ in 0..t.length {
-                    self.chars.next();
-                }
-
+                self.skip_to(t.offset + t.length as usize);
                 Ok(t)
             }
             Err(e) => Err(e),
@@ -231,10 +228,7 @@ impl<'a> Lexer<'a> {
 
         match peek {
             Ok(t) => {
-                for _ in 0..t.length {
-                    self.chars.next();
-                }
-
+                self.skip_to(t.offset + t.length as usize);
                 Ok(t)
             }
             Err(e) => Err(e),
@@ -248,6 +242,11 @@ impl<'a> Lexer<'a> {
 
     /// Returns true if the lexer is at the end of its source file
     pub fn is_eof(&mut self) -> bool {
+        // whitespace and comments after the last token are not significant
+        if self.skip_trivia().is_err() {
+            return false;
+        }
+
         self.offset() == self.source.len()
     }
 
@@ -312,6 +311,26 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Return a dot, a range separator `..` or an ellipsis `...`
+    fn dots(&self, offset: usize) -> Token {
+        let tok_value = &self.source[offset..];
+
+        let (kind, length) = if tok_value.starts_with("...") {
+            (TokenKind::Ellipsis, 3)
+        } else if tok_value.starts_with("..") {
+            (TokenKind::RangeSeparator, 2)
+        } else {
+            (TokenKind::Dot, 1)
+        };
+
+        Token {
+            kind,
+            length,
+            offset,
+            id: self.id,
+        }
+    }
+
     /// Parse a single line comment which is text between pairs of two hyphens.
     /// Non-breaking hyphens are also accepted instead of hyphens.
     fn single_comment(&mut self, first: char, offset: usize) -> bool {
@@ -492,7 +511,10 @@ impl<'a> Lexer<'a> {
     fn number(&mut self, offset: usize) -> Token {
         let mut length = self.digits(0);
 
-        if let Some(&(_, '.')) = self.chars.peek(length) {
+        // the dot of a range separator is not part of the number, `0..5`
+        if matches!(self.chars.peek(length), Some((_, '.')))
+            && !matches!(self.chars.peek(length + 1), Some((_, '.')))
+        {
             length += 1;
             length = self.digits(length);
         }
@@ -527,18 +549,23 @@ impl<'a> Lexer<'a> {
         len
     }
 
-    /// Parse a character string literal
+    /// Parse a character string literal.  The string may contain any
+    /// characters, so the characters peeked are counted separately from the
+    /// length of the string in bytes.
     fn c_string(&mut self, offset: usize) -> Result<Token> {
         let value = &self.source[offset..];
         let mut length = 1;
+        let mut index = 1;
 
         let mut double = None;
-        while let Some(&(offset, ch)) = self.chars.peek(length) {
+        while let Some(&(offset, ch)) = self.chars.peek(index) {
             length += ch.len_utf8();
+            index += 1;
 
             if ch == '"' {
-                if matches!(self.chars.peek(length), Some(&(_, '"'))) {
+                if matches!(self.chars.peek(index), Some(&(_, '"'))) {
                     length += 1;
+                    index += 1;
 
                     if double.is_none() {
                         double = Some(offset);
@@ -582,6 +609,13 @@ this does not represent two adjacent strings.",
         })
     }
 
+    /// Advance the source text to a byte offset, which is the end of a token
+    fn skip_to(&mut self, end: usize) {
+        while matches!(self.chars.peek(0), Some(&(offset, _)) if offset < end) {
+            self.chars.next();
+        }
+    }
+
     /// Parse either a b_string or an h_string (binary string or hexadecimal string)
     fn bh_string(&mut self, offset: usize) -> Result<Token> {
         let value = &self.source[offset..];
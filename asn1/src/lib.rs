@@ -2,17 +2,58 @@
 
 mod analysis;
 mod ast;
+mod build;
 mod codegen;
+mod compat;
 mod compiler;
 mod cst;
 mod diagnostic;
+mod doc;
+mod flatten;
+mod format;
+mod graph;
+mod json;
+mod json_schema;
 mod lexer;
 mod parser;
+mod proto;
+mod random;
+mod strings;
+mod time;
 mod token;
 mod util;
 
+pub use analysis::{
+    Definition, Iri, IriComponent, IriParseError, Oid, OidEntry, OidRegistry, Real, RealBase,
+    RelativeOid,
+};
+pub use build::{Build, BuildError};
+pub use codegen::{
+    CodegenError, CodegenOptions, CodegenTarget, GeneratedFile, IntegerType, ModuleLayout,
+    NameCase, Naming, ParameterizedTypes, Visibility,
+};
+pub use compat::{Change, ChangeKind, Severity};
 pub use compiler::{AsnCompiler, SourceId};
+pub use cst::{
+    Asn1, Asn1Tag, AsnNodeId, AssignmentNode, ChoiceTypeNode, ComponentTypeNode, ConstraintNode,
+    CstIter, CstNode, DefinedNode, EnumItemNode, EnumeratedTypeNode, ExportsNode, FieldSpecNode,
+    ImportsNode, ModuleDefinitionNode, ModuleIdentifierNode, ObjectClassNode, PrefixTypeNode,
+    SequenceTypeNode, SetTypeNode, TypeAssignmentNode, TypeOrValueNode, ValueAssignmentNode,
+    Visitor,
+};
 pub use diagnostic::{Diagnostic, Level};
+pub use doc::{DocFormat, DocPage};
+pub use format::{FormatOptions, KeywordCase};
+pub use graph::{DependencyGraph, GraphEdge, GraphLevel, GraphNode};
+pub use num_bigint::BigInt;
+pub use proto::ProtoFile;
+pub use random::{RandomOptions, RandomValue, RandomValues, Violation};
+pub use strings::{RestrictedString, StringError};
+pub use time::{
+    Date, DateTime, Duration, GeneralizedTime, PropertySettings, Time, TimeError, TimeOfDay,
+    TimeZone, UtcTime,
+};
+pub use token::{Token, TokenKind};
 
 const _: () = assert!(
     unicode_normalization::UNICODE_VERSION.0 == 15
@@ -14,6 +14,8 @@ use crate::{
     AsnCompiler,
 };
 
+pub(crate) use reference::symbols_from_modules;
+
 /// Parser for ASN.1 definition files
 #[derive(Debug)]
 pub struct Parser<'a> {
@@ -193,12 +195,24 @@ impl<'a> Parser<'a> {
 
         self.result.extend(self.current_nodes.drain(temp_start..));
 
+        // the node covers its contents, excluding any surrounding whitespace
+        let mut spans = self.result[start_node..]
+            .iter()
+            .map(TreeContent::span)
+            .filter(|span| !span.is_empty());
+        let start = spans.next();
+        let span = match (start, spans.next_back()) {
+            (Some(start), Some(end)) => start.start..end.end,
+            (Some(start), None) => start,
+            (None, _) => descriptor.source_offset..descriptor.source_offset,
+        };
+
         self.current_nodes.push(TreeContent::Tree {
             tag: descriptor.tag,
             start_node: start_node.try_into().unwrap(),
             node_count: count.try_into().unwrap(),
-            byte_length: (self.lexer.offset() - descriptor.source_offset).try_into().unwrap(),
-            source_offset: descriptor.source_offset,
+            byte_length: span.len().try_into().unwrap(),
+            source_offset: span.start,
         })
     }
 }
@@ -248,7 +248,14 @@ impl<'a> Parser<'a> {
         ])?;
 
         // TODO: Object set assignment
-        // TODO: Parameterized assignment
+
+        if matches!(
+            name.kind,
+            TokenKind::TypeOrModuleRef | TokenKind::ValueRefOrIdent
+        ) && self.peek(&[])?.kind == TokenKind::LeftCurly
+        {
+            self.parameter_list()?;
+        }
 
         match name.kind {
             TokenKind::TypeOrModuleRef => self.type_assignment()?,
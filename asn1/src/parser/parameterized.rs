@@ -1,8 +1,46 @@
 use crate::{cst::Asn1Tag, token::TokenKind};
 
-use super::{Parser, Result};
+use super::{type_or_value::TypeOrValue, Parser, Result};
 
 impl<'a> Parser<'a> {
+    /// Parse the list of dummy references for a parameterized assignment
+    /// ```bnf
+    /// ParameterList ::= "{" Parameter ("," Parameter)* "}"
+    /// Parameter ::= ParamGovernor ":" DummyReference | DummyReference
+    /// ```
+    pub(super) fn parameter_list(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::ParameterList)?;
+
+        self.next(&[TokenKind::LeftCurly])?;
+
+        loop {
+            self.start_temp_vec(Asn1Tag::Parameter)?;
+
+            // either the governor, or the dummy reference if there is no
+            // governor. This is determined by the presence of the colon.
+            self.type_or_value(TypeOrValue {
+                alternative: &[],
+                subsequent: &[TokenKind::Colon, TokenKind::Comma, TokenKind::RightCurly],
+            })?;
+
+            let tok = self.peek(&[TokenKind::Colon, TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::Colon {
+                self.next(&[TokenKind::Colon])?;
+                self.next(&[TokenKind::TypeOrModuleRef, TokenKind::ValueRefOrIdent])?;
+            }
+
+            self.end_temp_vec(Asn1Tag::Parameter);
+
+            let tok = self.next(&[TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::RightCurly {
+                break;
+            }
+        }
+
+        self.end_temp_vec(Asn1Tag::ParameterList);
+        Ok(())
+    }
+
     /// Parse the parameter list for a parameterized type
     /// ```bnf
     /// ActualParameterList ::= "{" ActualParameter ("," ActualParameter)* "}"
@@ -12,9 +50,17 @@ impl<'a> Parser<'a> {
 
         self.next(&[TokenKind::LeftCurly])?;
 
-        // TODO: contents
+        loop {
+            self.type_or_value(TypeOrValue {
+                alternative: &[],
+                subsequent: &[TokenKind::Comma, TokenKind::RightCurly],
+            })?;
 
-        self.next(&[TokenKind::RightCurly])?;
+            let tok = self.next(&[TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::RightCurly {
+                break;
+            }
+        }
 
         self.end_temp_vec(Asn1Tag::ActualParameterList);
         Ok(())
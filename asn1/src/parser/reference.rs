@@ -1,7 +1,32 @@
+use std::ops::Range;
+
 use crate::{cst::Asn1Tag, token::TokenKind};
 
 use super::{Parser, Result};
 
+/// Token index ranges of a single `SymbolsFromModule` within the flat token
+/// list produced when parsing an imports section.
+/// ```bnf
+/// SymbolsFromModule ::= SymbolList FROM GlobalModuleReference SelectionOption
+/// GlobalModuleReference ::= modulereference AssignedIdentifier
+/// AssignedIdentifier ::= ObjectIdentifierValue | DefinedValue | empty
+/// SelectionOption ::= WITH SUCCESSORS | WITH DESCENDANTS | empty
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SymbolsFromModule {
+    /// The symbol list, including commas and parameterized reference braces
+    pub symbols: Range<usize>,
+
+    /// Index of the module name, the `FROM` keyword is immediately before it
+    pub module: usize,
+
+    /// The assigned identifier after the module name
+    pub identifier: Range<usize>,
+
+    /// The selection option, either `WITH SUCCESSORS` or `WITH DESCENDANTS`
+    pub selection: Range<usize>,
+}
+
 /// Location that a symbol list is being parsed in, so that the next token can
 /// be peeked successfully.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -99,3 +124,88 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 }
+
+/// Split the flat list of (non-comment) token kinds within an imports section
+/// into the symbols imported from each module.  Returns `None` if the tokens do
+/// not form a valid list.
+///
+/// The assigned identifier is ambiguous with the start of the next symbol list
+/// if it is a single value reference, so the identifier is only consumed if it
+/// is not followed by a comma or the `FROM` keyword.
+pub(crate) fn symbols_from_modules(kinds: &[TokenKind]) -> Option<Vec<SymbolsFromModule>> {
+    let mut res = vec![];
+    let mut idx = 0;
+
+    while idx < kinds.len() {
+        let start = idx;
+        loop {
+            if !matches!(
+                kinds.get(idx)?,
+                TokenKind::TypeOrModuleRef | TokenKind::ValueRefOrIdent
+            ) {
+                return None;
+            }
+            idx += 1;
+
+            // parameterized reference `Name {}`
+            if kinds.get(idx) == Some(&TokenKind::LeftCurly) {
+                idx += 2;
+            }
+
+            match kinds.get(idx)? {
+                TokenKind::Comma => idx += 1,
+                TokenKind::KwFrom => break,
+                _ => return None,
+            }
+        }
+        let symbols = start..idx;
+
+        idx += 1;
+        if kinds.get(idx)? != &TokenKind::TypeOrModuleRef {
+            return None;
+        }
+        let module = idx;
+        idx += 1;
+
+        let identifier_start = idx;
+        match (kinds.get(idx), kinds.get(idx + 1)) {
+            (Some(TokenKind::LeftCurly), _) => {
+                let mut depth = 0;
+                while let Some(kind) = kinds.get(idx) {
+                    idx += 1;
+                    match kind {
+                        TokenKind::LeftCurly => depth += 1,
+                        TokenKind::RightCurly => depth -= 1,
+                        _ => (),
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            (Some(TokenKind::TypeOrModuleRef), Some(TokenKind::Dot)) => idx += 3,
+            (Some(TokenKind::ValueRefOrIdent), next)
+                if !matches!(next, Some(TokenKind::Comma | TokenKind::KwFrom)) =>
+            {
+                idx += 1
+            }
+            _ => (),
+        }
+        let identifier = identifier_start..idx.min(kinds.len());
+
+        let selection_start = idx;
+        if kinds.get(idx) == Some(&TokenKind::KwWith) {
+            idx += 2;
+        }
+        let selection = selection_start..idx.min(kinds.len());
+
+        res.push(SymbolsFromModule {
+            symbols,
+            module,
+            identifier,
+            selection,
+        });
+    }
+
+    Some(res)
+}
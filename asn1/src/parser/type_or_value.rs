@@ -1,4 +1,5 @@
 mod composite_ty;
+mod constraint;
 mod object;
 mod ty;
 mod value;
@@ -51,8 +52,6 @@ impl<'a> Parser<'a> {
         expecting: TypeOrValue,
         named: bool,
     ) -> Result<TypeOrValueResult> {
-        // TODO type: constrained type
-
         let tok = self.peek(&[])?;
 
         if expecting.alternative.contains(&tok.kind) {
@@ -61,9 +60,17 @@ impl<'a> Parser<'a> {
 
         self.start_temp_vec(Asn1Tag::TypeOrValue)?;
 
+        // types can be followed by any number of constraints
+        let mut subsequent = expecting.subsequent.to_vec();
+        subsequent.push(TokenKind::LeftParen);
+        let constrained = TypeOrValue {
+            alternative: expecting.alternative,
+            subsequent: &subsequent,
+        };
+
         match tok.kind {
             // either
-            TokenKind::TypeOrModuleRef => self.defined(expecting)?,
+            TokenKind::TypeOrModuleRef => self.defined(constrained)?,
             TokenKind::ValueRefOrIdent => self.ident_type_value(expecting, named)?,
 
             // values
@@ -81,23 +88,23 @@ impl<'a> Parser<'a> {
             }
 
             // types
-            TokenKind::KwInteger => self.integer_type(expecting)?,
-            TokenKind::KwEnumerated => self.enumerated_type(expecting)?,
-            TokenKind::KwObject => self.object_identifier_type(expecting)?,
-            TokenKind::KwBit => self.bit_string_type(expecting)?,
-            TokenKind::KwOctet => self.octet_string_type(expecting)?,
-            TokenKind::KwCharacter => self.character_string_type(expecting)?,
-            TokenKind::KwInstance => self.instance_of_type(expecting)?,
-            TokenKind::KwEmbedded => self.embedded_pdv_type(expecting)?,
-            TokenKind::KwSequence => self.sequence_type(expecting)?,
-            TokenKind::KwSet => self.set_type(expecting)?,
-            TokenKind::KwChoice => self.choice_type(expecting)?,
+            TokenKind::KwInteger => self.integer_type(constrained)?,
+            TokenKind::KwEnumerated => self.enumerated_type(constrained)?,
+            TokenKind::KwObject => self.object_identifier_type(constrained)?,
+            TokenKind::KwBit => self.bit_string_type(constrained)?,
+            TokenKind::KwOctet => self.octet_string_type(constrained)?,
+            TokenKind::KwCharacter => self.character_string_type(constrained)?,
+            TokenKind::KwInstance => self.instance_of_type(constrained)?,
+            TokenKind::KwEmbedded => self.embedded_pdv_type(constrained)?,
+            TokenKind::KwSequence => self.sequence_type(constrained)?,
+            TokenKind::KwSet => self.set_type(constrained)?,
+            TokenKind::KwChoice => self.choice_type(constrained)?,
 
             TokenKind::KwAbstractSyntax | TokenKind::KwTypeIdentifier => {
-                self.object_fields(expecting)?
+                self.object_fields(constrained)?
             }
 
-            TokenKind::LeftSquare => self.prefix_type(expecting)?,
+            TokenKind::LeftSquare => self.prefix_type(constrained)?,
 
             TokenKind::KwBoolean
             | TokenKind::KwNull
@@ -128,7 +135,7 @@ impl<'a> Parser<'a> {
             | TokenKind::KwVideotexString
             | TokenKind::KwVisibleString => {
                 self.next(&[])?;
-                self.open_type_field_value(expecting)?;
+                self.open_type_field_value(constrained)?;
             }
 
             // object definition - used in object class assignment.
@@ -155,6 +162,30 @@ impl<'a> Parser<'a> {
             }
         }
 
+        // the constraints of a named type are parsed with the type, after the
+        // identifier, and values are never constrained
+        let is_value = matches!(
+            tok.kind,
+            TokenKind::ValueRefOrIdent
+                | TokenKind::Number
+                | TokenKind::Hyphen
+                | TokenKind::LeftCurly
+                | TokenKind::KwContaining
+                | TokenKind::CString
+                | TokenKind::KwTrue
+                | TokenKind::KwFalse
+                | TokenKind::BHString
+                | TokenKind::KwPlusInfinity
+                | TokenKind::KwNotANumber
+                | TokenKind::KwMinusInfinity
+                | TokenKind::KwClass
+        );
+        if !is_value {
+            while self.peek(&[])?.kind == TokenKind::LeftParen {
+                self.constraint()?;
+            }
+        }
+
         self.end_temp_vec(Asn1Tag::TypeOrValue);
         Ok(TypeOrValueResult::TypeOrValue)
     }
@@ -190,15 +221,14 @@ impl<'a> Parser<'a> {
             // external references
             self.next(&[TokenKind::Dot])?;
 
-            let tok = self.peek(&[
+            // either the name in an external reference, or the first field of
+            // an information object class, `CLASS.&field`
+            self.next(&[
                 TokenKind::ValueRefOrIdent,
                 TokenKind::TypeOrModuleRef,
                 TokenKind::TypeField,
                 TokenKind::ValueField,
             ])?;
-            if tok.kind == TokenKind::ValueRefOrIdent || tok.kind == TokenKind::TypeOrModuleRef {
-                self.next(&[])?;
-            }
         }
 
         let mut kind = expecting.subsequent.to_vec();
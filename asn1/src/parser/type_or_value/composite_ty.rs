@@ -21,10 +21,23 @@ impl<'a> Parser<'a> {
 
         self.next(vec![kind])?;
 
-        let tok = self.peek(&[TokenKind::LeftCurly, TokenKind::KwOf])?;
+        let tok = self.peek(&[
+            TokenKind::LeftCurly,
+            TokenKind::KwOf,
+            TokenKind::KwSize,
+            TokenKind::LeftParen,
+        ])?;
         if tok.kind == TokenKind::LeftCurly {
             self.struct_type()?;
         } else {
+            // the constraint of `SEQUENCE SIZE (1..5) OF` or `SEQUENCE (...) OF`
+            if tok.kind == TokenKind::KwSize {
+                self.next(&[TokenKind::KwSize])?;
+            }
+            if tok.kind != TokenKind::KwOf {
+                self.constraint()?;
+            }
+
             self.next(&[TokenKind::KwOf])?;
             self.type_or_value_named(TypeOrValue {
                 alternative: &[],
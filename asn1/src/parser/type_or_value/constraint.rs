@@ -0,0 +1,358 @@
+//! constraint parsing
+
+use crate::{cst::Asn1Tag, token::TokenKind};
+
+use super::{Parser, Result, TypeOrValue};
+
+/// Tokens that combine the elements of an element set
+const SET_OPERATORS: &[TokenKind] = &[
+    TokenKind::Pipe,
+    TokenKind::KwUnion,
+    TokenKind::Caret,
+    TokenKind::KwIntersection,
+    TokenKind::KwExcept,
+];
+
+/// Tokens that can follow an element set
+const SET_END: &[TokenKind] = &[
+    TokenKind::Comma,
+    TokenKind::LeftCurly,
+    TokenKind::Exclamation,
+    TokenKind::RightParen,
+];
+
+/// Keywords giving the presence of a component in an inner type constraint
+const PRESENCE: &[TokenKind] = &[
+    TokenKind::KwPresent,
+    TokenKind::KwAbsent,
+    TokenKind::KwOptional,
+];
+
+/// Tokens that can follow an element of an element set
+fn element_end() -> Vec<TokenKind> {
+    [SET_OPERATORS, SET_END].concat()
+}
+
+impl<'a> Parser<'a> {
+    /// Parse a constraint following a type, including the parentheses.
+    /// ```bnf
+    /// Constraint ::= "(" ConstraintSpec ExceptionSpec ")"
+    /// ConstraintSpec ::= SubtypeConstraint | GeneralConstraint
+    /// GeneralConstraint ::=
+    ///     UserDefinedConstraint
+    ///   | TableConstraint
+    ///   | ContentsConstraint
+    /// ```
+    /// The object set of a table constraint is parsed as an element set, with
+    /// the list of component relations following it kept as a braced value.
+    pub(super) fn constraint(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::Constraint)?;
+
+        self.next(&[TokenKind::LeftParen])?;
+
+        match self.peek(&[])?.kind {
+            TokenKind::KwContaining | TokenKind::KwEncoded => self.contents_constraint()?,
+            TokenKind::KwConstrained => self.user_defined_constraint()?,
+            _ => {
+                self.element_set_specs()?;
+
+                let tok = self.peek(&[
+                    TokenKind::LeftCurly,
+                    TokenKind::Exclamation,
+                    TokenKind::RightParen,
+                ])?;
+                if tok.kind == TokenKind::LeftCurly {
+                    self.braced_value()?;
+                }
+            }
+        }
+
+        self.exception_spec(&[TokenKind::RightParen])?;
+        self.next(&[TokenKind::RightParen])?;
+
+        self.end_temp_vec(Asn1Tag::Constraint);
+        Ok(())
+    }
+
+    /// Parse the root and extension additions of a subtype constraint
+    /// ```bnf
+    /// ElementSetSpecs ::=
+    ///     RootElementSetSpec
+    ///   | RootElementSetSpec "," "..."
+    ///   | RootElementSetSpec "," "..." "," AdditionalElementSetSpec
+    /// ```
+    /// An extension marker without a root is also accepted, `(...)`.
+    fn element_set_specs(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::ElementSetSpecs)?;
+
+        let mut tok = self.peek(&[])?;
+        if tok.kind != TokenKind::Ellipsis {
+            self.element_set_spec()?;
+            tok = self.peek(SET_END)?;
+            if tok.kind == TokenKind::Comma {
+                self.next(&[TokenKind::Comma])?;
+                tok = self.peek(&[TokenKind::Ellipsis])?;
+            }
+        }
+
+        if tok.kind == TokenKind::Ellipsis {
+            self.next(&[TokenKind::Ellipsis])?;
+            if self.peek(SET_END)?.kind == TokenKind::Comma {
+                self.next(&[TokenKind::Comma])?;
+                self.element_set_spec()?;
+            }
+        }
+
+        self.end_temp_vec(Asn1Tag::ElementSetSpecs);
+        Ok(())
+    }
+
+    /// Parse a set of elements combined by set operators.  The operators are
+    /// kept in a flat list with the elements, their precedence is applied
+    /// when the constraint is interpreted.
+    /// ```bnf
+    /// ElementSetSpec ::= Unions | ALL Exclusions
+    /// Unions ::= Intersections | UElems UnionMark Intersections
+    /// Intersections ::= IntersectionElements | IElems IntersectionMark IntersectionElements
+    /// IntersectionElements ::= Elements | Elems Exclusions
+    /// Exclusions ::= EXCEPT Elements
+    /// ```
+    fn element_set_spec(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::ElementSetSpec)?;
+
+        if self.peek(&[])?.kind == TokenKind::KwAll {
+            self.next(&[TokenKind::KwAll])?;
+            self.next(&[TokenKind::KwExcept])?;
+        }
+
+        loop {
+            self.subtype_element()?;
+
+            let tok = self.peek(element_end())?;
+            if !SET_OPERATORS.contains(&tok.kind) {
+                break;
+            }
+            self.next(SET_OPERATORS)?;
+        }
+
+        self.end_temp_vec(Asn1Tag::ElementSetSpec);
+        Ok(())
+    }
+
+    /// Parse a single element of an element set
+    /// ```bnf
+    /// Elements ::= SubtypeElements | ObjectSetElements | "(" ElementSetSpec ")"
+    /// SubtypeElements ::=
+    ///     SingleValue
+    ///   | ContainedSubtype
+    ///   | ValueRange
+    ///   | PermittedAlphabet
+    ///   | SizeConstraint
+    ///   | TypeConstraint
+    ///   | InnerTypeConstraints
+    ///   | PatternConstraint
+    ///   | PropertySettings
+    /// ```
+    fn subtype_element(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::SubtypeElement)?;
+
+        let subsequent = element_end();
+
+        match self.peek(&[])?.kind {
+            TokenKind::KwSize | TokenKind::KwFrom => {
+                self.next(&[TokenKind::KwSize, TokenKind::KwFrom])?;
+                self.constraint()?;
+            }
+            TokenKind::KwWith => {
+                self.next(&[TokenKind::KwWith])?;
+                let tok = self.next(&[TokenKind::KwComponent, TokenKind::KwComponents])?;
+                if tok.kind == TokenKind::KwComponent {
+                    self.constraint()?;
+                } else {
+                    self.multiple_type_constraints()?;
+                }
+            }
+            TokenKind::KwPattern | TokenKind::KwIncludes => {
+                self.next(&[TokenKind::KwPattern, TokenKind::KwIncludes])?;
+                self.type_or_value(TypeOrValue {
+                    alternative: &[],
+                    subsequent: &subsequent,
+                })?;
+            }
+            TokenKind::KwSettings => {
+                self.next(&[TokenKind::KwSettings])?;
+                self.next(&[TokenKind::CString])?;
+            }
+            TokenKind::LeftParen => {
+                self.next(&[TokenKind::LeftParen])?;
+                self.element_set_spec()?;
+                self.next(&[TokenKind::RightParen])?;
+            }
+            TokenKind::KwMin => {
+                self.next(&[TokenKind::KwMin])?;
+                self.value_range()?;
+            }
+            _ => {
+                let mut kind = subsequent.clone();
+                kind.push(TokenKind::Less);
+                kind.push(TokenKind::RangeSeparator);
+                self.type_or_value(TypeOrValue {
+                    alternative: &[],
+                    subsequent: &kind,
+                })?;
+
+                let tok = self.peek(kind)?;
+                if matches!(tok.kind, TokenKind::Less | TokenKind::RangeSeparator) {
+                    self.value_range()?;
+                }
+            }
+        }
+
+        self.end_temp_vec(Asn1Tag::SubtypeElement);
+        Ok(())
+    }
+
+    /// Parse the rest of a value range after its lower endpoint value
+    /// ```bnf
+    /// ValueRange ::= LowerEndpoint ".." UpperEndpoint
+    /// LowerEndpoint ::= LowerEndValue | LowerEndValue "<"
+    /// UpperEndpoint ::= UpperEndValue | "<" UpperEndValue
+    /// ```
+    fn value_range(&mut self) -> Result {
+        if self
+            .peek(&[TokenKind::Less, TokenKind::RangeSeparator])?
+            .kind
+            == TokenKind::Less
+        {
+            self.next(&[TokenKind::Less])?;
+        }
+        self.next(&[TokenKind::RangeSeparator])?;
+
+        if self.peek(&[])?.kind == TokenKind::Less {
+            self.next(&[TokenKind::Less])?;
+        }
+
+        if self.peek(&[])?.kind == TokenKind::KwMax {
+            self.next(&[TokenKind::KwMax])?;
+            return Ok(());
+        }
+
+        self.type_or_value(TypeOrValue {
+            alternative: &[],
+            subsequent: &element_end(),
+        })?;
+
+        Ok(())
+    }
+
+    /// Parse the constraints on the components of a sequence, set or choice
+    /// type, starting with the curly brace.
+    /// ```bnf
+    /// MultipleTypeConstraints ::= "{" ( "..." "," )? TypeConstraints "}"
+    /// TypeConstraints ::= NamedConstraint ( "," NamedConstraint )*
+    /// ```
+    fn multiple_type_constraints(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::MultipleTypeConstraints)?;
+
+        self.next(&[TokenKind::LeftCurly])?;
+
+        let tok = self.peek(&[TokenKind::Ellipsis, TokenKind::ValueRefOrIdent])?;
+        if tok.kind == TokenKind::Ellipsis {
+            self.next(&[TokenKind::Ellipsis])?;
+            self.next(&[TokenKind::Comma])?;
+        }
+
+        loop {
+            self.named_constraint()?;
+            let tok = self.next(&[TokenKind::Comma, TokenKind::RightCurly])?;
+            if tok.kind == TokenKind::RightCurly {
+                break;
+            }
+        }
+
+        self.end_temp_vec(Asn1Tag::MultipleTypeConstraints);
+        Ok(())
+    }
+
+    /// Parse the constraint on a single component
+    /// `identifier Constraint? ( PRESENT | ABSENT | OPTIONAL )?`
+    fn named_constraint(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::NamedConstraint)?;
+
+        self.next(&[TokenKind::ValueRefOrIdent])?;
+
+        let mut kind = PRESENCE.to_vec();
+        kind.extend([
+            TokenKind::LeftParen,
+            TokenKind::Comma,
+            TokenKind::RightCurly,
+        ]);
+
+        if self.peek(kind.clone())?.kind == TokenKind::LeftParen {
+            self.constraint()?;
+        }
+        if PRESENCE.contains(&self.peek(kind)?.kind) {
+            self.next(PRESENCE)?;
+        }
+
+        self.end_temp_vec(Asn1Tag::NamedConstraint);
+        Ok(())
+    }
+
+    /// Parse a contents constraint of a bit string or octet string type
+    /// ```bnf
+    /// ContentsConstraint ::=
+    ///     CONTAINING Type
+    ///   | ENCODED BY Value
+    ///   | CONTAINING Type ENCODED BY Value
+    /// ```
+    fn contents_constraint(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::ContentsConstraint)?;
+
+        let subsequent = &[
+            TokenKind::KwEncoded,
+            TokenKind::Exclamation,
+            TokenKind::RightParen,
+        ];
+
+        let tok = self.next(&[TokenKind::KwContaining, TokenKind::KwEncoded])?;
+        let encoded = if tok.kind == TokenKind::KwContaining {
+            self.type_or_value(TypeOrValue {
+                alternative: &[],
+                subsequent,
+            })?;
+            if self.peek(subsequent)?.kind == TokenKind::KwEncoded {
+                self.next(&[TokenKind::KwEncoded])?;
+                true
+            } else {
+                false
+            }
+        } else {
+            true
+        };
+
+        if encoded {
+            self.next(&[TokenKind::KwBy])?;
+            self.type_or_value(TypeOrValue {
+                alternative: &[],
+                subsequent: &subsequent[1..],
+            })?;
+        }
+
+        self.end_temp_vec(Asn1Tag::ContentsConstraint);
+        Ok(())
+    }
+
+    /// Parse a user defined constraint, `CONSTRAINED BY { ... }`.  The
+    /// parameters are kept as a braced value as they are not checked.
+    fn user_defined_constraint(&mut self) -> Result {
+        self.start_temp_vec(Asn1Tag::UserDefinedConstraint)?;
+
+        self.next(&[TokenKind::KwConstrained])?;
+        self.next(&[TokenKind::KwBy])?;
+        self.braced_value()?;
+
+        self.end_temp_vec(Asn1Tag::UserDefinedConstraint);
+        Ok(())
+    }
+}
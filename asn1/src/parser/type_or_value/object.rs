@@ -85,12 +85,7 @@ impl<'a> Parser<'a> {
             TypeOrValueResult::Alternate(TokenKind::TypeField | TokenKind::ValueField)
         ) {
             self.next(&[TokenKind::TypeField, TokenKind::ValueField])?;
-            self.field(&[
-                TokenKind::KwOptional,
-                TokenKind::KwDefault,
-                TokenKind::Comma,
-                TokenKind::RightCurly,
-            ])?;
+            self.field_path()?;
         }
 
         self.optionality_spec()?;
@@ -123,12 +118,7 @@ impl<'a> Parser<'a> {
             }
         } else {
             self.next(&[TokenKind::TypeField, TokenKind::ValueField])?;
-            self.field(&[
-                TokenKind::KwOptional,
-                TokenKind::KwDefault,
-                TokenKind::Comma,
-                TokenKind::RightCurly,
-            ])?;
+            self.field_path()?;
         }
 
         self.optionality_spec()?;
@@ -137,6 +127,25 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    /// Parse the remainder of a field name that gives the type of a variable
+    /// type field, `&Type.&Inner`, after its first field has been consumed.
+    fn field_path(&mut self) -> Result {
+        let subsequent = &[
+            TokenKind::KwOptional,
+            TokenKind::KwDefault,
+            TokenKind::Comma,
+            TokenKind::RightCurly,
+        ];
+
+        let mut kind = subsequent.to_vec();
+        kind.push(TokenKind::Dot);
+        if self.peek(kind)?.kind == TokenKind::Dot {
+            self.field(subsequent)?;
+        }
+
+        Ok(())
+    }
+
     /// Optionally parse optional or default specifiers
     fn optionality_spec(&mut self) -> Result {
         self.start_temp_vec(Asn1Tag::OptionalitySpec)?;
@@ -171,6 +180,7 @@ impl<'a> Parser<'a> {
         kind.push(TokenKind::KwWith);
         let tok = self.peek(kind)?;
         if tok.kind != TokenKind::KwWith {
+            self.end_temp_vec(Asn1Tag::SyntaxSpec);
             return Ok(());
         }
 
@@ -84,7 +84,10 @@ impl<'a> Parser<'a> {
         let mut kind = expecting.subsequent.to_vec();
         kind.push(TokenKind::Colon);
         let tok = self.peek(kind)?;
-        if tok.kind != TokenKind::Colon {
+
+        // a colon that may follow the type is not the start of a value, such
+        // as the colon after a parameter governor
+        if tok.kind != TokenKind::Colon || expecting.subsequent.contains(&TokenKind::Colon) {
             return Ok(());
         }
 
@@ -0,0 +1,468 @@
+//! Protocol Buffers (proto3) schemas of analysed modules, with a file and
+//! package for each module.
+//!
+//! Sequence and set types are messages, choice types are messages with a
+//! `oneof`, and enumerated types are enums numbered by their enumeration
+//! values.  Other types assigned a name are messages with a single field
+//! named `value`.  Types without a direct equivalent are mapped as follows:
+//! - `INTEGER` is `int64`, or `uint64` if its constraints only permit
+//!   non-negative values that reach beyond `int64`, and `REAL` is `double`.
+//! - `BIT STRING` is the `asn1.BitString` message of `asn1.proto`, holding
+//!   the bits from the most significant bit of the first octet, and the
+//!   number of bits.
+//! - `OBJECT IDENTIFIER` and `RELATIVE-OID` are strings in dotted notation,
+//!   and the time types are strings of their ASN.1 value notation.
+//! - `NULL` is `google.protobuf.Empty`, and open types, `EXTERNAL`,
+//!   `EMBEDDED PDV` and `CHARACTER STRING` are `google.protobuf.Any`.
+//!
+//! Fields are numbered in the order components were added to a type: the
+//! components of the extension root first, then the extension additions in
+//! order, so that adding an extension keeps the numbers of existing fields.
+//! `SEQUENCE OF` types that cannot be repeated fields, such as a choice
+//! alternative or the elements of another `SEQUENCE OF`, are wrapped in a
+//! message.  `OPTIONAL` and `DEFAULT` components and extension additions are
+//! `optional` fields, except repeated fields, which are empty when absent.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+};
+
+use convert_case::{Case, Casing};
+
+use crate::{
+    analysis::{enum_numbers, AnalysisContext, Environment},
+    ast::{ComponentType, EnumItem, Extensible, ExtensionAddition, NamedType, Type, TypeOrValue},
+    cst::AsnNodeId,
+};
+
+/// A generated `.proto` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtoFile {
+    /// Name of the file, the package name with the `.proto` extension
+    pub file: String,
+
+    /// Content of the file
+    pub content: String,
+}
+
+/// The file defining messages shared by the generated files
+const SHARED_FILE: &str = "asn1.proto";
+
+impl AnalysisContext<'_> {
+    /// Generate a proto3 file for each module, and a shared file if any
+    /// module has a bit string type
+    pub fn protobuf(&self) -> Vec<ProtoFile> {
+        let mut modules: Vec<_> = self.modules().collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut files = vec![];
+        let mut shared = false;
+        for module in modules {
+            let mut codegen = ProtoCodegen {
+                analysis: self,
+                module,
+                instance: None,
+                imports: BTreeSet::new(),
+                result: String::new(),
+            };
+            codegen.run();
+            shared |= codegen.imports.contains(SHARED_FILE);
+            files.push(codegen.finish());
+        }
+
+        if shared {
+            files.push(ProtoFile {
+                file: SHARED_FILE.to_string(),
+                content: concat!(
+                    "// Types shared by the messages generated from ASN.1 modules\n",
+                    "syntax = \"proto3\";\n\n",
+                    "package asn1;\n\n",
+                    "// A bit string, from the most significant bit of the first octet\n",
+                    "message BitString {\n",
+                    "  bytes value = 1;\n",
+                    "  // number of bits\n",
+                    "  uint64 length = 2;\n",
+                    "}\n",
+                )
+                .to_string(),
+            });
+        }
+        files
+    }
+}
+
+/// Generate the proto3 file of a module
+struct ProtoCodegen<'a> {
+    analysis: &'a AnalysisContext<'a>,
+    module: &'a Environment,
+
+    /// The instance of a parameterized type being written, whose dummy
+    /// references in constraints are its actual parameters
+    instance: Option<&'a str>,
+
+    /// Files imported by the generated definitions
+    imports: BTreeSet<String>,
+    result: String,
+}
+
+/// A definition nested within a message, named after the field using it
+type Nested<'t> = Vec<(String, &'t Type, AsnNodeId)>;
+
+impl<'a> ProtoCodegen<'a> {
+    fn run(&mut self) {
+        let module = self.module;
+        let mut assignments: Vec<_> = module.expanded.iter().collect();
+        assignments.sort_by_key(|(name, _)| *name);
+
+        for (name, var) in assignments {
+            let TypeOrValue::Type(ty) = &*var.value else {
+                continue;
+            };
+            if matches!(ty, Type::Class(_)) {
+                continue;
+            }
+
+            self.result.push('\n');
+            self.comment(0, var.id);
+            self.instance = module.instances.contains_key(name).then_some(name.as_str());
+            self.definition(0, &message_name(name), ty, var.value.id);
+        }
+    }
+
+    fn finish(self) -> ProtoFile {
+        let package = package_name(&self.module.name);
+        let mut content = format!(
+            "// Generated from the ASN.1 module `{}`\n",
+            self.module.name
+        );
+        content.push_str("syntax = \"proto3\";\n\n");
+        let _ = writeln!(content, "package {package};");
+        if !self.imports.is_empty() {
+            content.push('\n');
+        }
+        for import in &self.imports {
+            let _ = writeln!(content, "import \"{import}\";");
+        }
+        content.push_str(&self.result);
+
+        ProtoFile {
+            file: format!("{package}.proto"),
+            content,
+        }
+    }
+
+    /// Write the message or enum representing a type, given the node ID of
+    /// the type
+    fn definition(&mut self, indent: usize, name: &str, ty: &Type, id: AsnNodeId) {
+        match ty {
+            Type::Tagged(tagged) => self.definition(indent, name, &tagged.ty, tagged.ty.id),
+            Type::Sequence(components) | Type::Set(components) => {
+                self.message(indent, name, components)
+            }
+            Type::Choice(alternatives) => self.choice(indent, name, alternatives),
+            Type::Enumerated(items) => self.enumeration(indent, name, items),
+            ty => {
+                let mut nested = vec![];
+                let (field_type, repeated) = self.field_type(ty, id, "value", &mut nested);
+                let label = if repeated { "repeated " } else { "" };
+
+                self.line(indent, format_args!("message {name} {{"));
+                self.line(indent + 1, format_args!("{label}{field_type} value = 1;"));
+                self.nested(indent + 1, nested);
+                self.line(indent, format_args!("}}"));
+            }
+        }
+    }
+
+    fn message(&mut self, indent: usize, name: &str, components: &Extensible<ComponentType>) {
+        // extension additions are numbered after every root component
+        let additions = components
+            .extension
+            .iter()
+            .flat_map(|ext| ext.additions.iter().flat_map(ExtensionAddition::items));
+        let numbers: HashMap<_, _> = components
+            .root_items()
+            .chain(additions)
+            .filter_map(|component| match component {
+                ComponentType::Named(component) => Some(component.name.id),
+                ComponentType::ComponentsOf(_) => None,
+            })
+            .zip(1..)
+            .collect();
+        let root = components
+            .root_items()
+            .filter(|component| matches!(component, ComponentType::Named(_)))
+            .count();
+
+        self.line(indent, format_args!("message {name} {{"));
+        let mut nested = vec![];
+        for component in components.items() {
+            let ComponentType::Named(component) = component else {
+                continue;
+            };
+
+            let ty = &component.ty;
+            let (field_type, repeated) = self.field_type(ty, ty.id, &component.name, &mut nested);
+            // extension additions are absent in values of earlier versions
+            let number = numbers[&component.name.id];
+            let optional = component.optional || component.default.is_some() || number > root;
+            let label = match (repeated, optional) {
+                (true, _) => "repeated ",
+                (false, true) => "optional ",
+                (false, false) => "",
+            };
+            let field = field_name(&component.name);
+            self.comment(indent + 1, component.name.id);
+            self.line(
+                indent + 1,
+                format_args!("{label}{field_type} {field} = {number};"),
+            );
+        }
+        self.nested(indent + 1, nested);
+        self.line(indent, format_args!("}}"));
+    }
+
+    fn choice(&mut self, indent: usize, name: &str, alternatives: &Extensible<NamedType>) {
+        let additions = alternatives
+            .extension
+            .iter()
+            .flat_map(|ext| ext.additions.iter().flat_map(ExtensionAddition::items));
+        let numbers: HashMap<_, _> = alternatives
+            .root_items()
+            .chain(additions)
+            .map(|alternative| alternative.name.id)
+            .zip(1..)
+            .collect();
+
+        self.line(indent, format_args!("message {name} {{"));
+        self.line(indent + 1, format_args!("oneof choice {{"));
+        let mut nested = vec![];
+        for alternative in alternatives.items() {
+            // fields of a oneof cannot be repeated
+            let ty = &alternative.ty;
+            let field_type = match self.field_type(ty, ty.id, &alternative.name, &mut nested) {
+                (field_type, false) => field_type,
+                (_, true) => {
+                    let wrapper = message_name(&alternative.name);
+                    nested.push((wrapper.clone(), &ty.value, ty.id));
+                    wrapper
+                }
+            };
+            let field = field_name(&alternative.name);
+            let number = numbers[&alternative.name.id];
+            self.comment(indent + 2, alternative.name.id);
+            self.line(indent + 2, format_args!("{field_type} {field} = {number};"));
+        }
+        self.line(indent + 1, format_args!("}}"));
+        self.nested(indent + 1, nested);
+        self.line(indent, format_args!("}}"));
+    }
+
+    /// Write an enum, numbered by the enumeration values.  The first value of
+    /// a proto3 enum must be zero, so an unspecified value is added if no
+    /// item is zero.
+    fn enumeration(&mut self, indent: usize, name: &str, items: &Extensible<EnumItem>) {
+        let prefix = name.to_case(Case::ScreamingSnake);
+        let known: HashMap<_, _> = enum_numbers(items)
+            .into_iter()
+            .filter_map(|(name, number, _)| Some((name.id, i128::try_from(number).ok()?)))
+            .collect();
+
+        // items numbered by references to values are numbered after the others
+        let mut next = known.values().max().map_or(0, |n| n + 1);
+        let mut values = vec![];
+        for item in items.items() {
+            let number = known.get(&item.name.id).copied().unwrap_or_else(|| {
+                next += 1;
+                next - 1
+            });
+            values.push((
+                Some(item.name.id),
+                item.name.to_case(Case::ScreamingSnake),
+                number,
+            ));
+        }
+        match values.iter().position(|(_, _, number)| *number == 0) {
+            Some(zero) => {
+                let zero = values.remove(zero);
+                values.insert(0, zero);
+            }
+            None => values.insert(0, (None, "UNSPECIFIED".to_string(), 0)),
+        }
+
+        self.line(indent, format_args!("enum {name} {{"));
+        for (id, item, number) in values {
+            if let Some(id) = id {
+                self.comment(indent + 1, id);
+            }
+            self.line(indent + 1, format_args!("{prefix}_{item} = {number};"));
+        }
+        self.line(indent, format_args!("}}"));
+    }
+
+    /// Get the type of a field of an ASN.1 type, given the node ID of the
+    /// type, and whether the field is repeated.  Types defined within the
+    /// field's type are added to the nested definitions, named after the
+    /// field.
+    fn field_type<'t>(
+        &mut self,
+        ty: &'t Type,
+        id: AsnNodeId,
+        field: &str,
+        nested: &mut Nested<'t>,
+    ) -> (String, bool) {
+        let scalar = match ty {
+            Type::Tagged(tagged) => {
+                return self.field_type(&tagged.ty, tagged.ty.id, field, nested)
+            }
+
+            Type::Sequence(_) | Type::Set(_) | Type::Choice(_) | Type::Enumerated(_) => {
+                let name = message_name(field);
+                nested.push((name.clone(), ty, id));
+                return (name, false);
+            }
+
+            // repeated fields of repeated fields are not possible, so inner
+            // lists are wrapped in a message
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                let item = format!("{field}_item");
+                return match self.field_type(&of.ty, of.ty.id, &item, nested) {
+                    (element, false) => (element, true),
+                    (_, true) => {
+                        let wrapper = message_name(&item);
+                        nested.push((wrapper.clone(), &of.ty.value, of.ty.id));
+                        (wrapper, true)
+                    }
+                };
+            }
+
+            Type::Reference(reference) => {
+                match self.analysis.resolve_expanded(self.module, reference) {
+                    Some((module, _)) if module.name == self.module.name => {
+                        message_name(&reference.name)
+                    }
+                    Some((module, _)) => {
+                        let package = package_name(&module.name);
+                        self.imports.insert(format!("{package}.proto"));
+                        format!("{package}.{}", message_name(&reference.name))
+                    }
+                    None => self.import("google.protobuf.Any", "google/protobuf/any.proto"),
+                }
+            }
+
+            Type::Boolean => "bool".to_string(),
+            Type::Integer(_) => self.integer(ty, id).to_string(),
+            Type::Real => "double".to_string(),
+            Type::OctetString => "bytes".to_string(),
+            Type::BitString(_) => self.import("asn1.BitString", SHARED_FILE),
+            Type::Null => self.import("google.protobuf.Empty", "google/protobuf/empty.proto"),
+
+            Type::ObjectIdentifier
+            | Type::RelativeOid
+            | Type::OidIri
+            | Type::RelativeOidIri
+            | Type::GeneralizedTime
+            | Type::UTCTime
+            | Type::Time
+            | Type::Date
+            | Type::TimeOfDay
+            | Type::DateTime
+            | Type::Duration
+            | Type::ObjectDescriptor
+            | Type::BmpString
+            | Type::GeneralString
+            | Type::GraphicString
+            | Type::IA5String
+            | Type::ISO64String
+            | Type::NumericString
+            | Type::PrintableString
+            | Type::TeletexString
+            | Type::T61String
+            | Type::UniversalString
+            | Type::UTF8String
+            | Type::VideotexString
+            | Type::VisibleString => "string".to_string(),
+
+            Type::External
+            | Type::EmbeddedPdv
+            | Type::CharacterString
+            | Type::Selection(..)
+            | Type::Class(_)
+            | Type::Field(_)
+            | Type::Parameterized(_) => {
+                self.import("google.protobuf.Any", "google/protobuf/any.proto")
+            }
+        };
+        (scalar, false)
+    }
+
+    /// Get the type of an `INTEGER` field, given the node ID of the type,
+    /// which is `uint64` if its constraints only permit non-negative values
+    /// that reach beyond `int64`
+    fn integer(&self, ty: &Type, id: AsnNodeId) -> &'static str {
+        let constraints = match self.instance {
+            Some(instance) => self
+                .analysis
+                .constraints_within(self.module, instance, ty, id, true),
+            None => self.analysis.constraints_of(self.module, ty, id, true),
+        };
+        let unsigned = constraints.value.is_some_and(|values| {
+            !values.extensible
+                && values.lower().is_some_and(|lower| *lower >= 0.into())
+                && values
+                    .upper()
+                    .is_some_and(|upper| *upper > i64::MAX.into() && *upper <= u64::MAX.into())
+        });
+        if unsigned {
+            "uint64"
+        } else {
+            "int64"
+        }
+    }
+
+    /// Write the definitions nested within a message
+    fn nested(&mut self, indent: usize, nested: Nested) {
+        for (name, ty, id) in nested {
+            self.definition(indent, &name, ty, id);
+        }
+    }
+
+    /// Use a type defined by an imported file
+    fn import(&mut self, ty: &str, file: &str) -> String {
+        self.imports.insert(file.to_string());
+        ty.to_string()
+    }
+
+    /// Write the comments documenting a name in the source
+    fn comment(&mut self, indent: usize, name: AsnNodeId) {
+        let Some(comments) = self.module.comments.get(&name) else {
+            return;
+        };
+        for line in comments.lines() {
+            match line {
+                "" => self.line(indent, format_args!("//")),
+                line => self.line(indent, format_args!("// {line}")),
+            }
+        }
+    }
+
+    fn line(&mut self, indent: usize, line: std::fmt::Arguments) {
+        let _ = writeln!(self.result, "{:1$}{line}", "", indent * 2);
+    }
+}
+
+/// Get the name of the message or enum of an assignment, an instance of a
+/// parameterized type, `Name { Type, value }`, or a nested definition
+fn message_name(name: &str) -> String {
+    name.replace(|c: char| !c.is_alphanumeric() && c != '-', " ")
+        .to_case(Case::Pascal)
+}
+
+fn field_name(name: &str) -> String {
+    name.to_case(Case::Snake)
+}
+
+fn package_name(module: &str) -> String {
+    module.to_case(Case::Snake)
+}
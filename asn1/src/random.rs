@@ -0,0 +1,1103 @@
+//! Random values of analysed types, for round trip tests of encodings and
+//! fuzzing of decoders.  Values are generated from a seed, so that any value
+//! can be generated again.
+//!
+//! Values conform to the character sets of restricted character string types,
+//! the items of enumerated types, the forms of the time types, the components
+//! required by sequence and set types, and the value ranges, sizes and
+//! permitted alphabets of the constraints on them.  Sizes and integers that are
+//! not constrained, and the depth of recursive values, are limited by the
+//! options instead.  Each of these can also be violated, one at a time, to
+//! check that decoders reject values that are not of their type.
+
+use std::fmt::{self, Write};
+
+use num_bigint::{BigInt, Sign};
+
+use crate::{
+    analysis::{
+        enum_numbers, parse_time, restricted_string, AnalysisContext, Bounds, EffectiveConstraints,
+        Environment, Real, RealBase,
+    },
+    ast::{ComponentType, EnumItem, Extensible, NamedNumber, NamedType, Type, TypeOrValue, Value},
+    cst::AsnNodeId,
+    strings::RestrictedString,
+};
+
+/// The number of characters tried for each character of a string before the
+/// string is abandoned, as its permitted alphabet has no characters of its type
+const CHARACTER_ATTEMPTS: usize = 256;
+
+/// The number of nested values beyond the maximum depth after which a value
+/// is abandoned, as its type has no values that end there
+const DEPTH_LIMIT: usize = 16;
+
+/// A value of an analysed type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RandomValue {
+    Boolean(bool),
+    Null,
+    Integer(BigInt),
+    Real(Real),
+
+    /// An enumeration item, by its identifier and number
+    Enumerated(String, BigInt),
+    BitString(Vec<bool>),
+    OctetString(Vec<u8>),
+
+    /// The arcs of an OBJECT IDENTIFIER or RELATIVE-OID value
+    ObjectIdentifier(Vec<u64>),
+
+    /// A value of a character string type, a time type or an OID-IRI type
+    String(String),
+
+    /// The components of a SEQUENCE or SET value that are present, in the
+    /// order of the type
+    Sequence(Vec<(String, RandomValue)>),
+
+    /// The elements of a SEQUENCE OF or SET OF value
+    SequenceOf(Vec<RandomValue>),
+
+    /// The chosen alternative of a CHOICE value
+    Choice(String, Box<RandomValue>),
+}
+
+/// A value that violates one constraint of its type, and is otherwise a value
+/// of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The component the constraint is violated in, `a.b[2]`, or an empty
+    /// string if it is the whole value
+    pub path: String,
+
+    /// The constraint that is violated
+    pub message: String,
+
+    /// The value
+    pub value: RandomValue,
+}
+
+/// Limits on the random values of types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomOptions {
+    /// The largest number of elements of SEQUENCE OF and SET OF values, and
+    /// of characters, bits and octets of strings
+    pub max_size: usize,
+
+    /// The size of the signed integers that INTEGER values are within
+    pub integer_bits: u32,
+
+    /// The depth of nested values beyond which optional components and
+    /// extension additions are absent, SEQUENCE OF and SET OF values are
+    /// empty, and choices are of alternatives that are not recursive
+    pub max_depth: usize,
+
+    /// Whether values may have extension additions, or are only values of
+    /// the extension root of their type
+    pub extensions: bool,
+}
+
+impl Default for RandomOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 8,
+            integer_bits: 64,
+            max_depth: 6,
+            extensions: true,
+        }
+    }
+}
+
+/// Generates random values of the types analysed by a context
+pub struct RandomValues<'a, 'b> {
+    context: &'a AnalysisContext<'b>,
+    options: RandomOptions,
+    rng: Rng,
+
+    /// The name of the instance of a parameterized type that the value being
+    /// generated is within, if any, whose actual parameters are used in the
+    /// constraints of its types
+    instance: Option<&'a str>,
+}
+
+impl<'a, 'b> RandomValues<'a, 'b> {
+    /// Create a generator of values from a seed.  The same seed and options
+    /// give the same values of the same types.
+    pub fn new(context: &'a AnalysisContext<'b>, seed: u64, options: RandomOptions) -> Self {
+        Self {
+            context,
+            options,
+            rng: Rng(seed),
+            instance: None,
+        }
+    }
+
+    /// Get the module and name of every type assignment and instance of a
+    /// parameterized type that values can be generated of, in order
+    pub fn types(&self) -> Vec<(String, String)> {
+        let mut types = vec![];
+        for module in self.context.modules() {
+            for name in module.expanded.keys() {
+                if self.assignment(&module.name, name).is_some() {
+                    types.push((module.name.clone(), name.clone()));
+                }
+            }
+        }
+        types.sort();
+        types
+    }
+
+    /// Generate a value of a type assignment, or of an instance of a
+    /// parameterized type.  There is no value if the type is not found, or
+    /// has no values that can be generated, such as open types.
+    pub fn value(&mut self, module: &str, name: &str) -> Option<RandomValue> {
+        let (module, ty, id) = self.assignment(module, name)?;
+        self.instance = instance(module, name);
+        self.generate(module, ty, id, 0)
+    }
+
+    /// Generate a value of a type assignment, and return each way of
+    /// violating one constraint of the type within it
+    pub fn violations(&mut self, module: &str, name: &str) -> Vec<Violation> {
+        let Some((module, ty, id)) = self.assignment(module, name) else {
+            return vec![];
+        };
+        self.instance = instance(module, name);
+        match self.generate(module, ty, id, 0) {
+            Some(value) => self.violate(module, ty, id, &value),
+            None => vec![],
+        }
+    }
+
+    /// Find an expanded type assignment, with the node ID of its type
+    fn assignment(
+        &self,
+        module: &str,
+        name: &str,
+    ) -> Option<(&'a Environment, &'a Type, AsnNodeId)> {
+        let module = self.context.module_named(module)?;
+        let var = module.expanded.get(name)?;
+        match &*var.value {
+            TypeOrValue::Type(Type::Class(_)) | TypeOrValue::Value(_) => None,
+            TypeOrValue::Type(ty) => Some((module, ty, var.value.id)),
+        }
+    }
+
+    /// Find the type a type is defined as, following references to other
+    /// expanded types and removing tags
+    fn builtin(
+        &self,
+        module: &'a Environment,
+        ty: &'a Type,
+    ) -> Option<(&'a Environment, &'a Type)> {
+        self.builtin_within(module, ty)
+            .map(|(module, ty, _)| (module, ty))
+    }
+
+    /// Find the type a type is defined as, with the instance of a
+    /// parameterized type that it is within
+    fn builtin_within(
+        &self,
+        module: &'a Environment,
+        ty: &'a Type,
+    ) -> Option<(&'a Environment, &'a Type, Option<&'a str>)> {
+        let mut current = (module, ty, self.instance);
+        let mut visited = vec![];
+        loop {
+            match current.1 {
+                Type::Tagged(tagged) => current.1 = &tagged.ty,
+                Type::Reference(reference) => {
+                    let (module, var) = self.context.resolve_expanded(current.0, reference)?;
+                    if visited.contains(&var.id) {
+                        return None;
+                    }
+                    visited.push(var.id);
+                    let TypeOrValue::Type(ty) = &*var.value else {
+                        return None;
+                    };
+                    let within = match reference.module {
+                        Some(_) => None,
+                        None => instance(module, &reference.name),
+                    };
+                    current = (module, ty, within);
+                }
+                _ => return Some(current),
+            }
+        }
+    }
+
+    /// Get the values of a type permitted by its constraints, within the
+    /// current instance of a parameterized type
+    fn constraints(
+        &self,
+        module: &'a Environment,
+        ty: &'a Type,
+        id: AsnNodeId,
+    ) -> EffectiveConstraints {
+        match self.instance {
+            Some(instance) => self
+                .context
+                .constraints_within(module, instance, ty, id, true),
+            None => self.context.constraints_of(module, ty, id, true),
+        }
+    }
+
+    /// Generate a value of a type, given the node ID of the type, nested
+    /// within `depth` other values
+    fn generate(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        id: AsnNodeId,
+        depth: usize,
+    ) -> Option<RandomValue> {
+        if depth > self.options.max_depth + DEPTH_LIMIT {
+            return None;
+        }
+        let constraints = self.constraints(module, ty, id);
+        let (module, ty, within) = self.builtin_within(module, ty)?;
+
+        let outer = std::mem::replace(&mut self.instance, within);
+        let value = self.builtin_value(module, ty, &constraints, depth);
+        self.instance = outer;
+        value
+    }
+
+    /// Generate a value of a built-in type with its constraints
+    fn builtin_value(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        constraints: &EffectiveConstraints,
+        depth: usize,
+    ) -> Option<RandomValue> {
+        if let Some(restricted) = restricted_string(ty) {
+            return Some(RandomValue::String(self.string(restricted, constraints)?));
+        }
+
+        let shallow = depth >= self.options.max_depth;
+        let sizes = constraints.size.as_ref();
+        Some(match ty {
+            Type::Boolean => RandomValue::Boolean(self.rng.below(2) == 0),
+            Type::Null => RandomValue::Null,
+            Type::Integer(numbers) => {
+                RandomValue::Integer(self.integer(numbers, constraints.value.as_ref())?)
+            }
+            Type::Real => RandomValue::Real(self.real()),
+            Type::Enumerated(items) => {
+                let numbers = self.enumeration(items);
+                let (name, number) = numbers.get(self.rng.index(numbers.len())?)?;
+                RandomValue::Enumerated(name.clone(), number.clone())
+            }
+            Type::BitString(_) => {
+                let len = self.size(sizes)?;
+                RandomValue::BitString((0..len).map(|_| self.rng.below(2) == 0).collect())
+            }
+            Type::OctetString => {
+                let len = self.size(sizes)?;
+                RandomValue::OctetString((0..len).map(|_| self.rng.next() as u8).collect())
+            }
+            Type::ObjectIdentifier => RandomValue::ObjectIdentifier(self.arcs(false)),
+            Type::RelativeOid => RandomValue::ObjectIdentifier(self.arcs(true)),
+            Type::OidIri | Type::RelativeOidIri => {
+                let arcs: Vec<_> = self.arcs(true).iter().map(u64::to_string).collect();
+                let prefix = if *ty == Type::OidIri { "/" } else { "" };
+                RandomValue::String(format!("{prefix}{}", arcs.join("/")))
+            }
+            Type::UTCTime
+            | Type::GeneralizedTime
+            | Type::Time
+            | Type::Date
+            | Type::TimeOfDay
+            | Type::DateTime
+            | Type::Duration => RandomValue::String(self.time(ty)),
+            Type::Sequence(components) | Type::Set(components) => {
+                self.sequence(module, components, depth)?
+            }
+            Type::SequenceOf(of) | Type::SetOf(of) => {
+                let len = match shallow {
+                    true => smallest(sizes)?,
+                    false => self.size(sizes)?,
+                };
+                let mut elements = vec![];
+                for _ in 0..len {
+                    elements.push(self.generate(module, &of.ty, of.ty.id, depth + 1)?);
+                }
+                RandomValue::SequenceOf(elements)
+            }
+            Type::Choice(alternatives) => self.choice(module, alternatives, depth)?,
+
+            // open types hold values of any type, and the remaining types
+            // have values with components of open types
+            _ => return None,
+        })
+    }
+
+    /// Generate a value of a sequence or set type.  Extension additions are
+    /// added in versions of the type, so the additions up to a random one
+    /// are present.
+    fn sequence(
+        &mut self,
+        module: &'a Environment,
+        components: &'a Extensible<ComponentType>,
+        depth: usize,
+    ) -> Option<RandomValue> {
+        let shallow = depth >= self.options.max_depth;
+        let (additions, trailing) = match &components.extension {
+            Some(extension) => (&extension.additions[..], &extension.root[..]),
+            None => (&[][..], &[][..]),
+        };
+        let version = if shallow || !self.options.extensions {
+            0
+        } else {
+            self.rng.index(additions.len() + 1)?
+        };
+
+        let added = additions[..version]
+            .iter()
+            .flat_map(|addition| addition.items());
+        let mut present = vec![];
+        for component in components.root.iter().chain(added).chain(trailing) {
+            // `COMPONENTS OF` is replaced by the components in expanded types
+            let ComponentType::Named(component) = component else {
+                continue;
+            };
+            if component.optional || component.default.is_some() {
+                if shallow || self.rng.below(2) == 0 {
+                    continue;
+                }
+                let ty = &component.ty;
+                if let Some(value) = self.generate(module, ty, ty.id, depth + 1) {
+                    present.push((component.name.value.clone(), value));
+                }
+            } else {
+                let ty = &component.ty;
+                let value = self.generate(module, ty, ty.id, depth + 1)?;
+                present.push((component.name.value.clone(), value));
+            }
+        }
+        Some(RandomValue::Sequence(present))
+    }
+
+    /// Generate a value of a choice type.  Beyond the maximum depth, the
+    /// alternative is the one least likely to be recursive.
+    fn choice(
+        &mut self,
+        module: &'a Environment,
+        alternatives: &'a Extensible<NamedType>,
+        depth: usize,
+    ) -> Option<RandomValue> {
+        let mut candidates: Vec<_> = if self.options.extensions {
+            alternatives.items().collect()
+        } else {
+            alternatives.root_items().collect()
+        };
+
+        if depth >= self.options.max_depth {
+            let alternative = candidates
+                .iter()
+                .min_by_key(|alternative| self.recursion(module, &alternative.ty))?;
+            let ty = &alternative.ty;
+            let value = self.generate(module, ty, ty.id, depth + 1)?;
+            return Some(RandomValue::Choice(
+                alternative.name.value.clone(),
+                Box::new(value),
+            ));
+        }
+
+        // alternatives with no values are skipped
+        while !candidates.is_empty() {
+            let alternative = candidates.swap_remove(self.rng.index(candidates.len())?);
+            let ty = &alternative.ty;
+            if let Some(value) = self.generate(module, ty, ty.id, depth + 1) {
+                return Some(RandomValue::Choice(
+                    alternative.name.value.clone(),
+                    Box::new(value),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Rank how likely the values of a type are to contain further values
+    /// of constructed types
+    fn recursion(&self, module: &'a Environment, ty: &'a Type) -> u8 {
+        match self.builtin(module, ty) {
+            Some((_, Type::SequenceOf(_) | Type::SetOf(_))) => 1,
+            Some((_, Type::Sequence(_) | Type::Set(_))) => 2,
+            Some((_, Type::Choice(_))) => 3,
+            Some(_) => 0,
+            None => 4,
+        }
+    }
+
+    /// The items of an enumerated type that values may be, with their numbers
+    fn enumeration(&self, items: &Extensible<EnumItem>) -> Vec<(String, BigInt)> {
+        let root: Vec<_> = items.root_items().map(|item| item.name.id).collect();
+        enum_numbers(items)
+            .into_iter()
+            .filter(|(name, _, _)| self.options.extensions || root.contains(&name.id))
+            .map(|(name, number, _)| (name.value.clone(), number))
+            .collect()
+    }
+
+    /// Generate a number of elements of a value within the permitted sizes.
+    /// Sizes are limited by the options, unless every permitted size is
+    /// larger.  There is no size if none are permitted.
+    fn size(&mut self, sizes: Option<&Bounds<BigInt>>) -> Option<usize> {
+        let Some(sizes) = sizes else {
+            return self.rng.index(self.options.max_size + 1);
+        };
+
+        let limit = Bounds::range(Some(BigInt::ZERO), Some(self.options.max_size.into()));
+        let size = match self.within(&sizes.intersection(&limit)) {
+            Some(size) => size,
+            None => smallest(Some(sizes))?.into(),
+        };
+        size.try_into().ok()
+    }
+
+    /// Generate an integer within the permitted values, which is sometimes
+    /// one of the named numbers of its type
+    fn integer(
+        &mut self,
+        numbers: &[NamedNumber],
+        values: Option<&Bounds<BigInt>>,
+    ) -> Option<BigInt> {
+        let named: Vec<BigInt> = numbers
+            .iter()
+            .filter_map(|number| match &number.value.value {
+                Value::Number(text) => text.parse().ok(),
+                _ => None,
+            })
+            .filter(|number| values.is_none_or(|values| values.contains(number)))
+            .collect();
+        if !named.is_empty() && self.rng.below(4) == 0 {
+            if let Some(i) = self.rng.index(named.len()) {
+                return Some(named[i].clone());
+            }
+        }
+        match values {
+            Some(values) => self.within(values),
+            None => Some(self.signed(self.options.integer_bits)),
+        }
+    }
+
+    /// Generate an integer within one of the intervals of a set of bounds.
+    /// Intervals without an end extend from the other by a random number of
+    /// bits.
+    fn within(&mut self, bounds: &Bounds<BigInt>) -> Option<BigInt> {
+        let interval = &bounds.intervals[self.rng.index(bounds.intervals.len())?];
+        let bits = self.options.integer_bits;
+        Some(match (&interval.lower, &interval.upper) {
+            (Some(lower), Some(upper)) => lower + self.below(&(upper - lower + 1)),
+            (Some(lower), None) => lower + BigInt::from(self.signed(bits).into_parts().1),
+            (None, Some(upper)) => upper - BigInt::from(self.signed(bits).into_parts().1),
+            (None, None) => self.signed(bits),
+        })
+    }
+
+    /// Generate a non-negative integer less than a positive bound
+    fn below(&mut self, bound: &BigInt) -> BigInt {
+        let mut value = BigInt::default();
+        for _ in 0..=bound.bits().div_ceil(64) {
+            value = (value << 64) | BigInt::from(self.rng.next());
+        }
+        value % bound
+    }
+
+    /// Generate a signed integer of a number of bits.  The number of bits
+    /// actually used is uniform, so that small values are as likely as large
+    /// ones.
+    fn signed(&mut self, bits: u32) -> BigInt {
+        let bits = self.rng.below(bits.max(1).into()) as u32;
+        let mut magnitude = BigInt::default();
+        for _ in 0..bits.div_ceil(64) {
+            magnitude = (magnitude << 64) | BigInt::from(self.rng.next());
+        }
+        magnitude %= BigInt::from(1) << bits;
+        if self.rng.below(2) == 0 {
+            -magnitude - 1
+        } else {
+            magnitude
+        }
+    }
+
+    /// Generate a REAL value.  Finite values are in base 2, with mantissas
+    /// and exponents that a double represents exactly.
+    fn real(&mut self) -> Real {
+        match self.rng.below(16) {
+            0 => Real::PlusInfinity,
+            1 => Real::MinusInfinity,
+            2 => Real::NotANumber,
+            3 => Real::MinusZero,
+            _ => {
+                let exponent = BigInt::from(self.rng.below(129) as i64 - 64);
+                Real::new(self.signed(54), RealBase::Two, exponent)
+            }
+        }
+    }
+
+    /// Generate the arcs of an object identifier, or of a relative object
+    /// identifier.  The first arc of an object identifier is 0, 1 or 2, and
+    /// the second is less than 40 under the first two.
+    fn arcs(&mut self, relative: bool) -> Vec<u64> {
+        let mut arcs = vec![];
+        if !relative {
+            let first = self.rng.below(3);
+            arcs.push(first);
+            arcs.push(if first < 2 {
+                self.rng.below(40)
+            } else {
+                self.arc()
+            });
+        }
+        let len = self.size(None).unwrap_or(0).max(1) - usize::from(!relative);
+        for _ in 0..len {
+            arcs.push(self.arc());
+        }
+        arcs
+    }
+
+    /// Generate an arc of up to 32 bits
+    fn arc(&mut self) -> u64 {
+        let bits = self.rng.below(33);
+        self.rng.next() & ((1 << bits) - 1)
+    }
+
+    /// Generate a value of a restricted character string type within its
+    /// permitted sizes and alphabet.  Half of the characters are ASCII, if
+    /// the type has them and the alphabet is not constrained.
+    fn string(
+        &mut self,
+        ty: RestrictedString,
+        constraints: &EffectiveConstraints,
+    ) -> Option<String> {
+        let range = match ty {
+            RestrictedString::Numeric
+            | RestrictedString::Printable
+            | RestrictedString::Visible
+            | RestrictedString::IA5 => 0x80,
+            RestrictedString::Teletex
+            | RestrictedString::Videotex
+            | RestrictedString::Graphic
+            | RestrictedString::General => 0x180,
+            RestrictedString::Bmp => 0x10000,
+            RestrictedString::Universal | RestrictedString::Utf8 => 0x110000,
+        };
+
+        let len = self.size(constraints.size.as_ref())?;
+        let mut string = String::new();
+        for _ in 0..len {
+            let mut attempts = 0..CHARACTER_ATTEMPTS;
+            loop {
+                attempts.next()?;
+                let c = match &constraints.alphabet {
+                    Some(alphabet) => self.character(alphabet),
+                    None => {
+                        let range = if self.rng.below(2) == 0 { 0x80 } else { range };
+                        char::from_u32(self.rng.below(range) as u32)
+                    }
+                };
+                if let Some(c) = c.filter(|c| ty.contains(*c)) {
+                    string.push(c);
+                    break;
+                }
+            }
+        }
+        Some(string)
+    }
+
+    /// Generate a character within one of the intervals of a permitted
+    /// alphabet
+    fn character(&mut self, alphabet: &Bounds<char>) -> Option<char> {
+        let interval = &alphabet.intervals[self.rng.index(alphabet.intervals.len())?];
+        let lower = interval.lower.map_or(0, u32::from);
+        let upper = interval.upper.map_or(u32::from(char::MAX), u32::from);
+        char::from_u32(lower + self.rng.below(u64::from(upper - lower) + 1) as u32)
+    }
+
+    /// Generate a value of a time type, in the canonical form of the type
+    fn time(&mut self, ty: &Type) -> String {
+        let year = 1950 + self.rng.below(100);
+        let month = 1 + self.rng.below(12);
+        let day = 1 + self.rng.below(28);
+        let hour = self.rng.below(24);
+        let minute = self.rng.below(60);
+        let second = self.rng.below(60);
+        let date = format!("{year:04}-{month:02}-{day:02}");
+        let time = format!("{hour:02}:{minute:02}:{second:02}");
+
+        match ty {
+            Type::UTCTime => format!(
+                "{:02}{month:02}{day:02}{hour:02}{minute:02}{second:02}Z",
+                year % 100
+            ),
+            Type::GeneralizedTime => {
+                // fractions of seconds have no trailing zeros
+                let mut fraction = self.rng.below(1000).to_string();
+                while fraction.ends_with('0') {
+                    fraction.pop();
+                }
+                if !fraction.is_empty() {
+                    fraction.insert(0, '.');
+                }
+                format!("{year:04}{month:02}{day:02}{hour:02}{minute:02}{second:02}{fraction}Z")
+            }
+            Type::Date => date,
+            Type::TimeOfDay => time,
+            Type::DateTime => format!("{date}T{time}"),
+            Type::Duration => {
+                let mut components = [0; 6].map(|_| self.rng.below(100));
+                components[0] += 1;
+                let [years, months, days, hours, minutes, seconds] = components;
+                format!("P{years}Y{months}M{days}DT{hours}H{minutes}M{seconds}S")
+            }
+            _ => match self.rng.below(3) {
+                0 => date,
+                1 => format!("{time}Z"),
+                _ => format!("{date}T{time}Z"),
+            },
+        }
+    }
+
+    /// Find every way of violating one constraint of a type within a value
+    /// of it, given the node ID of the type
+    fn violate(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        id: AsnNodeId,
+        value: &RandomValue,
+    ) -> Vec<Violation> {
+        let constraints = self.constraints(module, ty, id);
+        let Some((module, ty, within)) = self.builtin_within(module, ty) else {
+            return vec![];
+        };
+
+        let outer = std::mem::replace(&mut self.instance, within);
+        let mut violations = self.violate_constraints(module, ty, &constraints, value);
+        violations.extend(self.violate_builtin(module, ty, value));
+        self.instance = outer;
+        violations
+    }
+
+    /// Find every way of violating one constraint of a built-in type within a
+    /// value of it, other than those of its subtype constraints
+    fn violate_builtin(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        value: &RandomValue,
+    ) -> Vec<Violation> {
+        let violation = |message: String, value| Violation {
+            path: String::new(),
+            message,
+            value,
+        };
+
+        let mut violations = vec![];
+        if let (Some(restricted), RandomValue::String(text)) = (restricted_string(ty), value) {
+            let outside = ['!', '~', 'é', '€', '\u{1d11e}']
+                .into_iter()
+                .find(|c| !restricted.contains(*c));
+            if let Some(c) = outside {
+                let text = self.replace_character(text, c);
+                if let Err(e) = restricted.check(&text) {
+                    violations.push(violation(e.to_string(), RandomValue::String(text)));
+                }
+            }
+            return violations;
+        }
+
+        match (ty, value) {
+            (
+                Type::UTCTime
+                | Type::GeneralizedTime
+                | Type::Time
+                | Type::Date
+                | Type::TimeOfDay
+                | Type::DateTime
+                | Type::Duration,
+                RandomValue::String(_),
+            ) => {
+                // each value has a thirteenth month, or no components
+                let text = match ty {
+                    Type::UTCTime => "491301000000Z",
+                    Type::GeneralizedTime => "20241301000000Z",
+                    Type::TimeOfDay => "24:00:01",
+                    Type::DateTime => "2024-13-01T00:00:00",
+                    Type::Duration => "P",
+                    _ => "2024-13-01",
+                };
+                if let Err(e) = parse_time(ty, text) {
+                    violations.push(violation(e.to_string(), RandomValue::String(text.into())));
+                }
+            }
+            (Type::Enumerated(items), RandomValue::Enumerated(..)) if items.extension.is_none() => {
+                let numbers = enum_numbers(items);
+                let number = numbers
+                    .iter()
+                    .map(|(_, number, _)| number)
+                    .max()
+                    .map_or(BigInt::default(), |max| max + 1);
+                let name = (0..)
+                    .map(|i| format!("unknown{i}"))
+                    .find(|name| items.items().all(|item| item.name.value != *name))
+                    .unwrap_or_default();
+                violations.push(violation(
+                    format!("`{name}` ({number}) is not an item of the type"),
+                    RandomValue::Enumerated(name, number),
+                ));
+            }
+            (Type::ObjectIdentifier, RandomValue::ObjectIdentifier(arcs)) => {
+                let mut arcs = arcs.clone();
+                arcs[0] = 3;
+                violations.push(violation(
+                    "The first arc is 3, but must be 0, 1 or 2".into(),
+                    RandomValue::ObjectIdentifier(arcs),
+                ));
+            }
+            (
+                Type::Sequence(components) | Type::Set(components),
+                RandomValue::Sequence(present),
+            ) => {
+                for (i, (name, component_value)) in present.iter().enumerate() {
+                    let component = components.items().find_map(|component| match component {
+                        ComponentType::Named(component) if component.name.value == *name => {
+                            Some(component)
+                        }
+                        _ => None,
+                    });
+                    let Some(component) = component else {
+                        continue;
+                    };
+
+                    let ty = &component.ty;
+                    for inner in self.violate(module, ty, ty.id, component_value) {
+                        let mut present = present.clone();
+                        present[i].1 = inner.value;
+                        violations.push(Violation {
+                            path: join(name, &inner.path),
+                            message: inner.message,
+                            value: RandomValue::Sequence(present),
+                        });
+                    }
+
+                    let root = components.root_items().any(|root| {
+                        matches!(root, ComponentType::Named(root) if root.name.id == component.name.id)
+                    });
+                    if root && !component.optional && component.default.is_none() {
+                        let mut present = present.clone();
+                        present.remove(i);
+                        violations.push(Violation {
+                            path: String::new(),
+                            message: format!("`{name}` is absent, but is not OPTIONAL"),
+                            value: RandomValue::Sequence(present),
+                        });
+                    }
+                }
+            }
+            (Type::SequenceOf(of) | Type::SetOf(of), RandomValue::SequenceOf(elements)) => {
+                for (i, element) in elements.iter().enumerate() {
+                    for inner in self.violate(module, &of.ty, of.ty.id, element) {
+                        let mut elements = elements.clone();
+                        elements[i] = inner.value;
+                        violations.push(Violation {
+                            path: join(&format!("[{i}]"), &inner.path),
+                            message: inner.message,
+                            value: RandomValue::SequenceOf(elements),
+                        });
+                    }
+                }
+            }
+            (Type::Choice(alternatives), RandomValue::Choice(name, chosen)) => {
+                let alternative = alternatives
+                    .items()
+                    .find(|alternative| alternative.name.value == *name);
+                if let Some(alternative) = alternative {
+                    let ty = &alternative.ty;
+                    for inner in self.violate(module, ty, ty.id, chosen) {
+                        violations.push(Violation {
+                            path: join(name, &inner.path),
+                            message: inner.message,
+                            value: RandomValue::Choice(name.clone(), Box::new(inner.value)),
+                        });
+                    }
+                }
+            }
+            _ => (),
+        }
+        violations
+    }
+
+    /// Find the ways of violating the value range, size and permitted
+    /// alphabet constraints of a type within a value of it, one for each.
+    /// Values outside of extensible constraints are values of extensions of
+    /// the type, so they are not violations.
+    fn violate_constraints(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        constraints: &EffectiveConstraints,
+        value: &RandomValue,
+    ) -> Vec<Violation> {
+        let violation = |message: String, value| Violation {
+            path: String::new(),
+            message,
+            value,
+        };
+        let mut violations = vec![];
+
+        let values = constraints
+            .value
+            .as_ref()
+            .filter(|values| !values.extensible);
+        if let (Some(values), RandomValue::Integer(_)) = (values, value) {
+            let outside = match (values.lower(), values.upper()) {
+                (_, Some(upper)) => Some((upper + 1, format!("greater than {upper}"))),
+                (Some(lower), None) => Some((lower - 1, format!("less than {lower}"))),
+                (None, None) => None,
+            };
+            if let Some((integer, message)) = outside {
+                violations.push(violation(
+                    format!("`{integer}` is {message}, the bound of its value constraint"),
+                    RandomValue::Integer(integer),
+                ));
+            }
+        }
+
+        let sizes = constraints.size.as_ref().filter(|sizes| !sizes.extensible);
+        if let Some(sizes) = sizes {
+            let size = match (sizes.lower(), sizes.upper()) {
+                (Some(lower), _) if lower.sign() == Sign::Plus => Some(lower - 1),
+                (_, Some(upper)) => Some(upper + 1),
+                _ => None,
+            };
+            let resized = size
+                .as_ref()
+                .and_then(|size| usize::try_from(size).ok())
+                .and_then(|size| self.resize(module, ty, value, size));
+            if let (Some(size), Some(resized)) = (size, resized) {
+                violations.push(violation(
+                    format!("The size {size} is not permitted by its size constraint"),
+                    resized,
+                ));
+            }
+        }
+
+        let alphabet = constraints.alphabet.as_ref().filter(|a| !a.extensible);
+        if let (Some(alphabet), RandomValue::String(text)) = (alphabet, value) {
+            let restricted = restricted_string(ty);
+            let outside = (' '..='~')
+                .chain(['é', '€', '\u{1d11e}'])
+                .find(|c| !alphabet.contains(c) && restricted.is_none_or(|ty| ty.contains(*c)));
+            if let Some(c) = outside {
+                violations.push(violation(
+                    format!("`{c}` is not in the permitted alphabet"),
+                    RandomValue::String(self.replace_character(text, c)),
+                ));
+            }
+        }
+
+        violations
+    }
+
+    /// Replace a random character of a string with another, or add the
+    /// character if the string is empty
+    fn replace_character(&mut self, text: &str, c: char) -> String {
+        let mut chars: Vec<_> = text.chars().collect();
+        match self.rng.index(chars.len()) {
+            Some(at) => chars[at] = c,
+            None => chars.push(c),
+        }
+        chars.into_iter().collect()
+    }
+
+    /// Change the number of elements of a value, removing elements from its
+    /// end or repeating its last element
+    fn resize(
+        &mut self,
+        module: &'a Environment,
+        ty: &'a Type,
+        value: &RandomValue,
+        size: usize,
+    ) -> Option<RandomValue> {
+        fn resize<T: Clone>(items: &[T], size: usize, fill: T) -> Vec<T> {
+            let fill = items.last().cloned().unwrap_or(fill);
+            let mut items = items.to_vec();
+            items.resize(size, fill);
+            items
+        }
+
+        Some(match value {
+            RandomValue::BitString(bits) => RandomValue::BitString(resize(bits, size, false)),
+            RandomValue::OctetString(octets) => RandomValue::OctetString(resize(octets, size, 0)),
+            RandomValue::String(text) => {
+                let chars: Vec<_> = text.chars().collect();
+                let fill = match restricted_string(ty) {
+                    Some(RestrictedString::Numeric) => '0',
+                    _ => 'a',
+                };
+                RandomValue::String(resize(&chars, size, fill).into_iter().collect())
+            }
+            RandomValue::SequenceOf(elements) => {
+                let (Type::SequenceOf(of) | Type::SetOf(of)) = ty else {
+                    return None;
+                };
+                let fill = match elements.last() {
+                    Some(last) => last.clone(),
+                    None => self.generate(module, &of.ty, of.ty.id, self.options.max_depth)?,
+                };
+                RandomValue::SequenceOf(resize(elements, size, fill))
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// The name of an instance of a parameterized type within a module, if the
+/// name is one
+fn instance<'a>(module: &'a Environment, name: &str) -> Option<&'a str> {
+    module
+        .instances
+        .get_key_value(name)
+        .map(|(name, _)| name.as_str())
+}
+
+/// The smallest permitted size, which is zero if sizes are not constrained.
+/// There is none if no sizes are permitted.
+fn smallest(sizes: Option<&Bounds<BigInt>>) -> Option<usize> {
+    let Some(sizes) = sizes else {
+        return Some(0);
+    };
+    match &sizes.intervals.first()?.lower {
+        Some(lower) => lower.try_into().ok(),
+        None => Some(0),
+    }
+}
+
+/// Join the path of a component to the path within it
+fn join(component: &str, path: &str) -> String {
+    if path.is_empty() {
+        component.to_string()
+    } else if path.starts_with('[') {
+        format!("{component}{path}")
+    } else {
+        format!("{component}.{path}")
+    }
+}
+
+/// Values are written in ASN.1 value notation
+impl fmt::Display for RandomValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandomValue::Boolean(true) => f.write_str("TRUE"),
+            RandomValue::Boolean(false) => f.write_str("FALSE"),
+            RandomValue::Null => f.write_str("NULL"),
+            RandomValue::Integer(integer) => write!(f, "{integer}"),
+            RandomValue::Real(real) => write!(f, "{real}"),
+            RandomValue::Enumerated(name, _) => f.write_str(name),
+            RandomValue::BitString(bits) => {
+                f.write_char('\'')?;
+                for bit in bits {
+                    f.write_char(if *bit { '1' } else { '0' })?;
+                }
+                f.write_str("'B")
+            }
+            RandomValue::OctetString(octets) => {
+                f.write_char('\'')?;
+                for octet in octets {
+                    write!(f, "{octet:02X}")?;
+                }
+                f.write_str("'H")
+            }
+            RandomValue::ObjectIdentifier(arcs) => {
+                f.write_char('{')?;
+                for arc in arcs {
+                    write!(f, " {arc}")?;
+                }
+                f.write_str(" }")
+            }
+            RandomValue::String(string) => write_string(f, string),
+            RandomValue::Sequence(components) if components.is_empty() => f.write_str("{}"),
+            RandomValue::Sequence(components) => {
+                f.write_str("{ ")?;
+                for (i, (name, value)) in components.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{name} {value}")?;
+                }
+                f.write_str(" }")
+            }
+            RandomValue::SequenceOf(elements) if elements.is_empty() => f.write_str("{}"),
+            RandomValue::SequenceOf(elements) => {
+                f.write_str("{ ")?;
+                for (i, value) in elements.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str(" }")
+            }
+            RandomValue::Choice(name, value) => write!(f, "{name} : {value}"),
+        }
+    }
+}
+
+/// Write a string as a quoted string, or as a list of quoted strings and the
+/// positions of control characters, which cannot be written in quotes
+fn write_string(f: &mut fmt::Formatter<'_>, string: &str) -> fmt::Result {
+    let control = |c: char| unicode_data::CONTROL.contains_char(c);
+    if !string.contains(control) {
+        return write!(f, "\"{}\"", string.replace('"', "\"\""));
+    }
+
+    let mut items = vec![];
+    let mut quoted = String::new();
+    for c in string.chars() {
+        if !control(c) {
+            quoted.push(c);
+            continue;
+        }
+        if !quoted.is_empty() {
+            items.push(format!("\"{}\"", quoted.replace('"', "\"\"")));
+            quoted.clear();
+        }
+        // controls of ISO 646 are written by their column and row in it
+        let code = u32::from(c);
+        items.push(match code {
+            0..=0x7f => format!("{{{}, {}}}", code >> 4, code & 0xf),
+            _ => format!("{{0, 0, {}, {}}}", code >> 8, code & 0xff),
+        });
+    }
+    if !quoted.is_empty() {
+        items.push(format!("\"{}\"", quoted.replace('"', "\"\"")));
+    }
+    write!(f, "{{ {} }}", items.join(", "))
+}
+
+/// A SplitMix64 generator, which is small and good enough for test values
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number less than a bound, which must not be zero
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// An index into a list of a length, if it is not empty
+    fn index(&mut self, len: usize) -> Option<usize> {
+        (len > 0).then(|| self.below(len as u64) as usize)
+    }
+}
@@ -0,0 +1,358 @@
+//! The character sets of the restricted character string types, and the
+//! octets that their values are encoded as.
+//!
+//! `NumericString`, `PrintableString`, `VisibleString` and `IA5String` are
+//! subsets of ASCII, encoded one octet per character.  `BMPString` and
+//! `UniversalString` are the first plane and all of ISO 10646, encoded as
+//! big endian UCS-2 and UCS-4, and `UTF8String` is all of ISO 10646 encoded
+//! as UTF-8.
+//!
+//! The other types are defined by the character sets registered for use with
+//! ISO 2022, which is more than can be represented here.  `TeletexString`
+//! and `VideotexString` are the primary and supplementary sets of T.61, with
+//! accented letters written as a non-spacing accent followed by the letter.
+//! `GraphicString` and `GeneralString` are ASCII and the right hand part of
+//! ISO 8859-1, which is designated as G1 before the first character from it,
+//! and `GraphicString` has no control characters.
+
+use std::fmt;
+
+use unicode_normalization::UnicodeNormalization;
+
+/// A restricted character string type, which has a fixed set of characters
+/// and a fixed encoding of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RestrictedString {
+    Numeric,
+    Printable,
+    Visible,
+    IA5,
+    Teletex,
+    Videotex,
+    Graphic,
+    General,
+    Bmp,
+    Universal,
+    Utf8,
+}
+
+/// A character that is not in the character set of a type, or octets that
+/// are not the encoding of a value of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringError {
+    /// A character and its byte offset within the string
+    Character {
+        ty: RestrictedString,
+        character: char,
+        offset: usize,
+    },
+
+    /// The offset of the first octet that is not part of an encoded character
+    Octets { ty: RestrictedString, offset: usize },
+}
+
+impl fmt::Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringError::Character { ty, character, .. } => write!(
+                f,
+                "`{}` (U+{:04X}) is not in the character set of {ty}",
+                character.escape_debug(),
+                u32::from(*character)
+            ),
+            StringError::Octets { ty, offset } => {
+                write!(f, "Invalid {ty} encoding at octet {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StringError {}
+
+impl fmt::Display for RestrictedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RestrictedString::Numeric => "NumericString",
+            RestrictedString::Printable => "PrintableString",
+            RestrictedString::Visible => "VisibleString",
+            RestrictedString::IA5 => "IA5String",
+            RestrictedString::Teletex => "TeletexString",
+            RestrictedString::Videotex => "VideotexString",
+            RestrictedString::Graphic => "GraphicString",
+            RestrictedString::General => "GeneralString",
+            RestrictedString::Bmp => "BMPString",
+            RestrictedString::Universal => "UniversalString",
+            RestrictedString::Utf8 => "UTF8String",
+        })
+    }
+}
+
+/// Escape sequence designating the right hand part of ISO 8859-1 as G1
+const LATIN_1: [u8; 3] = [0x1b, 0x2d, 0x41];
+
+/// The characters of the supplementary set of T.61 that are not accents
+const T61_SUPPLEMENTARY: [(u8, char); 54] = [
+    (0xa0, '\u{a0}'),
+    (0xa1, '¡'),
+    (0xa2, '¢'),
+    (0xa3, '£'),
+    (0xa4, '$'),
+    (0xa5, '¥'),
+    (0xa6, '#'),
+    (0xa7, '§'),
+    (0xa8, '¤'),
+    (0xab, '«'),
+    (0xb0, '°'),
+    (0xb1, '±'),
+    (0xb2, '²'),
+    (0xb3, '³'),
+    (0xb4, '×'),
+    (0xb5, 'µ'),
+    (0xb6, '¶'),
+    (0xb7, '·'),
+    (0xb8, '÷'),
+    (0xbb, '»'),
+    (0xbc, '¼'),
+    (0xbd, '½'),
+    (0xbe, '¾'),
+    (0xbf, '¿'),
+    (0xe0, 'Ω'),
+    (0xe1, 'Æ'),
+    (0xe2, 'Đ'),
+    (0xe3, 'ª'),
+    (0xe4, 'Ħ'),
+    (0xe6, 'Ĳ'),
+    (0xe7, 'Ŀ'),
+    (0xe8, 'Ł'),
+    (0xe9, 'Ø'),
+    (0xea, 'Œ'),
+    (0xeb, 'º'),
+    (0xec, 'Þ'),
+    (0xed, 'Ŧ'),
+    (0xee, 'Ŋ'),
+    (0xef, 'ŉ'),
+    (0xf0, 'ĸ'),
+    (0xf1, 'æ'),
+    (0xf2, 'đ'),
+    (0xf3, 'ð'),
+    (0xf4, 'ħ'),
+    (0xf5, 'ı'),
+    (0xf6, 'ĳ'),
+    (0xf7, 'ŀ'),
+    (0xf8, 'ł'),
+    (0xf9, 'ø'),
+    (0xfa, 'œ'),
+    (0xfb, 'ß'),
+    (0xfc, 'þ'),
+    (0xfd, 'ŧ'),
+    (0xfe, 'ŋ'),
+];
+
+/// The non-spacing accents of T.61, which precede the letter they are on,
+/// and the Unicode combining characters for them
+const T61_ACCENTS: [(u8, char); 14] = [
+    (0xc1, '\u{300}'),
+    (0xc2, '\u{301}'),
+    (0xc3, '\u{302}'),
+    (0xc4, '\u{303}'),
+    (0xc5, '\u{304}'),
+    (0xc6, '\u{306}'),
+    (0xc7, '\u{307}'),
+    (0xc8, '\u{308}'),
+    (0xca, '\u{30a}'),
+    (0xcb, '\u{327}'),
+    (0xcc, '\u{332}'),
+    (0xcd, '\u{30b}'),
+    (0xce, '\u{328}'),
+    (0xcf, '\u{30c}'),
+];
+
+impl RestrictedString {
+    /// Is a character in the character set of the type
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            RestrictedString::Numeric => c.is_ascii_digit() || c == ' ',
+            RestrictedString::Printable => c.is_ascii_alphanumeric() || " '()+,-./:=?".contains(c),
+            RestrictedString::Visible => c.is_ascii_graphic() || c == ' ',
+            RestrictedString::IA5 => c.is_ascii(),
+            RestrictedString::Teletex | RestrictedString::Videotex => t61_char(c).is_some(),
+            RestrictedString::Graphic => {
+                u32::from(c) <= 0xff && !unicode_data::CONTROL.contains_char(c)
+            }
+            RestrictedString::General => c.is_ascii() || ('\u{a0}'..='\u{ff}').contains(&c),
+            RestrictedString::Bmp => u32::from(c) <= 0xffff,
+            RestrictedString::Universal | RestrictedString::Utf8 => true,
+        }
+    }
+
+    /// Check that every character of a string is in the character set of the
+    /// type
+    pub fn check(self, text: &str) -> Result<(), StringError> {
+        match text.char_indices().find(|(_, c)| !self.contains(*c)) {
+            Some((offset, character)) => Err(StringError::Character {
+                ty: self,
+                character,
+                offset,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Encode a string as the octets of a value of the type
+    pub fn encode(self, text: &str) -> Result<Vec<u8>, StringError> {
+        self.check(text)?;
+
+        let mut octets = Vec::with_capacity(text.len());
+        match self {
+            RestrictedString::Numeric
+            | RestrictedString::Printable
+            | RestrictedString::Visible
+            | RestrictedString::IA5
+            | RestrictedString::Utf8 => octets.extend_from_slice(text.as_bytes()),
+            RestrictedString::Teletex | RestrictedString::Videotex => {
+                for c in text.chars() {
+                    octets.extend(t61_char(c).into_iter().flatten().flatten());
+                }
+            }
+            RestrictedString::Graphic | RestrictedString::General => {
+                let mut designated = false;
+                for c in text.chars() {
+                    if !c.is_ascii() && !designated {
+                        octets.extend_from_slice(&LATIN_1);
+                        designated = true;
+                    }
+                    octets.push(u32::from(c) as u8);
+                }
+            }
+            RestrictedString::Bmp => {
+                for c in text.chars() {
+                    octets.extend_from_slice(&(u32::from(c) as u16).to_be_bytes());
+                }
+            }
+            RestrictedString::Universal => {
+                for c in text.chars() {
+                    octets.extend_from_slice(&u32::from(c).to_be_bytes());
+                }
+            }
+        }
+        Ok(octets)
+    }
+
+    /// Decode the octets of a value of the type
+    pub fn decode(self, octets: &[u8]) -> Result<String, StringError> {
+        let invalid = |offset| StringError::Octets { ty: self, offset };
+
+        let text = match self {
+            RestrictedString::Numeric
+            | RestrictedString::Printable
+            | RestrictedString::Visible
+            | RestrictedString::IA5 => match octets.iter().position(|octet| !octet.is_ascii()) {
+                Some(offset) => return Err(invalid(offset)),
+                None => octets.iter().map(|&octet| char::from(octet)).collect(),
+            },
+            RestrictedString::Utf8 => match std::str::from_utf8(octets) {
+                Ok(text) => text.to_string(),
+                Err(e) => return Err(invalid(e.valid_up_to())),
+            },
+            RestrictedString::Teletex | RestrictedString::Videotex => {
+                let mut text = String::new();
+                let mut offset = 0;
+                while offset < octets.len() {
+                    let (c, len) = t61_decode(&octets[offset..]).ok_or(invalid(offset))?;
+                    text.push(c);
+                    offset += len;
+                }
+                text
+            }
+            RestrictedString::Graphic | RestrictedString::General => {
+                let mut text = String::new();
+                let mut latin_1 = false;
+                let mut offset = 0;
+                while offset < octets.len() {
+                    if octets[offset..].starts_with(&LATIN_1) {
+                        latin_1 = true;
+                        offset += LATIN_1.len();
+                        continue;
+                    }
+                    let octet = octets[offset];
+                    if !(octet.is_ascii() || latin_1 && octet >= 0xa0) {
+                        return Err(invalid(offset));
+                    }
+                    text.push(char::from(octet));
+                    offset += 1;
+                }
+                text
+            }
+            RestrictedString::Bmp | RestrictedString::Universal => {
+                let width = if self == RestrictedString::Bmp { 2 } else { 4 };
+                let units = octets.chunks_exact(width);
+                if !units.remainder().is_empty() {
+                    return Err(invalid(octets.len() - units.remainder().len()));
+                }
+                let mut text = String::new();
+                for (i, unit) in units.enumerate() {
+                    let code = unit
+                        .iter()
+                        .fold(0, |code, &octet| code << 8 | u32::from(octet));
+                    text.push(char::from_u32(code).ok_or(invalid(i * width))?);
+                }
+                text
+            }
+        };
+
+        match self.check(&text) {
+            Ok(()) => Ok(text),
+            Err(StringError::Character { offset, .. }) => Err(invalid(offset)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The octets of a character in T.61, which is one octet, or an accent and a
+/// letter
+fn t61_char(c: char) -> Option<[Option<u8>; 2]> {
+    // the positions of ASCII that T.61 uses for other characters
+    if c.is_ascii() && !"#$\\^`{}~".contains(c) {
+        return Some([Some(c as u8), None]);
+    }
+    if let Some((octet, _)) = T61_SUPPLEMENTARY
+        .iter()
+        .find(|(_, supplementary)| *supplementary == c)
+    {
+        return Some([Some(*octet), None]);
+    }
+
+    let mut decomposed = std::iter::once(c).nfd();
+    let (Some(letter), Some(accent), None) =
+        (decomposed.next(), decomposed.next(), decomposed.next())
+    else {
+        return None;
+    };
+    let (octet, _) = T61_ACCENTS.iter().find(|(_, mark)| *mark == accent)?;
+    letter
+        .is_ascii_alphabetic()
+        .then_some([Some(*octet), Some(letter as u8)])
+}
+
+/// Decode the first character of T.61 octets, and the number of octets of it
+fn t61_decode(octets: &[u8]) -> Option<(char, usize)> {
+    let first = *octets.first()?;
+    if first.is_ascii() {
+        let c = char::from(first);
+        return t61_char(c).map(|_| (c, 1));
+    }
+    if let Some((_, c)) = T61_SUPPLEMENTARY.iter().find(|(octet, _)| *octet == first) {
+        return Some((*c, 1));
+    }
+
+    let (_, accent) = T61_ACCENTS.iter().find(|(octet, _)| *octet == first)?;
+    let letter = char::from(*octets.get(1)?);
+    if !letter.is_ascii_alphabetic() {
+        return None;
+    }
+    let mut composed = [letter, *accent].into_iter().nfc();
+    match (composed.next(), composed.next()) {
+        (Some(c), None) => Some((c, 2)),
+        _ => None,
+    }
+}
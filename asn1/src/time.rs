@@ -0,0 +1,1168 @@
+//! Values of the ASN.1 time types, parsed from and written as the strings of
+//! their value notation.
+//!
+//! `UTCTime` and `GeneralizedTime` values are times of day on a date, which
+//! have the canonical forms required by the distinguished encoding rules: in
+//! UTC, with seconds, and with any fraction of a second written without
+//! trailing zeros.  The two digit years of `UTCTime` values are 1950 to 2049.
+//! Leap seconds are not represented.
+//!
+//! `TIME` values are the subset of ISO 8601 of ITU-T X.680 clause 38, in the
+//! extended format: dates, times of day, dates with times of day, intervals
+//! between them or of durations, and recurring intervals.  Each value has the
+//! property settings of its form, which `TIME` types may be constrained by.
+//! `DATE`, `TIME-OF-DAY`, `DATE-TIME` and `DURATION` are `TIME` values with
+//! fixed property settings.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// An error parsing a time value, or finding the canonical form of one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeError(String);
+
+impl fmt::Display for TimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+fn error<T>(message: impl Into<String>) -> Result<T, TimeError> {
+    Err(TimeError(message.into()))
+}
+
+/// The time zone of a time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeZone {
+    /// Local time, with no relation to UTC given
+    Local,
+
+    /// UTC, written `Z`
+    Utc,
+
+    /// A difference from UTC in minutes, positive east of Greenwich, written
+    /// `+hhmm` or `-hhmm`
+    Offset(i16),
+}
+
+impl TimeZone {
+    /// The number of minutes to subtract from a time to get the time in UTC,
+    /// if the time zone is not local
+    fn minutes(self) -> Option<i64> {
+        match self {
+            TimeZone::Local => None,
+            TimeZone::Utc => Some(0),
+            TimeZone::Offset(minutes) => Some(minutes.into()),
+        }
+    }
+}
+
+impl fmt::Display for TimeZone {
+    /// Write the time zone as it is written by `UTCTime` and
+    /// `GeneralizedTime` values
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TimeZone::Local => Ok(()),
+            TimeZone::Utc => f.write_str("Z"),
+            TimeZone::Offset(minutes) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{sign}{:02}{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// A `UTCTime` value, `YYMMDDhhmm[ss]` followed by `Z` or a difference from
+/// UTC, `+hhmm` or `-hhmm`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UtcTime {
+    /// The year, from 1950 to 2049
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: Option<u8>,
+
+    /// The time zone, which is never local
+    pub zone: TimeZone,
+}
+
+impl UtcTime {
+    /// Get the canonical form of the value, in UTC with seconds
+    pub fn canonical(&self) -> Result<UtcTime, TimeError> {
+        let seconds = i64::from(self.second.unwrap_or(0));
+        let moment = Moment::new(
+            (self.year, self.month, self.day),
+            self.hour,
+            self.minute,
+            seconds,
+            self.zone,
+        )?;
+        let (year, month, day) = civil(moment.days);
+        let Some(year) = u16::try_from(year)
+            .ok()
+            .filter(|y| (1950..=2049).contains(y))
+        else {
+            return error(format!(
+                "UTCTime value `{self}` is not between 1950 and 2049 in UTC"
+            ));
+        };
+        Ok(UtcTime {
+            year,
+            month,
+            day,
+            hour: (moment.seconds / 3600) as u8,
+            minute: (moment.seconds / 60 % 60) as u8,
+            second: Some((moment.seconds % 60) as u8),
+            zone: TimeZone::Utc,
+        })
+    }
+
+    /// Get the distinguished encoding of the value, including its tag and
+    /// length
+    pub fn to_der(&self) -> Result<Vec<u8>, TimeError> {
+        Ok(tlv(23, &self.canonical()?.to_string()))
+    }
+}
+
+impl FromStr for UtcTime {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        let mut reader = Reader::new(text, "UTCTime");
+        let year = reader.number(2, "year")? as u16;
+        let year = if year < 50 { 2000 + year } else { 1900 + year };
+        let (month, day) = reader.month_day(year.into())?;
+        let hour = reader.hour()?;
+        let minute = reader.minute()?;
+        let second = match reader.peek_digit() {
+            true => Some(reader.second()?),
+            false => None,
+        };
+        let zone = reader.zone(false)?;
+        reader.end()?;
+
+        if zone == TimeZone::Local {
+            return reader.invalid("must end with `Z` or a difference from UTC");
+        }
+        Ok(UtcTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            zone,
+        })
+    }
+}
+
+impl fmt::Display for UtcTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}{:02}{:02}{:02}{:02}",
+            self.year % 100,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute
+        )?;
+        if let Some(second) = self.second {
+            write!(f, "{second:02}")?;
+        }
+        write!(f, "{}", self.zone)
+    }
+}
+
+/// A `GeneralizedTime` value, `YYYYMMDDhh[mm[ss]]` with an optional fraction
+/// of the last of the hour, minute and second, followed by `Z`, a difference
+/// from UTC, or nothing for local time
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GeneralizedTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: Option<u8>,
+
+    /// The second, only given with the minute
+    pub second: Option<u8>,
+
+    /// The digits of the fraction of the last of the hour, minute and second
+    /// given, empty if there is no fraction
+    pub fraction: String,
+    pub zone: TimeZone,
+}
+
+impl GeneralizedTime {
+    /// Get the canonical form of the value, in UTC with seconds and with any
+    /// fraction of the hour or minute converted to minutes and seconds.
+    /// Local times have no canonical form.
+    pub fn canonical(&self) -> Result<GeneralizedTime, TimeError> {
+        // the fraction is in units of the last component given
+        let unit: u128 = match (self.minute, self.second) {
+            (None, _) => 3600,
+            (Some(_), None) => 60,
+            (Some(_), Some(_)) => 1,
+        };
+        let digits = self.fraction.len();
+        let (whole, fraction) = match unit {
+            1 => (0, self.fraction.trim_end_matches('0').to_string()),
+            _ if digits > 30 => {
+                return error(format!(
+                    "GeneralizedTime value `{self}` has too many digits in its fraction"
+                ))
+            }
+            _ => {
+                let scale = 10u128.pow(digits as u32);
+                let value: u128 = self.fraction.parse().unwrap_or(0) * unit;
+                let fraction = format!("{:0digits$}", value % scale);
+                (
+                    (value / scale) as i64,
+                    fraction.trim_end_matches('0').to_string(),
+                )
+            }
+        };
+
+        let minute = self.minute.unwrap_or(0);
+        let seconds = i64::from(self.second.unwrap_or(0)) + whole;
+        let moment = Moment::new(
+            (self.year, self.month, self.day),
+            self.hour,
+            minute,
+            seconds,
+            self.zone,
+        )?;
+        let (year, month, day) = civil(moment.days);
+        let Some(year) = u16::try_from(year).ok().filter(|&y| y <= 9999) else {
+            return error(format!(
+                "GeneralizedTime value `{self}` is not between years 0 and 9999 in UTC"
+            ));
+        };
+        Ok(GeneralizedTime {
+            year,
+            month,
+            day,
+            hour: (moment.seconds / 3600) as u8,
+            minute: Some((moment.seconds / 60 % 60) as u8),
+            second: Some((moment.seconds % 60) as u8),
+            fraction,
+            zone: TimeZone::Utc,
+        })
+    }
+
+    /// Get the distinguished encoding of the value, including its tag and
+    /// length
+    pub fn to_der(&self) -> Result<Vec<u8>, TimeError> {
+        Ok(tlv(24, &self.canonical()?.to_string()))
+    }
+}
+
+impl FromStr for GeneralizedTime {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        let mut reader = Reader::new(text, "GeneralizedTime");
+        let year = reader.number(4, "year")? as u16;
+        let (month, day) = reader.month_day(year.into())?;
+        let hour = reader.hour()?;
+        let mut minute = None;
+        let mut second = None;
+        if reader.peek_digit() {
+            minute = Some(reader.minute()?);
+            if reader.peek_digit() {
+                second = Some(reader.second()?);
+            }
+        }
+        let fraction = reader.fraction()?;
+        let zone = reader.zone(false)?;
+        reader.end()?;
+
+        Ok(GeneralizedTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            fraction,
+            zone,
+        })
+    }
+}
+
+impl fmt::Display for GeneralizedTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}{:02}{:02}{:02}",
+            self.year, self.month, self.day, self.hour
+        )?;
+        if let Some(minute) = self.minute {
+            write!(f, "{minute:02}")?;
+        }
+        if let Some(second) = self.second {
+            write!(f, "{second:02}")?;
+        }
+        if !self.fraction.is_empty() {
+            write!(f, ".{}", self.fraction)?;
+        }
+        write!(f, "{}", self.zone)
+    }
+}
+
+/// A `DATE` value, `YYYY-MM-DD` with a year from 1582 to 9999
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl FromStr for Date {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        Time::with_settings(text, "DATE", PropertySettings::DATE)?;
+        Ok(Date {
+            year: text[0..4].parse().unwrap_or(0),
+            month: text[5..7].parse().unwrap_or(0),
+            day: text[8..10].parse().unwrap_or(0),
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A `TIME-OF-DAY` value, `hh:mm:ss` in local time.  Midnight may be either
+/// `00:00:00` or `24:00:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl FromStr for TimeOfDay {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        Time::with_settings(text, "TIME-OF-DAY", PropertySettings::TIME_OF_DAY)?;
+        Ok(TimeOfDay {
+            hour: text[0..2].parse().unwrap_or(0),
+            minute: text[3..5].parse().unwrap_or(0),
+            second: text[6..8].parse().unwrap_or(0),
+        })
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+/// A `DATE-TIME` value, `YYYY-MM-DDThh:mm:ss` in local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: TimeOfDay,
+}
+
+impl FromStr for DateTime {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        Time::with_settings(text, "DATE-TIME", PropertySettings::DATE_TIME)?;
+        Ok(DateTime {
+            date: text[..10].parse()?,
+            time: text[11..].parse()?,
+        })
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+/// A `DURATION` value, `PnYnMnDTnHnMnS` with at least one of the components,
+/// or `PnW`.  The last component given may have a fraction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Duration {
+    pub years: Option<u64>,
+    pub months: Option<u64>,
+    pub weeks: Option<u64>,
+    pub days: Option<u64>,
+    pub hours: Option<u64>,
+    pub minutes: Option<u64>,
+    pub seconds: Option<u64>,
+
+    /// The digits of the fraction of the last component, empty if there is
+    /// no fraction
+    pub fraction: String,
+}
+
+impl Duration {
+    /// The designator and value of each component, in the order they are
+    /// written
+    fn components(&self) -> [(char, Option<u64>); 7] {
+        [
+            ('Y', self.years),
+            ('M', self.months),
+            ('W', self.weeks),
+            ('D', self.days),
+            ('H', self.hours),
+            ('M', self.minutes),
+            ('S', self.seconds),
+        ]
+    }
+}
+
+impl FromStr for Duration {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        let mut reader = Reader::new(text, "DURATION");
+        if !reader.eat('P') {
+            return reader.invalid("must start with `P`");
+        }
+
+        let mut duration = Duration::default();
+        let mut time = false;
+        // the components that may still follow, by designator
+        let mut expected: &[char] = &['Y', 'M', 'W', 'D'];
+        loop {
+            if !time && reader.eat('T') {
+                time = true;
+                expected = &['H', 'M', 'S'];
+                if !reader.peek_digit() {
+                    return reader.invalid("must have a component after `T`");
+                }
+            }
+            if reader.at_end() {
+                break;
+            }
+            if !duration.fraction.is_empty() {
+                return reader.invalid("may only have a fraction in its last component");
+            }
+
+            let value = reader.digits("number")?;
+            duration.fraction = reader.fraction()?;
+            let Some(designator) = reader.next() else {
+                return reader.invalid("must end with a designator");
+            };
+            let Some(position) = expected.iter().position(|&c| c == designator) else {
+                return reader.invalid(format!("has an unexpected designator `{designator}`"));
+            };
+            let weeks = designator == 'W';
+            let component = match (designator, time) {
+                ('Y', _) => &mut duration.years,
+                ('M', false) => &mut duration.months,
+                ('W', _) => &mut duration.weeks,
+                ('D', _) => &mut duration.days,
+                ('H', _) => &mut duration.hours,
+                ('M', true) => &mut duration.minutes,
+                _ => &mut duration.seconds,
+            };
+            let Ok(value) = value.parse() else {
+                return reader.invalid("has a component that is too large");
+            };
+            *component = Some(value);
+
+            // weeks cannot be given with any other component
+            expected = if weeks {
+                &[]
+            } else {
+                &expected[position + 1..]
+            };
+            if weeks && !(reader.at_end() && duration.years.is_none() && duration.months.is_none())
+            {
+                return reader.invalid("cannot have weeks with other components");
+            }
+        }
+
+        if duration
+            .components()
+            .iter()
+            .all(|(_, value)| value.is_none())
+        {
+            return reader.invalid("must have at least one component");
+        }
+        Ok(duration)
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("P")?;
+        let components = self.components();
+        let last = components.iter().rposition(|(_, value)| value.is_some());
+        for (i, (designator, value)) in components.into_iter().enumerate() {
+            if i == 4 && components[4..].iter().any(|(_, value)| value.is_some()) {
+                f.write_str("T")?;
+            }
+            let Some(value) = value else {
+                continue;
+            };
+            write!(f, "{value}")?;
+            if Some(i) == last && !self.fraction.is_empty() {
+                write!(f, ".{}", self.fraction)?;
+            }
+            write!(f, "{designator}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A `TIME` value, kept as it is written, with the property settings of its
+/// form
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Time {
+    text: String,
+    settings: PropertySettings,
+}
+
+impl Time {
+    /// Get the property settings of the value, such as `Basic=Date Date=YMD
+    /// Year=Basic`
+    pub fn settings(&self) -> &PropertySettings {
+        &self.settings
+    }
+
+    /// Get the value as it is written
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Parse a value that must satisfy property settings, naming the type the
+    /// settings define in errors
+    fn with_settings(text: &str, ty: &str, settings: &str) -> Result<Time, TimeError> {
+        let time: Time = text
+            .parse()
+            .map_err(|e: TimeError| TimeError(e.0.replace("TIME", ty)))?;
+        let settings: PropertySettings = settings.parse()?;
+        if !settings.permits(&time.settings) {
+            return error(format!("{ty} value `{text}` is not of the form {settings}"));
+        }
+        Ok(time)
+    }
+}
+
+impl FromStr for Time {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        let mut settings = vec![];
+        if let Some(rest) = text.strip_prefix('R') {
+            // recurring intervals have a number of recurrences, or none if
+            // they are unlimited
+            let Some((count, interval)) = rest.split_once('/') else {
+                return error(format!(
+                    "TIME value `{text}` must have an interval after `R`"
+                ));
+            };
+            if !count.chars().all(|c| c.is_ascii_digit()) {
+                return error(format!(
+                    "TIME value `{text}` has an invalid number of recurrences"
+                ));
+            }
+            let recurrence = match count.len() {
+                0 => "Unlimited".to_string(),
+                n => format!("R{n}"),
+            };
+            settings.push(("Basic", "Rec-Interval".to_string()));
+            settings.push(("Recurrence", recurrence));
+            interval_settings(interval, text, &mut settings)?;
+        } else if text.contains('/') || text.starts_with('P') {
+            settings.push(("Basic", "Interval".to_string()));
+            interval_settings(text, text, &mut settings)?;
+        } else {
+            let basic = point_settings(text, text, &mut settings)?;
+            settings.insert(0, ("Basic", basic.to_string()));
+        }
+
+        let settings = settings
+            .into_iter()
+            .map(|(property, value)| (property.to_string(), value))
+            .collect();
+        Ok(Time {
+            text: text.to_string(),
+            settings: PropertySettings(settings),
+        })
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Add the settings of an interval, which is a start and end point, a point
+/// and a duration, or only a duration
+fn interval_settings(
+    interval: &str,
+    text: &str,
+    settings: &mut Vec<(&'static str, String)>,
+) -> Result<(), TimeError> {
+    let (kind, points, duration) = match interval.split_once('/') {
+        None => ("D", vec![], Some(interval)),
+        Some((start, end)) => match (start.starts_with('P'), end.starts_with('P')) {
+            (false, false) => ("SE", vec![start, end], None),
+            (false, true) => ("SD", vec![start], Some(end)),
+            (true, false) => ("DE", vec![end], Some(start)),
+            (true, true) => {
+                return error(format!(
+                    "TIME value `{text}` cannot be an interval of two durations"
+                ))
+            }
+        },
+    };
+    if let Some(duration) = duration {
+        duration
+            .parse::<Duration>()
+            .map_err(|e| TimeError(e.0.replace("DURATION", "TIME")))?;
+    }
+    settings.push(("Interval-type", kind.to_string()));
+
+    // the points of an interval have the same settings
+    let mut point = vec![];
+    let mut kinds = vec![];
+    for text_point in &points {
+        let mut settings = vec![];
+        kinds.push(point_settings(text_point, text, &mut settings)?);
+        if point.is_empty() {
+            point = settings;
+        } else if point != settings {
+            return error(format!(
+                "TIME value `{text}` must have start and end points of the same form"
+            ));
+        }
+    }
+    if let Some(kind) = kinds.first() {
+        settings.push(("SE-point", kind.to_string()));
+    }
+    settings.extend(point);
+    Ok(())
+}
+
+/// Add the settings of a date, a time of day, or both, returning which of
+/// these the point is
+fn point_settings(
+    point: &str,
+    text: &str,
+    settings: &mut Vec<(&'static str, String)>,
+) -> Result<&'static str, TimeError> {
+    if let Some(time) = point.strip_prefix('T') {
+        time_settings(time, text, settings)?;
+        return Ok("Time");
+    }
+    match point.split_once('T') {
+        Some((date, time)) => {
+            if !["YMD", "YD", "YWD"].contains(&date_settings(date, text, settings)?) {
+                return error(format!(
+                    "TIME value `{text}` must have a complete date with a time of day"
+                ));
+            }
+            time_settings(time, text, settings)?;
+            Ok("Date-Time")
+        }
+        None if point.contains(':') => {
+            time_settings(point, text, settings)?;
+            Ok("Time")
+        }
+        None => {
+            date_settings(point, text, settings)?;
+            Ok("Date")
+        }
+    }
+}
+
+/// Add the settings of a date, `YYYY-MM-DD`, `YYYY-MM`, `YYYY`, `YY` (a
+/// century), `YYYY-DDD` or `YYYY-Www[-D]`.  Years before 0 and after 9999
+/// are written with a sign and at least five digits, negative years after
+/// -10000 have four digits.  Returns the form of the date.
+fn date_settings(
+    date: &str,
+    text: &str,
+    settings: &mut Vec<(&'static str, String)>,
+) -> Result<&'static str, TimeError> {
+    let mut reader = Reader::new(text, "TIME");
+    reader.text = date;
+
+    let sign = match reader.peek() {
+        Some(sign @ ('+' | '-')) => {
+            reader.next();
+            Some(sign)
+        }
+        _ => None,
+    };
+    let digits = reader.digits("year")?;
+    let mut year: i64 = match digits.parse() {
+        Ok(year) => year,
+        Err(_) => return reader.invalid("has a year that is too large"),
+    };
+    if sign == Some('-') {
+        year = -year;
+    }
+    let year_setting = match (sign, digits.len()) {
+        (None, 2) if reader.at_end() => {
+            settings.push(("Date", "C".to_string()));
+            let basic = if year >= 16 { "Basic" } else { "Proleptic" };
+            settings.push(("Year", basic.to_string()));
+            return Ok("C");
+        }
+        (None, 4) if year >= 1582 => "Basic".to_string(),
+        (None, 4) => "Proleptic".to_string(),
+        (Some('-'), 4) => "Negative".to_string(),
+        (Some(_), n) if n >= 5 => format!("L{n}"),
+        _ => return reader.invalid("has an invalid year"),
+    };
+
+    let form = if reader.at_end() {
+        "Y"
+    } else if !reader.eat('-') {
+        return reader.invalid("must have `-` after the year");
+    } else if reader.eat('W') {
+        let week = reader.number(2, "week")?;
+        if !(1..=weeks_in_year(year)).contains(&week) {
+            return reader.invalid(format!("has an invalid week {week}"));
+        }
+        if reader.eat('-') {
+            let day = reader.number(1, "day")?;
+            if !(1..=7).contains(&day) {
+                return reader.invalid(format!("has an invalid day of the week {day}"));
+            }
+            "YWD"
+        } else {
+            "YW"
+        }
+    } else {
+        let digits = reader.digits("month")?;
+        match digits.len() {
+            3 => {
+                let day: u32 = digits.parse().unwrap_or(0);
+                let days = if is_leap_year(year) { 366 } else { 365 };
+                if !(1..=days).contains(&day) {
+                    return reader.invalid(format!("has an invalid day of the year {day}"));
+                }
+                "YD"
+            }
+            2 => {
+                let month: u32 = digits.parse().unwrap_or(0);
+                if !(1..=12).contains(&month) {
+                    return reader.invalid(format!("has an invalid month {month}"));
+                }
+                if reader.eat('-') {
+                    let day = reader.number(2, "day")?;
+                    if !(1..=days_in_month(year, month)).contains(&day) {
+                        return reader.invalid(format!("has an invalid day {day}"));
+                    }
+                    "YMD"
+                } else {
+                    "YM"
+                }
+            }
+            _ => return reader.invalid("must have a month, day of the year or week"),
+        }
+    };
+    reader.end()?;
+
+    settings.push(("Date", form.to_string()));
+    settings.push(("Year", year_setting));
+    Ok(form)
+}
+
+/// Add the settings of a time of day, `hh[:mm[:ss]]` with an optional
+/// fraction of the last component, followed by `Z`, a difference from UTC,
+/// `+hh[:mm]` or `-hh[:mm]`, or nothing for local time
+fn time_settings(
+    time: &str,
+    text: &str,
+    settings: &mut Vec<(&'static str, String)>,
+) -> Result<(), TimeError> {
+    let mut reader = Reader::new(text, "TIME");
+    reader.text = time;
+
+    let hour = reader.number(2, "hour")?;
+    let mut form = "H".to_string();
+    let mut rest = 0;
+    if reader.eat(':') {
+        rest += reader.minute()? as u32;
+        form.push('M');
+        if reader.eat(':') {
+            rest += reader.second()? as u32;
+            form.push('S');
+        }
+    }
+    let fraction = reader.fraction()?;
+    if !fraction.is_empty() {
+        form = format!("{form}F{}", fraction.len());
+    }
+    let zone = reader.zone(true)?;
+    reader.end()?;
+
+    let midnight = rest == 0 && fraction.trim_end_matches('0').is_empty();
+    if hour > 24 || (hour == 24 && !midnight) {
+        return reader.invalid(format!("has an invalid hour {hour}"));
+    }
+
+    settings.push(("Time", form));
+    let zone = match zone {
+        TimeZone::Local => "L",
+        TimeZone::Utc => "Z",
+        TimeZone::Offset(_) => "LD",
+    };
+    settings.push(("Local-or-UTC", zone.to_string()));
+    match hour {
+        0 if midnight => settings.push(("Midnight", "Start".to_string())),
+        24 => settings.push(("Midnight", "End".to_string())),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// The property settings of a `TIME` value or type, such as `Basic=Date
+/// Date=YMD Year=Basic`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PropertySettings(Vec<(String, String)>);
+
+impl PropertySettings {
+    /// The settings of `DATE` values
+    pub const DATE: &'static str = "Basic=Date Date=YMD Year=Basic";
+
+    /// The settings of `TIME-OF-DAY` values
+    pub const TIME_OF_DAY: &'static str = "Basic=Time Time=HMS Local-or-UTC=L";
+
+    /// The settings of `DATE-TIME` values
+    pub const DATE_TIME: &'static str =
+        "Basic=Date-Time Date=YMD Year=Basic Time=HMS Local-or-UTC=L";
+
+    /// The settings of `DURATION` values
+    pub const DURATION: &'static str = "Basic=Interval Interval-type=D";
+
+    /// Get the setting of a property
+    pub fn get(&self, property: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == property)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Do the settings of a value satisfy these settings.  Properties that
+    /// the value does not have, such as the form of the date of a time of
+    /// day, do not constrain it.
+    pub fn permits(&self, value: &PropertySettings) -> bool {
+        self.0
+            .iter()
+            .all(|(property, setting)| value.get(property).is_none_or(|value| value == setting))
+    }
+}
+
+impl FromStr for PropertySettings {
+    type Err = TimeError;
+
+    fn from_str(text: &str) -> Result<Self, TimeError> {
+        let mut settings = vec![];
+        for setting in text.split_whitespace() {
+            let Some((property, value)) = setting.split_once('=') else {
+                return error(format!(
+                    "Property setting `{setting}` must be `Property=Value`"
+                ));
+            };
+            let numbered = |prefix: &str| {
+                value
+                    .strip_prefix(prefix)
+                    .is_some_and(|n| n.parse::<u32>().is_ok_and(|n| n > 0))
+            };
+            let valid = match property {
+                "Basic" => {
+                    ["Date", "Time", "Date-Time", "Interval", "Rec-Interval"].contains(&value)
+                }
+                "Date" => ["C", "Y", "YM", "YMD", "YD", "YW", "YWD"].contains(&value),
+                "Year" => ["Basic", "Proleptic", "Negative"].contains(&value) || numbered("L"),
+                "Time" => {
+                    let (form, fraction) = value.split_once('F').unwrap_or((value, "1"));
+                    ["H", "HM", "HMS"].contains(&form)
+                        && fraction.parse::<u32>().is_ok_and(|n| n > 0)
+                }
+                "Local-or-UTC" => ["L", "Z", "LD"].contains(&value),
+                "Interval-type" => ["SE", "D", "SD", "DE"].contains(&value),
+                "SE-point" => ["Date", "Time", "Date-Time"].contains(&value),
+                "Recurrence" => value == "Unlimited" || numbered("R"),
+                "Midnight" => ["Start", "End"].contains(&value),
+                _ => return error(format!("Unknown property `{property}`")),
+            };
+            if !valid {
+                return error(format!(
+                    "Invalid setting `{value}` of property `{property}`"
+                ));
+            }
+            settings.push((property.to_string(), value.to_string()));
+        }
+        Ok(PropertySettings(settings))
+    }
+}
+
+impl fmt::Display for PropertySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (property, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{property}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the fields of a time value from left to right
+struct Reader<'t> {
+    text: &'t str,
+
+    /// The whole value and the name of its type, for errors
+    value: &'t str,
+    ty: &'static str,
+}
+
+impl<'t> Reader<'t> {
+    fn new(text: &'t str, ty: &'static str) -> Self {
+        Reader {
+            text,
+            value: text,
+            ty,
+        }
+    }
+
+    fn invalid<T>(&self, message: impl fmt::Display) -> Result<T, TimeError> {
+        error(format!("{} value `{}` {message}", self.ty, self.value))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text.chars().next()
+    }
+
+    fn peek_digit(&self) -> bool {
+        self.peek().is_some_and(|c| c.is_ascii_digit())
+    }
+
+    fn at_end(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.text = &self.text[c.len_utf8()..];
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        match self.text.strip_prefix(c) {
+            Some(rest) => {
+                self.text = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn end(&self) -> Result<(), TimeError> {
+        match self.at_end() {
+            true => Ok(()),
+            false => self.invalid(format!("has unexpected `{}`", self.text)),
+        }
+    }
+
+    /// Read one or more digits
+    fn digits(&mut self, what: &str) -> Result<&'t str, TimeError> {
+        let len = self.text.len()
+            - self
+                .text
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if len == 0 {
+            return self.invalid(format!("is missing its {what}"));
+        }
+        let (digits, rest) = self.text.split_at(len);
+        self.text = rest;
+        Ok(digits)
+    }
+
+    /// Read a number of exactly the given number of digits
+    fn number(&mut self, digits: usize, what: &str) -> Result<u32, TimeError> {
+        let number = self
+            .text
+            .get(..digits)
+            .filter(|number| number.chars().all(|c| c.is_ascii_digit()));
+        let Some(number) = number else {
+            return self.invalid(format!("must have {digits} digits for its {what}"));
+        };
+        self.text = &self.text[digits..];
+        Ok(number.parse().unwrap_or(0))
+    }
+
+    /// Read the month and day of a date, `MMDD`
+    fn month_day(&mut self, year: i64) -> Result<(u8, u8), TimeError> {
+        let month = self.number(2, "month")?;
+        if !(1..=12).contains(&month) {
+            return self.invalid(format!("has an invalid month {month}"));
+        }
+        let day = self.number(2, "day")?;
+        if !(1..=days_in_month(year, month)).contains(&day) {
+            return self.invalid(format!("has an invalid day {day}"));
+        }
+        Ok((month as u8, day as u8))
+    }
+
+    fn hour(&mut self) -> Result<u8, TimeError> {
+        self.component(23, "hour")
+    }
+
+    fn minute(&mut self) -> Result<u8, TimeError> {
+        self.component(59, "minute")
+    }
+
+    fn second(&mut self) -> Result<u8, TimeError> {
+        self.component(59, "second")
+    }
+
+    fn component(&mut self, max: u32, what: &str) -> Result<u8, TimeError> {
+        let value = self.number(2, what)?;
+        if value > max {
+            return self.invalid(format!("has an invalid {what} {value}"));
+        }
+        Ok(value as u8)
+    }
+
+    /// Read the digits of a fraction after a `.` or `,`, if there is one
+    fn fraction(&mut self) -> Result<String, TimeError> {
+        if !self.eat('.') && !self.eat(',') {
+            return Ok(String::new());
+        }
+        Ok(self.digits("fraction")?.to_string())
+    }
+
+    /// Read a time zone, `Z`, `+hh[mm]` or `-hh[mm]`, or nothing for local
+    /// time.  The minutes of an extended difference are after a colon.
+    fn zone(&mut self, extended: bool) -> Result<TimeZone, TimeError> {
+        if self.eat('Z') {
+            return Ok(TimeZone::Utc);
+        }
+        let sign = match self.peek() {
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => return Ok(TimeZone::Local),
+        };
+        self.next();
+        let hours = self.component(23, "hour of the difference from UTC")?;
+        let minutes = match (extended, self.peek()) {
+            (true, Some(':')) => {
+                self.next();
+                self.minute()?
+            }
+            (false, Some(c)) if c.is_ascii_digit() => self.minute()?,
+            _ => 0,
+        };
+        Ok(TimeZone::Offset(
+            sign * (i16::from(hours) * 60 + i16::from(minutes)),
+        ))
+    }
+}
+
+/// A time in UTC, as days since 1970-01-01 and seconds since midnight
+struct Moment {
+    days: i64,
+    seconds: i64,
+}
+
+impl Moment {
+    /// Convert a time in a time zone to UTC.  The seconds may be beyond a
+    /// minute, such as when a fraction of an hour has been converted.
+    fn new(
+        (year, month, day): (u16, u8, u8),
+        hour: u8,
+        minute: u8,
+        seconds: i64,
+        zone: TimeZone,
+    ) -> Result<Moment, TimeError> {
+        let Some(offset) = zone.minutes() else {
+            return error("Local times cannot be converted to UTC");
+        };
+        let seconds = i64::from(hour) * 3600 + (i64::from(minute) - offset) * 60 + seconds;
+        Ok(Moment {
+            days: days_from_civil(year.into(), month.into(), day.into())
+                + seconds.div_euclid(86400),
+            seconds: seconds.rem_euclid(86400),
+        })
+    }
+}
+
+/// Write an encoding of a string of one of the time types, with its
+/// universal tag and its length, which is always less than 128 octets
+fn tlv(tag: u8, text: &str) -> Vec<u8> {
+    let mut octets = vec![tag, text.len() as u8];
+    octets.extend_from_slice(text.as_bytes());
+    octets
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Get the number of ISO 8601 weeks in a year, which has 53 weeks if it
+/// starts on a Thursday, or is a leap year starting on a Wednesday
+fn weeks_in_year(year: i64) -> u32 {
+    // 1970-01-01 was a Thursday
+    let weekday = (days_from_civil(year, 1, 1) + 3).rem_euclid(7);
+    match weekday {
+        3 => 53,
+        2 if is_leap_year(year) => 53,
+        _ => 52,
+    }
+}
+
+/// Get the number of days since 1970-01-01 of a date in the proleptic
+/// Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Get the date of a number of days since 1970-01-01
+fn civil(days: i64) -> (i64, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u8, day as u8)
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use crate::compiler::SourceId;
 
 /// The kind of a lexed token
@@ -34,6 +36,7 @@ pub enum TokenKind {
     XMLEndTag,
     XMLSingleTagEnd,
     Ellipsis,
+    RangeSeparator,
     VersionOpen,
     VersionClose,
 
@@ -48,7 +51,7 @@ pub enum TokenKind {
     TypeField,
 
     // Keywords
-    // "ABSENT",
+    KwAbsent,
     KwAbstractSyntax,
     KwAll,
     KwApplication,
@@ -57,13 +60,13 @@ pub enum TokenKind {
     KwBit,
     KwBmpString,
     KwBoolean,
-    // "BY",
+    KwBy,
     KwCharacter,
     KwChoice,
     KwClass,
-    // "COMPONENT",
+    KwComponent,
     KwComponents,
-    // "CONSTRAINED",
+    KwConstrained,
     KwContaining,
     KwDate,
     KwDateTime,
@@ -71,11 +74,11 @@ pub enum TokenKind {
     KwDefinitions,
     KwDuration,
     KwEmbedded,
-    // "ENCODED",
+    KwEncoded,
     KwEncodingControl,
     KwEnd,
     KwEnumerated,
-    // "EXCEPT",
+    KwExcept,
     KwExplicit,
     KwExports,
     KwExtensibility,
@@ -90,14 +93,14 @@ pub enum TokenKind {
     KwImplicit,
     KwImplied,
     KwImports,
-    // "INCLUDES",
+    KwIncludes,
     KwInstance,
     KwInstructions,
     KwInteger,
-    // "INTERSECTION",
+    KwIntersection,
     KwISO64String,
-    // "MAX",
-    // "MIN",
+    KwMax,
+    KwMin,
     KwMinusInfinity,
     KwNotANumber,
     KwNull,
@@ -108,10 +111,10 @@ pub enum TokenKind {
     KwOf,
     KwOidIri,
     KwOptional,
-    // "PATTERN",
+    KwPattern,
     KwPDV,
     KwPlusInfinity,
-    // "PRESENT",
+    KwPresent,
     KwPrintableString,
     KwPrivate,
     KwReal,
@@ -119,8 +122,8 @@ pub enum TokenKind {
     KwRelativeOidIri,
     KwSequence,
     KwSet,
-    // "SETTINGS",
-    // "SIZE",
+    KwSettings,
+    KwSize,
     KwString,
     KwSyntax,
     KwT61String,
@@ -130,7 +133,7 @@ pub enum TokenKind {
     KwTimeOfDay,
     KwTrue,
     KwTypeIdentifier,
-    // "UNION",
+    KwUnion,
     KwUnique,
     KwUniversal,
     KwUniversalString,
@@ -158,9 +161,28 @@ pub struct Token {
     pub(crate) id: SourceId,
 }
 
+impl Token {
+    /// The type of this token
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    /// The byte range of the token in its source file
+    pub fn span(&self) -> Range<usize> {
+        self.offset..self.offset + self.length as usize
+    }
+
+    /// The file ID of the file the token was lexed from
+    pub fn source(&self) -> SourceId {
+        self.id
+    }
+}
+
 /// String/Enum mapping for keywords.  Contains both the normal and lowercase versions of the data.
-pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
-    // "ABSENT",
+/// Keywords only used within constraints have no lowercase version, so that they
+/// remain usable as component and value names.
+pub const KEYWORD_DATA: [(&str, TokenKind, &str); 91] = [
+    ("ABSENT", TokenKind::KwAbsent, "ABSENT"),
     (
         "ABSTRACT-SYNTAX",
         TokenKind::KwAbstractSyntax,
@@ -173,13 +195,13 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
     ("BIT", TokenKind::KwBit, "bit"),
     ("BMPString", TokenKind::KwBmpString, "BMPString"),
     ("BOOLEAN", TokenKind::KwBoolean, "boolean"),
-    // "BY",
+    ("BY", TokenKind::KwBy, "BY"),
     ("CHARACTER", TokenKind::KwCharacter, "character"),
     ("CHOICE", TokenKind::KwChoice, "choice"),
     ("CLASS", TokenKind::KwClass, "class"),
-    // "COMPONENT",
+    ("COMPONENT", TokenKind::KwComponent, "COMPONENT"),
     ("COMPONENTS", TokenKind::KwComponents, "components"),
-    // "CONSTRAINED",
+    ("CONSTRAINED", TokenKind::KwConstrained, "CONSTRAINED"),
     ("CONTAINING", TokenKind::KwContaining, "containing"),
     ("DATE", TokenKind::KwDate, "Date"),
     ("DATE-TIME", TokenKind::KwDateTime, "DateTime"),
@@ -187,7 +209,7 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
     ("DEFINITIONS", TokenKind::KwDefinitions, "definitions"),
     ("DURATION", TokenKind::KwDuration, "duration"),
     ("EMBEDDED", TokenKind::KwEmbedded, "embedded"),
-    // "ENCODED",
+    ("ENCODED", TokenKind::KwEncoded, "ENCODED"),
     (
         "ENCODING-CONTROL",
         TokenKind::KwEncodingControl,
@@ -195,7 +217,7 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
     ),
     ("END", TokenKind::KwEnd, "end"),
     ("ENUMERATED", TokenKind::KwEnumerated, "enumerated"),
-    // "EXCEPT",
+    ("EXCEPT", TokenKind::KwExcept, "EXCEPT"),
     ("EXPLICIT", TokenKind::KwExplicit, "explicit"),
     ("EXPORTS", TokenKind::KwExports, "exports"),
     ("EXTENSIBILITY", TokenKind::KwExtensibility, "extensibility"),
@@ -214,14 +236,14 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
     ("IMPLICIT", TokenKind::KwImplicit, "implicit"),
     ("IMPLIED", TokenKind::KwImplied, "implied"),
     ("IMPORTS", TokenKind::KwImports, "imports"),
-    // "INCLUDES",
+    ("INCLUDES", TokenKind::KwIncludes, "INCLUDES"),
     ("INSTANCE", TokenKind::KwInstance, "instance"),
     ("INSTRUCTIONS", TokenKind::KwInstructions, "instructions"),
     ("INTEGER", TokenKind::KwInteger, "integer"),
-    // "INTERSECTION",
+    ("INTERSECTION", TokenKind::KwIntersection, "INTERSECTION"),
     ("ISO646String", TokenKind::KwISO64String, "ISO646String"),
-    // "MAX",
-    // "MIN",
+    ("MAX", TokenKind::KwMax, "MAX"),
+    ("MIN", TokenKind::KwMin, "MIN"),
     (
         "MINUS-INFINITY",
         TokenKind::KwMinusInfinity,
@@ -240,10 +262,10 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
     ("OF", TokenKind::KwOf, "of"),
     ("OID-IRI", TokenKind::KwOidIri, "oid-iri"),
     ("OPTIONAL", TokenKind::KwOptional, "optional"),
-    // "PATTERN",
+    ("PATTERN", TokenKind::KwPattern, "PATTERN"),
     ("PDV", TokenKind::KwPDV, "pdv"),
     ("PLUS-INFINITY", TokenKind::KwPlusInfinity, "plus-infinity"),
-    // "PRESENT",
+    ("PRESENT", TokenKind::KwPresent, "PRESENT"),
     (
         "PrintableString",
         TokenKind::KwPrintableString,
@@ -259,8 +281,8 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
     ),
     ("SEQUENCE", TokenKind::KwSequence, "sequence"),
     ("SET", TokenKind::KwSet, "set"),
-    // "SETTINGS",
-    // "SIZE",
+    ("SETTINGS", TokenKind::KwSettings, "SETTINGS"),
+    ("SIZE", TokenKind::KwSize, "SIZE"),
     ("STRING", TokenKind::KwString, "string"),
     ("SYNTAX", TokenKind::KwSyntax, "syntax"),
     ("T61String", TokenKind::KwT61String, "T61String"),
@@ -274,7 +296,7 @@ pub const KEYWORD_DATA: [(&str, TokenKind, &str); 76] = [
         TokenKind::KwTypeIdentifier,
         "TypeIdentifier",
     ),
-    // "UNION",
+    ("UNION", TokenKind::KwUnion, "UNION"),
     ("UNIQUE", TokenKind::KwUnique, "unique"),
     ("UNIVERSAL", TokenKind::KwUniversal, "universal"),
     (
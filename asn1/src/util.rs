@@ -126,3 +126,43 @@ impl<T: Copy> CowVec<T> {
         }
     }
 }
+
+/// Find the candidate that is most similar to a name, if any is similar enough
+/// to likely be what was meant.  Used to suggest corrections for misspelt
+/// names.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max = name.chars().count() / 3;
+
+    candidates
+        .into_iter()
+        .filter(|&candidate| candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= max)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Number of single character insertions, deletions or substitutions needed
+/// to change one string into another.  A change of case only is counted as
+/// half a substitution, so is rounded down.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut prev: Vec<_> = (0..=b.len() * 2).step_by(2).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![(i + 1) * 2];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = if ca == cb {
+                0
+            } else if ca.to_lowercase().eq(cb.to_lowercase()) {
+                1
+            } else {
+                2
+            };
+            row.push((prev[j] + substitute).min(prev[j + 1] + 2).min(row[j] + 2));
+        }
+        prev = row;
+    }
+
+    prev[b.len()] / 2
+}
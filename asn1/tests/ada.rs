@@ -0,0 +1,123 @@
+use asn1::{AsnCompiler, CodegenOptions, CodegenTarget, GeneratedFile};
+
+const SOURCE: &str = "Specs DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Byte ::= INTEGER (0..255)
+    Small ::= INTEGER (0..7, ...)
+    Natural ::= INTEGER (-1..MAX)
+    Negative ::= INTEGER (MIN..-1)
+    Bytes ::= SEQUENCE SIZE (2..3) OF Byte
+    Tree ::= SEQUENCE {
+        value INTEGER (-5..5),
+        children SEQUENCE OF Tree,
+        label UTF8String OPTIONAL,
+        ...,
+        extra BOOLEAN
+    }
+    Shape ::= CHOICE { circle INTEGER (0..MAX), square NULL, tree Tree }
+    Colour ::= ENUMERATED { red, green(5), blue }
+    Empty ::= SEQUENCE {}
+END";
+
+/// Generate the Ada packages of the test module and the runtime
+fn generate() -> Vec<GeneratedFile> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("specs.asn1".into(), SOURCE.into())
+        .unwrap();
+    compiler
+        .analysis()
+        .codegen(CodegenTarget::Ada, &CodegenOptions::default())
+        .unwrap()
+}
+
+/// Get the content of a generated file
+fn content<'a>(files: &'a [GeneratedFile], name: &str) -> &'a str {
+    &files.iter().find(|file| file.file == name).unwrap().content
+}
+
+#[test]
+fn constrained_integers_are_range_subtypes() {
+    let files = generate();
+    let spec = content(&files, "specs.ads");
+
+    for declaration in [
+        "subtype Byte is Asn1_Runtime.Integer_Value range 0 .. 255;",
+        "subtype Small is Asn1_Runtime.Integer_Value;",
+        "subtype Natural_Id is Asn1_Runtime.Integer_Value range -1 .. Asn1_Runtime.Integer_Value'Last;",
+        "subtype Negative is Asn1_Runtime.Integer_Value range Asn1_Runtime.Integer_Value'First .. -1;",
+        "of Asn1_Runtime.Integer_Value range 0 .. 255;",
+        "value : Asn1_Runtime.Integer_Value range -5 .. 5;",
+    ] {
+        assert!(spec.contains(declaration), "{declaration}\n{spec}");
+    }
+}
+
+#[test]
+fn decoding_checks_ranges() {
+    let files = generate();
+    let body = content(&files, "specs.adb");
+
+    assert!(body.contains("if Number not in 0 .. 255 then"), "{body}");
+    assert!(body.contains("if Number not in -5 .. 5 then"), "{body}");
+}
+
+/// The `ada` crate only parses declarations, and not the statements of
+/// subprogram bodies, so only the specs are parsed.  Each body is only
+/// checked to be the body of the package of its spec.
+#[test]
+fn generated_specs_parse() {
+    let files = generate();
+    for file in &files {
+        if let Some(unit) = file.file.strip_suffix(".adb") {
+            let spec = content(&files, &format!("{unit}.ads"));
+            let package = spec
+                .lines()
+                .find_map(|line| line.strip_prefix("package ")?.strip_suffix(" is"))
+                .unwrap();
+            let body = file.content.trim_end();
+            assert!(
+                body.contains(&format!("package body {package} is"))
+                    && body.ends_with(&format!("end {package};")),
+                "{}\n{body}",
+                file.file
+            );
+            continue;
+        }
+
+        let mut compiler = ada::Compiler::new();
+        if let Err(diagnostics) = compiler.add_file(file.content.clone()) {
+            let messages: Vec<_> = diagnostics.iter().map(ToString::to_string).collect();
+            panic!("{}: {messages:?}\n{}", file.file, file.content);
+        }
+    }
+}
+
+#[test]
+fn names_differing_in_case_are_numbered() {
+    let source = "Cases DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Pair ::= SEQUENCE { name INTEGER, nAME BOOLEAN, present BOOLEAN OPTIONAL }
+    PAIR ::= SEQUENCE OF Pair
+    Holder ::= CHOICE { pair PAIR, kind NULL }
+    Way ::= ENUMERATED { up, uP }
+END";
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("cases.asn1".into(), source.into())
+        .unwrap();
+    let files = compiler
+        .analysis()
+        .codegen(CodegenTarget::Ada, &CodegenOptions::default())
+        .unwrap();
+    let spec = content(&files, "cases.ads");
+
+    for declaration in [
+        "type Pair_2 is record",
+        "name : Asn1_Runtime.Integer_Value;",
+        "nAME_2 : Boolean;",
+        "type PAIR_Items is array (1 .. Asn1_Runtime.Max_Items) of Cases.Pair_2;",
+        "(Way_up,",
+        "Way_uP_2);",
+    ] {
+        assert!(spec.contains(declaration), "{declaration}\n{spec}");
+    }
+}
@@ -0,0 +1,78 @@
+use std::{fs, path::PathBuf};
+
+use asn1::{Build, BuildError};
+
+const MESSAGES: &str = "Messages DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    IMPORTS Id FROM Common;
+    Message ::= SEQUENCE { id Id, flag BOOLEAN }
+END";
+
+const COMMON: &str = "Common DEFINITIONS ::= BEGIN
+    Id ::= INTEGER
+END";
+
+/// Create an empty directory for the files of a test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asn1-build-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn generated_source_is_written() {
+    let dir = test_dir("write");
+    fs::create_dir_all(dir.join("common")).unwrap();
+    fs::write(dir.join("messages.asn1"), MESSAGES).unwrap();
+    fs::write(dir.join("common").join("common.asn1"), COMMON).unwrap();
+
+    let path = Build::new()
+        .file(dir.join("messages.asn1"))
+        .include(dir.join("common"))
+        .out_dir(&dir)
+        .output("generated.rs")
+        .try_compile()
+        .unwrap();
+    assert_eq!(path, dir.join("generated.rs"));
+
+    let content = fs::read_to_string(path).unwrap();
+    for text in [
+        "pub mod messages {",
+        "pub struct Message {",
+        "pub mod common {",
+    ] {
+        assert!(content.contains(text), "{text}\n{content}");
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn diagnostics_are_errors() {
+    let dir = test_dir("diagnostics");
+    fs::write(dir.join("messages.asn1"), MESSAGES).unwrap();
+
+    let error = Build::new()
+        .file(dir.join("messages.asn1"))
+        .out_dir(&dir)
+        .try_compile()
+        .unwrap_err();
+    let BuildError::Diagnostics(errors) = &error else {
+        panic!("{error:?}");
+    };
+    assert!(!errors.is_empty());
+    assert!(error.to_string().contains("Common"), "{error}");
+    assert!(!dir.join("asn1.rs").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn missing_files_are_errors() {
+    let dir = test_dir("missing");
+    let error = Build::new()
+        .file(dir.join("missing.asn1"))
+        .out_dir(&dir)
+        .try_compile()
+        .unwrap_err();
+    assert!(matches!(error, BuildError::Read(path, _) if path == dir.join("missing.asn1")));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,177 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use asn1::{AsnCompiler, CodegenOptions, CodegenTarget};
+
+const SOURCE: &str = "Vectors DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Pair ::= SET { a INTEGER, b BOOLEAN }
+    Byte ::= INTEGER (0..255)
+    Small ::= INTEGER (0..7, ...)
+    Natural ::= INTEGER (-1..MAX)
+    Large ::= INTEGER (0..18446744073709551615)
+    Code ::= IA5String (SIZE (3) ^ FROM (\"A\"..\"F\" | \"0\"..\"9\"))
+    Name ::= PrintableString (SIZE (1..4, ...))
+    Bytes ::= SEQUENCE SIZE (2..3) OF Byte
+    Two ::= Bytes (SIZE (2))
+    Bits ::= BIT STRING (SIZE (12))
+    Digits ::= NumericString (FROM (\"0\"..\"9\"))
+END";
+
+/// Encodes each value in BER and UPER, printing the name of its type and
+/// both encodings, after checking that each decodes to the same encoding
+const MAIN: &str = r#"#include <stdio.h>
+#include <string.h>
+
+#include "vectors.h"
+
+static void hex(const uint8_t *data, size_t length) {
+    for (size_t i = 0; i < length; i++)
+        printf(i ? " %02X" : "%02X", data[i]);
+}
+
+#define VECTOR(Type, value)                                                    \
+    do {                                                                       \
+        uint8_t ber[64], uper[64], again[64];                                  \
+        size_t ber_length, uper_length, length, again_length;                  \
+        Type decoded;                                                          \
+        if (Type##_encode_ber(&value, ber, sizeof ber, &ber_length) ||         \
+            Type##_decode_ber(&decoded, ber, ber_length, &length) ||           \
+            Type##_encode_ber(&decoded, again, sizeof again, &again_length) || \
+            again_length != ber_length || memcmp(again, ber, ber_length))      \
+            return printf(#Type " BER\n"), 1;                                  \
+        if (Type##_encode_uper(&value, uper, sizeof uper, &uper_length) ||     \
+            Type##_decode_uper(&decoded, uper, uper_length, &length) ||        \
+            Type##_encode_uper(&decoded, again, sizeof again, &again_length) || \
+            again_length != uper_length || memcmp(again, uper, uper_length))   \
+            return printf(#Type " UPER\n"), 1;                                 \
+        printf(#Type ": ");                                                    \
+        hex(ber, ber_length);                                                  \
+        printf(" / ");                                                         \
+        hex(uper, uper_length);                                                \
+        printf("\n");                                                          \
+    } while (0)
+
+int main(void) {
+    Vectors_Pair pair = {5, true};
+    Vectors_Byte byte = 5;
+    Vectors_Small small = 3, large = 9;
+    Vectors_Natural natural = 0;
+    Vectors_Large largest = UINT64_MAX;
+    Vectors_Code code = {3, "A1F"};
+    Vectors_Name name = {2, "AB"};
+    Vectors_Bytes bytes = {3, {1, 2, 3}};
+    Vectors_Two two = {2, {1, 2}};
+    Vectors_Bits bits = {12, {0xAB, 0xC0}};
+    Vectors_Digits digits = {2, "42"};
+    Vectors_Small invalid = -1;
+    uint8_t buffer[8];
+    size_t length;
+
+    VECTOR(Vectors_Pair, pair);
+    VECTOR(Vectors_Byte, byte);
+    VECTOR(Vectors_Small, small);
+    VECTOR(Vectors_Small, large);
+    VECTOR(Vectors_Natural, natural);
+    VECTOR(Vectors_Large, largest);
+    VECTOR(Vectors_Code, code);
+    VECTOR(Vectors_Name, name);
+    VECTOR(Vectors_Bytes, bytes);
+    VECTOR(Vectors_Two, two);
+    VECTOR(Vectors_Bits, bits);
+    VECTOR(Vectors_Digits, digits);
+
+    /* values outside of constraints are not encoded */
+    if (Vectors_Bytes_encode_uper(&two, buffer, sizeof buffer, &length) != ASN1_OK ||
+        Vectors_Two_encode_uper(&bytes, buffer, sizeof buffer, &length) != ASN1_ERR_VALUE ||
+        Vectors_Byte_encode_uper(&invalid, buffer, sizeof buffer, &length) != ASN1_ERR_VALUE)
+        return printf("constraints\n"), 1;
+    return 0;
+}
+"#;
+
+const EXPECTED: &str = "Vectors_Pair: 31 06 80 01 05 81 01 FF / 01 05 80
+Vectors_Byte: 02 01 05 / 05
+Vectors_Small: 02 01 03 / 30
+Vectors_Small: 02 01 09 / 80 84 80
+Vectors_Natural: 02 01 00 / 01 01
+Vectors_Large: 02 09 00 FF FF FF FF FF FF FF FF / FF FF FF FF FF FF FF FF
+Vectors_Code: 16 03 41 31 46 / A1 F0
+Vectors_Name: 13 02 41 42 / 30 61 00
+Vectors_Bytes: 30 09 02 01 01 02 01 02 02 01 03 / 80 81 01 80
+Vectors_Two: 30 06 02 01 01 02 01 02 / 01 02
+Vectors_Bits: 03 03 04 AB C0 / AB C0
+Vectors_Digits: 12 02 34 32 / 02 42
+";
+
+/// Create an empty directory for the generated code of a test
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asn1-c-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn constrained_values_match_vectors() {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("vectors.asn1".into(), SOURCE.into())
+        .unwrap();
+    let files = compiler
+        .analysis()
+        .codegen(CodegenTarget::C, &CodegenOptions::default())
+        .unwrap();
+
+    let dir = output_dir("vectors");
+    for file in files {
+        fs::write(dir.join(file.file), file.content).unwrap();
+    }
+    fs::write(dir.join("main.c"), MAIN).unwrap();
+
+    // the vectors can only be checked where there is a C compiler
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+    let program = dir.join("vectors");
+    let Ok(compiled) = Command::new(compiler)
+        .args(["-std=c99", "-Wall", "-Werror", "-o"])
+        .arg(&program)
+        .args(["main.c", "vectors.c", "asn1_runtime.c", "-lm"])
+        .current_dir(&dir)
+        .output()
+    else {
+        eprintln!("no C compiler, skipping");
+        fs::remove_dir_all(dir).unwrap();
+        return;
+    };
+    assert!(
+        compiled.status.success(),
+        "{}",
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), EXPECTED);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unsupported_types_are_reported() {
+    let source = "Greek DEFINITIONS ::= BEGIN
+    Letters ::= BMPString (FROM (\"α\"..\"ω\"))
+END";
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("greek.asn1".into(), source.into())
+        .unwrap();
+    let error = compiler
+        .analysis()
+        .codegen(CodegenTarget::C, &CodegenOptions::default())
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "permitted alphabets beyond ASCII are not supported by the target language"
+    );
+}
@@ -0,0 +1,146 @@
+use std::collections::{BTreeMap, HashMap};
+
+use asn1::{
+    AsnCompiler, CodegenOptions, CodegenTarget, ModuleLayout, NameCase, Naming, ParameterizedTypes,
+    Visibility,
+};
+
+const SOURCE: &str = "My-Module DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Colour-Kind ::= ENUMERATED { light-red, dark-green }
+    My-Message ::= SEQUENCE { message-id INTEGER, colour-kind Colour-Kind, ratio REAL }
+    Wrapped ::= SEQUENCE { inner SEQUENCE { deep-value BOOLEAN } }
+    max-count INTEGER ::= 5
+    Pair { T } ::= SEQUENCE { first T }
+    Ints ::= Pair { INTEGER }
+END
+
+Other DEFINITIONS ::= BEGIN
+    Id ::= INTEGER
+END";
+
+/// Generate rust code for the test modules, keyed by file name
+fn rust(options: &CodegenOptions) -> HashMap<String, String> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("options.asn1".into(), SOURCE.into())
+        .unwrap();
+    let files = compiler
+        .analysis()
+        .codegen(CodegenTarget::Rust, options)
+        .unwrap();
+    files
+        .into_iter()
+        .map(|file| (file.file, file.content))
+        .collect()
+}
+
+#[test]
+fn defaults_are_a_single_file() {
+    let files = rust(&CodegenOptions::default());
+    let code = &files["asn1.rs"];
+    assert_eq!(files.len(), 1);
+    for text in [
+        "pub mod my_module {",
+        "    pub const MAX_COUNT: i64 = 5;",
+        "    pub enum ColourKind {
+        LightRed = 0,
+        DarkGreen = 1,
+    }",
+        "    pub struct MyMessage {
+        pub message_id: i64,
+        pub colour_kind: ColourKind,
+        pub ratio: f64,
+    }",
+        "    pub type Ints = PairInteger;",
+        "    pub struct WrappedInner {",
+        "pub mod other {
+    pub type Id = i64;
+}",
+    ] {
+        assert!(code.contains(text), "{text}\n{code}");
+    }
+}
+
+#[test]
+fn options_change_the_generated_code() {
+    let options = CodegenOptions {
+        derives: vec!["Eq".into(), "Hash".into()],
+        visibility: Visibility::Crate,
+        layout: ModuleLayout::FilePerModule,
+        naming: Naming {
+            modules: NameCase::Pascal,
+            types: NameCase::Snake,
+            fields: NameCase::Camel,
+            variants: NameCase::ScreamingSnake,
+            constants: NameCase::Snake,
+        },
+        parameterized: ParameterizedTypes::Generic,
+        ..CodegenOptions::default()
+    };
+    let files = rust(&options);
+    let mut names: Vec<_> = files.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["MyModule.rs", "Other.rs", "mod.rs"]);
+    assert_eq!(
+        files["mod.rs"],
+        "pub(crate) mod MyModule;\npub(crate) mod Other;\n"
+    );
+
+    let code = &files["MyModule.rs"];
+    for text in [
+        "pub(crate) const max_count: i64 = 5;",
+        "pub(crate) enum colour_kind {
+    LIGHT_RED = 0,
+    DARK_GREEN = 1,
+}",
+        "pub(crate) type ints = pair<i64>;",
+        "pub(crate) struct pair<t> {
+    pub(crate) first: t,
+}",
+        "    pub(crate) colourKind: colour_kind,",
+    ] {
+        assert!(code.contains(text), "{text}\n{code}");
+    }
+}
+
+#[test]
+fn derives_are_left_out_of_types_with_reals() {
+    let options = CodegenOptions {
+        derives: vec!["Eq".into(), "Hash".into()],
+        ..CodegenOptions::default()
+    };
+    let code = &rust(&options)["asn1.rs"];
+    assert!(code.contains(
+        "    #[derive(Debug, Clone, PartialEq)]
+    pub struct MyMessage {"
+    ));
+    assert!(code.contains(
+        "    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Wrapped {"
+    ));
+}
+
+#[test]
+fn renames_replace_converted_names() {
+    let renames = [
+        ("My-Module", "renamed"),
+        ("My-Module.My-Message", "Msg"),
+        ("My-Module.My-Message.message-id", "id"),
+        ("My-Module.Wrapped.inner.deep-value", "deep"),
+    ];
+    let options = CodegenOptions {
+        renames: BTreeMap::from(renames.map(|(from, to)| (from.to_string(), to.to_string()))),
+        ..CodegenOptions::default()
+    };
+    let code = &rust(&options)["asn1.rs"];
+    for text in [
+        "pub mod renamed {",
+        "    pub struct Msg {
+        pub id: i64,",
+        "    pub struct WrappedInner {
+        pub deep: bool,
+    }",
+    ] {
+        assert!(code.contains(text), "{text}\n{code}");
+    }
+}
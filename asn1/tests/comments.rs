@@ -0,0 +1,72 @@
+use asn1::{AsnCompiler, CodegenOptions, CodegenTarget};
+
+const SOURCE: &str = "Comments DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    -- A colour
+    -- used for painting
+    --
+    -- Second paragraph
+    Colour ::= ENUMERATED {
+        -- The colour red
+        red,
+        green -- The colour green
+    }
+
+    /* A point */
+    Point ::= SEQUENCE {
+        -- Horizontal
+        x INTEGER,
+        y INTEGER -- Vertical
+    }
+
+    Plain ::= BOOLEAN
+    Other ::= BOOLEAN -- Not a boolean
+END";
+
+/// Generate rust code for the test module
+fn rust() -> String {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("comments.asn1".into(), SOURCE.into())
+        .unwrap();
+    let files = compiler
+        .analysis()
+        .codegen(CodegenTarget::Rust, &CodegenOptions::default())
+        .unwrap();
+    files.into_iter().map(|file| file.content).collect()
+}
+
+#[test]
+fn assignments_are_documented() {
+    let code = rust();
+    for doc in [
+        "    /// A colour
+    /// used for painting
+    ///
+    /// Second paragraph
+    #[derive",
+        "    /// A point
+    #[derive",
+        "    /// Not a boolean
+    pub type Other = bool;",
+    ] {
+        assert!(code.contains(doc), "{doc}\n{code}");
+    }
+    assert!(code.contains("\n\n    pub type Plain = bool;"), "{code}");
+}
+
+#[test]
+fn items_and_components_are_documented() {
+    let code = rust();
+    for doc in [
+        "        /// The colour red
+        Red = 0,",
+        "        /// The colour green
+        Green = 1,",
+        "        /// Horizontal
+        pub x: i64,",
+        "        /// Vertical
+        pub y: i64,",
+    ] {
+        assert!(code.contains(doc), "{doc}\n{code}");
+    }
+}
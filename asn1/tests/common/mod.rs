@@ -0,0 +1,37 @@
+//! Helpers shared by the integration tests
+
+// every test crate includes this module but only uses some of the helpers
+#![allow(dead_code)]
+
+use asn1::AsnCompiler;
+
+/// Create a compiler with a file for each of the sources
+pub fn compile(sources: &[&str]) -> AsnCompiler {
+    let mut compiler = AsnCompiler::new();
+    for (i, source) in sources.iter().enumerate() {
+        compiler
+            .add_file(format!("{i}.asn1"), source.to_string())
+            .unwrap();
+    }
+    compiler
+}
+
+/// Analyse the sources of a compiler and get the code of every diagnostic
+pub fn codes(compiler: &mut AsnCompiler) -> Vec<String> {
+    compiler
+        .analysis()
+        .diagnostics
+        .into_iter()
+        .map(|d| d.error_code)
+        .collect()
+}
+
+/// Analyse the sources and get the code and name of every diagnostic
+pub fn diagnostics(sources: &[&str]) -> Vec<(String, String)> {
+    compile(sources)
+        .analysis()
+        .diagnostics
+        .into_iter()
+        .map(|d| (d.error_code, d.name))
+        .collect()
+}
@@ -0,0 +1,48 @@
+use asn1::{AsnCompiler, ChangeKind};
+
+const OLD: &str = "Module DEFINITIONS ::= BEGIN
+    Id ::= [APPLICATION 1] INTEGER
+    Pair ::= SEQUENCE { a [0] INTEGER, b Id }
+    Alias ::= Id
+END";
+
+const NEW: &str = "Module DEFINITIONS ::= BEGIN
+    Id ::= [APPLICATION 2] INTEGER
+    Pair ::= SEQUENCE { a [1] IMPLICIT INTEGER, b Id }
+    Alias ::= Id
+END";
+
+#[test]
+fn tag_changes_point_at_the_tag() {
+    let mut old_compiler = AsnCompiler::new();
+    old_compiler
+        .add_file("old.asn1".into(), OLD.into())
+        .unwrap();
+    let mut new_compiler = AsnCompiler::new();
+    new_compiler
+        .add_file("new.asn1".into(), NEW.into())
+        .unwrap();
+
+    let changes = old_compiler
+        .analysis()
+        .compatibility(&new_compiler.analysis());
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|c| {
+            let label = c.diagnostic.labels.first().and_then(|l| l.location.clone());
+            (
+                c.kind,
+                c.path.as_str(),
+                label.map(|location| &NEW[location]),
+            )
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (ChangeKind::TagChanged, "Module.Alias", Some("Id")),
+            (ChangeKind::TagChanged, "Module.Id", Some("[APPLICATION 2]")),
+            (ChangeKind::TagChanged, "Module.Pair.a", Some("[1]")),
+        ]
+    );
+}
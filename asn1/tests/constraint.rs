@@ -0,0 +1,199 @@
+use asn1::{
+    AsnCompiler, BigInt, ChangeKind, CstNode, FormatOptions, SequenceTypeNode, Severity,
+    TypeAssignmentNode,
+};
+
+const SOURCE: &str = "Module DEFINITIONS ::= BEGIN
+    Byte ::= INTEGER (0..255)
+    Small ::= INTEGER (MIN..<0 | 1<..MAX, ...)
+    Name ::= IA5String (SIZE (1..64)) (FROM (\"a\"..\"z\"))
+    Names ::= SEQUENCE SIZE (0..10) OF Name
+    Bytes ::= SEQUENCE (SIZE (1..4)) OF INTEGER (0..255)
+    Pair ::= SEQUENCE {
+        a INTEGER (1 | 2 | 3) OPTIONAL,
+        b OCTET STRING (CONTAINING Byte ENCODED BY { 2 1 1 }),
+        c Byte (ALL EXCEPT 0) DEFAULT 1
+    }
+    Only ::= Pair (WITH COMPONENTS { ..., a PRESENT, b (SIZE (1)) ABSENT })
+    Opaque ::= OCTET STRING (CONSTRAINED BY { -- hashed -- })
+END";
+
+#[test]
+fn constraints_are_parsed_and_analysed() {
+    let mut compiler = AsnCompiler::new();
+    compiler.add_file("a.asn1".into(), SOURCE.into()).unwrap();
+
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+}
+
+#[test]
+fn constraint_nodes() {
+    let mut compiler = AsnCompiler::new();
+    let file = compiler.add_file("a.asn1".into(), SOURCE.into()).unwrap();
+    let tree = compiler.tree(file).unwrap();
+    let text = compiler.source_text(file).unwrap();
+
+    let module = tree.modules().next().unwrap();
+    let types: Vec<_> = module
+        .assignments()
+        .filter_map(|a| a.type_assignment())
+        .filter_map(|a: TypeAssignmentNode| a.ty())
+        .collect();
+
+    let constraints: Vec<_> = types[2]
+        .constraints()
+        .into_iter()
+        .map(|c| &text[c.span()])
+        .collect();
+    assert_eq!(constraints, ["(SIZE (1..64))", "(FROM (\"a\"..\"z\"))"]);
+
+    let names = tree
+        .cast::<SequenceTypeNode>(types[3].inner().unwrap())
+        .unwrap();
+    assert!(names.is_of());
+    assert_eq!(&text[names.of_constraint().unwrap().span()], "(0..10)");
+    assert!(types[3].constraints().is_empty());
+}
+
+#[test]
+fn range_separator_is_not_part_of_numbers() {
+    let source = "Module DEFINITIONS ::= BEGIN
+    Real ::= REAL (1.5..2.5)
+    Int ::= INTEGER(-5 ..< 5)
+    Only ::= SEQUENCE { a INTEGER } (WITH COMPONENTS { a(1) })
+END";
+
+    let mut compiler = AsnCompiler::new();
+    let file = compiler.add_file("a.asn1".into(), source.into()).unwrap();
+    assert!(compiler.analysis().diagnostics.is_empty());
+
+    let formatted = compiler.format(file, &FormatOptions::default());
+    assert!(formatted.contains("REAL (1.5..2.5)"), "{formatted}");
+    assert!(formatted.contains("INTEGER (-5..<5)"), "{formatted}");
+    assert!(formatted.contains("{ a (1) }"), "{formatted}");
+}
+
+#[test]
+fn changed_constraints_are_incompatible() {
+    let old = "Module DEFINITIONS ::= BEGIN
+    Byte ::= INTEGER (0..255)
+    Pair ::= SEQUENCE { a Byte, b Byte (0..7) }
+END";
+    let new = "Module DEFINITIONS ::= BEGIN
+    Byte ::= INTEGER (0 .. 255 -- unsigned --)
+    Pair ::= SEQUENCE { a Byte, b Byte (0..15) }
+END";
+
+    let mut old_compiler = AsnCompiler::new();
+    old_compiler
+        .add_file("old.asn1".into(), old.into())
+        .unwrap();
+    let mut new_compiler = AsnCompiler::new();
+    new_compiler
+        .add_file("new.asn1".into(), new.into())
+        .unwrap();
+
+    let changes = old_compiler
+        .analysis()
+        .compatibility(&new_compiler.analysis());
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|c| (c.kind, c.path.as_str(), c.ber, c.per))
+        .collect();
+    assert_eq!(
+        changes,
+        [(
+            ChangeKind::ConstraintLoosened,
+            "Module.Pair.b",
+            Severity::Warning,
+            Severity::Breaking
+        )]
+    );
+}
+
+#[test]
+fn constraint_changes_are_classified_by_visibility() {
+    let old = "Module DEFINITIONS ::= BEGIN
+    Count ::= INTEGER (0..65535)
+    Name ::= UTF8String (SIZE (1..16))
+    Code ::= IA5String (SIZE (2))
+    Mixed ::= INTEGER (0..10 | 20..30)
+END";
+    let new = "Module DEFINITIONS ::= BEGIN
+    Count ::= INTEGER (0..255)
+    Name ::= UTF8String (SIZE (1..32))
+    Code ::= IA5String (SIZE (2, ...))
+    Mixed ::= INTEGER (5..25)
+END";
+
+    let mut old_compiler = AsnCompiler::new();
+    old_compiler
+        .add_file("old.asn1".into(), old.into())
+        .unwrap();
+    let mut new_compiler = AsnCompiler::new();
+    new_compiler
+        .add_file("new.asn1".into(), new.into())
+        .unwrap();
+
+    let changes = old_compiler
+        .analysis()
+        .compatibility(&new_compiler.analysis());
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|c| (c.kind, c.path.as_str(), c.per, c.oer))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (
+                ChangeKind::ConstraintLoosened,
+                "Module.Code",
+                Severity::Breaking,
+                Severity::Breaking
+            ),
+            (
+                ChangeKind::ConstraintTightened,
+                "Module.Count",
+                Severity::Breaking,
+                Severity::Breaking
+            ),
+            (
+                ChangeKind::ConstraintChanged,
+                "Module.Mixed",
+                Severity::Breaking,
+                Severity::Compatible
+            ),
+            (
+                ChangeKind::ConstraintLoosened,
+                "Module.Name",
+                Severity::Compatible,
+                Severity::Compatible
+            ),
+        ]
+    );
+}
+
+#[test]
+fn constraint_keywords_are_identifiers_when_lowercase() {
+    let source = "Module DEFINITIONS ::= BEGIN
+    s SEQUENCE { size INTEGER (0..max), max INTEGER, pattern BOOLEAN } ::= {
+        size 1, max 2, pattern TRUE
+    }
+    max INTEGER ::= 10
+    min INTEGER (0..max) ::= 1
+END";
+
+    let mut compiler = AsnCompiler::new();
+    compiler.lowercase_keywords = true;
+    compiler.add_file("a.asn1".into(), source.into()).unwrap();
+
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    assert_eq!(
+        analysis.integer_value("Module", "max"),
+        Some(&BigInt::from(10))
+    );
+}
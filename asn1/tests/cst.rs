@@ -0,0 +1,147 @@
+use asn1::{
+    Asn1, Asn1Tag, AsnCompiler, AsnNodeId, ComponentTypeNode, CstNode, EnumItemNode,
+    ModuleDefinitionNode, SequenceTypeNode, TypeAssignmentNode, Visitor,
+};
+
+const SOURCE: &str = "Module DEFINITIONS ::= BEGIN
+    EXPORTS Seq;
+    Seq ::= SEQUENCE {
+        a INTEGER,
+        b BOOLEAN OPTIONAL,
+        c Colour DEFAULT red
+    }
+    Colour ::= ENUMERATED { red, green }
+END";
+
+#[test]
+fn typed_nodes() {
+    let mut compiler = AsnCompiler::new();
+    let file = compiler.add_file("a.asn1".into(), SOURCE.into()).unwrap();
+    let tree = compiler.tree(file).unwrap();
+    let text = compiler.source_text(file).unwrap();
+
+    let module = tree.modules().next().unwrap();
+    assert_eq!(&text[module.name().unwrap().span()], "Module");
+    let exports = module.exports().unwrap();
+    assert!(!exports.is_all());
+    assert_eq!(exports.symbols().len(), 1);
+
+    let names: Vec<_> = module
+        .assignments()
+        .map(|a| &text[a.name().unwrap().span()])
+        .collect();
+    assert_eq!(names, ["Seq", "Colour"]);
+
+    let seq = module.assignments().next().unwrap();
+    let ty = seq.type_assignment().unwrap().ty().unwrap();
+    let seq = tree.cast::<SequenceTypeNode>(ty.inner().unwrap()).unwrap();
+    assert!(!seq.is_of());
+
+    let components = seq.components();
+    assert_eq!(components.len(), 3);
+    assert!(!components[0].is_optional());
+    assert!(components[1].is_optional());
+    assert!(components[2].default().is_some());
+    assert_eq!(&text[components[2].name().unwrap().span()], "c");
+}
+
+#[test]
+fn navigation() {
+    let mut compiler = AsnCompiler::new();
+    let file = compiler.add_file("a.asn1".into(), SOURCE.into()).unwrap();
+    let tree = compiler.tree(file).unwrap();
+    let text = compiler.source_text(file).unwrap();
+
+    let offset = text.find("OPTIONAL").unwrap();
+    let node = tree.node_at(offset).unwrap();
+    assert_eq!(tree.span(node), offset..offset + "OPTIONAL".len());
+
+    let component = tree.ancestor::<ComponentTypeNode>(node).unwrap();
+    assert_eq!(&text[component.name().unwrap().span()], "b");
+    assert!(tree.ancestor::<TypeAssignmentNode>(node).is_some());
+    assert!(tree.ancestor::<ModuleDefinitionNode>(node).is_some());
+
+    let next = tree.next_sibling(component.id()).unwrap();
+    assert_eq!(tree.prev_sibling(next), Some(component.id()));
+    assert_eq!(
+        tree.parent(component.id()),
+        component.parent(),
+        "wrappers and trees agree on parents"
+    );
+}
+
+/// Collects the names of components and enumeration items, and the nesting
+/// of the tree nodes entered
+#[derive(Default)]
+struct Names<'a> {
+    text: &'a str,
+    components: Vec<String>,
+    items: Vec<String>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Visitor for Names<'_> {
+    fn enter(&mut self, _: &Asn1, _: AsnNodeId, _: Asn1Tag) -> bool {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+        true
+    }
+
+    fn exit(&mut self, _: &Asn1, _: AsnNodeId, _: Asn1Tag) {
+        self.depth -= 1;
+    }
+
+    fn visit_component_type(&mut self, node: ComponentTypeNode<'_>) -> bool {
+        self.components
+            .push(self.text[node.name().unwrap().span()].to_string());
+        true
+    }
+
+    fn visit_enum_item(&mut self, node: EnumItemNode<'_>) -> bool {
+        self.items
+            .push(self.text[node.name().unwrap().span()].to_string());
+        true
+    }
+}
+
+#[test]
+fn typed_visitor() {
+    let mut compiler = AsnCompiler::new();
+    let file = compiler.add_file("a.asn1".into(), SOURCE.into()).unwrap();
+    let tree = compiler.tree(file).unwrap();
+
+    let mut names = Names {
+        text: compiler.source_text(file).unwrap(),
+        ..Default::default()
+    };
+    tree.walk(&mut names);
+    assert_eq!(names.components, ["a", "b", "c"]);
+    assert_eq!(names.items, ["red", "green"]);
+    assert_eq!(names.depth, 0);
+    assert!(names.max_depth > 4);
+}
+
+/// Skips the contents of every sequence type
+struct SkipSequences(usize);
+
+impl Visitor for SkipSequences {
+    fn visit_sequence_type(&mut self, _: SequenceTypeNode<'_>) -> bool {
+        false
+    }
+
+    fn visit_component_type(&mut self, _: ComponentTypeNode<'_>) -> bool {
+        self.0 += 1;
+        true
+    }
+}
+
+#[test]
+fn typed_visitor_skips_contents() {
+    let mut compiler = AsnCompiler::new();
+    let file = compiler.add_file("a.asn1".into(), SOURCE.into()).unwrap();
+
+    let mut skip = SkipSequences(0);
+    compiler.tree(file).unwrap().walk(&mut skip);
+    assert_eq!(skip.0, 0);
+}
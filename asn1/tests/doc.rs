@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use asn1::{AsnCompiler, DocFormat, DocPage};
+
+const SOURCE: &str = r#"Docs DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    limit INTEGER ::= 200
+    Byte ::= INTEGER (0..limit)
+    Small ::= Byte (MIN<..<10, ...)
+    Name ::= IA5String (SIZE (1..8)) (FROM ("a".."z")) (PATTERN "[a-z]*")
+    Point ::= SEQUENCE { x Byte OPTIONAL, y INTEGER (1 | 3 | 5..7) }
+END"#;
+
+const LINKED: &str = "Base { 1 2 3 } DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    -- A colour
+    Colour ::= ENUMERATED { red, green }
+    base OBJECT IDENTIFIER ::= { 1 2 3 4 }
+END
+
+User DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    IMPORTS Colour FROM Base;
+    -- Something painted
+    Paint ::= SEQUENCE {
+        -- The colour used
+        colour Colour,
+        other Base.Colour
+    }
+END";
+
+/// Generate the documentation of a source, keyed by file name
+fn pages(source: &str, format: DocFormat) -> HashMap<String, String> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("docs.asn1".into(), source.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    analysis
+        .documentation(format)
+        .into_iter()
+        .map(|DocPage { file, content }| (file, content))
+        .collect()
+}
+
+fn page(format: DocFormat) -> DocPage {
+    let (file, content) = pages(SOURCE, format)
+        .into_iter()
+        .find(|(file, _)| file.starts_with("Docs"))
+        .unwrap();
+    DocPage { file, content }
+}
+
+#[test]
+fn constraints_are_summarised() {
+    let content = page(DocFormat::Markdown).content;
+    for line in [
+        "Constraints: `0..200`",
+        "Constraints: `0..9, ...`",
+        "Constraints: `SIZE (1..8)`, `FROM (\"a\"..\"z\")`, `PATTERN \"[a-z]*\"`",
+        "| Identifier | Type | Constraints | Tag | Presence | Description |",
+        "| `x` | [`Byte`](#Byte) | `0..200` | `[0] IMPLICIT` (automatic) | `OPTIONAL` |  |",
+        "| `y` | `INTEGER` | `1 \\| 3 \\| 5..7` | `[1] IMPLICIT` (automatic) |  |  |",
+    ] {
+        assert!(content.contains(line), "{line}\n{content}");
+    }
+}
+
+#[test]
+fn constraints_are_escaped() {
+    let content = page(DocFormat::Html).content;
+    let line = "<p>Constraints: <code>SIZE (1..8)</code>, <code>FROM (&quot;a&quot;..&quot;z&quot;)</code>";
+    assert!(content.contains(line), "{content}");
+}
+
+#[test]
+fn pages_are_written_for_each_module() {
+    let mut files: Vec<_> = pages(LINKED, DocFormat::Markdown).into_keys().collect();
+    files.sort();
+    assert_eq!(files, ["Base.md", "User.md", "index.md"]);
+
+    let mut files: Vec<_> = pages(LINKED, DocFormat::Html).into_keys().collect();
+    files.sort();
+    assert_eq!(files, ["Base.html", "User.html", "index.html"]);
+}
+
+#[test]
+fn references_are_linked() {
+    let pages = pages(LINKED, DocFormat::Markdown);
+    let user = &pages["User.md"];
+    for line in [
+        "- From [`Base`](Base.md): [`Colour`](Base.md#Colour)",
+        "Something painted",
+        "colour <a href=\"Base.md#Colour\">Colour</a>,",
+        "| `colour` | [`Colour`](Base.md#Colour) |  | `[0] IMPLICIT` (automatic) |  | The colour used |",
+        "| `other` | [`Base.Colour`](Base.md#Colour) |",
+    ] {
+        assert!(user.contains(line), "{line}\n{user}");
+    }
+
+    let base = &pages["Base.md"];
+    assert!(base.contains("<a id=\"Colour\"></a>\n\n### `Colour`\n\nA colour"));
+}
+
+#[test]
+fn index_lists_modules_and_oids() {
+    let pages = pages(LINKED, DocFormat::Markdown);
+    let index = &pages["index.md"];
+    for line in [
+        "- [`Base`](Base.md) `{ iso(1) member-body(2) 3 }`",
+        "- [`User`](User.md)",
+        "- `{ iso(1) member-body(2) 3 }` [`Base`](Base.md)",
+        "  - `{ iso(1) member-body(2) 3 4 }` [`Base.base`](Base.md#base)",
+    ] {
+        assert!(index.contains(line), "{line}\n{index}");
+    }
+}
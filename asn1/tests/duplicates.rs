@@ -0,0 +1,99 @@
+mod common;
+
+use common::diagnostics;
+
+#[test]
+fn assignments_are_unique() {
+    let diagnostics = diagnostics(&["Module DEFINITIONS ::= BEGIN
+    Thing ::= INTEGER
+    Thing ::= BOOLEAN
+    value INTEGER ::= 1
+END"]);
+    assert_eq!(
+        diagnostics,
+        [(
+            "Asn1::Analysis::DuplicateAssignment".to_string(),
+            "`Thing` is defined more than once in module `Module`".to_string()
+        )]
+    );
+}
+
+#[test]
+fn identifiers_are_unique() {
+    let diagnostics = diagnostics(&["Module DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Pair ::= SEQUENCE { a INTEGER, b CHOICE { x NULL, x BOOLEAN }, a BOOLEAN }
+    Colour ::= ENUMERATED { red, green, red }
+    Flags ::= BIT STRING { first(0), second(1), first(2) }
+    Id {T, T} ::= SEQUENCE { value T }
+END"]);
+    let names: Vec<_> = diagnostics.iter().map(|(_, name)| name.as_str()).collect();
+    for name in [
+        "Duplicate component identifier `a`",
+        "Duplicate alternative identifier `x`",
+        "Duplicate enumeration item identifier `red`",
+        "Duplicate named bit identifier `first`",
+        "Duplicate parameter identifier `T`",
+    ] {
+        assert!(names.contains(&name), "{name} {names:?}");
+    }
+    assert!(diagnostics
+        .iter()
+        .all(|(code, _)| code == "Asn1::Analysis::DuplicateIdentifier"));
+}
+
+#[test]
+fn numbers_are_unique() {
+    let diagnostics = diagnostics(&["Module DEFINITIONS ::= BEGIN
+    Level ::= INTEGER { low(0), high(10), max(10) }
+    Colour ::= ENUMERATED { red(1), green(1) }
+    Grown ::= ENUMERATED { red, ..., green, blue(1) }
+END"]);
+    assert_eq!(
+        diagnostics,
+        [
+            (
+                "Asn1::Analysis::DuplicateValue".to_string(),
+                "Duplicate named number value 10".to_string()
+            ),
+            (
+                "Asn1::Analysis::DuplicateValue".to_string(),
+                "Duplicate enumeration item value 1".to_string()
+            ),
+            (
+                "Asn1::Analysis::DuplicateValue".to_string(),
+                "Duplicate enumeration item value 1".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn modules_with_the_same_name_are_reported() {
+    let diagnostics = diagnostics(&[
+        "Module DEFINITIONS ::= BEGIN A ::= NULL END",
+        "Module DEFINITIONS ::= BEGIN B ::= NULL END",
+    ]);
+    assert_eq!(
+        diagnostics,
+        [(
+            "Asn1::Analysis::DuplicateModule".to_string(),
+            "Module `Module` is defined more than once".to_string()
+        )]
+    );
+}
+
+#[test]
+fn modules_are_told_apart_by_oid() {
+    let diagnostics = diagnostics(&[
+        "Module { 1 2 3 } DEFINITIONS ::= BEGIN A ::= NULL END",
+        "Module { 1 2 4 } DEFINITIONS ::= BEGIN B ::= NULL END",
+        "Other { 1 2 3 } DEFINITIONS ::= BEGIN C ::= NULL END",
+    ]);
+    assert_eq!(
+        diagnostics,
+        [(
+            "Asn1::Analysis::DuplicateModuleOid".to_string(),
+            "Modules `Module` and `Other` have the same object identifier 1.2.3".to_string()
+        )]
+    );
+}
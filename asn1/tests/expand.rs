@@ -0,0 +1,88 @@
+mod common;
+
+use asn1::AsnCompiler;
+use common::diagnostics;
+
+const SOURCE: &str = "Expand DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Header ::= SEQUENCE { id INTEGER, name IA5String OPTIONAL }
+    Message ::= SEQUENCE { COMPONENTS OF Header, body OCTET STRING }
+    Either ::= CHOICE { number INTEGER, text UTF8String }
+    Chosen ::= text < Either
+    OPERATION ::= CLASS { &id INTEGER UNIQUE, &Result }
+    op1 OPERATION ::= { &id 1, &Result BOOLEAN }
+    Result ::= op1.&Result
+    Id ::= OPERATION.&id
+END";
+
+fn flatten(roots: &[&str]) -> String {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("expand.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    let roots: Vec<_> = roots.iter().map(|root| root.to_string()).collect();
+    analysis.flatten("Flat", &roots).unwrap()
+}
+
+#[test]
+fn components_of_are_included() {
+    let flat = flatten(&["Message"]);
+    assert!(
+        flat.contains(
+            "Message ::= SEQUENCE {
+    id   [0] IMPLICIT INTEGER,
+    name [1] IMPLICIT IA5String OPTIONAL,
+    body [2] IMPLICIT OCTET STRING
+}"
+        ),
+        "{flat}"
+    );
+    assert!(!flat.contains("Header"), "{flat}");
+}
+
+#[test]
+fn selections_are_replaced() {
+    let flat = flatten(&["Chosen"]);
+    assert!(flat.contains("Chosen ::= "), "{flat}");
+    assert!(flat.contains("UTF8String"), "{flat}");
+    assert!(!flat.contains("Either"), "{flat}");
+}
+
+#[test]
+fn field_types_are_replaced() {
+    let flat = flatten(&["Result", "Id"]);
+    assert!(flat.contains("Result ::= BOOLEAN"), "{flat}");
+    assert!(flat.contains("Id ::= INTEGER"), "{flat}");
+}
+
+#[test]
+fn invalid_expansions_are_reported() {
+    let codes: Vec<_> = diagnostics(&["Invalid DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Either ::= CHOICE { number INTEGER, text UTF8String }
+    Bad ::= SEQUENCE { COMPONENTS OF Either }
+    Wrong ::= missing < Either
+    Loop ::= SEQUENCE { COMPONENTS OF Loop }
+END"])
+    .into_iter()
+    .map(|(code, _)| code)
+    .collect();
+    for code in [
+        "Asn1::Analysis::ComponentsOf",
+        "Asn1::Analysis::Selection",
+        "Asn1::Analysis::RecursiveType",
+    ] {
+        assert!(codes.contains(&code.to_string()), "{code} {codes:?}");
+    }
+}
+
+#[test]
+fn duplicate_components_are_reported() {
+    let diagnostics = diagnostics(&["Included DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Header ::= SEQUENCE { id INTEGER }
+    Message ::= SEQUENCE { id BOOLEAN, COMPONENTS OF Header }
+END"]);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].0, "Asn1::Analysis::DuplicateIdentifier");
+}
@@ -0,0 +1,116 @@
+use asn1::{AsnCompiler, Diagnostic};
+
+const SOURCE: &str = "Shapes DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    limit INTEGER ::= 10
+
+    Byte ::= INTEGER (0..limit)
+    Local ::= INTEGER (MIN<..<limit, ..., 20)
+    Name ::= IA5String (SIZE (1..8) ^ FROM (\"a\"..\"z\") | SIZE (0)) (PATTERN \"[a-z]*\")
+    List ::= [0] SEQUENCE SIZE (1..4) OF Byte (1..5)
+    Point ::= SEQUENCE { x Byte OPTIONAL, y Byte } (WITH COMPONENTS { ..., x PRESENT })
+    Wrapped ::= OCTET STRING (CONTAINING Point)
+    Param { INTEGER: max, T } ::= SEQUENCE { a INTEGER (0..max), b OCTET STRING (CONTAINING T) }
+    Instance ::= Param { limit, Point }
+END
+
+Limits DEFINITIONS ::= BEGIN
+    limit INTEGER ::= 200
+    Small ::= INTEGER (0..limit)
+END";
+
+const TAGGED: &str = "Tagged DEFINITIONS IMPLICIT TAGS EXTENSIBILITY IMPLIED ::= BEGIN
+    Id ::= [APPLICATION 1] INTEGER
+    Holder ::= SEQUENCE { id [0] Id, any [1] CHOICE { a INTEGER, b BOOLEAN } }
+    Unused ::= BOOLEAN
+END
+
+Explicit DEFINITIONS ::= BEGIN
+    Wrapper ::= [2] INTEGER
+END";
+
+/// Flatten a source, keeping the assignments used by the roots
+fn flatten_source(source: &str, roots: &[&str]) -> Result<String, Diagnostic> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("shapes.asn1".into(), source.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    let roots: Vec<_> = roots.iter().map(|root| root.to_string()).collect();
+    analysis.flatten("Flat", &roots)
+}
+
+fn flatten(roots: &[&str]) -> String {
+    flatten_source(SOURCE, roots).unwrap()
+}
+
+#[test]
+fn constraints_are_written() {
+    let flat = flatten(&["Local", "Name", "List", "Point", "Wrapped"]);
+    for line in [
+        "Local ::= INTEGER (MIN<..<limit, ..., 20)",
+        "Name ::= IA5String (SIZE (1..8) ^ FROM (\"a\"..\"z\") | SIZE (0)) (PATTERN \"[a-z]*\")",
+        "List ::= [0] IMPLICIT SEQUENCE (SIZE (1..4)) OF Byte (1..5)",
+        "} (WITH COMPONENTS { ..., x PRESENT })",
+        "Wrapped ::= OCTET STRING (CONTAINING Point)",
+    ] {
+        assert!(flat.contains(line), "{line}\n{flat}");
+    }
+}
+
+#[test]
+fn constraint_references_are_renamed() {
+    let flat = flatten(&["Byte", "Small"]);
+    assert!(
+        flat.contains("Byte ::= INTEGER (0..shapes-limit)"),
+        "{flat}"
+    );
+    assert!(
+        flat.contains("Small ::= INTEGER (0..limits-limit)"),
+        "{flat}"
+    );
+    assert!(flat.contains("limits-limit INTEGER ::= 200"), "{flat}");
+    assert!(flat.contains("shapes-limit INTEGER ::= 10"), "{flat}");
+}
+
+#[test]
+fn instance_parameters_are_replaced() {
+    let flat = flatten(&["Instance"]);
+    assert!(flat.contains("a [0] IMPLICIT INTEGER (0..limit)"), "{flat}");
+    assert!(
+        flat.contains("b [1] IMPLICIT OCTET STRING (CONTAINING Point)"),
+        "{flat}"
+    );
+    assert!(!flat.contains("max"), "{flat}");
+}
+
+#[test]
+fn tags_are_made_explicit() {
+    let flat = flatten_source(TAGGED, &["Holder", "Wrapper"]).unwrap();
+    let expected = "Flat DEFINITIONS EXPLICIT TAGS ::=
+BEGIN
+
+Holder ::= SEQUENCE {
+    id  [0] IMPLICIT Id,
+    any [1] EXPLICIT CHOICE {
+        a INTEGER,
+        b BOOLEAN,
+        ...
+    },
+    ...
+}
+
+Wrapper ::= [2] EXPLICIT INTEGER
+
+Id ::= [APPLICATION 1] IMPLICIT INTEGER
+
+END";
+    assert_eq!(flat.trim_end(), expected);
+}
+
+#[test]
+fn unknown_roots_are_reported() {
+    let error = flatten_source(TAGGED, &["Missing"]).unwrap_err();
+    assert_eq!(error.error_code, "Asn1::Flatten::UnknownRoot");
+}
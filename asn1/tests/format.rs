@@ -0,0 +1,96 @@
+use asn1::{AsnCompiler, FormatOptions, KeywordCase};
+
+const SOURCE: &str = "Shapes   DEFINITIONS AUTOMATIC TAGS::=BEGIN
+IMPORTS Colour,Size FROM Paint   Other FROM Base;
+-- a point
+Point::=sequence{x INTEGER(0..255),-- across
+longName    INTEGER OPTIONAL,
+...}
+Shape ::= CHOICE { circle [0] Point, square Point }
+origin Point::={x 0,longName 1}
+END
+";
+
+const FORMATTED: &str = "Shapes DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    IMPORTS
+        Colour, Size FROM Paint
+        Other FROM Base;
+
+    -- a point
+    Point ::= sequence {
+        x        INTEGER (0..255), -- across
+        longName INTEGER OPTIONAL,
+        ...
+    }
+    Shape ::= CHOICE {
+        circle [0] Point,
+        square Point
+    }
+    origin Point ::= { x 0, longName 1 }
+END
+";
+
+fn format(source: &str, options: &FormatOptions) -> String {
+    let mut compiler = AsnCompiler::new();
+    compiler.lowercase_keywords = true;
+    let file = compiler
+        .add_file("shapes.asn1".into(), source.into())
+        .unwrap();
+    compiler.format(file, options)
+}
+
+#[test]
+fn layout_is_canonical() {
+    assert_eq!(format(SOURCE, &FormatOptions::default()), FORMATTED);
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let options = FormatOptions {
+        keyword_case: KeywordCase::Upper,
+        one_import_per_line: true,
+        ..FormatOptions::default()
+    };
+    let once = format(SOURCE, &options);
+    assert_eq!(format(&once, &options), once);
+    assert_eq!(format(FORMATTED, &FormatOptions::default()), FORMATTED);
+}
+
+#[test]
+fn options_change_layout() {
+    let options = FormatOptions {
+        indent: 2,
+        align_components: false,
+        one_import_per_line: true,
+        keyword_case: KeywordCase::Upper,
+    };
+    let formatted = format(SOURCE, &options);
+    for line in [
+        "  IMPORTS\n    Colour,\n    Size FROM Paint\n    Other FROM Base;\n",
+        "  Point ::= SEQUENCE {\n",
+        "    x INTEGER (0..255), -- across\n",
+        "    longName INTEGER OPTIONAL,\n",
+    ] {
+        assert!(formatted.contains(line), "{line}\n{formatted}");
+    }
+}
+
+#[test]
+fn keywords_are_lowercased() {
+    let options = FormatOptions {
+        keyword_case: KeywordCase::Lower,
+        ..FormatOptions::default()
+    };
+    let formatted = format(SOURCE, &options);
+    for line in [
+        "Shapes definitions automatic tags ::= begin\n",
+        "        Colour, Size from Paint\n",
+        "        longName integer optional,\n",
+        "    Shape ::= choice {\n",
+        "end\n",
+    ] {
+        assert!(formatted.contains(line), "{line}\n{formatted}");
+    }
+    // identifiers are not keywords
+    assert!(formatted.contains("Point ::= sequence"), "{formatted}");
+}
@@ -0,0 +1,106 @@
+use asn1::{AsnCompiler, DependencyGraph, GraphLevel};
+
+const SOURCE: &str = "A DEFINITIONS ::= BEGIN
+    IMPORTS Bt FROM B Missing FROM C;
+    At ::= SEQUENCE { b Bt, c Missing }
+END
+
+B DEFINITIONS ::= BEGIN
+    IMPORTS At FROM A;
+    Bt ::= SEQUENCE OF At
+    Local ::= Bt
+END";
+
+fn graph(level: GraphLevel) -> DependencyGraph {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("graph.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    analysis.dependency_graph(level)
+}
+
+#[test]
+fn modules_are_connected_by_imports() {
+    let graph = graph(GraphLevel::Modules);
+    let nodes: Vec<_> = graph
+        .nodes
+        .iter()
+        .map(|node| (node.name.as_str(), node.resolved, node.cyclic))
+        .collect();
+    assert_eq!(
+        nodes,
+        [("A", true, true), ("B", true, true), ("C", false, false)]
+    );
+
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                graph.nodes[edge.from].name.as_str(),
+                graph.nodes[edge.to].name.as_str(),
+                edge.symbols.clone(),
+                edge.unresolved.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        edges,
+        [
+            ("A", "B", vec!["Bt".to_string()], vec![]),
+            (
+                "A",
+                "C",
+                vec!["Missing".to_string()],
+                vec!["Missing".to_string()]
+            ),
+            ("B", "A", vec!["At".to_string()], vec![]),
+        ]
+    );
+    assert_eq!(graph.cycles, [vec![0, 1]]);
+}
+
+#[test]
+fn assignments_are_connected_by_references() {
+    let graph = graph(GraphLevel::Types);
+    assert_eq!(
+        graph.to_dot(),
+        "digraph types {
+\trankdir=LR;
+\tnode [shape=box];
+\t\"A.At\" [color=red];
+\t\"B.Bt\" [color=red];
+\t\"B.Local\";
+\t\"A.At\" -> \"B.Bt\" [color=red];
+\t\"B.Bt\" -> \"A.At\" [color=red];
+\t\"B.Local\" -> \"B.Bt\";
+}
+"
+    );
+}
+
+#[test]
+fn unresolved_modules_are_dashed() {
+    let dot = graph(GraphLevel::Modules).to_dot();
+    for line in [
+        "\t\"C\" [style=dashed, tooltip=\"not found\"];",
+        "\t\"A\" -> \"C\" [tooltip=\"Missing\", style=dashed, label=\"Missing\"];",
+        "\t\"A\" -> \"B\" [tooltip=\"Bt\", color=red];",
+    ] {
+        assert!(dot.contains(line), "{line}\n{dot}");
+    }
+}
+
+#[test]
+fn json_names_the_nodes() {
+    let json = graph(GraphLevel::Modules).to_json();
+    for text in [
+        "\"level\": \"modules\"",
+        "\"from\": \"A\",\n      \"to\": \"C\"",
+        "\"unresolved\": [\n        \"Missing\"\n      ]",
+        "\"cycles\": [\n    [\n      \"A\",\n      \"B\"\n    ]\n  ]",
+    ] {
+        assert!(json.contains(text), "{text}\n{json}");
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use asn1::{AsnCompiler, BigInt};
+use common::codes;
+
+const BASE: &str = "Base DEFINITIONS ::= BEGIN
+    base INTEGER ::= 5
+END";
+
+const USER: &str = "User DEFINITIONS ::= BEGIN
+    IMPORTS base FROM Base;
+    user INTEGER ::= base
+END";
+
+#[test]
+fn update_reanalyses_dependents() {
+    let mut compiler = AsnCompiler::new();
+    let base = compiler.add_file("base.asn1".into(), BASE.into()).unwrap();
+    compiler.add_file("user.asn1".into(), USER.into()).unwrap();
+
+    let analysis = compiler.analysis();
+    assert!(analysis.diagnostics.is_empty());
+    assert_eq!(
+        analysis.integer_value("User", "user"),
+        Some(&BigInt::from(5))
+    );
+
+    compiler.update_file(base, BASE.replace("5", "7")).unwrap();
+    let analysis = compiler.analysis();
+    assert!(analysis.diagnostics.is_empty());
+    assert_eq!(
+        analysis.integer_value("User", "user"),
+        Some(&BigInt::from(7))
+    );
+}
+
+#[test]
+fn parse_errors_are_reported_until_fixed() {
+    let mut compiler = AsnCompiler::new();
+    let base = compiler.add_file("base.asn1".into(), BASE.into()).unwrap();
+    compiler.add_file("user.asn1".into(), USER.into()).unwrap();
+    assert!(codes(&mut compiler).is_empty());
+
+    assert!(compiler
+        .update_file(base, BASE.replace("::= 5", "::="))
+        .is_err());
+
+    // the parse error is reported by every analysis, not only the first
+    for _ in 0..2 {
+        let codes = codes(&mut compiler);
+        assert!(
+            codes.contains(&"Asn::Parser::Type".to_string()),
+            "{codes:?}"
+        );
+        assert!(codes.contains(&"Asn1::Analysis::UnknownModule".to_string()));
+    }
+
+    compiler.update_file(base, BASE.into()).unwrap();
+    assert!(codes(&mut compiler).is_empty());
+}
+
+#[test]
+fn removed_files() {
+    let mut compiler = AsnCompiler::new();
+    let base = compiler.add_file("base.asn1".into(), BASE.into()).unwrap();
+    compiler.add_file("user.asn1".into(), USER.into()).unwrap();
+
+    compiler.remove_file(base);
+    compiler.remove_file(base);
+    assert_eq!(compiler.source_text(base), None);
+    assert_eq!(compiler.source_name(base), None);
+    assert!(compiler.tree(base).is_none());
+    assert!(compiler.update_file(base, BASE.into()).is_err());
+
+    let analysis = compiler.analysis();
+    assert_eq!(analysis.integer_value("Base", "base"), None);
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert_eq!(codes, ["Asn1::Analysis::UnknownModule"]);
+
+    // diagnostics referring to removed files are rendered without their location
+    let diagnostics = analysis.diagnostics;
+    compiler.remove_file(diagnostics[0].labels[0].source.unwrap());
+    assert!(compiler
+        .render_diagnostic(&diagnostics[0])
+        .contains("= note: Module imported here"));
+}
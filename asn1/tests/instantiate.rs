@@ -0,0 +1,117 @@
+use asn1::AsnCompiler;
+
+const SOURCE: &str = "Params DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Pair { A, B } ::= SEQUENCE { first A, second B }
+    Bounded { INTEGER: max } ::= INTEGER (0..max)
+    Numbers ::= Pair { INTEGER, BOOLEAN }
+    Again ::= Pair { INTEGER, BOOLEAN }
+    Small ::= Bounded { 10 }
+    Nested ::= Pair { Bounded { 5 }, Pair { NULL, INTEGER } }
+END";
+
+fn flatten(roots: &[&str]) -> String {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("params.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    let roots: Vec<_> = roots.iter().map(|root| root.to_string()).collect();
+    analysis.flatten("Flat", &roots).unwrap()
+}
+
+/// Analyse a single module and get the code and name of every diagnostic
+fn diagnostics(source: &str) -> Vec<(String, String)> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("test.asn1".into(), source.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    analysis
+        .diagnostics
+        .iter()
+        .map(|d| (d.error_code.clone(), d.name.clone()))
+        .collect()
+}
+
+#[test]
+fn parameters_are_substituted() {
+    let flat = flatten(&["Numbers", "Small"]);
+    assert!(
+        flat.contains(
+            "Pair-INTEGER-BOOLEAN ::= SEQUENCE {
+    first  [0] IMPLICIT INTEGER,
+    second [1] IMPLICIT BOOLEAN
+}"
+        ),
+        "{flat}"
+    );
+    assert!(flat.contains("Bounded-10 ::= INTEGER (0..10)"), "{flat}");
+    assert!(flat.contains("Small ::= Bounded-10"), "{flat}");
+}
+
+#[test]
+fn instances_are_shared() {
+    let flat = flatten(&["Numbers", "Again"]);
+    assert!(flat.contains("Numbers ::= Pair-INTEGER-BOOLEAN"), "{flat}");
+    assert!(flat.contains("Again ::= Pair-INTEGER-BOOLEAN"), "{flat}");
+    assert_eq!(
+        flat.matches("Pair-INTEGER-BOOLEAN ::=").count(),
+        1,
+        "{flat}"
+    );
+}
+
+#[test]
+fn nested_instances_are_created() {
+    let flat = flatten(&["Nested"]);
+    for line in [
+        "Nested ::= Pair-Bounded-5-Pair-NULL-INTEGER",
+        "    first  [0] IMPLICIT Bounded-5,",
+        "    second [1] IMPLICIT Pair-NULL-INTEGER",
+        "Bounded-5 ::= INTEGER (0..5)",
+        "Pair-NULL-INTEGER ::= SEQUENCE {",
+    ] {
+        assert!(flat.contains(line), "{line}\n{flat}");
+    }
+}
+
+#[test]
+fn invalid_parameters_are_reported() {
+    let diagnostics = diagnostics(
+        "Bad DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Pair { A, B } ::= SEQUENCE { first A, second B }
+    Bounded { INTEGER: max } ::= INTEGER (0..max)
+    Plain ::= INTEGER
+    Bare ::= Pair
+    Few ::= Pair { INTEGER }
+    Kind ::= Bounded { INTEGER }
+    NotParameterized ::= Plain { INTEGER }
+END",
+    );
+    let names: Vec<_> = diagnostics.iter().map(|(_, name)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "`Pair` is parameterized and must be given parameters",
+            "`Pair` expects 2 parameters, but 1 was given",
+            "Parameter `max` of `Bounded` is a value, but a type was given",
+            "`Plain` is not parameterized",
+        ]
+    );
+}
+
+#[test]
+fn recursive_instantiation_is_reported() {
+    let codes: Vec<_> = diagnostics(
+        "Recursive DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Deep { T } ::= SEQUENCE { next Deep { SEQUENCE OF T } OPTIONAL }
+    Use ::= Deep { NULL }
+END",
+    )
+    .into_iter()
+    .map(|(code, _)| code)
+    .collect();
+    assert_eq!(codes, ["Asn1::Analysis::RecursiveInstantiation"]);
+}
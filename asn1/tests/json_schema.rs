@@ -0,0 +1,108 @@
+use asn1::AsnCompiler;
+
+const SOURCE: &str = "Schema DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Byte ::= INTEGER (0..255)
+    Name ::= IA5String (SIZE (1..8))
+    Colour ::= ENUMERATED { red, green }
+    Point ::= SEQUENCE { x Byte, y Byte OPTIONAL, label Name DEFAULT \"a\" }
+    Shape ::= CHOICE { point Point, colours SEQUENCE (SIZE (1..4)) OF Colour }
+    Flag ::= BOOLEAN
+END";
+
+fn schema() -> String {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("schema.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    analysis.json_schema()
+}
+
+#[test]
+fn assignments_are_definitions() {
+    let schema = schema();
+    assert!(schema.starts_with(
+        "{
+  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",
+  \"$defs\": {
+    \"Schema.Byte\": {"
+    ));
+    for text in [
+        "\"Schema.Flag\": {
+      \"type\": \"boolean\"
+    }",
+        "\"Schema.Colour\": {
+      \"type\": \"string\",
+      \"enum\": [
+        \"red\",
+        \"green\"
+      ]
+    }",
+    ] {
+        assert!(schema.contains(text), "{text}\n{schema}");
+    }
+}
+
+#[test]
+fn constraints_are_bounds() {
+    let schema = schema();
+    for text in [
+        "\"Schema.Byte\": {
+      \"type\": \"integer\",
+      \"minimum\": 0,
+      \"maximum\": 255
+    }",
+        "\"Schema.Name\": {
+      \"type\": \"string\",
+      \"minLength\": 1,
+      \"maxLength\": 8
+    }",
+        "\"minItems\": 1,
+              \"maxItems\": 4",
+    ] {
+        assert!(schema.contains(text), "{text}\n{schema}");
+    }
+}
+
+#[test]
+fn sequences_are_objects() {
+    let schema = schema();
+    let text = "\"Schema.Point\": {
+      \"type\": \"object\",
+      \"properties\": {
+        \"x\": {
+          \"$ref\": \"#/$defs/Schema.Byte\"
+        },
+        \"y\": {
+          \"$ref\": \"#/$defs/Schema.Byte\"
+        },
+        \"label\": {
+          \"$ref\": \"#/$defs/Schema.Name\"
+        }
+      },
+      \"required\": [
+        \"x\"
+      ],
+      \"additionalProperties\": false
+    }";
+    assert!(schema.contains(text), "{schema}");
+}
+
+#[test]
+fn choices_are_alternatives() {
+    let schema = schema();
+    for text in [
+        "\"Schema.Shape\": {
+      \"oneOf\": [",
+        "\"required\": [
+            \"point\"
+          ],",
+        "\"items\": {
+                \"$ref\": \"#/$defs/Schema.Colour\"
+              },",
+    ] {
+        assert!(schema.contains(text), "{text}\n{schema}");
+    }
+}
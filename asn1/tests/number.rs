@@ -0,0 +1,204 @@
+use asn1::{AsnCompiler, BigInt, CodegenOptions, CodegenTarget, IntegerType, Real, RealBase};
+
+const SOURCE: &str = "Numbers DEFINITIONS ::= BEGIN
+    OPERATION ::= CLASS { &id INTEGER UNIQUE, &Result }
+    op1 OPERATION ::= { &id 7, &Result BOOLEAN }
+    v INTEGER ::= op1.&id
+    w INTEGER ::= v
+    below INTEGER ::= -9223372036854775809
+
+    Byte ::= INTEGER (0..255)
+    Huge ::= INTEGER (0..18446744073709551615)
+    Wide ::= INTEGER (-1..18446744073709551615)
+    Open ::= INTEGER (0..255, ...)
+    Plain ::= INTEGER
+    Pair ::= SEQUENCE { small INTEGER (1..3), large INTEGER (1..MAX) }
+    Param { INTEGER: max } ::= INTEGER (0..max)
+    Limited ::= Param { 18446744073709551615 }
+END";
+
+/// Generate rust code for the test module with the given integer type
+fn rust(integers: IntegerType) -> String {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("numbers.asn1".into(), SOURCE.into())
+        .unwrap();
+    let options = CodegenOptions {
+        integers,
+        ..CodegenOptions::default()
+    };
+    let files = compiler
+        .analysis()
+        .codegen(CodegenTarget::Rust, &options)
+        .unwrap();
+    files.into_iter().map(|file| file.content).collect()
+}
+
+#[test]
+fn information_object_fields_are_constants() {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("numbers.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    assert_eq!(
+        analysis.integer_value("Numbers", "v"),
+        Some(&BigInt::from(7))
+    );
+    assert_eq!(
+        analysis.integer_value("Numbers", "w"),
+        Some(&BigInt::from(7))
+    );
+
+    let code = rust(IntegerType::I64);
+    assert!(code.contains("pub const V: i64 = 7;"), "{code}");
+}
+
+#[test]
+fn integer_types_fit_constraints() {
+    let code = rust(IntegerType::BigInt);
+    for line in [
+        "pub type Byte = i64;",
+        "pub type Huge = u64;",
+        "pub type Wide = num_bigint::BigInt;",
+        "pub type Open = num_bigint::BigInt;",
+        "pub type Plain = num_bigint::BigInt;",
+        "pub small: i64,",
+        "pub large: num_bigint::BigInt,",
+        "pub type Param18446744073709551615 = u64;",
+    ] {
+        assert!(code.contains(line), "{line}\n{code}");
+    }
+
+    // integers that do not fit are i64 unless chosen otherwise
+    let code = rust(IntegerType::I64);
+    assert!(code.contains("pub type Huge = u64;"), "{code}");
+    assert!(code.contains("pub type Wide = i64;"), "{code}");
+}
+
+#[test]
+fn constants_fit_integer_types() {
+    let code = rust(IntegerType::I64);
+    assert!(
+        code.contains("pub const BELOW: i128 = -9223372036854775809;"),
+        "{code}"
+    );
+    let code = rust(IntegerType::BigInt);
+    assert!(
+        code.contains("pub static BELOW: std::sync::LazyLock<num_bigint::BigInt> ="),
+        "{code}"
+    );
+
+    // values beyond i128 need big integers
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("reals.asn1".into(), REALS.into())
+        .unwrap();
+    let error = compiler
+        .analysis()
+        .codegen(CodegenTarget::Rust, &CodegenOptions::default())
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "integer values beyond 128 bits without big integers are not supported by the target language"
+    );
+}
+
+const REALS: &str = "Reals DEFINITIONS ::= BEGIN
+    half REAL ::= 0.5
+    thousand REAL ::= 1e3
+    eighth REAL ::= { mantissa 1, base 2, exponent -3 }
+    scaled REAL ::= { mantissa 1200, base 10, exponent count }
+    count INTEGER ::= -2
+    infinite REAL ::= PLUS-INFINITY
+    negative REAL ::= -0.0
+    same REAL ::= half
+    huge INTEGER ::= 340282366920938463463374607431768211456
+    larger INTEGER ::= huge
+END";
+
+/// Analyse a single module and get the code and name of every diagnostic
+fn diagnostics(source: &str) -> Vec<(String, String)> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("test.asn1".into(), source.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    analysis
+        .diagnostics
+        .iter()
+        .map(|d| (d.error_code.clone(), d.name.clone()))
+        .collect()
+}
+
+#[test]
+fn reals_are_exact() {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("reals.asn1".into(), REALS.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    let real = |name| analysis.real_value("Reals", name).unwrap().to_string();
+    assert_eq!(real("half"), "5e-1");
+    assert_eq!(real("thousand"), "1e3");
+    assert_eq!(real("eighth"), "{ mantissa 1, base 2, exponent -3 }");
+    assert_eq!(real("scaled"), "12");
+    assert_eq!(real("infinite"), "PLUS-INFINITY");
+    assert_eq!(real("negative"), "-0");
+    assert_eq!(real("same"), "5e-1");
+
+    let huge: BigInt = "340282366920938463463374607431768211456".parse().unwrap();
+    assert_eq!(analysis.integer_value("Reals", "larger"), Some(&huge));
+}
+
+#[test]
+fn reals_are_normalised() {
+    let ten =
+        |mantissa: i32, exponent: i32| Real::new(mantissa.into(), RealBase::Ten, exponent.into());
+    assert_eq!(Real::parse("1.50"), Some(ten(15, -1)));
+    assert_eq!(Real::parse("1500"), Some(ten(15, 2)));
+    assert_eq!(Real::parse("0.000"), Some(ten(0, 0)));
+    assert_eq!(Real::parse("-0"), Some(Real::MinusZero));
+    assert_eq!(Real::parse("1.5e\u{2011}3"), Some(ten(15, -4)));
+    assert_eq!(Real::parse(".5"), None);
+    assert_eq!(Real::parse("1e"), None);
+
+    assert_eq!(ten(15, -1).to_f64(), 1.5);
+    assert_eq!(
+        Real::new((-3).into(), RealBase::Two, 2000.into()).to_f64(),
+        -3.0 * 2f64.powi(1000) * 2f64.powi(1000)
+    );
+    assert_eq!(
+        Real::new(1.into(), RealBase::Two, 5000.into()).to_f64(),
+        f64::INFINITY
+    );
+}
+
+#[test]
+fn invalid_numbers_are_reported() {
+    let diagnostics = diagnostics(
+        "Invalid DEFINITIONS ::= BEGIN
+    base REAL ::= { mantissa 1, base 3, exponent 0 }
+    named REAL ::= { base 2, mantissa 1, exponent 0 }
+    real REAL ::= 1.5
+    integer INTEGER ::= real
+END",
+    );
+    let codes: Vec<_> = diagnostics.iter().map(|(code, _)| code.as_str()).collect();
+    assert_eq!(
+        codes,
+        [
+            "Asn1::Analysis::RealValue",
+            "Asn1::Analysis::NumberValue",
+            "Asn1::Analysis::RealValue",
+        ],
+        "{diagnostics:?}"
+    );
+    assert_eq!(diagnostics[0].1, "Invalid REAL base 3");
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use asn1::{AsnCompiler, Iri, IriParseError, Oid};
+
+const BASE: &str = r#"Base { iso(1) standard(0) 8571 } "/ISO/Standard/8571" DEFINITIONS ::= BEGIN
+    base OBJECT IDENTIFIER ::= { iso standard 8571 1 }
+    child OBJECT IDENTIFIER ::= { base 2 }
+    example OID-IRI ::= "/Example/7"
+END"#;
+
+const USER: &str = "User DEFINITIONS ::= BEGIN
+    IMPORTS base FROM Base;
+    user OBJECT IDENTIFIER ::= { base 3 }
+    named OBJECT IDENTIFIER ::= { joint-iso-itu-t(2) 999 }
+END";
+
+/// Analyse modules, returning their diagnostics and object identifiers
+fn analyse(sources: &[&str]) -> (Vec<String>, Vec<(String, Oid)>) {
+    let mut compiler = common::compile(sources);
+    let codes = common::codes(&mut compiler);
+    let oids = compiler
+        .analysis()
+        .oid_registry()
+        .iter()
+        .flat_map(|(oid, entries)| {
+            entries.iter().map(|entry| {
+                let name = entry.name.as_deref().unwrap_or("");
+                (format!("{}.{name}", entry.module), oid.clone())
+            })
+        })
+        .collect();
+    (codes, oids)
+}
+
+#[test]
+fn references_are_followed_across_modules() {
+    let (codes, oids) = analyse(&[BASE, USER]);
+    assert!(codes.is_empty(), "{codes:?}");
+    assert_eq!(
+        oids,
+        [
+            ("Base.".into(), Oid::new([1, 0, 8571])),
+            ("Base.base".into(), Oid::new([1, 0, 8571, 1])),
+            ("Base.child".into(), Oid::new([1, 0, 8571, 1, 2])),
+            ("User.user".into(), Oid::new([1, 0, 8571, 1, 3])),
+            ("User.named".into(), Oid::new([2, 999])),
+            ("Base.example".into(), Oid::new([2, 999, 7])),
+        ]
+    );
+}
+
+#[test]
+fn registry_finds_descendants() {
+    let mut compiler = AsnCompiler::new();
+    compiler.add_file("base.asn1".into(), BASE.into()).unwrap();
+    compiler.add_file("user.asn1".into(), USER.into()).unwrap();
+    let registry = compiler.analysis().oid_registry();
+
+    let base = registry.find("Base", "base").unwrap();
+    assert_eq!(registry.module("Base"), Some(&Oid::new([1, 0, 8571])));
+    let names: Vec<_> = registry
+        .descendants(base)
+        .flat_map(|(_, entries)| entries.iter().map(|e| e.name.clone().unwrap()))
+        .collect();
+    assert_eq!(names, ["child", "user"]);
+    assert_eq!(base.to_asn1(), "{ iso(1) standard(0) 8571 1 }");
+}
+
+#[test]
+fn iris_are_parsed() {
+    let iri = Iri::parse("/Joint-ISO-ITU-T/Example/12", false).unwrap();
+    assert_eq!(iri.to_oid(), Some(Oid::new([2, 999, 12])));
+    let iri = Iri::parse("/ISO/Unknown", false).unwrap();
+    assert_eq!(iri.to_oid(), None);
+    let iri = Iri::parse("/ISO/Member-Body/840", false).unwrap();
+    assert_eq!(iri.to_oid(), Some(Oid::new([1, 2, 840])));
+    let iri = Iri::parse("/UUID/5", false).unwrap();
+    assert_eq!(iri.to_oid(), Some(Oid::new([2, 25, 5])));
+    assert_eq!(Iri::parse("a/b", true).unwrap().to_oid(), None);
+
+    assert_eq!(Iri::parse("ISO", false), Err(IriParseError::MissingPrefix));
+    assert_eq!(
+        Iri::parse("/ISO", true),
+        Err(IriParseError::UnexpectedPrefix)
+    );
+    assert_eq!(Iri::parse("/ISO//1", false), Err(IriParseError::EmptyLabel));
+    assert_eq!(
+        Iri::parse("/ISO/01", false),
+        Err(IriParseError::LeadingZero("01".into()))
+    );
+    assert_eq!(
+        Iri::parse("/ISO/a b", false),
+        Err(IriParseError::InvalidCharacter(' '))
+    );
+}
+
+#[test]
+fn invalid_values_are_reported() {
+    let (codes, _) = analyse(&["Bad DEFINITIONS ::= BEGIN
+    a OBJECT IDENTIFIER ::= { b 1 }
+    b OBJECT IDENTIFIER ::= { a 2 }
+    c OBJECT IDENTIFIER ::= { missing 1 }
+    d OBJECT IDENTIFIER ::= { iso(2) 1 }
+END"]);
+    assert_eq!(
+        codes,
+        [
+            "Asn1::Analysis::OidRecursive",
+            "Asn1::Analysis::OidRecursive",
+            "Asn1::Analysis::OidComponent",
+            "Asn1::Analysis::OidMismatch",
+        ]
+    );
+
+    let (codes, _) = analyse(&[r#"Header { iso 3 } "/ISO/4" DEFINITIONS ::= BEGIN
+    Empty ::= NULL
+END"#]);
+    assert_eq!(codes, ["Asn1::Analysis::IriMismatch"]);
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use asn1::{AsnCompiler, ProtoFile};
+
+const SOURCE: &str = "Proto DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    IMPORTS Name FROM Other;
+    Byte ::= INTEGER (0..255)
+    Flags ::= BIT STRING
+    Message ::= SEQUENCE {
+        id INTEGER,
+        ...,
+        flags Flags,
+        name Name,
+        labels SEQUENCE OF UTF8String,
+        nothing NULL
+    }
+    Status ::= ENUMERATED { ok(1), failed(5), ... }
+    Colour ::= ENUMERATED { red, green }
+    Shape ::= CHOICE { byte Byte, colours SEQUENCE OF Colour }
+    Counter ::= SEQUENCE {
+        total INTEGER (0..18446744073709551615),
+        offset INTEGER (-1..18446744073709551615)
+    }
+END
+
+Other DEFINITIONS ::= BEGIN
+    Name ::= UTF8String
+END";
+
+/// Generate the proto files of the test modules, keyed by file name
+fn files() -> HashMap<String, String> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("proto.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    analysis
+        .protobuf()
+        .into_iter()
+        .map(|ProtoFile { file, content }| (file, content))
+        .collect()
+}
+
+#[test]
+fn modules_are_packages() {
+    let files = files();
+    let mut names: Vec<_> = files.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["asn1.proto", "other.proto", "proto.proto"]);
+
+    let proto = &files["proto.proto"];
+    assert!(proto.starts_with(
+        "// Generated from the ASN.1 module `Proto`
+syntax = \"proto3\";
+
+package proto;
+
+import \"asn1.proto\";
+import \"google/protobuf/empty.proto\";
+import \"other.proto\";
+"
+    ));
+    assert!(files["asn1.proto"].contains("message BitString {"));
+}
+
+#[test]
+fn extension_additions_are_numbered_after_the_root() {
+    let proto = &files()["proto.proto"];
+    let message = "message Message {
+  int64 id = 1;
+  optional Flags flags = 2;
+  optional other.Name name = 3;
+  repeated string labels = 4;
+  optional google.protobuf.Empty nothing = 5;
+}";
+    assert!(proto.contains(message), "{proto}");
+}
+
+#[test]
+fn unsigned_ranges_are_uint64() {
+    let proto = &files()["proto.proto"];
+    let message = "message Counter {
+  uint64 total = 1;
+  int64 offset = 2;
+}";
+    assert!(proto.contains(message), "{proto}");
+}
+
+#[test]
+fn enumerations_are_numbered_by_value() {
+    let proto = &files()["proto.proto"];
+    for text in [
+        "enum Status {
+  STATUS_UNSPECIFIED = 0;
+  STATUS_OK = 1;
+  STATUS_FAILED = 5;
+}",
+        "enum Colour {
+  COLOUR_RED = 0;
+  COLOUR_GREEN = 1;
+}",
+    ] {
+        assert!(proto.contains(text), "{text}\n{proto}");
+    }
+}
+
+#[test]
+fn choices_are_oneofs() {
+    let proto = &files()["proto.proto"];
+    for text in [
+        "message Byte {
+  int64 value = 1;
+}",
+        "message Shape {
+  oneof choice {
+    Byte byte = 1;
+    Colours colours = 2;
+  }
+  message Colours {
+    repeated Colour value = 1;
+  }
+}",
+    ] {
+        assert!(proto.contains(text), "{text}\n{proto}");
+    }
+}
@@ -0,0 +1,191 @@
+use asn1::{AsnCompiler, BigInt, RandomOptions, RandomValue, RandomValues};
+
+const SOURCE: &str = r#"Random DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Param { INTEGER: max } ::= INTEGER (0..max)
+    Instance ::= Param { 5 }
+    Byte ::= INTEGER (0..255)
+    Code ::= PrintableString (SIZE (2..4)) (FROM ("A".."C"))
+    Colour ::= ENUMERATED { red, green, ..., blue }
+    Record ::= SEQUENCE {
+        id Byte,
+        code Code,
+        note UTF8String OPTIONAL,
+        ...,
+        colour Colour
+    }
+    Tree ::= SEQUENCE { value Byte, children SEQUENCE OF Tree }
+END"#;
+
+/// Generate values of the test module with the given seed and options
+fn generate<T>(
+    seed: u64,
+    options: RandomOptions,
+    f: impl FnOnce(&mut RandomValues<'_, '_>) -> T,
+) -> T {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("random.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let codes: Vec<_> = analysis.diagnostics.iter().map(|d| &d.error_code).collect();
+    assert!(codes.is_empty(), "{codes:?}");
+    f(&mut RandomValues::new(&analysis, seed, options))
+}
+
+/// The integer of an INTEGER value
+fn integer(value: Option<RandomValue>) -> BigInt {
+    match value {
+        Some(RandomValue::Integer(integer)) => integer,
+        value => panic!("{value:?}"),
+    }
+}
+
+#[test]
+fn types_include_instances() {
+    let types = generate(0, RandomOptions::default(), |random| random.types());
+    let names: Vec<_> = types.iter().map(|(_, name)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Byte",
+            "Code",
+            "Colour",
+            "Instance",
+            "Param { 5 }",
+            "Record",
+            "Tree"
+        ]
+    );
+    assert!(types.iter().all(|(module, _)| module == "Random"));
+}
+
+#[test]
+fn values_are_within_their_constraints() {
+    for seed in 0..50 {
+        generate(seed, RandomOptions::default(), |random| {
+            for name in ["Instance", "Param { 5 }"] {
+                let value = integer(random.value("Random", name));
+                assert!(value >= 0.into() && value <= 5.into(), "{name} {value}");
+            }
+            let value = integer(random.value("Random", "Byte"));
+            assert!(value >= 0.into() && value <= 255.into(), "{value}");
+
+            let Some(RandomValue::String(code)) = random.value("Random", "Code") else {
+                panic!("Code");
+            };
+            assert!((2..=4).contains(&code.len()), "{code}");
+            assert!(code.chars().all(|c| ('A'..='C').contains(&c)), "{code}");
+        });
+    }
+}
+
+#[test]
+fn seeds_give_the_same_values() {
+    let values = |seed| {
+        generate(seed, RandomOptions::default(), |random| {
+            ["Record", "Tree", "Instance"].map(|name| random.value("Random", name))
+        })
+    };
+    assert_eq!(values(7), values(7));
+    assert_ne!(values(7), values(8));
+}
+
+#[test]
+fn options_limit_values() {
+    let options = RandomOptions {
+        max_depth: 0,
+        extensions: false,
+        ..RandomOptions::default()
+    };
+    for seed in 0..20 {
+        let (record, tree) = generate(seed, options.clone(), |random| {
+            (
+                random.value("Random", "Record"),
+                random.value("Random", "Tree"),
+            )
+        });
+        let Some(RandomValue::Sequence(components)) = record else {
+            panic!("{record:?}");
+        };
+        let names: Vec<_> = components.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["id", "code"]);
+
+        let Some(RandomValue::Sequence(components)) = tree else {
+            panic!("{tree:?}");
+        };
+        assert_eq!(
+            components[1],
+            ("children".into(), RandomValue::SequenceOf(vec![]))
+        );
+    }
+
+    let options = RandomOptions {
+        extensions: false,
+        ..RandomOptions::default()
+    };
+    for seed in 0..20 {
+        let colour = generate(seed, options.clone(), |random| {
+            random.value("Random", "Colour")
+        });
+        let Some(RandomValue::Enumerated(name, _)) = colour else {
+            panic!("{colour:?}");
+        };
+        assert_ne!(name, "blue");
+    }
+}
+
+#[test]
+fn violations_break_one_constraint() {
+    let violations = generate(0, RandomOptions::default(), |random| {
+        random.violations("Random", "Instance")
+    });
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].path, "");
+    assert_eq!(
+        violations[0].message,
+        "`6` is greater than 5, the bound of its value constraint"
+    );
+    assert_eq!(violations[0].value, RandomValue::Integer(6.into()));
+
+    let violations = generate(0, RandomOptions::default(), |random| {
+        random.violations("Random", "Record")
+    });
+    let found: Vec<_> = violations
+        .iter()
+        .map(|violation| (violation.path.as_str(), violation.message.as_str()))
+        .collect();
+    for expected in [
+        (
+            "id",
+            "`256` is greater than 255, the bound of its value constraint",
+        ),
+        ("", "`id` is absent, but is not OPTIONAL"),
+        ("code", "The size 1 is not permitted by its size constraint"),
+        ("", "`code` is absent, but is not OPTIONAL"),
+    ] {
+        assert!(found.contains(&expected), "{expected:?}\n{found:?}");
+    }
+}
+
+#[test]
+fn values_are_written_in_value_notation() {
+    let value = RandomValue::Sequence(vec![
+        ("id".into(), RandomValue::Integer(3.into())),
+        ("code".into(), RandomValue::String("AB".into())),
+        (
+            "choice".into(),
+            RandomValue::Choice("flag".into(), Box::new(RandomValue::Boolean(true))),
+        ),
+        (
+            "list".into(),
+            RandomValue::SequenceOf(vec![RandomValue::Null, RandomValue::Null]),
+        ),
+        ("bits".into(), RandomValue::BitString(vec![true, false])),
+        ("octets".into(), RandomValue::OctetString(vec![0xab])),
+        ("oid".into(), RandomValue::ObjectIdentifier(vec![1, 2])),
+    ]);
+    assert_eq!(
+        value.to_string(),
+        "{ id 3, code \"AB\", choice flag : TRUE, list { NULL, NULL }, bits '10'B, octets 'AB'H, oid { 1 2 } }"
+    );
+}
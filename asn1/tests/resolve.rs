@@ -0,0 +1,152 @@
+mod common;
+
+use asn1::AsnCompiler;
+use common::{codes, compile};
+
+const BASE: &str = "Base DEFINITIONS ::= BEGIN
+    Colour ::= ENUMERATED { red, green }
+    limit INTEGER ::= 10
+END";
+
+const USER: &str = "User DEFINITIONS ::= BEGIN
+    IMPORTS Colour, limit FROM Base;
+    Paint ::= SEQUENCE { colour Colour, size INTEGER (0..limit) }
+    Other ::= Base.Colour
+    Local ::= Paint
+END";
+
+#[test]
+fn references_link_to_definitions() {
+    let mut compiler = AsnCompiler::new();
+    compiler.add_file("base.asn1".into(), BASE.into()).unwrap();
+    let user = compiler.add_file("user.asn1".into(), USER.into()).unwrap();
+    let tree = compiler.tree(user).unwrap().clone();
+    let analysis = compiler.analysis();
+    assert!(analysis.diagnostics.is_empty());
+
+    let definition = |text: &str| {
+        let offset = USER.find(text).unwrap() + text.len() - 1;
+        let node = tree.node_at(offset).unwrap();
+        analysis
+            .definition(node)
+            .map(|d| (d.module.clone(), d.name.clone()))
+    };
+    let base = |name: &str| Some(("Base".to_string(), name.to_string()));
+    assert_eq!(definition("colour Colour"), base("Colour"));
+    assert_eq!(definition("0..limit"), base("limit"));
+    assert_eq!(definition("Base.Colour"), base("Colour"));
+    assert_eq!(
+        definition("Local ::= Paint"),
+        Some(("User".to_string(), "Paint".to_string()))
+    );
+    assert_eq!(definition("SEQUENCE"), None::<(String, String)>);
+}
+
+#[test]
+fn types_without_finite_values_are_reported() {
+    let codes = codes(&mut compile(&["Types DEFINITIONS ::= BEGIN
+    Loop ::= SEQUENCE { next Loop }
+    Choice ::= CHOICE { again Choice }
+    Optional ::= SEQUENCE { next Optional OPTIONAL }
+    List ::= SEQUENCE OF List
+    Alternatives ::= CHOICE { stop NULL, again Alternatives }
+    Pairs ::= SEQUENCE SIZE (1..2) OF Pairs
+    Empty ::= SET SIZE (0..2) OF Empty
+    Extensible ::= SEQUENCE SIZE (1..2, ...) OF Extensible
+END"]));
+    assert_eq!(
+        codes,
+        [
+            "Asn1::Analysis::InfiniteType",
+            "Asn1::Analysis::InfiniteType",
+            "Asn1::Analysis::InfiniteType"
+        ]
+    );
+}
+
+#[test]
+fn recursive_values_are_reported() {
+    let codes = codes(&mut compile(&["Values DEFINITIONS ::= BEGIN
+    a INTEGER ::= b
+    b INTEGER ::= a
+    c INTEGER ::= c
+    d INTEGER ::= a
+END"]));
+    assert_eq!(
+        codes,
+        [
+            "Asn1::Analysis::RecursiveValue",
+            "Asn1::Analysis::RecursiveValue",
+            "Asn1::Analysis::RecursiveValue",
+        ]
+    );
+}
+
+#[test]
+fn unresolved_references_are_reported() {
+    let codes = codes(&mut compile(&[
+        BASE,
+        "User DEFINITIONS ::= BEGIN
+    IMPORTS Shade FROM Base Thing FROM Nowhere;
+    Paint ::= Missing
+    other INTEGER ::= missing
+END",
+    ]));
+    for code in [
+        "Asn1::Analysis::ImportedSymbol",
+        "Asn1::Analysis::UnknownModule",
+        "Asn1::Analysis::UnresolvedReference",
+    ] {
+        assert!(codes.contains(&code.to_string()), "{code} {codes:?}");
+    }
+}
+
+#[test]
+fn references_are_not_suggested_to_themselves() {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file(
+            "types.asn1".into(),
+            "Types DEFINITIONS ::= BEGIN
+    Nodes ::= SEQUENCE { next Node }
+    Paint ::= SEQUENCE { colour Colur }
+    Colour ::= ENUMERATED { red, green }
+END"
+            .into(),
+        )
+        .unwrap();
+    let analysis = compiler.analysis();
+
+    let suggestions: Vec<_> = analysis
+        .diagnostics
+        .iter()
+        .flat_map(|d| &d.labels)
+        .map(|label| label.message.as_str())
+        .filter(|message| message.starts_with("Did you mean"))
+        .collect();
+    assert_eq!(suggestions, ["Did you mean `Colour`?"]);
+}
+
+#[test]
+fn constraint_references_are_resolved() {
+    let codes = codes(&mut compile(&[
+        "Constraints DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Level ::= INTEGER { low(0), high(10) } (low..high | limit)
+    Colour ::= ENUMERATED { red, green }
+    Pair ::= SEQUENCE { colour Colour, size INTEGER }
+    Red ::= Pair (WITH COMPONENTS { colour (red), size (0..limit) })
+    List ::= SEQUENCE (SIZE (1..limit)) OF Level
+    Bounded { INTEGER:max } ::= INTEGER (0..max)
+    Unknown ::= INTEGER (0..nope)
+    Contained ::= OCTET STRING (CONTAINING Missing)
+    limit INTEGER ::= 20
+END",
+    ]));
+    assert_eq!(
+        codes,
+        [
+            "Asn1::Analysis::UnresolvedReference",
+            "Asn1::Analysis::UnresolvedReference",
+        ]
+    );
+}
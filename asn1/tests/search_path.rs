@@ -0,0 +1,117 @@
+use std::{fs, path::PathBuf};
+
+use asn1::{AsnCompiler, BigInt};
+
+const ROOT: &str = "Root DEFINITIONS ::= BEGIN
+    IMPORTS common FROM Common { iso 3 };
+    root INTEGER ::= common
+END";
+
+const COMMON: &str = "Common { iso 3 } DEFINITIONS ::= BEGIN
+    common INTEGER ::= 3
+END";
+
+/// Create an empty directory for the include files of a test
+fn include_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asn1-search-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn codes(compiler: &mut AsnCompiler) -> Vec<String> {
+    compiler
+        .analysis()
+        .diagnostics
+        .into_iter()
+        .map(|d| d.error_code)
+        .collect()
+}
+
+#[test]
+fn discovers_imported_modules() {
+    let dir = include_dir("discover");
+    fs::write(dir.join("common.asn1"), COMMON).unwrap();
+    fs::write(dir.join("unrelated.asn"), "Other DEFINITIONS ::= BEGIN END").unwrap();
+    fs::write(dir.join("notes.txt"), "not a module").unwrap();
+
+    let mut compiler = AsnCompiler::new();
+    compiler.add_include_dir(&dir);
+    compiler.add_file("root.asn1".into(), ROOT.into()).unwrap();
+
+    let analysis = compiler.analysis();
+    assert!(analysis.diagnostics.is_empty());
+    assert_eq!(
+        analysis.integer_value("Root", "root"),
+        Some(&BigInt::from(3))
+    );
+    assert_eq!(compiler.sources().count(), 2);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn prefers_object_identifier_over_name() {
+    let dir = include_dir("oid");
+    fs::write(
+        dir.join("a.asn1"),
+        "Common { iso 4 } DEFINITIONS ::= BEGIN common INTEGER ::= 4 END",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("b.asn1"),
+        "Renamed { iso 3 } DEFINITIONS ::= BEGIN common INTEGER ::= 3 END",
+    )
+    .unwrap();
+
+    let mut compiler = AsnCompiler::new();
+    compiler.add_include_dir(&dir);
+    compiler.add_file("root.asn1".into(), ROOT.into()).unwrap();
+    compiler.analysis();
+
+    let names: Vec<_> = compiler
+        .sources()
+        .filter_map(|file| compiler.source_name(file))
+        .collect();
+    assert_eq!(names.len(), 2);
+    assert!(names[1].ends_with("b.asn1"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn update_searches_again() {
+    let dir = include_dir("update");
+
+    let mut compiler = AsnCompiler::new();
+    compiler.add_include_dir(&dir);
+    let root = compiler.add_file("root.asn1".into(), ROOT.into()).unwrap();
+    assert_eq!(codes(&mut compiler), ["Asn1::Analysis::UnknownModule"]);
+
+    // the module is written after the directory was first searched
+    fs::write(dir.join("common.asn1"), COMMON).unwrap();
+    compiler
+        .update_file(root, ROOT.replace("root INTEGER", "root  INTEGER"))
+        .unwrap();
+    assert!(codes(&mut compiler).is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn removed_files_are_loaded_again() {
+    let dir = include_dir("remove");
+    fs::write(dir.join("common.asn1"), COMMON).unwrap();
+
+    let mut compiler = AsnCompiler::new();
+    compiler.add_include_dir(&dir);
+    let root = compiler.add_file("root.asn1".into(), ROOT.into()).unwrap();
+    assert!(codes(&mut compiler).is_empty());
+
+    let loaded = compiler.sources().find(|&file| file != root).unwrap();
+    compiler.remove_file(loaded);
+    assert!(codes(&mut compiler).is_empty());
+    assert_eq!(compiler.sources().count(), 2);
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,133 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use asn1::{AsnCompiler, CodegenOptions, CodegenTarget};
+
+const SOURCE: &str = "Values DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    Colour ::= ENUMERATED { light-red, dark-green }
+    Message ::= SEQUENCE {
+        message-id INTEGER,
+        colour Colour,
+        ratio REAL,
+        flags BIT STRING,
+        data OCTET STRING,
+        name UTF8String OPTIONAL,
+        items SEQUENCE OF INTEGER
+    }
+    Shape ::= CHOICE { nothing NULL, size INTEGER, message Message }
+END";
+
+const MANIFEST: &str = r#"[package]
+name = "serde-vectors"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+
+[workspace]
+"#;
+
+/// Writes each value as JSON, checking that it reads back as the same value
+const MAIN: &str = r##"mod asn1;
+
+use asn1::values::{Colour, Message, Shape};
+
+fn check<T>(value: T, expected: &str)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, expected);
+    assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+}
+
+fn main() {
+    let message = Message {
+        message_id: 9007199254740993,
+        colour: Colour::DarkGreen,
+        ratio: f64::INFINITY,
+        flags: vec![true, false, true, false, true],
+        data: vec![0xAB, 0x01],
+        name: None,
+        items: vec![1, -2],
+    };
+    check(Colour::LightRed, r#""light-red""#);
+    check(
+        message.clone(),
+        r#"{"message-id":"9007199254740993","colour":"dark-green","ratio":"INF","flags":{"value":"A8","length":5},"data":"AB01","name":null,"items":[1,-2]}"#,
+    );
+    check(Shape::Nothing(()), r#"{"nothing":null}"#);
+    check(Shape::Size(5), r#"{"size":5}"#);
+    let named = Message {
+        name: Some("a".into()),
+        ..message
+    };
+    check(
+        Shape::Message(named),
+        r#"{"message":{"message-id":"9007199254740993","colour":"dark-green","ratio":"INF","flags":{"value":"A8","length":5},"data":"AB01","name":"a","items":[1,-2]}}"#,
+    );
+
+    let absent: Message = serde_json::from_str(
+        r#"{"message-id":1,"colour":"light-red","ratio":0.5,"flags":{"value":"","length":0},"data":"","items":[]}"#,
+    )
+    .unwrap();
+    assert_eq!(absent.name, None);
+}
+"##;
+
+/// Create an empty directory for the generated crate of a test
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asn1-serde-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    dir
+}
+
+#[test]
+fn values_round_trip_through_json() {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("values.asn1".into(), SOURCE.into())
+        .unwrap();
+    let options = CodegenOptions {
+        serde: true,
+        ..CodegenOptions::default()
+    };
+    let files = compiler
+        .analysis()
+        .codegen(CodegenTarget::Rust, &options)
+        .unwrap();
+
+    let dir = output_dir("json");
+    for file in files {
+        fs::write(dir.join("src").join(file.file), file.content).unwrap();
+    }
+    fs::write(dir.join("Cargo.toml"), MANIFEST).unwrap();
+    fs::write(dir.join("src").join("main.rs"), MAIN).unwrap();
+
+    // the values can only be checked where serde can be fetched
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let fetched = Command::new(&cargo)
+        .arg("fetch")
+        .current_dir(&dir)
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !fetched {
+        eprintln!("serde cannot be fetched, skipping");
+        fs::remove_dir_all(dir).unwrap();
+        return;
+    }
+
+    let output = Command::new(&cargo)
+        .args(["run", "--quiet"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
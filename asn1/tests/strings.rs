@@ -0,0 +1,131 @@
+use asn1::{AsnCompiler, RestrictedString, StringError};
+
+const SOURCE: &str = r#"Strings DEFINITIONS ::= BEGIN
+    digits NumericString ::= "123 456"
+    name PrintableString ::= "Smith, J."
+    plane BMPString ::= { 0, 0, 3, 169 }
+    table IA5String ::= { 4, 1 }
+    joined UTF8String ::= { "a", { 0, 1, 244, 0 }, name }
+    nested VisibleString ::= { table, "!" }
+
+    letters NumericString ::= "12a"
+    emoji BMPString ::= { 0, 1, 244, 0 }
+    column IA5String ::= { 8, 1 }
+    number PrintableString ::= 5
+    surrogate UniversalString ::= { 0, 0, 216, 0 }
+END"#;
+
+/// Get the error codes of the diagnostics of a module, with the values of
+/// the source their labels point at
+fn diagnostics(source: &str) -> Vec<(String, String)> {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("strings.asn1".into(), source.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    analysis
+        .diagnostics
+        .iter()
+        .map(|d| {
+            let label = d.labels.first().and_then(|l| l.location.clone());
+            let label = label.map(|location| source[location].to_string());
+            (d.error_code.clone(), label.unwrap_or_default())
+        })
+        .collect()
+}
+
+#[test]
+fn values_are_interpreted() {
+    let mut compiler = AsnCompiler::new();
+    compiler
+        .add_file("strings.asn1".into(), SOURCE.into())
+        .unwrap();
+    let analysis = compiler.analysis();
+    let value = |name| analysis.string_value("Strings", name);
+    assert_eq!(value("digits"), Some("123 456"));
+    assert_eq!(value("name"), Some("Smith, J."));
+    assert_eq!(value("plane"), Some("Ω"));
+    assert_eq!(value("table"), Some("A"));
+    assert_eq!(value("joined"), Some("a🐀Smith, J."));
+    assert_eq!(value("nested"), Some("A!"));
+    assert_eq!(value("letters"), None);
+}
+
+#[test]
+fn invalid_values_are_reported() {
+    let found: Vec<_> = diagnostics(SOURCE)
+        .into_iter()
+        .map(|(code, label)| (code, label.split_whitespace().collect::<String>()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("Asn1::Analysis::StringValue".into(), "{8,1}".into()),
+            ("Asn1::Analysis::StringValue".into(), "{0,1,244,0}".into()),
+            ("Asn1::Analysis::StringValue".into(), "\"12a\"".into()),
+            ("Asn1::Analysis::StringValue".into(), "5".into()),
+            ("Asn1::Analysis::StringValue".into(), "{0,0,216,0}".into()),
+        ]
+    );
+}
+
+#[test]
+fn strings_are_checked_against_the_character_set() {
+    assert_eq!(RestrictedString::Numeric.check("0 9"), Ok(()));
+    assert_eq!(
+        RestrictedString::Printable.check("a*b"),
+        Err(StringError::Character {
+            ty: RestrictedString::Printable,
+            character: '*',
+            offset: 1
+        })
+    );
+    assert!(RestrictedString::Visible.check("tab\t").is_err());
+    assert!(RestrictedString::IA5.check("tab\t").is_ok());
+    assert!(RestrictedString::Bmp.check("é€").is_ok());
+    assert!(RestrictedString::Bmp.check("🐀").is_err());
+    assert!(RestrictedString::Graphic.check("é").is_ok());
+    assert!(RestrictedString::Graphic.check("\u{85}").is_err());
+    assert!(RestrictedString::Teletex.check("{").is_err());
+
+    let error = RestrictedString::Numeric.check("12a").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`a` (U+0061) is not in the character set of NumericString"
+    );
+}
+
+#[test]
+fn strings_are_transcoded() {
+    for (ty, text, octets) in [
+        (RestrictedString::IA5, "Ab", &b"Ab"[..]),
+        (RestrictedString::Utf8, "é", b"\xc3\xa9"),
+        (RestrictedString::Bmp, "A€", b"\x00A\x20\xac"),
+        (RestrictedString::Universal, "🐀", b"\x00\x01\xf4\x00"),
+        (RestrictedString::Teletex, "é£", b"\xc2e\xa3"),
+        (RestrictedString::General, "aé", b"a\x1b\x2d\x41\xe9"),
+    ] {
+        assert_eq!(ty.encode(text).unwrap(), octets, "{ty}");
+        assert_eq!(ty.decode(octets).unwrap(), text, "{ty}");
+    }
+    assert!(RestrictedString::Bmp.encode("🐀").is_err());
+}
+
+#[test]
+fn invalid_octets_are_reported() {
+    for (ty, octets, offset) in [
+        (RestrictedString::IA5, &b"a\x80"[..], 1),
+        (RestrictedString::Numeric, b"1a", 1),
+        (RestrictedString::Utf8, b"a\xff", 1),
+        (RestrictedString::Bmp, b"\x00A\x00", 2),
+        (RestrictedString::Universal, b"\x00\x00\xd8\x00", 0),
+        (RestrictedString::Teletex, b"\xc2", 0),
+        (RestrictedString::General, b"\xe9", 0),
+    ] {
+        assert_eq!(
+            ty.decode(octets),
+            Err(StringError::Octets { ty, offset }),
+            "{ty}"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use asn1::{Date, Duration, GeneralizedTime, Time, TimeOfDay, UtcTime};
+
+/// Get the error codes of the diagnostics of a module, with the values of
+/// the source their labels point at
+fn diagnostics(source: &str) -> Vec<(String, String)> {
+    common::compile(&[source])
+        .analysis()
+        .diagnostics
+        .iter()
+        .map(|d| {
+            let label = d.labels.first().and_then(|l| l.location.clone());
+            let label = label.map(|location| source[location].to_string());
+            (d.error_code.clone(), label.unwrap_or_default())
+        })
+        .collect()
+}
+
+#[test]
+fn settings_constrain_values() {
+    let found = diagnostics(
+        r#"Times DEFINITIONS ::= BEGIN
+    Day ::= TIME (SETTINGS "Basic=Date Date=YMD Year=Basic")
+    Either ::= TIME (SETTINGS "Basic=Date" | SETTINGS "Basic=Time")
+    Both ::= Day (SETTINGS "Basic=Date")
+    good Day ::= "2020-01-02"
+    wrong Day ::= "12:00:00"
+    either Either ::= "12:00"
+    week Both ::= "2020-W01"
+END"#,
+    );
+    assert_eq!(
+        found,
+        [
+            ("Asn1::Analysis::TimeValue".into(), "\"2020-W01\"".into()),
+            ("Asn1::Analysis::TimeValue".into(), "\"12:00:00\"".into()),
+        ]
+    );
+}
+
+#[test]
+fn invalid_settings_are_reported() {
+    let found = diagnostics(
+        r#"Times DEFINITIONS ::= BEGIN
+    Bad ::= TIME (SETTINGS "Basic=Week")
+    Unnamed ::= TIME (SETTINGS "Date")
+END"#,
+    );
+    assert_eq!(
+        found,
+        [
+            (
+                "Asn1::Analysis::TimeSettings".into(),
+                "\"Basic=Week\"".into()
+            ),
+            ("Asn1::Analysis::TimeSettings".into(), "\"Date\"".into()),
+        ]
+    );
+}
+
+#[test]
+fn values_are_checked_against_their_types() {
+    let found = diagnostics(
+        r#"Times DEFINITIONS ::= BEGIN
+    day DATE ::= "2021-02-29"
+    leap DATE ::= "2020-02-29"
+    utc UTCTime ::= "4912312359Z"
+    second GeneralizedTime ::= "20201231235960Z"
+    fraction GeneralizedTime ::= "20201231120000.5Z"
+    midnight TIME-OF-DAY ::= "24:00:00"
+    empty DURATION ::= "P"
+END"#,
+    );
+    assert_eq!(
+        found,
+        [
+            ("Asn1::Analysis::TimeValue".into(), "\"2021-02-29\"".into()),
+            ("Asn1::Analysis::TimeValue".into(), "\"P\"".into()),
+            (
+                "Asn1::Analysis::TimeValue".into(),
+                "\"20201231235960Z\"".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn times_are_converted_to_utc() {
+    let utc: UtcTime = "991231235959-0800".parse().unwrap();
+    assert_eq!(utc.canonical().unwrap().to_string(), "000101075959Z");
+    assert_eq!(utc.to_der().unwrap(), b"\x17\x0D000101075959Z");
+
+    // the year is out of range once converted to UTC
+    let early: UtcTime = "500101000000+0130".parse().unwrap();
+    assert!(early.canonical().is_err());
+
+    let general: GeneralizedTime = "20201231235959,500+0100".parse().unwrap();
+    assert_eq!(general.to_string(), "20201231235959.500+0100");
+    assert_eq!(
+        general.canonical().unwrap().to_string(),
+        "20201231225959.5Z"
+    );
+    let local: GeneralizedTime = "2020123123.5".parse().unwrap();
+    assert!(local.canonical().is_err());
+}
+
+#[test]
+fn time_values_have_property_settings() {
+    let settings = |text: &str| text.parse::<Time>().unwrap().settings().to_string();
+    assert_eq!(settings("2020-W01"), "Basic=Date Date=YW Year=Basic");
+    assert_eq!(settings("12:00"), "Basic=Time Time=HM Local-or-UTC=L");
+    assert_eq!(settings("+002020-01-01"), "Basic=Date Date=YMD Year=L6");
+    assert_eq!(
+        settings("R3/2020-01-01/P1D"),
+        "Basic=Rec-Interval Recurrence=R1 Interval-type=SD SE-point=Date Date=YMD Year=Basic"
+    );
+
+    assert!("2020-W53-7".parse::<Date>().is_err());
+    assert!("12:00Z".parse::<TimeOfDay>().is_err());
+    assert_eq!(
+        "P1Y2M3DT4H5M6S".parse::<Duration>().unwrap().to_string(),
+        "P1Y2M3DT4H5M6S"
+    );
+    assert!("PT".parse::<Duration>().is_err());
+}
@@ -61,14 +61,17 @@ impl<'a> ariadne::Cache<SourceId> for AsnCompilerCache<'a> {
         &mut self,
         id: &SourceId,
     ) -> Result<&ariadne::Source<Self::Storage>, Box<dyn std::fmt::Debug + '_>> {
+        let Some(text) = self.compiler.source_text(*id) else {
+            return Err(Box::new(format!("Source file {id:?} has been removed")));
+        };
         Ok(self
             .cache
             .entry(*id)
-            .or_insert_with(|| ariadne::Source::from(self.compiler.source_text(*id))))
+            .or_insert_with(|| ariadne::Source::from(text)))
     }
 
     fn display<'b>(&self, id: &'b SourceId) -> Option<Box<dyn std::fmt::Display + 'b>> {
-        Some(Box::new(self.compiler.source_name(*id).to_string()))
+        Some(Box::new(self.compiler.source_name(*id)?.to_string()))
     }
 }
 
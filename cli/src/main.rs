@@ -8,7 +8,11 @@ use std::{
     time::Instant,
 };
 
-use asn1::{AsnCompiler, Diagnostic};
+use asn1::{
+    AsnCompiler, CodegenOptions, CodegenTarget, Diagnostic, DocFormat, FormatOptions, GraphLevel,
+    IntegerType, KeywordCase, ModuleLayout, NameCase, ParameterizedTypes, RandomOptions,
+    RandomValues, Severity, Visibility,
+};
 use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
 use error::{to_error, AsnCompilerCache};
 
@@ -30,6 +34,34 @@ enum Commands {
     /// Run an ASN.1 source compiler
     Asn(AsnCommand),
 
+    /// Reformat ASN.1 source files in place
+    Fmt(FmtCommand),
+
+    /// Generate HTML or Markdown documentation of ASN.1 modules
+    Doc(DocCommand),
+
+    /// Report changes between two versions of a schema that break BER, PER
+    /// or OER compatibility
+    Compat(CompatCommand),
+
+    /// Write the import graph of ASN.1 modules, or the reference graph of
+    /// their assignments, as Graphviz DOT or JSON
+    Graph(GraphCommand),
+
+    /// Generate a JSON Schema of the JSON encodings of ASN.1 types
+    JsonSchema(JsonSchemaCommand),
+
+    /// Generate Protocol Buffers (proto3) files of ASN.1 modules
+    Proto(ProtoCommand),
+
+    /// Write the assignments used by chosen types as a single ASN.1 module,
+    /// with parameterized types instantiated and tags made explicit
+    Flatten(FlattenCommand),
+
+    /// Write random values of ASN.1 types in value notation, for testing
+    /// encoders and decoders
+    Values(ValuesCommand),
+
     /// Run an ada compiler
     Ada(AdaCommand),
 }
@@ -40,10 +72,15 @@ struct AsnCommand {
     #[arg(required = true, value_hint = ValueHint::FilePath)]
     files: Vec<PathBuf>,
 
-    /// Path to the output file. If '-' is passed, uses standard output.
+    /// Path to the output file, or the output directory of targets generating
+    /// several files. If '-' is passed, uses standard output.
     #[arg(short, long, default_value = "-")]
     output: PathBuf,
 
+    /// Language of the generated code
+    #[arg(value_enum, long, default_value = "rust")]
+    target: TargetArg,
+
     /// Display timing information for various phases within the compiler
     #[arg(short, long)]
     timing: bool,
@@ -52,6 +89,295 @@ struct AsnCommand {
     #[arg(long)]
     print_cst: bool,
 
+    /// Display every object identifier defined by the modules
+    #[arg(long)]
+    print_oids: bool,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    /// How instances of parameterized types are generated [default: monomorphised]
+    #[arg(value_enum, long)]
+    parameterized: Option<ParameterizedArg>,
+
+    /// TOML file of options for the rust target.  Options given as arguments
+    /// are used instead of those in the file.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    config: Option<PathBuf>,
+
+    /// Trait derived by every generated rust type, such as `Hash` or `Eq`
+    #[arg(long = "derive", value_name = "TRAIT")]
+    derives: Vec<String>,
+
+    /// Derive serde traits for generated rust types, with fields and variants
+    /// named by their ASN.1 identifiers
+    #[arg(long)]
+    serde: bool,
+
+    /// Rust type of INTEGER values whose constraints do not fit them in i64
+    /// or u64 [default: i64]
+    #[arg(value_enum, long)]
+    integer_type: Option<IntegerArg>,
+
+    /// Represent rust time values by types that parse and write their value
+    /// notation, rather than by `String`
+    #[arg(long)]
+    time_types: bool,
+
+    /// Visibility of generated rust modules, types and fields [default: pub]
+    #[arg(value_enum, long)]
+    visibility: Option<VisibilityArg>,
+
+    /// Write rust modules to a single file or to a file each, written to the
+    /// output directory with a `mod.rs` declaring them [default: single-file]
+    #[arg(value_enum, long)]
+    layout: Option<LayoutArg>,
+
+    /// Case of the names of rust modules [default: snake]
+    #[arg(value_enum, long)]
+    module_case: Option<CaseArg>,
+
+    /// Case of the names of rust types [default: pascal]
+    #[arg(value_enum, long)]
+    type_case: Option<CaseArg>,
+
+    /// Case of the names of struct fields [default: snake]
+    #[arg(value_enum, long)]
+    field_case: Option<CaseArg>,
+
+    /// Case of the names of enum variants [default: pascal]
+    #[arg(value_enum, long)]
+    variant_case: Option<CaseArg>,
+
+    /// Case of the names of constants [default: screaming-snake]
+    #[arg(value_enum, long)]
+    constant_case: Option<CaseArg>,
+
+    /// Name used for a module, assignment or member instead of its converted
+    /// name, such as `Module.Type=Name` or `Module.Type.component=name`
+    #[arg(long = "rename", value_name = "ASN1=RUST")]
+    renames: Vec<String>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct FmtCommand {
+    /// All source files to be formatted
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Do not modify any files, instead list the files that are not formatted
+    /// and exit with an error if there are any.
+    #[arg(long)]
+    check: bool,
+
+    /// Number of spaces used for each indentation level
+    #[arg(long, default_value_t = 4)]
+    indent: usize,
+
+    /// Place each imported symbol on its own line
+    #[arg(long)]
+    one_import_per_line: bool,
+
+    /// Do not align the types of components and fields
+    #[arg(long)]
+    no_align: bool,
+
+    /// How the case of keywords should be changed
+    #[arg(value_enum, long, default_value = "preserve")]
+    keyword_case: KeywordCaseArg,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct DocCommand {
+    /// All initial source files to be documented
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Directory the pages are written to
+    #[arg(short, long, default_value = "doc", value_hint = ValueHint::DirPath)]
+    output: PathBuf,
+
+    /// Format of the pages
+    #[arg(value_enum, long, default_value = "html")]
+    format: DocFormatArg,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct CompatCommand {
+    /// Source files of the old version of the schema
+    #[arg(long, required = true, num_args = 1.., value_hint = ValueHint::FilePath)]
+    old: Vec<PathBuf>,
+
+    /// Source files of the new version of the schema
+    #[arg(long, required = true, num_args = 1.., value_hint = ValueHint::FilePath)]
+    new: Vec<PathBuf>,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct GraphCommand {
+    /// All initial source files to be included in the graph
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Path to the output file. If '-' is passed, uses standard output.
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+
+    /// Format of the graph
+    #[arg(value_enum, long, default_value = "dot")]
+    format: GraphFormatArg,
+
+    /// Make a node of each assignment instead of each module
+    #[arg(long)]
+    types: bool,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct JsonSchemaCommand {
+    /// All initial source files to be included in the schema
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Path to the output file. If '-' is passed, uses standard output.
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct ProtoCommand {
+    /// All initial source files to be generated
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Directory the files are written to
+    #[arg(short, long, default_value = "proto", value_hint = ValueHint::DirPath)]
+    output: PathBuf,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct FlattenCommand {
+    /// All initial source files to be flattened
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Path to the output file. If '-' is passed, uses standard output.
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+
+    /// Name of the flattened module
+    #[arg(long, default_value = "Flattened")]
+    module_name: String,
+
+    /// Assignments to keep with the assignments they use, either
+    /// 'Module.name' or 'name'. Every assignment is kept if none are given.
+    #[arg(short, long)]
+    root: Vec<String>,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct ValuesCommand {
+    /// All initial source files defining the types
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Path to the output file. If '-' is passed, uses standard output.
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+
+    /// Types to write values of, either 'Module.Type' or 'Type'. Values of
+    /// every type are written if none are given.
+    #[arg(short = 't', long = "type")]
+    types: Vec<String>,
+
+    /// Number of values of each type
+    #[arg(short = 'n', long, default_value_t = 1)]
+    count: usize,
+
+    /// Seed of the random values. The same seed gives the same values.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Largest number of elements of SEQUENCE OF and SET OF values, and of
+    /// characters, bits and octets of strings
+    #[arg(long, default_value_t = 8)]
+    max_size: usize,
+
+    /// Depth of nested values beyond which values are made as small as
+    /// possible
+    #[arg(long, default_value_t = 6)]
+    max_depth: usize,
+
+    /// Size of the signed integers that INTEGER values are within
+    #[arg(long, default_value_t = 64)]
+    integer_bits: u32,
+
+    /// Only write values of the extension roots of types
+    #[arg(long)]
+    no_extensions: bool,
+
+    /// Also write each way of violating one constraint of each value
+    #[arg(long)]
+    violations: bool,
+
+    /// Directories to search for imported modules that are not in the given files
+    #[arg(short = 'I', long = "include", value_hint = ValueHint::DirPath)]
+    include: Vec<PathBuf>,
+
+    #[command(flatten)]
+    features: FeatureArgs,
+}
+
+#[derive(Args)]
+struct FeatureArgs {
     /// Disable all default features.
     #[arg(short, long)]
     strict: bool,
@@ -73,6 +399,150 @@ enum Feature {
     UnicodeWhitespace,
 }
 
+#[derive(ValueEnum, Clone, Copy)]
+enum KeywordCaseArg {
+    /// Keep keywords as they are written
+    Preserve,
+
+    /// Use uppercase keywords
+    Upper,
+
+    /// Use lowercase keywords
+    Lower,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ParameterizedArg {
+    /// Generate generic types, with the actual parameters as type arguments
+    Generic,
+
+    /// Generate a separate type for each instance
+    Monomorphised,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum IntegerArg {
+    /// `i64`
+    I64,
+
+    /// `num_bigint::BigInt`
+    Bigint,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum VisibilityArg {
+    /// `pub`
+    Pub,
+
+    /// `pub(crate)`
+    Crate,
+
+    /// `pub(super)`
+    Super,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum LayoutArg {
+    /// A single file containing every module
+    SingleFile,
+
+    /// A file for each module
+    FilePerModule,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum CaseArg {
+    /// `snake_case`
+    Snake,
+
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+
+    /// `PascalCase`
+    Pascal,
+
+    /// `camelCase`
+    Camel,
+}
+
+impl From<ParameterizedArg> for ParameterizedTypes {
+    fn from(value: ParameterizedArg) -> Self {
+        match value {
+            ParameterizedArg::Generic => ParameterizedTypes::Generic,
+            ParameterizedArg::Monomorphised => ParameterizedTypes::Monomorphised,
+        }
+    }
+}
+
+impl From<IntegerArg> for IntegerType {
+    fn from(value: IntegerArg) -> Self {
+        match value {
+            IntegerArg::I64 => IntegerType::I64,
+            IntegerArg::Bigint => IntegerType::BigInt,
+        }
+    }
+}
+
+impl From<VisibilityArg> for Visibility {
+    fn from(value: VisibilityArg) -> Self {
+        match value {
+            VisibilityArg::Pub => Visibility::Public,
+            VisibilityArg::Crate => Visibility::Crate,
+            VisibilityArg::Super => Visibility::Super,
+        }
+    }
+}
+
+impl From<LayoutArg> for ModuleLayout {
+    fn from(value: LayoutArg) -> Self {
+        match value {
+            LayoutArg::SingleFile => ModuleLayout::SingleFile,
+            LayoutArg::FilePerModule => ModuleLayout::FilePerModule,
+        }
+    }
+}
+
+impl From<CaseArg> for NameCase {
+    fn from(value: CaseArg) -> Self {
+        match value {
+            CaseArg::Snake => NameCase::Snake,
+            CaseArg::ScreamingSnake => NameCase::ScreamingSnake,
+            CaseArg::Pascal => NameCase::Pascal,
+            CaseArg::Camel => NameCase::Camel,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum TargetArg {
+    /// A rust source file
+    Rust,
+
+    /// C header and source files, with BER and UPER encoders and decoders
+    C,
+
+    /// Ada package specs and bodies, with BER encoders and decoders
+    Ada,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum DocFormatArg {
+    /// Standalone HTML pages
+    Html,
+
+    /// Markdown pages
+    Markdown,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum GraphFormatArg {
+    /// Graphviz DOT
+    Dot,
+
+    /// JSON
+    Json,
+}
+
 #[derive(Args)]
 struct AdaCommand {
     /// All initial source files to be parsed
@@ -85,6 +555,14 @@ fn main() -> ExitCode {
 
     match &cli.command {
         Commands::Asn(cli) => asn_command(cli),
+        Commands::Fmt(cli) => fmt_command(cli),
+        Commands::Doc(cli) => doc_command(cli),
+        Commands::Compat(cli) => compat_command(cli),
+        Commands::Graph(cli) => graph_command(cli),
+        Commands::JsonSchema(cli) => json_schema_command(cli),
+        Commands::Proto(cli) => proto_command(cli),
+        Commands::Flatten(cli) => flatten_command(cli),
+        Commands::Values(cli) => values_command(cli),
         Commands::Ada(cli) => ada_command(cli),
     }
 }
@@ -111,9 +589,7 @@ fn asn_command(cli: &AsnCommand) -> ExitCode {
     }
 }
 
-fn run_asn(compiler: &mut AsnCompiler, cli: &AsnCommand) -> Result<Vec<Diagnostic>, ExitCode> {
-    let mut errors = vec![];
-
+fn set_features(compiler: &mut AsnCompiler, cli: &FeatureArgs) {
     let features = if cli.strict {
         &[][..]
     } else {
@@ -130,6 +606,22 @@ fn run_asn(compiler: &mut AsnCompiler, cli: &AsnCommand) -> Result<Vec<Diagnosti
             Feature::UnicodeWhitespace => compiler.unicode_whitespace = true,
         }
     }
+}
+
+fn run_asn(compiler: &mut AsnCompiler, cli: &AsnCommand) -> Result<Vec<Diagnostic>, ExitCode> {
+    let mut errors = vec![];
+    let options = match codegen_options(cli) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            return Err(ExitCode::FAILURE);
+        }
+    };
+
+    set_features(compiler, &cli.features);
+    for dir in &cli.include {
+        compiler.add_include_dir(dir);
+    }
 
     let mut timings = vec![];
 
@@ -148,8 +640,8 @@ fn run_asn(compiler: &mut AsnCompiler, cli: &AsnCommand) -> Result<Vec<Diagnosti
 
         match res {
             Ok(t) => {
-                if cli.print_cst {
-                    print!("{}", compiler.print_cst(t))
+                if let Some(cst) = compiler.print_cst(t).filter(|_| cli.print_cst) {
+                    print!("{cst}")
                 }
             }
             Err(e) => errors.push(e),
@@ -165,21 +657,66 @@ fn run_asn(compiler: &mut AsnCompiler, cli: &AsnCommand) -> Result<Vec<Diagnosti
     let end = start.elapsed();
     timings.push(format!("Analysis: {end:?}"));
 
+    if cli.print_oids {
+        for (oid, entries) in an.oid_registry().iter() {
+            let names: Vec<_> = entries
+                .iter()
+                .map(|e| match &e.name {
+                    Some(name) => format!("{}.{name}", e.module),
+                    None => e.module.clone(),
+                })
+                .collect();
+            println!("{oid}\t{}", names.join(", "));
+        }
+    }
+
     if an.diagnostics.is_empty() {
         let start = Instant::now();
-        let code = an.rust_codegen();
+        let target = match cli.target {
+            TargetArg::Rust => CodegenTarget::Rust,
+            TargetArg::C => CodegenTarget::C,
+            TargetArg::Ada => CodegenTarget::Ada,
+        };
+        let code = an.codegen(target, &options);
         let end = start.elapsed();
         timings.push(format!("Codegen: {end:?}"));
 
         match code {
-            Ok(s) => {
+            // a single file is written to the output path
+            Ok(files)
+                if target == CodegenTarget::Rust && options.layout == ModuleLayout::SingleFile =>
+            {
+                let s = &files[0].content;
                 if cli.output == Path::new("-") {
                     println!("{s}");
                 } else if std::fs::write(&cli.output, s).is_err() {
                     eprintln!("Error writing output file");
                 }
             }
-            Err(e) => eprintln!("{e:?}"),
+            Ok(files) => {
+                if cli.output == Path::new("-") {
+                    let comment = match target {
+                        CodegenTarget::Ada => "--",
+                        _ => "//",
+                    };
+                    for file in files {
+                        println!("{comment} {}\n{}", file.file, file.content);
+                    }
+                } else if std::fs::create_dir_all(&cli.output).is_err() {
+                    eprintln!("Unable to create output directory `{:?}`", cli.output);
+                } else {
+                    for file in files {
+                        let path = cli.output.join(&file.file);
+                        if std::fs::write(&path, file.content).is_err() {
+                            eprintln!("Error writing output file `{path:?}`");
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Unable to generate code: {e}");
+                return Err(ExitCode::FAILURE);
+            }
         }
     } else {
         return Ok(an.diagnostics);
@@ -194,13 +731,500 @@ fn run_asn(compiler: &mut AsnCompiler, cli: &AsnCommand) -> Result<Vec<Diagnosti
     Ok(vec![])
 }
 
+/// Get the options of the rust target from the config file, replaced by
+/// those given as arguments
+fn codegen_options(cli: &AsnCommand) -> Result<CodegenOptions, String> {
+    let mut options = CodegenOptions::default();
+    if let Some(path) = &cli.config {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read config file `{}`: {e}", path.display()))?;
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e| format!("Invalid config file `{}`: {e}", path.display()))?;
+        read_config(&mut options, &table)?;
+    }
+
+    if let Some(parameterized) = cli.parameterized {
+        options.parameterized = parameterized.into();
+    }
+    options.derives.extend(cli.derives.iter().cloned());
+    options.serde |= cli.serde;
+    if let Some(integers) = cli.integer_type {
+        options.integers = integers.into();
+    }
+    options.time_types |= cli.time_types;
+    if let Some(visibility) = cli.visibility {
+        options.visibility = visibility.into();
+    }
+    if let Some(layout) = cli.layout {
+        options.layout = layout.into();
+    }
+    let naming = &mut options.naming;
+    for (case, name_case) in [
+        (cli.module_case, &mut naming.modules),
+        (cli.type_case, &mut naming.types),
+        (cli.field_case, &mut naming.fields),
+        (cli.variant_case, &mut naming.variants),
+        (cli.constant_case, &mut naming.constants),
+    ] {
+        if let Some(case) = case {
+            *name_case = case.into();
+        }
+    }
+    for rename in &cli.renames {
+        let Some((name, rust)) = rename.split_once('=') else {
+            return Err(format!("Invalid rename `{rename}`, expected `ASN1=RUST`"));
+        };
+        options.renames.insert(name.into(), rust.into());
+    }
+    Ok(options)
+}
+
+/// Read the options of a config file, which are named as the arguments are,
+/// with the cases of names in a `naming` table and renames in a `renames`
+/// table:
+///
+/// ```toml
+/// derives = ["Hash"]
+/// serde = true
+/// integers = "bigint"
+/// visibility = "crate"
+///
+/// [naming]
+/// types = "pascal"
+///
+/// [renames]
+/// "Module.Type" = "Name"
+/// ```
+fn read_config(options: &mut CodegenOptions, table: &toml::Table) -> Result<(), String> {
+    for (key, value) in table {
+        match key.as_str() {
+            "parameterized" => {
+                options.parameterized = config_enum::<ParameterizedArg>(key, value)?.into()
+            }
+            "derives" => {
+                let derives = value.as_array().and_then(|derives| {
+                    derives
+                        .iter()
+                        .map(|derive| derive.as_str().map(str::to_string))
+                        .collect::<Option<Vec<_>>>()
+                });
+                options.derives =
+                    derives.ok_or("Config option `derives` must be an array of strings")?;
+            }
+            "serde" => {
+                options.serde = value
+                    .as_bool()
+                    .ok_or("Config option `serde` must be a boolean")?;
+            }
+            "integers" => options.integers = config_enum::<IntegerArg>(key, value)?.into(),
+            "time_types" => {
+                options.time_types = value
+                    .as_bool()
+                    .ok_or("Config option `time_types` must be a boolean")?;
+            }
+            "visibility" => options.visibility = config_enum::<VisibilityArg>(key, value)?.into(),
+            "layout" => options.layout = config_enum::<LayoutArg>(key, value)?.into(),
+            "naming" => {
+                let naming = value
+                    .as_table()
+                    .ok_or("Config option `naming` must be a table")?;
+                for (key, value) in naming {
+                    let case = config_enum::<CaseArg>(key, value)?.into();
+                    match key.as_str() {
+                        "modules" => options.naming.modules = case,
+                        "types" => options.naming.types = case,
+                        "fields" => options.naming.fields = case,
+                        "variants" => options.naming.variants = case,
+                        "constants" => options.naming.constants = case,
+                        _ => return Err(format!("Unknown config option `naming.{key}`")),
+                    }
+                }
+            }
+            "renames" => {
+                let renames = value
+                    .as_table()
+                    .ok_or("Config option `renames` must be a table")?;
+                for (name, rust) in renames {
+                    let rust = rust
+                        .as_str()
+                        .ok_or_else(|| format!("Rename of `{name}` must be a string"))?;
+                    options.renames.insert(name.clone(), rust.to_string());
+                }
+            }
+            _ => return Err(format!("Unknown config option `{key}`")),
+        }
+    }
+    Ok(())
+}
+
+/// Read a config option that is one of the values of an argument
+fn config_enum<T: ValueEnum>(key: &str, value: &toml::Value) -> Result<T, String> {
+    let value = value
+        .as_str()
+        .and_then(|value| T::from_str(value, false).ok());
+    value.ok_or_else(|| {
+        let values: Vec<_> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| format!("`{}`", v.get_name()))
+            .collect();
+        format!("Config option `{key}` must be one of {}", values.join(", "))
+    })
+}
+
+fn fmt_command(cli: &FmtCommand) -> ExitCode {
+    let mut compiler = AsnCompiler::new();
+    set_features(&mut compiler, &cli.features);
+
+    let options = FormatOptions {
+        indent: cli.indent,
+        align_components: !cli.no_align,
+        one_import_per_line: cli.one_import_per_line,
+        keyword_case: match cli.keyword_case {
+            KeywordCaseArg::Preserve => KeywordCase::Preserve,
+            KeywordCaseArg::Upper => KeywordCase::Upper,
+            KeywordCaseArg::Lower => KeywordCase::Lower,
+        },
+    };
+
+    let mut success = true;
+    for path in &cli.files {
+        let Ok(source) = std::fs::read_to_string(path) else {
+            eprintln!("Unable to open source file `{path:?}`");
+            return ExitCode::FAILURE;
+        };
+        let display_name = path.to_string_lossy().to_string();
+
+        let file = match compiler.add_file(display_name.clone(), source.clone()) {
+            Ok(file) => file,
+            Err(diag) => {
+                let err =
+                    to_error(&diag).and_then(|r| Ok(r.eprint(AsnCompilerCache::new(&compiler))?));
+                if let Err(err) = err {
+                    eprintln!("Error while printing error messages: {err:?}");
+                }
+                success = false;
+                continue;
+            }
+        };
+
+        let formatted = compiler.format(file, &options);
+        if formatted == source {
+            continue;
+        }
+
+        if cli.check {
+            println!("{display_name}");
+            success = false;
+        } else if std::fs::write(path, formatted).is_err() {
+            eprintln!("Error writing formatted file `{display_name}`");
+            success = false;
+        }
+    }
+
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn doc_command(cli: &DocCommand) -> ExitCode {
+    let mut compiler = match load(&cli.features, &cli.files, &cli.include) {
+        Ok(compiler) => compiler,
+        Err(code) => return code,
+    };
+    if let Err(code) = analyse(&mut compiler) {
+        return code;
+    }
+
+    let pages = compiler.analysis().documentation(match cli.format {
+        DocFormatArg::Html => DocFormat::Html,
+        DocFormatArg::Markdown => DocFormat::Markdown,
+    });
+    if std::fs::create_dir_all(&cli.output).is_err() {
+        eprintln!("Unable to create output directory `{:?}`", cli.output);
+        return ExitCode::FAILURE;
+    }
+    for page in pages {
+        let path = cli.output.join(&page.file);
+        if std::fs::write(&path, page.content).is_err() {
+            eprintln!("Error writing documentation page `{path:?}`");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn compat_command(cli: &CompatCommand) -> ExitCode {
+    // the errors of both revisions are reported before failing
+    let old = load(&cli.features, &cli.old, &cli.include).and_then(|mut old| {
+        analyse(&mut old)?;
+        Ok(old)
+    });
+    let new = load(&cli.features, &cli.new, &cli.include).and_then(|mut new| {
+        analyse(&mut new)?;
+        Ok(new)
+    });
+    let (mut old, mut new) = match (old, new) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+
+    let changes = old.analysis().compatibility(&new.analysis());
+    let diagnostics: Vec<_> = changes.iter().map(|c| c.diagnostic.clone()).collect();
+    print_diagnostics(&new, &diagnostics);
+
+    if changes.iter().any(|c| c.severity() == Severity::Breaking) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn graph_command(cli: &GraphCommand) -> ExitCode {
+    let mut compiler = match load(&cli.features, &cli.files, &cli.include) {
+        Ok(compiler) => compiler,
+        Err(code) => return code,
+    };
+
+    // unresolved imports are shown in the graph, so analysis errors are
+    // reported without preventing it being written
+    let analysis = compiler.analysis();
+    let graph = analysis.dependency_graph(if cli.types {
+        GraphLevel::Types
+    } else {
+        GraphLevel::Modules
+    });
+    let diagnostics = analysis.diagnostics;
+    print_diagnostics(&compiler, &diagnostics);
+
+    let output = match cli.format {
+        GraphFormatArg::Dot => graph.to_dot(),
+        GraphFormatArg::Json => graph.to_json(),
+    };
+    if cli.output == Path::new("-") {
+        print!("{output}");
+    } else if std::fs::write(&cli.output, output).is_err() {
+        eprintln!("Error writing output file");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn json_schema_command(cli: &JsonSchemaCommand) -> ExitCode {
+    let mut compiler = match load(&cli.features, &cli.files, &cli.include) {
+        Ok(compiler) => compiler,
+        Err(code) => return code,
+    };
+    if let Err(code) = analyse(&mut compiler) {
+        return code;
+    }
+
+    let schema = compiler.analysis().json_schema();
+    if cli.output == Path::new("-") {
+        print!("{schema}");
+    } else if std::fs::write(&cli.output, schema).is_err() {
+        eprintln!("Error writing output file");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn proto_command(cli: &ProtoCommand) -> ExitCode {
+    let mut compiler = match load(&cli.features, &cli.files, &cli.include) {
+        Ok(compiler) => compiler,
+        Err(code) => return code,
+    };
+    if let Err(code) = analyse(&mut compiler) {
+        return code;
+    }
+
+    let files = compiler.analysis().protobuf();
+    if std::fs::create_dir_all(&cli.output).is_err() {
+        eprintln!("Unable to create output directory `{:?}`", cli.output);
+        return ExitCode::FAILURE;
+    }
+    for file in files {
+        let path = cli.output.join(&file.file);
+        if std::fs::write(&path, file.content).is_err() {
+            eprintln!("Error writing proto file `{path:?}`");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn flatten_command(cli: &FlattenCommand) -> ExitCode {
+    let mut compiler = match load(&cli.features, &cli.files, &cli.include) {
+        Ok(compiler) => compiler,
+        Err(code) => return code,
+    };
+    if let Err(code) = analyse(&mut compiler) {
+        return code;
+    }
+
+    let module = match compiler.analysis().flatten(&cli.module_name, &cli.root) {
+        Ok(module) => module,
+        Err(e) => {
+            print_diagnostics(&compiler, &[e]);
+            return ExitCode::FAILURE;
+        }
+    };
+    if cli.output == Path::new("-") {
+        print!("{module}");
+    } else if std::fs::write(&cli.output, module).is_err() {
+        eprintln!("Error writing output file");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn values_command(cli: &ValuesCommand) -> ExitCode {
+    let mut compiler = match load(&cli.features, &cli.files, &cli.include) {
+        Ok(compiler) => compiler,
+        Err(code) => return code,
+    };
+    if let Err(code) = analyse(&mut compiler) {
+        return code;
+    }
+
+    let analysis = compiler.analysis();
+    let options = RandomOptions {
+        max_size: cli.max_size,
+        integer_bits: cli.integer_bits,
+        max_depth: cli.max_depth,
+        extensions: !cli.no_extensions,
+    };
+    let mut values = RandomValues::new(&analysis, cli.seed, options);
+
+    let all = values.types();
+    let mut types = vec![];
+    for name in &cli.types {
+        let found = all.iter().find(|(module, ty)| match name.split_once('.') {
+            Some((m, t)) => m == module && t == ty,
+            None => name == ty,
+        });
+        match found {
+            Some(found) => types.push(found.clone()),
+            None => {
+                eprintln!("Unknown type `{name}`");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if types.is_empty() {
+        types = all;
+    }
+
+    let mut output = String::new();
+    for (module, ty) in &types {
+        for _ in 0..cli.count {
+            if cli.violations {
+                for violation in values.violations(module, ty) {
+                    let path = match violation.path.as_str() {
+                        "" => String::new(),
+                        path => format!(" at {path}"),
+                    };
+                    output.push_str(&format!(
+                        "-- {module}.{ty}, violated{path}: {}\n{}\n",
+                        violation.message, violation.value
+                    ));
+                }
+            } else if let Some(value) = values.value(module, ty) {
+                output.push_str(&format!("-- {module}.{ty}\n{value}\n"));
+            }
+        }
+    }
+
+    if cli.output == Path::new("-") {
+        print!("{output}");
+    } else if std::fs::write(&cli.output, output).is_err() {
+        eprintln!("Error writing output file");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Create a compiler with the chosen features and include directories, and
+/// parse the source files, printing the errors found in them
+fn load(
+    features: &FeatureArgs,
+    files: &[PathBuf],
+    include: &[PathBuf],
+) -> Result<AsnCompiler, ExitCode> {
+    let mut compiler = AsnCompiler::new();
+    set_features(&mut compiler, features);
+    for dir in include {
+        compiler.add_include_dir(dir);
+    }
+
+    let mut errors = vec![];
+    for path in files {
+        let Ok(source) = std::fs::read_to_string(path) else {
+            eprintln!("Unable to open source file `{path:?}`");
+            return Err(ExitCode::FAILURE);
+        };
+        if let Err(e) = compiler.add_file(path.to_string_lossy().to_string(), source) {
+            errors.push(e);
+        }
+    }
+    if !errors.is_empty() {
+        print_diagnostics(&compiler, &errors);
+        return Err(ExitCode::FAILURE);
+    }
+    Ok(compiler)
+}
+
+/// Analyse the sources of a compiler, printing the errors found
+fn analyse(compiler: &mut AsnCompiler) -> Result<(), ExitCode> {
+    let errors = compiler.analysis().diagnostics;
+    if errors.is_empty() {
+        return Ok(());
+    }
+    print_diagnostics(compiler, &errors);
+    Err(ExitCode::FAILURE)
+}
+
+/// Print diagnostics with the sources of a compiler, or just their names if
+/// they are not located in any source
+fn print_diagnostics(compiler: &AsnCompiler, diagnostics: &[Diagnostic]) {
+    for diag in diagnostics {
+        let err = to_error(diag).and_then(|r| Ok(r.eprint(AsnCompilerCache::new(compiler))?));
+        if err.is_err() {
+            eprintln!("[{}] {}", diag.error_code, diag.name);
+        }
+    }
+}
+
 fn ada_command(cli: &AdaCommand) -> ExitCode {
     let mut compiler = ada::Compiler::new();
 
+    let mut success = true;
     for path in &cli.files {
-        let source = std::fs::read_to_string(path).unwrap();
-        compiler.add_file(source)
+        let Ok(source) = std::fs::read_to_string(path) else {
+            eprintln!("Unable to open source file `{path:?}`");
+            return ExitCode::FAILURE;
+        };
+
+        if let Err(diagnostics) = compiler.add_file(source) {
+            for diagnostic in diagnostics {
+                eprintln!("{}: {diagnostic}", path.to_string_lossy());
+            }
+            success = false;
+        }
     }
 
-    ExitCode::SUCCESS
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }